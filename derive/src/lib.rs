@@ -0,0 +1,191 @@
+//! `device-state-exchange-lib` 的 derive macro
+//!
+//! 請透過 `device-state-exchange-lib` 的 `derive` feature 使用，不需要直接依賴本 crate：
+//!
+//! ```toml
+//! device-state-exchange-lib = { version = "*", features = ["derive"] }
+//! ```
+//!
+//! 實作的 trait 仍要求 [`Debug`] 與 [`Clone`] ，請一併以 `#[derive(Debug, Clone)]` 實作，`downcast` 相關的 method 會自動取得
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::{Data, DeriveInput, Error, Field, Index, Member, parse_macro_input, spanned::Spanned};
+
+/// 實作 `Target`
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::Target;
+///
+/// #[derive(Debug, Clone, Target)]
+/// struct ExampleModbusTarget(serde_json::Value);
+///
+/// let target: Box<dyn Target> = Box::new(ExampleModbusTarget(serde_json::json!({ "address": 40001 })));
+/// assert!(target.is::<ExampleModbusTarget>());
+/// ```
+#[proc_macro_derive(Target)]
+pub fn derive_target(input: TokenStream) -> TokenStream {
+    marker(&parse_macro_input!(input as DeriveInput), &quote!(Target))
+}
+
+/// 實作 `DeviceStateRequest`
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::DeviceStateRequest;
+///
+/// #[derive(Debug, Clone, DeviceStateRequest)]
+/// struct ExampleModbusRequest {
+///     id: u8,
+///     function_code: u8,
+///     address: u16,
+///     length: u16,
+/// }
+/// ```
+#[proc_macro_derive(DeviceStateRequest)]
+pub fn derive_device_state_request(input: TokenStream) -> TokenStream {
+    marker(
+        &parse_macro_input!(input as DeriveInput),
+        &quote!(DeviceStateRequest),
+    )
+}
+
+/// 實作 `DeviceStateResponse`
+///
+/// 以欄位屬性指定回覆的內容：
+///
+/// - `#[device_state(value)]`：`to_value()` 回傳的欄位，型別需實作 `serde::Serialize` ，轉換方式參見 `value::serialize()` ；
+///   struct 只有一個欄位時可以省略
+/// - `#[device_state(raw)]`：`raw()` 回傳的原始封包欄位（非必需），型別需為 `bytes::Bytes`
+///
+/// # 範例
+/// ```rust
+/// use bytes::Bytes;
+/// use device_state_exchange_lib::DeviceStateResponse;
+///
+/// #[derive(Debug, Clone, DeviceStateResponse)]
+/// struct ExampleModbusResponse {
+///     #[device_state(raw)]
+///     raw_words: Bytes,
+///     #[device_state(value)]
+///     temperature: f64,
+/// }
+///
+/// #[derive(Debug, Clone, DeviceStateResponse)]
+/// struct Switch(bool);
+///
+/// let response = ExampleModbusResponse { raw_words: Bytes::from_static(&[0x00, 0xd7]), temperature: 21.5 };
+/// assert_eq!(response.to_value_lossy(), 21.5);
+/// assert_eq!(response.raw().unwrap().len(), 2);
+/// assert_eq!(Switch(true).to_value_lossy(), true);
+///
+/// // NaN 無法以 JSON 表示
+/// assert!(ExampleModbusResponse { temperature: f64::NAN, ..response }.to_value().is_err());
+/// ```
+#[proc_macro_derive(DeviceStateResponse, attributes(device_state))]
+pub fn derive_device_state_response(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    response(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn marker(input: &DeriveInput, name: &TokenStream2) -> TokenStream {
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics ::device_state_exchange_lib::#name for #ident #type_generics #where_clause {}
+    }
+    .into()
+}
+
+fn response(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new(
+            input.span(),
+            "DeviceStateResponse 只支援 struct ，enum 請自行實作 to_value()",
+        ));
+    };
+
+    let mut value = None;
+    let mut raw = None;
+    for (index, field) in data.fields.iter().enumerate() {
+        let member = member(index, field);
+
+        for attribute in field
+            .attrs
+            .iter()
+            .filter(|attribute| attribute.path().is_ident("device_state"))
+        {
+            attribute.parse_nested_meta(|meta| {
+                let slot = if meta.path.is_ident("value") {
+                    &mut value
+                } else if meta.path.is_ident("raw") {
+                    &mut raw
+                } else {
+                    return Err(meta.error("不支援的屬性，請使用 value 或 raw"));
+                };
+                if slot.is_some() {
+                    return Err(meta.error("同一個屬性只能標記一個欄位"));
+                }
+                *slot = Some((member.clone(), field.span()));
+                Ok(())
+            })?;
+        }
+    }
+
+    // 只有一個欄位時可以省略 #[device_state(value)]
+    let value = match value {
+        Some(value) => value,
+        None if data.fields.len() == 1 => data
+            .fields
+            .iter()
+            .map(|field| (member(0, field), field.span()))
+            .next()
+            .ok_or_else(|| Error::new(input.span(), "struct 沒有欄位"))?,
+        None => {
+            return Err(Error::new(
+                input.span(),
+                "請以 #[device_state(value)] 標記 to_value() 回傳的欄位",
+            ));
+        }
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    let (value, value_span) = value;
+    let to_value = quote_spanned! {value_span=>
+        fn to_value(
+            &self,
+        ) -> ::std::result::Result<
+            ::std::borrow::Cow<'_, ::device_state_exchange_lib::__private::serde_json::Value>,
+            ::device_state_exchange_lib::value::ConversionError,
+        > {
+            ::device_state_exchange_lib::value::serialize(&self.#value)
+        }
+    };
+    let raw = raw.map(|(raw, raw_span)| {
+        quote_spanned! {raw_span=>
+            fn raw(&self) -> ::std::option::Option<::device_state_exchange_lib::__private::bytes::Bytes> {
+                ::std::option::Option::Some(::std::clone::Clone::clone(&self.#raw))
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::device_state_exchange_lib::DeviceStateResponse for #ident #type_generics #where_clause {
+            #to_value
+            #raw
+        }
+    })
+}
+
+fn member(index: usize, field: &Field) -> Member {
+    field
+        .ident
+        .clone()
+        .map_or_else(|| Member::Unnamed(Index::from(index)), Member::Named)
+}
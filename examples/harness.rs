@@ -0,0 +1,336 @@
+//! 端對端範例主程式
+//!
+//! 只使用本 crate 的公開 API ，將設定檔、連線、排程、點位狀態與事件接收端串接成可執行的主程式：
+//!
+//! 1. 讀取設定檔（預設為 `examples/harness.json`），以 [`TargetDefinition`] 描述點位
+//! 2. 以 [`Connection::init()`] 與 [`Connection::init_targets()`] 初始化模擬設備，統計數據登記至 [`ConnectionStatsRegistry`]
+//! 3. 依各連線的 [`ConnectionArtifact::update_interval`] 排程輪詢自動更新點位，結果寫入 [`StateStore`]
+//! 4. 以 [`EnvelopeSource::wrap()`] 包裝回覆後交給 [`Sink`] ，本範例以 JSON Lines 輸出至 stdout ，
+//!    連接 MQTT 等訊息佇列時實作 [`Sink`] 即可
+//! 5. 以 [`LifecycleEvents`] 回報初始化與第一輪輪詢完成，結束時輸出點位狀態與統計數據
+//!
+//! 執行方式：
+//!
+//! ```sh
+//! cargo run --example harness --features examples-harness -- [設定檔路徑]
+//! ```
+
+use std::{
+    borrow::Cow,
+    error::Error,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use device_state_exchange_lib::{
+    Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+    ConnectionStatsRegistry, ConnectionTargets, DeviceStateRequest, DeviceStateResponse,
+    InitedTarget, StateStore, Target, TargetAddressNumber, TargetDefinition,
+    envelope::{EnvelopeSource, ResponseEnvelope},
+    lifecycle::{LifecycleEvents, LifecyclePhase},
+    value::{self, ConversionError, Quality},
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// 設定檔
+#[derive(Debug, Clone, Deserialize)]
+struct HarnessConfig {
+    /// 每個連線輪詢的輪數
+    #[serde(default = "default_cycles")]
+    cycles: u32,
+    connections: Vec<ConnectionEntry>,
+}
+
+const fn default_cycles() -> u32 {
+    3
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ConnectionEntry {
+    name: String,
+    #[serde(flatten)]
+    config: SimulatedConfig,
+    targets: Vec<TargetDefinition>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct SimulatedConfig {
+    port: String,
+    update_interval_ms: u64,
+}
+
+impl ConnectionConfig for SimulatedConfig {}
+
+#[derive(Debug, Clone)]
+struct SimulatedTarget(TargetDefinition);
+
+impl Target for SimulatedTarget {}
+
+#[derive(Debug, Clone, Copy)]
+enum Waveform {
+    Sine,
+    Ramp,
+    Constant,
+}
+
+#[derive(Debug, Clone)]
+struct Read {
+    device: TargetAddressNumber,
+    waveform: Waveform,
+    base: f64,
+    amplitude: f64,
+}
+
+impl DeviceStateRequest for Read {}
+
+#[derive(Debug, Clone)]
+struct Reading(f64);
+
+impl DeviceStateResponse for Reading {
+    fn to_value(&self) -> Result<Cow<'_, Value>, ConversionError> {
+        value::finite(self.0).map(Cow::Owned)
+    }
+}
+
+/// 模擬設備，依點位的波形產生數值
+struct Simulator {
+    tick: u32,
+}
+
+impl Connection for Simulator {
+    const NAMES: &[&str] = &["Simulated"];
+    type Config = SimulatedConfig;
+    type Target = SimulatedTarget;
+    type Request = Read;
+    type Response = Reading;
+    type Result = ();
+
+    async fn init(config: &SimulatedConfig) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+        Ok(ConnectionArtifact::new(
+            Self { tick: 0 },
+            ConnectionStats::new(config.port.clone(), None),
+        )
+        .update_every(Duration::from_millis(config.update_interval_ms)))
+    }
+
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<SimulatedTarget>,
+    ) -> ConnectionTargets<Read, ()> {
+        ConnectionTargets(
+            targets
+                .into_iter()
+                .filter_map(|SimulatedTarget(definition)| {
+                    let waveform = match definition.address.as_str() {
+                        "sine" => Waveform::Sine,
+                        "ramp" => Waveform::Ramp,
+                        "constant" => Waveform::Constant,
+                        address => {
+                            eprintln!("略過點位「{}」：不支援的波形 {address}", definition.name);
+                            return None;
+                        }
+                    };
+                    let parameter = |key: &str| {
+                        definition
+                            .extra
+                            .get(key)
+                            .and_then(Value::as_f64)
+                            .unwrap_or_default()
+                    };
+
+                    Some(InitedTarget {
+                        name: definition.name.clone(),
+                        request: Read {
+                            device: definition.device.clone(),
+                            waveform,
+                            base: parameter("base"),
+                            amplitude: parameter("amplitude"),
+                        },
+                        result: (),
+                        default_status: definition.default_status.clone(),
+                        auto_refresh: definition.auto_refresh,
+                        refresh_interval: None,
+                        keep_raw_frames: None,
+                        group: None,
+                        safe_state: None,
+                        array: None,
+                        change: None,
+                        statistics: Some(
+                            connection_statistics.insert_target(definition.device.clone()),
+                        ),
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+        Ok(())
+    }
+
+    async fn update_config(&mut self, _: &SimulatedConfig) -> Result<(), ConnectionError> {
+        Ok(())
+    }
+
+    async fn request_process(&mut self, request: Read) -> Result<(Reading, bool), ConnectionError> {
+        self.tick = self.tick.wrapping_add(1);
+        let phase = f64::from(self.tick % 20) / 20.0;
+        let offset = match request.waveform {
+            Waveform::Sine => (phase * std::f64::consts::TAU).sin(),
+            Waveform::Ramp => phase,
+            Waveform::Constant => 0.0,
+        };
+
+        Ok((
+            Reading(request.amplitude.mul_add(offset, request.base)),
+            true,
+        ))
+    }
+}
+
+/// 事件接收端
+trait Sink: Send {
+    /// 發佈回覆信封
+    fn publish(&mut self, envelope: &ResponseEnvelope) -> Result<(), Box<dyn Error>>;
+}
+
+/// 以 JSON Lines 輸出至 stdout
+struct StdoutSink;
+
+impl Sink for StdoutSink {
+    fn publish(&mut self, envelope: &ResponseEnvelope) -> Result<(), Box<dyn Error>> {
+        println!("{}", serde_json::to_string(envelope)?);
+        Ok(())
+    }
+}
+
+/// 一個連線的輪詢狀態
+struct Poller {
+    name: String,
+    connection: Simulator,
+    targets: ConnectionTargets<Read, ()>,
+    interval: Duration,
+    next_due: Instant,
+    completed_cycles: u32,
+    source: EnvelopeSource,
+}
+
+impl Poller {
+    /// 輪詢所有自動更新點位一次
+    async fn poll(
+        &mut self,
+        store: &StateStore,
+        sink: &mut dyn Sink,
+    ) -> Result<(), Box<dyn Error>> {
+        for target in self.targets.0.iter().filter(|target| target.auto_refresh) {
+            let started = Instant::now();
+            let request = self.connection.preprocess(target.request.clone(), None)?;
+            let response = match self.connection.request_process(request.clone()).await {
+                Ok((response, _)) => self.connection.postprocess(request.clone(), response),
+                Err(error) => Err(error),
+            };
+            let response_ms = i64::try_from(started.elapsed().as_millis()).unwrap_or(i64::MAX);
+
+            match response {
+                Ok(response) => {
+                    if let Some(statistics) = &target.statistics {
+                        statistics.record_success(response_ms);
+                    }
+                    let _ = store.update_response(&target.name, &response);
+                    sink.publish(&self.source.wrap(&target.name, &request.device, &response))?;
+                }
+                Err(error) => {
+                    if let Some(statistics) = &target.statistics {
+                        statistics.record_failure();
+                    }
+                    let _ = store.set_quality(&target.name, Quality::Bad);
+                    eprintln!("點位「{}」讀取失敗：{error}", target.name);
+                }
+            }
+        }
+
+        self.completed_cycles += 1;
+        self.next_due += self.interval;
+        Ok(())
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let path = std::env::args_os().nth(1).map_or_else(
+        || {
+            PathBuf::from(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/examples/harness.json"
+            ))
+        },
+        PathBuf::from,
+    );
+    let config: HarnessConfig = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+
+    let registry = ConnectionStatsRegistry::new();
+    let store = StateStore::new();
+    let lifecycle = LifecycleEvents::new(config.connections.iter().map(|entry| &entry.name));
+    let mut sink = StdoutSink;
+    let mut pollers = Vec::new();
+
+    for entry in config.connections {
+        let artifact = match Simulator::init(&entry.config).await {
+            Ok(artifact) => artifact,
+            Err(error) => {
+                lifecycle.connection_failed(&entry.name, error.to_string());
+                continue;
+            }
+        };
+        let ConnectionArtifact {
+            artifact: mut connection,
+            mut statistics,
+            update_interval,
+            ..
+        } = artifact;
+
+        let targets = connection.init_targets(
+            &mut statistics,
+            entry.targets.into_iter().map(SimulatedTarget).collect(),
+        );
+        for target in &targets.0 {
+            store.register(&target.name, target.default_status.clone());
+        }
+        registry.insert(&entry.name, statistics);
+        lifecycle.connection_initialized(&entry.name);
+
+        pollers.push(Poller {
+            source: EnvelopeSource::new(&entry.name),
+            name: entry.name,
+            connection,
+            targets,
+            interval: update_interval,
+            next_due: Instant::now(),
+            completed_cycles: 0,
+        });
+    }
+
+    // 每次輪詢最早到期的連線，直到所有連線都完成指定的輪數
+    while let Some(poller) = pollers
+        .iter_mut()
+        .filter(|poller| poller.completed_cycles < config.cycles)
+        .min_by_key(|poller| poller.next_due)
+    {
+        tokio::time::sleep_until(poller.next_due.into()).await;
+        poller.poll(&store, &mut sink).await?;
+        lifecycle.poll_cycle_completed(&poller.name);
+    }
+
+    if lifecycle.phase() == LifecyclePhase::Ready {
+        eprintln!("所有連線都已完成第一輪輪詢");
+    }
+    eprintln!("{}", serde_json::to_string_pretty(&store.snapshot())?);
+    eprintln!(
+        "{}",
+        serde_json::to_string_pretty(&registry.snapshot_all())?
+    );
+
+    Ok(())
+}
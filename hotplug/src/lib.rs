@@ -0,0 +1,25 @@
+//! `device-state-exchange-lib` 的裝置插拔通知
+//!
+//! 請透過 `device-state-exchange-lib` 的 `serial` feature 使用（參見 `serial::port::HotplugMonitor`），不需要直接依賴本 crate
+//!
+//! 接收作業系統的裝置插拔通知需要 `unsafe` ，因此獨立為本 crate ，`device-state-exchange-lib` 維持禁止 `unsafe` ：
+//!
+//! - Linux：以 `NETLINK_KOBJECT_UEVENT` socket 接收 kernel 發出的 uevent（udev 的事件來源），只回報 `tty` 子系統的插入與移除
+//! - Windows：在背景執行緒建立 message-only window ，以 `RegisterDeviceNotificationW` 接收 `WM_DEVICECHANGE` 的插入與移除
+//! - 其他平台：[`DeviceEvents::open()`] 回傳 [`std::io::ErrorKind::Unsupported`]
+//!
+//! 通知只代表「可能有變化」，不包含裝置資訊，收到後請重新列舉序列埠
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(not(any(target_os = "linux", windows)))]
+mod unsupported;
+#[cfg(windows)]
+mod windows;
+
+#[cfg(target_os = "linux")]
+pub use linux::DeviceEvents;
+#[cfg(not(any(target_os = "linux", windows)))]
+pub use unsupported::DeviceEvents;
+#[cfg(windows)]
+pub use windows::DeviceEvents;
@@ -0,0 +1,81 @@
+use std::{
+    io::{self, Read},
+    mem::size_of,
+};
+
+use socket2::{Domain, Protocol, SockAddr, SockAddrStorage, Socket, Type, socklen_t};
+use tokio::io::unix::AsyncFd;
+
+/// kernel uevent 的 multicast group
+const KERNEL_GROUP: u32 = 1;
+
+/// 單一 uevent 的長度上限，超過的部分會被截斷
+const MESSAGE_LENGTH: usize = 8192;
+
+/// 裝置插拔通知
+#[derive(Debug)]
+pub struct DeviceEvents(AsyncFd<Socket>);
+
+impl DeviceEvents {
+    /// 開始接收裝置插拔通知，需在 tokio runtime 中調用
+    ///
+    /// # Errors
+    /// 無法建立 netlink socket 時回傳
+    pub fn open() -> io::Result<Self> {
+        let socket = Socket::new(
+            Domain::from(libc::AF_NETLINK),
+            Type::DGRAM,
+            Some(Protocol::from(libc::NETLINK_KOBJECT_UEVENT)),
+        )?;
+        socket.bind(&address())?;
+        socket.set_nonblocking(true)?;
+        Ok(Self(AsyncFd::new(socket)?))
+    }
+
+    /// 等待下一次序列埠裝置插入或移除
+    ///
+    /// 本 function 可以安全地在 `tokio::select!` 中取消
+    ///
+    /// # Errors
+    /// 讀取 socket 失敗時回傳
+    pub async fn next(&mut self) -> io::Result<()> {
+        let mut message = vec![0; MESSAGE_LENGTH];
+        loop {
+            let mut guard = self.0.readable().await?;
+            let Ok(read) = guard.try_io(|socket| socket.get_ref().read(&mut message)) else {
+                continue;
+            };
+            if is_tty_change(&message[..read?]) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// uevent 是否為 `tty` 子系統的插入或移除
+///
+/// uevent 的內容為以 NUL 分隔的 `KEY=value` 欄位，第一個欄位為 `ACTION@DEVPATH`
+fn is_tty_change(message: &[u8]) -> bool {
+    let mut action = false;
+    let mut tty = false;
+    for field in message.split(|byte| *byte == 0) {
+        match field {
+            b"ACTION=add" | b"ACTION=remove" => action = true,
+            b"SUBSYSTEM=tty" => tty = true,
+            _ => {}
+        }
+    }
+    action && tty
+}
+
+/// 訂閱 kernel uevent 的 `sockaddr_nl`
+#[expect(unsafe_code, clippy::cast_possible_truncation)]
+fn address() -> SockAddr {
+    let mut storage = SockAddrStorage::zeroed();
+    // SAFETY: `sockaddr_nl` 是 Linux 定義的 `sockaddr` 型別
+    let netlink = unsafe { storage.view_as::<libc::sockaddr_nl>() };
+    netlink.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+    netlink.nl_groups = KERNEL_GROUP;
+    // SAFETY: `storage` 已全部初始化，長度為 `sockaddr_nl` 的長度
+    unsafe { SockAddr::new(storage, size_of::<libc::sockaddr_nl>() as socklen_t) }
+}
@@ -0,0 +1,27 @@
+use std::io::{self, ErrorKind};
+
+/// 裝置插拔通知，本平台不支援
+#[derive(Debug)]
+pub struct DeviceEvents(());
+
+impl DeviceEvents {
+    /// 本平台不支援裝置插拔通知
+    ///
+    /// # Errors
+    /// 一律回傳 [`ErrorKind::Unsupported`]
+    pub fn open() -> io::Result<Self> {
+        Err(io::Error::new(
+            ErrorKind::Unsupported,
+            "本平台不支援裝置插拔通知",
+        ))
+    }
+
+    /// 本平台不支援裝置插拔通知
+    ///
+    /// # Errors
+    /// 一律回傳 [`ErrorKind::Unsupported`]
+    #[expect(clippy::unused_async)]
+    pub async fn next(&mut self) -> io::Result<()> {
+        Err(io::Error::from(ErrorKind::Unsupported))
+    }
+}
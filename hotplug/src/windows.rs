@@ -0,0 +1,209 @@
+use std::{
+    cell::RefCell,
+    io::{self, ErrorKind},
+    mem::size_of,
+    ptr::{null, null_mut},
+    sync::mpsc as std_mpsc,
+    thread,
+};
+
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use windows_sys::Win32::{
+    Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+    System::LibraryLoader::GetModuleHandleW,
+    UI::WindowsAndMessaging::{
+        CreateWindowExW, DBT_DEVICEARRIVAL, DBT_DEVICEREMOVECOMPLETE, DBT_DEVTYP_DEVICEINTERFACE,
+        DEV_BROADCAST_DEVICEINTERFACE_W, DEVICE_NOTIFY_ALL_INTERFACE_CLASSES,
+        DEVICE_NOTIFY_WINDOW_HANDLE, DefWindowProcW, DestroyWindow, DispatchMessageW, GetMessageW,
+        HDEVNOTIFY, HWND_MESSAGE, MSG, PostMessageW, PostQuitMessage, RegisterClassW,
+        RegisterDeviceNotificationW, UnregisterDeviceNotification, WM_CLOSE, WM_DESTROY,
+        WM_DEVICECHANGE, WNDCLASSW,
+    },
+};
+
+/// 視窗類別已登記（`ERROR_CLASS_ALREADY_EXISTS`）
+const CLASS_ALREADY_EXISTS: i32 = 1410;
+
+thread_local! {
+    /// 背景執行緒的視窗程序使用的通知管道
+    static SENDER: RefCell<Option<UnboundedSender<()>>> = const { RefCell::new(None) };
+}
+
+/// 裝置插拔通知
+///
+/// 背景執行緒在本 struct 被 drop 時結束
+#[derive(Debug)]
+pub struct DeviceEvents {
+    receiver: UnboundedReceiver<()>,
+    /// 背景執行緒的視窗，以整數保存讓本 struct 可以跨執行緒傳遞
+    window: usize,
+}
+
+impl DeviceEvents {
+    /// 開始接收裝置插拔通知，會建立一個背景執行緒
+    ///
+    /// # Errors
+    /// 無法建立背景執行緒、視窗或登記通知時回傳
+    pub fn open() -> io::Result<Self> {
+        let (sender, receiver) = unbounded_channel();
+        let (ready, started) = std_mpsc::channel();
+        thread::Builder::new()
+            .name("device-hotplug".to_owned())
+            .spawn(move || run(sender, &ready))?;
+        let window = started
+            .recv()
+            .map_err(|_| io::Error::new(ErrorKind::BrokenPipe, "裝置插拔通知執行緒已結束"))??;
+        Ok(Self { receiver, window })
+    }
+
+    /// 等待下一次裝置插入或移除
+    ///
+    /// 本 function 可以安全地在 `tokio::select!` 中取消
+    ///
+    /// # Errors
+    /// 背景執行緒已結束時回傳
+    pub async fn next(&mut self) -> io::Result<()> {
+        self.receiver
+            .recv()
+            .await
+            .ok_or_else(|| io::Error::new(ErrorKind::BrokenPipe, "裝置插拔通知執行緒已結束"))
+    }
+}
+
+impl Drop for DeviceEvents {
+    #[expect(unsafe_code)]
+    fn drop(&mut self) {
+        // SAFETY: 視窗由背景執行緒建立，已關閉時本呼叫只會失敗
+        unsafe { PostMessageW(self.window as HWND, WM_CLOSE, 0, 0) };
+    }
+}
+
+/// 背景執行緒，建立視窗並處理訊息直到視窗關閉
+#[expect(unsafe_code)]
+fn run(sender: UnboundedSender<()>, ready: &std_mpsc::Sender<io::Result<usize>>) {
+    SENDER.with_borrow_mut(|slot| *slot = Some(sender));
+
+    let (window, notification) = match open_window() {
+        Ok(opened) => opened,
+        Err(error) => {
+            let _ = ready.send(Err(error));
+            return;
+        }
+    };
+    let _ = ready.send(Ok(window as usize));
+
+    let mut message = MSG::default();
+    // SAFETY: `message` 在呼叫期間有效；視窗關閉後收到 `WM_QUIT` ，`GetMessageW` 回傳 0
+    while unsafe { GetMessageW(&raw mut message, null_mut(), 0, 0) } > 0 {
+        // SAFETY: `message` 由 `GetMessageW` 填入
+        unsafe { DispatchMessageW(&raw const message) };
+    }
+
+    // SAFETY: `notification` 由 `RegisterDeviceNotificationW` 取得，只會註銷一次
+    unsafe { UnregisterDeviceNotification(notification) };
+}
+
+/// 建立 message-only window 並登記所有裝置介面的插拔通知
+#[expect(unsafe_code, clippy::cast_possible_truncation)]
+fn open_window() -> io::Result<(HWND, HDEVNOTIFY)> {
+    let class = wide("DeviceStateExchangeHotplug");
+    // SAFETY: 傳入 null 取得目前執行檔的模組
+    let instance = unsafe { GetModuleHandleW(null()) };
+    let window_class = WNDCLASSW {
+        lpfnWndProc: Some(window_proc),
+        hInstance: instance,
+        lpszClassName: class.as_ptr(),
+        ..WNDCLASSW::default()
+    };
+    // SAFETY: `window_class` 與 `class` 在呼叫期間有效
+    if unsafe { RegisterClassW(&raw const window_class) } == 0 {
+        let error = io::Error::last_os_error();
+        if error.raw_os_error() != Some(CLASS_ALREADY_EXISTS) {
+            return Err(error);
+        }
+    }
+
+    // SAFETY: 類別已登記，`class` 在呼叫期間有效
+    let window = unsafe {
+        CreateWindowExW(
+            0,
+            class.as_ptr(),
+            null(),
+            0,
+            0,
+            0,
+            0,
+            0,
+            HWND_MESSAGE,
+            null_mut(),
+            instance,
+            null(),
+        )
+    };
+    if window.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+
+    let filter = DEV_BROADCAST_DEVICEINTERFACE_W {
+        dbcc_size: size_of::<DEV_BROADCAST_DEVICEINTERFACE_W>() as u32,
+        dbcc_devicetype: DBT_DEVTYP_DEVICEINTERFACE,
+        ..DEV_BROADCAST_DEVICEINTERFACE_W::default()
+    };
+    // SAFETY: `window` 為有效的視窗，`filter` 在呼叫期間有效
+    let notification = unsafe {
+        RegisterDeviceNotificationW(
+            window,
+            (&raw const filter).cast(),
+            DEVICE_NOTIFY_WINDOW_HANDLE | DEVICE_NOTIFY_ALL_INTERFACE_CLASSES,
+        )
+    };
+    if notification.is_null() {
+        let error = io::Error::last_os_error();
+        // SAFETY: `window` 由本執行緒建立
+        unsafe { DestroyWindow(window) };
+        return Err(error);
+    }
+
+    Ok((window, notification))
+}
+
+/// 視窗程序，收到插入或移除時通知接收端，接收端已關閉時關閉視窗
+#[expect(unsafe_code)]
+unsafe extern "system" fn window_proc(
+    window: HWND,
+    message: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match message {
+        WM_DEVICECHANGE
+            if matches!(
+                u32::try_from(wparam),
+                Ok(DBT_DEVICEARRIVAL | DBT_DEVICEREMOVECOMPLETE)
+            ) =>
+        {
+            let sent = SENDER.with_borrow(|sender| {
+                sender
+                    .as_ref()
+                    .is_some_and(|sender| sender.send(()).is_ok())
+            });
+            if !sent {
+                // SAFETY: `window` 為本執行緒的視窗
+                unsafe { DestroyWindow(window) };
+            }
+            1
+        }
+        WM_DESTROY => {
+            // SAFETY: 在建立視窗的執行緒中呼叫
+            unsafe { PostQuitMessage(0) };
+            0
+        }
+        // SAFETY: 其他訊息交由預設的視窗程序處理
+        _ => unsafe { DefWindowProcW(window, message, wparam, lparam) },
+    }
+}
+
+/// 以 NUL 結尾的 UTF-16 字串
+fn wide(text: &str) -> Vec<u16> {
+    text.encode_utf16().chain([0]).collect()
+}
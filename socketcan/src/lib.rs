@@ -0,0 +1,91 @@
+//! `device-state-exchange-lib` 的 SocketCAN 存取
+//!
+//! 以 Linux 的 `AF_CAN` raw socket 直接存取 CAN 介面，請透過 `device-state-exchange-lib` 的 `canbus` feature 使用，不需要直接依賴本 crate
+//!
+//! 建立 socket 與讀取訊框都經由 [`socket2`] ，只有填入 `sockaddr_can` 與查詢介面編號需要 `unsafe` ，
+//! 因此獨立為本 crate ，`device-state-exchange-lib` 維持禁止 `unsafe` ；非 Linux 平台上本 crate 為空
+
+#![cfg(target_os = "linux")]
+
+use std::{
+    ffi::CString,
+    io::{self, ErrorKind, Read},
+    mem::size_of,
+};
+
+use socket2::{Domain, Protocol, SockAddr, SockAddrStorage, Socket, Type, socklen_t};
+use tokio::io::unix::AsyncFd;
+
+/// `struct can_frame` 的長度
+pub const FRAME_LENGTH: usize = 16;
+
+/// 綁定至單一 CAN 介面的 raw socket
+#[derive(Debug)]
+pub struct CanSocket(AsyncFd<Socket>);
+
+impl CanSocket {
+    /// 開啟 raw socket 並綁定至介面 `interface`（如 `can0`）
+    ///
+    /// # Errors
+    /// 介面不存在、系統不支援 `AF_CAN` 或綁定失敗時回傳
+    pub fn open(interface: &str) -> io::Result<Self> {
+        let address = address(interface_index(interface)?);
+        let socket = Socket::new(
+            Domain::from(libc::AF_CAN),
+            Type::from(libc::SOCK_RAW),
+            Some(Protocol::from(libc::CAN_RAW)),
+        )?;
+        socket.bind(&address)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self(AsyncFd::new(socket)?))
+    }
+
+    /// 接收一個 `struct can_frame` ，識別碼轉為 little-endian
+    ///
+    /// # Errors
+    /// - 讀取失敗時回傳，通常代表介面已關閉
+    /// - 讀取的長度不是 [`FRAME_LENGTH`]（如 CAN FD 訊框）時回傳 [`ErrorKind::InvalidData`] ，該訊框已被捨棄，可以繼續接收
+    pub async fn recv(&self) -> io::Result<[u8; FRAME_LENGTH]> {
+        loop {
+            let mut guard = self.0.readable().await?;
+            let mut frame = [0; FRAME_LENGTH];
+            let Ok(read) = guard.try_io(|socket| socket.get_ref().read(&mut frame)) else {
+                continue;
+            };
+            let read = read?;
+            if read != FRAME_LENGTH {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("訊框長度 {read} 不是 {FRAME_LENGTH}"),
+                ));
+            }
+            let id = u32::from_ne_bytes([frame[0], frame[1], frame[2], frame[3]]);
+            frame[..4].copy_from_slice(&id.to_le_bytes());
+            return Ok(frame);
+        }
+    }
+}
+
+/// 介面名稱對應的介面編號
+#[expect(unsafe_code)]
+fn interface_index(interface: &str) -> io::Result<libc::c_int> {
+    let name = CString::new(interface).map_err(|_| io::Error::from(ErrorKind::InvalidInput))?;
+    // SAFETY: `name` 是以 NUL 結尾的字串，呼叫期間不會被釋放
+    let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+    if index == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    libc::c_int::try_from(index).map_err(|_| io::Error::from(ErrorKind::InvalidInput))
+}
+
+/// 介面編號 `index` 的 `sockaddr_can`
+#[expect(unsafe_code, clippy::cast_possible_truncation)]
+fn address(index: libc::c_int) -> SockAddr {
+    let mut storage = SockAddrStorage::zeroed();
+    // SAFETY: `sockaddr_can` 是 Linux 定義的 `sockaddr` 型別
+    let can = unsafe { storage.view_as::<libc::sockaddr_can>() };
+    can.can_family = libc::AF_CAN as libc::sa_family_t;
+    can.can_ifindex = index;
+    // SAFETY: `storage` 已全部初始化，長度為 `sockaddr_can` 的長度
+    unsafe { SockAddr::new(storage, size_of::<libc::sockaddr_can>() as socklen_t) }
+}
@@ -0,0 +1,109 @@
+//! 讀寫權限檢查
+//!
+//! 外部界面在讀取點位狀態、加入寫入指令或讀取連線的診斷資料前，會以 [`Authorizer::check()`] 檢查請求來源（參見 [`crate::ClientId`]）是否有權限，
+//! 部署時可以實作 [`Authorizer`] trait 接上角色權限（RBAC）等機制，而不需要修改外部界面；未指定時使用允許所有請求的 [`AllowAll`]
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{ClientId, TenantId, auth::{Action, Authorizer, Decision}};
+//!
+//! /// 只有 `operator` 可以寫入與檢視連線，且只能存取租戶 `plant-a` ；其他用戶端只能讀取
+//! #[derive(Debug)]
+//! struct OperatorOnly;
+//!
+//! impl Authorizer for OperatorOnly {
+//!     fn check(
+//!         &self,
+//!         principal: Option<&ClientId>,
+//!         tenant: Option<&TenantId>,
+//!         _target: &str,
+//!         action: Action,
+//!     ) -> Decision {
+//!         let operator = principal.is_some_and(|client| client.as_str() == "operator")
+//!             && tenant.is_none_or(|tenant| tenant.as_str() == "plant-a");
+//!         match action {
+//!             Action::Read => Decision::Allow,
+//!             Action::Write | Action::Inspect if operator => Decision::Allow,
+//!             Action::Write | Action::Inspect => Decision::Deny,
+//!         }
+//!     }
+//! }
+//!
+//! let operator = ClientId::new("operator");
+//! let plant_a = TenantId::new("plant-a");
+//! let plant_b = TenantId::new("plant-b");
+//! assert!(OperatorOnly.check(Some(&operator), Some(&plant_a), "setpoint", Action::Write).is_allowed());
+//! assert!(!OperatorOnly.check(Some(&operator), Some(&plant_b), "setpoint", Action::Write).is_allowed());
+//! assert!(!OperatorOnly.check(None, None, "COM1", Action::Inspect).is_allowed());
+//! ```
+
+use std::fmt::Debug;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ClientId, TenantId};
+
+/// 請求動作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// 讀取點位狀態
+    Read,
+    /// 寫入點位狀態
+    Write,
+    /// 讀取連線的診斷資料，如連線統計數據與請求紀錄；目標為連線識別名稱
+    Inspect,
+}
+
+/// 權限檢查結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decision {
+    /// 允許
+    Allow,
+    /// 拒絕
+    Deny,
+}
+
+impl Decision {
+    /// 是否允許
+    #[must_use]
+    pub const fn is_allowed(self) -> bool {
+        matches!(self, Self::Allow)
+    }
+}
+
+/// 讀寫權限檢查
+///
+/// 實作本 trait 的 struct/enum 必須同時實作 [`Debug`] ，且需要可以在多個執行緒間共享
+pub trait Authorizer: Debug + Send + Sync + 'static {
+    /// 檢查請求是否有權限
+    ///
+    /// 本 function 會在每個外部請求處理前調用，請不要在此處執行需要長時間等待的邏輯
+    ///
+    /// # 參數
+    /// - `principal`：請求來源，無法識別時為 [`None`]
+    /// - `tenant`：請求存取的租戶，不是多租戶界面時為 [`None`] ；同名的點位在不同租戶中是不同的點位
+    /// - `target`：點位名稱，[`Action::Inspect`] 時為連線識別名稱
+    /// - `action`：請求動作
+    ///
+    /// # 回傳值
+    /// 權限檢查結果
+    fn check(
+        &self,
+        principal: Option<&ClientId>,
+        tenant: Option<&TenantId>,
+        target: &str,
+        action: Action,
+    ) -> Decision;
+}
+
+/// 允許所有請求
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllowAll;
+
+impl Authorizer for AllowAll {
+    fn check(&self, _: Option<&ClientId>, _: Option<&TenantId>, _: &str, _: Action) -> Decision {
+        Decision::Allow
+    }
+}
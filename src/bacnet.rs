@@ -0,0 +1,2223 @@
+//! BACnet/IP 參考實作（需啟用 `bacnet` feature）
+//!
+//! 以 UDP 與 BACnet/IP 設備交換資料，支援：
+//!
+//! - 設備探索：[`crate::Connection::init()`] 時廣播 Who-Is ，收集設備的 I-Am 回覆，探索結果放在 [`crate::ConnectionArtifact::discovery`]，
+//!   點位依設備編號（device instance）對應到設備位址，找不到設備的點位會被略過，並記錄於 [`BacnetConnection::rejected_targets()`]
+//! - 讀取：同一設備的自動更新點位以 `ReadPropertyMultiple` 合併讀取（參見 [`BacnetConfig::max_properties_per_request`]），其他點位使用 `ReadProperty`
+//! - COV 訂閱：設定 [`BacnetConfig::cov_lifetime`] 後，自動更新點位的 `Present_Value` 改為訂閱 `SubscribeCOV` ，
+//!   主程式輪詢時直接回傳設備通知的最新數值，訂閱在有效期限過半時重新訂閱；設備不支援 COV 時，該物件改回輪詢
+//! - 寫入：`WriteProperty` ，可以在點位設定優先權
+//!
+//! 經由 BACnet 路由器連接的設備（如 MS/TP 設備）會依 I-Am 回覆中的來源網路轉送請求；不支援分段傳送（segmentation），
+//! 回覆過長時請調低 [`BacnetConfig::max_properties_per_request`]
+//!
+//! 點位設定：
+//!
+//! | 欄位 | 說明 |
+//! | --- | --- |
+//! | `device` | 設備編號（device instance），0 至 4194302 |
+//! | `address` | 物件，`物件型別:編號`，物件型別可以使用名稱（如 `analog-input`）、縮寫（如 `ai`）或數字 |
+//! | `data_type` | 寫入時使用的資料型別，參見 [`BacnetType`] ，未設定時依物件型別決定 |
+//! | `property` | 屬性名稱（如 `present-value`）或數字，未設定時為 `present-value` |
+//! | `array_index` | 陣列屬性的索引 |
+//! | `priority` | 寫入的優先權，1 至 16 |
+//! | `cov` | 是否以 COV 訂閱取代輪詢，未設定時為 `true` |
+//!
+//! # 範例
+//! ```rust
+//! use std::time::Duration;
+//! use device_state_exchange_lib::{
+//!     TargetDefinition,
+//!     bacnet::{BacnetConfig, BacnetPoint, BacnetValue, ObjectIdentifier, PRESENT_VALUE},
+//! };
+//! use serde_json::json;
+//!
+//! let config: BacnetConfig = serde_json::from_value(json!({
+//!     "bind": "0.0.0.0:47808",
+//!     "cov_lifetime_ms": 300000,
+//!     "devices": { "1001": "192.168.1.20:47808" },
+//! }))
+//! .unwrap();
+//! assert_eq!(config.cov_lifetime, Some(Duration::from_secs(300)));
+//!
+//! let definition: TargetDefinition = serde_json::from_value(json!({
+//!     "name": "回風溫度",
+//!     "device": "1001",
+//!     "address": "ai:3",
+//! }))
+//! .unwrap();
+//! let point = BacnetPoint::parse(&definition).unwrap();
+//! assert_eq!(point.property.object, ObjectIdentifier { object_type: 0, instance: 3 });
+//! assert_eq!(point.property.property, PRESENT_VALUE);
+//! assert_eq!(point.property.to_string(), "analog-input:3/present-value");
+//!
+//! // 應用標籤編碼
+//! let mut encoded = Vec::new();
+//! BacnetValue::Real(21.5).encode(&mut encoded);
+//! assert_eq!(encoded, [0x44, 0x41, 0xac, 0x00, 0x00]);
+//! assert_eq!(BacnetValue::decode(&encoded).unwrap(), BacnetValue::Real(21.5));
+//! assert_eq!(BacnetValue::Real(21.5).to_json(), json!(21.5));
+//! ```
+
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    error::Error,
+    fmt::{Display, Write},
+    io::ErrorKind,
+    net::{Ipv4Addr, SocketAddr, SocketAddrV4},
+    sync::Arc,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{
+    net::UdpSocket,
+    time::{Instant, timeout_at},
+};
+
+use crate::{
+    Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+    ConnectionTargets, DeviceStateRequest, DeviceStateResponse, DeviceStateWrite, HashMap, HashSet,
+    InitedTarget, RequestContext, Target, TargetDefinition,
+    cache::{CacheLookup, CachePolicy, ResponseCache},
+    definition::InvalidTarget,
+    discovery::{BusScanReport, DiscoveryReport, ProbeOutcome, ScannedAddress},
+    fixture::FrameDecoder,
+    millis,
+    value::ConversionError,
+};
+
+/// BACnet/IP 預設的 UDP 埠號（0xBAC0）
+pub const DEFAULT_PORT: u16 = 47808;
+
+/// 預設的更新間隔
+pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 預設的回覆逾時，參見 BACnet 規範的 `APDU_Timeout`
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 預設的設備探索時間
+pub const DEFAULT_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 預設的單次 `ReadPropertyMultiple` 屬性數量上限
+pub const DEFAULT_MAX_PROPERTIES_PER_REQUEST: u16 = 16;
+
+/// 設備編號的上限，4194303 保留為萬用字元
+pub const MAX_DEVICE_INSTANCE: u32 = 4_194_302;
+
+/// `Present_Value` 屬性
+pub const PRESENT_VALUE: u32 = 85;
+
+/// COV 訂閱使用的訂閱者程序編號
+const COV_PROCESS_ID: u32 = 1;
+
+/// 可接受的 APDU 長度上限（1476 位元組，BACnet/IP 的上限）
+const MAX_APDU: usize = 1476;
+
+/// 常用的物件型別：編號、名稱、縮寫
+const OBJECT_TYPES: &[(u16, &str, &str)] = &[
+    (0, "analog-input", "ai"),
+    (1, "analog-output", "ao"),
+    (2, "analog-value", "av"),
+    (3, "binary-input", "bi"),
+    (4, "binary-output", "bo"),
+    (5, "binary-value", "bv"),
+    (6, "calendar", "cal"),
+    (8, "device", "dev"),
+    (13, "multi-state-input", "msi"),
+    (14, "multi-state-output", "mso"),
+    (15, "notification-class", "nc"),
+    (17, "schedule", "sch"),
+    (19, "multi-state-value", "msv"),
+    (20, "trend-log", "tl"),
+];
+
+/// 常用的屬性：編號、名稱
+const PROPERTIES: &[(u32, &str)] = &[
+    (28, "description"),
+    (36, "event-state"),
+    (76, "object-list"),
+    (77, "object-name"),
+    (81, "out-of-service"),
+    (85, "present-value"),
+    (87, "priority-array"),
+    (103, "reliability"),
+    (104, "relinquish-default"),
+    (111, "status-flags"),
+    (117, "units"),
+];
+
+const fn default_bind() -> SocketAddr {
+    SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, DEFAULT_PORT))
+}
+
+const fn default_broadcast() -> SocketAddr {
+    SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::BROADCAST, DEFAULT_PORT))
+}
+
+const fn default_update_interval() -> Duration {
+    DEFAULT_UPDATE_INTERVAL
+}
+
+const fn default_timeout() -> Duration {
+    DEFAULT_TIMEOUT
+}
+
+const fn default_discovery_timeout() -> Duration {
+    DEFAULT_DISCOVERY_TIMEOUT
+}
+
+const fn default_max_properties_per_request() -> u16 {
+    DEFAULT_MAX_PROPERTIES_PER_REQUEST
+}
+
+/// BACnet/IP 連線設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BacnetConfig {
+    /// 本機綁定的位址，預設為 `0.0.0.0:47808`
+    ///
+    /// 多數設備以廣播回覆 I-Am ，綁定其他埠號時可能收不到探索結果，請改以 [`Self::devices`] 指定設備位址
+    #[serde(default = "default_bind")]
+    pub bind: SocketAddr,
+    /// 送出 Who-Is 的廣播位址，預設為 `255.255.255.255:47808`
+    #[serde(default = "default_broadcast")]
+    pub broadcast: SocketAddr,
+    /// 固定的設備位址，以設備編號為鍵，適用於廣播無法到達的設備；探索到的位址優先於本設定
+    #[serde(default)]
+    pub devices: BTreeMap<u32, SocketAddr>,
+    /// 更新間隔
+    #[serde(
+        rename = "update_interval_ms",
+        with = "millis",
+        default = "default_update_interval"
+    )]
+    pub update_interval: Duration,
+    /// 回覆逾時
+    #[serde(rename = "timeout_ms", with = "millis", default = "default_timeout")]
+    pub timeout: Duration,
+    /// 等待 I-Am 回覆的時間
+    #[serde(
+        rename = "discovery_timeout_ms",
+        with = "millis",
+        default = "default_discovery_timeout"
+    )]
+    pub discovery_timeout: Duration,
+    /// COV 訂閱的有效期限，未設定時不使用 COV ，所有點位皆以輪詢讀取
+    #[serde(rename = "cov_lifetime_ms", with = "millis::option", default)]
+    pub cov_lifetime: Option<Duration>,
+    /// 單次 `ReadPropertyMultiple` 的屬性數量上限，預設為 [`DEFAULT_MAX_PROPERTIES_PER_REQUEST`] ，設為 1 時只使用 `ReadProperty`
+    #[serde(default = "default_max_properties_per_request")]
+    pub max_properties_per_request: u16,
+    /// 最大重試次數，未設定時使用 [`crate::ConnectionArtifact`] 的預設值
+    #[serde(default)]
+    pub max_retry_count: Option<u32>,
+}
+
+impl ConnectionConfig for BacnetConfig {}
+
+/// 物件識別碼
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ObjectIdentifier {
+    /// 物件型別，如 `0`（Analog Input）
+    pub object_type: u16,
+    /// 物件編號
+    pub instance: u32,
+}
+
+impl ObjectIdentifier {
+    /// 設備物件
+    #[must_use]
+    pub const fn device(instance: u32) -> Self {
+        Self {
+            object_type: 8,
+            instance,
+        }
+    }
+
+    /// 解析 `物件型別:編號` 格式的字串，物件型別可以使用名稱、縮寫或數字，不區分大小寫
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let (object_type, instance) = text.trim().split_once(':')?;
+        let object_type = object_type.trim().to_ascii_lowercase().replace('_', "-");
+        let object_type = OBJECT_TYPES
+            .iter()
+            .find(|(_, name, short)| *name == object_type || *short == object_type)
+            .map(|(number, ..)| *number)
+            .or_else(|| object_type.parse().ok())
+            .filter(|object_type| *object_type < 1024)?;
+        let instance = instance
+            .trim()
+            .parse()
+            .ok()
+            .filter(|instance| *instance <= 0x3f_ffff)?;
+        Some(Self {
+            object_type,
+            instance,
+        })
+    }
+
+    const fn to_u32(self) -> u32 {
+        ((self.object_type as u32) << 22) | (self.instance & 0x3f_ffff)
+    }
+
+    const fn from_u32(value: u32) -> Self {
+        Self {
+            object_type: (value >> 22) as u16,
+            instance: value & 0x3f_ffff,
+        }
+    }
+}
+
+impl Display for ObjectIdentifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match OBJECT_TYPES
+            .iter()
+            .find(|(number, ..)| *number == self.object_type)
+        {
+            Some((_, name, _)) => write!(f, "{name}:{}", self.instance),
+            None => write!(f, "{}:{}", self.object_type, self.instance),
+        }
+    }
+}
+
+/// 屬性參照
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PropertyReference {
+    /// 物件
+    pub object: ObjectIdentifier,
+    /// 屬性編號，如 [`PRESENT_VALUE`]
+    pub property: u32,
+    /// 陣列屬性的索引
+    pub array_index: Option<u32>,
+}
+
+impl Display for PropertyReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/", self.object)?;
+        match PROPERTIES
+            .iter()
+            .find(|(number, _)| *number == self.property)
+        {
+            Some((_, name)) => write!(f, "{name}")?,
+            None => write!(f, "{}", self.property)?,
+        }
+        self.array_index
+            .map_or(Ok(()), |index| write!(f, "[{index}]"))
+    }
+}
+
+/// 寫入時使用的資料型別
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BacnetType {
+    /// 布林值
+    Boolean,
+    /// 無號整數，多狀態物件的 `Present_Value` 預設使用此型別
+    Unsigned,
+    /// 有號整數
+    Signed,
+    /// 單精度浮點數，類比物件的 `Present_Value` 預設使用此型別
+    Real,
+    /// 雙精度浮點數
+    Double,
+    /// 列舉，二元物件的 `Present_Value` 預設使用此型別（`0` 為 inactive ，`1` 為 active）
+    Enumerated,
+    /// 字串
+    CharacterString,
+}
+
+impl BacnetType {
+    /// 物件 `Present_Value` 的資料型別
+    const fn of_present_value(object_type: u16) -> Option<Self> {
+        match object_type {
+            0..=2 => Some(Self::Real),
+            3..=5 => Some(Self::Enumerated),
+            13 | 14 | 19 => Some(Self::Unsigned),
+            _ => None,
+        }
+    }
+}
+
+/// 應用標籤資料
+#[derive(Debug, Clone, PartialEq)]
+pub enum BacnetValue {
+    /// 空值，寫入時代表解除該優先權的命令
+    Null,
+    /// 布林值
+    Boolean(bool),
+    /// 無號整數
+    Unsigned(u64),
+    /// 有號整數
+    Signed(i64),
+    /// 單精度浮點數
+    Real(f32),
+    /// 雙精度浮點數
+    Double(f64),
+    /// 位元組字串
+    OctetString(Vec<u8>),
+    /// 字串
+    CharacterString(String),
+    /// 位元字串
+    BitString(Vec<bool>),
+    /// 列舉
+    Enumerated(u32),
+    /// 日期：年（減去 1900）、月、日、星期，`0xff` 代表不指定
+    Date([u8; 4]),
+    /// 時間：時、分、秒、百分之一秒，`0xff` 代表不指定
+    Time([u8; 4]),
+    /// 物件識別碼
+    ObjectIdentifier(ObjectIdentifier),
+    /// 多個數值，如陣列或清單屬性
+    List(Vec<Self>),
+}
+
+impl BacnetValue {
+    /// 以應用標籤編碼
+    ///
+    /// # 參數
+    /// - `buffer`：編碼結果會附加在此緩衝區之後
+    pub fn encode(&self, buffer: &mut Vec<u8>) {
+        match self {
+            Self::Null => encode_tag(buffer, 0, false, 0),
+            Self::Boolean(value) => buffer.push(0x10 | u8::from(*value)),
+            Self::Unsigned(value) => {
+                let bytes = unsigned_bytes(*value);
+                encode_tag(buffer, 2, false, bytes.len());
+                buffer.extend(bytes);
+            }
+            Self::Signed(value) => {
+                let bytes = signed_bytes(*value);
+                encode_tag(buffer, 3, false, bytes.len());
+                buffer.extend(bytes);
+            }
+            Self::Real(value) => {
+                encode_tag(buffer, 4, false, 4);
+                buffer.extend(value.to_be_bytes());
+            }
+            Self::Double(value) => {
+                encode_tag(buffer, 5, false, 8);
+                buffer.extend(value.to_be_bytes());
+            }
+            Self::OctetString(bytes) => {
+                encode_tag(buffer, 6, false, bytes.len());
+                buffer.extend(bytes);
+            }
+            Self::CharacterString(text) => {
+                // 字元集 0 為 UTF-8
+                encode_tag(buffer, 7, false, text.len() + 1);
+                buffer.push(0);
+                buffer.extend(text.as_bytes());
+            }
+            Self::BitString(bits) => {
+                let unused = (8 - bits.len() % 8) % 8;
+                encode_tag(buffer, 8, false, bits.len().div_ceil(8) + 1);
+                buffer.push(u8::try_from(unused).unwrap_or_default());
+                buffer.extend(bits.chunks(8).map(|chunk| {
+                    chunk.iter().enumerate().fold(0, |byte, (index, bit)| {
+                        byte | (u8::from(*bit) << (7 - index))
+                    })
+                }));
+            }
+            Self::Enumerated(value) => {
+                let bytes = unsigned_bytes(u64::from(*value));
+                encode_tag(buffer, 9, false, bytes.len());
+                buffer.extend(bytes);
+            }
+            Self::Date(date) => {
+                encode_tag(buffer, 10, false, 4);
+                buffer.extend(date);
+            }
+            Self::Time(time) => {
+                encode_tag(buffer, 11, false, 4);
+                buffer.extend(time);
+            }
+            Self::ObjectIdentifier(object) => {
+                encode_tag(buffer, 12, false, 4);
+                buffer.extend(object.to_u32().to_be_bytes());
+            }
+            Self::List(values) => {
+                for value in values {
+                    value.encode(buffer);
+                }
+            }
+        }
+    }
+
+    /// 解析應用標籤編碼的資料，包含多個數值時回傳 [`Self::List`]
+    ///
+    /// # Errors
+    /// 資料不完整或包含不支援的標籤時回傳 [`ConnectionError::Protocol`]
+    ///
+    /// # 範例
+    /// 以內建的測試向量驗證：
+    /// ```rust
+    /// use device_state_exchange_lib::{bacnet::BacnetValue, vectors::{self, encode_hex}};
+    ///
+    /// let report = vectors::built_in_set("bacnet/application_value").unwrap().verify(|frame, value| {
+    ///     let decoded = BacnetValue::decode(frame).map_err(|error| error.to_string())?;
+    ///     if decoded.to_json() != *value {
+    ///         return Err(format!("解析結果為 {}", decoded.to_json()));
+    ///     }
+    ///     let mut encoded = Vec::new();
+    ///     decoded.encode(&mut encoded);
+    ///     if encoded == frame { Ok(()) } else { Err(format!("重新編碼為 {}", encode_hex(&encoded))) }
+    /// });
+    /// assert!(report.is_complete(), "{report:?}");
+    /// ```
+    pub fn decode(data: &[u8]) -> Result<Self, ConnectionError> {
+        let mut reader = Reader::new(data);
+        let mut values = Vec::new();
+        while !reader.is_empty() {
+            values.push(reader.application_value()?);
+        }
+        Ok(Self::collect(values))
+    }
+
+    fn collect(mut values: Vec<Self>) -> Self {
+        if values.len() == 1 {
+            values.swap_remove(0)
+        } else {
+            Self::List(values)
+        }
+    }
+
+    /// 轉換為 JSON
+    ///
+    /// 位元組字串轉換為十六進位字串，位元字串轉換為布林陣列，日期與時間轉換為 `YYYY-MM-DD` 與 `hh:mm:ss.cc` 格式的字串（不指定的欄位為 `*`），
+    /// 物件識別碼轉換為 `物件型別:編號` 格式的字串，無法以 JSON 表示的浮點數（如 NaN）轉換為 `null`
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        fn field(value: u8, offset: u16, width: usize) -> String {
+            if value == 0xff {
+                "*".to_owned()
+            } else {
+                format!("{:0width$}", u16::from(value) + offset)
+            }
+        }
+
+        match self {
+            Self::Null => Value::Null,
+            Self::Boolean(value) => Value::Bool(*value),
+            Self::Unsigned(value) => Value::from(*value),
+            Self::Signed(value) => Value::from(*value),
+            Self::Real(value) => Value::from(f64::from(*value)),
+            Self::Double(value) => Value::from(*value),
+            Self::OctetString(bytes) => {
+                Value::String(bytes.iter().fold(String::new(), |mut text, byte| {
+                    let _ = write!(text, "{byte:02x}");
+                    text
+                }))
+            }
+            Self::CharacterString(text) => Value::String(text.clone()),
+            Self::BitString(bits) => bits.iter().copied().map(Value::Bool).collect(),
+            Self::Enumerated(value) => Value::from(*value),
+            Self::Date([year, month, day, _]) => Value::String(format!(
+                "{}-{}-{}",
+                field(*year, 1900, 4),
+                field(*month, 0, 2),
+                field(*day, 0, 2)
+            )),
+            Self::Time([hour, minute, second, hundredths]) => Value::String(format!(
+                "{}:{}:{}.{}",
+                field(*hour, 0, 2),
+                field(*minute, 0, 2),
+                field(*second, 0, 2),
+                field(*hundredths, 0, 2)
+            )),
+            Self::ObjectIdentifier(object) => Value::String(object.to_string()),
+            Self::List(values) => values.iter().map(Self::to_json).collect(),
+        }
+    }
+
+    /// 由 JSON 轉換為寫入的數值
+    ///
+    /// # 參數
+    /// - `value`：設定值，`null` 代表解除命令
+    /// - `data_type`：資料型別，未指定時依 JSON 的型別決定
+    #[must_use]
+    pub fn from_json(value: &Value, data_type: Option<BacnetType>) -> Option<Self> {
+        if value.is_null() {
+            return Some(Self::Null);
+        }
+        let Some(data_type) = data_type else {
+            return match value {
+                Value::Bool(value) => Some(Self::Boolean(*value)),
+                Value::Number(number) => number
+                    .as_u64()
+                    .map(Self::Unsigned)
+                    .or_else(|| number.as_i64().map(Self::Signed))
+                    .or_else(|| number.as_f64().map(Self::Double)),
+                Value::String(text) => Some(Self::CharacterString(text.clone())),
+                _ => None,
+            };
+        };
+
+        let integer = || value.as_u64().or_else(|| value.as_bool().map(u64::from));
+        match data_type {
+            BacnetType::Boolean => value
+                .as_bool()
+                .or_else(|| {
+                    value
+                        .as_u64()
+                        .filter(|value| *value <= 1)
+                        .map(|value| value == 1)
+                })
+                .map(Self::Boolean),
+            BacnetType::Unsigned => integer().map(Self::Unsigned),
+            BacnetType::Signed => value.as_i64().map(Self::Signed),
+            #[expect(clippy::cast_possible_truncation)]
+            BacnetType::Real => value
+                .as_f64()
+                .filter(|value| value.is_finite())
+                .map(|value| Self::Real(value as f32)),
+            BacnetType::Double => value.as_f64().map(Self::Double),
+            BacnetType::Enumerated => match value.as_str() {
+                Some("inactive") => Some(Self::Enumerated(0)),
+                Some("active") => Some(Self::Enumerated(1)),
+                _ => integer()
+                    .and_then(|value| u32::try_from(value).ok())
+                    .map(Self::Enumerated),
+            },
+            BacnetType::CharacterString => value
+                .as_str()
+                .map(|text| Self::CharacterString(text.to_owned())),
+        }
+    }
+}
+
+/// 設備回覆的錯誤
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BacnetError {
+    /// Error PDU 或 `ReadPropertyMultiple` 中個別屬性的錯誤
+    Error {
+        /// 錯誤類別，如 `1`（object）、`2`（property）
+        class: u32,
+        /// 錯誤碼，如 `31`（unknown-object）
+        code: u32,
+    },
+    /// 設備拒絕請求（Reject PDU）
+    Reject {
+        /// 拒絕原因，如 `9`（unrecognized-service）
+        reason: u8,
+    },
+    /// 設備中止交易（Abort PDU）
+    Abort {
+        /// 中止原因，如 `4`（segmentation-not-supported）
+        reason: u8,
+    },
+}
+
+impl Display for BacnetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Error { class, code } => {
+                let reason = match code {
+                    9 => "資料型別錯誤",
+                    29 => "設備拒絕服務",
+                    31 => "物件不存在",
+                    32 => "屬性不存在",
+                    37 => "數值超出範圍",
+                    40 => "屬性不可寫入",
+                    42 => "無效的陣列索引",
+                    43 => "COV 訂閱失敗",
+                    50 => "屬性不是陣列",
+                    _ => "未知的錯誤",
+                };
+                write!(f, "設備回覆錯誤（class {class}，code {code}）：{reason}")
+            }
+            Self::Reject { reason } => {
+                let description = match reason {
+                    9 => "不支援的服務",
+                    _ => "請求無效",
+                };
+                write!(f, "設備拒絕請求（原因 {reason}）：{description}")
+            }
+            Self::Abort { reason } => {
+                let description = match reason {
+                    4 => "回覆過長，設備需要分段傳送",
+                    _ => "交易已中止",
+                };
+                write!(f, "設備中止交易（原因 {reason}）：{description}")
+            }
+        }
+    }
+}
+
+impl Error for BacnetError {}
+
+/// 設備位址
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BacnetAddress {
+    /// BACnet/IP 設備或路由器的位址
+    pub socket: SocketAddr,
+    /// 經由路由器連接時，設備所在的網路編號與 MAC 位址
+    pub route: Option<(u16, Vec<u8>)>,
+}
+
+impl From<SocketAddr> for BacnetAddress {
+    fn from(socket: SocketAddr) -> Self {
+        Self {
+            socket,
+            route: None,
+        }
+    }
+}
+
+impl Display for BacnetAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.socket)?;
+        match &self.route {
+            Some((network, mac)) => {
+                write!(f, "/{network}:")?;
+                mac.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// I-Am 回覆
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IAm {
+    /// 設備編號
+    pub device: u32,
+    /// 設備位址
+    pub address: BacnetAddress,
+    /// 設備可接受的 APDU 長度上限
+    pub max_apdu: u64,
+    /// 設備支援的分段傳送方式
+    pub segmentation: u32,
+    /// 廠商編號
+    pub vendor: u64,
+}
+
+/// COV 通知
+#[derive(Debug, Clone, PartialEq)]
+pub struct CovNotification {
+    /// 送出通知的設備編號
+    pub device: u32,
+    /// 被監看的物件
+    pub object: ObjectIdentifier,
+    /// 訂閱的剩餘時間
+    pub time_remaining: Duration,
+    /// 變動的屬性與數值
+    pub values: Vec<(u32, BacnetValue)>,
+}
+
+/// BACnet/IP 用戶端
+///
+/// 一次只處理一個確認請求（confirmed request），交易編號（invoke id）不符的回覆會被捨棄；
+/// 等待回覆期間收到的 COV 通知會被保留，由 [`Self::notifications()`] 取出
+///
+/// # 範例
+/// ```rust
+/// use std::time::Duration;
+/// use device_state_exchange_lib::bacnet::{
+///     BacnetClient, BacnetError, BacnetValue, ObjectIdentifier, PRESENT_VALUE, PropertyReference,
+/// };
+/// use tokio::net::UdpSocket;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// // 模擬設備
+/// let device = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+/// let address = device.local_addr().unwrap();
+/// tokio::spawn(async move {
+///     let mut buffer = [0; 1500];
+///     let (length, peer) = device.recv_from(&mut buffer).await.unwrap();
+///     // BVLC 、NPDU 、`ReadProperty`（analog-input:1 、present-value）
+///     assert_eq!(buffer[6..8], [0x00, 0x05]);
+///     assert_eq!(buffer[9..length], [0x0c, 0x0c, 0x00, 0x00, 0x00, 0x01, 0x19, 0x55]);
+///     let invoke_id = buffer[8];
+///     let response = [
+///         0x81, 0x0a, 0x00, 0x17, 0x01, 0x00, 0x30, invoke_id, 0x0c,
+///         0x0c, 0x00, 0x00, 0x00, 0x01, 0x19, 0x55, 0x3e, 0x44, 0x41, 0xac, 0x00, 0x00, 0x3f,
+///     ];
+///     device.send_to(&response[..response.len() - 3], peer).await.unwrap();
+///     device.send_to(&response, peer).await.unwrap();
+///
+///     // 物件不存在
+///     let (_, peer) = device.recv_from(&mut buffer).await.unwrap();
+///     let error = [0x81, 0x0a, 0x00, 0x0d, 0x01, 0x00, 0x50, buffer[8], 0x0c, 0x91, 0x01, 0x91, 0x1f];
+///     device.send_to(&error, peer).await.unwrap();
+/// });
+///
+/// let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+/// let mut client = BacnetClient::new(socket, Duration::from_millis(500));
+/// let reference = PropertyReference {
+///     object: ObjectIdentifier::parse("ai:1").unwrap(),
+///     property: PRESENT_VALUE,
+///     array_index: None,
+/// };
+/// // 第一個回覆不完整，會被捨棄
+/// let value = client.read_property(&address.into(), &reference).await.unwrap();
+/// assert_eq!(value, BacnetValue::Real(21.5));
+///
+/// let error = client.read_property(&address.into(), &reference).await.unwrap_err();
+/// assert_eq!(error.downcast_ref(), Some(&BacnetError::Error { class: 1, code: 31 }));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct BacnetClient {
+    socket: UdpSocket,
+    timeout: Duration,
+    invoke_id: u8,
+    notifications: Vec<CovNotification>,
+}
+
+impl BacnetClient {
+    /// 建立用戶端，廣播 Who-Is 前需先以 [`UdpSocket::set_broadcast()`] 開啟廣播
+    ///
+    /// # 參數
+    /// - `socket`：已綁定的 UDP socket
+    /// - `timeout`：回覆逾時
+    #[must_use]
+    pub const fn new(socket: UdpSocket, timeout: Duration) -> Self {
+        Self {
+            socket,
+            timeout,
+            invoke_id: 0,
+            notifications: Vec::new(),
+        }
+    }
+
+    /// 廣播 Who-Is 並收集 I-Am 回覆
+    ///
+    /// # 參數
+    /// - `broadcast`：廣播位址
+    /// - `range`：設備編號範圍，未指定時所有設備都會回覆
+    /// - `wait`：等待回覆的時間
+    ///
+    /// # 回傳值
+    /// 依回覆順序排列的 I-Am ，同一設備只保留第一個回覆
+    ///
+    /// # Errors
+    /// 送出或接收失敗時回傳 [`ConnectionError::Io`]
+    pub async fn who_is(
+        &mut self,
+        broadcast: SocketAddr,
+        range: Option<(u32, u32)>,
+        wait: Duration,
+    ) -> Result<Vec<IAm>, ConnectionError> {
+        let mut apdu = vec![0x10, 0x08];
+        if let Some((low, high)) = range {
+            encode_context_unsigned(&mut apdu, 0, u64::from(low));
+            encode_context_unsigned(&mut apdu, 1, u64::from(high));
+        }
+        self.socket
+            .send_to(&frame(0x0b, None, false, &apdu), broadcast)
+            .await?;
+
+        let deadline = Instant::now() + wait;
+        let mut devices: Vec<IAm> = Vec::new();
+        let mut buffer = vec![0; MAX_APDU + 64];
+        while let Ok(received) = timeout_at(deadline, self.socket.recv_from(&mut buffer)).await {
+            let (length, peer) = received?;
+            match self.dispatch(&buffer[..length], peer) {
+                Some(Incoming::IAm(i_am))
+                    if !devices.iter().any(|device| device.device == i_am.device) =>
+                {
+                    devices.push(i_am);
+                }
+                _ => {}
+            }
+        }
+        Ok(devices)
+    }
+
+    /// 讀取單一屬性（`ReadProperty`）
+    ///
+    /// # Errors
+    /// 逾時回傳 [`ConnectionError::Timeout`] ，設備回覆錯誤時回傳包含 [`BacnetError`] 的 [`ConnectionError::Custom`] ，
+    /// 無法解析的回覆回傳 [`ConnectionError::Protocol`]
+    pub async fn read_property(
+        &mut self,
+        address: &BacnetAddress,
+        reference: &PropertyReference,
+    ) -> Result<BacnetValue, ConnectionError> {
+        let mut request = Vec::new();
+        encode_reference(&mut request, reference, 0);
+        let response = self.confirmed(address, 0x0c, &request).await?;
+        read_property_ack(&response)
+    }
+
+    /// 讀取多個屬性（`ReadPropertyMultiple`）
+    ///
+    /// # 回傳值
+    /// 與 `references` 順序相同的讀取結果，個別屬性的錯誤以 [`BacnetError`] 表示
+    ///
+    /// # Errors
+    /// 整個請求失敗時的錯誤同 [`Self::read_property()`]
+    pub async fn read_property_multiple(
+        &mut self,
+        address: &BacnetAddress,
+        references: &[PropertyReference],
+    ) -> Result<Vec<Result<BacnetValue, BacnetError>>, ConnectionError> {
+        let mut request = Vec::new();
+        for group in references.chunk_by(|a, b| a.object == b.object) {
+            encode_context_object(&mut request, 0, group[0].object);
+            encode_opening(&mut request, 1);
+            for reference in group {
+                encode_context_unsigned(&mut request, 0, u64::from(reference.property));
+                if let Some(index) = reference.array_index {
+                    encode_context_unsigned(&mut request, 1, u64::from(index));
+                }
+            }
+            encode_closing(&mut request, 1);
+        }
+        let response = self.confirmed(address, 0x0e, &request).await?;
+
+        let mut results = HashMap::new();
+        let mut reader = Reader::new(&response);
+        while !reader.is_empty() {
+            let object = reader.context_object(0)?;
+            reader.opening(1)?;
+            while !reader.is_closing(1) {
+                let property =
+                    u32::try_from(reader.context_unsigned(2)?).map_err(|_| Reader::malformed())?;
+                let array_index = reader.optional_context_unsigned(3)?;
+                let result = if reader.is_opening(4) {
+                    Ok(reader.values(4)?)
+                } else {
+                    reader.opening(5)?;
+                    let error = reader.error()?;
+                    reader.closing(5)?;
+                    Err(error)
+                };
+                results.insert((object, property, array_index), result);
+            }
+            reader.closing(1)?;
+        }
+
+        references
+            .iter()
+            .map(|reference| {
+                results
+                    .get(&(reference.object, reference.property, reference.array_index))
+                    .cloned()
+                    .ok_or_else(|| ConnectionError::Protocol(format!("回覆中沒有屬性 {reference}")))
+            })
+            .collect()
+    }
+
+    /// 寫入屬性（`WriteProperty`）
+    ///
+    /// # 參數
+    /// - `priority`：優先權，1 至 16 ，未指定時由設備決定（通常為 16）
+    ///
+    /// # Errors
+    /// 同 [`Self::read_property()`]
+    pub async fn write_property(
+        &mut self,
+        address: &BacnetAddress,
+        reference: &PropertyReference,
+        value: &BacnetValue,
+        priority: Option<u8>,
+    ) -> Result<(), ConnectionError> {
+        let mut request = Vec::new();
+        encode_reference(&mut request, reference, 0);
+        encode_opening(&mut request, 3);
+        value.encode(&mut request);
+        encode_closing(&mut request, 3);
+        if let Some(priority) = priority {
+            encode_context_unsigned(&mut request, 4, u64::from(priority));
+        }
+        self.confirmed(address, 0x0f, &request).await.map(|_| ())
+    }
+
+    /// 訂閱物件的 COV 通知（`SubscribeCOV`），設備以 `UnconfirmedCOVNotification` 通知
+    ///
+    /// # 參數
+    /// - `process_id`：訂閱者程序編號，通知中會帶有此編號
+    /// - `lifetime`：訂閱的有效期限，未指定時取消訂閱
+    ///
+    /// # Errors
+    /// 同 [`Self::read_property()`]
+    pub async fn subscribe_cov(
+        &mut self,
+        address: &BacnetAddress,
+        process_id: u32,
+        object: ObjectIdentifier,
+        lifetime: Option<Duration>,
+    ) -> Result<(), ConnectionError> {
+        let mut request = Vec::new();
+        encode_context_unsigned(&mut request, 0, u64::from(process_id));
+        encode_context_object(&mut request, 1, object);
+        if let Some(lifetime) = lifetime {
+            request.push(0x29);
+            request.push(0x00);
+            encode_context_unsigned(&mut request, 3, lifetime.as_secs().max(1));
+        }
+        self.confirmed(address, 0x05, &request).await.map(|_| ())
+    }
+
+    /// 取出已收到的 COV 通知，會先讀取 socket 中尚未處理的封包，不會等待
+    ///
+    /// # Errors
+    /// 接收失敗時回傳 [`ConnectionError::Io`]
+    pub fn notifications(&mut self) -> Result<Vec<CovNotification>, ConnectionError> {
+        let mut buffer = vec![0; MAX_APDU + 64];
+        loop {
+            match self.socket.try_recv_from(&mut buffer) {
+                Ok((length, peer)) => {
+                    self.dispatch(&buffer[..length], peer);
+                }
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                Err(error) => return Err(error.into()),
+            }
+        }
+        Ok(std::mem::take(&mut self.notifications))
+    }
+
+    /// 送出確認請求並等待回覆
+    ///
+    /// # 回傳值
+    /// `ComplexACK` 的服務資料，`SimpleACK` 時為空
+    async fn confirmed(
+        &mut self,
+        address: &BacnetAddress,
+        service: u8,
+        request: &[u8],
+    ) -> Result<Vec<u8>, ConnectionError> {
+        self.invoke_id = self.invoke_id.wrapping_add(1);
+        let mut apdu = vec![0x00, 0x05, self.invoke_id, service];
+        apdu.extend(request);
+        self.socket
+            .send_to(
+                &frame(0x0a, address.route.as_ref(), true, &apdu),
+                address.socket,
+            )
+            .await?;
+
+        let deadline = Instant::now() + self.timeout;
+        let mut buffer = vec![0; MAX_APDU + 64];
+        loop {
+            let (length, peer) = timeout_at(deadline, self.socket.recv_from(&mut buffer)).await??;
+            if peer != address.socket {
+                self.dispatch(&buffer[..length], peer);
+                continue;
+            }
+            match self.dispatch(&buffer[..length], peer) {
+                Some(Incoming::Reply { invoke_id, result }) if invoke_id == self.invoke_id => {
+                    return result.map_err(|error| error.into_connection_error(service));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// 解析收到的封包，COV 通知會被保留，無法解析的封包回傳 [`None`]
+    fn dispatch(&mut self, datagram: &[u8], peer: SocketAddr) -> Option<Incoming> {
+        let frame = parse_frame(datagram)?;
+        let address = BacnetAddress {
+            socket: frame.origin.unwrap_or(peer),
+            route: frame.source,
+        };
+        match parse_apdu(frame.apdu, address)? {
+            Incoming::Notification(notification) => {
+                self.notifications.push(notification);
+                None
+            }
+            incoming => Some(incoming),
+        }
+    }
+}
+
+/// 收到的封包
+enum Incoming {
+    IAm(IAm),
+    Notification(CovNotification),
+    Reply {
+        invoke_id: u8,
+        result: Result<Vec<u8>, Failure>,
+    },
+}
+
+/// 確認請求失敗的原因
+enum Failure {
+    Device(BacnetError),
+    Segmented,
+    Malformed,
+}
+
+impl Failure {
+    fn into_connection_error(self, service: u8) -> ConnectionError {
+        match self {
+            Self::Device(error) => ConnectionError::custom(error),
+            Self::Segmented => {
+                ConnectionError::Protocol("設備以分段傳送回覆，請減少單次讀取的屬性數量".to_owned())
+            }
+            Self::Malformed => {
+                ConnectionError::Protocol(format!("無法解析服務 0x{service:02x} 的回覆"))
+            }
+        }
+    }
+}
+
+/// 解析後的 BVLC 與 NPDU
+struct Frame<'a> {
+    /// 經由 BBMD 轉送時的原始來源位址
+    origin: Option<SocketAddr>,
+    /// 經由路由器轉送時的來源網路與 MAC 位址
+    source: Option<(u16, Vec<u8>)>,
+    apdu: &'a [u8],
+}
+
+/// 組成 BVLC 與 NPDU
+///
+/// # 參數
+/// - `function`：BVLC 功能，`0x0a` 為單播，`0x0b` 為廣播
+/// - `route`：經由路由器轉送時的目的網路與 MAC 位址
+/// - `expecting_reply`：是否等待回覆
+fn frame(
+    function: u8,
+    route: Option<&(u16, Vec<u8>)>,
+    expecting_reply: bool,
+    apdu: &[u8],
+) -> Vec<u8> {
+    let mut npdu = vec![0x01, if expecting_reply { 0x04 } else { 0x00 }];
+    if let Some((network, mac)) = route {
+        npdu[1] |= 0x20;
+        npdu.extend(network.to_be_bytes());
+        npdu.push(u8::try_from(mac.len()).unwrap_or_default());
+        npdu.extend(mac);
+        npdu.push(0xff);
+    }
+
+    let length = 4 + npdu.len() + apdu.len();
+    let mut frame = vec![0x81, function];
+    frame.extend(u16::try_from(length).unwrap_or(u16::MAX).to_be_bytes());
+    frame.extend(npdu);
+    frame.extend(apdu);
+    frame
+}
+
+/// 解析 BVLC 與 NPDU
+///
+/// # 回傳值
+/// 網路層訊息與無法解析的封包回傳 [`None`]
+fn parse_frame(datagram: &[u8]) -> Option<Frame<'_>> {
+    let [0x81, function, length_high, length_low, npdu @ ..] = datagram else {
+        return None;
+    };
+    if usize::from(u16::from_be_bytes([*length_high, *length_low])) != datagram.len() {
+        return None;
+    }
+    let (origin, npdu) = match function {
+        0x0a | 0x0b => (None, npdu),
+        // Forwarded-NPDU ，前 6 個位元組為原始來源位址
+        0x04 => {
+            let [a, b, c, d, port_high, port_low, npdu @ ..] = npdu else {
+                return None;
+            };
+            let origin = SocketAddrV4::new(
+                Ipv4Addr::new(*a, *b, *c, *d),
+                u16::from_be_bytes([*port_high, *port_low]),
+            );
+            (Some(SocketAddr::V4(origin)), npdu)
+        }
+        _ => return None,
+    };
+
+    let [0x01, control, rest @ ..] = npdu else {
+        return None;
+    };
+    if control & 0x80 != 0 {
+        return None;
+    }
+    let mut rest = rest;
+    if control & 0x20 != 0 {
+        let length = usize::from(*rest.get(2)?);
+        rest = rest.get(3 + length..)?;
+    }
+    let mut source = None;
+    if control & 0x08 != 0 {
+        let network = u16::from_be_bytes([*rest.first()?, *rest.get(1)?]);
+        let length = usize::from(*rest.get(2)?);
+        source = Some((network, rest.get(3..3 + length)?.to_vec()));
+        rest = rest.get(3 + length..)?;
+    }
+    if control & 0x20 != 0 {
+        // 跳數
+        rest = rest.get(1..)?;
+    }
+    Some(Frame {
+        origin,
+        source,
+        apdu: rest,
+    })
+}
+
+/// 解析 APDU
+fn parse_apdu(apdu: &[u8], address: BacnetAddress) -> Option<Incoming> {
+    let [pdu_type, rest @ ..] = apdu else {
+        return None;
+    };
+    match (pdu_type >> 4, rest) {
+        // Unconfirmed-Request
+        (0x1, [0x00, data @ ..]) => parse_i_am(data, address).map(Incoming::IAm),
+        (0x1, [0x02, data @ ..]) => parse_notification(data).map(Incoming::Notification),
+        // SimpleACK
+        (0x2, [invoke_id, _, ..]) => Some(Incoming::Reply {
+            invoke_id: *invoke_id,
+            result: Ok(Vec::new()),
+        }),
+        // ComplexACK
+        (0x3, [invoke_id, _, data @ ..]) => Some(Incoming::Reply {
+            invoke_id: *invoke_id,
+            result: if pdu_type & 0x08 == 0 {
+                Ok(data.to_vec())
+            } else {
+                Err(Failure::Segmented)
+            },
+        }),
+        // Error
+        (0x5, [invoke_id, _, data @ ..]) => Some(Incoming::Reply {
+            invoke_id: *invoke_id,
+            result: Err(Reader::new(data)
+                .error()
+                .map_or(Failure::Malformed, Failure::Device)),
+        }),
+        // Reject
+        (0x6, [invoke_id, reason, ..]) => Some(Incoming::Reply {
+            invoke_id: *invoke_id,
+            result: Err(Failure::Device(BacnetError::Reject { reason: *reason })),
+        }),
+        // Abort
+        (0x7, [invoke_id, reason, ..]) => Some(Incoming::Reply {
+            invoke_id: *invoke_id,
+            result: Err(Failure::Device(BacnetError::Abort { reason: *reason })),
+        }),
+        _ => None,
+    }
+}
+
+/// 解析 `ReadProperty` 回覆（ComplexACK）的服務資料
+fn read_property_ack(data: &[u8]) -> Result<BacnetValue, ConnectionError> {
+    let mut reader = Reader::new(data);
+    reader.context_object(0)?;
+    reader.context_unsigned(1)?;
+    reader.optional_context_unsigned(2)?;
+    reader.values(3)
+}
+
+fn parse_i_am(data: &[u8], address: BacnetAddress) -> Option<IAm> {
+    let mut reader = Reader::new(data);
+    let BacnetValue::ObjectIdentifier(device) = reader.application_value().ok()? else {
+        return None;
+    };
+    let BacnetValue::Unsigned(max_apdu) = reader.application_value().ok()? else {
+        return None;
+    };
+    let BacnetValue::Enumerated(segmentation) = reader.application_value().ok()? else {
+        return None;
+    };
+    let BacnetValue::Unsigned(vendor) = reader.application_value().ok()? else {
+        return None;
+    };
+    Some(IAm {
+        device: device.instance,
+        address,
+        max_apdu,
+        segmentation,
+        vendor,
+    })
+}
+
+fn parse_notification(data: &[u8]) -> Option<CovNotification> {
+    let mut reader = Reader::new(data);
+    if reader.context_unsigned(0).ok()? != u64::from(COV_PROCESS_ID) {
+        return None;
+    }
+    let device = reader.context_object(1).ok()?;
+    let object = reader.context_object(2).ok()?;
+    let time_remaining = Duration::from_secs(reader.context_unsigned(3).ok()?);
+    reader.opening(4).ok()?;
+    let mut values = Vec::new();
+    while !reader.is_closing(4) {
+        let property = u32::try_from(reader.context_unsigned(0).ok()?).ok()?;
+        reader.optional_context_unsigned(1).ok()?;
+        values.push((property, reader.values(2).ok()?));
+        reader.optional_context_unsigned(3).ok()?;
+    }
+    Some(CovNotification {
+        device: device.instance,
+        object,
+        time_remaining,
+        values,
+    })
+}
+
+fn encode_tag(buffer: &mut Vec<u8>, number: u8, context: bool, length: usize) {
+    let class = if context { 0x08 } else { 0x00 };
+    let lvt = match length {
+        0 => 0,
+        1 => 1,
+        2 => 2,
+        3 => 3,
+        4 => 4,
+        _ => 5,
+    };
+    if number <= 14 {
+        buffer.push((number << 4) | class | lvt);
+    } else {
+        buffer.push(0xf0 | class | lvt);
+        buffer.push(number);
+    }
+    if length > 4 {
+        match (u8::try_from(length), u16::try_from(length)) {
+            (Ok(length), _) if length < 254 => buffer.push(length),
+            (_, Ok(length)) => {
+                buffer.push(254);
+                buffer.extend(length.to_be_bytes());
+            }
+            _ => {
+                buffer.push(255);
+                buffer.extend(u32::try_from(length).unwrap_or(u32::MAX).to_be_bytes());
+            }
+        }
+    }
+}
+
+fn encode_context_unsigned(buffer: &mut Vec<u8>, number: u8, value: u64) {
+    let bytes = unsigned_bytes(value);
+    encode_tag(buffer, number, true, bytes.len());
+    buffer.extend(bytes);
+}
+
+fn encode_context_object(buffer: &mut Vec<u8>, number: u8, object: ObjectIdentifier) {
+    encode_tag(buffer, number, true, 4);
+    buffer.extend(object.to_u32().to_be_bytes());
+}
+
+fn encode_opening(buffer: &mut Vec<u8>, number: u8) {
+    buffer.push((number << 4) | 0x0e);
+}
+
+fn encode_closing(buffer: &mut Vec<u8>, number: u8) {
+    buffer.push((number << 4) | 0x0f);
+}
+
+/// 以 `first` 起的三個 context 標籤編碼物件、屬性與陣列索引
+fn encode_reference(buffer: &mut Vec<u8>, reference: &PropertyReference, first: u8) {
+    encode_context_object(buffer, first, reference.object);
+    encode_context_unsigned(buffer, first + 1, u64::from(reference.property));
+    if let Some(index) = reference.array_index {
+        encode_context_unsigned(buffer, first + 2, u64::from(index));
+    }
+}
+
+fn unsigned_bytes(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take_while(|byte| **byte == 0).count().min(7);
+    bytes[skip..].to_vec()
+}
+
+fn signed_bytes(value: i64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes
+        .windows(2)
+        .take_while(|pair| {
+            (pair[0] == 0x00 && pair[1] & 0x80 == 0) || (pair[0] == 0xff && pair[1] & 0x80 != 0)
+        })
+        .count();
+    bytes[skip..].to_vec()
+}
+
+/// 標籤
+#[derive(Debug, Clone, Copy)]
+struct Tag {
+    number: u8,
+    context: bool,
+    kind: TagKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TagKind {
+    /// 資料長度；應用標籤的布林值沒有資料，長度欄位即為數值
+    Length(usize),
+    Opening,
+    Closing,
+}
+
+/// 標籤解析器
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    const fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn malformed() -> ConnectionError {
+        ConnectionError::Protocol("無法解析的 BACnet 資料".to_owned())
+    }
+
+    /// 解析下一個標籤，回傳標籤與標頭長度，不移動讀取位置
+    fn peek(&self) -> Option<(Tag, usize)> {
+        let (&first, rest) = self.data.split_first()?;
+        let context = first & 0x08 != 0;
+        let (number, mut header) = if first >> 4 == 0x0f {
+            (*rest.first()?, 2)
+        } else {
+            (first >> 4, 1)
+        };
+        let kind = match first & 0x07 {
+            6 if context => TagKind::Opening,
+            7 if context => TagKind::Closing,
+            _ if !context && number == 1 => TagKind::Length(0),
+            5 => {
+                let extended = *self.data.get(header)?;
+                header += 1;
+                TagKind::Length(match extended {
+                    254 => {
+                        header += 2;
+                        usize::from(u16::from_be_bytes(
+                            self.data.get(header - 2..header)?.try_into().ok()?,
+                        ))
+                    }
+                    255 => {
+                        header += 4;
+                        usize::try_from(u32::from_be_bytes(
+                            self.data.get(header - 4..header)?.try_into().ok()?,
+                        ))
+                        .ok()?
+                    }
+                    length => usize::from(length),
+                })
+            }
+            length => TagKind::Length(usize::from(length)),
+        };
+        Some((
+            Tag {
+                number,
+                context,
+                kind,
+            },
+            header,
+        ))
+    }
+
+    /// 讀取標籤與其資料
+    fn tag(&mut self) -> Result<(Tag, &'a [u8]), ConnectionError> {
+        let (tag, header) = self.peek().ok_or_else(Self::malformed)?;
+        let length = match tag.kind {
+            TagKind::Length(length) => length,
+            TagKind::Opening | TagKind::Closing => 0,
+        };
+        let data = self
+            .data
+            .get(header..header + length)
+            .ok_or_else(Self::malformed)?;
+        self.data = &self.data[header + length..];
+        Ok((tag, data))
+    }
+
+    fn is_tag(&self, number: u8, kind: TagKind) -> bool {
+        self.peek()
+            .is_some_and(|(tag, _)| tag.context && tag.number == number && tag.kind == kind)
+    }
+
+    fn is_opening(&self, number: u8) -> bool {
+        self.is_tag(number, TagKind::Opening)
+    }
+
+    fn is_closing(&self, number: u8) -> bool {
+        self.is_tag(number, TagKind::Closing)
+    }
+
+    fn opening(&mut self, number: u8) -> Result<(), ConnectionError> {
+        if !self.is_opening(number) {
+            return Err(Self::malformed());
+        }
+        self.tag().map(|_| ())
+    }
+
+    fn closing(&mut self, number: u8) -> Result<(), ConnectionError> {
+        if !self.is_closing(number) {
+            return Err(Self::malformed());
+        }
+        self.tag().map(|_| ())
+    }
+
+    fn context(&mut self, number: u8) -> Result<&'a [u8], ConnectionError> {
+        match self.tag()? {
+            (
+                Tag {
+                    number: found,
+                    context: true,
+                    kind: TagKind::Length(_),
+                },
+                data,
+            ) if found == number => Ok(data),
+            _ => Err(Self::malformed()),
+        }
+    }
+
+    fn context_unsigned(&mut self, number: u8) -> Result<u64, ConnectionError> {
+        decode_unsigned(self.context(number)?).ok_or_else(Self::malformed)
+    }
+
+    fn optional_context_unsigned(&mut self, number: u8) -> Result<Option<u32>, ConnectionError> {
+        match self.peek() {
+            Some((tag, _))
+                if tag.context
+                    && tag.number == number
+                    && matches!(tag.kind, TagKind::Length(_)) =>
+            {
+                u32::try_from(self.context_unsigned(number)?)
+                    .map(Some)
+                    .map_err(|_| Self::malformed())
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn context_object(&mut self, number: u8) -> Result<ObjectIdentifier, ConnectionError> {
+        let data: [u8; 4] = self
+            .context(number)?
+            .try_into()
+            .map_err(|_| Self::malformed())?;
+        Ok(ObjectIdentifier::from_u32(u32::from_be_bytes(data)))
+    }
+
+    /// 讀取以 `number` 開啟與關閉標籤包住的數值
+    fn values(&mut self, number: u8) -> Result<BacnetValue, ConnectionError> {
+        self.opening(number)?;
+        let mut values = Vec::new();
+        while !self.is_closing(number) {
+            values.push(self.application_value()?);
+        }
+        self.closing(number)?;
+        Ok(BacnetValue::collect(values))
+    }
+
+    /// 讀取錯誤類別與錯誤碼
+    fn error(&mut self) -> Result<BacnetError, ConnectionError> {
+        let class = self.application_value()?;
+        let code = self.application_value()?;
+        match (class, code) {
+            (BacnetValue::Enumerated(class), BacnetValue::Enumerated(code)) => {
+                Ok(BacnetError::Error { class, code })
+            }
+            _ => Err(Self::malformed()),
+        }
+    }
+
+    fn application_value(&mut self) -> Result<BacnetValue, ConnectionError> {
+        let boolean = self.data.first().map(|first| first & 0x07 != 0);
+        let (tag, data) = self.tag()?;
+        if tag.context || !matches!(tag.kind, TagKind::Length(_)) {
+            return Err(ConnectionError::Protocol(
+                "不支援的資料型別（結構化資料）".to_owned(),
+            ));
+        }
+        let fixed = |data: &[u8]| -> Result<[u8; 4], ConnectionError> {
+            data.try_into().map_err(|_| Self::malformed())
+        };
+        Ok(match tag.number {
+            0 => BacnetValue::Null,
+            1 => BacnetValue::Boolean(boolean.unwrap_or_default()),
+            2 => BacnetValue::Unsigned(decode_unsigned(data).ok_or_else(Self::malformed)?),
+            3 => BacnetValue::Signed(decode_signed(data).ok_or_else(Self::malformed)?),
+            4 => BacnetValue::Real(f32::from_be_bytes(fixed(data)?)),
+            5 => BacnetValue::Double(f64::from_be_bytes(
+                data.try_into().map_err(|_| Self::malformed())?,
+            )),
+            6 => BacnetValue::OctetString(data.to_vec()),
+            7 => BacnetValue::CharacterString(decode_string(data).ok_or_else(Self::malformed)?),
+            8 => {
+                let (unused, bytes) = data.split_first().ok_or_else(Self::malformed)?;
+                let length = (bytes.len() * 8)
+                    .checked_sub(usize::from(*unused))
+                    .ok_or_else(Self::malformed)?;
+                BacnetValue::BitString(
+                    (0..length)
+                        .map(|index| bytes[index / 8] & (0x80 >> (index % 8)) != 0)
+                        .collect(),
+                )
+            }
+            9 => BacnetValue::Enumerated(
+                decode_unsigned(data)
+                    .and_then(|value| u32::try_from(value).ok())
+                    .ok_or_else(Self::malformed)?,
+            ),
+            10 => BacnetValue::Date(fixed(data)?),
+            11 => BacnetValue::Time(fixed(data)?),
+            12 => BacnetValue::ObjectIdentifier(ObjectIdentifier::from_u32(u32::from_be_bytes(
+                fixed(data)?,
+            ))),
+            number => {
+                return Err(ConnectionError::Protocol(format!(
+                    "不支援的資料型別（應用標籤 {number}）"
+                )));
+            }
+        })
+    }
+}
+
+fn decode_unsigned(data: &[u8]) -> Option<u64> {
+    if data.is_empty() || data.len() > 8 {
+        return None;
+    }
+    Some(
+        data.iter()
+            .fold(0, |value, byte| (value << 8) | u64::from(*byte)),
+    )
+}
+
+fn decode_signed(data: &[u8]) -> Option<i64> {
+    let first = *data.first()?;
+    if data.len() > 8 {
+        return None;
+    }
+    let fill = if first & 0x80 == 0 { 0x00 } else { 0xff };
+    let mut bytes = [fill; 8];
+    bytes[8 - data.len()..].copy_from_slice(data);
+    Some(i64::from_be_bytes(bytes))
+}
+
+/// 解析字串，支援 UTF-8（字元集 0）、UCS-2（字元集 4）與 ISO 8859-1（字元集 5）
+fn decode_string(data: &[u8]) -> Option<String> {
+    let (charset, text) = data.split_first()?;
+    Some(match charset {
+        4 => char::decode_utf16(
+            text.chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]])),
+        )
+        .map(|character| character.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect(),
+        5 => text.iter().copied().map(char::from).collect(),
+        _ => String::from_utf8_lossy(text).into_owned(),
+    })
+}
+
+/// 點位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BacnetPoint {
+    /// 設備編號
+    pub device: u32,
+    /// 屬性
+    pub property: PropertyReference,
+    /// 寫入時使用的資料型別
+    pub data_type: Option<BacnetType>,
+    /// 寫入的優先權
+    pub priority: Option<u8>,
+    /// 是否以 COV 訂閱取代輪詢
+    pub cov: bool,
+}
+
+impl BacnetPoint {
+    /// 由通用點位定義轉換，欄位說明參見 [`crate::bacnet`]
+    ///
+    /// # Errors
+    /// 設備編號、物件、屬性或其他欄位無效時回傳 [`InvalidTarget`]
+    pub fn parse(definition: &TargetDefinition) -> Result<Self, InvalidTarget> {
+        let invalid = |reason: String| InvalidTarget {
+            target: definition.name.clone(),
+            reason,
+        };
+        let extra = |name: &str| definition.extra.get(name).filter(|value| !value.is_null());
+
+        let device = definition
+            .device
+            .as_deref()
+            .and_then(|device| device.trim().parse().ok())
+            .filter(|device| *device <= MAX_DEVICE_INSTANCE)
+            .ok_or_else(|| invalid("設備編號需為 0 至 4194302 的整數".to_owned()))?;
+        let object = ObjectIdentifier::parse(&definition.address)
+            .ok_or_else(|| invalid(format!("無效的物件「{}」", definition.address)))?;
+        let property = match extra("property") {
+            None => PRESENT_VALUE,
+            Some(property) => property
+                .as_u64()
+                .and_then(|property| u32::try_from(property).ok())
+                .or_else(|| {
+                    let name = property
+                        .as_str()?
+                        .trim()
+                        .to_ascii_lowercase()
+                        .replace('_', "-");
+                    PROPERTIES
+                        .iter()
+                        .find(|(_, known)| *known == name)
+                        .map(|(number, _)| *number)
+                })
+                .ok_or_else(|| invalid(format!("無效的 property ：{property}")))?,
+        };
+        let array_index = extra("array_index")
+            .map(|index| {
+                index
+                    .as_u64()
+                    .and_then(|index| u32::try_from(index).ok())
+                    .ok_or_else(|| invalid(format!("無效的 array_index ：{index}")))
+            })
+            .transpose()?;
+        let data_type = match definition.data_type.as_deref() {
+            Some(data_type) => Some(
+                serde_json::from_value(Value::String(data_type.to_owned()))
+                    .map_err(|_| invalid(format!("不支援的資料型別「{data_type}」")))?,
+            ),
+            None if property == PRESENT_VALUE && array_index.is_none() => {
+                BacnetType::of_present_value(object.object_type)
+            }
+            None => None,
+        };
+        let priority = extra("priority")
+            .map(|priority| {
+                priority
+                    .as_u64()
+                    .filter(|priority| (1..=16).contains(priority))
+                    .and_then(|priority| u8::try_from(priority).ok())
+                    .ok_or_else(|| invalid(format!("priority 需為 1 至 16 ：{priority}")))
+            })
+            .transpose()?;
+        let cov = extra("cov")
+            .map(|cov| {
+                cov.as_bool()
+                    .ok_or_else(|| invalid(format!("無效的 cov ：{cov}")))
+            })
+            .transpose()?
+            .unwrap_or(true);
+
+        Ok(Self {
+            device,
+            property: PropertyReference {
+                object,
+                property,
+                array_index,
+            },
+            data_type,
+            priority,
+            cov,
+        })
+    }
+}
+
+/// 同一設備以 `ReadPropertyMultiple` 合併讀取的屬性
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadBatch {
+    /// 設備編號
+    pub device: u32,
+    /// 屬性，依物件排序
+    pub properties: Arc<[PropertyReference]>,
+}
+
+impl ReadBatch {
+    fn key(&self) -> String {
+        format!("{}:{}", self.device, self.properties[0])
+    }
+}
+
+/// 點位
+#[derive(Debug, Clone)]
+pub struct BacnetTarget(pub TargetDefinition);
+
+impl Target for BacnetTarget {}
+
+/// 讀取請求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BacnetRequest {
+    /// 點位
+    pub point: BacnetPoint,
+    /// 包含點位的合併讀取，單獨讀取的點位為 [`None`]
+    pub batch: Option<ReadBatch>,
+    /// 是否以 COV 訂閱取得數值
+    pub cov: bool,
+}
+
+impl DeviceStateRequest for BacnetRequest {}
+
+/// 讀取回覆
+#[derive(Debug, Clone, PartialEq)]
+pub struct BacnetResponse {
+    /// 屬性數值
+    pub value: BacnetValue,
+}
+
+impl DeviceStateResponse for BacnetResponse {
+    fn to_value(&self) -> Result<Cow<'_, Value>, ConversionError> {
+        Ok(Cow::Owned(self.value.to_json()))
+    }
+}
+
+/// 寫入請求
+#[derive(Debug, Clone, PartialEq)]
+pub struct BacnetWrite {
+    /// 設備編號
+    pub device: u32,
+    /// 屬性
+    pub property: PropertyReference,
+    /// 寫入的數值
+    pub value: BacnetValue,
+    /// 優先權
+    pub priority: Option<u8>,
+    /// 包含點位的合併讀取，寫入後會清除該次讀取的結果
+    pub batch: Option<ReadBatch>,
+}
+
+impl DeviceStateWrite for BacnetWrite {}
+
+/// COV 訂閱的物件
+type CovKey = (u32, ObjectIdentifier);
+
+/// BACnet/IP 設備連線
+///
+/// 合併讀取的結果會保留更新間隔的一半，期間內的其他點位直接使用該結果，並跳過等待間隔
+pub struct BacnetConnection {
+    config: BacnetConfig,
+    client: BacnetClient,
+    devices: BTreeMap<u32, BacnetAddress>,
+    reads: ResponseCache<Arc<[Result<BacnetValue, BacnetError>]>>,
+    batches: Vec<ReadBatch>,
+    /// 已訂閱的物件與重新訂閱的時間
+    subscriptions: HashMap<CovKey, Instant>,
+    /// COV 通知的最新 `Present_Value`
+    cov_values: HashMap<CovKey, BacnetValue>,
+    /// 不支援 COV 的物件
+    cov_unsupported: HashSet<CovKey>,
+    rejected: Vec<InvalidTarget>,
+}
+
+impl BacnetConnection {
+    /// 初始化點位時被略過的點位
+    #[must_use]
+    pub fn rejected_targets(&self) -> &[InvalidTarget] {
+        &self.rejected
+    }
+
+    /// 探索到的設備與位址
+    #[must_use]
+    pub const fn devices(&self) -> &BTreeMap<u32, BacnetAddress> {
+        &self.devices
+    }
+
+    /// 設備位址，重新探索後可能改變，因此不存放在請求中
+    fn address(&self, device: u32) -> Result<BacnetAddress, ConnectionError> {
+        self.devices
+            .get(&device)
+            .cloned()
+            .ok_or_else(|| ConnectionError::InvalidConfig(format!("找不到設備 {device}")))
+    }
+
+    async fn open(config: &BacnetConfig) -> Result<BacnetClient, ConnectionError> {
+        let socket = UdpSocket::bind(config.bind).await?;
+        socket.set_broadcast(true)?;
+        Ok(BacnetClient::new(socket, config.timeout))
+    }
+
+    /// 廣播 Who-Is ，以探索結果與固定位址更新設備清單
+    async fn discover(&mut self) -> Result<DiscoveryReport, ConnectionError> {
+        let started = Instant::now();
+        let found = self
+            .client
+            .who_is(self.config.broadcast, None, self.config.discovery_timeout)
+            .await?;
+
+        self.devices = self
+            .config
+            .devices
+            .iter()
+            .map(|(device, socket)| (*device, BacnetAddress::from(*socket)))
+            .collect();
+        let addresses = found
+            .into_iter()
+            .map(|i_am| {
+                let response = serde_json::json!({
+                    "address": i_am.address.to_string(),
+                    "max_apdu": i_am.max_apdu,
+                    "segmentation": i_am.segmentation,
+                    "vendor": i_am.vendor,
+                });
+                self.devices.insert(i_am.device, i_am.address);
+                ScannedAddress {
+                    address_number: i_am.device.to_string(),
+                    outcome: ProbeOutcome::Responded,
+                    elapsed: started.elapsed(),
+                    response: Some(response),
+                }
+            })
+            .collect();
+
+        Ok(DiscoveryReport {
+            serial: None,
+            bus_scan: Some(BusScanReport { addresses }),
+        })
+    }
+
+    fn set_ttl(&self) {
+        let policy = CachePolicy {
+            ttl: self.config.update_interval / 2,
+            stale_while_revalidate: Duration::ZERO,
+        };
+        for batch in &self.batches {
+            self.reads.enable(batch.key(), policy);
+        }
+    }
+
+    /// 清除訂閱狀態，下一次讀取時重新訂閱
+    fn reset_subscriptions(&mut self) {
+        self.subscriptions.clear();
+        self.cov_values.clear();
+        self.cov_unsupported.clear();
+    }
+
+    /// 套用收到的 COV 通知
+    fn apply_notifications(&mut self) -> Result<(), ConnectionError> {
+        for notification in self.client.notifications()? {
+            let key = (notification.device, notification.object);
+            if !self.subscriptions.contains_key(&key) {
+                continue;
+            }
+            if let Some((_, value)) = notification
+                .values
+                .into_iter()
+                .find(|(property, _)| *property == PRESENT_VALUE)
+            {
+                self.cov_values.insert(key, value);
+            }
+        }
+        Ok(())
+    }
+
+    /// 以 COV 訂閱取得 `Present_Value` ，訂閱過期或尚未收到數值時先訂閱並讀取一次
+    ///
+    /// # 回傳值
+    /// 設備不支援 COV 時回傳 [`None`] ，改以輪詢讀取
+    async fn subscribed(
+        &mut self,
+        request: &BacnetRequest,
+    ) -> Result<Option<BacnetValue>, ConnectionError> {
+        let key = (request.point.device, request.point.property.object);
+        if self.cov_unsupported.contains(&key) {
+            return Ok(None);
+        }
+        self.apply_notifications()?;
+        let address = self.address(key.0)?;
+
+        let now = Instant::now();
+        let lifetime = self.config.cov_lifetime.unwrap_or_default();
+        if self
+            .subscriptions
+            .get(&key)
+            .is_none_or(|renew| *renew <= now)
+        {
+            let result = self
+                .client
+                .subscribe_cov(&address, COV_PROCESS_ID, key.1, Some(lifetime))
+                .await;
+            match result {
+                Ok(()) => {
+                    self.subscriptions.insert(key, now + lifetime / 2);
+                }
+                Err(error) if error.downcast_ref::<BacnetError>().is_some() => {
+                    self.cov_unsupported.insert(key);
+                    self.subscriptions.remove(&key);
+                    return Ok(None);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+
+        if let Some(value) = self.cov_values.get(&key) {
+            return Ok(Some(value.clone()));
+        }
+        let value = self
+            .client
+            .read_property(&address, &request.point.property)
+            .await?;
+        self.cov_values.insert(key, value.clone());
+        Ok(Some(value))
+    }
+}
+
+impl Connection for BacnetConnection {
+    const NAMES: &[&str] = &["Bacnet", "BacnetIp"];
+    type Config = BacnetConfig;
+    type Target = BacnetTarget;
+    type Request = BacnetRequest;
+    type Response = BacnetResponse;
+    type Result = ();
+
+    async fn init(config: &BacnetConfig) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+        let mut connection = Self {
+            config: config.clone(),
+            client: Self::open(config).await?,
+            devices: BTreeMap::new(),
+            reads: ResponseCache::new(),
+            batches: Vec::new(),
+            subscriptions: HashMap::default(),
+            cov_values: HashMap::default(),
+            cov_unsupported: HashSet::default(),
+            rejected: Vec::new(),
+        };
+        let report = connection.discover().await?;
+
+        let artifact = ConnectionArtifact::new(
+            connection,
+            ConnectionStats::new(config.bind.to_string(), None),
+        )
+        .update_every(config.update_interval)
+        .timeout_after(config.timeout)
+        .with_discovery(report);
+
+        Ok(match config.max_retry_count {
+            Some(count) => artifact.retry_up_to(count),
+            None => artifact,
+        })
+    }
+
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<BacnetTarget>,
+    ) -> ConnectionTargets<BacnetRequest, ()> {
+        let mut parsed = Vec::with_capacity(targets.len());
+        for BacnetTarget(definition) in targets {
+            let point = BacnetPoint::parse(&definition).and_then(|point| {
+                if self.devices.contains_key(&point.device) {
+                    Ok(point)
+                } else {
+                    Err(InvalidTarget {
+                        target: definition.name.clone(),
+                        reason: format!(
+                            "找不到設備 {}，請確認設備有回覆 Who-Is ，或於 devices 設定設備位址",
+                            point.device
+                        ),
+                    })
+                }
+            });
+            match point {
+                Ok(point) => {
+                    let cov = definition.auto_refresh
+                        && point.cov
+                        && self.config.cov_lifetime.is_some()
+                        && point.property.property == PRESENT_VALUE
+                        && point.property.array_index.is_none();
+                    parsed.push((definition, point, cov));
+                }
+                Err(error) => self.rejected.push(error),
+            }
+        }
+
+        // 依設備合併自動更新、未使用 COV 的點位
+        let mut polled: BTreeMap<u32, Vec<PropertyReference>> = BTreeMap::new();
+        for (definition, point, cov) in &parsed {
+            if definition.auto_refresh && !cov {
+                polled.entry(point.device).or_default().push(point.property);
+            }
+        }
+        let chunk = usize::from(self.config.max_properties_per_request.max(1));
+        self.batches = polled
+            .into_iter()
+            .flat_map(|(device, mut properties)| {
+                properties.sort_unstable();
+                properties.dedup();
+                properties
+                    .chunks(chunk)
+                    .filter(|properties| properties.len() > 1)
+                    .map(|properties| ReadBatch {
+                        device,
+                        properties: properties.into(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        self.set_ttl();
+
+        ConnectionTargets(
+            parsed
+                .into_iter()
+                .map(|(definition, point, cov)| InitedTarget {
+                    name: definition.name,
+                    request: BacnetRequest {
+                        point,
+                        batch: self
+                            .batches
+                            .iter()
+                            .find(|batch| {
+                                definition.auto_refresh
+                                    && !cov
+                                    && batch.device == point.device
+                                    && batch.properties.contains(&point.property)
+                            })
+                            .cloned(),
+                        cov,
+                    },
+                    result: (),
+                    default_status: definition.default_status,
+                    auto_refresh: definition.auto_refresh,
+                    refresh_interval: None,
+                    keep_raw_frames: None,
+                    group: None,
+                    safe_state: None,
+                    array: None,
+                    change: None,
+                    statistics: Some(connection_statistics.insert_target(definition.device)),
+                })
+                .collect(),
+        )
+    }
+
+    async fn request_process(
+        &mut self,
+        request: BacnetRequest,
+    ) -> Result<(BacnetResponse, bool), ConnectionError> {
+        if request.cov
+            && let Some(value) = self.subscribed(&request).await?
+        {
+            return Ok((BacnetResponse { value }, true));
+        }
+
+        let address = self.address(request.point.device)?;
+        let Some(batch) = &request.batch else {
+            let value = self
+                .client
+                .read_property(&address, &request.point.property)
+                .await?;
+            return Ok((BacnetResponse { value }, true));
+        };
+        let position = batch
+            .properties
+            .iter()
+            .position(|property| *property == request.point.property)
+            .ok_or_else(|| ConnectionError::Protocol("點位不在合併讀取中".to_owned()))?;
+
+        let (results, wait) = match self.reads.lookup(&batch.key(), None) {
+            CacheLookup::Fresh(results) => (results, false),
+            CacheLookup::Stale { .. } | CacheLookup::Miss => {
+                let results: Arc<[_]> = self
+                    .client
+                    .read_property_multiple(&address, &batch.properties)
+                    .await?
+                    .into();
+                self.reads.store(&batch.key(), results.clone());
+                (results, true)
+            }
+        };
+        let value = results[position].clone().map_err(ConnectionError::custom)?;
+        Ok((BacnetResponse { value }, wait))
+    }
+
+    fn write_preprocess(
+        &self,
+        request: BacnetRequest,
+        value: Value,
+        _: &RequestContext,
+    ) -> Result<Box<dyn DeviceStateWrite>, ConnectionError> {
+        let converted = BacnetValue::from_json(&value, request.point.data_type)
+            .ok_or_else(|| ConnectionError::InvalidConfig(format!("無效的設定值：{value}")))?;
+
+        Ok(Box::new(BacnetWrite {
+            device: request.point.device,
+            property: request.point.property,
+            value: converted,
+            priority: request.point.priority,
+            batch: request.batch,
+        }))
+    }
+
+    async fn write_process(
+        &mut self,
+        write: Box<dyn DeviceStateWrite>,
+    ) -> Result<Option<BacnetResponse>, ConnectionError> {
+        let write = write
+            .downcast::<BacnetWrite>()
+            .map_err(|_| ConnectionError::Protocol("非預期的寫入請求".to_owned()))?;
+        if let Some(batch) = &write.batch {
+            self.reads.invalidate(&batch.key());
+        }
+        self.cov_values
+            .remove(&(write.device, write.property.object));
+
+        let address = self.address(write.device)?;
+        self.client
+            .write_property(&address, &write.property, &write.value, write.priority)
+            .await?;
+        Ok(None)
+    }
+
+    async fn disconnect(&mut self) -> Result<(), ConnectionError> {
+        // 盡力取消訂閱，設備沒有回應時訂閱會在有效期限後失效
+        let subscriptions: Vec<CovKey> = self.subscriptions.keys().copied().collect();
+        for (device, object) in subscriptions {
+            if let Some(address) = self.devices.get(&device).cloned() {
+                let _ = self
+                    .client
+                    .subscribe_cov(&address, COV_PROCESS_ID, object, None)
+                    .await;
+            }
+        }
+        self.reset_subscriptions();
+        Ok(())
+    }
+
+    async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+        self.reset_subscriptions();
+        self.discover().await.map(|_| ())
+    }
+
+    async fn update_config(&mut self, new_config: &BacnetConfig) -> Result<(), ConnectionError> {
+        if new_config.bind == self.config.bind {
+            self.client.timeout = new_config.timeout;
+        } else {
+            self.client = Self::open(new_config).await?;
+        }
+        self.config = new_config.clone();
+        self.set_ttl();
+        self.reconnect().await
+    }
+}
+
+/// 以記錄下來的 BACnet/IP 封包解碼點位
+///
+/// `frame` 為完整的 UDP 封包（包含 BVLC 與 NPDU），可以是 `ReadProperty` 的回覆、錯誤回覆或 COV 通知（取 `Present_Value`），
+/// 點位由封包內容決定，`target` 不會被使用
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::{bacnet::BacnetConnection, fixture::Fixture};
+///
+/// let fixture = Fixture::from_json(r#"{
+///     "connection": "Bacnet",
+///     "cases": [
+///         { "name": "ReadProperty 回覆", "target": "ai:3", "frame": "810a0017 0100 30010c 0c00000003 1955 3e 4441ac0000 3f", "expected": 21.5 },
+///         { "name": "錯誤回覆", "target": "ai:3", "frame": "810a000d 0100 50010c 9101 911f" },
+///         { "name": "長度不符", "target": "ai:3", "frame": "810a0018 0100 30010c 0c00000003 1955 3e 4441ac0000 3f" }
+///     ]
+/// }"#).unwrap();
+///
+/// let report = fixture.run::<BacnetConnection>();
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+impl FrameDecoder for BacnetConnection {
+    fn decode_frame(_target: &str, frame: &[u8]) -> Result<BacnetResponse, Box<dyn Error>> {
+        let malformed = || ConnectionError::Protocol("無法解析的 BACnet/IP 封包".to_owned());
+        let apdu = parse_frame(frame).ok_or_else(malformed)?.apdu;
+        let address = BacnetAddress::from(SocketAddr::from((Ipv4Addr::UNSPECIFIED, DEFAULT_PORT)));
+        let value = match parse_apdu(apdu, address).ok_or_else(malformed)? {
+            Incoming::Reply {
+                result: Ok(data), ..
+            } => read_property_ack(&data)?,
+            Incoming::Reply {
+                result: Err(failure),
+                ..
+            } => return Err(failure.into_connection_error(0x0c).into()),
+            Incoming::Notification(notification) => notification
+                .values
+                .into_iter()
+                .find_map(|(property, value)| (property == PRESENT_VALUE).then_some(value))
+                .ok_or_else(malformed)?,
+            Incoming::IAm(_) => return Err(malformed().into()),
+        };
+        Ok(BacnetResponse { value })
+    }
+}
@@ -0,0 +1,727 @@
+//! 同步連線
+//!
+//! 部分閘道器將本 crate 嵌入同步的程式中，既有的設備驅動（如廠商提供的 SDK）只有阻塞的 API 。
+//! 實作者可以改為實作 [`BlockingConnection`] ，再將 [`AsyncBridge`] 作為連線定義交給主程式：
+//! [`AsyncBridge`] 實作了 [`Connection`] ，並在專屬的執行緒池 [`BlockingPool`] 上調用阻塞的 method ，不會佔用主程式的 executor
+//!
+//! 使用時請注意：
+//!
+//! - 主程式因逾時放棄等待時，執行緒池上的工作仍會執行到結束，下一個請求會等待該工作完成後才開始
+//! - [`Connection::init_targets()`] 等非 async 的 method 在呼叫端的執行緒上直接調用，若此時有執行中的工作，會阻塞到該工作完成
+//! - 錯誤需要傳回主程式的執行緒，[`ConnectionError::Custom`] 會轉換為僅保留錯誤訊息的 [`BlockingError::Custom`]
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{
+//!     Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+//!     ConnectionTargets, DeviceStateRequest, DeviceStateResponse, InitedTarget, Target,
+//!     blocking::{AsyncBridge, BlockingConnection},
+//!     value::ConversionError,
+//! };
+//! use serde_json::Value;
+//! use std::borrow::Cow;
+//!
+//! #[derive(Debug, Clone)]
+//! struct CounterConfig;
+//! impl ConnectionConfig for CounterConfig {}
+//!
+//! #[derive(Debug, Clone)]
+//! struct CounterTarget(String);
+//! impl Target for CounterTarget {}
+//!
+//! #[derive(Debug, Clone)]
+//! struct CounterRequest;
+//! impl DeviceStateRequest for CounterRequest {}
+//!
+//! #[derive(Debug, Clone)]
+//! struct CounterResponse(Value);
+//! impl DeviceStateResponse for CounterResponse {
+//!     fn to_value(&self) -> Result<Cow<'_, Value>, ConversionError> {
+//!         Ok(Cow::Borrowed(&self.0))
+//!     }
+//! }
+//!
+//! /// 以阻塞的 API 讀取的計數器
+//! struct Counter(u64);
+//!
+//! impl BlockingConnection for Counter {
+//!     const NAMES: &[&str] = &["Counter"];
+//!     type Config = CounterConfig;
+//!     type Target = CounterTarget;
+//!     type Request = CounterRequest;
+//!     type Response = CounterResponse;
+//!     type Result = ();
+//!
+//!     fn init(_: &CounterConfig) -> Result<ConnectionArtifact<AsyncBridge<Self>>, ConnectionError> {
+//!         Ok(ConnectionArtifact::new(AsyncBridge::new(Self(0)), ConnectionStats::new("counter", None)))
+//!     }
+//!
+//!     fn init_targets(
+//!         &mut self,
+//!         _: &mut ConnectionStats,
+//!         targets: Vec<CounterTarget>,
+//!     ) -> ConnectionTargets<CounterRequest, ()> {
+//!         ConnectionTargets(
+//!             targets
+//!                 .into_iter()
+//!                 .map(|CounterTarget(name)| InitedTarget {
+//!                     name,
+//!                     request: CounterRequest,
+//!                     result: (),
+//!                     default_status: None,
+//!                     auto_refresh: true,
+//!                     refresh_interval: None,
+//!                     keep_raw_frames: None,
+//!                     group: None,
+//!                     safe_state: None,
+//!                     array: None,
+//!                     change: None,
+//!                     statistics: None,
+//!                 })
+//!                 .collect(),
+//!         )
+//!     }
+//!
+//!     fn request_process(&mut self, _: CounterRequest) -> Result<(CounterResponse, bool), ConnectionError> {
+//!         // 阻塞的讀取
+//!         std::thread::sleep(std::time::Duration::from_millis(1));
+//!         self.0 += 1;
+//!         Ok((CounterResponse(self.0.into()), true))
+//!     }
+//!
+//!     fn reconnect(&mut self) -> Result<(), ConnectionError> {
+//!         Err(ConnectionError::Protocol("設備離線".to_owned()))
+//!     }
+//!
+//!     fn update_config(&mut self, _: &CounterConfig) -> Result<(), ConnectionError> {
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let mut artifact = AsyncBridge::<Counter>::init(&CounterConfig).await.unwrap();
+//! let targets = artifact
+//!     .artifact
+//!     .init_targets(&mut artifact.statistics, vec![CounterTarget("次數".to_owned())])
+//!     .0;
+//!
+//! let (response, _) = artifact.artifact.request_process(CounterRequest).await.unwrap();
+//! assert_eq!(response.0, 1);
+//! let (response, _) = artifact.artifact.request_process(targets[0].request.clone()).await.unwrap();
+//! assert_eq!(response.0, 2);
+//!
+//! let error = artifact.artifact.reconnect().await.unwrap_err();
+//! assert!(matches!(error, ConnectionError::Protocol(_)));
+//! # }
+//! ```
+
+use std::{
+    error::Error,
+    fmt::Display,
+    io,
+    panic::{AssertUnwindSafe, catch_unwind},
+    sync::{
+        Arc, LazyLock, Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use crate::{
+    Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+    ConnectionTargets, DeviceStateRequest, DeviceStateResponse, DeviceStateWrite, RequestContext,
+    Target, command, diagnostics, session,
+};
+
+/// 共用執行緒池的名稱
+pub const DEFAULT_POOL_NAME: &str = "blocking-connection";
+
+/// 同步的設備連線定義
+///
+/// 與 [`Connection`] 相同，但所有 method 都是同步的，由 [`AsyncBridge`] 在 [`BlockingPool`] 的執行緒上調用，
+/// 各 method 的說明參見 [`Connection`] 中的同名 method
+#[expect(unused_variables)]
+pub trait BlockingConnection: Sized + Send + 'static {
+    /// 設備型態名稱列表，參見 [`Connection::NAMES`]
+    const NAMES: &[&str];
+
+    /// 定義連線參數的型別，[`AsyncBridge`] 需要將參數複製到執行緒池上，因此需要實作 [`Clone`]
+    type Config: ConnectionConfig + Clone;
+
+    /// 定義點位的型別，參見 [`Connection::Target`]
+    type Target: Target;
+
+    /// 設備狀態請求型別，參見 [`Connection::Request`]
+    type Request: DeviceStateRequest;
+
+    /// 設備狀態回覆型別，參見 [`Connection::Response`]
+    type Response: DeviceStateResponse;
+
+    /// 定義將狀態回覆給外部服務的型別，參見 [`Connection::Result`]
+    type Result;
+
+    /// 初始化設備連線，參見 [`Connection::init()`]
+    ///
+    /// 回傳的連線產品需以 [`AsyncBridge::new()`] 或 [`AsyncBridge::with_pool()`] 包裝
+    ///
+    /// # Errors
+    /// 無法建立連線時回傳 [`ConnectionError`]
+    fn init(
+        config: &Self::Config,
+    ) -> Result<ConnectionArtifact<AsyncBridge<Self>>, ConnectionError>;
+
+    /// 初始化點位，參見 [`Connection::init_targets()`]
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<Self::Target>,
+    ) -> ConnectionTargets<Self::Request, Self::Result>;
+
+    /// 請求前處理，參見 [`Connection::preprocess()`]
+    ///
+    /// # Errors
+    /// 請求無法處理時回傳 [`ConnectionError`]
+    fn preprocess(
+        &self,
+        request: Self::Request,
+        new_status: Option<Value>,
+    ) -> Result<Self::Request, ConnectionError> {
+        Ok(request)
+    }
+
+    /// 附帶請求脈絡的請求前處理，參見 [`Connection::preprocess_with_context()`]
+    ///
+    /// # Errors
+    /// 請求無法處理時回傳 [`ConnectionError`]
+    fn preprocess_with_context(
+        &self,
+        request: Self::Request,
+        new_status: Option<Value>,
+        context: &RequestContext,
+    ) -> Result<Self::Request, ConnectionError> {
+        self.preprocess(request, new_status)
+    }
+
+    /// 處理請求，參見 [`Connection::request_process()`]
+    ///
+    /// # Errors
+    /// 請求失敗時回傳 [`ConnectionError`]
+    fn request_process(
+        &mut self,
+        request: Self::Request,
+    ) -> Result<(Self::Response, bool), ConnectionError>;
+
+    /// 回覆後處理，參見 [`Connection::postprocess()`]
+    ///
+    /// # Errors
+    /// 回覆無法處理時回傳 [`ConnectionError`]
+    fn postprocess(
+        &self,
+        request: Self::Request,
+        response: Self::Response,
+    ) -> Result<Self::Response, ConnectionError> {
+        Ok(response)
+    }
+
+    /// 寫入前處理，參見 [`Connection::write_preprocess()`]
+    ///
+    /// # Errors
+    /// 不支援寫入或設定值無效時回傳 [`ConnectionError`]
+    fn write_preprocess(
+        &self,
+        request: Self::Request,
+        value: Value,
+        context: &RequestContext,
+    ) -> Result<Box<dyn DeviceStateWrite>, ConnectionError> {
+        Err(ConnectionError::custom(command::UnsupportedWrite))
+    }
+
+    /// 處理寫入，參見 [`Connection::write_process()`]
+    ///
+    /// # Errors
+    /// 寫入失敗時回傳 [`ConnectionError`]
+    fn write_process(
+        &mut self,
+        write: Box<dyn DeviceStateWrite>,
+    ) -> Result<Option<Self::Response>, ConnectionError> {
+        Err(ConnectionError::custom(command::UnsupportedWrite))
+    }
+
+    /// 寫入後處理，參見 [`Connection::write_postprocess()`]
+    ///
+    /// # Errors
+    /// 回覆無法處理時回傳 [`ConnectionError`]
+    fn write_postprocess(
+        &self,
+        write: &dyn DeviceStateWrite,
+        response: Option<Self::Response>,
+    ) -> Result<Option<Self::Response>, ConnectionError> {
+        Ok(response)
+    }
+
+    /// 保持連線，參見 [`Connection::keepalive()`]
+    ///
+    /// # Errors
+    /// 連線已中斷時回傳 [`ConnectionError`]
+    fn keepalive(&mut self) -> Result<(), ConnectionError> {
+        Ok(())
+    }
+
+    /// 執行診斷指令，參見 [`Connection::diagnostics()`]
+    ///
+    /// # Errors
+    /// 不支援該指令或執行失敗時回傳 [`ConnectionError`]
+    fn diagnostics(
+        &mut self,
+        command: diagnostics::DiagnosticsCommand,
+    ) -> Result<Value, ConnectionError> {
+        Err(ConnectionError::custom(
+            diagnostics::UnsupportedDiagnostics(command),
+        ))
+    }
+
+    /// 中斷連線，參見 [`Connection::disconnect()`]
+    ///
+    /// # Errors
+    /// 無法正常中斷時回傳 [`ConnectionError`]
+    fn disconnect(&mut self) -> Result<(), ConnectionError> {
+        Ok(())
+    }
+
+    /// 重新連線，參見 [`Connection::reconnect()`]
+    ///
+    /// # Errors
+    /// 無法重新連線時回傳 [`ConnectionError`]
+    fn reconnect(&mut self) -> Result<(), ConnectionError>;
+
+    /// 工作階段狀態，參見 [`Connection::session_state()`]
+    fn session_state(&self) -> Option<Value> {
+        None
+    }
+
+    /// 依提示重新連線，參見 [`Connection::reconnect_with()`]
+    ///
+    /// # Errors
+    /// 無法重新連線時回傳 [`ConnectionError`]
+    fn reconnect_with(
+        &mut self,
+        hint: session::ReconnectHint,
+    ) -> Result<session::ReconnectOutcome, ConnectionError> {
+        self.reconnect().map(|()| session::ReconnectOutcome::Cold)
+    }
+
+    /// 更新連線參數，參見 [`Connection::update_config()`]
+    ///
+    /// # Errors
+    /// 無法套用新參數時回傳 [`ConnectionError`]
+    fn update_config(&mut self, new_config: &Self::Config) -> Result<(), ConnectionError>;
+}
+
+/// 同步連線在執行緒池上的錯誤
+#[derive(Debug)]
+pub enum BlockingError {
+    /// 無法建立執行緒
+    Spawn(io::Error),
+    /// 工作 panic
+    Panicked,
+    /// 連線回傳的 [`ConnectionError::Custom`] ，僅保留錯誤訊息
+    Custom(String),
+}
+
+impl Display for BlockingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Spawn(error) => write!(f, "無法建立執行緒：{error}"),
+            Self::Panicked => f.write_str("同步連線的工作 panic"),
+            Self::Custom(message) => f.write_str(message),
+        }
+    }
+}
+
+impl Error for BlockingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Spawn(error) => Some(error),
+            Self::Panicked | Self::Custom(_) => None,
+        }
+    }
+}
+
+impl From<BlockingError> for ConnectionError {
+    fn from(error: BlockingError) -> Self {
+        match error {
+            BlockingError::Spawn(error) => Self::Io(error),
+            error => Self::custom(error),
+        }
+    }
+}
+
+/// 可傳送至其他執行緒的 [`ConnectionError`]
+enum SendableError {
+    Timeout,
+    Io(io::Error),
+    Protocol(String),
+    InvalidConfig(String),
+    Fatal(String),
+    Custom(String),
+}
+
+impl From<ConnectionError> for SendableError {
+    fn from(error: ConnectionError) -> Self {
+        match error {
+            ConnectionError::Timeout => Self::Timeout,
+            ConnectionError::Io(error) => Self::Io(error),
+            ConnectionError::Protocol(message) => Self::Protocol(message),
+            ConnectionError::InvalidConfig(message) => Self::InvalidConfig(message),
+            ConnectionError::Fatal(message) => Self::Fatal(message),
+            ConnectionError::Custom(error) => Self::Custom(error.to_string()),
+        }
+    }
+}
+
+impl From<SendableError> for ConnectionError {
+    fn from(error: SendableError) -> Self {
+        match error {
+            SendableError::Timeout => Self::Timeout,
+            SendableError::Io(error) => Self::Io(error),
+            SendableError::Protocol(message) => Self::Protocol(message),
+            SendableError::InvalidConfig(message) => Self::InvalidConfig(message),
+            SendableError::Fatal(message) => Self::Fatal(message),
+            SendableError::Custom(message) => Self::custom(BlockingError::Custom(message)),
+        }
+    }
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+static DEFAULT_POOL: LazyLock<BlockingPool> = LazyLock::new(|| {
+    BlockingPool::new(
+        DEFAULT_POOL_NAME,
+        thread::available_parallelism().map_or(4, usize::from),
+    )
+});
+
+/// 執行阻塞工作的執行緒池
+///
+/// 執行緒在需要時才建立，數量不超過建立時指定的上限；本 struct 與其複本都被 drop 後，各執行緒會在執行中的工作完成後結束
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會使用同一組執行緒
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::blocking::BlockingPool;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let pool = BlockingPool::new("meter", 2);
+/// let name = pool.run(|| std::thread::current().name().map(str::to_owned)).await.unwrap();
+/// assert_eq!(name.as_deref(), Some("meter-0"));
+/// assert!(pool.run(|| panic!()).await.is_err());
+/// assert_eq!(pool.run(|| 1 + 1).await.unwrap(), 2);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BlockingPool {
+    inner: Arc<PoolInner>,
+}
+
+#[derive(Debug)]
+struct PoolInner {
+    name: String,
+    max_threads: usize,
+    sender: mpsc::Sender<Job>,
+    jobs: Arc<Mutex<mpsc::Receiver<Job>>>,
+    threads: AtomicUsize,
+    idle: Arc<AtomicUsize>,
+}
+
+impl BlockingPool {
+    /// 建立執行緒池
+    ///
+    /// # 參數
+    /// - `name`：執行緒名稱的前綴，執行緒依建立順序命名為 `{name}-0` 、`{name}-1` …
+    /// - `max_threads`：執行緒數量上限，至少為 1
+    #[must_use]
+    pub fn new(name: impl Into<String>, max_threads: usize) -> Self {
+        let (sender, jobs) = mpsc::channel();
+        Self {
+            inner: Arc::new(PoolInner {
+                name: name.into(),
+                max_threads: max_threads.max(1),
+                sender,
+                jobs: Arc::new(Mutex::new(jobs)),
+                threads: AtomicUsize::new(0),
+                idle: Arc::new(AtomicUsize::new(0)),
+            }),
+        }
+    }
+
+    /// 共用的執行緒池，名稱為 [`DEFAULT_POOL_NAME`] ，執行緒數量上限為 CPU 核心數
+    #[must_use]
+    pub fn shared() -> Self {
+        DEFAULT_POOL.clone()
+    }
+
+    /// 執行緒池名稱
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    /// 已建立的執行緒數量
+    #[must_use]
+    pub fn threads(&self) -> usize {
+        self.inner.threads.load(Ordering::Acquire)
+    }
+
+    /// 在執行緒池上執行阻塞的工作，並等待其完成
+    ///
+    /// 回傳的 future 被 drop 時，工作仍會執行到結束
+    ///
+    /// # Errors
+    /// 工作 panic 時回傳 [`BlockingError::Panicked`] ，沒有可用的執行緒且無法建立時回傳 [`BlockingError::Spawn`]
+    pub async fn run<F, R>(&self, job: F) -> Result<R, BlockingError>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        let job: Job = Box::new(move || {
+            if let Ok(result) = catch_unwind(AssertUnwindSafe(job)) {
+                let _ = sender.send(result);
+            }
+        });
+        self.inner
+            .sender
+            .send(job)
+            .map_err(|_| BlockingError::Spawn(io::Error::from(io::ErrorKind::BrokenPipe)))?;
+        self.grow()?;
+        receiver.await.map_err(|_| BlockingError::Panicked)
+    }
+
+    /// 沒有閒置的執行緒且未達上限時建立新的執行緒
+    fn grow(&self) -> Result<(), BlockingError> {
+        let inner = &self.inner;
+        if inner.idle.load(Ordering::Acquire) > 0 {
+            return Ok(());
+        }
+        let Ok(index) =
+            inner
+                .threads
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |threads| {
+                    (threads < inner.max_threads).then_some(threads + 1)
+                })
+        else {
+            return Ok(());
+        };
+
+        let jobs = Arc::clone(&inner.jobs);
+        let idle = Arc::clone(&inner.idle);
+        let spawned = thread::Builder::new()
+            .name(format!("{}-{index}", inner.name))
+            .spawn(move || {
+                loop {
+                    idle.fetch_add(1, Ordering::AcqRel);
+                    let job = jobs.lock().unwrap_or_else(PoisonError::into_inner).recv();
+                    idle.fetch_sub(1, Ordering::AcqRel);
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => break,
+                    }
+                }
+            });
+        match spawned {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                inner.threads.fetch_sub(1, Ordering::AcqRel);
+                // 已有執行緒時，工作會由既有的執行緒處理
+                if index == 0 {
+                    Err(BlockingError::Spawn(error))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+/// 在執行緒池上執行 [`BlockingConnection`] 的連線定義
+///
+/// 實作了 [`Connection`] ，可如同其他連線定義交給主程式
+pub struct AsyncBridge<T: BlockingConnection> {
+    connection: Arc<Mutex<T>>,
+    pool: BlockingPool,
+}
+
+impl<T: BlockingConnection> AsyncBridge<T> {
+    /// 在共用的執行緒池 [`BlockingPool::shared()`] 上執行
+    #[must_use]
+    pub fn new(connection: T) -> Self {
+        Self::with_pool(connection, BlockingPool::shared())
+    }
+
+    /// 在指定的執行緒池上執行，可將阻塞時間較長的連線與其他連線隔離
+    #[must_use]
+    pub fn with_pool(connection: T, pool: BlockingPool) -> Self {
+        Self {
+            connection: Arc::new(Mutex::new(connection)),
+            pool,
+        }
+    }
+
+    /// 執行連線的執行緒池
+    #[must_use]
+    pub const fn pool(&self) -> &BlockingPool {
+        &self.pool
+    }
+
+    /// 在呼叫端的執行緒上取得連線，有執行中的工作時會阻塞到該工作完成
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        self.connection
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// 在執行緒池上調用連線的 method
+    async fn call<F, R>(&self, job: F) -> Result<R, ConnectionError>
+    where
+        F: FnOnce(&mut T) -> Result<R, ConnectionError> + Send + 'static,
+        R: Send + 'static,
+    {
+        let connection = Arc::clone(&self.connection);
+        self.pool
+            .run(move || {
+                let mut connection = connection.lock().unwrap_or_else(PoisonError::into_inner);
+                job(&mut connection).map_err(SendableError::from)
+            })
+            .await?
+            .map_err(ConnectionError::from)
+    }
+}
+
+impl<T: BlockingConnection> Connection for AsyncBridge<T> {
+    const NAMES: &[&str] = T::NAMES;
+    type Config = T::Config;
+    type Target = T::Target;
+    type Request = T::Request;
+    type Response = T::Response;
+    type Result = T::Result;
+
+    async fn init(config: &T::Config) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+        let config = config.clone();
+        BlockingPool::shared()
+            .run(move || T::init(&config).map_err(SendableError::from))
+            .await?
+            .map_err(ConnectionError::from)
+    }
+
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<T::Target>,
+    ) -> ConnectionTargets<T::Request, T::Result> {
+        self.lock().init_targets(connection_statistics, targets)
+    }
+
+    fn preprocess(
+        &self,
+        request: T::Request,
+        new_status: Option<Value>,
+    ) -> Result<T::Request, ConnectionError> {
+        self.lock().preprocess(request, new_status)
+    }
+
+    fn preprocess_with_context(
+        &self,
+        request: T::Request,
+        new_status: Option<Value>,
+        context: &RequestContext,
+    ) -> Result<T::Request, ConnectionError> {
+        self.lock()
+            .preprocess_with_context(request, new_status, context)
+    }
+
+    async fn request_process(
+        &mut self,
+        request: T::Request,
+    ) -> Result<(T::Response, bool), ConnectionError> {
+        self.call(move |connection| connection.request_process(request))
+            .await
+    }
+
+    fn postprocess(
+        &self,
+        request: T::Request,
+        response: T::Response,
+    ) -> Result<T::Response, ConnectionError> {
+        self.lock().postprocess(request, response)
+    }
+
+    fn write_preprocess(
+        &self,
+        request: T::Request,
+        value: Value,
+        context: &RequestContext,
+    ) -> Result<Box<dyn DeviceStateWrite>, ConnectionError> {
+        self.lock().write_preprocess(request, value, context)
+    }
+
+    async fn write_process(
+        &mut self,
+        write: Box<dyn DeviceStateWrite>,
+    ) -> Result<Option<T::Response>, ConnectionError> {
+        self.call(move |connection| connection.write_process(write))
+            .await
+    }
+
+    fn write_postprocess(
+        &self,
+        write: &dyn DeviceStateWrite,
+        response: Option<T::Response>,
+    ) -> Result<Option<T::Response>, ConnectionError> {
+        self.lock().write_postprocess(write, response)
+    }
+
+    async fn keepalive(&mut self) -> Result<(), ConnectionError> {
+        self.call(T::keepalive).await
+    }
+
+    async fn diagnostics(
+        &mut self,
+        command: diagnostics::DiagnosticsCommand,
+    ) -> Result<Value, ConnectionError> {
+        self.call(move |connection| connection.diagnostics(command))
+            .await
+    }
+
+    async fn disconnect(&mut self) -> Result<(), ConnectionError> {
+        self.call(T::disconnect).await
+    }
+
+    async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+        self.call(T::reconnect).await
+    }
+
+    fn session_state(&self) -> Option<Value> {
+        self.lock().session_state()
+    }
+
+    async fn reconnect_with(
+        &mut self,
+        hint: session::ReconnectHint,
+    ) -> Result<session::ReconnectOutcome, ConnectionError> {
+        self.call(move |connection| connection.reconnect_with(hint))
+            .await
+    }
+
+    async fn update_config(&mut self, new_config: &T::Config) -> Result<(), ConnectionError> {
+        let new_config = new_config.clone();
+        self.call(move |connection| connection.update_config(&new_config))
+            .await
+    }
+}
@@ -0,0 +1,173 @@
+//! 時間區間計數
+//!
+//! [`crate::TargetStats`] 記錄的是程式啟動以來的累計數值，無法回答「過去 24 小時內每小時的錯誤次數」這類問題，
+//! [`BucketedCounters`] 依固定的時間區間（預設為一小時）分別計數，並自動捨棄超過保留數量的舊區間，不需要另外架設時序資料庫
+//!
+//! 區間以 UNIX 時間對齊，如一小時的區間會從整點開始，沒有任何紀錄的區間仍會出現在快照中，數值為 0
+//!
+//! # 範例
+//! ```rust
+//! use std::time::Duration;
+//! use device_state_exchange_lib::bucket::BucketedCounters;
+//!
+//! // 每 15 分鐘一個區間，保留 96 個（24 小時）
+//! let counters = BucketedCounters::new(Duration::from_secs(15 * 60), 96);
+//! counters.record_success(12);
+//! counters.record_success(12);
+//! counters.record_failure();
+//!
+//! let buckets = counters.snapshot();
+//! assert_eq!(buckets.len(), 1);
+//! assert_eq!((buckets[0].polls, buckets[0].failures, buckets[0].bytes), (3, 1, 24));
+//! ```
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// 預設區間長度，一小時
+pub const DEFAULT_BUCKET_WIDTH: Duration = Duration::from_hours(1);
+
+/// 預設保留的區間數量，搭配預設區間長度為 24 小時
+pub const DEFAULT_BUCKET_RETAIN: usize = 24;
+
+/// 時間區間計數器
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份數據
+#[derive(Debug, Clone)]
+pub struct BucketedCounters(Arc<Mutex<BucketRing>>);
+
+#[derive(Debug)]
+struct BucketRing {
+    bucket_ms: u64,
+    retain: usize,
+    buckets: VecDeque<Bucket>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    index: u64,
+    polls: u64,
+    failures: u64,
+    bytes: u64,
+}
+
+/// 時間區間計數快照
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BucketSnapshot {
+    /// 區間開始時間
+    pub start: SystemTime,
+    /// 輪詢次數
+    pub polls: u64,
+    /// 失敗的輪詢次數
+    pub failures: u64,
+    /// 成功輪詢收到的位元組數
+    pub bytes: u64,
+}
+
+impl Default for BucketedCounters {
+    fn default() -> Self {
+        Self::new(DEFAULT_BUCKET_WIDTH, DEFAULT_BUCKET_RETAIN)
+    }
+}
+
+impl BucketedCounters {
+    /// 建立時間區間計數器
+    ///
+    /// # 參數
+    /// - `width`：區間長度，以毫秒為最小單位，小於 1 毫秒時視為 1 毫秒
+    /// - `retain`：保留的區間數量（包含目前的區間），小於 1 時視為 1
+    #[must_use]
+    pub fn new(width: Duration, retain: usize) -> Self {
+        Self(Arc::new(Mutex::new(BucketRing {
+            bucket_ms: u64::try_from(width.as_millis()).unwrap_or(u64::MAX).max(1),
+            retain: retain.max(1),
+            buckets: VecDeque::new(),
+        })))
+    }
+
+    /// 記錄一次成功的輪詢
+    ///
+    /// # 參數
+    /// - `bytes`：本次收到的位元組數
+    pub fn record_success(&self, bytes: u64) {
+        self.record(|bucket| {
+            bucket.polls += 1;
+            bucket.bytes += bytes;
+        });
+    }
+
+    /// 記錄一次失敗的輪詢
+    pub fn record_failure(&self) {
+        self.record(|bucket| {
+            bucket.polls += 1;
+            bucket.failures += 1;
+        });
+    }
+
+    /// 取得所有保留區間的快照，依時間由舊到新排列
+    ///
+    /// 最後一個區間為目前的區間，尚未有任何紀錄時回傳空陣列
+    #[must_use]
+    pub fn snapshot(&self) -> Vec<BucketSnapshot> {
+        let mut buckets = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if !buckets.buckets.is_empty() {
+            buckets.roll(SystemTime::now());
+        }
+
+        buckets
+            .buckets
+            .iter()
+            .map(|bucket| BucketSnapshot {
+                start: UNIX_EPOCH
+                    + Duration::from_millis(bucket.index.saturating_mul(buckets.bucket_ms)),
+                polls: bucket.polls,
+                failures: bucket.failures,
+                bytes: bucket.bytes,
+            })
+            .collect()
+    }
+
+    fn record(&self, update: impl FnOnce(&mut Bucket)) {
+        let mut buckets = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        buckets.roll(SystemTime::now());
+        if let Some(bucket) = buckets.buckets.back_mut() {
+            update(bucket);
+        }
+    }
+}
+
+impl BucketRing {
+    /// 補上到 `now` 為止的區間，並捨棄超過保留數量的舊區間
+    ///
+    /// 作業系統時鐘被往回調整時，會繼續使用最後一個區間
+    fn roll(&mut self, now: SystemTime) {
+        let elapsed_ms = now.duration_since(UNIX_EPOCH).map_or(0, |elapsed| {
+            u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX)
+        });
+        let index = elapsed_ms / self.bucket_ms;
+        let retain = u64::try_from(self.retain).unwrap_or(u64::MAX);
+
+        let first = self
+            .buckets
+            .back()
+            .map_or(index, |last| last.index + 1)
+            .max(index.saturating_sub(retain - 1));
+
+        for index in first..=index {
+            self.buckets.push_back(Bucket {
+                index,
+                polls: 0,
+                failures: 0,
+                bytes: 0,
+            });
+        }
+
+        let excess = self.buckets.len().saturating_sub(self.retain);
+        self.buckets.drain(..excess);
+    }
+}
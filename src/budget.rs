@@ -0,0 +1,207 @@
+//! 匯流排輪詢預算
+//!
+//! 序列埠等頻寬有限的線路在點位數量多時會飽和，輪詢不及的點位更新間隔會不規則地拉長，
+//! 關鍵點位（如保護電驛狀態）也可能被大量次要點位排擠
+//!
+//! 實作者可以在 [`crate::Connection::init()`] 中透過 [`crate::ConnectionArtifact::budget_polls()`] 宣告線路在每個更新間隔內能處理的輪詢次數，
+//! 維運人員則以 [`BusBudget::set_weight()`] 為點位或點位群組（參見 [`crate::InitedTarget::group`]）設定權重；
+//! 主程式在每個更新間隔開始時調用 [`BusBudget::plan()`] ，依權重比例分配輪詢次數，並在每次輪詢完成後調用 [`BusBudget::record_poll()`]
+//!
+//! 分配時使用平滑加權輪替（smooth weighted round-robin），同一個間隔內各點位的輪詢次數與權重成正比，且會平均穿插而不會集中在間隔開頭；
+//! 分配給點位群組的輪詢次數，由主程式在群組的點位之間輪流使用
+//!
+//! 各點位與群組在最近一個完整間隔內分配到與實際完成的輪詢次數，會顯示於統計數據快照中
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::budget::BusBudget;
+//!
+//! let budget = BusBudget::new();
+//! budget.set_weight("protection", 3);
+//! budget.set_weight("energy", 1);
+//!
+//! let slots = budget.plan(8);
+//! assert_eq!(slots.iter().filter(|key| *key == "protection").count(), 6);
+//! assert_eq!(slots.iter().filter(|key| *key == "energy").count(), 2);
+//!
+//! // 線路飽和，只完成了部分輪詢
+//! for key in &slots[..5] {
+//!     budget.record_poll(key);
+//! }
+//!
+//! let _ = budget.plan(8);
+//! let protection = budget.snapshot()["protection"];
+//! assert_eq!(protection.requested, 6);
+//! assert!(protection.realized < protection.requested);
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// 匯流排輪詢預算
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件（包含登記至 [`crate::ConnectionStatsRegistry`] 的 [`crate::ConnectionStats`]）會存取同一份資料
+#[derive(Debug, Clone, Default)]
+pub struct BusBudget(Arc<Mutex<BTreeMap<String, BudgetEntry>>>);
+
+#[derive(Debug, Default)]
+struct BudgetEntry {
+    weight: u32,
+    credit: i64,
+    requested: u64,
+    realized: u64,
+    last_requested: u64,
+    last_realized: u64,
+    total_requested: u64,
+    total_realized: u64,
+}
+
+/// 點位或點位群組的輪詢預算快照
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct BudgetSnapshot {
+    /// 權重
+    pub weight: u32,
+    /// 最近一個完整間隔內分配到的輪詢次數
+    pub requested: u64,
+    /// 最近一個完整間隔內實際完成的輪詢次數
+    pub realized: u64,
+    /// 累計分配到的輪詢次數
+    pub total_requested: u64,
+    /// 累計實際完成的輪詢次數
+    pub total_realized: u64,
+}
+
+impl BusBudget {
+    /// 建立輪詢預算
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 設定點位或點位群組的權重
+    ///
+    /// # 參數
+    /// - `key`：點位名稱或點位群組名稱
+    /// - `weight`：權重，為 `0` 時不會分配到任何輪詢次數
+    pub fn set_weight(&self, key: impl Into<String>, weight: u32) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(key.into())
+            .or_default()
+            .weight = weight;
+    }
+
+    /// 取得點位或點位群組的權重，未設定時為 [`None`]
+    #[must_use]
+    pub fn weight(&self, key: &str) -> Option<u32> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+            .map(|entry| entry.weight)
+    }
+
+    /// 移除點位或點位群組的權重與統計
+    pub fn remove(&self, key: &str) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(key);
+    }
+
+    /// 開始新的更新間隔，並依權重分配輪詢次數
+    ///
+    /// 目前間隔的分配與完成次數會成為快照中的最近一個完整間隔
+    ///
+    /// # 參數
+    /// - `slots`：本間隔可用的輪詢次數，參見 [`crate::ConnectionArtifact::poll_budget`]
+    ///
+    /// # 回傳值
+    /// 依輪詢順序排列的點位或點位群組名稱，所有權重均為 `0` 時為空
+    #[must_use]
+    pub fn plan(&self, slots: u32) -> Vec<String> {
+        allocate(
+            &mut self.0.lock().unwrap_or_else(PoisonError::into_inner),
+            slots,
+        )
+    }
+
+    /// 記錄點位或點位群組完成一次輪詢
+    ///
+    /// # 回傳值
+    /// 點位或點位群組是否已設定權重
+    pub fn record_poll(&self, key: &str) -> bool {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(key)
+            .map(|entry| {
+                entry.realized += 1;
+                entry.total_realized += 1;
+            })
+            .is_some()
+    }
+
+    /// 取得各點位與點位群組的輪詢預算快照
+    #[must_use]
+    pub fn snapshot(&self) -> BTreeMap<String, BudgetSnapshot> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(key, entry)| {
+                (
+                    key.clone(),
+                    BudgetSnapshot {
+                        weight: entry.weight,
+                        requested: entry.last_requested,
+                        realized: entry.last_realized,
+                        total_requested: entry.total_requested,
+                        total_realized: entry.total_realized,
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+fn allocate(entries: &mut BTreeMap<String, BudgetEntry>, slots: u32) -> Vec<String> {
+    for entry in entries.values_mut() {
+        entry.last_requested = std::mem::take(&mut entry.requested);
+        entry.last_realized = std::mem::take(&mut entry.realized);
+    }
+
+    let total: i64 = entries.values().map(|entry| i64::from(entry.weight)).sum();
+    if total == 0 {
+        return Vec::new();
+    }
+
+    let mut plan = Vec::new();
+    for _ in 0..slots {
+        let mut chosen: Option<(&String, &mut BudgetEntry)> = None;
+
+        for (key, entry) in entries.iter_mut().filter(|(_, entry)| entry.weight > 0) {
+            entry.credit += i64::from(entry.weight);
+            if chosen
+                .as_ref()
+                .is_none_or(|(_, best)| entry.credit > best.credit)
+            {
+                chosen = Some((key, entry));
+            }
+        }
+
+        if let Some((key, entry)) = chosen {
+            entry.credit -= total;
+            entry.requested += 1;
+            entry.total_requested += 1;
+            plan.push(key.clone());
+        }
+    }
+
+    plan
+}
@@ -0,0 +1,485 @@
+//! 回覆快取
+//!
+//! 部分設備查詢的成本很高（如完整的參數傾印），但經常被多個用戶端同時要求，
+//! 主程式可以利用 [`ResponseCache`] 為這類點位設定 [`CachePolicy`] ，在調用 [`crate::Connection::request_process()`] 前先以 [`ResponseCache::lookup()`] 查詢：
+//!
+//! - [`CacheLookup::Fresh`]：快取仍在有效期限內，直接使用快取的回覆
+//! - [`CacheLookup::Stale`]：快取已過期但仍在 [`CachePolicy::stale_while_revalidate`] 期間內，先使用快取的回覆，
+//!   `revalidate` 為 `true` 時由本次呼叫者在背景重新讀取並以 [`ResponseCache::store()`] 更新，同一段期間只會有一個呼叫者負責更新
+//! - [`CacheLookup::Miss`]：沒有可用的快取，需要向設備讀取後以 [`ResponseCache::store()`] 存入
+//!
+//! 寫入點位後請以 [`ResponseCache::invalidate()`] 清除快取；查詢時傳入點位的 [`TargetStats`] ，命中與未命中的次數會記錄於 [`TargetStats::cache_snapshot()`]
+//!
+//! # 範例
+//! ```rust
+//! use std::time::Duration;
+//! use device_state_exchange_lib::{TargetStats, cache::{CacheLookup, CachePolicy, ResponseCache}};
+//!
+//! let cache = ResponseCache::new();
+//! let statistics = TargetStats::default();
+//! cache.enable("parameters", CachePolicy { ttl: Duration::from_secs(60), stale_while_revalidate: Duration::ZERO });
+//!
+//! assert_eq!(cache.lookup("parameters", Some(&statistics)), CacheLookup::Miss);
+//! cache.store("parameters", vec![1, 2, 3]);
+//! assert_eq!(cache.lookup("parameters", Some(&statistics)), CacheLookup::Fresh(vec![1, 2, 3]));
+//!
+//! // 未設定快取的點位
+//! assert_eq!(cache.lookup("temperature", Some(&statistics)), CacheLookup::Miss);
+//!
+//! let snapshot = statistics.cache_snapshot();
+//! assert_eq!((snapshot.hits, snapshot.misses), (1, 1));
+//!
+//! // 過期後在 stale_while_revalidate 期間內
+//! cache.enable("parameters", CachePolicy { ttl: Duration::ZERO, stale_while_revalidate: Duration::from_secs(60) });
+//! assert_eq!(cache.lookup("parameters", None), CacheLookup::Stale { value: vec![1, 2, 3], revalidate: true });
+//! assert_eq!(cache.lookup("parameters", None), CacheLookup::Stale { value: vec![1, 2, 3], revalidate: false });
+//! ```
+//!
+//! # 最後已知數值
+//! [`ValueCache`] 存放各點位最後一次取得的數值，主程式與實作者可以共用同一份資料（如由主程式寫入、在 [`crate::Connection::preprocess()`] 中讀取相依點位的數值），
+//! 每筆數值附帶更新時間、[`Quality`] 與有效期限，[`ValueCache::get_fresh()`] 在數值過期後回傳 [`None`] ，避免讀取端在不知情的情況下使用過舊的數值
+//!
+//! ```rust
+//! use std::time::Duration;
+//! use device_state_exchange_lib::{cache::ValueCache, value::Quality};
+//! use serde_json::json;
+//!
+//! let cache = ValueCache::new().with_stale_after(Duration::from_secs(60));
+//! cache.set_stale_after("alarm", Some(Duration::ZERO));
+//!
+//! cache.update("temperature", json!(21.5), Quality::Good);
+//! cache.update("alarm", json!(false), Quality::Good);
+//!
+//! let temperature = cache.get_fresh("temperature").unwrap();
+//! assert_eq!((temperature.value, temperature.stale_after), (json!(21.5), Some(Duration::from_secs(60))));
+//!
+//! // 已過期的數值仍可以 get() 取得，並標示為過期
+//! assert!(cache.get_fresh("alarm").is_none());
+//! assert!(cache.get("alarm").unwrap().is_stale());
+//! assert_eq!(cache.stale_names(), ["alarm"]);
+//! ```
+
+use std::{
+    sync::{
+        Arc, PoisonError, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{HashMap, TargetStats, value::Quality};
+
+/// 快取設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CachePolicy {
+    /// 快取有效期限，設定檔中以毫秒數表示
+    #[serde(rename = "ttl_ms", with = "crate::millis")]
+    pub ttl: Duration,
+    /// 過期後仍可使用快取、同時在背景重新讀取的期間，設定檔中以毫秒數表示，預設為 0 （過期後必須重新讀取）
+    #[serde(rename = "stale_while_revalidate_ms", with = "crate::millis", default)]
+    pub stale_while_revalidate: Duration,
+}
+
+/// 快取查詢結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheLookup<T> {
+    /// 快取仍在有效期限內
+    Fresh(T),
+    /// 快取已過期，但仍在 [`CachePolicy::stale_while_revalidate`] 期間內
+    Stale {
+        /// 快取的回覆
+        value: T,
+        /// 是否由本次呼叫者負責重新讀取
+        revalidate: bool,
+    },
+    /// 沒有可用的快取，或點位未設定快取
+    Miss,
+}
+
+/// 快取命中統計快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStatsSnapshot {
+    /// 使用有效快取的次數
+    pub hits: u64,
+    /// 使用過期快取的次數
+    pub stale_hits: u64,
+    /// 沒有可用快取的次數
+    pub misses: u64,
+}
+
+impl CacheStatsSnapshot {
+    /// 是否沒有任何查詢紀錄
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.hits == 0 && self.stale_hits == 0 && self.misses == 0
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct CacheCounters {
+    hits: AtomicU64,
+    stale_hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CacheCounters {
+    pub(crate) fn snapshot(&self) -> CacheStatsSnapshot {
+        CacheStatsSnapshot {
+            hits: self.hits.load(Ordering::Relaxed),
+            stale_hits: self.stale_hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.stale_hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug)]
+struct Entry<T> {
+    policy: CachePolicy,
+    cached: Option<(T, Instant)>,
+    revalidating: bool,
+}
+
+/// 回覆快取
+///
+/// 以點位名稱存放最近一次的回覆，只有透過 [`ResponseCache::enable()`] 設定的點位會被快取
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份資料
+#[derive(Debug)]
+pub struct ResponseCache<T> {
+    entries: Arc<RwLock<HashMap<String, Entry<T>>>>,
+}
+
+impl<T> Clone for ResponseCache<T> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: Arc::clone(&self.entries),
+        }
+    }
+}
+
+impl<T> Default for ResponseCache<T> {
+    fn default() -> Self {
+        Self {
+            entries: Arc::default(),
+        }
+    }
+}
+
+impl<T: Clone> ResponseCache<T> {
+    /// 建立回覆快取，所有點位預設不快取
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 設定點位的快取，已設定時會更新設定並保留已快取的回覆
+    pub fn enable(&self, name: impl Into<String>, policy: CachePolicy) {
+        self.entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(name.into())
+            .and_modify(|entry| entry.policy = policy)
+            .or_insert(Entry {
+                policy,
+                cached: None,
+                revalidating: false,
+            });
+    }
+
+    /// 取消點位的快取，並清除已快取的回覆
+    pub fn disable(&self, name: &str) {
+        self.entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(name);
+    }
+
+    /// 取得點位的快取設定，未設定時為 [`None`]
+    #[must_use]
+    pub fn policy(&self, name: &str) -> Option<CachePolicy> {
+        self.entries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .map(|entry| entry.policy)
+    }
+
+    /// 查詢快取
+    ///
+    /// # 參數
+    /// - `name`：點位名稱
+    /// - `statistics`：點位統計數據，傳入時會記錄命中與未命中的次數，點位未設定快取時不會記錄
+    pub fn lookup(&self, name: &str, statistics: Option<&TargetStats>) -> CacheLookup<T> {
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        let Some(entry) = entries.get_mut(name) else {
+            return CacheLookup::Miss;
+        };
+        let lookup = classify(entry);
+        drop(entries);
+
+        if let Some(statistics) = statistics {
+            let counter = match lookup {
+                CacheLookup::Fresh(_) => &statistics.cache.hits,
+                CacheLookup::Stale { .. } => &statistics.cache.stale_hits,
+                CacheLookup::Miss => &statistics.cache.misses,
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+
+        lookup
+    }
+
+    /// 存入讀取到的回覆，點位未設定快取時不會存入
+    pub fn store(&self, name: &str, value: T) {
+        if let Some(entry) = self
+            .entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(name)
+        {
+            entry.cached = Some((value, Instant::now()));
+            entry.revalidating = false;
+        }
+    }
+
+    /// 清除點位已快取的回覆，請在寫入點位後調用
+    pub fn invalidate(&self, name: &str) {
+        if let Some(entry) = self
+            .entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(name)
+        {
+            entry.cached = None;
+            entry.revalidating = false;
+        }
+    }
+}
+
+fn classify<T: Clone>(entry: &mut Entry<T>) -> CacheLookup<T> {
+    let Some((value, stored)) = &entry.cached else {
+        return CacheLookup::Miss;
+    };
+    let age = stored.elapsed();
+
+    if age < entry.policy.ttl {
+        CacheLookup::Fresh(value.clone())
+    } else if age < entry.policy.ttl + entry.policy.stale_while_revalidate {
+        let revalidate = !entry.revalidating;
+        let value = value.clone();
+        entry.revalidating = true;
+        CacheLookup::Stale { value, revalidate }
+    } else {
+        CacheLookup::Miss
+    }
+}
+
+/// 最後已知數值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedValue {
+    /// 數值
+    pub value: Value,
+    /// 最後一次更新的時間
+    pub last_updated: SystemTime,
+    /// 數值品質
+    pub quality: Quality,
+    /// 有效期限，為 [`None`] 時不會過期
+    pub stale_after: Option<Duration>,
+    updated: Instant,
+}
+
+impl CachedValue {
+    /// 距離最後一次更新的時間，以單調時鐘計算
+    #[must_use]
+    pub fn age(&self) -> Duration {
+        self.updated.elapsed()
+    }
+
+    /// 是否已超過有效期限
+    #[must_use]
+    pub fn is_stale(&self) -> bool {
+        self.stale_after
+            .is_some_and(|stale_after| self.age() >= stale_after)
+    }
+}
+
+#[derive(Debug, Default)]
+struct ValueSlot {
+    /// 是否以 [`ValueCache::set_stale_after()`] 個別設定有效期限
+    overridden: bool,
+    stale_after: Option<Duration>,
+    cached: Option<CachedValue>,
+}
+
+impl ValueSlot {
+    const fn set_stale_after(&mut self, stale_after: Option<Duration>) {
+        self.overridden = true;
+        self.stale_after = stale_after;
+        if let Some(cached) = &mut self.cached {
+            cached.stale_after = stale_after;
+        }
+    }
+
+    fn store(&mut self, value: Value, quality: Quality, default_stale_after: Option<Duration>) {
+        self.cached = Some(CachedValue {
+            value,
+            last_updated: SystemTime::now(),
+            quality,
+            stale_after: if self.overridden {
+                self.stale_after
+            } else {
+                default_stale_after
+            },
+            updated: Instant::now(),
+        });
+    }
+}
+
+/// 最後已知數值快取
+///
+/// 以點位名稱存放最後一次取得的數值，有效期限可以在 [`ValueCache::with_stale_after()`] 統一設定，
+/// 或以 [`ValueCache::set_stale_after()`] 個別設定
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份資料
+#[derive(Debug, Clone, Default)]
+pub struct ValueCache {
+    default_stale_after: Option<Duration>,
+    entries: Arc<RwLock<HashMap<String, ValueSlot>>>,
+}
+
+impl ValueCache {
+    /// 建立最後已知數值快取，數值預設不會過期
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 設定未個別設定有效期限的點位使用的有效期限
+    #[must_use]
+    pub const fn with_stale_after(mut self, stale_after: Duration) -> Self {
+        self.default_stale_after = Some(stale_after);
+        self
+    }
+
+    /// 個別設定點位的有效期限，已存入的數值會一併套用
+    ///
+    /// # 參數
+    /// - `name`：點位名稱
+    /// - `stale_after`：有效期限，為 [`None`] 時不會過期
+    pub fn set_stale_after(&self, name: impl Into<String>, stale_after: Option<Duration>) {
+        self.entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(name.into())
+            .or_default()
+            .set_stale_after(stale_after);
+    }
+
+    /// 存入點位最新的數值
+    ///
+    /// # 參數
+    /// - `name`：點位名稱
+    /// - `value`：數值
+    /// - `quality`：數值品質
+    pub fn update(&self, name: impl Into<String>, value: Value, quality: Quality) {
+        self.entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(name.into())
+            .or_default()
+            .store(value, quality, self.default_stale_after);
+    }
+
+    /// 取得點位最後已知的數值，包含已過期的數值
+    ///
+    /// 尚未存入數值時為 [`None`]
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<CachedValue> {
+        self.entries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .and_then(|slot| slot.cached.clone())
+    }
+
+    /// 取得點位仍在有效期限內的數值
+    ///
+    /// 尚未存入數值或數值已過期時為 [`None`]
+    #[must_use]
+    pub fn get_fresh(&self, name: &str) -> Option<CachedValue> {
+        self.get(name).filter(|cached| !cached.is_stale())
+    }
+
+    /// 列出數值已過期的點位名稱，依名稱排序
+    #[must_use]
+    pub fn stale_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .entries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|(_, slot)| slot.cached.as_ref().is_some_and(CachedValue::is_stale))
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// 移除點位的數值與有效期限設定
+    pub fn remove(&self, name: &str) -> Option<CachedValue> {
+        self.entries
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(name)
+            .and_then(|slot| slot.cached)
+    }
+
+    /// 所有已存入數值的點位名稱與數值
+    pub(crate) fn cached(&self) -> Vec<(String, CachedValue)> {
+        self.entries
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter_map(|(name, slot)| Some((name.clone(), slot.cached.clone()?)))
+            .collect()
+    }
+
+    /// 匯入持久化儲存的數值，已有數值的點位不會被取代
+    ///
+    /// 更新時間沿用 `last_updated` ，有效期限依本快取的設定計算
+    ///
+    /// # 回傳值
+    /// 是否匯入
+    pub(crate) fn restore(
+        &self,
+        name: String,
+        value: Value,
+        quality: Quality,
+        last_updated: SystemTime,
+    ) -> bool {
+        let mut entries = self.entries.write().unwrap_or_else(PoisonError::into_inner);
+        let slot = entries.entry(name).or_default();
+        if slot.cached.is_some() {
+            return false;
+        }
+        slot.store(value, quality, self.default_stale_after);
+        if let Some(cached) = &mut slot.cached {
+            let age = SystemTime::now()
+                .duration_since(last_updated)
+                .unwrap_or_default();
+            cached.last_updated = last_updated;
+            cached.updated = Instant::now().checked_sub(age).unwrap_or(cached.updated);
+        }
+        drop(entries);
+        true
+    }
+}
@@ -0,0 +1,1167 @@
+//! CAN bus / J1939 參考實作（需啟用 `canbus` feature）
+//!
+//! 預設以 Linux 的 `AF_CAN` raw socket 直接存取本機的 SocketCAN 介面（如 `can0`）；
+//! CAN 介面位於其他主機，或閘道器不是 Linux 時，可改為經由 [socketcand](https://github.com/linux-can/socketcand) 以 TCP 存取，
+//! 參見 [`CanTransport`] 。
+//!
+//! [`CanConnection`] 在背景持續接收匯流排上的訊框，依 CAN ID 保留最新的訊框，
+//! [`Connection::request_process()`] 直接由最新的訊框解析點位的訊號：
+//!
+//! - 識別碼：點位可指定 11 位元標準識別碼、29 位元延伸識別碼，或 J1939 的參數群組編號（PGN），
+//!   指定 PGN 時不比對優先權，未指定 `source_address` 時接受任何來源位址，參見 [`CanId`]
+//! - 訊號：依起始位元、長度與位元組順序取出原始數值，再套用倍率與偏移量，定義方式與 DBC 檔案相同，參見 [`CanSignal`]
+//! - J1939 多封包訊息：以 BAM（TP.CM/TP.DT）廣播的訊息會重組後視為一個訊框，訊號可以超過 8 個位元組；
+//!   需要回覆的 RTS/CTS 傳輸不支援
+//! - J1939 特殊數值：指定 PGN 的點位，原始數值為「無法取得」或「錯誤」時回傳 [`CanSignalError::NotAvailable`] 或 [`CanSignalError::ErrorIndicator`]
+//! - 重新連線：CAN 介面關閉或與 socketcand 的連線中斷後，下一個請求會先重新連線
+//! - 捨棄的訊框：raw socket 讀取到長度不符的訊框（如 CAN FD 訊框）時會捨棄並繼續接收，數量參見 [`CanConnection::dropped_frames()`]
+//!
+//! 本連線只接收訊框，不支援寫入。
+//!
+//! 點位設定：
+//!
+//! | 欄位 | 說明 |
+//! | --- | --- |
+//! | `address` | CAN ID（如 `0x123` 、`0x18FEF100`），或 J1939 的 PGN（如 `pgn:65262` 、`pgn:0xFEEE`） |
+//! | `extended` | CAN ID 是否為延伸識別碼，未設定時大於 `0x7FF` 的識別碼視為延伸識別碼 |
+//! | `source_address` | J1939 來源位址，未設定時接受任何來源 |
+//! | `start_bit` | 訊號的起始位元，位元組順序為 `big_endian` 時為最高位元的位置（與 DBC 檔案相同） |
+//! | `length` | 訊號的位元數，1 至 64 |
+//! | `byte_order` | `little_endian`（Intel ，預設）或 `big_endian`（Motorola） |
+//! | `signed` | 原始數值是否為二補數有號整數，未設定時為 `false` |
+//! | `scale` | 倍率，未設定時為 1 |
+//! | `offset` | 偏移量，未設定時為 0 |
+//! | `stale_after_ms` | 訊框的有效期限，未設定時使用 [`CanConfig::stale_after`] |
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{
+//!     TargetDefinition,
+//!     canbus::{CanConfig, CanFrame, CanId, CanRequest, CanSignalError, CanTransport},
+//! };
+//! use serde_json::json;
+//!
+//! let config: CanConfig = serde_json::from_value(json!({
+//!     "interface": "can0",
+//!     "stale_after_ms": 3000,
+//! }))
+//! .unwrap();
+//! assert_eq!(config.interface, "can0");
+//! assert_eq!(config.transport, CanTransport::SocketCan);
+//!
+//! let config: CanConfig = serde_json::from_value(json!({
+//!     "interface": "can0",
+//!     "transport": { "type": "socketcand", "host": "192.168.1.50", "port": 29536 },
+//! }))
+//! .unwrap();
+//! assert!(matches!(&config.transport, CanTransport::Socketcand(endpoint) if endpoint.port == 29536));
+//!
+//! // J1939 引擎溫度（PGN 65262 ，SPN 110）：第一個位元組，1 °C/bit ，偏移 -40 °C
+//! let definition: TargetDefinition = serde_json::from_value(json!({
+//!     "name": "冷卻水溫度",
+//!     "address": "pgn:65262",
+//!     "start_bit": 0,
+//!     "length": 8,
+//!     "offset": -40,
+//! }))
+//! .unwrap();
+//! let request = CanRequest::parse(&definition).unwrap();
+//! assert_eq!(request.id, CanId::J1939 { pgn: 65262, source_address: None });
+//!
+//! let frame = CanFrame { id: 0x18FEEE00, extended: true, data: vec![0x7D, 0xFF, 0xFF, 0xFF] };
+//! assert!(request.id.matches(&frame));
+//! assert_eq!(request.extract(&frame).unwrap(), json!(85.0));
+//!
+//! let frame = CanFrame { id: 0x18FEEE03, extended: true, data: vec![0xFF; 8] };
+//! assert_eq!(request.extract(&frame), Err(CanSignalError::NotAvailable));
+//!
+//! let definition: TargetDefinition = serde_json::from_value(json!({
+//!     "name": "車速",
+//!     "address": "0x123",
+//!     "start_bit": 7,
+//!     "length": 16,
+//!     "byte_order": "big_endian",
+//!     "scale": 0.01,
+//! }))
+//! .unwrap();
+//! let request = CanRequest::parse(&definition).unwrap();
+//! assert_eq!(request.id, CanId::Standard(0x123));
+//! assert_eq!(request.signal.decode(&[0x12, 0x34]).unwrap(), json!(46.6));
+//! ```
+
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::Display,
+    net::SocketAddr,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::tcp::OwnedReadHalf,
+    sync::Notify,
+    task::JoinHandle,
+    time::Instant,
+};
+
+use crate::{
+    Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+    ConnectionTargets, DeviceStateRequest, DeviceStateResponse, HashMap, InitedTarget,
+    RemoteAddress, Target, TargetDefinition,
+    definition::InvalidTarget,
+    fixture::FrameDecoder,
+    transport::tcp::{self, TcpEndpoint},
+    value::ConversionError,
+};
+
+/// 預設的更新間隔
+pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 預設的逾時，用於建立連線與等待點位的第一個訊框
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// socketcand 單一訊息的長度上限
+const MAX_MESSAGE_LENGTH: usize = 1024;
+
+/// 延伸識別碼的旗標，與 Linux 的 `CAN_EFF_FLAG` 相同
+const EXTENDED_FLAG: u32 = 0x8000_0000;
+
+/// 遠端請求訊框的旗標，與 Linux 的 `CAN_RTR_FLAG` 相同
+const REMOTE_FLAG: u32 = 0x4000_0000;
+
+/// 錯誤訊框的旗標，與 Linux 的 `CAN_ERR_FLAG` 相同
+const ERROR_FLAG: u32 = 0x2000_0000;
+
+/// J1939 傳輸協定連線管理（TP.CM）的 PF
+const TP_CM: u32 = 0xEC;
+
+/// J1939 傳輸協定資料傳輸（TP.DT）的 PF
+const TP_DT: u32 = 0xEB;
+
+/// TP.CM 的 BAM 控制位元組
+const BAM: u8 = 32;
+
+/// 重組後的多封包訊息使用的優先權
+const REASSEMBLED_PRIORITY: u32 = 6;
+
+const fn default_update_interval() -> Duration {
+    DEFAULT_UPDATE_INTERVAL
+}
+
+const fn default_timeout() -> Duration {
+    DEFAULT_TIMEOUT
+}
+
+/// 存取 CAN 介面的方式
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CanTransport {
+    /// 以 `AF_CAN` raw socket 直接存取本機的介面，只支援 Linux
+    #[default]
+    #[serde(rename = "socketcan")]
+    SocketCan,
+    /// 經由 socketcand 以 TCP 存取，數值為 socketcand 的位址，可經由代理伺服器連線
+    Socketcand(TcpEndpoint),
+}
+
+/// 連線參數
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanConfig {
+    /// CAN 介面名稱，如 `can0`
+    pub interface: String,
+    /// 存取 CAN 介面的方式，預設為 [`CanTransport::SocketCan`]
+    #[serde(default)]
+    pub transport: CanTransport,
+    /// 更新間隔，序列化時以毫秒數表示，預設為 [`DEFAULT_UPDATE_INTERVAL`]
+    #[serde(
+        rename = "update_interval_ms",
+        with = "crate::millis",
+        default = "default_update_interval"
+    )]
+    pub update_interval: Duration,
+    /// 逾時，序列化時以毫秒數表示，預設為 [`DEFAULT_TIMEOUT`]
+    #[serde(
+        rename = "timeout_ms",
+        with = "crate::millis",
+        default = "default_timeout"
+    )]
+    pub timeout: Duration,
+    /// 訊框的有效期限，序列化時以毫秒數表示，超過期限沒有收到新訊框時，點位的請求回傳 [`CanSignalError::Stale`] ；未設定時不會過期
+    #[serde(rename = "stale_after_ms", with = "crate::millis::option", default)]
+    pub stale_after: Option<Duration>,
+    /// 最高重試次數，參見 [`ConnectionArtifact::max_retry_count`]
+    #[serde(default)]
+    pub max_retry_count: Option<u32>,
+}
+
+impl ConnectionConfig for CanConfig {}
+
+/// CAN 訊框
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanFrame {
+    /// 識別碼，不包含延伸識別碼旗標
+    pub id: u32,
+    /// 是否為 29 位元延伸識別碼
+    pub extended: bool,
+    /// 資料，重組後的 J1939 多封包訊息可超過 8 個位元組
+    pub data: Vec<u8>,
+}
+
+impl CanFrame {
+    const fn key(&self) -> u32 {
+        if self.extended {
+            self.id | EXTENDED_FLAG
+        } else {
+            self.id
+        }
+    }
+
+    /// 解析 SocketCAN 的 `struct can_frame`（識別碼為 little-endian），遠端請求與錯誤訊框回傳 [`None`]
+    fn from_socketcan(frame: &[u8]) -> Option<Self> {
+        let [a, b, c, d, length, _, _, _, data @ ..] = frame else {
+            return None;
+        };
+        let id = u32::from_le_bytes([*a, *b, *c, *d]);
+        if data.len() != 8 || id & (REMOTE_FLAG | ERROR_FLAG) != 0 {
+            return None;
+        }
+        Some(Self {
+            id: if id & EXTENDED_FLAG == 0 {
+                id & 0x7FF
+            } else {
+                id & 0x1FFF_FFFF
+            },
+            extended: id & EXTENDED_FLAG != 0,
+            data: data.get(..usize::from(*length))?.to_vec(),
+        })
+    }
+
+    /// 解析 socketcand 的 `< frame ID 秒數.微秒 資料 >` 訊息，其他訊息回傳 [`None`]
+    fn parse(message: &str) -> Option<Self> {
+        let mut fields = message
+            .trim()
+            .strip_prefix('<')?
+            .strip_suffix('>')?
+            .split_whitespace();
+        if fields.next()? != "frame" {
+            return None;
+        }
+        let id = fields.next()?;
+        fields.next()?;
+        let hex: String = fields.collect();
+        if !hex.len().is_multiple_of(2) {
+            return None;
+        }
+        let data = hex
+            .as_bytes()
+            .chunks(2)
+            .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self {
+            id: u32::from_str_radix(id, 16).ok()?,
+            extended: id.len() > 3,
+            data,
+        })
+    }
+}
+
+/// 點位比對的識別碼
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CanId {
+    /// 11 位元標準識別碼
+    Standard(u16),
+    /// 29 位元延伸識別碼
+    Extended(u32),
+    /// J1939 參數群組，不比對優先權與目的位址
+    J1939 {
+        /// 參數群組編號
+        pgn: u32,
+        /// 來源位址，為 [`None`] 時接受任何來源
+        source_address: Option<u8>,
+    },
+}
+
+impl CanId {
+    /// 解析點位的 `address` 欄位
+    ///
+    /// # 參數
+    /// - `text`：CAN ID 或 `pgn:` 開頭的 PGN ，可使用十進位或 `0x` 開頭的十六進位
+    /// - `extended`：CAN ID 是否為延伸識別碼，為 [`None`] 時大於 `0x7FF` 的識別碼視為延伸識別碼
+    /// - `source_address`：J1939 來源位址
+    #[must_use]
+    pub fn parse(text: &str, extended: Option<bool>, source_address: Option<u8>) -> Option<Self> {
+        let number = |text: &str| {
+            text.strip_prefix("0x")
+                .or_else(|| text.strip_prefix("0X"))
+                .map_or_else(
+                    || text.parse().ok(),
+                    |hex| u32::from_str_radix(hex, 16).ok(),
+                )
+        };
+
+        if let Some(pgn) = text.trim().strip_prefix("pgn:") {
+            let pgn = number(pgn.trim()).filter(|pgn| *pgn <= 0x3_FFFF)?;
+            // PDU1 格式（PF < 240）的 PS 為目的位址，PGN 的最低位元組必須為 0
+            if (pgn >> 8) & 0xFF < 240 && pgn & 0xFF != 0 {
+                return None;
+            }
+            return Some(Self::J1939 {
+                pgn,
+                source_address,
+            });
+        }
+        let id = number(text.trim())?;
+        match extended {
+            Some(true) => (id <= 0x1FFF_FFFF).then_some(Self::Extended(id)),
+            Some(false) => u16::try_from(id)
+                .ok()
+                .filter(|id| *id <= 0x7FF)
+                .map(Self::Standard),
+            None => match u16::try_from(id) {
+                Ok(id) if id <= 0x7FF => Some(Self::Standard(id)),
+                _ => (id <= 0x1FFF_FFFF).then_some(Self::Extended(id)),
+            },
+        }
+    }
+
+    /// 訊框是否符合識別碼
+    #[must_use]
+    pub fn matches(&self, frame: &CanFrame) -> bool {
+        match *self {
+            Self::Standard(id) => !frame.extended && frame.id == u32::from(id),
+            Self::Extended(id) => frame.extended && frame.id == id,
+            Self::J1939 {
+                pgn,
+                source_address,
+            } => {
+                let mask = if (pgn >> 8) & 0xFF < 240 {
+                    0x03FF_0000
+                } else {
+                    0x03FF_FF00
+                };
+                frame.extended
+                    && frame.id & mask == (pgn << 8) & mask
+                    && source_address.is_none_or(|address| frame.id & 0xFF == u32::from(address))
+            }
+        }
+    }
+
+    /// 可直接以訊框的識別碼查詢的點位
+    const fn key(self) -> Option<u32> {
+        match self {
+            Self::Standard(id) => Some(id as u32),
+            Self::Extended(id) => Some(id | EXTENDED_FLAG),
+            Self::J1939 { .. } => None,
+        }
+    }
+}
+
+impl Display for CanId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Standard(id) => write!(f, "0x{id:03X}"),
+            Self::Extended(id) => write!(f, "0x{id:08X}"),
+            Self::J1939 {
+                pgn,
+                source_address: None,
+            } => write!(f, "PGN {pgn}"),
+            Self::J1939 {
+                pgn,
+                source_address: Some(address),
+            } => write!(f, "PGN {pgn}（來源位址 {address}）"),
+        }
+    }
+}
+
+/// 訊號的位元組順序
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ByteOrder {
+    /// Intel ，起始位元為最低位元
+    #[default]
+    LittleEndian,
+    /// Motorola ，起始位元為最高位元
+    BigEndian,
+}
+
+/// 訊號定義
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CanSignal {
+    /// 起始位元，以第一個位元組的最低位元為 0
+    pub start_bit: u16,
+    /// 位元數
+    pub length: u8,
+    /// 位元組順序
+    pub byte_order: ByteOrder,
+    /// 原始數值是否為二補數有號整數
+    pub signed: bool,
+    /// 倍率
+    pub scale: Option<f64>,
+    /// 偏移量
+    pub offset: Option<f64>,
+}
+
+impl CanSignal {
+    /// 由訊框資料取出原始數值
+    ///
+    /// # 回傳值
+    /// 資料長度不足時回傳 [`None`]
+    #[must_use]
+    pub fn raw(&self, data: &[u8]) -> Option<u64> {
+        let bit = |position: usize| {
+            data.get(position / 8)
+                .map(|byte| u64::from(byte >> (position % 8) & 1))
+        };
+
+        let mut raw = 0;
+        let mut position = usize::from(self.start_bit);
+        match self.byte_order {
+            ByteOrder::LittleEndian => {
+                for index in 0..self.length {
+                    raw |= bit(position + usize::from(index))? << index;
+                }
+            }
+            ByteOrder::BigEndian => {
+                for index in 0..self.length {
+                    raw = (raw << 1) | bit(position)?;
+                    if index + 1 < self.length {
+                        // 由每個位元組的最低位元跳至下一個位元組的最高位元
+                        position = if position % 8 == 0 {
+                            position + 15
+                        } else {
+                            position - 1
+                        };
+                    }
+                }
+            }
+        }
+        Some(raw)
+    }
+
+    /// 取出訊號並轉換為數值，沒有設定倍率與偏移量時回傳整數
+    ///
+    /// # Errors
+    /// 資料長度不足時回傳 [`CanSignalError::TooShort`]
+    ///
+    /// # 範例
+    /// 以內建的測試向量驗證：
+    /// ```rust
+    /// use device_state_exchange_lib::{TargetDefinition, canbus::CanRequest, vectors};
+    /// use serde_json::json;
+    ///
+    /// let report = vectors::built_in_set("canbus/signal").unwrap().verify(|frame, value| {
+    ///     let mut definition = value["signal"].clone();
+    ///     definition["name"] = json!("訊號");
+    ///     definition["address"] = json!("0x100");
+    ///     let definition: TargetDefinition =
+    ///         serde_json::from_value(definition).map_err(|error| error.to_string())?;
+    ///     let signal = CanRequest::parse(&definition).map_err(|error| error.to_string())?.signal;
+    ///     match signal.decode(frame) {
+    ///         Ok(decoded) if decoded == value["value"] => Ok(()),
+    ///         Err(_) if value["value"].is_null() => Ok(()),
+    ///         Ok(decoded) => Err(format!("解碼結果為 {decoded}")),
+    ///         Err(error) => Err(format!("解碼失敗：{error}")),
+    ///     }
+    /// });
+    /// assert!(report.is_complete(), "{report:?}");
+    /// ```
+    #[expect(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
+    pub fn decode(&self, data: &[u8]) -> Result<Value, CanSignalError> {
+        let raw = self
+            .raw(data)
+            .ok_or(CanSignalError::TooShort { length: data.len() })?;
+        let unused = 64 - u32::from(self.length);
+        let signed = ((raw << unused) as i64) >> unused;
+
+        if self.scale.is_none() && self.offset.is_none() {
+            return Ok(if self.signed {
+                Value::from(signed)
+            } else {
+                Value::from(raw)
+            });
+        }
+        let raw = if self.signed {
+            signed as f64
+        } else {
+            raw as f64
+        };
+        Ok(Value::from(raw.mul_add(
+            self.scale.unwrap_or(1.0),
+            self.offset.unwrap_or_default(),
+        )))
+    }
+
+    /// J1939 的特殊數值，參見 SAE J1939-71
+    ///
+    /// 位元數為 8 的倍數時，最高位元組為 `0xFF` 表示無法取得，`0xFE` 表示錯誤；
+    /// 2 位元與 4 位元的狀態值全為 1 表示無法取得，`10` 、`1110` 表示錯誤
+    const fn j1939_indicator(&self, raw: u64) -> Option<CanSignalError> {
+        let top = match self.length {
+            2 => Some((raw, 0b11)),
+            4 => Some((raw, 0b1111)),
+            length if length % 8 == 0 => Some((raw >> (length - 8), 0xFF)),
+            _ => None,
+        };
+        match top {
+            Some((value, all)) if value == all => Some(CanSignalError::NotAvailable),
+            Some((value, all)) if value == all - 1 => Some(CanSignalError::ErrorIndicator),
+            _ => None,
+        }
+    }
+}
+
+/// 點位無法取得數值的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CanSignalError {
+    /// 超過有效期限沒有收到新的訊框
+    Stale {
+        /// 點位的識別碼
+        id: CanId,
+    },
+    /// 訊框的資料長度不足以容納訊號
+    TooShort {
+        /// 訊框的資料長度
+        length: usize,
+    },
+    /// J1939 訊號回報無法取得
+    NotAvailable,
+    /// J1939 訊號回報錯誤
+    ErrorIndicator,
+}
+
+impl Display for CanSignalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stale { id } => write!(f, "{id} 的訊框已超過有效期限"),
+            Self::TooShort { length } => write!(f, "訊框只有 {length} 個位元組，不足以容納訊號"),
+            Self::NotAvailable => write!(f, "訊號回報無法取得"),
+            Self::ErrorIndicator => write!(f, "訊號回報錯誤"),
+        }
+    }
+}
+
+impl Error for CanSignalError {}
+
+/// 點位
+#[derive(Debug, Clone)]
+pub struct CanTarget(pub TargetDefinition);
+
+impl Target for CanTarget {}
+
+/// 讀取請求
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanRequest {
+    /// 識別碼
+    pub id: CanId,
+    /// 訊號定義
+    pub signal: CanSignal,
+    /// 訊框的有效期限，為 [`None`] 時使用 [`CanConfig::stale_after`]
+    pub stale_after: Option<Duration>,
+}
+
+impl DeviceStateRequest for CanRequest {}
+
+impl CanRequest {
+    /// 由通用點位定義轉換，欄位說明參見 [`crate::canbus`]
+    ///
+    /// # Errors
+    /// 識別碼或訊號定義無效時回傳 [`InvalidTarget`]
+    pub fn parse(definition: &TargetDefinition) -> Result<Self, InvalidTarget> {
+        let invalid = |reason: String| InvalidTarget {
+            target: definition.name.clone(),
+            reason,
+        };
+        let extra = |key: &str| definition.extra.get(key);
+        let integer = |key: &str, max: u64| {
+            extra(key)
+                .map(|value| {
+                    value
+                        .as_u64()
+                        .filter(|value| *value <= max)
+                        .ok_or_else(|| invalid(format!("無效的 {key} ：{value}")))
+                })
+                .transpose()
+        };
+        let boolean = |key: &str| {
+            extra(key)
+                .map(|value| {
+                    value
+                        .as_bool()
+                        .ok_or_else(|| invalid(format!("無效的 {key} ：{value}")))
+                })
+                .transpose()
+        };
+        let number = |key: &str| {
+            extra(key)
+                .map(|value| {
+                    value
+                        .as_f64()
+                        .filter(|value| value.is_finite())
+                        .ok_or_else(|| invalid(format!("無效的 {key} ：{value}")))
+                })
+                .transpose()
+        };
+
+        let source_address =
+            integer("source_address", 0xFF)?.and_then(|address| u8::try_from(address).ok());
+        let id = CanId::parse(&definition.address, boolean("extended")?, source_address)
+            .ok_or_else(|| invalid(format!("無效的 CAN ID「{}」", definition.address)))?;
+        let start_bit = integer("start_bit", u64::from(u16::MAX))?
+            .and_then(|bit| u16::try_from(bit).ok())
+            .ok_or_else(|| invalid("缺少 start_bit".to_owned()))?;
+        let length = integer("length", 64)?
+            .and_then(|length| u8::try_from(length).ok())
+            .filter(|length| *length > 0)
+            .ok_or_else(|| invalid("length 需為 1 至 64".to_owned()))?;
+        let byte_order = extra("byte_order")
+            .map(|order| {
+                ByteOrder::deserialize(order)
+                    .map_err(|_| invalid(format!("無效的 byte_order ：{order}")))
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let scale = number("scale")?;
+        if scale.is_some_and(|scale| !scale.is_normal()) {
+            return Err(invalid("scale 不可為 0".to_owned()));
+        }
+
+        Ok(Self {
+            id,
+            signal: CanSignal {
+                start_bit,
+                length,
+                byte_order,
+                signed: boolean("signed")?.unwrap_or_default(),
+                scale,
+                offset: number("offset")?,
+            },
+            stale_after: integer("stale_after_ms", u64::MAX)?.map(Duration::from_millis),
+        })
+    }
+
+    /// 由訊框取出點位的數值
+    ///
+    /// # Errors
+    /// 資料長度不足或 J1939 訊號為特殊數值時回傳 [`CanSignalError`]
+    pub fn extract(&self, frame: &CanFrame) -> Result<Value, CanSignalError> {
+        if matches!(self.id, CanId::J1939 { .. })
+            && let Some(error) = self
+                .signal
+                .raw(&frame.data)
+                .and_then(|raw| self.signal.j1939_indicator(raw))
+        {
+            return Err(error);
+        }
+        self.signal.decode(&frame.data)
+    }
+}
+
+/// 讀取回覆
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanResponse {
+    /// 數值
+    pub value: Value,
+}
+
+impl DeviceStateResponse for CanResponse {
+    fn to_value(&self) -> Result<Cow<'_, Value>, ConversionError> {
+        Ok(Cow::Borrowed(&self.value))
+    }
+}
+
+/// 收到的訊框
+#[derive(Debug, Clone)]
+struct Received {
+    frame: CanFrame,
+    at: Instant,
+}
+
+/// 進行中的 J1939 BAM 傳輸
+#[derive(Debug)]
+struct Transfer {
+    pgn: u32,
+    size: usize,
+    packets: u8,
+    data: Vec<u8>,
+}
+
+/// 各識別碼最新的訊框，與背景工作共用
+#[derive(Debug, Default)]
+struct Frames {
+    latest: Mutex<HashMap<u32, Received>>,
+    connected: AtomicBool,
+    dropped: AtomicU64,
+    notify: Notify,
+}
+
+impl Frames {
+    /// 保留收到的訊框，J1939 BAM 傳輸完成時一併保留重組後的訊框
+    fn receive(&self, transfers: &mut HashMap<u8, Transfer>, frame: CanFrame) {
+        if let Some(reassembled) = reassemble(transfers, &frame) {
+            self.received(reassembled);
+        }
+        self.received(frame);
+    }
+
+    /// 接收中斷，通知等待中的請求
+    fn disconnected(&self) {
+        self.connected.store(false, Ordering::Relaxed);
+        self.notify.notify_waiters();
+    }
+
+    fn received(&self, frame: CanFrame) {
+        self.latest
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                frame.key(),
+                Received {
+                    frame,
+                    at: Instant::now(),
+                },
+            );
+        self.notify.notify_waiters();
+    }
+
+    /// 符合識別碼的最新訊框
+    fn get(&self, id: CanId) -> Option<Received> {
+        let latest = self.latest.lock().unwrap_or_else(PoisonError::into_inner);
+        id.key().map_or_else(
+            || {
+                latest
+                    .values()
+                    .filter(|received| id.matches(&received.frame))
+                    .max_by_key(|received| received.at)
+                    .cloned()
+            },
+            |key| latest.get(&key).cloned(),
+        )
+    }
+}
+
+/// 處理 J1939 BAM 傳輸的訊框，傳輸完成時回傳重組後的訊框
+fn reassemble(transfers: &mut HashMap<u8, Transfer>, frame: &CanFrame) -> Option<CanFrame> {
+    if !frame.extended || frame.id & 0xFF00 != 0xFF00 {
+        return None;
+    }
+    let source = u8::try_from(frame.id & 0xFF).ok()?;
+    match (frame.id >> 16) & 0xFF {
+        TP_CM if frame.data.first() == Some(&BAM) && frame.data.len() >= 8 => {
+            transfers.insert(
+                source,
+                Transfer {
+                    pgn: u32::from_le_bytes([frame.data[5], frame.data[6], frame.data[7], 0]),
+                    size: usize::from(u16::from_le_bytes([frame.data[1], frame.data[2]])),
+                    packets: frame.data[3],
+                    data: Vec::with_capacity(usize::from(frame.data[3]) * 7),
+                },
+            );
+            None
+        }
+        TP_DT => {
+            let transfer = transfers.get_mut(&source)?;
+            let sequence = *frame.data.first()?;
+            // 封包遺失或順序錯誤時放棄本次傳輸
+            if usize::from(sequence) != transfer.data.len() / 7 + 1 || frame.data.len() < 8 {
+                transfers.remove(&source);
+                return None;
+            }
+            transfer.data.extend_from_slice(&frame.data[1..8]);
+            if sequence < transfer.packets {
+                return None;
+            }
+            let mut transfer = transfers.remove(&source)?;
+            transfer.data.truncate(transfer.size);
+            Some(CanFrame {
+                id: (REASSEMBLED_PRIORITY << 26) | (transfer.pgn << 8) | u32::from(source),
+                extended: true,
+                data: transfer.data,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// 讀取一個 socketcand 訊息（`< ... >`）
+async fn read_message(
+    reader: &mut BufReader<OwnedReadHalf>,
+    buffer: &mut Vec<u8>,
+) -> Result<String, ConnectionError> {
+    buffer.clear();
+    loop {
+        let read = reader.read_until(b'>', buffer).await?;
+        if read == 0 {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        if buffer.len() > MAX_MESSAGE_LENGTH {
+            return Err(ConnectionError::Protocol("socketcand 訊息過長".to_owned()));
+        }
+        if buffer.ends_with(b">") {
+            let message = String::from_utf8_lossy(buffer);
+            // 訊息之間可能有換行或空白
+            return Ok(message.trim_start_matches(|c| c != '<').to_owned());
+        }
+    }
+}
+
+/// 等待 socketcand 的回應，`< error ... >` 回傳 [`ConnectionError::Protocol`]
+async fn expect(
+    reader: &mut BufReader<OwnedReadHalf>,
+    buffer: &mut Vec<u8>,
+    expected: &str,
+) -> Result<(), ConnectionError> {
+    let message = read_message(reader, buffer).await?;
+    let words: Vec<_> = message
+        .trim_start_matches('<')
+        .trim_end_matches('>')
+        .split_whitespace()
+        .collect();
+    if words == [expected] {
+        Ok(())
+    } else {
+        Err(ConnectionError::Protocol(format!(
+            "非預期的 socketcand 回應：{message}"
+        )))
+    }
+}
+
+/// 背景工作，持續由 socketcand 接收訊框，連線中斷時結束
+async fn drive(mut reader: BufReader<OwnedReadHalf>, frames: Arc<Frames>) {
+    let mut buffer = Vec::new();
+    let mut transfers = HashMap::default();
+    while let Ok(message) = read_message(&mut reader, &mut buffer).await {
+        if let Some(frame) = CanFrame::parse(&message) {
+            frames.receive(&mut transfers, frame);
+        }
+    }
+    frames.disconnected();
+}
+
+/// 背景工作，持續由 raw socket 接收訊框，介面關閉時結束
+#[cfg(target_os = "linux")]
+async fn drive_socket(socket: device_state_exchange_socketcan::CanSocket, frames: Arc<Frames>) {
+    let mut transfers = HashMap::default();
+    loop {
+        match socket.recv().await {
+            Ok(frame) => {
+                if let Some(frame) = CanFrame::from_socketcan(&frame) {
+                    frames.receive(&mut transfers, frame);
+                }
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::InvalidData => {
+                frames.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(_) => break,
+        }
+    }
+    frames.disconnected();
+}
+
+/// 一次連線，在背景持續接收訊框
+#[derive(Debug)]
+struct Session {
+    task: JoinHandle<()>,
+    peer: Option<SocketAddr>,
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// CAN bus 連線
+pub struct CanConnection {
+    config: CanConfig,
+    session: Option<Session>,
+    frames: Arc<Frames>,
+    remote_address: RemoteAddress,
+    rejected: Vec<InvalidTarget>,
+}
+
+impl CanConnection {
+    /// 初始化點位時被略過的點位
+    #[must_use]
+    pub fn rejected_targets(&self) -> &[InvalidTarget] {
+        &self.rejected
+    }
+
+    /// raw socket 因長度不符而捨棄的訊框數量（如 CAN FD 訊框），重新連線後仍會累計；經由 socketcand 連線時為 0
+    #[must_use]
+    pub fn dropped_frames(&self) -> u64 {
+        self.frames.dropped.load(Ordering::Relaxed)
+    }
+
+    /// 開啟 CAN 介面，並在背景持續接收訊框
+    async fn open(config: &CanConfig, frames: &Arc<Frames>) -> Result<Session, ConnectionError> {
+        match &config.transport {
+            #[cfg(target_os = "linux")]
+            CanTransport::SocketCan => {
+                let socket = device_state_exchange_socketcan::CanSocket::open(&config.interface)?;
+                frames.connected.store(true, Ordering::Relaxed);
+                let task = tokio::spawn(drive_socket(socket, Arc::clone(frames)));
+                Ok(Session { task, peer: None })
+            }
+            #[cfg(not(target_os = "linux"))]
+            CanTransport::SocketCan => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "SocketCAN 只支援 Linux ，請改用 socketcand",
+            )
+            .into()),
+            CanTransport::Socketcand(endpoint) => {
+                Self::open_socketcand(config, endpoint, frames).await
+            }
+        }
+    }
+
+    /// 連線至 socketcand 、開啟 CAN 介面並切換為 raw 模式，並在背景持續接收訊框
+    async fn open_socketcand(
+        config: &CanConfig,
+        endpoint: &TcpEndpoint,
+        frames: &Arc<Frames>,
+    ) -> Result<Session, ConnectionError> {
+        let stream = tokio::time::timeout(config.timeout, tcp::connect(endpoint)).await??;
+        stream.set_nodelay(true)?;
+        let peer = stream.peer_addr().ok();
+        let (read, mut write) = stream.into_split();
+        let mut reader = BufReader::new(read);
+
+        tokio::time::timeout(config.timeout, async {
+            let mut buffer = Vec::new();
+            expect(&mut reader, &mut buffer, "hi").await?;
+            write
+                .write_all(format!("< open {} >", config.interface).as_bytes())
+                .await?;
+            expect(&mut reader, &mut buffer, "ok").await?;
+            write.write_all(b"< rawmode >").await?;
+            expect(&mut reader, &mut buffer, "ok").await
+        })
+        .await??;
+
+        frames.connected.store(true, Ordering::Relaxed);
+        let frames = Arc::clone(frames);
+        let task = tokio::spawn(async move {
+            // 保留寫入端，避免 socketcand 視為連線結束
+            let _write = write;
+            drive(reader, frames).await;
+        });
+        Ok(Session { task, peer })
+    }
+
+    /// 取得連線，連線已中斷時先重新連線
+    async fn session(&mut self) -> Result<&Session, ConnectionError> {
+        if !self.frames.connected.load(Ordering::Relaxed) {
+            self.session = None;
+            self.remote_address.set(None);
+        }
+        if self.session.is_none() {
+            let session = Self::open(&self.config, &self.frames).await?;
+            self.remote_address.set(session.peer);
+            self.session = Some(session);
+        }
+        self.session
+            .as_ref()
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotConnected).into())
+    }
+
+    fn close(&mut self) {
+        self.session = None;
+        self.frames.connected.store(false, Ordering::Relaxed);
+        self.remote_address.set(None);
+    }
+}
+
+impl Connection for CanConnection {
+    const NAMES: &[&str] = &["Can", "J1939"];
+    type Config = CanConfig;
+    type Target = CanTarget;
+    type Request = CanRequest;
+    type Response = CanResponse;
+    type Result = ();
+
+    async fn init(config: &CanConfig) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+        let frames = Arc::new(Frames::default());
+        let session = Self::open(config, &frames).await?;
+        let statistics = ConnectionStats::new(
+            match &config.transport {
+                CanTransport::SocketCan => config.interface.clone(),
+                CanTransport::Socketcand(endpoint) => {
+                    format!("{}:{}/{}", endpoint.host, endpoint.port, config.interface)
+                }
+            },
+            None,
+        );
+        statistics.remote_address.set(session.peer);
+
+        let artifact = ConnectionArtifact::new(
+            Self {
+                config: config.clone(),
+                session: Some(session),
+                frames,
+                remote_address: statistics.remote_address.clone(),
+                rejected: Vec::new(),
+            },
+            statistics,
+        )
+        .update_every(config.update_interval)
+        .timeout_after(config.timeout);
+
+        Ok(match config.max_retry_count {
+            Some(count) => artifact.retry_up_to(count),
+            None => artifact,
+        })
+    }
+
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<CanTarget>,
+    ) -> ConnectionTargets<CanRequest, ()> {
+        let mut inited = Vec::with_capacity(targets.len());
+        for CanTarget(definition) in targets {
+            match CanRequest::parse(&definition) {
+                Ok(request) => inited.push(InitedTarget {
+                    name: definition.name,
+                    request,
+                    result: (),
+                    default_status: definition.default_status,
+                    auto_refresh: definition.auto_refresh,
+                    refresh_interval: None,
+                    keep_raw_frames: None,
+                    group: None,
+                    safe_state: None,
+                    array: None,
+                    change: None,
+                    statistics: Some(connection_statistics.insert_target(definition.device)),
+                }),
+                Err(error) => self.rejected.push(error),
+            }
+        }
+        ConnectionTargets(inited)
+    }
+
+    async fn request_process(
+        &mut self,
+        request: CanRequest,
+    ) -> Result<(CanResponse, bool), ConnectionError> {
+        self.session().await?;
+
+        // 尚未收到訊框時，等待第一個訊框
+        let frames = &self.frames;
+        let received = tokio::time::timeout(self.config.timeout, async {
+            loop {
+                let notified = frames.notify.notified();
+                if let Some(received) = frames.get(request.id) {
+                    return Ok(received);
+                }
+                if !frames.connected.load(Ordering::Relaxed) {
+                    return Err(std::io::Error::from(std::io::ErrorKind::NotConnected));
+                }
+                notified.await;
+            }
+        })
+        .await??;
+        if request
+            .stale_after
+            .or(self.config.stale_after)
+            .is_some_and(|stale_after| received.at.elapsed() > stale_after)
+        {
+            return Err(ConnectionError::custom(CanSignalError::Stale {
+                id: request.id,
+            }));
+        }
+
+        let value = request
+            .extract(&received.frame)
+            .map_err(ConnectionError::custom)?;
+        Ok((CanResponse { value }, true))
+    }
+
+    async fn disconnect(&mut self) -> Result<(), ConnectionError> {
+        self.close();
+        Ok(())
+    }
+
+    async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+        self.close();
+        self.session().await.map(|_| ())
+    }
+
+    async fn update_config(&mut self, new_config: &CanConfig) -> Result<(), ConnectionError> {
+        self.close();
+        self.config = new_config.clone();
+        // 介面可能已變更，捨棄先前的訊框
+        self.frames
+            .latest
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        self.session().await.map(|_| ())
+    }
+}
+
+/// 以記錄下來的訊框解碼點位
+///
+/// `frame` 為 SocketCAN 的 `struct can_frame`（16 個位元組，識別碼為 little-endian ，與 x86 、ARM 上的記憶體內容相同），
+/// `target` 為不含 `name` 的點位定義 JSON 物件；J1939 多封包訊息需要多個訊框，無法以本方式解碼
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::{canbus::CanConnection, fixture::Fixture};
+///
+/// let fixture = Fixture::from_json(r#"{
+///     "connection": "Can",
+///     "cases": [
+///         {
+///             "name": "冷卻水溫度",
+///             "target": "{\"address\": \"pgn:65262\", \"start_bit\": 0, \"length\": 8, \"offset\": -40}",
+///             "frame": "00eefe98 08 000000 7dffffffffffffff",
+///             "expected": 85.0
+///         },
+///         {
+///             "name": "車速",
+///             "target": "{\"address\": \"0x123\", \"start_bit\": 7, \"length\": 16, \"byte_order\": \"big_endian\", \"scale\": 0.01}",
+///             "frame": "23010000 02 000000 1234000000000000",
+///             "expected": 46.6
+///         },
+///         {
+///             "name": "無法取得",
+///             "target": "{\"address\": \"pgn:65262\", \"start_bit\": 0, \"length\": 8, \"offset\": -40}",
+///             "frame": "00eefe98 08 000000 ffffffffffffffff"
+///         },
+///         {
+///             "name": "識別碼不符",
+///             "target": "{\"address\": \"0x124\", \"start_bit\": 0, \"length\": 8}",
+///             "frame": "23010000 02 000000 1234000000000000"
+///         }
+///     ]
+/// }"#).unwrap();
+///
+/// let report = fixture.run::<CanConnection>();
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+impl FrameDecoder for CanConnection {
+    fn decode_frame(target: &str, frame: &[u8]) -> Result<CanResponse, Box<dyn Error>> {
+        let mut definition: serde_json::Map<String, Value> = serde_json::from_str(target)?;
+        definition
+            .entry("name")
+            .or_insert_with(|| Value::from(target));
+        let request =
+            CanRequest::parse(&TargetDefinition::deserialize(Value::Object(definition))?)?;
+        let frame = CanFrame::from_socketcan(frame).ok_or("無效的 SocketCAN 訊框")?;
+        if !request.id.matches(&frame) {
+            return Err(format!("訊框的識別碼與 {} 不符", request.id).into());
+        }
+        Ok(CanResponse {
+            value: request.extract(&frame)?,
+        })
+    }
+}
@@ -0,0 +1,267 @@
+//! 編譯功能查詢
+//!
+//! 大型部署會依需求以不同的 feature 編譯本函式庫，主程式可以在啟動時以 [`features()`] 查詢實際編譯進來的傳輸方式、編碼、壓縮與外部界面，
+//! 再以 [`Capabilities::with_driver()`] 加入主程式支援的設備連線，最後以 [`Capabilities::check()`] 檢查設定檔需要的功能是否都已支援，
+//! 缺少時立即停止並回報需要啟用的 feature ，而不是在執行期間才發生錯誤
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::capability::{CapabilityKind, features};
+//!
+//! let capabilities = features();
+//! assert!(capabilities.is_enabled(CapabilityKind::Transport, "tcp"));
+//!
+//! let missing = capabilities
+//!     .check([(CapabilityKind::Encoding, "json"), (CapabilityKind::Driver, "Modbus")])
+//!     .unwrap_err();
+//! assert_eq!(missing.missing.len(), 1);
+//! println!("{missing}");
+//! ```
+
+use std::{error::Error, fmt::Display};
+
+use serde::Serialize;
+
+use crate::Connection;
+
+/// 本函式庫的版本
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// 功能類別
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CapabilityKind {
+    /// 設備連線，名稱為 [`Connection::NAMES`]
+    Driver,
+    /// 傳輸方式
+    Transport,
+    /// 編碼，參見 [`crate::encoding`]
+    Encoding,
+    /// 壓縮，參見 [`crate::compression`]
+    Compression,
+    /// 外部界面
+    Bridge,
+    /// 點位定義格式，參見 [`crate::definition`]
+    Definition,
+}
+
+impl Display for CapabilityKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Driver => "設備連線",
+            Self::Transport => "傳輸方式",
+            Self::Encoding => "編碼",
+            Self::Compression => "壓縮",
+            Self::Bridge => "外部界面",
+            Self::Definition => "點位定義格式",
+        })
+    }
+}
+
+/// 功能
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct Capability {
+    /// 功能類別
+    pub kind: CapabilityKind,
+    /// 功能名稱
+    pub name: &'static str,
+    /// 版本
+    pub version: &'static str,
+    /// 啟用本功能所需的 feature ，不需要額外啟用時為 [`None`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub feature: Option<&'static str>,
+    /// 是否已編譯進來
+    pub enabled: bool,
+}
+
+const fn built_in(
+    kind: CapabilityKind,
+    name: &'static str,
+    feature: Option<&'static str>,
+    enabled: bool,
+) -> Capability {
+    Capability {
+        kind,
+        name,
+        version: VERSION,
+        feature,
+        enabled,
+    }
+}
+
+/// 內建的功能，包含未啟用的功能
+const BUILT_IN: &[Capability] = &[
+    built_in(CapabilityKind::Transport, "tcp", None, true),
+    built_in(
+        CapabilityKind::Transport,
+        "serial",
+        Some("serial"),
+        cfg!(feature = "serial"),
+    ),
+    built_in(CapabilityKind::Encoding, "json", None, true),
+    built_in(
+        CapabilityKind::Encoding,
+        "cbor",
+        Some("cbor"),
+        cfg!(feature = "cbor"),
+    ),
+    built_in(
+        CapabilityKind::Encoding,
+        "msgpack",
+        Some("msgpack"),
+        cfg!(feature = "msgpack"),
+    ),
+    built_in(
+        CapabilityKind::Compression,
+        "gzip",
+        Some("gzip"),
+        cfg!(feature = "gzip"),
+    ),
+    built_in(
+        CapabilityKind::Compression,
+        "zstd",
+        Some("zstd"),
+        cfg!(feature = "zstd"),
+    ),
+    built_in(
+        CapabilityKind::Bridge,
+        "rest",
+        Some("axum"),
+        cfg!(feature = "axum"),
+    ),
+    built_in(
+        CapabilityKind::Bridge,
+        "proto",
+        Some("proto"),
+        cfg!(feature = "proto"),
+    ),
+    built_in(
+        CapabilityKind::Definition,
+        "csv",
+        Some("csv"),
+        cfg!(feature = "csv"),
+    ),
+];
+
+/// 功能清單
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Capabilities(Vec<Capability>);
+
+/// 取得本函式庫的功能清單
+///
+/// 包含未啟用的功能（[`Capability::enabled`] 為 `false`），以便回報需要啟用的 feature
+#[must_use]
+pub fn features() -> Capabilities {
+    Capabilities(BUILT_IN.to_vec())
+}
+
+impl Capabilities {
+    /// 加入主程式支援的設備連線
+    ///
+    /// # 參數
+    /// - `version`：設備連線實作的版本，通常為實作所在 crate 的 `env!("CARGO_PKG_VERSION")`
+    #[must_use]
+    pub fn with_driver<T: Connection>(mut self, version: &'static str) -> Self {
+        self.0.extend(T::NAMES.iter().map(|name| Capability {
+            kind: CapabilityKind::Driver,
+            name,
+            version,
+            feature: None,
+            enabled: true,
+        }));
+        self
+    }
+
+    /// 所有功能，包含未啟用的功能
+    #[must_use]
+    pub fn all(&self) -> &[Capability] {
+        &self.0
+    }
+
+    /// 已啟用的功能
+    pub fn enabled(&self) -> impl Iterator<Item = &Capability> {
+        self.0.iter().filter(|capability| capability.enabled)
+    }
+
+    /// 功能是否已啟用
+    #[must_use]
+    pub fn is_enabled(&self, kind: CapabilityKind, name: &str) -> bool {
+        self.enabled()
+            .any(|capability| capability.kind == kind && capability.name == name)
+    }
+
+    /// 檢查需要的功能是否都已啟用
+    ///
+    /// # 參數
+    /// - `required`：設定檔需要的功能類別與名稱
+    ///
+    /// # Errors
+    /// 有任何功能未啟用時，回傳所有未啟用的功能
+    pub fn check<'a>(
+        &self,
+        required: impl IntoIterator<Item = (CapabilityKind, &'a str)>,
+    ) -> Result<(), MissingCapabilities> {
+        let missing: Vec<MissingCapability> = required
+            .into_iter()
+            .filter(|(kind, name)| !self.is_enabled(*kind, name))
+            .map(|(kind, name)| MissingCapability {
+                kind,
+                name: name.to_owned(),
+                feature: self
+                    .0
+                    .iter()
+                    .find(|capability| capability.kind == kind && capability.name == name)
+                    .and_then(|capability| capability.feature),
+            })
+            .collect();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(MissingCapabilities { missing })
+        }
+    }
+}
+
+/// 未啟用的功能
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingCapability {
+    /// 功能類別
+    pub kind: CapabilityKind,
+    /// 功能名稱
+    pub name: String,
+    /// 啟用本功能所需的 feature ，未知的功能為 [`None`]
+    pub feature: Option<&'static str>,
+}
+
+/// 設定檔需要的功能未啟用
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingCapabilities {
+    /// 未啟用的功能
+    pub missing: Vec<MissingCapability>,
+}
+
+impl Display for MissingCapabilities {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "有 {} 項需要的功能未啟用：", self.missing.len())?;
+
+        for capability in &self.missing {
+            match capability.feature {
+                Some(feature) => write!(
+                    f,
+                    "\n- {}「{}」：請以 `{feature}` feature 重新編譯",
+                    capability.kind, capability.name
+                )?,
+                None => write!(
+                    f,
+                    "\n- {}「{}」：不支援，請確認名稱是否正確或主程式是否已加入",
+                    capability.kind, capability.name
+                )?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for MissingCapabilities {}
@@ -0,0 +1,120 @@
+//! 重新連線後的追趕模式
+//!
+//! 主程式在每個請求之間等待 [`crate::ConnectionArtifact::update_interval`] ，點位數量多時，重新連線後要等上好幾個間隔才能讓所有點位恢復最新的數值，
+//! 期間外部服務看到的都是斷線前的舊數值
+//!
+//! 實作者可以在 [`crate::Connection::init()`] 中透過 [`crate::ConnectionArtifact::catch_up_every()`] 宣告設備能承受的最短請求間隔，
+//! 主程式則依 [`CatchUp::from_artifact()`] 建立追趕狀態：
+//!
+//! - 在 [`crate::Connection::init()`] 與 [`crate::Connection::reconnect()`] 成功後，以所有自動更新點位調用 [`CatchUp::restart()`] 開始追趕
+//! - 追趕期間優先輪詢 [`CatchUp::pending()`] 中的點位，請求之間改為等待 [`CatchUp::gap()`]
+//! - 每次輪詢完成後（不論成功與否）調用 [`CatchUp::record()`] ，所有點位都輪詢過一次後即恢復正常的更新間隔
+//!
+//! 實作者未宣告最短請求間隔時不會進入追趕模式，避免超出設備的處理能力
+//!
+//! # 範例
+//! ```rust
+//! use std::time::Duration;
+//! use device_state_exchange_lib::catch_up::CatchUp;
+//!
+//! let mut catch_up = CatchUp::new(Some(Duration::from_millis(20)), Duration::from_secs(1));
+//! assert_eq!(catch_up.gap(), Duration::from_secs(1));
+//!
+//! // 重新連線成功
+//! catch_up.restart(["voltage", "current", "power"]);
+//! assert!(catch_up.is_catching_up());
+//! assert_eq!(catch_up.gap(), Duration::from_millis(20));
+//!
+//! catch_up.record("current");
+//! assert_eq!(catch_up.pending().collect::<Vec<_>>(), ["voltage", "power"]);
+//!
+//! catch_up.record("voltage");
+//! catch_up.record("power");
+//! assert!(!catch_up.is_catching_up());
+//! assert_eq!(catch_up.gap(), Duration::from_secs(1));
+//! ```
+
+use std::time::Duration;
+
+use crate::{Connection, ConnectionArtifact};
+
+/// 追趕狀態
+///
+/// 記錄重新連線後尚未輪詢過的點位，並決定請求之間應等待的時間
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatchUp {
+    gap: Option<Duration>,
+    update_interval: Duration,
+    pending: Vec<String>,
+}
+
+impl CatchUp {
+    /// 建立追趕狀態
+    ///
+    /// 建立後尚未開始追趕，請在連線建立後調用 [`CatchUp::restart()`]
+    ///
+    /// # 參數
+    /// - `gap`：追趕期間的請求間隔，為 [`None`] 時不會進入追趕模式
+    /// - `update_interval`：正常的更新間隔
+    #[must_use]
+    pub const fn new(gap: Option<Duration>, update_interval: Duration) -> Self {
+        Self {
+            gap,
+            update_interval,
+            pending: Vec::new(),
+        }
+    }
+
+    /// 依 [`ConnectionArtifact::catch_up_gap`] 與 [`ConnectionArtifact::update_interval`] 建立追趕狀態
+    #[must_use]
+    pub const fn from_artifact<T: Connection>(artifact: &ConnectionArtifact<T>) -> Self {
+        Self::new(artifact.catch_up_gap, artifact.update_interval)
+    }
+
+    /// 開始追趕
+    ///
+    /// 請在 [`crate::Connection::init()`] 與 [`crate::Connection::reconnect()`] 成功後調用，前一次追趕尚未完成時會重新開始
+    ///
+    /// # 參數
+    /// - `targets`：需要追趕的點位名稱，通常為所有自動更新的點位
+    pub fn restart<S: Into<String>>(&mut self, targets: impl IntoIterator<Item = S>) {
+        self.pending.clear();
+        if self.gap.is_some() {
+            self.pending.extend(targets.into_iter().map(Into::into));
+        }
+    }
+
+    /// 記錄點位已輪詢過
+    ///
+    /// # 回傳值
+    /// 點位是否仍在等待追趕
+    pub fn record(&mut self, target: &str) -> bool {
+        let Some(position) = self.pending.iter().position(|pending| pending == target) else {
+            return false;
+        };
+        self.pending.remove(position);
+        true
+    }
+
+    /// 是否仍在追趕
+    #[must_use]
+    pub const fn is_catching_up(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// 尚未輪詢過的點位，依 [`CatchUp::restart()`] 傳入的順序排列
+    pub fn pending(&self) -> impl Iterator<Item = &str> {
+        self.pending.iter().map(String::as_str)
+    }
+
+    /// 請求之間應等待的時間
+    ///
+    /// 追趕期間為實作者宣告的最短請求間隔，與正常的更新間隔取較小者，其餘時間為正常的更新間隔
+    #[must_use]
+    pub fn gap(&self) -> Duration {
+        match self.gap {
+            Some(gap) if self.is_catching_up() => gap.min(self.update_interval),
+            _ => self.update_interval,
+        }
+    }
+}
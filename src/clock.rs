@@ -0,0 +1,137 @@
+//! 時鐘
+//!
+//! 排程請一律使用單調時鐘（[`std::time::Instant`] 、 [`tokio::time`]），作業系統時鐘（[`SystemTime`]）只用於顯示與記錄，
+//! 如此 NTP 校時、手動調整時間或日光節約時間切換都不會造成請求被跳過或連續執行
+//!
+//! 作業系統時鐘被調整時，仍可能影響依賴時間戳記的外部服務，主程式可以利用 [`ClockMonitor`] 偵測並記錄調整次數
+
+use std::{
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use tokio::time::{Interval, MissedTickBehavior};
+
+/// 作業系統時鐘與單調時鐘的差異超過此數值時，視為作業系統時鐘被調整
+pub const CLOCK_ADJUSTMENT_THRESHOLD: Duration = Duration::from_secs(1);
+
+/// 建立依單調時鐘執行的間隔計時器
+///
+/// 主程式處理 [`crate::ConnectionArtifact::update_interval`] 時可以使用本 function ，處理時間超過間隔時，
+/// 下一次執行會從處理完成後重新計算間隔，不會因此跳過請求，也不會為了補上延遲而連續執行
+///
+/// 本 function 需於 tokio runtime 中執行
+///
+/// # 參數
+/// - `period`：間隔時間
+///
+/// # Panics
+/// `period` 為 0 時
+///
+/// # 範例
+/// ```rust
+/// use std::time::Duration;
+/// use device_state_exchange_lib::clock::ticker;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let mut ticker = ticker(Duration::from_millis(10));
+/// ticker.tick().await; // 第一次立即完成
+/// ticker.tick().await;
+/// # }
+/// ```
+#[must_use]
+pub fn ticker(period: Duration) -> Interval {
+    let mut interval = tokio::time::interval(period);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    interval
+}
+
+/// 作業系統時鐘調整
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockAdjustment {
+    /// 調整的毫秒數，正數代表作業系統時鐘被調快
+    pub offset_ms: i64,
+}
+
+/// 作業系統時鐘調整偵測
+///
+/// 比較兩次檢查之間，作業系統時鐘與單調時鐘經過的時間，差異超過 [`CLOCK_ADJUSTMENT_THRESHOLD`] 時視為作業系統時鐘被調整
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件（包含登記至 [`crate::ConnectionStatsRegistry`] 的 [`crate::ConnectionStats`]）會存取同一份資料
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::clock::ClockMonitor;
+///
+/// let monitor = ClockMonitor::new();
+/// assert_eq!(monitor.check(), None);
+/// assert_eq!(monitor.adjusted_count(), 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClockMonitor(Arc<ClockMonitorInner>);
+
+#[derive(Debug)]
+struct ClockMonitorInner {
+    reference: Mutex<(Instant, SystemTime)>,
+    adjusted_count: AtomicU64,
+}
+
+impl Default for ClockMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClockMonitor {
+    /// 建立作業系統時鐘調整偵測，以目前的時間作為基準
+    #[must_use]
+    pub fn new() -> Self {
+        Self(Arc::new(ClockMonitorInner {
+            reference: Mutex::new((Instant::now(), SystemTime::now())),
+            adjusted_count: AtomicU64::new(0),
+        }))
+    }
+
+    /// 檢查作業系統時鐘是否被調整，並以目前的時間作為下次檢查的基準
+    ///
+    /// 主程式可以在每次排程執行時調用
+    ///
+    /// # 回傳值
+    /// 自上次檢查後的調整，沒有調整時為 [`None`]
+    #[must_use]
+    pub fn check(&self) -> Option<ClockAdjustment> {
+        let (instant, wall) = (Instant::now(), SystemTime::now());
+        let (last_instant, last_wall) = std::mem::replace(
+            &mut *self
+                .0
+                .reference
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+            (instant, wall),
+        );
+
+        let expected = last_wall + instant.duration_since(last_instant);
+        let (difference, sign) = match wall.duration_since(expected) {
+            Ok(ahead) => (ahead, 1),
+            Err(behind) => (behind.duration(), -1),
+        };
+
+        if difference < CLOCK_ADJUSTMENT_THRESHOLD {
+            return None;
+        }
+
+        let offset = sign * i64::try_from(difference.as_millis()).unwrap_or(i64::MAX);
+        self.0.adjusted_count.fetch_add(1, Ordering::Relaxed);
+        Some(ClockAdjustment { offset_ms: offset })
+    }
+
+    /// 偵測到作業系統時鐘被調整的次數
+    #[must_use]
+    pub fn adjusted_count(&self) -> u64 {
+        self.0.adjusted_count.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,342 @@
+//! 資料解碼
+//!
+//! 以暫存器為單位存取的協定（如 Modbus）經常將設備名稱、故障訊息、韌體版本等文字拆放在多個暫存器中，
+//! 且各廠商的編碼方式不同（固定長度補空白、以 NUL 結尾、UTF-16 、每個暫存器內的位元組順序相反），
+//! 本模組提供文字解碼的工具，[`TextTarget`] 則將解碼方式包裝為可寫在點位設定中的格式，
+//! 並實作 [`ResultFormatter`] ，設備連線只需要以 [`DeviceData::Bytes`] 回傳原始資料，不需要在每個設備連線的 [`crate::Connection::postprocess()`] 中自行解碼
+//!
+//! 電力品質分析儀等設備會一次回傳整個陣列（如各次諧波、波形取樣），[`ArrayLayout`] 記錄陣列的元素型別與長度，
+//! 並將原始資料轉換為 [`Samples`] ，保留原本的位元組而不是轉換為數字陣列，參見 [`crate::InitedTarget::array`]
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::codec::{Termination, TextEncoding, TextTarget, decode_text, registers_to_bytes};
+//!
+//! // 「PUMP-01」，每個暫存器內的位元組順序相反，以 NUL 補滿 5 個暫存器
+//! let registers = [0x5550, 0x504d, 0x302d, 0x0031, 0x0000];
+//! let bytes = registers_to_bytes(&registers);
+//!
+//! let text = TextTarget {
+//!     encoding: TextEncoding::Ascii,
+//!     termination: Termination::NullTerminated,
+//!     byte_swapped: true,
+//! };
+//! assert_eq!(text.decode(&bytes).unwrap(), "PUMP-01");
+//!
+//! // UTF-16 ，固定長度，尾端以空白補滿
+//! let bytes = registers_to_bytes(&[0x6cf5, 0x6d66, 0x0020]);
+//! assert_eq!(decode_text(&bytes, TextEncoding::Utf16Be, Termination::Fixed).unwrap(), "泵浦");
+//! ```
+//!
+//! 陣列：
+//! ```rust
+//! use bytes::Bytes;
+//! use device_state_exchange_lib::{codec::{ArrayLayout, ByteOrder, ElementType}, value::Samples};
+//!
+//! // 第 1 至 3 次諧波含量（%）
+//! let layout = ArrayLayout {
+//!     element_type: ElementType::F32,
+//!     length: 3,
+//!     byte_order: ByteOrder::BigEndian,
+//! };
+//! assert_eq!(layout.registers(), 6);
+//!
+//! let raw: Vec<u8> = [100.0_f32, 3.5, 1.25].iter().flat_map(|sample| sample.to_be_bytes()).collect();
+//! let samples = layout.decode(Bytes::from(raw)).unwrap();
+//! assert_eq!(samples.to_vec(), [100.0, 3.5, 1.25]);
+//!
+//! // 以 JSON 傳遞時，原始資料以 base64 表示
+//! let json = serde_json::to_value(&samples).unwrap();
+//! assert_eq!(
+//!     json,
+//!     serde_json::json!({ "element_type": "f32", "byte_order": "big_endian", "data": "QsgAAEBgAAA/oAAA" }),
+//! );
+//! assert_eq!(serde_json::from_value::<Samples>(json).unwrap(), samples);
+//!
+//! assert!(layout.decode(Bytes::from_static(&[0; 8])).is_err());
+//! ```
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    format::ResultFormatter,
+    value::{ConversionError, DeviceData, Samples},
+};
+
+/// 文字編碼
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TextEncoding {
+    /// ASCII ，含有超過 `0x7f` 的位元組時視為轉換錯誤
+    #[default]
+    Ascii,
+    /// UTF-8
+    Utf8,
+    /// UTF-16 ，大端序
+    Utf16Be,
+    /// UTF-16 ，小端序
+    Utf16Le,
+}
+
+/// 文字結尾方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Termination {
+    /// 固定長度，移除尾端用於補滿長度的 NUL 與空白
+    #[default]
+    Fixed,
+    /// 以 NUL 結尾，第一個 NUL （UTF-16 為 `0x0000`）之後的資料會被忽略
+    NullTerminated,
+}
+
+/// 將暫存器轉換為位元組，每個暫存器以大端序（高位元組在前）排列
+#[must_use]
+pub fn registers_to_bytes(registers: &[u16]) -> Vec<u8> {
+    registers
+        .iter()
+        .flat_map(|register| register.to_be_bytes())
+        .collect()
+}
+
+/// 交換每兩個位元組的順序，用於每個暫存器內的位元組順序相反的設備，長度為奇數時最後一個位元組維持不變
+#[must_use]
+pub fn swap_bytes(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .chunks(2)
+        .flat_map(|pair| pair.iter().rev().copied())
+        .collect()
+}
+
+/// 解碼文字
+///
+/// # 參數
+/// - `bytes`：原始資料
+/// - `encoding`：文字編碼
+/// - `termination`：文字結尾方式
+///
+/// # Errors
+/// 資料不符合指定的編碼，或 UTF-16 資料長度為奇數時回傳 [`ConversionError::InvalidRaw`]
+pub fn decode_text(
+    bytes: &[u8],
+    encoding: TextEncoding,
+    termination: Termination,
+) -> Result<String, ConversionError> {
+    let text = match encoding {
+        TextEncoding::Ascii | TextEncoding::Utf8 => {
+            let bytes = match termination {
+                Termination::NullTerminated => {
+                    bytes.split(|byte| *byte == 0).next().unwrap_or(bytes)
+                }
+                Termination::Fixed => bytes,
+            };
+
+            if encoding == TextEncoding::Ascii && !bytes.is_ascii() {
+                return Err(ConversionError::InvalidRaw(
+                    "文字含有非 ASCII 字元".to_owned(),
+                ));
+            }
+
+            String::from_utf8(bytes.to_vec()).map_err(|error| {
+                ConversionError::InvalidRaw(format!("文字不是有效的 UTF-8 ：{error}"))
+            })?
+        }
+        TextEncoding::Utf16Be | TextEncoding::Utf16Le => {
+            if !bytes.len().is_multiple_of(2) {
+                return Err(ConversionError::InvalidRaw(format!(
+                    "UTF-16 文字長度為 {} 位元組，不是 2 的倍數",
+                    bytes.len()
+                )));
+            }
+
+            let units = bytes.chunks_exact(2).map(|pair| {
+                if encoding == TextEncoding::Utf16Be {
+                    u16::from_be_bytes([pair[0], pair[1]])
+                } else {
+                    u16::from_le_bytes([pair[0], pair[1]])
+                }
+            });
+            let units: Vec<u16> = match termination {
+                Termination::NullTerminated => units.take_while(|unit| *unit != 0).collect(),
+                Termination::Fixed => units.collect(),
+            };
+
+            String::from_utf16(&units).map_err(|error| {
+                ConversionError::InvalidRaw(format!("文字不是有效的 UTF-16 ：{error}"))
+            })?
+        }
+    };
+
+    Ok(match termination {
+        Termination::Fixed => text.trim_end_matches(['\0', ' ']).to_owned(),
+        Termination::NullTerminated => text,
+    })
+}
+
+/// 文字點位
+///
+/// 記錄文字點位的解碼方式，可直接寫在點位設定中，並以 [`ResultFormatter`] 將設備資料轉換為文字：
+///
+/// - [`DeviceData::Bytes`]：原始資料
+/// - [`DeviceData::Array`] 、[`DeviceData::Samples`]：暫存器數值，每個取樣必須是 0 至 65535 的整數
+/// - [`DeviceData::Scalar`]：已經是文字時原樣輸出
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TextTarget {
+    /// 文字編碼
+    pub encoding: TextEncoding,
+    /// 文字結尾方式
+    pub termination: Termination,
+    /// 每個暫存器內的位元組順序是否相反
+    pub byte_swapped: bool,
+}
+
+impl TextTarget {
+    /// 解碼原始資料
+    ///
+    /// # Errors
+    /// 回傳 [`decode_text()`] 的錯誤
+    pub fn decode(&self, bytes: &[u8]) -> Result<String, ConversionError> {
+        if self.byte_swapped {
+            decode_text(&swap_bytes(bytes), self.encoding, self.termination)
+        } else {
+            decode_text(bytes, self.encoding, self.termination)
+        }
+    }
+
+    /// 解碼暫存器
+    ///
+    /// # Errors
+    /// 回傳 [`decode_text()`] 的錯誤
+    pub fn decode_registers(&self, registers: &[u16]) -> Result<String, ConversionError> {
+        self.decode(&registers_to_bytes(registers))
+    }
+
+    fn format_registers(self, samples: Vec<f64>) -> Result<Value, ConversionError> {
+        samples
+            .into_iter()
+            .map(|sample| {
+                to_register(sample).ok_or_else(|| {
+                    ConversionError::InvalidRaw(format!("{sample} 不是有效的暫存器數值"))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(|registers| self.decode_registers(&registers))
+            .map(Value::String)
+    }
+}
+
+impl<RES> ResultFormatter<RES> for TextTarget {
+    fn format(&self, _: &RES, data: DeviceData) -> Result<Value, ConversionError> {
+        match data {
+            DeviceData::Bytes(bytes) => self.decode(&bytes).map(Value::String),
+            DeviceData::Array(samples) => self.format_registers(samples),
+            DeviceData::Samples(samples) => self.format_registers(samples.to_vec()),
+            DeviceData::Scalar(Value::String(text)) => Ok(Value::String(text)),
+            DeviceData::Scalar(value) => {
+                Err(ConversionError::InvalidRaw(format!("{value} 不是文字")))
+            }
+        }
+    }
+
+    fn format_default(&self, _: &RES, default_status: &Value) -> Value {
+        default_status.clone()
+    }
+}
+
+/// 陣列元素資料型別
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElementType {
+    /// 16 位元無號整數
+    U16,
+    /// 16 位元有號整數
+    I16,
+    /// 32 位元無號整數
+    U32,
+    /// 32 位元有號整數
+    I32,
+    /// 32 位元浮點數
+    F32,
+    /// 64 位元浮點數
+    F64,
+}
+
+impl ElementType {
+    /// 每個元素的位元組數
+    #[must_use]
+    pub const fn size(self) -> usize {
+        match self {
+            Self::U16 | Self::I16 => 2,
+            Self::U32 | Self::I32 | Self::F32 => 4,
+            Self::F64 => 8,
+        }
+    }
+}
+
+/// 多位元組數值的排列方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ByteOrder {
+    /// 大端序（ABCD）
+    #[default]
+    BigEndian,
+    /// 小端序（DCBA）
+    LittleEndian,
+    /// 暫存器以小端序排列，每個暫存器內為大端序（CDAB），常見於以 Modbus 傳遞 32 位元數值的設備
+    WordSwapped,
+}
+
+/// 陣列格式
+///
+/// 記錄陣列點位的元素型別、長度與排列方式，可直接寫在點位設定中，參見 [`crate::InitedTarget::array`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ArrayLayout {
+    /// 元素資料型別
+    pub element_type: ElementType,
+    /// 元素數量
+    pub length: usize,
+    /// 排列方式，預設為 [`ByteOrder::BigEndian`]
+    #[serde(default)]
+    pub byte_order: ByteOrder,
+}
+
+impl ArrayLayout {
+    /// 整個陣列的位元組數
+    #[must_use]
+    pub const fn byte_len(&self) -> usize {
+        self.element_type.size() * self.length
+    }
+
+    /// 整個陣列佔用的 16 位元暫存器數量，用於決定讀取範圍
+    #[must_use]
+    pub const fn registers(&self) -> usize {
+        self.byte_len().div_ceil(2)
+    }
+
+    /// 將原始資料轉換為取樣陣列，不會複製資料
+    ///
+    /// # Errors
+    /// 原始資料長度與 [`ArrayLayout::byte_len()`] 不符時回傳 [`ConversionError::InvalidRaw`]
+    pub fn decode(&self, data: Bytes) -> Result<Samples, ConversionError> {
+        if data.len() != self.byte_len() {
+            return Err(ConversionError::InvalidRaw(format!(
+                "陣列應為 {} 位元組，實際為 {} 位元組",
+                self.byte_len(),
+                data.len()
+            )));
+        }
+
+        Samples::new(self.element_type, self.byte_order, data)
+    }
+}
+
+/// 將取樣轉換為暫存器數值，不是 0 至 65535 的整數時回傳 [`None`]
+fn to_register(sample: f64) -> Option<u16> {
+    if sample.fract() != 0.0 || !(0.0..=f64::from(u16::MAX)).contains(&sample) {
+        return None;
+    }
+
+    format!("{sample:.0}").parse().ok()
+}
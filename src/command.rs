@@ -0,0 +1,341 @@
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::Display,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicI64, AtomicU64, Ordering},
+    },
+    time::Instant,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    ClientId, Connection, ConnectionError, ConnectionTargets, CorrelationId, RequestContext,
+};
+
+/// 寫入指令
+///
+/// 外部界面要求變更點位狀態時產生，由主程式取出後，以 [`execute()`] 交由設備連線的 [`crate::Connection::write_process()`] 寫入；
+/// 未實作寫入的連線，仍可以透過 [`crate::Connection::preprocess()`] 的 `new_status` 參數傳遞給設備連線
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriteCommand {
+    /// 點位名稱
+    pub target: String,
+    /// 欲寫入的新狀態
+    pub value: Value,
+    /// 請求來源，由主程式（如數位分身同步）產生或無法識別時為 [`None`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<ClientId>,
+    /// 請求追蹤編號，參見 [`RequestContext::correlation`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation: Option<CorrelationId>,
+}
+
+impl WriteCommand {
+    /// 取得指令的請求資訊，參見 [`crate::Connection::preprocess_with_context()`]
+    #[must_use]
+    pub fn context(&self) -> RequestContext {
+        RequestContext {
+            client: self.client.clone(),
+            correlation: self.correlation.clone(),
+        }
+    }
+}
+
+/// 寫入指令佇列
+///
+/// 外部界面透過 [`CommandQueue::push()`] 加入指令，主程式在處理自動更新點位前，需先利用 [`CommandQueue::pop()`] 取出待處理的指令，
+/// 對應 [`crate::Connection::request_process()`] 中「主程式會先處理由外部服務傳入的請求」的行為
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一個佇列
+#[derive(Debug, Clone, Default)]
+pub struct CommandQueue {
+    commands: Arc<Mutex<VecDeque<WriteCommand>>>,
+}
+
+impl CommandQueue {
+    /// 建立空的寫入指令佇列
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 將指令加入佇列尾端
+    pub fn push(&self, command: WriteCommand) {
+        self.commands
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push_back(command);
+    }
+
+    /// 從佇列前端取出指令
+    #[must_use]
+    pub fn pop(&self) -> Option<WriteCommand> {
+        self.commands
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop_front()
+    }
+
+    /// 待處理的指令數量
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.commands
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// 佇列是否為空
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// 執行寫入指令
+///
+/// 依序調用 [`Connection::write_preprocess()`] 、[`Connection::write_process()`] 與 [`Connection::write_postprocess()`] ，
+/// 點位有 [`crate::InitedTarget::statistics`] 時，會以 [`crate::TargetStats::record_write_success()`] 或
+/// [`crate::TargetStats::record_write_failure()`] 記錄 [`Connection::write_process()`] 的結果，預處理拒絕的指令不會被記錄
+///
+/// 回讀的狀態不會自動寫入 [`crate::StateStore`] ，主程式需要時請以 [`crate::StateStore::update_response()`] 寫入
+///
+/// # 參數
+/// - `connection`：設備連線
+/// - `targets`：連線的點位
+/// - `command`：寫入指令
+///
+/// # 回傳值
+/// 寫入結果
+///
+/// # Errors
+/// 點位不存在時回傳 [`WriteError::UnknownTarget`] ，設備連線回傳錯誤時回傳 [`WriteError::Connection`]
+///
+/// # 範例
+/// ```rust
+/// # use std::error::Error;
+/// # use device_state_exchange_lib::*;
+/// use device_state_exchange_lib::command::{self, UnsupportedWrite, WriteError};
+/// use serde_json::{Value, json};
+///
+/// # #[derive(Debug, Clone)] struct Config;
+/// # impl ConnectionConfig for Config {}
+/// # #[derive(Debug, Clone)] struct Point;
+/// # impl Target for Point {}
+/// # #[derive(Debug, Clone)] struct Register(u16);
+/// # impl DeviceStateRequest for Register {}
+/// # #[derive(Debug, Clone)] struct Response(Value);
+/// # impl DeviceStateResponse for Response { fn to_value(&self) -> Result<std::borrow::Cow<'_, Value>, value::ConversionError> { Ok(std::borrow::Cow::Borrowed(&self.0)) } }
+/// #[derive(Debug, Clone)]
+/// struct WriteRegister {
+///     address: u16,
+///     value: u16,
+/// }
+///
+/// impl DeviceStateWrite for WriteRegister {}
+///
+/// struct Plc {
+///     registers: [u16; 16],
+/// }
+///
+/// impl Connection for Plc {
+/// #   const NAMES: &[&str] = &["Plc"];
+/// #   type Config = Config;
+/// #   type Target = Point;
+/// #   type Request = Register;
+/// #   type Response = Response;
+/// #   type Result = ();
+/// #   async fn init(_: &Config) -> Result<ConnectionArtifact<Self>, ConnectionError> { unimplemented!() }
+/// #   fn init_targets(&mut self, _: &mut ConnectionStats, _: Vec<Point>) -> ConnectionTargets<Register, ()> { unimplemented!() }
+/// #   async fn request_process(&mut self, _: Register) -> Result<(Response, bool), ConnectionError> { unimplemented!() }
+/// #   async fn reconnect(&mut self) -> Result<(), ConnectionError> { Ok(()) }
+/// #   async fn update_config(&mut self, _: &Config) -> Result<(), ConnectionError> { Ok(()) }
+///     fn write_preprocess(&self, Register(address): Register, value: Value, _: &RequestContext) -> Result<Box<dyn DeviceStateWrite>, ConnectionError> {
+///         let value = value
+///             .as_u64()
+///             .and_then(|value| u16::try_from(value).ok())
+///             .ok_or_else(|| ConnectionError::InvalidConfig(format!("無效的設定值：{value}")))?;
+///         Ok(Box::new(WriteRegister { address, value }))
+///     }
+///
+///     async fn write_process(&mut self, write: Box<dyn DeviceStateWrite>) -> Result<Option<Response>, ConnectionError> {
+///         let WriteRegister { address, value } = *write.downcast::<WriteRegister>().map_err(|_| "非預期的寫入請求")?;
+///         self.registers[usize::from(address)] = value;
+///         Ok(Some(Response(json!(self.registers[usize::from(address)]))))
+///     }
+/// }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let mut statistics = ConnectionStats::new("COM1", None);
+/// let device = statistics.insert_target(Some("1".to_owned()));
+/// let targets = ConnectionTargets(vec![InitedTarget { name: "setpoint".to_owned(), request: Register(3), result: (), default_status: None, auto_refresh: false, refresh_interval: None, keep_raw_frames: None, group: None, safe_state: None, array: None, change: None, statistics: Some(device) }]);
+/// let mut plc = Plc { registers: [0; 16] };
+///
+/// let setpoint = |value| WriteCommand { target: "setpoint".to_owned(), value, client: None, correlation: None };
+/// let outcome = command::execute(&mut plc, &targets, &setpoint(json!(250))).await.unwrap();
+/// assert_eq!(outcome.response.unwrap().to_value_lossy(), 250);
+///
+/// assert!(matches!(command::execute(&mut plc, &targets, &setpoint(json!(-1))).await, Err(WriteError::Connection(_))));
+/// assert!(matches!(
+///     command::execute(&mut plc, &targets, &WriteCommand { target: "mode".to_owned(), ..setpoint(json!(1)) }).await,
+///     Err(WriteError::UnknownTarget(_))
+/// ));
+///
+/// let writes = statistics.snapshot().targets[0].writes;
+/// assert_eq!((writes.total_write_count, writes.failed_write_count), (1, 0));
+/// # }
+/// ```
+pub async fn execute<T: Connection>(
+    connection: &mut T,
+    targets: &ConnectionTargets<T::Request, T::Result>,
+    command: &WriteCommand,
+) -> Result<WriteOutcome<T::Response>, WriteError>
+where
+    T::Result: Sync,
+{
+    let target = targets
+        .0
+        .iter()
+        .find(|target| target.name == command.target)
+        .ok_or_else(|| WriteError::UnknownTarget(command.target.clone()))?;
+    let statistics = target.statistics.clone();
+
+    let write = connection.write_preprocess(
+        dyn_clone::clone(&target.request),
+        command.value.clone(),
+        &command.context(),
+    )?;
+
+    let started = Instant::now();
+    let response = connection.write_process(write.clone()).await;
+    let response_ms = i64::try_from(started.elapsed().as_millis()).unwrap_or(i64::MAX);
+
+    if let Some(statistics) = statistics {
+        match &response {
+            Ok(_) => statistics.record_write_success(response_ms),
+            Err(_) => statistics.record_write_failure(),
+        }
+    }
+
+    Ok(WriteOutcome {
+        target: command.target.clone(),
+        response: connection.write_postprocess(write.as_ref(), response?)?,
+        response_ms,
+    })
+}
+
+/// 寫入結果
+#[derive(Debug)]
+pub struct WriteOutcome<RES> {
+    /// 點位名稱
+    pub target: String,
+    /// 設備回讀的狀態，參見 [`Connection::write_process()`]
+    pub response: Option<RES>,
+    /// 本次寫入所花費的毫秒數
+    pub response_ms: i64,
+}
+
+/// 寫入錯誤
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WriteError {
+    /// 指令的點位不存在於連線中
+    UnknownTarget(String),
+    /// 設備連線回傳錯誤
+    Connection(ConnectionError),
+}
+
+impl Display for WriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownTarget(target) => write!(f, "點位「{target}」不存在"),
+            Self::Connection(error) => write!(f, "寫入失敗：{error}"),
+        }
+    }
+}
+
+impl Error for WriteError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Connection(error) => Some(error),
+            Self::UnknownTarget(_) => None,
+        }
+    }
+}
+
+impl From<ConnectionError> for WriteError {
+    fn from(error: ConnectionError) -> Self {
+        Self::Connection(error)
+    }
+}
+
+/// 連線不支援寫入
+///
+/// [`Connection::write_preprocess()`] 與 [`Connection::write_process()`] 的預設實作會回傳本錯誤，
+/// 可以利用 [`ConnectionError::downcast_ref()`] 與寫入失敗區分
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedWrite;
+
+impl Display for UnsupportedWrite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "連線不支援寫入")
+    }
+}
+
+impl Error for UnsupportedWrite {}
+
+/// 寫入統計快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriteStatsSnapshot {
+    /// 寫入次數
+    pub total_write_count: u64,
+    /// 寫入失敗次數
+    pub failed_write_count: u64,
+    /// 最近一次成功寫入所花費的毫秒數
+    pub last_response_ms: i64,
+}
+
+impl WriteStatsSnapshot {
+    /// 是否沒有任何寫入紀錄
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.total_write_count == 0
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct WriteCounters {
+    total: AtomicU64,
+    failed: AtomicU64,
+    last_response_ms: AtomicI64,
+}
+
+impl WriteCounters {
+    pub(crate) fn record(&self, succeeded: bool, response_ms: i64) {
+        self.total.fetch_add(1, Ordering::Relaxed);
+        if succeeded {
+            self.last_response_ms.store(response_ms, Ordering::Relaxed);
+        } else {
+            self.failed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> WriteStatsSnapshot {
+        WriteStatsSnapshot {
+            total_write_count: self.total.load(Ordering::Relaxed),
+            failed_write_count: self.failed.load(Ordering::Relaxed),
+            last_response_ms: self.last_response_ms.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        self.total.store(0, Ordering::Relaxed);
+        self.failed.store(0, Ordering::Relaxed);
+        self.last_response_ms.store(0, Ordering::Relaxed);
+    }
+}
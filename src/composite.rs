@@ -0,0 +1,523 @@
+//! 閘道器組合連線
+//!
+//! 協定轉換器（閘道器）常以一個上游連線同時轉接多種設備，各設備型態已經有各自的 [`Connection`] 實作，
+//! [`CompositeConnection`] 將兩個子連線組合為一個 [`Connection`] ，讓主程式將其視為同一組設備：
+//!
+//! - 點位依 [`Routed`] 分配給所屬的子連線，請求也會交由建立該請求的子連線處理
+//! - 子連線在 [`Connection::init_targets()`] 中取得的是同一份 [`ConnectionStats`] ，統計數據會合併計算，因此各子連線的設備編號不可重複
+//! - 保持連線、中斷連線、重新連線與更新設定檔會依序套用至所有子連線
+//!
+//! 三個以上的子連線，可以將 [`CompositeConnection`] 作為子連線再次組合
+//!
+//! # 範例
+//! ```rust
+//! # use std::{borrow::Cow, error::Error};
+//! # use device_state_exchange_lib::{*, composite::{CompositeConfig, CompositeConnection, Gateway, Routed}};
+//! # #[derive(Debug, Clone)] struct Config;
+//! # impl ConnectionConfig for Config {}
+//! # #[derive(Debug, Clone)] struct Point(&'static str);
+//! # impl Target for Point {}
+//! # #[derive(Debug, Clone)] struct Request(&'static str);
+//! # impl DeviceStateRequest for Request {}
+//! # #[derive(Debug, Clone)] struct Response(&'static str);
+//! # impl DeviceStateResponse for Response { fn to_value(&self) -> Result<Cow<'_, serde_json::Value>, value::ConversionError> { Ok(Cow::Owned(self.0.into())) } }
+//! # macro_rules! device {
+//! #     ($name:ident, $port:literal) => {
+//! #         struct $name;
+//! #         impl Connection for $name {
+//! #             const NAMES: &[&str] = &[stringify!($name)];
+//! #             type Config = Config;
+//! #             type Target = Point;
+//! #             type Request = Request;
+//! #             type Response = Response;
+//! #             type Result = ();
+//! #             async fn init(_: &Config) -> Result<ConnectionArtifact<Self>, ConnectionError> { Ok(ConnectionArtifact::new($name, ConnectionStats::new($port, None))) }
+//! #             fn init_targets(&mut self, _: &mut ConnectionStats, targets: Vec<Point>) -> ConnectionTargets<Request, ()> {
+//! #                 ConnectionTargets(targets.into_iter().map(|Point(name)| InitedTarget { name: name.to_owned(), request: Request(name), result: (), default_status: None, auto_refresh: true, refresh_interval: None, keep_raw_frames: None, group: None, safe_state: None, array: None, change: None, statistics: None }).collect())
+//! #             }
+//! #             async fn request_process(&mut self, _: Request) -> Result<(Response, bool), ConnectionError> { Ok((Response(concat!(stringify!($name), ":", $port)), true)) }
+//! #             async fn reconnect(&mut self) -> Result<(), ConnectionError> { Ok(()) }
+//! #             async fn update_config(&mut self, _: &Config) -> Result<(), ConnectionError> { Ok(()) }
+//! #         }
+//! #     };
+//! # }
+//! // `Meter` 與 `Thermostat` 為既有的連線實作
+//! # device!(Meter, "gateway");
+//! # device!(Thermostat, "gateway");
+//! struct ProtocolConverter;
+//!
+//! impl Gateway for ProtocolConverter {
+//!     const NAMES: &[&str] = &["ProtocolConverter"];
+//! }
+//!
+//! type ConverterConnection = CompositeConnection<ProtocolConverter, Meter, Thermostat>;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let mut artifact = ConverterConnection::init(&CompositeConfig { first: Config, second: Config }).await.unwrap();
+//! let targets = artifact.artifact.init_targets(
+//!     &mut artifact.statistics,
+//!     vec![Routed::First(Point("電能")), Routed::Second(Point("溫度"))],
+//! );
+//!
+//! let request = targets.0[1].request.clone();
+//! let (response, _) = artifact.artifact.request_process(request).await.unwrap();
+//! assert_eq!(response.to_value_lossy(), "Thermostat:gateway");
+//! # }
+//! ```
+
+use std::{borrow::Cow, error::Error, fmt::Display, marker::PhantomData};
+
+use dyn_clone::DynClone;
+use serde_json::{Value, json};
+
+use crate::{
+    Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+    ConnectionTargets, DeviceStateRequest, DeviceStateResponse, DeviceStateWrite, InitedTarget,
+    RequestContext, Target,
+    diagnostics::{DiagnosticsCommand, UnsupportedDiagnostics},
+    retry::RetryPolicy,
+    session::{ReconnectHint, ReconnectOutcome},
+    value::{ConversionError, DeviceData},
+};
+
+/// 閘道器的設備型態名稱
+///
+/// [`CompositeConnection`] 無法自動合併子連線的 [`Connection::NAMES`] ，請以一個空的 struct 實作本 trait ，定義組合後的設備型態名稱
+pub trait Gateway: Send + 'static {
+    /// 設備型態名稱列表，參見 [`Connection::NAMES`]
+    const NAMES: &[&str];
+}
+
+/// 所屬子連線
+///
+/// 用於組合後的點位、請求、回覆與回覆給外部服務的資料，`First` 屬於第一個子連線，`Second` 屬於第二個子連線
+#[derive(Debug)]
+pub enum Routed<A, B> {
+    /// 屬於第一個子連線
+    First(A),
+    /// 屬於第二個子連線
+    Second(B),
+}
+
+impl<A: DynClone, B: DynClone> Clone for Routed<A, B> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::First(first) => Self::First(dyn_clone::clone(first)),
+            Self::Second(second) => Self::Second(dyn_clone::clone(second)),
+        }
+    }
+}
+
+impl<A: Target, B: Target> Target for Routed<A, B> {}
+
+impl<A: DeviceStateRequest, B: DeviceStateRequest> DeviceStateRequest for Routed<A, B> {}
+
+/// 組合連線的寫入請求，由 [`CompositeConnection`] 的 [`Connection::write_preprocess()`] 產生
+impl DeviceStateWrite for Routed<Box<dyn DeviceStateWrite>, Box<dyn DeviceStateWrite>> {}
+
+type RoutedWrite = Routed<Box<dyn DeviceStateWrite>, Box<dyn DeviceStateWrite>>;
+
+impl<A: DeviceStateResponse, B: DeviceStateResponse> DeviceStateResponse for Routed<A, B> {
+    fn to_value(&self) -> Result<Cow<'_, Value>, ConversionError> {
+        match self {
+            Self::First(first) => first.to_value(),
+            Self::Second(second) => second.to_value(),
+        }
+    }
+
+    fn to_data(&self) -> Result<DeviceData, ConversionError> {
+        match self {
+            Self::First(first) => first.to_data(),
+            Self::Second(second) => second.to_data(),
+        }
+    }
+
+    fn raw(&self) -> Option<bytes::Bytes> {
+        match self {
+            Self::First(first) => first.raw(),
+            Self::Second(second) => second.raw(),
+        }
+    }
+}
+
+/// 組合連線的設定檔
+#[derive(Debug, Clone)]
+pub struct CompositeConfig<A, B> {
+    /// 第一個子連線的設定檔
+    pub first: A,
+    /// 第二個子連線的設定檔
+    pub second: B,
+}
+
+impl<A: ConnectionConfig, B: ConnectionConfig> ConnectionConfig for CompositeConfig<A, B> {}
+
+/// 請求與回覆不屬於同一個子連線
+///
+/// [`CompositeConnection::postprocess()`] 收到的請求與回覆分屬不同子連線時回傳，代表主程式將回覆交給了錯誤的請求
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoutingMismatch;
+
+impl Display for RoutingMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "請求與回覆不屬於同一個子連線")
+    }
+}
+
+impl Error for RoutingMismatch {}
+
+/// 閘道器組合連線
+///
+/// 泛型 `G` 定義組合後的設備型態名稱（參見 [`Gateway`]），`A` 與 `B` 為子連線
+///
+/// [`Connection::init()`] 回傳的設定會依下列方式合併：
+///
+/// - 更新間隔與保持連線間隔取較短者，逾時取較長者
+/// - 最高重試次數取較小者，閒置中斷時間只有在兩個子連線都設定時才會啟用，並取較長者
+/// - 同時處理中的請求數量上限取較小者
+/// - 執行位置優先採用第一個子連線要求的隔離位置（參見 [`crate::execution::ExecutionHint::is_isolated()`]）
+/// - 暖機時間取較長者，暖機期間的數值處理方式優先採用第一個有設定暖機時間的子連線
+/// - 連線統計數據、統計數據設定採用第一個子連線的設定，設備探索報告優先採用第一個子連線的報告
+pub struct CompositeConnection<G: Gateway, A: Connection, B: Connection> {
+    first: A,
+    second: B,
+    gateway: PhantomData<G>,
+}
+
+impl<G: Gateway, A: Connection, B: Connection> CompositeConnection<G, A, B> {
+    /// 第一個子連線
+    #[must_use]
+    pub const fn first(&mut self) -> &mut A {
+        &mut self.first
+    }
+
+    /// 第二個子連線
+    #[must_use]
+    pub const fn second(&mut self) -> &mut B {
+        &mut self.second
+    }
+}
+
+type RoutedTargets<A, B> = ConnectionTargets<
+    Routed<<A as Connection>::Request, <B as Connection>::Request>,
+    Routed<<A as Connection>::Result, <B as Connection>::Result>,
+>;
+
+impl<G: Gateway, A: Connection, B: Connection> Connection for CompositeConnection<G, A, B> {
+    const NAMES: &[&str] = G::NAMES;
+    type Config = CompositeConfig<A::Config, B::Config>;
+    type Target = Routed<A::Target, B::Target>;
+    type Request = Routed<A::Request, B::Request>;
+    type Response = Routed<A::Response, B::Response>;
+    type Result = Routed<A::Result, B::Result>;
+
+    async fn init(config: &Self::Config) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+        let first = A::init(&config.first).await?;
+        let second = B::init(&config.second).await?;
+
+        Ok(ConnectionArtifact {
+            artifact: Self {
+                first: first.artifact,
+                second: second.artifact,
+                gateway: PhantomData,
+            },
+            max_retry_count: either_min(first.max_retry_count, second.max_retry_count),
+            retry_policy: match first.retry_policy {
+                RetryPolicy::Immediate => second.retry_policy,
+                policy => policy,
+            },
+            update_interval: first.update_interval.min(second.update_interval),
+            timeout: first.timeout.max(second.timeout),
+            keepalive_interval: either_min(first.keepalive_interval, second.keepalive_interval),
+            idle_timeout: first
+                .idle_timeout
+                .zip(second.idle_timeout)
+                .map(|(first, second)| first.max(second)),
+            max_in_flight: first.max_in_flight.min(second.max_in_flight),
+            execution: if first.execution.is_isolated() {
+                first.execution
+            } else {
+                second.execution
+            },
+            warmup: first.warmup.max(second.warmup),
+            settle_policy: if first.warmup.is_some() {
+                first.settle_policy
+            } else {
+                second.settle_policy
+            },
+            poll_budget: either_min(first.poll_budget, second.poll_budget),
+            catch_up_gap: first
+                .catch_up_gap
+                .zip(second.catch_up_gap)
+                .map(|(first, second)| first.max(second)),
+            statistics: first.statistics,
+            stats_config: first.stats_config,
+            discovery: first.discovery.or(second.discovery),
+        })
+    }
+
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<Self::Target>,
+    ) -> RoutedTargets<A, B> {
+        let (first, second) = targets.into_iter().fold(
+            (Vec::new(), Vec::new()),
+            |(mut first, mut second), target| {
+                match target {
+                    Routed::First(target) => first.push(target),
+                    Routed::Second(target) => second.push(target),
+                }
+                (first, second)
+            },
+        );
+
+        let first = self
+            .first
+            .init_targets(connection_statistics, first)
+            .0
+            .into_iter()
+            .map(|target| route(target, Routed::First, Routed::First));
+        let second = self
+            .second
+            .init_targets(connection_statistics, second)
+            .0
+            .into_iter()
+            .map(|target| route(target, Routed::Second, Routed::Second));
+
+        ConnectionTargets(first.chain(second).collect())
+    }
+
+    fn preprocess(
+        &self,
+        request: Self::Request,
+        new_status: Option<Value>,
+    ) -> Result<Self::Request, ConnectionError> {
+        match request {
+            Routed::First(request) => self
+                .first
+                .preprocess(request, new_status)
+                .map(Routed::First),
+            Routed::Second(request) => self
+                .second
+                .preprocess(request, new_status)
+                .map(Routed::Second),
+        }
+    }
+
+    fn preprocess_with_context(
+        &self,
+        request: Self::Request,
+        new_status: Option<Value>,
+        context: &RequestContext,
+    ) -> Result<Self::Request, ConnectionError> {
+        match request {
+            Routed::First(request) => self
+                .first
+                .preprocess_with_context(request, new_status, context)
+                .map(Routed::First),
+            Routed::Second(request) => self
+                .second
+                .preprocess_with_context(request, new_status, context)
+                .map(Routed::Second),
+        }
+    }
+
+    async fn request_process(
+        &mut self,
+        request: Self::Request,
+    ) -> Result<(Self::Response, bool), ConnectionError> {
+        match request {
+            Routed::First(request) => self
+                .first
+                .request_process(request)
+                .await
+                .map(|(response, wait)| (Routed::First(response), wait)),
+            Routed::Second(request) => self
+                .second
+                .request_process(request)
+                .await
+                .map(|(response, wait)| (Routed::Second(response), wait)),
+        }
+    }
+
+    fn postprocess(
+        &self,
+        request: Self::Request,
+        response: Self::Response,
+    ) -> Result<Self::Response, ConnectionError> {
+        match (request, response) {
+            (Routed::First(request), Routed::First(response)) => {
+                self.first.postprocess(request, response).map(Routed::First)
+            }
+            (Routed::Second(request), Routed::Second(response)) => self
+                .second
+                .postprocess(request, response)
+                .map(Routed::Second),
+            _ => Err(ConnectionError::custom(RoutingMismatch)),
+        }
+    }
+
+    fn write_preprocess(
+        &self,
+        request: Self::Request,
+        value: Value,
+        context: &RequestContext,
+    ) -> Result<Box<dyn DeviceStateWrite>, ConnectionError> {
+        let write: RoutedWrite = match request {
+            Routed::First(request) => {
+                Routed::First(self.first.write_preprocess(request, value, context)?)
+            }
+            Routed::Second(request) => {
+                Routed::Second(self.second.write_preprocess(request, value, context)?)
+            }
+        };
+        Ok(Box::new(write))
+    }
+
+    async fn write_process(
+        &mut self,
+        write: Box<dyn DeviceStateWrite>,
+    ) -> Result<Option<Self::Response>, ConnectionError> {
+        let write = write
+            .downcast::<RoutedWrite>()
+            .map_err(|_| ConnectionError::custom(RoutingMismatch))?;
+
+        match *write {
+            Routed::First(write) => Ok(self.first.write_process(write).await?.map(Routed::First)),
+            Routed::Second(write) => {
+                Ok(self.second.write_process(write).await?.map(Routed::Second))
+            }
+        }
+    }
+
+    fn write_postprocess(
+        &self,
+        write: &dyn DeviceStateWrite,
+        response: Option<Self::Response>,
+    ) -> Result<Option<Self::Response>, ConnectionError> {
+        match (write.downcast_ref::<RoutedWrite>(), response) {
+            (Some(Routed::First(write)), None) => self
+                .first
+                .write_postprocess(write.as_ref(), None)
+                .map(|response| response.map(Routed::First)),
+            (Some(Routed::First(write)), Some(Routed::First(response))) => self
+                .first
+                .write_postprocess(write.as_ref(), Some(response))
+                .map(|response| response.map(Routed::First)),
+            (Some(Routed::Second(write)), None) => self
+                .second
+                .write_postprocess(write.as_ref(), None)
+                .map(|response| response.map(Routed::Second)),
+            (Some(Routed::Second(write)), Some(Routed::Second(response))) => self
+                .second
+                .write_postprocess(write.as_ref(), Some(response))
+                .map(|response| response.map(Routed::Second)),
+            _ => Err(ConnectionError::custom(RoutingMismatch)),
+        }
+    }
+
+    async fn keepalive(&mut self) -> Result<(), ConnectionError> {
+        self.first.keepalive().await?;
+        self.second.keepalive().await
+    }
+
+    /// 先交由第一個子連線執行，第一個子連線不支援時，再交由第二個子連線執行
+    async fn diagnostics(&mut self, command: DiagnosticsCommand) -> Result<Value, ConnectionError> {
+        let command = match self.first.diagnostics(command).await {
+            Err(error) => error.downcast::<UnsupportedDiagnostics>()?.0,
+            result => return result,
+        };
+
+        self.second.diagnostics(command).await
+    }
+
+    /// 第一個子連線中斷失敗時，仍會中斷第二個子連線
+    async fn disconnect(&mut self) -> Result<(), ConnectionError> {
+        let first = self
+            .first
+            .disconnect()
+            .await
+            .map_err(|error| error.to_string());
+        self.second.disconnect().await?;
+        first.map_err(Into::into)
+    }
+
+    async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+        self.first.reconnect().await?;
+        self.second.reconnect().await
+    }
+
+    /// 任一子連線有工作階段狀態時，以 `{ "first": ..., "second": ... }` 的格式合併兩者的狀態
+    fn session_state(&self) -> Option<Value> {
+        let first = self.first.session_state();
+        let second = self.second.session_state();
+
+        (first.is_some() || second.is_some()).then(|| {
+            json!({
+                "first": first,
+                "second": second,
+            })
+        })
+    }
+
+    /// 將工作階段狀態拆分後交由各子連線重新連線，兩者都續用了工作階段時才視為 [`ReconnectOutcome::Resumed`]
+    async fn reconnect_with(
+        &mut self,
+        hint: ReconnectHint,
+    ) -> Result<ReconnectOutcome, ConnectionError> {
+        let split = |key: &str| ReconnectHint {
+            session: hint
+                .session
+                .as_ref()
+                .and_then(|session| session.get(key))
+                .filter(|session| !session.is_null())
+                .cloned(),
+            ..hint
+        };
+
+        let first = self.first.reconnect_with(split("first")).await?;
+        let second = self.second.reconnect_with(split("second")).await?;
+
+        Ok(
+            if (first, second) == (ReconnectOutcome::Resumed, ReconnectOutcome::Resumed) {
+                ReconnectOutcome::Resumed
+            } else {
+                ReconnectOutcome::Cold
+            },
+        )
+    }
+
+    async fn update_config(&mut self, new_config: &Self::Config) -> Result<(), ConnectionError> {
+        self.first.update_config(&new_config.first).await?;
+        self.second.update_config(&new_config.second).await
+    }
+}
+
+fn route<REQ: DeviceStateRequest, RES, ROUTEDREQ: DeviceStateRequest, ROUTEDRES>(
+    target: InitedTarget<REQ, RES>,
+    request: impl FnOnce(REQ) -> ROUTEDREQ,
+    result: impl FnOnce(RES) -> ROUTEDRES,
+) -> InitedTarget<ROUTEDREQ, ROUTEDRES> {
+    InitedTarget {
+        name: target.name,
+        request: request(target.request),
+        result: result(target.result),
+        default_status: target.default_status,
+        auto_refresh: target.auto_refresh,
+        refresh_interval: target.refresh_interval,
+        keep_raw_frames: target.keep_raw_frames,
+        group: target.group,
+        safe_state: target.safe_state,
+        array: target.array,
+        change: target.change,
+        statistics: target.statistics,
+    }
+}
+
+fn either_min<T: Ord>(first: Option<T>, second: Option<T>) -> Option<T> {
+    match (first, second) {
+        (Some(first), Some(second)) => Some(first.min(second)),
+        (first, second) => first.or(second),
+    }
+}
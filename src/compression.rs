@@ -0,0 +1,247 @@
+//! 資料壓縮
+//!
+//! 快照匯出、歷史資料補傳在行動網路等計費線路上的資料量很大，主程式可以依每個接收端的設定，
+//! 利用 [`Compressor`] 在送出前壓縮資料，並由 [`CompressionStats`] 統計壓縮率
+//!
+//! - [`Compression::None`]：不壓縮
+//! - [`Compression::Gzip`]：gzip（RFC 1952），需啟用 `gzip` feature
+//! - [`Compression::Zstd`]：Zstandard（RFC 8878），需啟用 `zstd` feature
+//!
+//! # 範例
+//! ```rust
+//! # #[cfg(feature = "gzip")] {
+//! use device_state_exchange_lib::compression::{Compression, Compressor};
+//!
+//! let config: Compression = serde_json::from_value(serde_json::json!({ "algorithm": "gzip", "level": 9 })).unwrap();
+//! let compressor = Compressor::new(config);
+//!
+//! let export = serde_json::to_vec(&vec![serde_json::json!({ "temperature": 21.5 }); 100]).unwrap();
+//! let compressed = compressor.compress(&export).unwrap();
+//!
+//! assert_eq!(compressor.compression().content_encoding(), Some("gzip"));
+//! assert_eq!(compressor.compression().decompress(&compressed).unwrap(), export);
+//! assert!(compressor.stats().snapshot().ratio < 0.1);
+//! # }
+//! ```
+
+use std::{
+    io,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+/// 壓縮演算法
+///
+/// 可直接寫在接收端的設定檔中，如 `{ "algorithm": "zstd", "level": 3 }` ，未啟用對應 feature 的演算法無法被反序列化
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Compression {
+    /// 不壓縮
+    #[default]
+    None,
+    /// gzip（需啟用 `gzip` feature）
+    #[cfg(feature = "gzip")]
+    Gzip {
+        /// 壓縮等級，0 至 9 ，預設為 6
+        #[serde(default = "default_gzip_level")]
+        level: u32,
+    },
+    /// Zstandard（需啟用 `zstd` feature）
+    #[cfg(feature = "zstd")]
+    Zstd {
+        /// 壓縮等級，1 至 22 ，預設為 3
+        #[serde(default = "default_zstd_level")]
+        level: i32,
+    },
+}
+
+#[cfg(feature = "gzip")]
+const fn default_gzip_level() -> u32 {
+    6
+}
+
+#[cfg(feature = "zstd")]
+const fn default_zstd_level() -> i32 {
+    3
+}
+
+impl Compression {
+    /// 對應的 HTTP `Content-Encoding` ，不壓縮時為 [`None`]
+    #[must_use]
+    pub const fn content_encoding(&self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            #[cfg(feature = "gzip")]
+            Self::Gzip { .. } => Some("gzip"),
+            #[cfg(feature = "zstd")]
+            Self::Zstd { .. } => Some("zstd"),
+        }
+    }
+
+    /// 壓縮
+    ///
+    /// # 參數
+    /// - `bytes`：原始資料
+    ///
+    /// # 回傳值
+    /// 壓縮後的資料，不壓縮時為原始資料的複本
+    ///
+    /// # Errors
+    /// 壓縮失敗時回傳 [`io::Error`]
+    pub fn compress(&self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(bytes.to_vec()),
+            #[cfg(feature = "gzip")]
+            Self::Gzip { level } => {
+                use std::io::Write;
+
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::new(*level));
+                encoder.write_all(bytes)?;
+                encoder.finish()
+            }
+            #[cfg(feature = "zstd")]
+            Self::Zstd { level } => zstd::encode_all(bytes, *level),
+        }
+    }
+
+    /// 解壓縮
+    ///
+    /// # 參數
+    /// - `bytes`：壓縮後的資料
+    ///
+    /// # 回傳值
+    /// 原始資料
+    ///
+    /// # Errors
+    /// 資料格式錯誤時回傳 [`io::Error`]
+    pub fn decompress(&self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(bytes.to_vec()),
+            #[cfg(feature = "gzip")]
+            Self::Gzip { .. } => {
+                use std::io::Read;
+
+                let mut decompressed = Vec::new();
+                flate2::read::GzDecoder::new(bytes).read_to_end(&mut decompressed)?;
+                Ok(decompressed)
+            }
+            #[cfg(feature = "zstd")]
+            Self::Zstd { .. } => zstd::decode_all(bytes),
+        }
+    }
+}
+
+/// 壓縮器
+///
+/// 每個接收端建立一個，壓縮時會將資料量記錄至 [`CompressionStats`]
+///
+/// 本 struct 可直接複製，複製後的物件會記錄至同一份統計數據
+#[derive(Debug, Clone, Default)]
+pub struct Compressor {
+    compression: Compression,
+    stats: CompressionStats,
+}
+
+impl Compressor {
+    /// 建立壓縮器
+    ///
+    /// # 參數
+    /// - `compression`：壓縮演算法
+    #[must_use]
+    pub fn new(compression: Compression) -> Self {
+        Self {
+            compression,
+            stats: CompressionStats::default(),
+        }
+    }
+
+    /// 壓縮演算法
+    #[must_use]
+    pub const fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// 壓縮統計數據
+    #[must_use]
+    pub const fn stats(&self) -> &CompressionStats {
+        &self.stats
+    }
+
+    /// 壓縮並記錄資料量
+    ///
+    /// # Errors
+    /// 壓縮失敗時回傳 [`io::Error`] ，失敗的資料不會被記錄
+    pub fn compress(&self, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        let compressed = self.compression.compress(bytes)?;
+        self.stats.record(bytes.len(), compressed.len());
+        Ok(compressed)
+    }
+}
+
+/// 壓縮統計數據
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份數據
+#[derive(Debug, Clone, Default)]
+pub struct CompressionStats(Arc<CompressionCounters>);
+
+#[derive(Debug, Default)]
+struct CompressionCounters {
+    payloads: AtomicU64,
+    input_bytes: AtomicU64,
+    output_bytes: AtomicU64,
+}
+
+impl CompressionStats {
+    /// 記錄一筆壓縮結果
+    ///
+    /// # 參數
+    /// - `input_bytes`：原始資料的位元組數
+    /// - `output_bytes`：壓縮後的位元組數
+    pub fn record(&self, input_bytes: usize, output_bytes: usize) {
+        self.0.payloads.fetch_add(1, Ordering::Relaxed);
+        self.0
+            .input_bytes
+            .fetch_add(input_bytes as u64, Ordering::Relaxed);
+        self.0
+            .output_bytes
+            .fetch_add(output_bytes as u64, Ordering::Relaxed);
+    }
+
+    /// 取得統計數據快照
+    #[must_use]
+    #[expect(clippy::cast_precision_loss)]
+    pub fn snapshot(&self) -> CompressionStatsSnapshot {
+        let input_bytes = self.0.input_bytes.load(Ordering::Relaxed);
+        let output_bytes = self.0.output_bytes.load(Ordering::Relaxed);
+
+        CompressionStatsSnapshot {
+            payloads: self.0.payloads.load(Ordering::Relaxed),
+            input_bytes,
+            output_bytes,
+            ratio: if input_bytes == 0 {
+                1.0
+            } else {
+                output_bytes as f64 / input_bytes as f64
+            },
+        }
+    }
+}
+
+/// 壓縮統計數據快照
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CompressionStatsSnapshot {
+    /// 壓縮的資料筆數
+    pub payloads: u64,
+    /// 原始資料的總位元組數
+    pub input_bytes: u64,
+    /// 壓縮後的總位元組數
+    pub output_bytes: u64,
+    /// 壓縮率（壓縮後 / 原始），尚未壓縮過資料時為 1
+    pub ratio: f64,
+}
@@ -0,0 +1,181 @@
+//! 同時處理中請求數量限制
+//!
+//! 部分設備或閘道器只能同時處理有限數量的交易（如 Modbus TCP 閘道器最多同時處理 4 筆），
+//! 主程式在批次、管線化或平行發送請求時，應依 [`crate::ConnectionArtifact::max_in_flight`] 建立 [`InFlightLimiter`] ，
+//! 每次發送請求前取得 [`InFlightPermit`] ，處理完成後釋放
+
+use std::{
+    error::Error,
+    fmt::Display,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// 預設同時處理中的請求數量上限，參見 [`crate::ConnectionArtifact::max_in_flight`]
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 1;
+
+/// 同時處理中請求數量限制器
+///
+/// 超過上限的請求會依到達順序排隊等待，排隊中與處理中的數量會顯示於統計數據快照中
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件（包含登記至 [`crate::ConnectionStatsRegistry`] 的 [`crate::ConnectionStats`]）會存取同一份資料
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::concurrency::InFlightLimiter;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let limiter = InFlightLimiter::new(2);
+///
+/// let first = limiter.acquire().await.unwrap();
+/// let second = limiter.acquire().await.unwrap();
+/// assert!(limiter.try_acquire().is_none());
+/// assert_eq!(limiter.snapshot().in_flight, 2);
+///
+/// drop(first);
+/// assert!(limiter.try_acquire().is_some());
+/// # drop(second);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct InFlightLimiter(Arc<InFlightLimiterInner>);
+
+#[derive(Debug)]
+struct InFlightLimiterInner {
+    semaphore: Arc<Semaphore>,
+    max_in_flight: usize,
+    queued: AtomicUsize,
+    peak_queued: AtomicUsize,
+}
+
+/// 處理中請求的許可
+///
+/// 請在請求處理完成（包含失敗與逾時）後釋放，釋放後排隊中的下一個請求才能發送
+#[derive(Debug)]
+pub struct InFlightPermit(#[expect(dead_code)] OwnedSemaphorePermit);
+
+/// 同時處理中請求數量快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InFlightSnapshot {
+    /// 同時處理中的請求數量上限
+    pub max_in_flight: usize,
+    /// 處理中的請求數量
+    pub in_flight: usize,
+    /// 排隊等待中的請求數量
+    pub queued: usize,
+    /// 曾經同時排隊等待的最大請求數量
+    pub peak_queued: usize,
+}
+
+/// 限制器已關閉
+///
+/// 連線被移除時，主程式可以調用 [`InFlightLimiter::close()`] ，讓排隊中的請求收到本錯誤並結束等待
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LimiterClosed;
+
+impl Display for LimiterClosed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "同時處理中請求數量限制器已關閉")
+    }
+}
+
+impl Error for LimiterClosed {}
+
+impl Default for InFlightLimiter {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_IN_FLIGHT)
+    }
+}
+
+impl InFlightLimiter {
+    /// 建立同時處理中請求數量限制器
+    ///
+    /// # 參數
+    /// - `max_in_flight`：同時處理中的請求數量上限，小於 1 時視為 1
+    #[must_use]
+    pub fn new(max_in_flight: usize) -> Self {
+        let max_in_flight = max_in_flight.clamp(1, Semaphore::MAX_PERMITS);
+
+        Self(Arc::new(InFlightLimiterInner {
+            semaphore: Arc::new(Semaphore::new(max_in_flight)),
+            max_in_flight,
+            queued: AtomicUsize::new(0),
+            peak_queued: AtomicUsize::new(0),
+        }))
+    }
+
+    /// 取得處理中請求的許可，已達上限時排隊等待
+    ///
+    /// 等待中的 future 被捨棄時（如逾時），會自動離開佇列
+    ///
+    /// # Errors
+    /// 限制器已透過 [`InFlightLimiter::close()`] 關閉時回傳 [`LimiterClosed`]
+    pub async fn acquire(&self) -> Result<InFlightPermit, LimiterClosed> {
+        if let Some(permit) = self.try_acquire() {
+            return Ok(permit);
+        }
+
+        let _queued = QueuedGuard::enter(&self.0);
+        Arc::clone(&self.0.semaphore)
+            .acquire_owned()
+            .await
+            .map(InFlightPermit)
+            .map_err(|_| LimiterClosed)
+    }
+
+    /// 不等待，嘗試取得處理中請求的許可
+    ///
+    /// # 回傳值
+    /// 已達上限或限制器已關閉時為 [`None`]
+    #[must_use]
+    pub fn try_acquire(&self) -> Option<InFlightPermit> {
+        Arc::clone(&self.0.semaphore)
+            .try_acquire_owned()
+            .ok()
+            .map(InFlightPermit)
+    }
+
+    /// 關閉限制器，排隊中與之後的請求都會收到 [`LimiterClosed`]
+    ///
+    /// 已取得的許可不受影響
+    pub fn close(&self) {
+        self.0.semaphore.close();
+    }
+
+    /// 取得同時處理中請求數量快照
+    #[must_use]
+    pub fn snapshot(&self) -> InFlightSnapshot {
+        InFlightSnapshot {
+            max_in_flight: self.0.max_in_flight,
+            in_flight: self
+                .0
+                .max_in_flight
+                .saturating_sub(self.0.semaphore.available_permits()),
+            queued: self.0.queued.load(Ordering::Relaxed),
+            peak_queued: self.0.peak_queued.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 排隊期間計入排隊數量，離開（取得許可或被捨棄）時扣除
+struct QueuedGuard<'a>(&'a InFlightLimiterInner);
+
+impl<'a> QueuedGuard<'a> {
+    fn enter(inner: &'a InFlightLimiterInner) -> Self {
+        let queued = inner.queued.fetch_add(1, Ordering::Relaxed) + 1;
+        inner.peak_queued.fetch_max(queued, Ordering::Relaxed);
+        Self(inner)
+    }
+}
+
+impl Drop for QueuedGuard<'_> {
+    fn drop(&mut self) {
+        self.0.queued.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,299 @@
+//! 請求來源
+//!
+//! 外部請求可能來自不同的用戶端（API 使用者、其他服務），外部界面在產生 [`crate::WriteCommand`] 時應一併記錄 [`ClientId`] ，
+//! 主程式取出指令後，以 [`crate::WriteCommand::context()`] 取得 [`RequestContext`] ，並在以下環節使用：
+//!
+//! - 調用 [`crate::Connection::preprocess_with_context()`] ，讓設備連線依請求來源調整或拒絕請求
+//! - 調用 [`crate::auth::Authorizer::check()`] 檢查讀寫權限，`principal` 參數請傳入 [`RequestContext::client`]
+//! - 調用 [`crate::lease::LeaseManager::admit_write()`] 檢查租約，`writer` 參數請傳入 [`RequestContext::writer()`]
+//! - 調用 [`crate::diagnostics::RequestJournal::record_with_context()`] ，讓請求紀錄可以追溯請求來源
+//! - 以 [`RequestContext::client`] 作為限流的索引
+//!
+//! 外部界面也可以為每個請求指定 [`CorrelationId`] （如 HTTP 的 `X-Request-Id`），請求紀錄會一併保留；
+//! 協定本身帶有交易編號（如 Modbus TCP MBAP 標頭的 Transaction Identifier 、OPC UA 的 request handle）時，
+//! 設備連線可以利用 [`TransactionIds`] 在 [`crate::Connection::preprocess_with_context()`] 中將追蹤編號對應到交易編號，
+//! 收到回覆或分析封包擷取時再對應回來，讓封包擷取、主程式紀錄與外部請求可以互相比對
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{ClientId, CommandQueue, WriteCommand};
+//! use serde_json::json;
+//!
+//! let commands = CommandQueue::new();
+//! commands.push(WriteCommand {
+//!     target: "setpoint".to_owned(),
+//!     value: json!(80),
+//!     client: Some(ClientId::new("scada")),
+//!     correlation: None,
+//! });
+//!
+//! let command = commands.pop().unwrap();
+//! assert_eq!(command.context().writer(), Some("scada"));
+//! ```
+//!
+//! 交易編號：
+//! ```rust
+//! use device_state_exchange_lib::{CorrelationId, RequestContext, context::TransactionIds};
+//!
+//! // MBAP Transaction Identifier 為 16 位元
+//! let transactions = TransactionIds::new(0, u32::from(u16::MAX));
+//! let context = RequestContext::default().with_correlation(CorrelationId::new("req-7f3a"));
+//!
+//! let tid = transactions.assign(&context);
+//! assert_eq!(tid, 0);
+//! assert_eq!(transactions.correlation(tid), Some(CorrelationId::new("req-7f3a")));
+//!
+//! // 收到回覆後
+//! assert_eq!(transactions.release(tid).unwrap().as_str(), "req-7f3a");
+//! assert_eq!(transactions.correlation(tid), None);
+//!
+//! // 自動更新的請求沒有追蹤編號，仍會分配交易編號
+//! assert_eq!(transactions.assign(&RequestContext::default()), 1);
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// 用戶端識別名稱
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ClientId(String);
+
+impl ClientId {
+    /// 建立用戶端識別名稱
+    #[must_use]
+    pub fn new(id: impl AsRef<str>) -> Self {
+        Self(id.as_ref().to_owned())
+    }
+
+    /// 取得字串形式的用戶端識別名稱
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for ClientId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for ClientId {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+/// 請求追蹤編號
+///
+/// 識別單一外部請求，在外部界面、主程式紀錄與設備協定之間傳遞，參見 [`TransactionIds`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct CorrelationId(String);
+
+impl CorrelationId {
+    /// 建立請求追蹤編號
+    #[must_use]
+    pub fn new(id: impl AsRef<str>) -> Self {
+        Self(id.as_ref().to_owned())
+    }
+
+    /// 產生新的請求追蹤編號，格式為十六進位的程式啟動時間與遞增序號，同一個程式中不會重複
+    #[must_use]
+    pub fn generate() -> Self {
+        static STARTED: AtomicU64 = AtomicU64::new(0);
+        static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| {
+                u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX)
+            });
+        let started = match STARTED.compare_exchange(0, now, Ordering::Relaxed, Ordering::Relaxed) {
+            Ok(_) => now,
+            Err(started) => started,
+        };
+
+        Self(format!(
+            "{started:x}-{:x}",
+            SEQUENCE.fetch_add(1, Ordering::Relaxed)
+        ))
+    }
+
+    /// 取得字串形式的請求追蹤編號
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for CorrelationId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for CorrelationId {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+/// 請求內容以外的請求資訊
+///
+/// 主程式自動更新點位時沒有請求來源，請使用 [`RequestContext::default()`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RequestContext {
+    /// 請求來源，主程式自動更新或無法識別時為 [`None`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<ClientId>,
+    /// 請求追蹤編號，主程式自動更新或外部界面未指定時為 [`None`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub correlation: Option<CorrelationId>,
+}
+
+impl RequestContext {
+    /// 建立指定請求來源的請求資訊
+    #[must_use]
+    pub const fn from_client(client: ClientId) -> Self {
+        Self {
+            client: Some(client),
+            correlation: None,
+        }
+    }
+
+    /// 設定請求追蹤編號
+    #[must_use]
+    pub fn with_correlation(mut self, correlation: CorrelationId) -> Self {
+        self.correlation = Some(correlation);
+        self
+    }
+
+    /// 寫入者名稱，參見 [`crate::lease::LeaseManager::admit_write()`]
+    #[must_use]
+    pub fn writer(&self) -> Option<&str> {
+        self.client.as_ref().map(ClientId::as_str)
+    }
+}
+
+/// 協定交易編號分配
+///
+/// 依序分配協定的交易編號（超過上限後從下限重新開始），並記錄交易編號與 [`CorrelationId`] 的對應，
+/// 只保留最近的對應（預設 1024 筆），重新分配到相同的交易編號時會覆蓋舊的對應
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份資料
+#[derive(Debug, Clone)]
+pub struct TransactionIds {
+    first: u32,
+    last: u32,
+    capacity: usize,
+    state: Arc<Mutex<TransactionState>>,
+}
+
+#[derive(Debug)]
+struct TransactionState {
+    next: u32,
+    correlations: VecDeque<(u32, CorrelationId)>,
+}
+
+impl TransactionIds {
+    /// 建立交易編號分配
+    ///
+    /// # 參數
+    /// - `first`：交易編號下限，第一個分配的編號
+    /// - `last`：交易編號上限（包含），如 MBAP 為 [`u16::MAX`]
+    ///
+    /// # Panics
+    /// `first` 大於 `last` 時
+    #[must_use]
+    pub fn new(first: u32, last: u32) -> Self {
+        assert!(first <= last, "交易編號下限 {first} 大於上限 {last}");
+
+        Self {
+            first,
+            last,
+            capacity: 1024,
+            state: Arc::new(Mutex::new(TransactionState {
+                next: first,
+                correlations: VecDeque::new(),
+            })),
+        }
+    }
+
+    /// 設定保留的對應數量
+    #[must_use]
+    pub const fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// 分配下一個交易編號
+    ///
+    /// # 參數
+    /// - `context`：請求資訊，有 [`RequestContext::correlation`] 時記錄對應
+    pub fn assign(&self, context: &RequestContext) -> u32 {
+        allocate(
+            &mut self.state.lock().unwrap_or_else(PoisonError::into_inner),
+            self,
+            context.correlation.as_ref(),
+        )
+    }
+
+    /// 取得交易編號對應的請求追蹤編號，用於比對封包擷取
+    #[must_use]
+    pub fn correlation(&self, transaction_id: u32) -> Option<CorrelationId> {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .correlations
+            .iter()
+            .find(|(id, _)| *id == transaction_id)
+            .map(|(_, correlation)| correlation.clone())
+    }
+
+    /// 收到回覆後移除交易編號的對應
+    ///
+    /// # 回傳值
+    /// 交易編號對應的請求追蹤編號，沒有對應時為 [`None`]
+    pub fn release(&self, transaction_id: u32) -> Option<CorrelationId> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let index = state
+            .correlations
+            .iter()
+            .position(|(id, _)| *id == transaction_id)?;
+
+        state
+            .correlations
+            .remove(index)
+            .map(|(_, correlation)| correlation)
+    }
+}
+
+fn allocate(
+    state: &mut TransactionState,
+    ids: &TransactionIds,
+    correlation: Option<&CorrelationId>,
+) -> u32 {
+    let id = state.next;
+    state.next = if id >= ids.last { ids.first } else { id + 1 };
+    state.correlations.retain(|(assigned, _)| *assigned != id);
+
+    if let Some(correlation) = correlation
+        && ids.capacity > 0
+    {
+        if state.correlations.len() >= ids.capacity {
+            state.correlations.pop_front();
+        }
+        state.correlations.push_back((id, correlation.clone()));
+    }
+
+    id
+}
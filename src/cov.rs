@@ -0,0 +1,153 @@
+//! 數值變化通知（change of value）
+//!
+//! 外部服務原本需要定期讀取 [`crate::StateStore`] 才能得知點位數值的變化，點位設定 [`crate::InitedTarget::change`] 後，
+//! 主程式在 [`crate::Connection::postprocess()`] 取得回覆後調用 [`crate::InitedTarget::notify_change()`] ，
+//! 數值與上一次通知的數值不同時，會將 [`ChangeEvent`] 推送給所有以 [`crate::InitedTarget::on_change()`] 訂閱的接收端
+//!
+//! 數值點位可以設定不敏感帶（deadband），變化量小於不敏感帶時不會通知，也不會更新比較用的數值，
+//! 因此緩慢的漂移累積超過不敏感帶後仍會通知
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::cov::ChangeNotifier;
+//! use serde_json::json;
+//!
+//! let notifier = ChangeNotifier::default().with_deadband(0.5);
+//! let mut receiver = notifier.subscribe();
+//!
+//! assert!(notifier.update("temperature", json!(21.0)));
+//! assert!(!notifier.update("temperature", json!(21.3)));
+//! assert!(notifier.update("temperature", json!(21.6)));
+//!
+//! assert_eq!(receiver.try_recv().unwrap().old_value, None);
+//! let event = receiver.try_recv().unwrap();
+//! assert_eq!((event.old_value, event.new_value), (Some(json!(21.0)), json!(21.6)));
+//! assert!(receiver.try_recv().is_err());
+//! ```
+
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    time::SystemTime,
+};
+
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+use crate::event::DEFAULT_EVENT_CAPACITY;
+
+/// 數值變化事件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangeEvent {
+    /// 點位名稱
+    pub target: String,
+    /// 上一次通知的數值，第一次取得數值時為 [`None`]
+    pub old_value: Option<Value>,
+    /// 新的數值
+    pub new_value: Value,
+    /// 取得新數值的時間
+    pub timestamp: SystemTime,
+}
+
+/// 數值變化通知
+///
+/// 利用 [`tokio::sync::broadcast`] 將事件傳遞給所有訂閱者，訂閱者處理速度過慢時，會收到 [`broadcast::error::RecvError::Lagged`] 並遺失最舊的事件
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件（包含複製後的 [`crate::InitedTarget`]）會存取同一份資料
+#[derive(Debug, Clone)]
+pub struct ChangeNotifier {
+    sender: broadcast::Sender<ChangeEvent>,
+    deadband: Option<f64>,
+    last: Arc<Mutex<Option<Value>>>,
+}
+
+impl Default for ChangeNotifier {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_CAPACITY)
+    }
+}
+
+impl ChangeNotifier {
+    /// 建立數值變化通知，數值有任何變化都會通知
+    ///
+    /// # 參數
+    /// - `capacity`：每個訂閱者最多可暫存的事件數量
+    ///
+    /// # Panics
+    /// `capacity` 為 0 時
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            sender: broadcast::Sender::new(capacity),
+            deadband: None,
+            last: Arc::default(),
+        }
+    }
+
+    /// 設定不敏感帶
+    ///
+    /// 新舊數值都是數字，且差的絕對值小於 `deadband` 時不會通知，其他型別的數值仍以是否相等判斷
+    #[must_use]
+    pub const fn with_deadband(mut self, deadband: f64) -> Self {
+        self.deadband = Some(deadband);
+        self
+    }
+
+    /// 不敏感帶，未設定時為 [`None`]
+    #[must_use]
+    pub const fn deadband(&self) -> Option<f64> {
+        self.deadband
+    }
+
+    /// 訂閱數值變化
+    ///
+    /// 只會收到訂閱後發生的變化
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<ChangeEvent> {
+        self.sender.subscribe()
+    }
+
+    /// 比較新的數值，有變化時通知所有訂閱者
+    ///
+    /// 沒有訂閱者時仍會更新比較用的數值
+    ///
+    /// # 參數
+    /// - `target`：點位名稱
+    /// - `value`：新的數值
+    ///
+    /// # 回傳值
+    /// 數值是否有變化
+    pub fn update(&self, target: &str, value: Value) -> bool {
+        let old_value = {
+            let mut last = self.last.lock().unwrap_or_else(PoisonError::into_inner);
+            if last
+                .as_ref()
+                .is_some_and(|last| !self.is_change(last, &value))
+            {
+                return false;
+            }
+            last.replace(value.clone())
+        };
+
+        let _ = self.sender.send(ChangeEvent {
+            target: target.to_owned(),
+            old_value,
+            new_value: value,
+            timestamp: SystemTime::now(),
+        });
+        true
+    }
+
+    /// 清除比較用的數值，下一次 [`ChangeNotifier::update()`] 一定會通知
+    ///
+    /// 適用於重新連線後，讓訂閱者取得目前的數值
+    pub fn reset(&self) {
+        *self.last.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    fn is_change(&self, old: &Value, new: &Value) -> bool {
+        match (self.deadband, old.as_f64(), new.as_f64()) {
+            (Some(deadband), Some(old), Some(new)) => (new - old).abs() >= deadband,
+            _ => old != new,
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::{error::Error, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    TargetAddressNumber,
+    validation::{AddressConflict, detect_address_conflicts},
+};
+
+#[cfg(feature = "csv")]
+pub mod csv;
+
+/// 通用點位定義
+///
+/// 與設備協定無關的點位描述，供設定檔、試算表等外部來源使用，實作者可在 [`crate::Connection::init_targets()`] 前，
+/// 將本 struct 轉換為 [`crate::Connection::Target`] 所指定的型別
+///
+/// 協定特有的欄位可以放在 [`TargetDefinition::extra`] 中
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetDefinition {
+    /// 點位名稱
+    pub name: String,
+    /// 設備編號，參見 [`TargetAddressNumber`]
+    #[serde(default)]
+    pub device: TargetAddressNumber,
+    /// 設備型態
+    ///
+    /// 對應 [`crate::Connection::NAMES`] 中的設備型態名稱
+    #[serde(default)]
+    pub device_type: Option<String>,
+    /// 點位位址
+    ///
+    /// 格式由各協定自行定義，如 Modbus 的 Register 位址
+    pub address: String,
+    /// 資料型別
+    ///
+    /// 格式由各協定自行定義，如 `u16`、`f32`
+    #[serde(default)]
+    pub data_type: Option<String>,
+    /// 是否要自動更新
+    #[serde(default = "default_auto_refresh")]
+    pub auto_refresh: bool,
+    /// 點位初始狀態
+    #[serde(default)]
+    pub default_status: Option<Value>,
+    /// 協定特有的其他欄位
+    #[serde(default, flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// 無法轉換的點位
+///
+/// 各協定的參考實作由 [`TargetDefinition`] 轉換點位失敗時回傳，點位會被略過，並記錄於各連線的 `rejected_targets()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidTarget {
+    /// 點位名稱
+    pub target: String,
+    /// 原因
+    pub reason: String,
+}
+
+impl Display for InvalidTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "點位「{}」設定錯誤：{}", self.target, self.reason)
+    }
+}
+
+impl Error for InvalidTarget {}
+
+impl TargetDefinition {
+    /// 檢查點位定義中的設備編號衝突
+    ///
+    /// 沒有設定 [`TargetDefinition::device_type`] 的點位不會被檢查，參見 [`detect_address_conflicts()`]
+    #[must_use]
+    pub fn address_conflicts(targets: &[Self]) -> Vec<AddressConflict> {
+        detect_address_conflicts(targets.iter().filter_map(|target| {
+            target
+                .device_type
+                .as_deref()
+                .map(|device_type| (target.name.as_str(), &target.device, device_type))
+        }))
+    }
+}
+
+const fn default_auto_refresh() -> bool {
+    true
+}
@@ -0,0 +1,302 @@
+//! CSV 格式的點位定義匯入/匯出（需啟用 `csv` feature）
+//!
+//! # 欄位格式
+//!
+//! 第一列必須為標題列，欄位順序不限：
+//!
+//! | 欄位 | 必填 | 說明 |
+//! | --- | --- | --- |
+//! | `name` | ✅ | 點位名稱，不可重複 |
+//! | `address` | ✅ | 點位位址，格式由各協定自行定義 |
+//! | `device` | | 設備編號，空白代表 [`None`] |
+//! | `device_type` | | 設備型態，對應 [`crate::Connection::NAMES`] |
+//! | `data_type` | | 資料型別，格式由各協定自行定義 |
+//! | `auto_refresh` | | 是否要自動更新，可填入 `true`/`false`、`1`/`0`、`yes`/`no`，空白代表 `true` |
+//! | `default_status` | | 點位初始狀態，需為合法的 JSON，空白代表 [`None`] |
+//!
+//! 其餘欄位會以字串形式放入 [`TargetDefinition::extra`] 中，空白的欄位會被略過
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::definition::csv::{read_targets, write_targets};
+//!
+//! let input = "\
+//! name,device,address,data_type,function_code
+//! 電壓,1,40001,f32,3
+//! 電流,1,,f32,3
+//! ";
+//!
+//! let import = read_targets(input.as_bytes());
+//!
+//! assert_eq!(import.targets.len(), 1);
+//! assert_eq!(import.targets[0].extra["function_code"], "3");
+//! assert_eq!(import.errors.len(), 1);
+//! assert_eq!(import.errors[0].line, Some(3));
+//!
+//! let mut output = Vec::new();
+//! write_targets(&mut output, &import.targets).unwrap();
+//! ```
+
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    fmt::Display,
+    io::{Read, Write},
+};
+
+use csv::{ReaderBuilder, StringRecord, Trim, WriterBuilder};
+use serde_json::{Map, Value};
+
+use super::TargetDefinition;
+use crate::HashSet;
+
+const NAME: &str = "name";
+const DEVICE: &str = "device";
+const DEVICE_TYPE: &str = "device_type";
+const ADDRESS: &str = "address";
+const DATA_TYPE: &str = "data_type";
+const AUTO_REFRESH: &str = "auto_refresh";
+const DEFAULT_STATUS: &str = "default_status";
+const COLUMNS: &[&str] = &[
+    NAME,
+    DEVICE,
+    DEVICE_TYPE,
+    ADDRESS,
+    DATA_TYPE,
+    AUTO_REFRESH,
+    DEFAULT_STATUS,
+];
+
+/// CSV 匯入結果
+///
+/// 無法解析的資料列不會中斷匯入，而是記錄在 [`CsvImport::errors`] 中
+#[derive(Debug, Clone, Default)]
+pub struct CsvImport {
+    /// 成功解析的點位定義
+    pub targets: Vec<TargetDefinition>,
+    /// 各資料列的錯誤
+    pub errors: Vec<CsvRowError>,
+}
+
+/// CSV 資料列錯誤
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvRowError {
+    /// 發生錯誤的行號（由 1 起算，包含標題列），無法判斷時為 [`None`]
+    pub line: Option<u64>,
+    /// 錯誤原因
+    pub kind: CsvRowErrorKind,
+}
+
+/// CSV 資料列錯誤原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvRowErrorKind {
+    /// 標題列缺少必填欄位
+    MissingColumn(&'static str),
+    /// 必填欄位為空白
+    EmptyField(&'static str),
+    /// 欄位內容無法解析
+    InvalidField {
+        /// 欄位名稱
+        column: &'static str,
+        /// 原始內容
+        value: String,
+    },
+    /// 點位名稱重複
+    DuplicateName(String),
+    /// CSV 格式錯誤
+    Malformed(String),
+}
+
+impl Display for CsvRowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "第 {line} 行：")?;
+        }
+
+        match &self.kind {
+            CsvRowErrorKind::MissingColumn(column) => write!(f, "缺少 {column} 欄位"),
+            CsvRowErrorKind::EmptyField(column) => write!(f, "{column} 欄位不可為空白"),
+            CsvRowErrorKind::InvalidField { column, value } => {
+                write!(f, "{column} 欄位內容無法解析：{value}")
+            }
+            CsvRowErrorKind::DuplicateName(name) => write!(f, "點位名稱重複：{name}"),
+            CsvRowErrorKind::Malformed(message) => write!(f, "CSV 格式錯誤：{message}"),
+        }
+    }
+}
+
+impl Error for CsvRowError {}
+
+/// 從 CSV 讀取點位定義
+///
+/// # 參數
+/// - `reader`：CSV 資料來源
+///
+/// # 回傳值
+/// 成功解析的點位定義與各資料列的錯誤，參見 [`CsvImport`]
+pub fn read_targets(reader: impl Read) -> CsvImport {
+    let mut reader = ReaderBuilder::new().trim(Trim::All).from_reader(reader);
+    let mut import = CsvImport::default();
+
+    let headers = match reader.headers() {
+        Ok(headers) => headers.clone(),
+        Err(error) => {
+            import.errors.push(CsvRowError {
+                line: error.position().map(csv::Position::line),
+                kind: CsvRowErrorKind::Malformed(error.to_string()),
+            });
+            return import;
+        }
+    };
+
+    for required in [NAME, ADDRESS] {
+        if !headers.iter().any(|header| header == required) {
+            import.errors.push(CsvRowError {
+                line: Some(1),
+                kind: CsvRowErrorKind::MissingColumn(required),
+            });
+        }
+    }
+
+    if !import.errors.is_empty() {
+        return import;
+    }
+
+    let mut names = HashSet::new();
+
+    for record in reader.records() {
+        let result = record
+            .map_err(|error| CsvRowError {
+                line: error.position().map(csv::Position::line),
+                kind: CsvRowErrorKind::Malformed(error.to_string()),
+            })
+            .and_then(|record| {
+                parse_record(&headers, &record).map_err(|kind| CsvRowError {
+                    line: record.position().map(csv::Position::line),
+                    kind,
+                })
+            })
+            .and_then(|(line, target)| {
+                if names.insert(target.name.clone()) {
+                    Ok(target)
+                } else {
+                    Err(CsvRowError {
+                        line,
+                        kind: CsvRowErrorKind::DuplicateName(target.name),
+                    })
+                }
+            });
+
+        match result {
+            Ok(target) => import.targets.push(target),
+            Err(error) => import.errors.push(error),
+        }
+    }
+
+    import
+}
+
+fn parse_record(
+    headers: &StringRecord,
+    record: &StringRecord,
+) -> Result<(Option<u64>, TargetDefinition), CsvRowErrorKind> {
+    let field = |column: &str| {
+        headers
+            .iter()
+            .position(|header| header == column)
+            .and_then(|index| record.get(index))
+            .filter(|value| !value.is_empty())
+    };
+
+    let name = field(NAME).ok_or(CsvRowErrorKind::EmptyField(NAME))?;
+    let address = field(ADDRESS).ok_or(CsvRowErrorKind::EmptyField(ADDRESS))?;
+
+    let auto_refresh = match field(AUTO_REFRESH).map(str::to_ascii_lowercase).as_deref() {
+        None | Some("true" | "1" | "yes") => true,
+        Some("false" | "0" | "no") => false,
+        Some(value) => {
+            return Err(CsvRowErrorKind::InvalidField {
+                column: AUTO_REFRESH,
+                value: value.to_owned(),
+            });
+        }
+    };
+
+    let default_status = field(DEFAULT_STATUS)
+        .map(|value| {
+            serde_json::from_str(value).map_err(|_| CsvRowErrorKind::InvalidField {
+                column: DEFAULT_STATUS,
+                value: value.to_owned(),
+            })
+        })
+        .transpose()?;
+
+    let extra = headers
+        .iter()
+        .zip(record.iter())
+        .filter(|(header, value)| !COLUMNS.contains(header) && !value.is_empty())
+        .map(|(header, value)| (header.to_owned(), Value::from(value)))
+        .collect::<Map<_, _>>();
+
+    Ok((
+        record.position().map(csv::Position::line),
+        TargetDefinition {
+            name: name.to_owned(),
+            device: field(DEVICE).map(str::to_owned),
+            device_type: field(DEVICE_TYPE).map(str::to_owned),
+            address: address.to_owned(),
+            data_type: field(DATA_TYPE).map(str::to_owned),
+            auto_refresh,
+            default_status,
+            extra,
+        },
+    ))
+}
+
+/// 將點位定義匯出為 CSV
+///
+/// 標題列依序為固定欄位，以及所有點位 [`TargetDefinition::extra`] 中出現過的欄位（依名稱排序）
+///
+/// # 參數
+/// - `writer`：CSV 輸出目標
+/// - `targets`：欲匯出的點位定義
+///
+/// # Errors
+/// 寫入失敗時回傳 [`csv::Error`]
+pub fn write_targets(writer: impl Write, targets: &[TargetDefinition]) -> Result<(), csv::Error> {
+    let extra_columns = targets
+        .iter()
+        .flat_map(|target| target.extra.keys().map(String::as_str))
+        .collect::<BTreeSet<_>>();
+
+    let mut writer = WriterBuilder::new().from_writer(writer);
+
+    writer.write_record(COLUMNS.iter().copied().chain(extra_columns.iter().copied()))?;
+
+    for target in targets {
+        let fixed = [
+            target.name.clone(),
+            target.device.clone().unwrap_or_default(),
+            target.device_type.clone().unwrap_or_default(),
+            target.address.clone(),
+            target.data_type.clone().unwrap_or_default(),
+            target.auto_refresh.to_string(),
+            target
+                .default_status
+                .as_ref()
+                .map(Value::to_string)
+                .unwrap_or_default(),
+        ];
+
+        let extra = extra_columns.iter().map(|column| match target.extra.get(*column) {
+            None => String::new(),
+            Some(Value::String(value)) => value.clone(),
+            Some(value) => value.to_string(),
+        });
+
+        writer.write_record(fixed.into_iter().chain(extra))?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
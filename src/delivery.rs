@@ -0,0 +1,401 @@
+//! 輸出端傳遞方式
+//!
+//! 不同的輸出端（sink）對點位數值的需求不同：MQTT 等訊息通道通常只需要數值變動，歷史資料庫則需要每一筆取樣，
+//! 主程式將每個輸出端以 [`SinkDelivery::register()`] 登記並指定 [`DeliveryMode`] ，每次取得點位數值後，
+//! 以 [`SinkDelivery::route()`] 判斷要傳遞給哪些輸出端
+//!
+//! 部分輸出端（如公開的儀表板）不應收到特定點位（如計費電表），可以在執行期間以 [`SinkDelivery::set_filter()`] 為輸出端設定 [`TargetFilter`] ，
+//! 依點位名稱（支援 `*` 與 `?` 萬用字元）或以 [`SinkDelivery::set_tags()`] 設定的點位標籤選擇傳遞的點位
+//!
+//! # 範例
+//! ```rust
+//! use std::time::Duration;
+//! use device_state_exchange_lib::{delivery::{DeliveryMode, SinkDelivery}, value::Quality};
+//! use serde_json::json;
+//!
+//! let delivery = SinkDelivery::new();
+//! delivery.register("historian", DeliveryMode::EverySample);
+//! delivery.register("mqtt", DeliveryMode::OnChangeWithHeartbeat { heartbeat: Duration::from_secs(60) });
+//!
+//! assert_eq!(delivery.route("溫度", &json!(21.5), Quality::Good), ["historian", "mqtt"]);
+//! assert_eq!(delivery.route("溫度", &json!(21.5), Quality::Good), ["historian"]);
+//! assert_eq!(delivery.route("溫度", &json!(21.7), Quality::Good), ["historian", "mqtt"]);
+//!
+//! let mqtt = delivery.snapshot()["mqtt"];
+//! assert_eq!((mqtt.delivered, mqtt.suppressed), (2, 1));
+//! ```
+//!
+//! 排除計費點位：
+//! ```rust
+//! use device_state_exchange_lib::{delivery::{DeliveryMode, SinkDelivery, TargetFilter, TargetMatcher}, value::Quality};
+//! use serde_json::json;
+//!
+//! let delivery = SinkDelivery::new();
+//! delivery.register("dashboard", DeliveryMode::EverySample);
+//! delivery.set_filter("dashboard", TargetFilter {
+//!     include: vec![TargetMatcher::Name("panel-*".to_owned())],
+//!     exclude: vec![TargetMatcher::Tag("billing".to_owned())],
+//! });
+//! delivery.set_tags("panel-1-kwh", ["billing"]);
+//!
+//! assert_eq!(delivery.route("panel-1-voltage", &json!(220), Quality::Good), ["dashboard"]);
+//! assert!(delivery.route("panel-1-kwh", &json!(1024), Quality::Good).is_empty());
+//! assert!(delivery.route("boiler", &json!(80), Quality::Good).is_empty());
+//! assert_eq!(delivery.snapshot()["dashboard"].filtered, 2);
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, PoisonError, RwLock},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{HashMap, value::Quality};
+
+/// 傳遞方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum DeliveryMode {
+    /// 傳遞每一筆取樣
+    #[default]
+    EverySample,
+    /// 只傳遞數值或品質與前一次傳遞時不同的取樣
+    OnChange,
+    /// 只傳遞數值或品質變動的取樣，但距離前一次傳遞超過心跳間隔時，即使沒有變動也會傳遞
+    OnChangeWithHeartbeat {
+        /// 心跳間隔
+        #[serde(rename = "heartbeat_ms", with = "crate::millis")]
+        heartbeat: Duration,
+    },
+}
+
+/// 輸出端傳遞統計快照
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliverySnapshot {
+    /// 傳遞方式
+    pub mode: DeliveryMode,
+    /// 已傳遞的取樣數量
+    pub delivered: u64,
+    /// 因數值未變動而略過的取樣數量
+    pub suppressed: u64,
+    /// 因 [`TargetFilter`] 而略過的取樣數量
+    pub filtered: u64,
+}
+
+/// 點位選擇條件
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetMatcher {
+    /// 點位名稱，支援 `*`（任意長度的字元）與 `?`（單一字元）萬用字元
+    Name(String),
+    /// 點位標籤，參見 [`SinkDelivery::set_tags()`]
+    Tag(String),
+}
+
+impl TargetMatcher {
+    /// 點位是否符合條件
+    ///
+    /// # 參數
+    /// - `target`：點位名稱
+    /// - `tags`：點位標籤
+    #[must_use]
+    pub fn matches(&self, target: &str, tags: &[String]) -> bool {
+        match self {
+            Self::Name(pattern) => glob_matches(pattern, target),
+            Self::Tag(tag) => tags.contains(tag),
+        }
+    }
+}
+
+/// 輸出端的點位篩選
+///
+/// 點位需符合 `include` 中任一條件（`include` 為空時視為全部符合），且不符合 `exclude` 中的任何條件，才會傳遞給輸出端
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetFilter {
+    /// 包含的點位
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<TargetMatcher>,
+    /// 排除的點位，優先於 `include`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<TargetMatcher>,
+}
+
+impl TargetFilter {
+    /// 點位是否可以傳遞給輸出端
+    ///
+    /// # 參數
+    /// - `target`：點位名稱
+    /// - `tags`：點位標籤
+    #[must_use]
+    pub fn allows(&self, target: &str, tags: &[String]) -> bool {
+        (self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|matcher| matcher.matches(target, tags)))
+            && !self
+                .exclude
+                .iter()
+                .any(|matcher| matcher.matches(target, tags))
+    }
+}
+
+/// 單一輸出端的傳遞判斷
+///
+/// 依 [`TargetFilter`] 篩選點位，並記錄每個點位最後一次傳遞的數值、品質與時間，供 [`DeliveryMode::OnChange`] 與 [`DeliveryMode::OnChangeWithHeartbeat`] 比較
+#[derive(Debug, Clone, Default)]
+pub struct DeliveryFilter {
+    mode: DeliveryMode,
+    filter: TargetFilter,
+    last: HashMap<String, (Value, Quality, Instant)>,
+    delivered: u64,
+    suppressed: u64,
+    filtered: u64,
+}
+
+impl DeliveryFilter {
+    /// 建立傳遞判斷
+    #[must_use]
+    pub fn new(mode: DeliveryMode) -> Self {
+        Self {
+            mode,
+            ..Self::default()
+        }
+    }
+
+    /// 傳遞方式
+    #[must_use]
+    pub const fn mode(&self) -> DeliveryMode {
+        self.mode
+    }
+
+    /// 點位篩選
+    #[must_use]
+    pub const fn filter(&self) -> &TargetFilter {
+        &self.filter
+    }
+
+    /// 設定點位篩選，參見 [`TargetFilter`]
+    pub fn set_filter(&mut self, filter: TargetFilter) {
+        self.filter = filter;
+    }
+
+    /// 判斷取樣是否應傳遞，應傳遞時會記錄為最後一次傳遞的數值
+    ///
+    /// # 參數
+    /// - `target`：點位名稱
+    /// - `tags`：點位標籤
+    /// - `value`：點位數值
+    /// - `quality`：數值品質
+    pub fn should_deliver(
+        &mut self,
+        target: &str,
+        tags: &[String],
+        value: &Value,
+        quality: Quality,
+    ) -> bool {
+        if !self.filter.allows(target, tags) {
+            self.filtered += 1;
+            return false;
+        }
+
+        let now = Instant::now();
+        let deliver = match (self.mode, self.last.get(target)) {
+            (DeliveryMode::EverySample, _) | (_, None) => true,
+            (DeliveryMode::OnChange, Some((last_value, last_quality, _))) => {
+                last_value != value || *last_quality != quality
+            }
+            (
+                DeliveryMode::OnChangeWithHeartbeat { heartbeat },
+                Some((last_value, last_quality, delivered_at)),
+            ) => {
+                last_value != value
+                    || *last_quality != quality
+                    || now.duration_since(*delivered_at) >= heartbeat
+            }
+        };
+
+        if deliver {
+            self.delivered += 1;
+            if self.mode != DeliveryMode::EverySample {
+                self.last
+                    .insert(target.to_owned(), (value.clone(), quality, now));
+            }
+        } else {
+            self.suppressed += 1;
+        }
+
+        deliver
+    }
+
+    /// 清除點位最後一次傳遞的紀錄，下一筆取樣一定會傳遞
+    pub fn forget(&mut self, target: &str) {
+        self.last.remove(target);
+    }
+
+    /// 取得傳遞統計快照
+    #[must_use]
+    pub const fn snapshot(&self) -> DeliverySnapshot {
+        DeliverySnapshot {
+            mode: self.mode,
+            delivered: self.delivered,
+            suppressed: self.suppressed,
+            filtered: self.filtered,
+        }
+    }
+}
+
+/// 輸出端傳遞分派
+///
+/// 依名稱登記各輸出端的 [`DeliveryFilter`] ，輸出端依名稱排序
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份資料
+#[derive(Debug, Clone, Default)]
+pub struct SinkDelivery {
+    sinks: Arc<Mutex<BTreeMap<String, DeliveryFilter>>>,
+    tags: Arc<RwLock<HashMap<String, Vec<String>>>>,
+}
+
+impl SinkDelivery {
+    /// 建立輸出端傳遞分派
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登記輸出端
+    ///
+    /// # 參數
+    /// - `sink`：輸出端名稱，已存在時會以新的傳遞方式取代，並清除點位篩選與最後一次傳遞的紀錄
+    /// - `mode`：傳遞方式
+    pub fn register(&self, sink: impl Into<String>, mode: DeliveryMode) {
+        self.sinks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(sink.into(), DeliveryFilter::new(mode));
+    }
+
+    /// 設定輸出端的點位篩選
+    ///
+    /// # 回傳值
+    /// 輸出端是否已登記
+    pub fn set_filter(&self, sink: &str, filter: TargetFilter) -> bool {
+        self.sinks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(sink)
+            .map(|delivery| delivery.set_filter(filter))
+            .is_some()
+    }
+
+    /// 設定點位標籤，供 [`TargetMatcher::Tag`] 使用，會取代既有的標籤
+    ///
+    /// # 參數
+    /// - `target`：點位名稱
+    /// - `tags`：點位標籤，如 `billing` 、`internal`
+    pub fn set_tags<I>(&self, target: impl Into<String>, tags: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.tags
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(target.into(), tags.into_iter().map(Into::into).collect());
+    }
+
+    /// 取消登記輸出端
+    pub fn unregister(&self, sink: &str) {
+        self.sinks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(sink);
+    }
+
+    /// 判斷取樣要傳遞給哪些輸出端
+    ///
+    /// # 參數
+    /// - `target`：點位名稱
+    /// - `value`：點位數值
+    /// - `quality`：數值品質
+    ///
+    /// # 回傳值
+    /// 應傳遞的輸出端名稱，依名稱排序
+    #[must_use]
+    pub fn route(&self, target: &str, value: &Value, quality: Quality) -> Vec<String> {
+        let tags = self
+            .tags
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(target)
+            .cloned()
+            .unwrap_or_default();
+
+        self.sinks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter_mut()
+            .filter_map(|(sink, filter)| {
+                filter
+                    .should_deliver(target, &tags, value, quality)
+                    .then(|| sink.clone())
+            })
+            .collect()
+    }
+
+    /// 清除所有輸出端中點位最後一次傳遞的紀錄
+    ///
+    /// 點位被移除或重新登記時調用，參見 [`crate::reload::TargetDiff`] ，點位標籤不會被清除
+    pub fn forget(&self, target: &str) {
+        self.sinks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values_mut()
+            .for_each(|filter| filter.forget(target));
+    }
+
+    /// 取得各輸出端的傳遞統計快照
+    #[must_use]
+    pub fn snapshot(&self) -> BTreeMap<String, DeliverySnapshot> {
+        self.sinks
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(sink, filter)| (sink.clone(), filter.snapshot()))
+            .collect()
+    }
+}
+
+/// 比對含有 `*` 與 `?` 萬用字元的樣式
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    backtrack = Some((star, matched + 1));
+                    p = star + 1;
+                    t = matched + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
@@ -0,0 +1,307 @@
+//! 數值差量編碼
+//!
+//! 高頻率（如每秒 10 次）發布的數值點位若每次都傳送完整的數值，會佔滿窄頻的上行線路，
+//! 橋接程式可以利用 [`DeltaEncoder`] 將數值量化後只傳送與上一筆的差量，接收端以 [`DeltaDecoder`] 還原
+//!
+//! # 編碼方式
+//! 每個點位各自維護一組序號與上一筆的量化值，量化值為 `round(value / quantum)` ，每筆取樣編碼為 [`DeltaSample`] ：
+//!
+//! - [`DeltaSample::Full`]：完整的數值，以下情況會傳送完整的數值
+//!   - 點位的第一筆取樣，或調用 [`DeltaEncoder::reset()`] 後的第一筆取樣
+//!   - 距離上一筆完整數值已經過 [`DeltaConfig::full_every`] 筆取樣
+//!   - 數值變化達到 [`DeltaConfig::threshold`]
+//!   - 距離上一筆取樣超過 [`DeltaConfig::max_gap`] （如連線中斷後恢復）
+//! - [`DeltaSample::Delta`]：量化值與上一筆的差，還原方式為 `(上一筆的量化值 + delta) × quantum`
+//!
+//! 每筆取樣的序號都會遞增，接收端發現序號不連續（遺失封包）時，會捨棄差量直到收到下一筆完整數值，
+//! 因此 [`DeltaConfig::full_every`] 也決定了遺失封包後最長的復原時間；
+//! 發布失敗或重新連線時，請調用 [`DeltaEncoder::reset()`] 讓下一筆取樣改為完整數值
+//!
+//! 差量還原後的數值精度為 [`DeltaConfig::quantum`] ，完整數值則不會失真
+//!
+//! # 範例
+//! ```rust
+//! use std::time::Duration;
+//! use device_state_exchange_lib::delta::{DeltaConfig, DeltaDecoder, DeltaError, DeltaSample, DeltaEncoder};
+//!
+//! let config = DeltaConfig { quantum: 0.1, full_every: 10, threshold: 5.0, max_gap: Duration::from_secs(10) };
+//! let mut encoder = DeltaEncoder::new(config);
+//! let mut decoder = DeltaDecoder::new(config.quantum);
+//!
+//! let first = encoder.encode("voltage", 220.04);
+//! assert_eq!(first, DeltaSample::Full { sequence: 0, value: 220.04 });
+//! let second = encoder.encode("voltage", 220.31);
+//! assert_eq!(second, DeltaSample::Delta { sequence: 1, delta: 3 });
+//! // 變化超過 5.0
+//! assert!(matches!(encoder.encode("voltage", 230.0), DeltaSample::Full { sequence: 2, .. }));
+//!
+//! assert_eq!(decoder.decode("voltage", &first).unwrap(), 220.04);
+//! assert!((decoder.decode("voltage", &second).unwrap() - 220.3).abs() < 1e-9);
+//!
+//! // 遺失序號 2 後，差量會被捨棄直到下一筆完整數值
+//! let fourth = encoder.encode("voltage", 230.1);
+//! assert!(matches!(decoder.decode("voltage", &fourth), Err(DeltaError::Gap { expected: 2, received: 3, .. })));
+//!
+//! // 以 JSON 傳遞時
+//! assert_eq!(serde_json::to_value(&fourth).unwrap(), serde_json::json!({ "kind": "delta", "sequence": 3, "delta": 1 }));
+//! ```
+
+use std::{
+    error::Error,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::HashMap;
+
+/// 差量編碼設定
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DeltaConfig {
+    /// 量化單位，如 `0.01` 表示差量的精度為小數點後兩位，必須大於 0
+    pub quantum: f64,
+    /// 每隔幾筆取樣傳送一次完整數值，為 0 時視為 1 （每筆都傳送完整數值）
+    pub full_every: u32,
+    /// 數值變化達到此值時傳送完整數值
+    pub threshold: f64,
+    /// 距離上一筆取樣超過此時間時傳送完整數值，設定檔中以毫秒數表示
+    #[serde(rename = "max_gap_ms", with = "crate::millis")]
+    pub max_gap: Duration,
+}
+
+/// 差量編碼後的取樣
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DeltaSample {
+    /// 完整數值
+    Full {
+        /// 點位序號
+        sequence: u64,
+        /// 數值
+        value: f64,
+    },
+    /// 與上一筆取樣的量化值差量
+    Delta {
+        /// 點位序號
+        sequence: u64,
+        /// 量化值差量，以 [`DeltaConfig::quantum`] 為單位
+        delta: i64,
+    },
+}
+
+impl DeltaSample {
+    /// 點位序號
+    #[must_use]
+    pub const fn sequence(&self) -> u64 {
+        match self {
+            Self::Full { sequence, .. } | Self::Delta { sequence, .. } => *sequence,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct EncoderState {
+    sequence: u64,
+    quantized: i64,
+    since_full: u32,
+    last_sample: Instant,
+}
+
+/// 差量編碼器
+///
+/// 每個橋接程式的上行連線建立一個，依點位名稱分別記錄狀態
+#[derive(Debug, Clone)]
+pub struct DeltaEncoder {
+    config: DeltaConfig,
+    points: HashMap<String, EncoderState>,
+}
+
+impl DeltaEncoder {
+    /// 建立差量編碼器
+    ///
+    /// # Panics
+    /// [`DeltaConfig::quantum`] 不是大於 0 的有限數值時
+    #[must_use]
+    pub fn new(config: DeltaConfig) -> Self {
+        assert!(
+            config.quantum.is_finite() && config.quantum > 0.0,
+            "量化單位 {} 必須是大於 0 的有限數值",
+            config.quantum
+        );
+
+        Self {
+            config,
+            points: HashMap::default(),
+        }
+    }
+
+    /// 差量編碼設定
+    #[must_use]
+    pub const fn config(&self) -> &DeltaConfig {
+        &self.config
+    }
+
+    /// 編碼一筆取樣
+    ///
+    /// 數值為 NaN 或無限大時一律傳送完整數值
+    pub fn encode(&mut self, target: &str, value: f64) -> DeltaSample {
+        let now = Instant::now();
+        let quantized = quantize(value, self.config.quantum);
+        let previous = self.points.get(target).copied();
+
+        let delta = previous.and_then(|previous| {
+            let delta = quantized?.checked_sub(previous.quantized)?;
+            let significant = (value - dequantize(previous.quantized, self.config.quantum)).abs()
+                >= self.config.threshold;
+            let due = previous.since_full.saturating_add(1) >= self.config.full_every.max(1);
+            let gap = now.duration_since(previous.last_sample) > self.config.max_gap;
+
+            (!significant && !due && !gap).then_some(delta)
+        });
+
+        let sequence = previous.map_or(0, |previous| previous.sequence.wrapping_add(1));
+        let since_full = match (quantized, previous.filter(|_| delta.is_some())) {
+            // 無法量化的數值之後，下一筆仍需傳送完整數值
+            (None, _) => u32::MAX,
+            (Some(_), Some(previous)) => previous.since_full + 1,
+            (Some(_), None) => 0,
+        };
+        self.points.insert(
+            target.to_owned(),
+            EncoderState {
+                sequence,
+                quantized: quantized.unwrap_or_default(),
+                since_full,
+                last_sample: now,
+            },
+        );
+
+        delta.map_or(DeltaSample::Full { sequence, value }, |delta| {
+            DeltaSample::Delta { sequence, delta }
+        })
+    }
+
+    /// 讓點位的下一筆取樣改為完整數值，請在發布失敗或重新連線時調用
+    pub fn reset(&mut self, target: &str) {
+        if let Some(state) = self.points.get_mut(target) {
+            state.since_full = u32::MAX;
+        }
+    }
+
+    /// 讓所有點位的下一筆取樣改為完整數值
+    pub fn reset_all(&mut self) {
+        for state in self.points.values_mut() {
+            state.since_full = u32::MAX;
+        }
+    }
+}
+
+/// 差量解碼器
+#[derive(Debug, Clone)]
+pub struct DeltaDecoder {
+    quantum: f64,
+    points: HashMap<String, (u64, i64)>,
+}
+
+impl DeltaDecoder {
+    /// 建立差量解碼器
+    ///
+    /// # 參數
+    /// - `quantum`：量化單位，必須與編碼端的 [`DeltaConfig::quantum`] 相同
+    #[must_use]
+    pub fn new(quantum: f64) -> Self {
+        Self {
+            quantum,
+            points: HashMap::default(),
+        }
+    }
+
+    /// 還原一筆取樣
+    ///
+    /// # Errors
+    /// 收到差量時，尚未收到點位的完整數值回傳 [`DeltaError::MissingBase`] ，序號不連續時回傳 [`DeltaError::Gap`] ，
+    /// 兩種情況都會捨棄之後的差量直到收到下一筆完整數值
+    pub fn decode(&mut self, target: &str, sample: &DeltaSample) -> Result<f64, DeltaError> {
+        match *sample {
+            DeltaSample::Full { sequence, value } => {
+                match quantize(value, self.quantum) {
+                    Some(quantized) => self.points.insert(target.to_owned(), (sequence, quantized)),
+                    None => self.points.remove(target),
+                };
+                Ok(value)
+            }
+            DeltaSample::Delta { sequence, delta } => {
+                let Some((previous, quantized)) = self.points.remove(target) else {
+                    return Err(DeltaError::MissingBase {
+                        target: target.to_owned(),
+                    });
+                };
+
+                let expected = previous.wrapping_add(1);
+                if sequence != expected {
+                    return Err(DeltaError::Gap {
+                        target: target.to_owned(),
+                        expected,
+                        received: sequence,
+                    });
+                }
+
+                let quantized = quantized.saturating_add(delta);
+                self.points.insert(target.to_owned(), (sequence, quantized));
+                Ok(dequantize(quantized, self.quantum))
+            }
+        }
+    }
+}
+
+/// 差量解碼錯誤
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeltaError {
+    /// 尚未收到點位的完整數值
+    MissingBase {
+        /// 點位名稱
+        target: String,
+    },
+    /// 序號不連續，中間的取樣已遺失
+    Gap {
+        /// 點位名稱
+        target: String,
+        /// 預期的序號
+        expected: u64,
+        /// 收到的序號
+        received: u64,
+    },
+}
+
+impl Display for DeltaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingBase { target } => {
+                write!(f, "點位「{target}」尚未收到完整數值，無法還原差量")
+            }
+            Self::Gap {
+                target,
+                expected,
+                received,
+            } => write!(
+                f,
+                "點位「{target}」的序號不連續（預期 {expected}，收到 {received}），等待下一筆完整數值"
+            ),
+        }
+    }
+}
+
+impl Error for DeltaError {}
+
+#[expect(clippy::cast_possible_truncation)]
+fn quantize(value: f64, quantum: f64) -> Option<i64> {
+    let steps = (value / quantum).round();
+    // i64::MAX 無法以 f64 精確表示，以 2^63 作為上限
+    (steps.is_finite() && steps.abs() < 9_223_372_036_854_775_808.0).then_some(steps as i64)
+}
+
+#[expect(clippy::cast_precision_loss)]
+fn dequantize(quantized: i64, quantum: f64) -> f64 {
+    quantized as f64 * quantum
+}
@@ -0,0 +1,522 @@
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::{Debug, Display, Write},
+    sync::{Arc, PoisonError, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::{
+    ClientId, CorrelationId, DeviceStateResponse, HashMap, RequestContext,
+    memory::{MemoryCharge, MemoryQuota},
+};
+
+/// 診斷指令
+///
+/// 維運工具可以透過主程式將本指令傳遞給 [`crate::Connection::diagnostics()`] ，在同一個受管理的連線上執行協定層級的診斷，
+/// 而不需要另外開啟連線、與主程式搶用序列埠或設備的連線數量
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum DiagnosticsCommand {
+    /// 回音測試，設備應原樣回傳 `payload` ，如 Modbus 功能碼 08 子功能 00
+    Echo {
+        /// 測試資料
+        payload: Vec<u8>,
+    },
+    /// 通訊迴路測試，由實作者決定測試方式，如序列埠的本地迴路
+    Loopback,
+    /// 讀取設備異常狀態，如 Modbus 功能碼 07
+    ReadExceptionStatus,
+    /// 重新啟動設備通訊，如 Modbus 功能碼 08 子功能 01
+    RestartCommunications {
+        /// 是否同時清除設備的通訊事件紀錄
+        #[serde(default)]
+        clear_log: bool,
+    },
+    /// 協定專屬的診斷指令
+    Custom {
+        /// 指令名稱
+        name: String,
+        /// 指令參數
+        #[serde(default)]
+        arguments: Value,
+    },
+}
+
+/// 連線不支援的診斷指令
+///
+/// [`crate::Connection::diagnostics()`] 的預設實作會回傳本錯誤，實作者遇到不支援的指令時，也請回傳本錯誤，讓主程式可以與執行失敗區分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsupportedDiagnostics(pub DiagnosticsCommand);
+
+impl Display for UnsupportedDiagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "連線不支援此診斷指令：{:?}", self.0)
+    }
+}
+
+impl Error for UnsupportedDiagnostics {}
+
+/// 原始封包
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RawFrame {
+    /// 收到封包的時間
+    pub timestamp: SystemTime,
+    /// 封包內容，與設備回覆共用記憶體，序列化時以十六進位字串表示
+    #[serde(serialize_with = "hex")]
+    pub bytes: Bytes,
+}
+
+fn hex<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    serializer.serialize_str(&hex)
+}
+
+/// 原始封包保留區
+///
+/// 依點位保留最近 N 筆 [`DeviceStateResponse::raw()`] 的內容，只有透過 [`RawFrameStore::enable()`] 啟用的點位會被保留，
+/// 主程式應依 [`crate::InitedTarget::keep_raw_frames`] 啟用，並在每次 [`crate::Connection::request_process()`] 完成後調用 [`RawFrameStore::record()`]
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份資料
+///
+/// # 範例
+/// ```rust
+/// # use std::borrow::Cow;
+/// # use device_state_exchange_lib::{DeviceStateResponse, value::ConversionError};
+/// use bytes::Bytes;
+/// use device_state_exchange_lib::diagnostics::RawFrameStore;
+///
+/// #[derive(Debug, Clone)]
+/// struct Frame(Bytes);
+///
+/// impl DeviceStateResponse for Frame {
+///     fn to_value(&self) -> Result<Cow<'_, serde_json::Value>, ConversionError> {
+///         Err(ConversionError::InvalidRaw("長度不符".to_owned()))
+///     }
+///
+///     fn raw(&self) -> Option<Bytes> {
+///         Some(self.0.clone())
+///     }
+/// }
+///
+/// let frames = RawFrameStore::new();
+/// frames.enable("pressure", 2);
+///
+/// let received = Bytes::from_static(&[0x01, 0x02, 0x03, 0x04]);
+/// for frame in [received.slice(0..1), received.slice(1..2), received.slice(2..4)] {
+///     frames.record("pressure", &Frame(frame));
+/// }
+///
+/// let kept = frames.frames("pressure").unwrap();
+/// assert_eq!(kept.iter().map(|frame| frame.bytes.clone()).collect::<Vec<_>>(), [received.slice(1..2), received.slice(2..4)]);
+/// assert_eq!(serde_json::to_value(&kept[1]).unwrap()["bytes"], "0304");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RawFrameStore {
+    targets: Arc<RwLock<HashMap<String, FrameBuffer>>>,
+    quota: Option<MemoryQuota>,
+}
+
+#[derive(Debug, Default)]
+struct FrameBuffer {
+    capacity: usize,
+    frames: VecDeque<(RawFrame, Option<MemoryCharge>)>,
+}
+
+impl FrameBuffer {
+    fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        let excess = self.frames.len().saturating_sub(capacity);
+        self.frames.drain(..excess);
+    }
+
+    fn push(&mut self, frame: RawFrame, quota: Option<&MemoryQuota>) -> bool {
+        if self.frames.len() >= self.capacity {
+            self.frames.pop_front();
+        }
+
+        let charge = match quota {
+            Some(quota) => {
+                let bytes = size_of::<RawFrame>() + frame.bytes.len();
+                match quota.charge_evicting(bytes, || self.frames.pop_front().is_some()) {
+                    Ok(charge) => Some(charge),
+                    Err(_) => return false,
+                }
+            }
+            None => None,
+        };
+        self.frames.push_back((frame, charge));
+        true
+    }
+}
+
+impl RawFrameStore {
+    /// 建立空的原始封包保留區
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 建立受記憶體配額限制的原始封包保留區
+    ///
+    /// 超出配額時會先捨棄同一個點位最舊的封包，仍無法容納時不保留新的封包，參見 [`crate::memory`]
+    #[must_use]
+    pub fn with_quota(quota: MemoryQuota) -> Self {
+        Self {
+            targets: Arc::default(),
+            quota: Some(quota),
+        }
+    }
+
+    /// 啟用點位的原始封包保留
+    ///
+    /// 點位已啟用時，會以新的數量取代，並捨棄超出數量的舊封包
+    ///
+    /// # 參數
+    /// - `name`：點位名稱
+    /// - `capacity`：保留的封包數量，為 0 時等同於 [`RawFrameStore::disable()`]
+    pub fn enable(&self, name: impl Into<String>, capacity: usize) {
+        if capacity == 0 {
+            self.disable(&name.into());
+            return;
+        }
+
+        self.targets
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(name.into())
+            .or_default()
+            .resize(capacity);
+    }
+
+    /// 停用點位的原始封包保留，並捨棄已保留的封包
+    pub fn disable(&self, name: &str) {
+        self.targets
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(name);
+    }
+
+    /// 記錄設備回覆的原始封包
+    ///
+    /// # 參數
+    /// - `name`：點位名稱
+    /// - `response`：設備回覆
+    ///
+    /// # 回傳值
+    /// 是否有保留封包，點位未啟用、回覆沒有原始封包或超出記憶體配額時為 `false`
+    pub fn record(&self, name: &str, response: &dyn DeviceStateResponse) -> bool {
+        let Some(bytes) = response.raw() else {
+            return false;
+        };
+
+        self.targets
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(name)
+            .is_some_and(|buffer| {
+                buffer.push(
+                    RawFrame {
+                        timestamp: SystemTime::now(),
+                        bytes,
+                    },
+                    self.quota.as_ref(),
+                )
+            })
+    }
+
+    /// 取得點位保留的原始封包，依接收順序排列
+    ///
+    /// # 回傳值
+    /// 點位未啟用時為 [`None`]
+    #[must_use]
+    pub fn frames(&self, name: &str) -> Option<Vec<RawFrame>> {
+        self.targets
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .map(|buffer| {
+                buffer
+                    .frames
+                    .iter()
+                    .map(|(frame, _)| frame.clone())
+                    .collect()
+            })
+    }
+}
+
+/// 請求紀錄
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JournalEntry {
+    /// 請求完成的時間
+    pub timestamp: SystemTime,
+    /// 請求花費的時間，序列化時以毫秒數表示
+    #[serde(rename = "duration_ms", with = "crate::millis")]
+    pub duration: Duration,
+    /// 請求內容（[`Debug`] 格式），啟用遮蔽時為 [`None`]
+    pub request: Option<String>,
+    /// 請求來源，自動更新或無法識別時為 [`None`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<ClientId>,
+    /// 請求追蹤編號，參見 [`RequestContext::correlation`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub correlation: Option<CorrelationId>,
+    /// 請求結果
+    #[serde(flatten)]
+    pub outcome: JournalOutcome,
+}
+
+impl JournalEntry {
+    /// 估計紀錄佔用的位元組數，供記憶體配額使用
+    fn estimated_size(&self) -> usize {
+        let outcome = match &self.outcome {
+            JournalOutcome::Response { value } => {
+                value.as_ref().map_or(0, |value| value.to_string().len())
+            }
+            JournalOutcome::Error { message } => message.len(),
+        };
+
+        size_of::<Self>() + self.request.as_ref().map_or(0, String::len) + outcome
+    }
+}
+
+/// 請求結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum JournalOutcome {
+    /// 設備回覆
+    Response {
+        /// 回覆數值，啟用遮蔽時為 [`None`]
+        value: Option<Value>,
+    },
+    /// 請求失敗
+    Error {
+        /// 錯誤訊息
+        message: String,
+    },
+}
+
+/// 請求紀錄保留區
+///
+/// 依連線保留最近 N 筆請求、回覆與錯誤，供事後分析現場偶發的通訊問題，不需要另外擷取完整的封包；
+/// 只有透過 [`RequestJournal::enable()`] 啟用的連線會被記錄，主程式應在每次 [`crate::Connection::request_process()`] 完成後調用 [`RequestJournal::record()`]
+///
+/// 點位數值可能包含敏感資料時，可以在啟用時要求遮蔽，只保留時間、花費時間與錯誤訊息
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份資料
+///
+/// # 範例
+/// ```rust
+/// # use std::borrow::Cow;
+/// # use device_state_exchange_lib::{DeviceStateResponse, value::ConversionError};
+/// use std::time::Duration;
+/// use device_state_exchange_lib::diagnostics::{JournalOutcome, RequestJournal};
+///
+/// #[derive(Debug, Clone)]
+/// struct Reading(f64);
+///
+/// impl DeviceStateResponse for Reading {
+///     fn to_value(&self) -> Result<Cow<'_, serde_json::Value>, ConversionError> {
+///         Ok(Cow::Owned(self.0.into()))
+///     }
+/// }
+///
+/// let journal = RequestJournal::new();
+/// journal.enable("boiler", 2, false);
+///
+/// journal.record("boiler", &"read 40001", Duration::from_millis(12), Ok(&Reading(21.5)));
+/// journal.record("boiler", &"read 40002", Duration::from_millis(500), Err(&std::io::Error::other("逾時")));
+///
+/// let entries = journal.entries("boiler").unwrap();
+/// assert_eq!(entries[0].request.as_deref(), Some("\"read 40001\""));
+/// assert_eq!(entries[1].outcome, JournalOutcome::Error { message: "逾時".to_owned() });
+/// assert_eq!(serde_json::to_value(&entries[1]).unwrap()["duration_ms"], 500);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RequestJournal {
+    connections: Arc<RwLock<HashMap<String, JournalBuffer>>>,
+    quota: Option<MemoryQuota>,
+}
+
+#[derive(Debug, Default)]
+struct JournalBuffer {
+    capacity: usize,
+    redact: bool,
+    entries: VecDeque<(JournalEntry, Option<MemoryCharge>)>,
+}
+
+impl JournalBuffer {
+    fn configure(&mut self, capacity: usize, redact: bool) {
+        self.capacity = capacity;
+        self.redact = redact;
+        let excess = self.entries.len().saturating_sub(capacity);
+        self.entries.drain(..excess);
+    }
+}
+
+impl RequestJournal {
+    /// 建立空的請求紀錄保留區
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 建立受記憶體配額限制的請求紀錄保留區
+    ///
+    /// 超出配額時會先捨棄同一個連線最舊的紀錄，仍無法容納時不記錄本次請求，參見 [`crate::memory`]
+    #[must_use]
+    pub fn with_quota(quota: MemoryQuota) -> Self {
+        Self {
+            connections: Arc::default(),
+            quota: Some(quota),
+        }
+    }
+
+    /// 啟用連線的請求紀錄
+    ///
+    /// 連線已啟用時，會以新的設定取代，並捨棄超出數量的舊紀錄
+    ///
+    /// # 參數
+    /// - `connection`：連線識別名稱
+    /// - `capacity`：保留的紀錄數量，為 0 時等同於 [`RequestJournal::disable()`]
+    /// - `redact`：是否遮蔽請求內容與回覆數值
+    pub fn enable(&self, connection: impl Into<String>, capacity: usize, redact: bool) {
+        if capacity == 0 {
+            self.disable(&connection.into());
+            return;
+        }
+
+        self.connections
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(connection.into())
+            .or_default()
+            .configure(capacity, redact);
+    }
+
+    /// 停用連線的請求紀錄，並捨棄已保留的紀錄
+    pub fn disable(&self, connection: &str) {
+        self.connections
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(connection);
+    }
+
+    /// 記錄一次請求
+    ///
+    /// 與 [`RequestJournal::record_with_context()`] 相同，但不記錄請求來源，適用於自動更新點位的請求
+    ///
+    /// # 參數
+    /// - `connection`：連線識別名稱
+    /// - `request`：傳入 [`crate::Connection::request_process()`] 的請求
+    /// - `duration`：請求花費的時間
+    /// - `outcome`：設備回覆或錯誤
+    ///
+    /// # 回傳值
+    /// 是否有記錄，連線未啟用時為 `false`
+    pub fn record(
+        &self,
+        connection: &str,
+        request: &dyn Debug,
+        duration: Duration,
+        outcome: Result<&dyn DeviceStateResponse, &dyn Error>,
+    ) -> bool {
+        self.record_with_context(
+            connection,
+            &RequestContext::default(),
+            request,
+            duration,
+            outcome,
+        )
+    }
+
+    /// 記錄一次外部服務的請求
+    ///
+    /// # 參數
+    /// - `connection`：連線識別名稱
+    /// - `context`：請求資訊，參見 [`crate::WriteCommand::context()`] ，請求來源與請求追蹤編號不會被遮蔽
+    /// - `request`：傳入 [`crate::Connection::request_process()`] 的請求
+    /// - `duration`：請求花費的時間
+    /// - `outcome`：設備回覆或錯誤
+    ///
+    /// # 回傳值
+    /// 是否有記錄，連線未啟用或超出記憶體配額時為 `false`
+    pub fn record_with_context(
+        &self,
+        connection: &str,
+        context: &RequestContext,
+        request: &dyn Debug,
+        duration: Duration,
+        outcome: Result<&dyn DeviceStateResponse, &dyn Error>,
+    ) -> bool {
+        let mut connections = self
+            .connections
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let Some(buffer) = connections.get_mut(connection) else {
+            return false;
+        };
+
+        let entry = JournalEntry {
+            timestamp: SystemTime::now(),
+            duration,
+            request: (!buffer.redact).then(|| format!("{request:?}")),
+            client: context.client.clone(),
+            correlation: context.correlation.clone(),
+            outcome: match outcome {
+                Ok(response) => JournalOutcome::Response {
+                    value: (!buffer.redact).then(|| response.to_value_lossy()),
+                },
+                Err(error) => JournalOutcome::Error {
+                    message: error.to_string(),
+                },
+            },
+        };
+
+        if buffer.entries.len() >= buffer.capacity {
+            buffer.entries.pop_front();
+        }
+        let charge = match &self.quota {
+            Some(quota) => {
+                match quota.charge_evicting(entry.estimated_size(), || {
+                    buffer.entries.pop_front().is_some()
+                }) {
+                    Ok(charge) => Some(charge),
+                    Err(_) => return false,
+                }
+            }
+            None => None,
+        };
+        buffer.entries.push_back((entry, charge));
+        drop(connections);
+
+        true
+    }
+
+    /// 取得連線保留的請求紀錄，依時間由舊到新排列
+    ///
+    /// # 回傳值
+    /// 連線未啟用時為 [`None`]
+    #[must_use]
+    pub fn entries(&self, connection: &str) -> Option<Vec<JournalEntry>> {
+        self.connections
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(connection)
+            .map(|buffer| {
+                buffer
+                    .entries
+                    .iter()
+                    .map(|(entry, _)| entry.clone())
+                    .collect()
+            })
+    }
+}
@@ -0,0 +1,115 @@
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{Connection, DeviceStateResponse, serial::SerialProbeReport};
+
+/// 設備探索報告
+///
+/// 實作者可以在 [`crate::Connection::init()`] 中自動偵測連線參數，並將結果放入 [`crate::ConnectionArtifact::discovery`] 回報給主程式，
+/// 供主程式或調試工具顯示、寫回設定檔
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoveryReport {
+    /// 序列埠線路參數探測報告，參見 [`crate::serial::probe_line_settings()`]
+    pub serial: Option<SerialProbeReport>,
+    /// 匯流排設備編號掃描報告，參見 [`scan_bus()`]
+    pub bus_scan: Option<BusScanReport>,
+}
+
+/// 探測結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProbeOutcome {
+    /// 設備正常回應
+    Responded,
+    /// 探測請求回傳錯誤
+    Failed(String),
+    /// 探測請求逾時
+    TimedOut,
+}
+
+/// 匯流排設備編號掃描報告
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BusScanReport {
+    /// 各設備編號的掃描結果，依掃描順序排列
+    pub addresses: Vec<ScannedAddress>,
+}
+
+impl BusScanReport {
+    /// 有回應的設備編號
+    pub fn responding(&self) -> impl Iterator<Item = &ScannedAddress> {
+        self.addresses
+            .iter()
+            .filter(|address| address.outcome == ProbeOutcome::Responded)
+    }
+}
+
+/// 單一設備編號的掃描結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScannedAddress {
+    /// 設備編號，對應 [`crate::TargetAddressNumber`]
+    pub address_number: String,
+    /// 探測結果
+    pub outcome: ProbeOutcome,
+    /// 本次探測所花費的時間
+    pub elapsed: Duration,
+    /// 設備回應的內容（[`DeviceStateResponse::to_value()`] 的結果），沒有回應或回應無法轉換時為 [`None`]
+    pub response: Option<Value>,
+}
+
+/// 掃描多點（multi-drop）匯流排上的設備編號
+///
+/// 依序以 `ping` 為每個設備編號建立探測請求，並透過 [`Connection::request_process()`] 送出，在 `timeout` 內回傳 [`Ok`] 的設備編號即視為存在
+///
+/// 掃描會佔用連線，請在初始化階段或暫停自動更新時使用，建議將 `timeout` 設定得比一般請求短，以縮短掃描時間
+///
+/// 本 function 利用 [`tokio::time::timeout()`] 計算逾時，需於 tokio runtime 中執行
+///
+/// # 參數
+/// - `connection`：已建立的設備連線
+/// - `addresses`：欲掃描的設備編號，如 Modbus 的 `1..=247`
+/// - `timeout`：單一設備編號的逾時
+/// - `ping`：依設備編號建立探測請求的 closure ，建議使用讀取量最小、所有設備皆支援的請求
+///
+/// # 回傳值
+/// 掃描報告，參見 [`BusScanReport`]
+pub async fn scan_bus<C, A>(
+    connection: &mut C,
+    addresses: impl IntoIterator<Item = A>,
+    timeout: Duration,
+    mut ping: impl FnMut(&A) -> C::Request,
+) -> BusScanReport
+where
+    C: Connection,
+    A: Display,
+{
+    let mut report = BusScanReport::default();
+
+    for address in addresses {
+        let request = ping(&address);
+        let started = Instant::now();
+
+        let (outcome, response) =
+            match tokio::time::timeout(timeout, connection.request_process(request)).await {
+                Ok(Ok((response, _))) => (
+                    ProbeOutcome::Responded,
+                    response.to_value().ok().map(Cow::into_owned),
+                ),
+                Ok(Err(error)) => (ProbeOutcome::Failed(error.to_string()), None),
+                Err(_) => (ProbeOutcome::TimedOut, None),
+            };
+
+        report.addresses.push(ScannedAddress {
+            address_number: address.to_string(),
+            outcome,
+            elapsed: started.elapsed(),
+            response,
+        });
+    }
+
+    report
+}
@@ -0,0 +1,1443 @@
+//! DNP3 主站參考實作（需啟用 `dnp3` feature）
+//!
+//! 以 DNP3 over TCP（IEEE 1815）輪詢子站（outstation），[`Dnp3Connection`] 維護各點位最新的數值，
+//! [`Connection::request_process()`] 在輪詢到期時送出輪詢，再由最新的數值回覆點位：
+//!
+//! - 完整性輪詢（integrity poll）：讀取 Class 0123 ，取得所有點位的目前數值與尚未讀取的事件，
+//!   間隔為 [`Dnp3Config::integrity_interval`] ，連線建立、子站重新啟動或事件緩衝區溢位時立即執行
+//! - 事件輪詢（event poll）：只讀取點位使用的事件類別（`class`），間隔為 [`Dnp3Config::event_interval`] ，
+//!   子站回報還有事件時，下一個請求會立即再次輪詢
+//! - 主動回報（unsolicited response）：等待回覆期間收到的主動回報同樣會更新數值，需要確認時自動回覆確認；是否主動回報由子站設定決定
+//! - 品質：點位的旗標（flags）會轉換為 [`Quality`] ，參見 [`Flags::quality()`]
+//! - 子站重新啟動：回覆的 IIN 包含 `DEVICE_RESTART` 時清除該位元並執行完整性輪詢
+//!
+//! 本連線只讀取點位，不支援控制輸出。
+//!
+//! 點位設定：
+//!
+//! | 欄位 | 說明 |
+//! | --- | --- |
+//! | `address` | 點位類型與索引，如 `ai:3` ，類型參見 [`PointType`] |
+//! | `class` | 點位的事件類別（1 至 3），0 表示只在完整性輪詢時更新；所有點位都沒有設定時，事件輪詢讀取全部類別 |
+//!
+//! # 範例
+//! ```rust
+//! use std::time::Duration;
+//! use device_state_exchange_lib::{
+//!     TargetDefinition,
+//!     dnp3::{Dnp3Config, Dnp3Point, Dnp3Request, Flags, PointType},
+//!     value::Quality,
+//! };
+//! use serde_json::json;
+//!
+//! let config: Dnp3Config = serde_json::from_value(json!({
+//!     "host": "10.0.0.20",
+//!     "port": 20000,
+//!     "outstation_address": 10,
+//!     "integrity_interval_ms": 600000,
+//!     "event_interval_ms": 2000,
+//! }))
+//! .unwrap();
+//! assert_eq!(config.master_address, 1);
+//! assert_eq!(config.event_interval, Duration::from_secs(2));
+//!
+//! let definition: TargetDefinition = serde_json::from_value(json!({
+//!     "name": "饋線電流",
+//!     "address": "ai:3",
+//!     "class": 2,
+//! }))
+//! .unwrap();
+//! let request = Dnp3Request::parse(&definition).unwrap();
+//! assert_eq!(request.point, Dnp3Point { kind: PointType::AnalogInput, index: 3 });
+//! assert_eq!(request.class, Some(2));
+//! assert_eq!(request.point.to_string(), "ai:3");
+//!
+//! assert_eq!(Flags(0x01).quality(PointType::AnalogInput), Quality::Good);
+//! assert_eq!(Flags(0x21).quality(PointType::AnalogInput), Quality::Uncertain);
+//! assert_eq!(Flags(0x05).quality(PointType::AnalogInput), Quality::Stale);
+//! assert_eq!(Flags(0x80).quality(PointType::BinaryInput), Quality::Bad);
+//! ```
+
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::Display,
+    io::ErrorKind,
+    net::SocketAddr,
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{io::AsyncWriteExt, net::TcpStream, time::Instant};
+
+use crate::{
+    Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+    ConnectionTargets, DeviceStateRequest, DeviceStateResponse, HashMap, InitedTarget,
+    RemoteAddress, Target, TargetDefinition,
+    definition::InvalidTarget,
+    fixture::FrameDecoder,
+    transport::{
+        frame::FrameReader,
+        tcp::{self, TcpEndpoint},
+    },
+    value::{ConversionError, Quality},
+};
+
+/// 預設的主站位址
+pub const DEFAULT_MASTER_ADDRESS: u16 = 1;
+
+/// 預設的子站位址
+pub const DEFAULT_OUTSTATION_ADDRESS: u16 = 1024;
+
+/// 預設的更新間隔
+pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 預設的回覆逾時
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 預設的完整性輪詢間隔
+pub const DEFAULT_INTEGRITY_INTERVAL: Duration = Duration::from_mins(10);
+
+/// 預設的事件輪詢間隔
+pub const DEFAULT_EVENT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 鏈結層訊框的起始位元組
+const START: [u8; 2] = [0x05, 0x64];
+
+/// 鏈結層標頭長度（不含 CRC）
+const HEADER_LENGTH: usize = 8;
+
+/// 每個資料區塊的長度，區塊之後接著 2 個位元組的 CRC
+const BLOCK_LENGTH: usize = 16;
+
+/// 傳輸層區段的應用層資料長度上限
+const MAX_SEGMENT_LENGTH: usize = 249;
+
+/// 應用層片段長度上限，超過時放棄重組
+const MAX_FRAGMENT_LENGTH: usize = 65_536;
+
+const DIR: u8 = 0x80;
+const PRM: u8 = 0x40;
+const LINK_ACK: u8 = 0;
+const LINK_RESET: u8 = 0;
+const LINK_CONFIRMED_USER_DATA: u8 = 3;
+const LINK_UNCONFIRMED_USER_DATA: u8 = 4;
+const LINK_REQUEST_STATUS: u8 = 9;
+const LINK_STATUS: u8 = 11;
+
+const FIN: u8 = 0x80;
+const FIR: u8 = 0x40;
+const CON: u8 = 0x20;
+const UNS: u8 = 0x10;
+
+const CONFIRM: u8 = 0;
+const READ: u8 = 1;
+const WRITE: u8 = 2;
+const RESPONSE: u8 = 0x81;
+const UNSOLICITED_RESPONSE: u8 = 0x82;
+
+/// IIN1 ：子站重新啟動
+const IIN1_DEVICE_RESTART: u8 = 0x80;
+/// IIN2 ：事件緩衝區溢位
+const IIN2_EVENT_BUFFER_OVERFLOW: u8 = 0x08;
+/// IIN2 ：請求被拒絕（不支援的功能碼、未知的物件、參數錯誤）
+const IIN2_REJECTED: u8 = 0x07;
+
+/// 讀取 Class 1 、2 、3 、0 ，依 IEEE 1815 建議的順序
+const INTEGRITY_POLL: [u8; 12] = [60, 2, 0x06, 60, 3, 0x06, 60, 4, 0x06, 60, 1, 0x06];
+
+/// 清除 IIN1.7（g80v1 ，索引 7 寫入 0）
+const CLEAR_RESTART: [u8; 6] = [80, 1, 0x00, 7, 7, 0x00];
+
+/// 所有事件類別
+const ALL_EVENT_CLASSES: u8 = 0b1110;
+
+const fn default_master_address() -> u16 {
+    DEFAULT_MASTER_ADDRESS
+}
+
+const fn default_outstation_address() -> u16 {
+    DEFAULT_OUTSTATION_ADDRESS
+}
+
+const fn default_update_interval() -> Duration {
+    DEFAULT_UPDATE_INTERVAL
+}
+
+const fn default_timeout() -> Duration {
+    DEFAULT_TIMEOUT
+}
+
+const fn default_integrity_interval() -> Duration {
+    DEFAULT_INTEGRITY_INTERVAL
+}
+
+const fn default_event_interval() -> Duration {
+    DEFAULT_EVENT_INTERVAL
+}
+
+/// 連線參數
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dnp3Config {
+    /// 子站的位址，可經由代理伺服器連線
+    #[serde(flatten)]
+    pub endpoint: TcpEndpoint,
+    /// 主站的鏈結層位址，預設為 [`DEFAULT_MASTER_ADDRESS`]
+    #[serde(default = "default_master_address")]
+    pub master_address: u16,
+    /// 子站的鏈結層位址，預設為 [`DEFAULT_OUTSTATION_ADDRESS`]
+    #[serde(default = "default_outstation_address")]
+    pub outstation_address: u16,
+    /// 更新間隔，序列化時以毫秒數表示，預設為 [`DEFAULT_UPDATE_INTERVAL`]
+    #[serde(
+        rename = "update_interval_ms",
+        with = "crate::millis",
+        default = "default_update_interval"
+    )]
+    pub update_interval: Duration,
+    /// 回覆逾時，序列化時以毫秒數表示，預設為 [`DEFAULT_TIMEOUT`]
+    #[serde(
+        rename = "timeout_ms",
+        with = "crate::millis",
+        default = "default_timeout"
+    )]
+    pub timeout: Duration,
+    /// 完整性輪詢間隔，序列化時以毫秒數表示，預設為 [`DEFAULT_INTEGRITY_INTERVAL`]
+    #[serde(
+        rename = "integrity_interval_ms",
+        with = "crate::millis",
+        default = "default_integrity_interval"
+    )]
+    pub integrity_interval: Duration,
+    /// 事件輪詢間隔，序列化時以毫秒數表示，預設為 [`DEFAULT_EVENT_INTERVAL`]
+    #[serde(
+        rename = "event_interval_ms",
+        with = "crate::millis",
+        default = "default_event_interval"
+    )]
+    pub event_interval: Duration,
+    /// 保持連線間隔，序列化時以毫秒數表示，閒置時送出鏈結層狀態請求，參見 [`ConnectionArtifact::keepalive_interval`]
+    #[serde(
+        rename = "keepalive_interval_ms",
+        with = "crate::millis::option",
+        default
+    )]
+    pub keepalive_interval: Option<Duration>,
+    /// 最高重試次數，參見 [`ConnectionArtifact::max_retry_count`]
+    #[serde(default)]
+    pub max_retry_count: Option<u32>,
+}
+
+impl ConnectionConfig for Dnp3Config {}
+
+/// 點位類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PointType {
+    /// 二進位輸入（`bi`），數值為 `bool`
+    BinaryInput,
+    /// 雙位元二進位輸入（`dbi`），數值為 0（中間狀態）、1（關）、2（開）或 3（不確定）
+    DoubleBitBinaryInput,
+    /// 二進位輸出狀態（`bo`），數值為 `bool`
+    BinaryOutput,
+    /// 計數器（`counter`）
+    Counter,
+    /// 凍結計數器（`frozen_counter`）
+    FrozenCounter,
+    /// 類比輸入（`ai`）
+    AnalogInput,
+    /// 類比輸出狀態（`ao`）
+    AnalogOutput,
+}
+
+impl PointType {
+    const fn prefix(self) -> &'static str {
+        match self {
+            Self::BinaryInput => "bi",
+            Self::DoubleBitBinaryInput => "dbi",
+            Self::BinaryOutput => "bo",
+            Self::Counter => "counter",
+            Self::FrozenCounter => "frozen_counter",
+            Self::AnalogInput => "ai",
+            Self::AnalogOutput => "ao",
+        }
+    }
+}
+
+/// 點位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Dnp3Point {
+    /// 點位類型
+    pub kind: PointType,
+    /// 索引
+    pub index: u16,
+}
+
+impl Dnp3Point {
+    /// 解析 `類型:索引` 格式的點位，如 `ai:3`
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let (prefix, index) = text.trim().split_once(':')?;
+        let kind = [
+            PointType::BinaryInput,
+            PointType::DoubleBitBinaryInput,
+            PointType::BinaryOutput,
+            PointType::Counter,
+            PointType::FrozenCounter,
+            PointType::AnalogInput,
+            PointType::AnalogOutput,
+        ]
+        .into_iter()
+        .find(|kind| kind.prefix().eq_ignore_ascii_case(prefix.trim()))?;
+
+        Some(Self {
+            kind,
+            index: index.trim().parse().ok()?,
+        })
+    }
+}
+
+impl Display for Dnp3Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.kind.prefix(), self.index)
+    }
+}
+
+/// 點位的旗標（flags）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Flags(pub u8);
+
+impl Flags {
+    /// 子站與點位正常運作
+    pub const ONLINE: u8 = 0x01;
+    /// 子站重新啟動後尚未更新
+    pub const RESTART: u8 = 0x02;
+    /// 與數值來源的通訊中斷，數值為中斷前最後一次取得的數值
+    pub const COMM_LOST: u8 = 0x04;
+    /// 數值被遠端強制設定
+    pub const REMOTE_FORCED: u8 = 0x08;
+    /// 數值被本地強制設定
+    pub const LOCAL_FORCED: u8 = 0x10;
+
+    /// 轉換為數值品質
+    ///
+    /// - 沒有 `ONLINE` 時為 [`Quality::Bad`]
+    /// - `COMM_LOST` 時為 [`Quality::Stale`]
+    /// - `RESTART` 、強制設定，或類型特定的異常旗標（二進位輸入的 `CHATTER_FILTER` 、類比的 `OVER_RANGE` 與 `REFERENCE_ERR` 、
+    ///   計數器的 `ROLLOVER` 與 `DISCONTINUITY`）時為 [`Quality::Uncertain`]
+    /// - 其他為 [`Quality::Good`]
+    #[must_use]
+    pub const fn quality(self, kind: PointType) -> Quality {
+        // 二進位點位的最高位元（雙位元為最高兩個位元）為狀態，不是異常旗標
+        let abnormal = match kind {
+            PointType::BinaryInput | PointType::DoubleBitBinaryInput | PointType::BinaryOutput => {
+                0x3A
+            }
+            _ => 0x7A,
+        };
+        if self.0 & Self::ONLINE == 0 {
+            Quality::Bad
+        } else if self.0 & Self::COMM_LOST != 0 {
+            Quality::Stale
+        } else if self.0 & abnormal != 0 {
+            Quality::Uncertain
+        } else {
+            Quality::Good
+        }
+    }
+}
+
+/// 子站回報的點位數值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measurement {
+    /// 數值
+    pub value: Value,
+    /// 旗標，物件沒有旗標時為 [`None`]
+    pub flags: Option<Flags>,
+    /// 事件發生的時間，物件沒有時間時為 [`None`]
+    pub timestamp: Option<SystemTime>,
+}
+
+/// 請求無法完成的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Dnp3Error {
+    /// 子站的回覆中沒有點位
+    UnknownPoint(Dnp3Point),
+    /// 子站拒絕請求，數值為 IIN2
+    Rejected(u8),
+    /// 無法解析的物件
+    UnsupportedObject {
+        /// 群組
+        group: u8,
+        /// 變體
+        variation: u8,
+        /// 限定詞
+        qualifier: u8,
+    },
+}
+
+impl Display for Dnp3Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownPoint(point) => write!(f, "子站的回覆中沒有點位 {point}"),
+            Self::Rejected(iin2) => write!(f, "子站拒絕請求（IIN2 = 0x{iin2:02X}）"),
+            Self::UnsupportedObject {
+                group,
+                variation,
+                qualifier,
+            } => write!(
+                f,
+                "不支援的物件 g{group}v{variation}（限定詞 0x{qualifier:02X}）"
+            ),
+        }
+    }
+}
+
+impl Error for Dnp3Error {}
+
+/// 點位
+#[derive(Debug, Clone)]
+pub struct Dnp3Target(pub TargetDefinition);
+
+impl Target for Dnp3Target {}
+
+/// 讀取請求
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dnp3Request {
+    /// 點位
+    pub point: Dnp3Point,
+    /// 事件類別
+    pub class: Option<u8>,
+}
+
+impl DeviceStateRequest for Dnp3Request {}
+
+impl Dnp3Request {
+    /// 由通用點位定義轉換，欄位說明參見 [`crate::dnp3`]
+    ///
+    /// # Errors
+    /// 點位或事件類別無效時回傳 [`InvalidTarget`]
+    pub fn parse(definition: &TargetDefinition) -> Result<Self, InvalidTarget> {
+        let invalid = |reason: String| InvalidTarget {
+            target: definition.name.clone(),
+            reason,
+        };
+
+        let point = Dnp3Point::parse(&definition.address)
+            .ok_or_else(|| invalid(format!("無效的點位「{}」", definition.address)))?;
+        let class = definition
+            .extra
+            .get("class")
+            .map(|class| {
+                class
+                    .as_u64()
+                    .and_then(|class| u8::try_from(class).ok())
+                    .filter(|class| *class <= 3)
+                    .ok_or_else(|| invalid(format!("class 需為 0 至 3 ：{class}")))
+            })
+            .transpose()?;
+
+        Ok(Self { point, class })
+    }
+}
+
+/// 讀取回覆
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dnp3Response {
+    /// 數值
+    pub value: Value,
+    /// 由旗標轉換的品質，物件沒有旗標時為 [`Quality::Good`]
+    pub quality: Quality,
+    /// 旗標
+    pub flags: Option<Flags>,
+    /// 事件發生的時間
+    pub timestamp: Option<SystemTime>,
+}
+
+impl Dnp3Response {
+    fn new(kind: PointType, measurement: &Measurement) -> Self {
+        Self {
+            value: measurement.value.clone(),
+            quality: measurement
+                .flags
+                .map_or(Quality::Good, |flags| flags.quality(kind)),
+            flags: measurement.flags,
+            timestamp: measurement.timestamp,
+        }
+    }
+}
+
+impl DeviceStateResponse for Dnp3Response {
+    fn to_value(&self) -> Result<Cow<'_, Value>, ConversionError> {
+        Ok(Cow::Borrowed(&self.value))
+    }
+}
+
+/// CRC-16/DNP
+fn crc(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for byte in data {
+        crc ^= u16::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 0 {
+                crc >> 1
+            } else {
+                (crc >> 1) ^ 0xA6BC
+            };
+        }
+    }
+    !crc
+}
+
+fn malformed() -> ConnectionError {
+    ConnectionError::Protocol("無法解析的 DNP3 訊框".to_owned())
+}
+
+/// 驗證鏈結層標頭
+///
+/// # 回傳值
+/// 使用者資料的長度（不含 CRC）
+fn link_header(header: &[u8]) -> Result<usize, ConnectionError> {
+    if header.len() < HEADER_LENGTH + 2
+        || header[..2] != START
+        || crc(&header[..HEADER_LENGTH]).to_le_bytes() != header[HEADER_LENGTH..HEADER_LENGTH + 2]
+        || header[2] < 5
+    {
+        return Err(malformed());
+    }
+    Ok(usize::from(header[2]) - 5)
+}
+
+/// 使用者資料加上各區塊 CRC 後的長度
+const fn blocks_length(data_length: usize) -> usize {
+    data_length + data_length.div_ceil(BLOCK_LENGTH) * 2
+}
+
+/// 拆解鏈結層訊框，並驗證標頭與各資料區塊的 CRC
+///
+/// # 回傳值
+/// 控制位元組、來源位址、目的位址與使用者資料（不含 CRC）
+///
+/// # Errors
+/// 訊框長度不符或 CRC 錯誤時回傳 [`ConnectionError::Protocol`]
+///
+/// # 範例
+/// 以內建的測試向量驗證：
+/// ```rust
+/// use device_state_exchange_lib::{dnp3::split_link_frame, vectors::{self, encode_hex}};
+/// use serde_json::json;
+///
+/// let report = vectors::built_in_set("dnp3/link_frame").unwrap().verify(|frame, value| {
+///     match (split_link_frame(frame), value["data"].as_str()) {
+///         (Ok((control, source, destination, data)), Some(expected))
+///             if json!({ "control": control, "destination": destination, "source": source, "data": expected }) == *value
+///                 && encode_hex(&data) == expected => Ok(()),
+///         (Err(_), None) => Ok(()),
+///         (result, _) => Err(format!("拆解結果為 {result:?}")),
+///     }
+/// });
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+pub fn split_link_frame(frame: &[u8]) -> Result<(u8, u16, u16, Vec<u8>), ConnectionError> {
+    let data_length = link_header(frame)?;
+    let body = &frame[HEADER_LENGTH + 2..];
+    if body.len() != blocks_length(data_length) {
+        return Err(malformed());
+    }
+
+    let mut data = Vec::with_capacity(data_length);
+    for block in body.chunks(BLOCK_LENGTH + 2) {
+        let (block, checksum) = block.split_at(block.len() - 2);
+        if crc(block).to_le_bytes() != checksum {
+            return Err(malformed());
+        }
+        data.extend_from_slice(block);
+    }
+    Ok((
+        frame[3],
+        u16::from_le_bytes([frame[6], frame[7]]),
+        u16::from_le_bytes([frame[4], frame[5]]),
+        data,
+    ))
+}
+
+/// 物件的資料格式
+#[derive(Debug, Clone, Copy)]
+enum Encoding {
+    /// 沒有數值，如 CTO 以外的時間物件
+    Skip,
+    /// 以旗標的最高位元表示狀態
+    StateBit,
+    /// 以旗標的最高兩個位元表示狀態
+    DoubleBit,
+    U16,
+    U32,
+    I16,
+    I32,
+    F32,
+    F64,
+}
+
+/// 物件的時間格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Time {
+    None,
+    /// 自 1970 年起的毫秒數（48 位元）
+    Absolute,
+    /// 相對於前一個 CTO 物件的毫秒數（16 位元）
+    Relative,
+}
+
+/// 物件的格式
+#[derive(Debug, Clone, Copy)]
+enum Layout {
+    /// 每個物件佔用固定位元數，沒有旗標
+    Packed {
+        bits: usize,
+        kind: Option<PointType>,
+    },
+    Fixed {
+        kind: Option<PointType>,
+        flags: bool,
+        encoding: Encoding,
+        time: Time,
+    },
+    /// 時間基準（CTO ，g51）
+    CommonTime,
+    /// 長度等於變體的字串（g110 、g111）
+    Octets,
+}
+
+impl Layout {
+    const fn of(group: u8, variation: u8) -> Option<Self> {
+        use Encoding::{DoubleBit, F32, F64, I16, I32, Skip, StateBit, U16, U32};
+        use PointType::{
+            AnalogInput, AnalogOutput, BinaryInput, BinaryOutput, Counter, DoubleBitBinaryInput,
+            FrozenCounter,
+        };
+
+        const fn fixed(kind: PointType, flags: bool, encoding: Encoding, time: Time) -> Layout {
+            Layout::Fixed {
+                kind: Some(kind),
+                flags,
+                encoding,
+                time,
+            }
+        }
+        const fn analog(kind: PointType, variation: u8, event: bool) -> Option<Layout> {
+            // 類比輸入（g30/g32）與類比輸出（g40/g42）的變體編號不同，統一為 (數值, 時間)
+            let layout = match (kind, event, variation) {
+                (_, _, 1) => (I32, true, Time::None),
+                (_, _, 2) => (I16, true, Time::None),
+                (AnalogInput, false, 3) => (I32, false, Time::None),
+                (AnalogInput, false, 4) => (I16, false, Time::None),
+                (AnalogInput, false, 5) | (AnalogOutput, false, 3) | (_, true, 5) => {
+                    (F32, true, Time::None)
+                }
+                (AnalogInput, false, 6) | (AnalogOutput, false, 4) | (_, true, 6) => {
+                    (F64, true, Time::None)
+                }
+                (_, true, 3) => (I32, true, Time::Absolute),
+                (_, true, 4) => (I16, true, Time::Absolute),
+                (_, true, 7) => (F32, true, Time::Absolute),
+                (_, true, 8) => (F64, true, Time::Absolute),
+                _ => return None,
+            };
+            Some(fixed(kind, layout.1, layout.0, layout.2))
+        }
+        const fn counter(kind: PointType, variation: u8) -> Option<Layout> {
+            let layout = match variation {
+                1 => (U32, true, Time::None),
+                2 => (U16, true, Time::None),
+                5 => (U32, true, Time::Absolute),
+                6 => (U16, true, Time::Absolute),
+                _ => return None,
+            };
+            Some(fixed(kind, layout.1, layout.0, layout.2))
+        }
+
+        match (group, variation) {
+            (1, 1) => Some(Self::Packed {
+                bits: 1,
+                kind: Some(BinaryInput),
+            }),
+            (1, 2) | (2, 1) => Some(fixed(BinaryInput, true, StateBit, Time::None)),
+            (2, 2) => Some(fixed(BinaryInput, true, StateBit, Time::Absolute)),
+            (2, 3) => Some(fixed(BinaryInput, true, StateBit, Time::Relative)),
+            (3, 1) => Some(Self::Packed {
+                bits: 2,
+                kind: Some(DoubleBitBinaryInput),
+            }),
+            (3, 2) | (4, 1) => Some(fixed(DoubleBitBinaryInput, true, DoubleBit, Time::None)),
+            (4, 2) => Some(fixed(DoubleBitBinaryInput, true, DoubleBit, Time::Absolute)),
+            (4, 3) => Some(fixed(DoubleBitBinaryInput, true, DoubleBit, Time::Relative)),
+            (10, 1) => Some(Self::Packed {
+                bits: 1,
+                kind: Some(BinaryOutput),
+            }),
+            (10, 2) | (11, 1) => Some(fixed(BinaryOutput, true, StateBit, Time::None)),
+            (11, 2) => Some(fixed(BinaryOutput, true, StateBit, Time::Absolute)),
+            (20, 1) => Some(fixed(Counter, true, U32, Time::None)),
+            (20, 2) => Some(fixed(Counter, true, U16, Time::None)),
+            (20, 5) => Some(fixed(Counter, false, U32, Time::None)),
+            (20, 6) => Some(fixed(Counter, false, U16, Time::None)),
+            (21, 9) => Some(fixed(FrozenCounter, false, U32, Time::None)),
+            (21, 10) => Some(fixed(FrozenCounter, false, U16, Time::None)),
+            (21 | 23, _) => counter(FrozenCounter, variation),
+            (22, _) => counter(Counter, variation),
+            (30, _) => analog(AnalogInput, variation, false),
+            (32, _) => analog(AnalogInput, variation, true),
+            (40, _) => analog(AnalogOutput, variation, false),
+            (42, _) => analog(AnalogOutput, variation, true),
+            (50, 1) => Some(Self::Fixed {
+                kind: None,
+                flags: false,
+                encoding: Skip,
+                time: Time::Absolute,
+            }),
+            (51, 1 | 2) => Some(Self::CommonTime),
+            (52, 1 | 2) => Some(Self::Fixed {
+                kind: None,
+                flags: false,
+                encoding: U16,
+                time: Time::None,
+            }),
+            (80, 1) => Some(Self::Packed {
+                bits: 1,
+                kind: None,
+            }),
+            (110 | 111, 1..) => Some(Self::Octets),
+            _ => None,
+        }
+    }
+}
+
+/// 應用層物件的讀取器
+struct Objects<'a> {
+    data: &'a [u8],
+    /// 前一個 CTO 物件的時間
+    common_time: Option<u64>,
+}
+
+impl<'a> Objects<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], ConnectionError> {
+        if self.data.len() < length {
+            return Err(malformed());
+        }
+        let (taken, rest) = self.data.split_at(length);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, ConnectionError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ConnectionError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u48(&mut self) -> Result<u64, ConnectionError> {
+        let bytes = self.take(6)?;
+        let mut value = [0; 8];
+        value[..6].copy_from_slice(bytes);
+        Ok(u64::from_le_bytes(value))
+    }
+
+    /// 解析所有物件
+    ///
+    /// # 回傳值
+    /// 點位與數值，依回覆中的順序排列，同一個點位的事件以最後一個為最新
+    fn parse(mut self) -> Result<Vec<(Dnp3Point, Measurement)>, ConnectionError> {
+        let mut measurements = Vec::new();
+        while !self.data.is_empty() {
+            let group = self.u8()?;
+            let variation = self.u8()?;
+            let qualifier = self.u8()?;
+            let unsupported = || {
+                ConnectionError::custom(Dnp3Error::UnsupportedObject {
+                    group,
+                    variation,
+                    qualifier,
+                })
+            };
+            let layout = Layout::of(group, variation).ok_or_else(unsupported)?;
+
+            // 各物件的索引（範圍限定詞）或物件數量，以及索引前綴的長度
+            let range = |start: u16, stop: u16| {
+                (start <= stop)
+                    .then(|| (start..=stop).map(Some).collect::<Vec<_>>())
+                    .ok_or_else(malformed)
+            };
+            let (indexes, prefix_length) = match qualifier {
+                0x00 => {
+                    let start = u16::from(self.u8()?);
+                    (range(start, u16::from(self.u8()?))?, 0)
+                }
+                0x01 => {
+                    let start = self.u16()?;
+                    (range(start, self.u16()?)?, 0)
+                }
+                0x07 => (vec![None; usize::from(self.u8()?)], 0),
+                0x08 => (vec![None; usize::from(self.u16()?)], 0),
+                0x17 => (vec![None; usize::from(self.u8()?)], 1),
+                0x28 => (vec![None; usize::from(self.u16()?)], 2),
+                _ => return Err(unsupported()),
+            };
+
+            if let Layout::Packed { bits, kind } = layout {
+                if prefix_length != 0 || indexes.contains(&None) {
+                    return Err(unsupported());
+                }
+                let packed = self.take((indexes.len() * bits).div_ceil(8))?;
+                for (position, index) in indexes.into_iter().flatten().enumerate() {
+                    let bit = position * bits;
+                    let state = (packed[bit / 8] >> (bit % 8)) & if bits == 1 { 1 } else { 3 };
+                    if let Some(kind) = kind {
+                        let value = if bits == 1 {
+                            Value::Bool(state != 0)
+                        } else {
+                            Value::from(state)
+                        };
+                        measurements.push((
+                            Dnp3Point { kind, index },
+                            Measurement {
+                                value,
+                                flags: None,
+                                timestamp: None,
+                            },
+                        ));
+                    }
+                }
+                continue;
+            }
+
+            for index in indexes {
+                let index = match (index, prefix_length) {
+                    (Some(index), _) => Some(index),
+                    (None, 1) => Some(u16::from(self.u8()?)),
+                    (None, 2) => Some(self.u16()?),
+                    // 沒有索引的物件（如時間）
+                    (None, _) => None,
+                };
+                match layout {
+                    Layout::CommonTime => self.common_time = Some(self.u48()?),
+                    Layout::Octets => {
+                        self.take(usize::from(variation))?;
+                    }
+                    Layout::Fixed {
+                        kind,
+                        flags,
+                        encoding,
+                        time,
+                    } => {
+                        let measurement = self.fixed(flags, encoding, time)?;
+                        if let (Some(kind), Some(index)) = (kind, index) {
+                            measurements.push((Dnp3Point { kind, index }, measurement));
+                        }
+                    }
+                    Layout::Packed { .. } => {}
+                }
+            }
+        }
+        Ok(measurements)
+    }
+
+    fn fixed(
+        &mut self,
+        flags: bool,
+        encoding: Encoding,
+        time: Time,
+    ) -> Result<Measurement, ConnectionError> {
+        let flags = if flags { Some(self.u8()?) } else { None };
+        let value = match encoding {
+            Encoding::Skip => Value::Null,
+            Encoding::StateBit => Value::Bool(flags.unwrap_or_default() & 0x80 != 0),
+            Encoding::DoubleBit => Value::from(flags.unwrap_or_default() >> 6),
+            Encoding::U16 => Value::from(self.u16()?),
+            Encoding::U32 => {
+                let bytes = self.take(4)?;
+                Value::from(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            }
+            Encoding::I16 => {
+                let bytes = self.take(2)?;
+                Value::from(i16::from_le_bytes([bytes[0], bytes[1]]))
+            }
+            Encoding::I32 => {
+                let bytes = self.take(4)?;
+                Value::from(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            }
+            Encoding::F32 => {
+                let bytes = self.take(4)?;
+                Value::from(f64::from(f32::from_le_bytes([
+                    bytes[0], bytes[1], bytes[2], bytes[3],
+                ])))
+            }
+            Encoding::F64 => {
+                let mut bytes = [0; 8];
+                bytes.copy_from_slice(self.take(8)?);
+                Value::from(f64::from_le_bytes(bytes))
+            }
+        };
+        let timestamp = match time {
+            Time::None => None,
+            Time::Absolute => Some(self.u48()?),
+            // 沒有 CTO 時無法得知時間
+            Time::Relative => {
+                let offset = u64::from(self.u16()?);
+                self.common_time.map(|common| common + offset)
+            }
+        };
+
+        Ok(Measurement {
+            value,
+            flags: flags.map(Flags),
+            timestamp: timestamp
+                .map(|millis| SystemTime::UNIX_EPOCH + Duration::from_millis(millis)),
+        })
+    }
+}
+
+/// 鏈結層收到的內容
+#[derive(Debug)]
+enum LinkEvent {
+    /// 鏈結層狀態回覆
+    Status,
+    /// 重組後的應用層片段
+    Fragment(Vec<u8>),
+}
+
+/// 應用層回覆
+#[derive(Debug, Default)]
+struct Response {
+    iin: [u8; 2],
+    measurements: Vec<(Dnp3Point, Measurement)>,
+}
+
+/// DNP3 主站的 TCP 通道
+#[derive(Debug)]
+struct Master {
+    stream: FrameReader<TcpStream>,
+    peer: Option<SocketAddr>,
+    /// 主站位址
+    source: u16,
+    /// 子站位址
+    destination: u16,
+    transport_sequence: u8,
+    application_sequence: u8,
+    /// 重組中的傳輸層區段
+    segments: Option<Vec<u8>>,
+    /// 逾時時已讀取標頭、尚未讀取內容的長度
+    pending: usize,
+}
+
+impl Master {
+    async fn connect(config: &Dnp3Config) -> Result<Self, ConnectionError> {
+        let stream = tokio::time::timeout(config.timeout, tcp::connect(&config.endpoint)).await??;
+        stream.set_nodelay(true)?;
+        let peer = stream.peer_addr().ok();
+        Ok(Self {
+            stream: FrameReader::new(stream),
+            peer,
+            source: config.master_address,
+            destination: config.outstation_address,
+            transport_sequence: 0,
+            application_sequence: 0,
+            segments: None,
+            pending: 0,
+        })
+    }
+
+    /// 送出鏈結層訊框
+    async fn write_link(&mut self, control: u8, data: &[u8]) -> Result<(), ConnectionError> {
+        let length = u8::try_from(5 + data.len()).map_err(|_| malformed())?;
+        let mut frame = Vec::with_capacity(10 + data.len() + data.len().div_ceil(BLOCK_LENGTH) * 2);
+        frame.extend_from_slice(&START);
+        frame.extend_from_slice(&[length, control]);
+        frame.extend_from_slice(&self.destination.to_le_bytes());
+        frame.extend_from_slice(&self.source.to_le_bytes());
+        frame.extend_from_slice(&crc(&frame).to_le_bytes());
+        for block in data.chunks(BLOCK_LENGTH) {
+            frame.extend_from_slice(block);
+            frame.extend_from_slice(&crc(block).to_le_bytes());
+        }
+
+        let stream = self.stream.get_mut();
+        stream.write_all(&frame).await?;
+        stream.flush().await?;
+        Ok(())
+    }
+
+    /// 讀取一個鏈結層訊框，並驗證 CRC
+    ///
+    /// # 回傳值
+    /// 控制位元組、來源位址、目的位址與使用者資料
+    async fn read_link(&mut self) -> Result<(u8, u16, u16, Vec<u8>), ConnectionError> {
+        if self.pending > 0 {
+            self.stream.read_frame(self.pending).await?;
+            self.pending = 0;
+        }
+
+        let header = self.stream.read_frame(HEADER_LENGTH + 2).await?;
+        self.pending = blocks_length(link_header(&header)?);
+        let body = self.stream.read_frame(self.pending).await?;
+        self.pending = 0;
+
+        let mut frame = header.to_vec();
+        frame.extend_from_slice(&body);
+        split_link_frame(&frame)
+    }
+
+    /// 讀取下一個鏈結層狀態回覆或應用層片段，並回應子站的鏈結層請求
+    async fn next_event(&mut self) -> Result<LinkEvent, ConnectionError> {
+        loop {
+            let (control, source, destination, data) = self.read_link().await?;
+            if source != self.destination || destination != self.source {
+                continue;
+            }
+            if control & PRM == 0 {
+                if control & 0x0F == LINK_STATUS {
+                    return Ok(LinkEvent::Status);
+                }
+                continue;
+            }
+            match control & 0x0F {
+                LINK_RESET => {
+                    self.write_link(DIR | LINK_ACK, &[]).await?;
+                    continue;
+                }
+                LINK_REQUEST_STATUS => {
+                    self.write_link(DIR | LINK_STATUS, &[]).await?;
+                    continue;
+                }
+                LINK_CONFIRMED_USER_DATA => self.write_link(DIR | LINK_ACK, &[]).await?,
+                LINK_UNCONFIRMED_USER_DATA => {}
+                _ => continue,
+            }
+
+            let Some((&transport, segment)) = data.split_first() else {
+                continue;
+            };
+            if transport & FIR != 0 {
+                self.segments = Some(Vec::new());
+            }
+            // 沒有收到第一個區段時捨棄
+            let Some(segments) = &mut self.segments else {
+                continue;
+            };
+            segments.extend_from_slice(segment);
+            if segments.len() > MAX_FRAGMENT_LENGTH {
+                self.segments = None;
+                return Err(ConnectionError::Protocol("應用層片段過長".to_owned()));
+            }
+            if transport & FIN != 0
+                && let Some(fragment) = self.segments.take()
+            {
+                return Ok(LinkEvent::Fragment(fragment));
+            }
+        }
+    }
+
+    /// 以傳輸層區段送出應用層片段
+    async fn send_fragment(&mut self, fragment: &[u8]) -> Result<(), ConnectionError> {
+        let count = fragment.chunks(MAX_SEGMENT_LENGTH).count();
+        for (position, chunk) in fragment.chunks(MAX_SEGMENT_LENGTH).enumerate() {
+            let mut transport = self.transport_sequence;
+            self.transport_sequence = (self.transport_sequence + 1) & 0x3F;
+            if position == 0 {
+                transport |= FIR;
+            }
+            if position + 1 == count {
+                transport |= FIN;
+            }
+            let mut segment = Vec::with_capacity(chunk.len() + 1);
+            segment.push(transport);
+            segment.extend_from_slice(chunk);
+            self.write_link(DIR | PRM | LINK_UNCONFIRMED_USER_DATA, &segment)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// 處理主動回報，需要時回覆確認
+    async fn unsolicited(
+        &mut self,
+        fragment: &[u8],
+        response: &mut Response,
+    ) -> Result<(), ConnectionError> {
+        if fragment[0] & CON != 0 {
+            self.send_fragment(&[FIR | FIN | UNS | (fragment[0] & 0x0F), CONFIRM])
+                .await?;
+        }
+        let objects = Objects {
+            data: &fragment[4..],
+            common_time: None,
+        };
+        response.measurements.extend(objects.parse()?);
+        Ok(())
+    }
+
+    /// 送出請求並等待回覆，多片段的回覆會合併
+    async fn request(&mut self, function: u8, objects: &[u8]) -> Result<Response, ConnectionError> {
+        let sequence = self.application_sequence;
+        self.application_sequence = (self.application_sequence + 1) & 0x0F;
+        let mut fragment = Vec::with_capacity(objects.len() + 2);
+        fragment.extend_from_slice(&[FIR | FIN | sequence, function]);
+        fragment.extend_from_slice(objects);
+        self.send_fragment(&fragment).await?;
+
+        let mut response = Response::default();
+        let mut expected = sequence;
+        loop {
+            let LinkEvent::Fragment(fragment) = self.next_event().await? else {
+                continue;
+            };
+            if fragment.len() < 4 {
+                return Err(malformed());
+            }
+            let control = fragment[0];
+            if fragment[1] == UNSOLICITED_RESPONSE {
+                self.unsolicited(&fragment, &mut response).await?;
+                continue;
+            }
+            // 先前逾時的請求的回覆
+            if fragment[1] != RESPONSE || control & 0x0F != expected {
+                continue;
+            }
+            if control & CON != 0 {
+                self.send_fragment(&[FIR | FIN | expected, CONFIRM]).await?;
+            }
+            response.iin = [fragment[2], fragment[3]];
+            let objects = Objects {
+                data: &fragment[4..],
+                common_time: None,
+            };
+            response.measurements.extend(objects.parse()?);
+            if control & FIN != 0 {
+                return Ok(response);
+            }
+            expected = (expected + 1) & 0x0F;
+            self.application_sequence = (expected + 1) & 0x0F;
+        }
+    }
+
+    /// 送出鏈結層狀態請求並等待回覆
+    async fn link_status(&mut self) -> Result<Response, ConnectionError> {
+        self.write_link(DIR | PRM | LINK_REQUEST_STATUS, &[])
+            .await?;
+        let mut response = Response::default();
+        loop {
+            let event = self.next_event().await?;
+            match event {
+                LinkEvent::Status => return Ok(response),
+                LinkEvent::Fragment(fragment)
+                    if fragment.len() >= 4 && fragment[1] == UNSOLICITED_RESPONSE =>
+                {
+                    self.unsolicited(&fragment, &mut response).await?;
+                }
+                LinkEvent::Fragment(_) => {}
+            }
+        }
+    }
+}
+
+/// DNP3 主站連線
+pub struct Dnp3Connection {
+    config: Dnp3Config,
+    master: Option<Master>,
+    /// 各點位最新的數值
+    points: HashMap<Dnp3Point, Measurement>,
+    /// 事件輪詢讀取的類別，第 1 至 3 位元分別為 Class 1 至 3
+    event_classes: u8,
+    integrity_polled: Option<Instant>,
+    events_polled: Option<Instant>,
+    remote_address: RemoteAddress,
+    rejected: Vec<InvalidTarget>,
+}
+
+impl Dnp3Connection {
+    /// 初始化點位時被略過的點位
+    #[must_use]
+    pub fn rejected_targets(&self) -> &[InvalidTarget] {
+        &self.rejected
+    }
+
+    /// 取得主站通道，連線已中斷時先重新連線並安排完整性輪詢
+    async fn master(&mut self) -> Result<&mut Master, ConnectionError> {
+        if self.master.is_none() {
+            let master = Master::connect(&self.config).await?;
+            self.remote_address.set(master.peer);
+            self.master = Some(master);
+            self.integrity_polled = None;
+        }
+        self.master
+            .as_mut()
+            .ok_or_else(|| std::io::Error::from(ErrorKind::NotConnected).into())
+    }
+
+    /// 讀寫失敗或回覆無法解析時關閉連線，讓下一個請求重新連線
+    fn settle<T>(&mut self, result: Result<T, ConnectionError>) -> Result<T, ConnectionError> {
+        if let Err(ConnectionError::Io(_) | ConnectionError::Protocol(_)) = &result {
+            self.master = None;
+            self.remote_address.set(None);
+        }
+        result
+    }
+
+    /// 更新點位數值，並依 IIN 安排輪詢
+    async fn apply(&mut self, response: Response) -> Result<(), ConnectionError> {
+        self.points.extend(response.measurements);
+        let [iin1, iin2] = response.iin;
+        if iin1 & IIN1_DEVICE_RESTART != 0 {
+            let timeout = self.config.timeout;
+            let master = self.master().await?;
+            let result = tokio::time::timeout(timeout, master.request(WRITE, &CLEAR_RESTART))
+                .await
+                .map_err(ConnectionError::from)
+                .flatten();
+            let response = self.settle(result)?;
+            self.points.extend(response.measurements);
+            self.integrity_polled = None;
+        }
+        if iin2 & IIN2_EVENT_BUFFER_OVERFLOW != 0 {
+            self.integrity_polled = None;
+        }
+        // 子站還有尚未讀取的事件
+        if iin1 & self.event_classes != 0 {
+            self.events_polled = None;
+        }
+        if iin2 & IIN2_REJECTED != 0 {
+            return Err(ConnectionError::custom(Dnp3Error::Rejected(iin2)));
+        }
+        Ok(())
+    }
+
+    async fn request(&mut self, function: u8, objects: &[u8]) -> Result<(), ConnectionError> {
+        let timeout = self.config.timeout;
+        let master = self.master().await?;
+        let result = tokio::time::timeout(timeout, master.request(function, objects))
+            .await
+            .map_err(ConnectionError::from)
+            .flatten();
+        let response = self.settle(result)?;
+        self.apply(response).await
+    }
+
+    /// 到期時執行完整性輪詢或事件輪詢
+    ///
+    /// # 回傳值
+    /// 是否送出了輪詢
+    async fn poll(&mut self) -> Result<bool, ConnectionError> {
+        if self.master.is_none() {
+            self.master().await?;
+        }
+        let due = |polled: Option<Instant>, interval: Duration| {
+            polled.is_none_or(|polled| polled.elapsed() >= interval)
+        };
+
+        if due(self.integrity_polled, self.config.integrity_interval) {
+            let now = Instant::now();
+            self.integrity_polled = Some(now);
+            self.events_polled = Some(now);
+            if let Err(error) = self.request(READ, &INTEGRITY_POLL).await {
+                self.integrity_polled = None;
+                return Err(error);
+            }
+            return Ok(true);
+        }
+        if due(self.events_polled, self.config.event_interval) {
+            self.events_polled = Some(Instant::now());
+            let objects: Vec<u8> = (1..=3)
+                .filter(|class| self.event_classes & (1 << class) != 0)
+                .flat_map(|class| [60, class + 1, 0x06])
+                .collect();
+            self.request(READ, &objects).await?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+}
+
+impl Connection for Dnp3Connection {
+    const NAMES: &[&str] = &["Dnp3"];
+    type Config = Dnp3Config;
+    type Target = Dnp3Target;
+    type Request = Dnp3Request;
+    type Response = Dnp3Response;
+    type Result = ();
+
+    async fn init(config: &Dnp3Config) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+        let master = Master::connect(config).await?;
+        let statistics = ConnectionStats::new(
+            format!("{}:{}", config.endpoint.host, config.endpoint.port),
+            None,
+        );
+        statistics.remote_address.set(master.peer);
+
+        let artifact = ConnectionArtifact::new(
+            Self {
+                config: config.clone(),
+                master: Some(master),
+                points: HashMap::default(),
+                event_classes: ALL_EVENT_CLASSES,
+                integrity_polled: None,
+                events_polled: None,
+                remote_address: statistics.remote_address.clone(),
+                rejected: Vec::new(),
+            },
+            statistics,
+        )
+        .update_every(config.update_interval)
+        .timeout_after(config.timeout);
+        let artifact = match config.keepalive_interval {
+            Some(interval) => artifact.keepalive_every(interval),
+            None => artifact,
+        };
+
+        Ok(match config.max_retry_count {
+            Some(count) => artifact.retry_up_to(count),
+            None => artifact,
+        })
+    }
+
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<Dnp3Target>,
+    ) -> ConnectionTargets<Dnp3Request, ()> {
+        let mut inited = Vec::with_capacity(targets.len());
+        let mut event_classes = 0;
+        let mut classified = false;
+        for Dnp3Target(definition) in targets {
+            match Dnp3Request::parse(&definition) {
+                Ok(request) => {
+                    if let Some(class) = request.class {
+                        classified = true;
+                        if class > 0 {
+                            event_classes |= 1 << class;
+                        }
+                    }
+                    inited.push(InitedTarget {
+                        name: definition.name,
+                        request,
+                        result: (),
+                        default_status: definition.default_status,
+                        auto_refresh: definition.auto_refresh,
+                        refresh_interval: None,
+                        keep_raw_frames: None,
+                        group: None,
+                        safe_state: None,
+                        array: None,
+                        change: None,
+                        statistics: Some(connection_statistics.insert_target(definition.device)),
+                    });
+                }
+                Err(error) => self.rejected.push(error),
+            }
+        }
+        self.event_classes = if classified {
+            event_classes
+        } else {
+            ALL_EVENT_CLASSES
+        };
+        ConnectionTargets(inited)
+    }
+
+    async fn request_process(
+        &mut self,
+        request: Dnp3Request,
+    ) -> Result<(Dnp3Response, bool), ConnectionError> {
+        let polled = self.poll().await?;
+        let measurement = self
+            .points
+            .get(&request.point)
+            .ok_or_else(|| ConnectionError::custom(Dnp3Error::UnknownPoint(request.point)))?;
+
+        Ok((Dnp3Response::new(request.point.kind, measurement), polled))
+    }
+
+    async fn keepalive(&mut self) -> Result<(), ConnectionError> {
+        let timeout = self.config.timeout;
+        let master = self.master().await?;
+        let result = tokio::time::timeout(timeout, master.link_status())
+            .await
+            .map_err(ConnectionError::from)
+            .flatten();
+        let response = self.settle(result)?;
+        self.points.extend(response.measurements);
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> Result<(), ConnectionError> {
+        self.master = None;
+        self.remote_address.set(None);
+        Ok(())
+    }
+
+    async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+        self.master = None;
+        self.remote_address.set(None);
+        self.master().await.map(|_| ())
+    }
+
+    async fn update_config(&mut self, new_config: &Dnp3Config) -> Result<(), ConnectionError> {
+        self.config = new_config.clone();
+        self.points.clear();
+        self.events_polled = None;
+        self.reconnect().await
+    }
+}
+
+/// 以記錄下來的鏈結層訊框解碼點位
+///
+/// `frame` 為包含單一區段回覆（或主動回報）的鏈結層訊框，`target` 為點位的 `address`（如 `ai:3`），
+/// 同一個點位出現多次時以最後一個為準
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::{dnp3::Dnp3Connection, fixture::Fixture};
+///
+/// let fixture = Fixture::from_json(r#"{
+///     "connection": "Dnp3",
+///     "cases": [
+///         { "name": "g30v1", "target": "ai:3", "frame": "0564144401000a00 aaac c0c08100001e01000303012a000000 04ed", "expected": 42 },
+///         { "name": "g30v5", "target": "ai:4", "frame": "0564194401000a00 ffce c1c18100001e05000304010000ac4101 4f3f 00002041 d669", "expected": 10.0 },
+///         { "name": "沒有點位", "target": "ai:5", "frame": "0564194401000a00 ffce c1c18100001e05000304010000ac4101 4f3f 00002041 d669" },
+///         { "name": "子站拒絕", "target": "ai:3", "frame": "05640a4401000a00 6e25 c2c2810001 6750" }
+///     ]
+/// }"#).unwrap();
+///
+/// let report = fixture.run::<Dnp3Connection>();
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+impl FrameDecoder for Dnp3Connection {
+    fn decode_frame(target: &str, frame: &[u8]) -> Result<Dnp3Response, Box<dyn Error>> {
+        let point = Dnp3Point::parse(target).ok_or_else(|| format!("無效的點位「{target}」"))?;
+        let (_, _, _, data) = split_link_frame(frame)?;
+        let fragment = match data.split_first() {
+            Some((transport, fragment))
+                if transport & (FIR | FIN) == FIR | FIN
+                    && fragment.len() >= 4
+                    && matches!(fragment[1], RESPONSE | UNSOLICITED_RESPONSE) =>
+            {
+                fragment
+            }
+            _ => return Err(malformed().into()),
+        };
+        if fragment[3] & IIN2_REJECTED != 0 {
+            return Err(ConnectionError::custom(Dnp3Error::Rejected(fragment[3])).into());
+        }
+
+        let objects = Objects {
+            data: &fragment[4..],
+            common_time: None,
+        };
+        let (_, measurement) = objects
+            .parse()?
+            .into_iter()
+            .rfind(|(received, _)| *received == point)
+            .ok_or_else(|| ConnectionError::custom(Dnp3Error::UnknownPoint(point)))?;
+        Ok(Dnp3Response::new(point.kind, &measurement))
+    }
+}
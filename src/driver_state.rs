@@ -0,0 +1,305 @@
+//! 設備連線共用狀態
+//!
+//! 設備連線經常需要在 [`crate::Connection::request_process()`] （`&mut self`）與 [`crate::Connection::preprocess()`] 、
+//! [`crate::Connection::postprocess()`] 等同步 hook （`&self`）之間，或與背景工作之間共用可變的狀態（如工作階段、最後一次的回覆），
+//! [`DriverState`] 統一了這類狀態的存取方式：
+//!
+//! - async 程式碼以 [`DriverState::read()`] 、[`DriverState::write()`] 等待鎖，不會阻塞 executor
+//! - 同步 hook 以 [`DriverState::try_read()`] 、[`DriverState::try_write()`] 取得鎖，鎖被佔用時立即回傳 [`DriverStateError::WouldBlock`]
+//! - 持有寫入鎖期間發生 panic 時，依 [`PoisonPolicy`] 決定後續是否繼續使用狀態
+//! - 每次取得鎖時記錄是否需要等待與等待時間，以 [`DriverState::snapshot()`] 觀察鎖的競爭情形
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::driver_state::{DriverState, DriverStateError};
+//!
+//! #[derive(Debug, Default)]
+//! struct Session {
+//!     transaction_id: u16,
+//! }
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let state = DriverState::new(Session::default());
+//!
+//! // request_process()
+//! state.write().await.unwrap().transaction_id += 1;
+//!
+//! // postprocess()
+//! assert_eq!(state.try_read().unwrap().transaction_id, 1);
+//!
+//! let guard = state.write().await.unwrap();
+//! assert!(matches!(state.try_read(), Err(DriverStateError::WouldBlock)));
+//! drop(guard);
+//!
+//! let snapshot = state.snapshot();
+//! assert_eq!((snapshot.reads, snapshot.writes, snapshot.would_block), (1, 2, 1));
+//! # }
+//! ```
+
+use std::{
+    error::Error,
+    fmt::Display,
+    ops::{Deref, DerefMut},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// 持有寫入鎖期間發生 panic 後的處理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PoisonPolicy {
+    /// 繼續使用狀態，與本函式庫其他共用資料的處理方式相同，狀態可能只更新了一部分
+    #[default]
+    Recover,
+    /// 回傳 [`DriverStateError::Poisoned`] ，直到調用 [`DriverState::clear_poison()`] 為止
+    Fail,
+}
+
+/// 共用狀態存取錯誤
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriverStateError {
+    /// 持有寫入鎖期間曾發生 panic ，且 [`PoisonPolicy`] 為 [`PoisonPolicy::Fail`]
+    Poisoned,
+    /// 鎖被佔用，只有 [`DriverState::try_read()`] 與 [`DriverState::try_write()`] 會回傳
+    WouldBlock,
+}
+
+impl Display for DriverStateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Poisoned => write!(f, "設備連線共用狀態在寫入期間發生 panic ，狀態可能不完整"),
+            Self::WouldBlock => write!(f, "設備連線共用狀態正被其他工作使用"),
+        }
+    }
+}
+
+impl Error for DriverStateError {}
+
+/// 共用狀態鎖競爭統計快照
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DriverStateSnapshot {
+    /// 成功取得讀取鎖的次數
+    pub reads: u64,
+    /// 成功取得寫入鎖的次數
+    pub writes: u64,
+    /// 需要等待其他工作釋放鎖的次數
+    pub contended: u64,
+    /// [`DriverState::try_read()`] 與 [`DriverState::try_write()`] 因鎖被佔用而失敗的次數
+    pub would_block: u64,
+    /// 累計等待時間，序列化時以微秒數表示
+    #[serde(rename = "total_wait_us", with = "crate::micros")]
+    pub total_wait: Duration,
+    /// 最長等待時間，序列化時以微秒數表示
+    #[serde(rename = "max_wait_us", with = "crate::micros")]
+    pub max_wait: Duration,
+    /// 是否曾在持有寫入鎖期間發生 panic
+    pub poisoned: bool,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    reads: AtomicU64,
+    writes: AtomicU64,
+    contended: AtomicU64,
+    would_block: AtomicU64,
+    total_wait_us: AtomicU64,
+    max_wait_us: AtomicU64,
+}
+
+impl Counters {
+    fn record_wait(&self, started: Instant) {
+        let waited = u64::try_from(started.elapsed().as_micros()).unwrap_or(u64::MAX);
+        self.contended.fetch_add(1, Ordering::Relaxed);
+        self.total_wait_us.fetch_add(waited, Ordering::Relaxed);
+        self.max_wait_us.fetch_max(waited, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Default)]
+struct Shared<T> {
+    lock: RwLock<T>,
+    policy: PoisonPolicy,
+    poisoned: AtomicBool,
+    counters: Counters,
+}
+
+/// 設備連線共用狀態
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份狀態
+#[derive(Debug, Default)]
+pub struct DriverState<T>(Arc<Shared<T>>);
+
+impl<T> Clone for DriverState<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T: Send + Sync> DriverState<T> {
+    /// 建立共用狀態，[`PoisonPolicy`] 為 [`PoisonPolicy::Recover`]
+    #[must_use]
+    pub fn new(value: T) -> Self {
+        Self::with_poison_policy(value, PoisonPolicy::default())
+    }
+
+    /// 建立指定 [`PoisonPolicy`] 的共用狀態
+    #[must_use]
+    pub fn with_poison_policy(value: T, policy: PoisonPolicy) -> Self {
+        Self(Arc::new(Shared {
+            lock: RwLock::new(value),
+            policy,
+            poisoned: AtomicBool::new(false),
+            counters: Counters::default(),
+        }))
+    }
+
+    /// 等待並取得讀取鎖
+    ///
+    /// # Errors
+    /// 狀態已損毀且 [`PoisonPolicy`] 為 [`PoisonPolicy::Fail`] 時回傳 [`DriverStateError::Poisoned`]
+    pub async fn read(&self) -> Result<RwLockReadGuard<'_, T>, DriverStateError> {
+        self.check_poison()?;
+
+        let guard = if let Ok(guard) = self.0.lock.try_read() {
+            guard
+        } else {
+            let started = Instant::now();
+            let guard = self.0.lock.read().await;
+            self.0.counters.record_wait(started);
+            guard
+        };
+
+        self.0.counters.reads.fetch_add(1, Ordering::Relaxed);
+        Ok(guard)
+    }
+
+    /// 等待並取得寫入鎖
+    ///
+    /// # Errors
+    /// 狀態已損毀且 [`PoisonPolicy`] 為 [`PoisonPolicy::Fail`] 時回傳 [`DriverStateError::Poisoned`]
+    pub async fn write(&self) -> Result<DriverStateWriteGuard<'_, T>, DriverStateError> {
+        self.check_poison()?;
+
+        let guard = if let Ok(guard) = self.0.lock.try_write() {
+            guard
+        } else {
+            let started = Instant::now();
+            let guard = self.0.lock.write().await;
+            self.0.counters.record_wait(started);
+            guard
+        };
+
+        self.0.counters.writes.fetch_add(1, Ordering::Relaxed);
+        Ok(self.write_guard(guard))
+    }
+
+    /// 不等待，立即嘗試取得讀取鎖，供同步 hook 使用
+    ///
+    /// # Errors
+    /// 鎖被佔用時回傳 [`DriverStateError::WouldBlock`] ，狀態已損毀且 [`PoisonPolicy`] 為 [`PoisonPolicy::Fail`] 時回傳 [`DriverStateError::Poisoned`]
+    pub fn try_read(&self) -> Result<RwLockReadGuard<'_, T>, DriverStateError> {
+        self.check_poison()?;
+
+        let guard = self.0.lock.try_read().map_err(|_| self.would_block())?;
+        self.0.counters.reads.fetch_add(1, Ordering::Relaxed);
+        Ok(guard)
+    }
+
+    /// 不等待，立即嘗試取得寫入鎖，供同步 hook 使用
+    ///
+    /// # Errors
+    /// 鎖被佔用時回傳 [`DriverStateError::WouldBlock`] ，狀態已損毀且 [`PoisonPolicy`] 為 [`PoisonPolicy::Fail`] 時回傳 [`DriverStateError::Poisoned`]
+    pub fn try_write(&self) -> Result<DriverStateWriteGuard<'_, T>, DriverStateError> {
+        self.check_poison()?;
+
+        let guard = self.0.lock.try_write().map_err(|_| self.would_block())?;
+        self.0.counters.writes.fetch_add(1, Ordering::Relaxed);
+        Ok(self.write_guard(guard))
+    }
+
+    /// 是否曾在持有寫入鎖期間發生 panic
+    #[must_use]
+    pub fn is_poisoned(&self) -> bool {
+        self.0.poisoned.load(Ordering::Relaxed)
+    }
+
+    /// 清除損毀狀態，請在確認狀態已修復（如重新連線後）調用
+    pub fn clear_poison(&self) {
+        self.0.poisoned.store(false, Ordering::Relaxed);
+    }
+
+    /// 取得鎖競爭統計快照
+    #[must_use]
+    pub fn snapshot(&self) -> DriverStateSnapshot {
+        let counters = &self.0.counters;
+
+        DriverStateSnapshot {
+            reads: counters.reads.load(Ordering::Relaxed),
+            writes: counters.writes.load(Ordering::Relaxed),
+            contended: counters.contended.load(Ordering::Relaxed),
+            would_block: counters.would_block.load(Ordering::Relaxed),
+            total_wait: Duration::from_micros(counters.total_wait_us.load(Ordering::Relaxed)),
+            max_wait: Duration::from_micros(counters.max_wait_us.load(Ordering::Relaxed)),
+            poisoned: self.is_poisoned(),
+        }
+    }
+
+    fn check_poison(&self) -> Result<(), DriverStateError> {
+        if self.0.policy == PoisonPolicy::Fail && self.is_poisoned() {
+            Err(DriverStateError::Poisoned)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn would_block(&self) -> DriverStateError {
+        self.0.counters.would_block.fetch_add(1, Ordering::Relaxed);
+        DriverStateError::WouldBlock
+    }
+
+    fn write_guard<'a>(&'a self, guard: RwLockWriteGuard<'a, T>) -> DriverStateWriteGuard<'a, T> {
+        DriverStateWriteGuard {
+            guard,
+            poisoned: &self.0.poisoned,
+        }
+    }
+}
+
+/// 共用狀態寫入鎖
+///
+/// 持有期間發生 panic 時，會將共用狀態標記為損毀，參見 [`PoisonPolicy`]
+#[derive(Debug)]
+pub struct DriverStateWriteGuard<'a, T> {
+    guard: RwLockWriteGuard<'a, T>,
+    poisoned: &'a AtomicBool,
+}
+
+impl<T> Deref for DriverStateWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for DriverStateWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for DriverStateWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            self.poisoned.store(true, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! 回覆編碼
+//!
+//! JSON 在窄頻的上行線路（如 LPWAN 、衛星）上過於冗長，橋接程式可以利用 [`crate::DeviceStateResponse::serialize_with()`]
+//! 搭配本模組的編碼器，將回覆轉換為較精簡的二進位格式，[`crate::DeviceStateResponse::to_value()`] 則仍保留用於偵錯
+//!
+//! - [`JsonEncoder`]：JSON
+//! - [`CborEncoder`]：CBOR（RFC 8949），需啟用 `cbor` feature
+//! - [`MessagePackEncoder`]：`MessagePack` ，需啟用 `msgpack` feature
+//!
+//! 高頻率的數值點位可以再搭配 [`crate::delta`] ，只傳送量化後的差量
+
+use std::{error::Error, fmt::Display};
+
+use serde_json::Value;
+
+use crate::value::ConversionError;
+
+/// 回覆編碼器
+///
+/// 實作本 trait 的 struct/enum 定義了如何將 [`Value`] 編碼為位元組，可自行實作以支援其他格式
+pub trait ResponseEncoder: Send + Sync {
+    /// 編碼後的 MIME type ，如 `application/cbor`
+    fn content_type(&self) -> &'static str;
+
+    /// 編碼
+    ///
+    /// # 參數
+    /// - `value`：[`crate::DeviceStateResponse::to_value()`] 的結果
+    ///
+    /// # 回傳值
+    /// 編碼後的位元組
+    ///
+    /// # Errors
+    /// 無法編碼時回傳 [`EncodingError::Encode`]
+    fn encode(&self, value: &Value) -> Result<Vec<u8>, EncodingError>;
+}
+
+/// 回覆編碼錯誤
+#[derive(Debug)]
+pub enum EncodingError {
+    /// 設備回覆無法轉換為 [`Value`]
+    Conversion(ConversionError),
+    /// 編碼失敗
+    Encode(Box<dyn Error + Send + Sync>),
+}
+
+impl Display for EncodingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Conversion(error) => write!(f, "{error}"),
+            Self::Encode(error) => write!(f, "回覆編碼失敗：{error}"),
+        }
+    }
+}
+
+impl Error for EncodingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Conversion(error) => Some(error),
+            Self::Encode(error) => Some(error.as_ref()),
+        }
+    }
+}
+
+impl From<ConversionError> for EncodingError {
+    fn from(error: ConversionError) -> Self {
+        Self::Conversion(error)
+    }
+}
+
+/// JSON 編碼器
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonEncoder;
+
+impl ResponseEncoder for JsonEncoder {
+    fn content_type(&self) -> &'static str {
+        "application/json"
+    }
+
+    fn encode(&self, value: &Value) -> Result<Vec<u8>, EncodingError> {
+        serde_json::to_vec(value).map_err(|error| EncodingError::Encode(error.into()))
+    }
+}
+
+/// CBOR 編碼器（需啟用 `cbor` feature）
+///
+/// # 範例
+/// ```rust
+/// # use std::borrow::Cow;
+/// # use device_state_exchange_lib::{DeviceStateResponse, value::ConversionError};
+/// use device_state_exchange_lib::encoding::CborEncoder;
+///
+/// #[derive(Debug, Clone)]
+/// struct Reading(serde_json::Value);
+///
+/// impl DeviceStateResponse for Reading {
+///     fn to_value(&self) -> Result<Cow<'_, serde_json::Value>, ConversionError> {
+///         Ok(Cow::Borrowed(&self.0))
+///     }
+/// }
+///
+/// let reading = Reading(serde_json::json!({ "temperature": 21.5 }));
+/// let json = reading.serialize_with(&device_state_exchange_lib::encoding::JsonEncoder).unwrap();
+/// let cbor = reading.serialize_with(&CborEncoder).unwrap();
+/// assert!(cbor.len() < json.len());
+/// ```
+#[cfg(feature = "cbor")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CborEncoder;
+
+#[cfg(feature = "cbor")]
+impl ResponseEncoder for CborEncoder {
+    fn content_type(&self) -> &'static str {
+        "application/cbor"
+    }
+
+    fn encode(&self, value: &Value) -> Result<Vec<u8>, EncodingError> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(value, &mut bytes)
+            .map_err(|error| EncodingError::Encode(error.into()))?;
+        Ok(bytes)
+    }
+}
+
+/// `MessagePack` 編碼器（需啟用 `msgpack` feature）
+///
+/// 物件會編碼為 map（包含欄位名稱），而不是省略欄位名稱的 array ，以便接收端不需要事先知道欄位順序
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MessagePackEncoder;
+
+#[cfg(feature = "msgpack")]
+impl ResponseEncoder for MessagePackEncoder {
+    fn content_type(&self) -> &'static str {
+        "application/msgpack"
+    }
+
+    fn encode(&self, value: &Value) -> Result<Vec<u8>, EncodingError> {
+        rmp_serde::to_vec_named(value).map_err(|error| EncodingError::Encode(error.into()))
+    }
+}
@@ -0,0 +1,190 @@
+//! 回覆信封
+//!
+//! 主程式在 [`crate::Connection::postprocess()`] 完成後，利用 [`EnvelopeSource::wrap()`] 將回覆包裝為 [`ResponseEnvelope`] ，
+//! 再交給事件接收端及橋接程式，讓所有接收端取得相同格式的來源資訊，而不需要各自定義包裝格式
+//!
+//! # 範例
+//! ```rust
+//! # use std::borrow::Cow;
+//! # use device_state_exchange_lib::{DeviceStateResponse, value::ConversionError};
+//! use device_state_exchange_lib::{
+//!     envelope::EnvelopeSource,
+//!     value::{DeviceData, Quality},
+//! };
+//!
+//! #[derive(Debug, Clone)]
+//! struct Reading(Option<f64>);
+//!
+//! impl DeviceStateResponse for Reading {
+//!     fn to_value(&self) -> Result<Cow<'_, serde_json::Value>, ConversionError> {
+//!         self.0
+//!             .map(|value| Cow::Owned(serde_json::json!(value)))
+//!             .ok_or_else(|| ConversionError::InvalidRaw("沒有數值".to_owned()))
+//!     }
+//! }
+//!
+//! let source = EnvelopeSource::new("boiler-room");
+//! let address = Some("1".to_owned());
+//!
+//! let first = source.wrap("temperature", &address, &Reading(Some(21.5)));
+//! let second = source.wrap("temperature", &address, &Reading(None));
+//!
+//! assert_eq!(first.connection, "boiler-room");
+//! assert_eq!(first.value, Some(DeviceData::Scalar(serde_json::json!(21.5))));
+//! assert_eq!(second.sequence, first.sequence + 1);
+//! assert_eq!(second.quality, Quality::Bad);
+//! assert_eq!(second.value, None);
+//! ```
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::SystemTime,
+};
+
+use crate::{
+    DeviceStateResponse, TargetAddressNumber,
+    sampling::SampleGroupStamp,
+    skip::SkipReason,
+    timing::StageTimings,
+    value::{DeviceData, Quality},
+};
+use serde::{Deserialize, Serialize};
+
+/// 回覆信封
+///
+/// 包含回覆數值及其來源資訊，由 [`EnvelopeSource::wrap()`] 產生
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseEnvelope {
+    /// 連線識別名稱
+    pub connection: String,
+    /// 點位名稱
+    pub target: String,
+    /// 設備編號，參見 [`TargetAddressNumber`]
+    pub address: TargetAddressNumber,
+    /// 包裝的時間
+    pub timestamp: SystemTime,
+    /// 數值品質，回覆無法轉換時為 [`Quality::Bad`]
+    pub quality: Quality,
+    /// 序號
+    ///
+    /// 同一個 [`EnvelopeSource`] 產生的信封序號依產生順序遞增，接收端可以依此判斷是否有遺漏或重複的信封
+    pub sequence: u64,
+    /// 回覆資料，參見 [`crate::DeviceStateResponse::to_data()`] ，回覆無法轉換時為 [`None`]
+    pub value: Option<DeviceData>,
+    /// 包裝前各處理階段的耗時，參見 [`crate::timing`] ，主程式未計時時為 [`None`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<StageTimings>,
+    /// 主程式略過請求的原因，參見 [`EnvelopeSource::skipped()`] ，有向設備送出請求時為 [`None`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<SkipReason>,
+    /// 所屬的取樣群組，參見 [`crate::sampling`] ，同一次讀取的信封有相同的 [`ResponseEnvelope::timestamp`] 與群組序號，單獨讀取時為 [`None`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_group: Option<SampleGroupStamp>,
+}
+
+impl ResponseEnvelope {
+    /// 附加各處理階段的耗時，參見 [`ResponseEnvelope::timings`]
+    #[must_use]
+    pub fn with_timings(mut self, timings: StageTimings) -> Self {
+        self.timings = Some(timings);
+        self
+    }
+}
+
+/// 回覆信封來源
+///
+/// 每個連線建立一個，負責填入連線識別名稱及遞增序號
+///
+/// 本 struct 內部利用 [`Arc`] 共享序號，複製後的物件會延續同一組序號
+#[derive(Debug, Clone)]
+pub struct EnvelopeSource {
+    connection: String,
+    sequence: Arc<AtomicU64>,
+}
+
+impl EnvelopeSource {
+    /// 建立回覆信封來源，序號由 0 開始
+    ///
+    /// # 參數
+    /// - `connection`：連線識別名稱
+    #[must_use]
+    pub fn new(connection: impl Into<String>) -> Self {
+        Self {
+            connection: connection.into(),
+            sequence: Arc::default(),
+        }
+    }
+
+    /// 連線識別名稱
+    #[must_use]
+    pub fn connection(&self) -> &str {
+        &self.connection
+    }
+
+    /// 包裝回覆
+    ///
+    /// # 參數
+    /// - `target`：點位名稱
+    /// - `address`：設備編號
+    /// - `response`：經過 [`crate::Connection::postprocess()`] 的回覆
+    ///
+    /// # 回傳值
+    /// 回覆信封，時間為呼叫當下的時間
+    #[must_use]
+    pub fn wrap(
+        &self,
+        target: impl Into<String>,
+        address: &TargetAddressNumber,
+        response: &dyn DeviceStateResponse,
+    ) -> ResponseEnvelope {
+        let (quality, value) = response
+            .to_data()
+            .map_or((Quality::Bad, None), |data| (Quality::Good, Some(data)));
+
+        ResponseEnvelope {
+            connection: self.connection.clone(),
+            target: target.into(),
+            address: address.clone(),
+            timestamp: SystemTime::now(),
+            quality,
+            sequence: self.sequence.fetch_add(1, Ordering::Relaxed),
+            value,
+            timings: None,
+            skipped: None,
+            sample_group: None,
+        }
+    }
+
+    /// 產生略過請求的回覆信封
+    ///
+    /// # 參數
+    /// - `target`：點位名稱
+    /// - `address`：設備編號
+    /// - `reason`：略過請求的原因
+    ///
+    /// # 回傳值
+    /// 不含數值的回覆信封，數值品質為 [`Quality::Uncertain`] ，時間為呼叫當下的時間
+    #[must_use]
+    pub fn skipped(
+        &self,
+        target: impl Into<String>,
+        address: &TargetAddressNumber,
+        reason: SkipReason,
+    ) -> ResponseEnvelope {
+        ResponseEnvelope {
+            connection: self.connection.clone(),
+            target: target.into(),
+            address: address.clone(),
+            timestamp: SystemTime::now(),
+            quality: Quality::Uncertain,
+            sequence: self.sequence.fetch_add(1, Ordering::Relaxed),
+            value: None,
+            timings: None,
+            skipped: Some(reason),
+            sample_group: None,
+        }
+    }
+}
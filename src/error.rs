@@ -0,0 +1,159 @@
+//! 連線錯誤
+//!
+//! [`crate::Connection`] 的所有 method 都以 [`ConnectionError`] 回傳錯誤，主程式可以依錯誤種類決定後續處理方式，
+//! 而不需要比對錯誤訊息：
+//!
+//! - [`ConnectionError::Timeout`] 、[`ConnectionError::Io`] 與 [`ConnectionError::Protocol`]：暫時性的錯誤，
+//!   依 [`crate::ConnectionArtifact::max_retry_count`] 累加失敗次數，達到上限後重新連線
+//! - [`ConnectionError::InvalidConfig`] 與 [`ConnectionError::Fatal`]：重試或重新連線也無法排除的錯誤，
+//!   主程式應停止輪詢該連線，等待新的設定檔（參見 [`crate::Connection::update_config()`]）或人工處理
+//! - [`ConnectionError::Custom`]：實作者自訂的錯誤，視為暫時性的錯誤，可以利用 [`ConnectionError::downcast()`] 取回原本的錯誤
+//!
+//! 實作者可以直接以 `?` 傳遞 [`std::io::Error`] 、[`tokio::time::error::Elapsed`] 與 `Box<dyn Error>` ，其他錯誤請利用
+//! [`ConnectionError::custom()`] 包裝，或依錯誤的性質建立對應的 variant
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::ConnectionError;
+//!
+//! let error = ConnectionError::from(std::io::Error::from(std::io::ErrorKind::ConnectionReset));
+//! assert!(error.is_retryable());
+//!
+//! let error = ConnectionError::InvalidConfig("鮑率不可為 0".to_owned());
+//! assert!(!error.is_retryable());
+//! assert_eq!(error.to_string(), "連線設定錯誤：鮑率不可為 0");
+//! ```
+
+use std::{error::Error, fmt::Display};
+
+/// 連線錯誤
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ConnectionError {
+    /// 等待設備回覆逾時
+    Timeout,
+    /// 讀寫連線失敗，如 socket 被中斷、序列埠被拔除
+    Io(std::io::Error),
+    /// 設備的回覆不符合通訊協定，如校驗碼錯誤、例外回覆
+    Protocol(String),
+    /// 連線設定錯誤，需要新的設定檔才能排除
+    InvalidConfig(String),
+    /// 無法恢復的錯誤，重試或重新連線都無法排除
+    Fatal(String),
+    /// 實作者自訂的錯誤
+    Custom(Box<dyn Error>),
+}
+
+impl ConnectionError {
+    /// 包裝實作者自訂的錯誤
+    #[must_use]
+    pub fn custom(error: impl Error + 'static) -> Self {
+        Self::Custom(Box::new(error))
+    }
+
+    /// 是否為暫時性的錯誤
+    ///
+    /// # 回傳值
+    /// [`ConnectionError::InvalidConfig`] 與 [`ConnectionError::Fatal`] 為 `false` ，其餘為 `true`
+    #[must_use]
+    pub const fn is_retryable(&self) -> bool {
+        !matches!(self, Self::InvalidConfig(_) | Self::Fatal(_))
+    }
+
+    /// 是否為逾時，包含 [`std::io::ErrorKind::TimedOut`] 的 [`ConnectionError::Io`]
+    #[must_use]
+    pub fn is_timeout(&self) -> bool {
+        match self {
+            Self::Timeout => true,
+            Self::Io(error) => error.kind() == std::io::ErrorKind::TimedOut,
+            _ => false,
+        }
+    }
+
+    /// 取回 [`ConnectionError::Custom`] 中的錯誤
+    ///
+    /// # Errors
+    /// 不是 [`ConnectionError::Custom`] 或型別不符時回傳原本的錯誤
+    pub fn downcast<T: Error + 'static>(self) -> Result<T, Self> {
+        match self {
+            Self::Custom(error) => error.downcast().map(|error| *error).map_err(Self::Custom),
+            error => Err(error),
+        }
+    }
+
+    /// 取得 [`ConnectionError::Custom`] 中的錯誤的引用，不是 [`ConnectionError::Custom`] 或型別不符時為 [`None`]
+    #[must_use]
+    pub fn downcast_ref<T: Error + 'static>(&self) -> Option<&T> {
+        match self {
+            Self::Custom(error) => error.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ConnectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Timeout => write!(f, "等待設備回覆逾時"),
+            Self::Io(error) => write!(f, "連線讀寫失敗：{error}"),
+            Self::Protocol(reason) => write!(f, "通訊協定錯誤：{reason}"),
+            Self::InvalidConfig(reason) => write!(f, "連線設定錯誤：{reason}"),
+            Self::Fatal(reason) => write!(f, "無法恢復的連線錯誤：{reason}"),
+            Self::Custom(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Error for ConnectionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Custom(error) => Some(error.as_ref()),
+            Self::Timeout | Self::Protocol(_) | Self::InvalidConfig(_) | Self::Fatal(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ConnectionError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<tokio::time::error::Elapsed> for ConnectionError {
+    fn from(_: tokio::time::error::Elapsed) -> Self {
+        Self::Timeout
+    }
+}
+
+/// 內容為 [`ConnectionError`] 或 [`std::io::Error`] 時會取回原本的錯誤，其餘包裝為 [`ConnectionError::Custom`]
+impl From<Box<dyn Error>> for ConnectionError {
+    fn from(error: Box<dyn Error>) -> Self {
+        let error = match error.downcast::<Self>() {
+            Ok(error) => return *error,
+            Err(error) => error,
+        };
+        match error.downcast::<std::io::Error>() {
+            Ok(error) => Self::Io(*error),
+            Err(error) => Self::Custom(error),
+        }
+    }
+}
+
+impl From<Box<dyn Error + Send + Sync>> for ConnectionError {
+    fn from(error: Box<dyn Error + Send + Sync>) -> Self {
+        Self::from(error as Box<dyn Error>)
+    }
+}
+
+impl From<String> for ConnectionError {
+    fn from(message: String) -> Self {
+        Self::Custom(message.into())
+    }
+}
+
+impl From<&str> for ConnectionError {
+    fn from(message: &str) -> Self {
+        Self::Custom(message.into())
+    }
+}
@@ -0,0 +1,1368 @@
+//! EtherNet/IP 參考實作（需啟用 `ethernet-ip` feature）
+//!
+//! 以 EtherNet/IP 的非連線式訊息（UCMM）讀寫 Allen-Bradley Logix 控制器（ControlLogix 、CompactLogix）的標籤，
+//! 點位直接使用控制器中的標籤名稱，不需要設定位址對應：
+//!
+//! - 讀取：同一輪輪詢中的自動更新點位以 Multiple Service Packet 合併為一個 CIP 請求（參見 [`EthernetIpConfig::max_tags_per_request`]），
+//!   讀取結果保留更新間隔的一半，期間內的其他點位直接使用該結果；回覆超過單一封包時，該標籤改以 `Read Tag Fragmented` 分段讀取
+//! - 結構：UDT 與字串標籤依控制器中的結構範本（Template 物件）解碼為巢狀的 JSON 物件，`STRING` 與自訂長度的字串型別解碼為字串，
+//!   範本在第一次讀取到該結構時由控制器讀取並保留，重新連線後清除
+//! - 寫入：基本資料型別的標籤以 `Write Tag` 寫入，整數中的位元（如 `Status.3`）以 `Read Modify Write` 寫入，不影響其他位元；
+//!   資料型別取自點位的 `data_type` ，未設定時使用最近一次讀取的型別
+//!
+//! 控制器位於機架的其他槽位時，請求以 Unconnected Send 經由 [`EthernetIpConfig::route`] 轉送；Micro800 等不支援轉送的控制器請將 `route` 設為空陣列。
+//! EtherNet/IP 的埠號為 44818 。
+//!
+//! 點位設定：
+//!
+//! | 欄位 | 說明 |
+//! | --- | --- |
+//! | `address` | 標籤路徑，如 `Counter` 、`Motors[2].Speed` 、`Program:MainProgram.Recipe` ；整數標籤後接 `.位元` 讀寫單一位元，如 `Status.3` |
+//! | `elements` | 讀取的陣列元素數量，大於 1 時數值為 JSON 陣列，未設定時為 1 |
+//! | `data_type` | 寫入時使用的基本資料型別，如 `DINT` 、`REAL` ，參見 [`AtomicType`] |
+//!
+//! # 範例
+//! ```rust
+//! use std::time::Duration;
+//! use device_state_exchange_lib::{
+//!     TargetDefinition,
+//!     ethernet_ip::{AtomicType, EthernetIpConfig, EthernetIpRequest},
+//! };
+//! use serde_json::json;
+//!
+//! let config: EthernetIpConfig = serde_json::from_value(json!({
+//!     "host": "192.168.1.10",
+//!     "port": 44818,
+//!     "update_interval_ms": 500,
+//! }))
+//! .unwrap();
+//! assert_eq!(config.route, [1, 0]);
+//! assert_eq!(config.update_interval, Duration::from_millis(500));
+//!
+//! let definition: TargetDefinition = serde_json::from_value(json!({
+//!     "name": "馬達轉速",
+//!     "address": "Program:MainProgram.Motors[2].Speed",
+//!     "data_type": "real",
+//! }))
+//! .unwrap();
+//! let request = EthernetIpRequest::parse(&definition).unwrap();
+//! assert_eq!(request.tag.program(), Some("Program:MainProgram"));
+//! assert_eq!(request.tag.root(), Some("Motors"));
+//! assert_eq!(request.data_type, Some(AtomicType::Real));
+//! assert_eq!(request.elements, 1);
+//!
+//! let definition: TargetDefinition = serde_json::from_value(json!({
+//!     "name": "急停",
+//!     "address": "Status.3",
+//! }))
+//! .unwrap();
+//! let request = EthernetIpRequest::parse(&definition).unwrap();
+//! assert_eq!(request.tag.to_string(), "Status");
+//! assert_eq!(request.bit, Some(3));
+//! ```
+
+mod cip;
+
+use std::{
+    borrow::Cow, error::Error, fmt::Display, io::ErrorKind, net::SocketAddr, sync::Arc,
+    time::Duration,
+};
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{io::AsyncWriteExt, net::TcpStream};
+
+pub use self::cip::{AtomicType, TagPath, TagSegment, TagType};
+use self::cip::{Template, Templates, logical, symbolic};
+use crate::{
+    Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+    ConnectionTargets, DeviceStateRequest, DeviceStateResponse, DeviceStateWrite, HashMap,
+    InitedTarget, RemoteAddress, RequestContext, Target, TargetDefinition,
+    cache::{CacheLookup, CachePolicy, ResponseCache},
+    definition::InvalidTarget,
+    fixture::FrameDecoder,
+    transport::{
+        frame::FrameReader,
+        tcp::{self, TcpEndpoint},
+    },
+    value::ConversionError,
+};
+
+/// 預設的更新間隔
+pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 預設的回覆逾時
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 預設的單次請求標籤數量上限
+pub const DEFAULT_MAX_TAGS_PER_REQUEST: u16 = 20;
+
+/// 預設的轉送路徑：背板（埠號 1）的槽位 0
+pub const DEFAULT_ROUTE: [u8; 2] = [1, 0];
+
+/// 合併讀取的請求長度上限，非連線式訊息最長為 504 個位元組，保留 Unconnected Send 使用的長度
+const MAX_REQUEST_SIZE: usize = 480;
+
+/// 封裝標頭長度
+const HEADER_LENGTH: usize = 24;
+
+const REGISTER_SESSION: u16 = 0x0065;
+const UNREGISTER_SESSION: u16 = 0x0066;
+const SEND_RR_DATA: u16 = 0x006F;
+
+/// 非連線式資料項目
+const UNCONNECTED_DATA_ITEM: u16 = 0x00B2;
+
+const GET_ATTRIBUTE_LIST: u8 = 0x03;
+const MULTIPLE_SERVICE_PACKET: u8 = 0x0A;
+const READ_TAG: u8 = 0x4C;
+const WRITE_TAG: u8 = 0x4D;
+const READ_MODIFY_WRITE: u8 = 0x4E;
+const READ_TAG_FRAGMENTED: u8 = 0x52;
+const GET_INSTANCE_ATTRIBUTE_LIST: u8 = 0x55;
+/// Template 物件的 Read 服務，與 `Read Tag` 的服務碼相同
+const READ_TEMPLATE: u8 = 0x4C;
+const UNCONNECTED_SEND: u8 = 0x52;
+
+const SUCCESS: u8 = 0x00;
+/// 部分傳輸，還有資料尚未讀取
+const PARTIAL_TRANSFER: u8 = 0x06;
+/// 回覆資料過長
+const REPLY_TOO_LARGE: u8 = 0x11;
+/// Multiple Service Packet 中有服務失敗
+const EMBEDDED_SERVICE_ERROR: u8 = 0x1E;
+
+/// Message Router 物件（Class 2 、Instance 1）
+const MESSAGE_ROUTER: [u8; 4] = [0x20, 0x02, 0x24, 0x01];
+
+/// Connection Manager 物件（Class 6 、Instance 1）
+const CONNECTION_MANAGER: [u8; 4] = [0x20, 0x06, 0x24, 0x01];
+
+/// Symbol 物件的類別編號
+const SYMBOL_CLASS: u32 = 0x6B;
+
+/// Template 物件的類別編號
+const TEMPLATE_CLASS: u32 = 0x6C;
+
+const fn default_update_interval() -> Duration {
+    DEFAULT_UPDATE_INTERVAL
+}
+
+const fn default_timeout() -> Duration {
+    DEFAULT_TIMEOUT
+}
+
+const fn default_max_tags_per_request() -> u16 {
+    DEFAULT_MAX_TAGS_PER_REQUEST
+}
+
+fn default_route() -> Vec<u8> {
+    DEFAULT_ROUTE.to_vec()
+}
+
+/// 連線參數
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EthernetIpConfig {
+    /// 控制器或通訊模組的位址，可經由代理伺服器連線
+    #[serde(flatten)]
+    pub endpoint: TcpEndpoint,
+    /// Unconnected Send 的轉送路徑，依序為埠號與位址的位元組，預設為 [`DEFAULT_ROUTE`]（背板槽位 0）；
+    /// 控制器位於其他槽位時設為 `[1, 槽位]` ，設為空陣列時直接將請求送給連線的設備
+    #[serde(default = "default_route")]
+    pub route: Vec<u8>,
+    /// 更新間隔，序列化時以毫秒數表示，預設為 [`DEFAULT_UPDATE_INTERVAL`]
+    #[serde(
+        rename = "update_interval_ms",
+        with = "crate::millis",
+        default = "default_update_interval"
+    )]
+    pub update_interval: Duration,
+    /// 回覆逾時，序列化時以毫秒數表示，預設為 [`DEFAULT_TIMEOUT`]
+    #[serde(
+        rename = "timeout_ms",
+        with = "crate::millis",
+        default = "default_timeout"
+    )]
+    pub timeout: Duration,
+    /// 單次 Multiple Service Packet 的標籤數量上限，預設為 [`DEFAULT_MAX_TAGS_PER_REQUEST`]
+    ///
+    /// 合併讀取的請求長度另有上限，標籤名稱較長時實際數量會較少；大型陣列或結構經常需要分段讀取時請調低
+    #[serde(default = "default_max_tags_per_request")]
+    pub max_tags_per_request: u16,
+    /// 最高重試次數，參見 [`ConnectionArtifact::max_retry_count`]
+    #[serde(default)]
+    pub max_retry_count: Option<u32>,
+}
+
+impl ConnectionConfig for EthernetIpConfig {}
+
+/// 請求無法完成的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CipError {
+    /// 封裝層回覆的錯誤狀態，如無效的工作階段
+    Encapsulation(u32),
+    /// CIP 服務回覆的錯誤狀態
+    Status {
+        /// 服務碼
+        service: u8,
+        /// 一般狀態碼
+        status: u8,
+        /// 延伸狀態碼
+        extended: Option<u16>,
+    },
+    /// 控制器中找不到結構的範本
+    UnknownStructure(u16),
+    /// 不支援的資料型別碼
+    UnsupportedType(u16),
+    /// 資料無法解碼，數值為原因
+    InvalidData(String),
+}
+
+impl CipError {
+    const fn description(status: u8) -> &'static str {
+        match status {
+            0x01 => "連線失敗",
+            0x04 => "路徑格式錯誤",
+            0x05 => "找不到路徑目的地",
+            0x06 => "部分傳輸",
+            0x08 => "不支援的服務",
+            0x0A => "屬性清單錯誤",
+            0x0C => "物件狀態衝突",
+            0x0F => "權限不足",
+            0x10 => "設備狀態衝突",
+            0x11 => "回覆資料過長",
+            0x13 => "資料不足",
+            0x15 => "資料過多",
+            0x1E => "內嵌服務錯誤",
+            0x26 => "路徑長度錯誤",
+            0xFF => "一般錯誤",
+            _ => "未知的錯誤",
+        }
+    }
+}
+
+impl Display for CipError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Encapsulation(status) => write!(f, "封裝層回覆錯誤（0x{status:08X}）"),
+            Self::Status {
+                service,
+                status,
+                extended,
+            } => {
+                write!(
+                    f,
+                    "CIP 服務 0x{service:02X} 失敗：{}（0x{status:02X}",
+                    Self::description(*status)
+                )?;
+                if let Some(extended) = extended {
+                    write!(f, "，延伸狀態 0x{extended:04X}")?;
+                }
+                f.write_str("）")
+            }
+            Self::UnknownStructure(handle) => {
+                write!(f, "控制器中找不到結構 0x{handle:04X} 的範本")
+            }
+            Self::UnsupportedType(code) => write!(f, "不支援的資料型別 0x{code:04X}"),
+            Self::InvalidData(reason) => write!(f, "資料無法解碼：{reason}"),
+        }
+    }
+}
+
+impl Error for CipError {}
+
+fn malformed() -> ConnectionError {
+    ConnectionError::Protocol("無法解析的 EtherNet/IP 回覆".to_owned())
+}
+
+/// CIP 請求：服務碼、路徑長度（word）、路徑與資料，路徑長度需為偶數
+#[expect(clippy::cast_possible_truncation)]
+fn message(service: u8, path: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(2 + path.len() + data.len());
+    message.extend_from_slice(&[service, (path.len() / 2) as u8]);
+    message.extend_from_slice(path);
+    message.extend_from_slice(data);
+    message
+}
+
+/// 將多個請求合併為 Multiple Service Packet ，位移由服務數量欄位開始計算
+#[expect(clippy::cast_possible_truncation)]
+fn multiple_service_packet(messages: &[Vec<u8>]) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&(messages.len() as u16).to_le_bytes());
+    let mut offset = 2 + messages.len() * 2;
+    for message in messages {
+        data.extend_from_slice(&(offset as u16).to_le_bytes());
+        offset += message.len();
+    }
+    for message in messages {
+        data.extend_from_slice(message);
+    }
+    self::message(MULTIPLE_SERVICE_PACKET, &MESSAGE_ROUTER, &data)
+}
+
+/// 以 Unconnected Send 經由 `route` 轉送請求
+#[expect(clippy::cast_possible_truncation)]
+fn unconnected_send(message: &[u8], route: &[u8]) -> Vec<u8> {
+    // 優先權與時間單位 1024 ms ，逾時 14 個單位
+    let mut data = vec![0x0A, 0x0E];
+    data.extend_from_slice(&(message.len() as u16).to_le_bytes());
+    data.extend_from_slice(message);
+    if message.len() % 2 == 1 {
+        data.push(0);
+    }
+    data.extend_from_slice(&[(route.len() / 2) as u8, 0]);
+    data.extend_from_slice(route);
+    self::message(UNCONNECTED_SEND, &CONNECTION_MANAGER, &data)
+}
+
+/// 封裝標頭，狀態、傳送者內容與選項皆為 0
+#[expect(clippy::cast_possible_truncation)]
+fn header(command: u16, length: usize, session: u32) -> Vec<u8> {
+    let mut header = Vec::with_capacity(HEADER_LENGTH + length);
+    header.extend_from_slice(&command.to_le_bytes());
+    header.extend_from_slice(&(length as u16).to_le_bytes());
+    header.extend_from_slice(&session.to_le_bytes());
+    header.extend_from_slice(&[0; 16]);
+    header
+}
+
+/// CIP 回覆
+#[derive(Debug, Clone)]
+struct Reply {
+    service: u8,
+    status: u8,
+    extended: Option<u16>,
+    data: Bytes,
+}
+
+impl Reply {
+    fn parse(reply: &Bytes) -> Option<Self> {
+        let [service, _, status, size] = *reply.first_chunk()?;
+        let start = 4 + usize::from(size) * 2;
+        let extended = reply
+            .get(4..6)
+            .filter(|_| size > 0)
+            .map(|word| u16::from_le_bytes([word[0], word[1]]));
+        Some(Self {
+            service,
+            status,
+            extended,
+            data: reply.get(start..).map(|_| reply.slice(start..))?,
+        })
+    }
+
+    /// 由 `SendRRData` 回覆的內容取出非連線式資料項目中的回覆
+    fn unconnected(body: &Bytes) -> Result<Self, ConnectionError> {
+        let count = body.get(6..8).ok_or_else(malformed)?;
+        let mut cursor = 8;
+        for _ in 0..u16::from_le_bytes([count[0], count[1]]) {
+            let item = body.get(cursor..cursor + 4).ok_or_else(malformed)?;
+            let kind = u16::from_le_bytes([item[0], item[1]]);
+            let length = usize::from(u16::from_le_bytes([item[2], item[3]]));
+            let end = cursor + 4 + length;
+            if end > body.len() {
+                return Err(malformed());
+            }
+            if kind == UNCONNECTED_DATA_ITEM {
+                return Self::parse(&body.slice(cursor + 4..end)).ok_or_else(malformed);
+            }
+            cursor = end;
+        }
+        Err(malformed())
+    }
+
+    /// 是否為部分傳輸以外的錯誤狀態
+    const fn failed(&self) -> bool {
+        !matches!(self.status, SUCCESS | PARTIAL_TRANSFER)
+    }
+
+    const fn error(&self) -> CipError {
+        CipError::Status {
+            service: self.service & 0x7F,
+            status: self.status,
+            extended: self.extended,
+        }
+    }
+
+    /// 拆開 Multiple Service Packet 的回覆
+    fn split(&self) -> Option<Vec<Self>> {
+        let data = &self.data;
+        let count = usize::from(u16::from_le_bytes(*data.first_chunk()?));
+        let offsets = (0..count)
+            .map(|index| {
+                let offset = data.get(2 + index * 2..4 + index * 2)?;
+                Some(usize::from(u16::from_le_bytes([offset[0], offset[1]])))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        offsets
+            .iter()
+            .enumerate()
+            .map(|(index, start)| {
+                let end = offsets.get(index + 1).copied().unwrap_or(data.len());
+                data.get(*start..end)?;
+                Self::parse(&data.slice(*start..end))
+            })
+            .collect()
+    }
+}
+
+/// 標籤讀取的結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TagData {
+    kind: TagType,
+    data: Bytes,
+}
+
+impl TagData {
+    fn parse(data: &Bytes) -> Result<Self, CipError> {
+        match TagType::parse(data) {
+            Some(Ok((kind, length))) => Ok(Self {
+                kind,
+                data: data.slice(length..),
+            }),
+            Some(Err(code)) => Err(CipError::UnsupportedType(code)),
+            None => Err(CipError::InvalidData("回覆缺少資料型別".to_owned())),
+        }
+    }
+}
+
+/// EtherNet/IP 工作階段
+struct Session {
+    stream: FrameReader<TcpStream>,
+    handle: u32,
+    peer: Option<SocketAddr>,
+    route: Vec<u8>,
+    timeout: Duration,
+}
+
+impl Session {
+    async fn open(config: &EthernetIpConfig) -> Result<Self, ConnectionError> {
+        if config.route.len() % 2 == 1 || config.route.len() > usize::from(u8::MAX) * 2 {
+            return Err(ConnectionError::InvalidConfig(
+                "route 需為埠號與位址成對的位元組".to_owned(),
+            ));
+        }
+        let stream = tokio::time::timeout(config.timeout, tcp::connect(&config.endpoint)).await??;
+        stream.set_nodelay(true)?;
+        let peer = stream.peer_addr().ok();
+        let mut session = Self {
+            stream: FrameReader::new(stream),
+            handle: 0,
+            peer,
+            route: config.route.clone(),
+            timeout: config.timeout,
+        };
+        // 協定版本 1 ，選項 0
+        let (handle, _) = tokio::time::timeout(
+            config.timeout,
+            session.encapsulate(REGISTER_SESSION, &[1, 0, 0, 0]),
+        )
+        .await??;
+        session.handle = handle;
+        Ok(session)
+    }
+
+    /// 送出封裝層命令並等待回覆
+    ///
+    /// # 回傳值
+    /// 工作階段代碼與回覆的資料
+    async fn encapsulate(
+        &mut self,
+        command: u16,
+        data: &[u8],
+    ) -> Result<(u32, Bytes), ConnectionError> {
+        if data.len() > usize::from(u16::MAX) {
+            return Err(ConnectionError::Protocol("請求過長".to_owned()));
+        }
+        let mut frame = header(command, data.len(), self.handle);
+        frame.extend_from_slice(data);
+        self.stream.get_mut().write_all(&frame).await?;
+
+        let header = self.stream.read_frame(HEADER_LENGTH).await?;
+        let length = usize::from(u16::from_le_bytes([header[2], header[3]]));
+        let body = self.stream.read_frame(length).await?;
+        if u16::from_le_bytes([header[0], header[1]]) != command {
+            self.stream.discard();
+            return Err(malformed());
+        }
+        let status = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+        if status != 0 {
+            return Err(ConnectionError::custom(CipError::Encapsulation(status)));
+        }
+        Ok((
+            u32::from_le_bytes([header[4], header[5], header[6], header[7]]),
+            body,
+        ))
+    }
+
+    /// 以 `SendRRData` 送出 CIP 請求並等待回覆
+    async fn send(&mut self, message: &[u8]) -> Result<Reply, ConnectionError> {
+        let timeout = self.timeout;
+        tokio::time::timeout(timeout, self.exchange(message))
+            .await
+            .map_err(ConnectionError::from)
+            .flatten()
+    }
+
+    async fn exchange(&mut self, message: &[u8]) -> Result<Reply, ConnectionError> {
+        let message = if self.route.is_empty() {
+            Cow::Borrowed(message)
+        } else {
+            Cow::Owned(unconnected_send(message, &self.route))
+        };
+        let length = u16::try_from(message.len())
+            .map_err(|_| ConnectionError::Protocol("請求過長".to_owned()))?;
+
+        // 介面代碼、逾時、項目數量，接著是空位址項目與非連線式資料項目
+        let mut data = Vec::with_capacity(16 + message.len());
+        data.extend_from_slice(&[0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0]);
+        data.extend_from_slice(&UNCONNECTED_DATA_ITEM.to_le_bytes());
+        data.extend_from_slice(&length.to_le_bytes());
+        data.extend_from_slice(&message);
+        let (_, body) = self.encapsulate(SEND_RR_DATA, &data).await?;
+        Reply::unconnected(&body)
+    }
+
+    /// 盡力取消工作階段，`UnRegisterSession` 沒有回覆
+    async fn close(mut self) {
+        let frame = header(UNREGISTER_SESSION, 0, self.handle);
+        let _ = tokio::time::timeout(self.timeout, self.stream.get_mut().write_all(&frame)).await;
+    }
+}
+
+/// 標籤與讀取的元素數量
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TagRead {
+    /// 標籤路徑
+    pub tag: TagPath,
+    /// 元素數量
+    pub elements: u16,
+}
+
+/// 以 Multiple Service Packet 合併讀取的標籤
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadBatch {
+    /// 標籤，依順序排列
+    pub reads: Arc<[TagRead]>,
+}
+
+impl ReadBatch {
+    fn key(&self) -> String {
+        format!("{}#{}", self.reads[0].tag, self.reads[0].elements)
+    }
+}
+
+/// 點位
+#[derive(Debug, Clone)]
+pub struct EthernetIpTarget(pub TargetDefinition);
+
+impl Target for EthernetIpTarget {}
+
+/// 讀取請求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EthernetIpRequest {
+    /// 標籤路徑（不含位元）
+    pub tag: TagPath,
+    /// 讀取的陣列元素數量
+    pub elements: u16,
+    /// 位元編號
+    pub bit: Option<u8>,
+    /// 寫入時使用的資料型別
+    pub data_type: Option<AtomicType>,
+    /// 包含點位的合併讀取，單獨讀取的點位為 [`None`]
+    pub batch: Option<ReadBatch>,
+}
+
+impl DeviceStateRequest for EthernetIpRequest {}
+
+impl EthernetIpRequest {
+    /// 由通用點位定義轉換，欄位說明參見 [`crate::ethernet_ip`]
+    ///
+    /// # Errors
+    /// 標籤路徑、元素數量或資料型別無效時回傳 [`InvalidTarget`]
+    pub fn parse(definition: &TargetDefinition) -> Result<Self, InvalidTarget> {
+        let invalid = |reason: String| InvalidTarget {
+            target: definition.name.clone(),
+            reason,
+        };
+
+        let address = definition.address.trim();
+        let (path, bit) = match address.rsplit_once('.') {
+            Some((path, bit))
+                if !bit.is_empty() && bit.chars().all(|character| character.is_ascii_digit()) =>
+            {
+                let bit = bit
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|bit| *bit < 64)
+                    .ok_or_else(|| invalid(format!("位元編號需為 0 至 63 ：{bit}")))?;
+                (path, Some(bit))
+            }
+            _ => (address, None),
+        };
+        let tag =
+            TagPath::parse(path).ok_or_else(|| invalid(format!("無效的標籤路徑「{address}」")))?;
+        let elements = definition
+            .extra
+            .get("elements")
+            .map(|elements| {
+                elements
+                    .as_u64()
+                    .and_then(|elements| u16::try_from(elements).ok())
+                    .filter(|elements| *elements >= 1)
+                    .ok_or_else(|| invalid(format!("elements 需為 1 至 65535 ：{elements}")))
+            })
+            .transpose()?
+            .unwrap_or(1);
+        if bit.is_some() && elements > 1 {
+            return Err(invalid("位元點位不能讀取多個元素".to_owned()));
+        }
+        let data_type = definition
+            .data_type
+            .as_deref()
+            .map(|data_type| {
+                AtomicType::parse(data_type)
+                    .ok_or_else(|| invalid(format!("不支援的資料型別「{data_type}」")))
+            })
+            .transpose()?;
+        if let (Some(bit), Some(data_type)) = (bit, data_type)
+            && !data_type.accepts_bit(bit)
+        {
+            return Err(invalid(format!("{data_type} 沒有位元 {bit}")));
+        }
+
+        Ok(Self {
+            tag,
+            elements,
+            bit,
+            data_type,
+            batch: None,
+        })
+    }
+
+    /// 以已讀取的結構範本解碼讀取的資料
+    fn respond(
+        &self,
+        data: TagData,
+        templates: &Templates,
+    ) -> Result<EthernetIpResponse, ConnectionError> {
+        let value = match (data.kind, self.bit) {
+            (TagType::Atomic(atomic), Some(bit)) => atomic.bit(&data.data, bit),
+            (_, None) => templates.decode(data.kind, &data.data, self.elements),
+            (TagType::Structure(_), Some(_)) => None,
+        }
+        .ok_or_else(|| {
+            ConnectionError::custom(CipError::InvalidData(format!(
+                "標籤 {} 的資料長度或型別不符",
+                self.tag
+            )))
+        })?;
+
+        Ok(EthernetIpResponse {
+            value,
+            tag_type: data.kind,
+            raw: data.data,
+        })
+    }
+
+    fn read(&self) -> TagRead {
+        TagRead {
+            tag: self.tag.clone(),
+            elements: self.elements,
+        }
+    }
+}
+
+/// 讀取回覆
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EthernetIpResponse {
+    /// 解碼後的數值，結構為 JSON 物件，字串為 JSON 字串；NaN 或無限大的浮點數為 [`Value::Null`]
+    pub value: Value,
+    /// 控制器回覆的資料型別
+    pub tag_type: TagType,
+    /// 原始資料（不含資料型別）
+    pub raw: Bytes,
+}
+
+impl DeviceStateResponse for EthernetIpResponse {
+    fn to_value(&self) -> Result<Cow<'_, Value>, ConversionError> {
+        match self.tag_type {
+            TagType::Atomic(AtomicType::Real) if self.value.is_null() => {
+                Err(ConversionError::NonFinite(f64::from(f32::from_le_bytes(
+                    *self.raw.first_chunk().ok_or_else(|| {
+                        ConversionError::InvalidRaw("REAL 的資料長度不足".to_owned())
+                    })?,
+                ))))
+            }
+            TagType::Atomic(AtomicType::Lreal) if self.value.is_null() => {
+                Err(ConversionError::NonFinite(f64::from_le_bytes(
+                    *self.raw.first_chunk().ok_or_else(|| {
+                        ConversionError::InvalidRaw("LREAL 的資料長度不足".to_owned())
+                    })?,
+                )))
+            }
+            _ => Ok(Cow::Borrowed(&self.value)),
+        }
+    }
+
+    fn raw(&self) -> Option<Bytes> {
+        Some(self.raw.clone())
+    }
+}
+
+/// 寫入請求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EthernetIpWrite {
+    /// 標籤路徑
+    pub tag: TagPath,
+    /// 資料型別，位元點位為所屬整數的型別
+    pub data_type: AtomicType,
+    /// 元素數量
+    pub elements: u16,
+    /// 位元編號
+    pub bit: Option<u8>,
+    /// 編碼後的資料，位元點位為 0 或 1
+    pub data: Vec<u8>,
+    /// 包含點位的合併讀取，寫入後會清除該次讀取的結果
+    pub batch: Option<ReadBatch>,
+}
+
+impl DeviceStateWrite for EthernetIpWrite {}
+
+impl EthernetIpWrite {
+    fn message(&self) -> Vec<u8> {
+        let path = self.tag.encode();
+        let Some(bit) = self.bit else {
+            let mut data = Vec::with_capacity(4 + self.data.len());
+            data.extend_from_slice(&self.data_type.code().to_le_bytes());
+            data.extend_from_slice(&self.elements.to_le_bytes());
+            data.extend_from_slice(&self.data);
+            return message(WRITE_TAG, &path, &data);
+        };
+
+        // OR 遮罩設定位元，AND 遮罩清除位元
+        let size = self.data_type.size();
+        let mask = 1_u64 << bit;
+        let (or, and) = if self.data.first().is_some_and(|value| *value != 0) {
+            (mask, u64::MAX)
+        } else {
+            (0, !mask)
+        };
+        let mut data = Vec::with_capacity(2 + size * 2);
+        data.extend_from_slice(&u16::try_from(size).unwrap_or_default().to_le_bytes());
+        data.extend_from_slice(&or.to_le_bytes()[..size]);
+        data.extend_from_slice(&and.to_le_bytes()[..size]);
+        message(READ_MODIFY_WRITE, &path, &data)
+    }
+}
+
+/// EtherNet/IP 設備連線
+///
+/// 合併讀取的結果會保留更新間隔的一半，期間內的其他點位直接使用該結果，並跳過等待間隔
+pub struct EthernetIpConnection {
+    config: EthernetIpConfig,
+    session: Option<Session>,
+    reads: ResponseCache<Arc<[Result<TagData, CipError>]>>,
+    batches: Vec<ReadBatch>,
+    templates: Templates,
+    /// 各範圍（控制器或程式）的標籤名稱（小寫）與型別
+    symbols: HashMap<String, HashMap<String, u16>>,
+    /// 最近一次讀取的基本資料型別，寫入時使用
+    types: HashMap<TagPath, AtomicType>,
+    remote_address: RemoteAddress,
+    rejected: Vec<InvalidTarget>,
+}
+
+impl EthernetIpConnection {
+    /// 初始化點位時被略過的點位
+    #[must_use]
+    pub fn rejected_targets(&self) -> &[InvalidTarget] {
+        &self.rejected
+    }
+
+    fn set_ttl(&self) {
+        let policy = CachePolicy {
+            ttl: self.config.update_interval / 2,
+            stale_while_revalidate: Duration::ZERO,
+        };
+        for batch in &self.batches {
+            self.reads.enable(batch.key(), policy);
+        }
+    }
+
+    /// 取得工作階段，連線已中斷時先重新連線
+    async fn session(&mut self) -> Result<&mut Session, ConnectionError> {
+        if self.session.is_none() {
+            let session = Session::open(&self.config).await?;
+            self.remote_address.set(session.peer);
+            self.session = Some(session);
+        }
+        self.session
+            .as_mut()
+            .ok_or_else(|| std::io::Error::from(ErrorKind::NotConnected).into())
+    }
+
+    /// 送出 CIP 請求，讀寫失敗、逾時或工作階段無效時關閉連線，讓下一個請求重新連線
+    async fn send(&mut self, message: &[u8]) -> Result<Reply, ConnectionError> {
+        let session = self.session().await?;
+        let result = session.send(message).await;
+        if let Err(error) = &result
+            && (matches!(
+                error,
+                ConnectionError::Io(_) | ConnectionError::Protocol(_) | ConnectionError::Timeout
+            ) || error.downcast_ref::<CipError>().is_some())
+        {
+            self.session = None;
+            self.remote_address.set(None);
+        }
+        result
+    }
+
+    /// 以 `Read Tag Fragmented` 讀取單一標籤，回覆超過單一封包時分段讀取
+    async fn read_tag(
+        &mut self,
+        read: &TagRead,
+    ) -> Result<Result<TagData, CipError>, ConnectionError> {
+        let path = read.tag.encode();
+        let mut data = Vec::new();
+        loop {
+            let offset = u32::try_from(data.len()).map_err(|_| malformed())?;
+            let mut request = read.elements.to_le_bytes().to_vec();
+            request.extend_from_slice(&offset.to_le_bytes());
+            let reply = self
+                .send(&message(READ_TAG_FRAGMENTED, &path, &request))
+                .await?;
+            if reply.failed() {
+                return Ok(Err(reply.error()));
+            }
+            let fragment = match TagData::parse(&reply.data) {
+                Ok(fragment) => fragment,
+                Err(error) => return Ok(Err(error)),
+            };
+            if reply.status == SUCCESS && data.is_empty() {
+                return Ok(Ok(fragment));
+            }
+            if fragment.data.is_empty() {
+                return Err(malformed());
+            }
+            data.extend_from_slice(&fragment.data);
+            if reply.status == SUCCESS {
+                return Ok(Ok(TagData {
+                    kind: fragment.kind,
+                    data: data.into(),
+                }));
+            }
+        }
+    }
+
+    /// 以 Multiple Service Packet 合併讀取，回覆過長的標籤改為單獨分段讀取
+    async fn read_tags(
+        &mut self,
+        reads: &[TagRead],
+    ) -> Result<Vec<Result<TagData, CipError>>, ConnectionError> {
+        let messages: Vec<Vec<u8>> = reads
+            .iter()
+            .map(|read| message(READ_TAG, &read.tag.encode(), &read.elements.to_le_bytes()))
+            .collect();
+        let reply = self.send(&multiple_service_packet(&messages)).await?;
+        if !matches!(reply.status, SUCCESS | EMBEDDED_SERVICE_ERROR) {
+            return Ok(vec![Err(reply.error()); reads.len()]);
+        }
+        let replies = reply
+            .split()
+            .filter(|replies| replies.len() == reads.len())
+            .ok_or_else(malformed)?;
+
+        let mut results = Vec::with_capacity(reads.len());
+        for (read, reply) in reads.iter().zip(replies) {
+            results.push(match reply.status {
+                SUCCESS => TagData::parse(&reply.data),
+                PARTIAL_TRANSFER | REPLY_TOO_LARGE => self.read_tag(read).await?,
+                _ => Err(reply.error()),
+            });
+        }
+        Ok(results)
+    }
+
+    /// 讀取範圍中所有標籤的名稱與型別，`program` 為 [`None`] 時讀取控制器範圍的標籤
+    async fn list_symbols(
+        &mut self,
+        program: Option<&str>,
+    ) -> Result<HashMap<String, u16>, ConnectionError> {
+        let mut symbols = HashMap::default();
+        let mut instance = 0;
+        loop {
+            let mut path = Vec::new();
+            if let Some(program) = program {
+                symbolic(&mut path, program);
+            }
+            logical(&mut path, 0x20, SYMBOL_CLASS);
+            logical(&mut path, 0x24, instance);
+            // 屬性 1 （名稱）與屬性 2 （型別）
+            let reply = self
+                .send(&message(
+                    GET_INSTANCE_ATTRIBUTE_LIST,
+                    &path,
+                    &[2, 0, 1, 0, 2, 0],
+                ))
+                .await?;
+            if reply.failed() {
+                return Err(ConnectionError::custom(reply.error()));
+            }
+
+            let data = &reply.data;
+            let mut cursor = 0;
+            let mut listed = false;
+            while cursor < data.len() {
+                let entry = data.get(cursor..cursor + 6).ok_or_else(malformed)?;
+                let id = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+                let length = usize::from(u16::from_le_bytes([entry[4], entry[5]]));
+                let name = data
+                    .get(cursor + 6..cursor + 6 + length)
+                    .ok_or_else(malformed)?;
+                let kind = data
+                    .get(cursor + 6 + length..cursor + 8 + length)
+                    .ok_or_else(malformed)?;
+                symbols.insert(
+                    String::from_utf8_lossy(name).to_ascii_lowercase(),
+                    u16::from_le_bytes([kind[0], kind[1]]),
+                );
+                instance = id + 1;
+                cursor += 8 + length;
+                listed = true;
+            }
+            if reply.status == SUCCESS || !listed {
+                return Ok(symbols);
+            }
+        }
+    }
+
+    /// 讀取結構範本
+    async fn read_template(&mut self, instance: u16) -> Result<Template, ConnectionError> {
+        let mut path = Vec::new();
+        logical(&mut path, 0x20, TEMPLATE_CLASS);
+        logical(&mut path, 0x24, u32::from(instance));
+
+        // 屬性 4 （定義長度，32 位元 word）、5 （結構長度）、2 （成員數量）、1 （結構代碼）
+        let reply = self
+            .send(&message(
+                GET_ATTRIBUTE_LIST,
+                &path,
+                &[4, 0, 4, 0, 5, 0, 2, 0, 1, 0],
+            ))
+            .await?;
+        if reply.status != SUCCESS {
+            return Err(ConnectionError::custom(reply.error()));
+        }
+        let (mut definition_size, mut size, mut member_count, mut handle) = (0, 0, 0, 0);
+        let data = &reply.data;
+        let mut cursor = 2;
+        while cursor < data.len() {
+            let attribute = data.get(cursor..cursor + 4).ok_or_else(malformed)?;
+            let value = &data[cursor + 4..];
+            let word = || Some(u16::from_le_bytes([*value.first()?, *value.get(1)?]));
+            let double = || Some(u32::from_le_bytes(*value.first_chunk()?));
+            match u16::from_le_bytes([attribute[0], attribute[1]]) {
+                1 => handle = word().ok_or_else(malformed)?,
+                2 => member_count = word().ok_or_else(malformed)?,
+                4 => definition_size = double().ok_or_else(malformed)?,
+                5 => size = double().ok_or_else(malformed)?,
+                _ => return Err(malformed()),
+            }
+            cursor += if matches!(attribute[0], 1 | 2) { 6 } else { 8 };
+        }
+
+        // 範本定義的長度，扣除不在回覆中的 23 個位元組
+        let total = usize::try_from(definition_size)
+            .map_err(|_| malformed())?
+            .saturating_mul(4)
+            .saturating_sub(23);
+        let mut definition = Vec::with_capacity(total);
+        loop {
+            let offset = u32::try_from(definition.len()).map_err(|_| malformed())?;
+            let remaining =
+                u16::try_from(total.saturating_sub(definition.len())).unwrap_or(u16::MAX);
+            let mut request = offset.to_le_bytes().to_vec();
+            request.extend_from_slice(&remaining.to_le_bytes());
+            let reply = self.send(&message(READ_TEMPLATE, &path, &request)).await?;
+            if reply.failed() {
+                return Err(ConnectionError::custom(reply.error()));
+            }
+            definition.extend_from_slice(&reply.data);
+            if reply.status == SUCCESS {
+                break;
+            }
+            if reply.data.is_empty() {
+                return Err(malformed());
+            }
+        }
+
+        Template::parse(
+            handle,
+            usize::try_from(size).map_err(|_| malformed())?,
+            member_count,
+            &definition,
+        )
+        .ok_or_else(malformed)
+    }
+
+    /// 讀取標籤所屬的結構範本，以及其中巢狀結構的範本
+    async fn load_structure(&mut self, tag: &TagPath, handle: u16) -> Result<(), ConnectionError> {
+        if self.templates.contains(handle) {
+            return Ok(());
+        }
+        let unknown = || ConnectionError::custom(CipError::UnknownStructure(handle));
+
+        let scope = tag.program().unwrap_or_default().to_ascii_lowercase();
+        if !self.symbols.contains_key(&scope) {
+            let symbols = self.list_symbols(tag.program()).await?;
+            self.symbols.insert(scope.clone(), symbols);
+        }
+        let kind = tag
+            .root()
+            .and_then(|root| self.symbols.get(&scope)?.get(&root.to_ascii_lowercase()))
+            .copied()
+            .filter(|kind| kind & 0x8000 != 0)
+            .ok_or_else(unknown)?;
+
+        let mut pending = vec![kind & 0x0FFF];
+        while let Some(instance) = pending.pop() {
+            if self.templates.is_loaded(instance) {
+                continue;
+            }
+            let template = self.read_template(instance).await?;
+            pending.extend(template.members.iter().filter_map(cip::Member::template));
+            self.templates.insert(instance, template);
+        }
+        if self.templates.contains(handle) {
+            Ok(())
+        } else {
+            Err(unknown())
+        }
+    }
+
+    /// 解碼讀取的資料
+    async fn decode(
+        &mut self,
+        request: &EthernetIpRequest,
+        data: TagData,
+    ) -> Result<EthernetIpResponse, ConnectionError> {
+        match data.kind {
+            TagType::Atomic(atomic) => {
+                self.types.insert(request.tag.clone(), atomic);
+            }
+            TagType::Structure(handle) if request.bit.is_none() => {
+                self.load_structure(&request.tag, handle).await?;
+            }
+            TagType::Structure(_) => {}
+        }
+        request.respond(data, &self.templates)
+    }
+}
+
+impl Connection for EthernetIpConnection {
+    const NAMES: &[&str] = &["EthernetIp", "Logix"];
+    type Config = EthernetIpConfig;
+    type Target = EthernetIpTarget;
+    type Request = EthernetIpRequest;
+    type Response = EthernetIpResponse;
+    type Result = ();
+
+    async fn init(config: &EthernetIpConfig) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+        let session = Session::open(config).await?;
+        let statistics = ConnectionStats::new(
+            format!("{}:{}", config.endpoint.host, config.endpoint.port),
+            None,
+        );
+        statistics.remote_address.set(session.peer);
+
+        let artifact = ConnectionArtifact::new(
+            Self {
+                config: config.clone(),
+                session: Some(session),
+                reads: ResponseCache::new(),
+                batches: Vec::new(),
+                templates: Templates::default(),
+                symbols: HashMap::default(),
+                types: HashMap::default(),
+                remote_address: statistics.remote_address.clone(),
+                rejected: Vec::new(),
+            },
+            statistics,
+        )
+        .update_every(config.update_interval)
+        .timeout_after(config.timeout);
+
+        Ok(match config.max_retry_count {
+            Some(count) => artifact.retry_up_to(count),
+            None => artifact,
+        })
+    }
+
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<EthernetIpTarget>,
+    ) -> ConnectionTargets<EthernetIpRequest, ()> {
+        let mut parsed = Vec::with_capacity(targets.len());
+        for EthernetIpTarget(definition) in targets {
+            match EthernetIpRequest::parse(&definition) {
+                Ok(request) => parsed.push((definition, request)),
+                Err(error) => self.rejected.push(error),
+            }
+        }
+
+        // 依標籤數量與請求長度合併自動更新的點位
+        let mut polled: Vec<TagRead> = parsed
+            .iter()
+            .filter(|(definition, _)| definition.auto_refresh)
+            .map(|(_, request)| request.read())
+            .collect();
+        polled.sort_unstable();
+        polled.dedup();
+        let limit = usize::from(self.config.max_tags_per_request.max(1));
+        let mut chunks: Vec<Vec<TagRead>> = Vec::new();
+        let mut length = 0;
+        for read in polled {
+            // 服務碼、路徑長度、元素數量與位移
+            let cost = read.tag.encode().len() + 6;
+            match chunks.last_mut() {
+                Some(chunk) if chunk.len() < limit && length + cost <= MAX_REQUEST_SIZE => {
+                    chunk.push(read);
+                    length += cost;
+                }
+                _ => {
+                    chunks.push(vec![read]);
+                    length = MESSAGE_ROUTER.len() + 4 + cost;
+                }
+            }
+        }
+        self.batches = chunks
+            .into_iter()
+            .filter(|reads| reads.len() > 1)
+            .map(|reads| ReadBatch {
+                reads: reads.into(),
+            })
+            .collect();
+        self.set_ttl();
+
+        ConnectionTargets(
+            parsed
+                .into_iter()
+                .map(|(definition, mut request)| {
+                    let read = request.read();
+                    request.batch = self
+                        .batches
+                        .iter()
+                        .find(|batch| definition.auto_refresh && batch.reads.contains(&read))
+                        .cloned();
+                    InitedTarget {
+                        name: definition.name,
+                        request,
+                        result: (),
+                        default_status: definition.default_status,
+                        auto_refresh: definition.auto_refresh,
+                        refresh_interval: None,
+                        keep_raw_frames: None,
+                        group: None,
+                        safe_state: None,
+                        array: None,
+                        change: None,
+                        statistics: Some(connection_statistics.insert_target(definition.device)),
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    async fn request_process(
+        &mut self,
+        request: EthernetIpRequest,
+    ) -> Result<(EthernetIpResponse, bool), ConnectionError> {
+        let read = request.read();
+        let (result, wait) = match &request.batch {
+            None => (self.read_tag(&read).await?, true),
+            Some(batch) => {
+                let position = batch
+                    .reads
+                    .iter()
+                    .position(|batched| *batched == read)
+                    .ok_or_else(|| ConnectionError::Protocol("點位不在合併讀取中".to_owned()))?;
+                match self.reads.lookup(&batch.key(), None) {
+                    CacheLookup::Fresh(results) => (results[position].clone(), false),
+                    CacheLookup::Stale { .. } | CacheLookup::Miss => {
+                        let results: Arc<[_]> = self.read_tags(&batch.reads).await?.into();
+                        self.reads.store(&batch.key(), results.clone());
+                        (results[position].clone(), true)
+                    }
+                }
+            }
+        };
+        let data = result.map_err(ConnectionError::custom)?;
+        let response = self.decode(&request, data).await?;
+        Ok((response, wait))
+    }
+
+    fn write_preprocess(
+        &self,
+        request: EthernetIpRequest,
+        value: Value,
+        _: &RequestContext,
+    ) -> Result<Box<dyn DeviceStateWrite>, ConnectionError> {
+        let data_type = request
+            .data_type
+            .or_else(|| self.types.get(&request.tag).copied())
+            .ok_or_else(|| {
+                ConnectionError::InvalidConfig(format!(
+                    "尚未讀取標籤 {} ，無法得知資料型別，請設定 data_type",
+                    request.tag
+                ))
+            })?;
+        let invalid = || ConnectionError::InvalidConfig(format!("無效的設定值：{value}"));
+
+        let data = match request.bit {
+            Some(bit) => {
+                if !data_type.accepts_bit(bit) {
+                    return Err(ConnectionError::InvalidConfig(format!(
+                        "{data_type} 沒有位元 {bit}"
+                    )));
+                }
+                vec![u8::from(value.as_bool().ok_or_else(invalid)?)]
+            }
+            None if request.elements > 1 => value
+                .as_array()
+                .filter(|items| items.len() == usize::from(request.elements))
+                .ok_or_else(invalid)?
+                .iter()
+                .map(|item| data_type.encode(item))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(invalid)?
+                .concat(),
+            None => data_type.encode(&value).ok_or_else(invalid)?,
+        };
+
+        let write = EthernetIpWrite {
+            tag: request.tag,
+            data_type,
+            elements: request.elements,
+            bit: request.bit,
+            data,
+            batch: request.batch,
+        };
+        if write.message().len() > MAX_REQUEST_SIZE {
+            return Err(ConnectionError::InvalidConfig(format!(
+                "寫入標籤 {} 的資料過長",
+                write.tag
+            )));
+        }
+        Ok(Box::new(write))
+    }
+
+    async fn write_process(
+        &mut self,
+        write: Box<dyn DeviceStateWrite>,
+    ) -> Result<Option<EthernetIpResponse>, ConnectionError> {
+        let write = write
+            .downcast::<EthernetIpWrite>()
+            .map_err(|_| ConnectionError::Protocol("非預期的寫入請求".to_owned()))?;
+        if let Some(batch) = &write.batch {
+            self.reads.invalidate(&batch.key());
+        }
+
+        let reply = self.send(&write.message()).await?;
+        if reply.status != SUCCESS {
+            return Err(ConnectionError::custom(reply.error()));
+        }
+        Ok(None)
+    }
+
+    async fn disconnect(&mut self) -> Result<(), ConnectionError> {
+        if let Some(session) = self.session.take() {
+            session.close().await;
+        }
+        self.remote_address.set(None);
+        Ok(())
+    }
+
+    async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+        if let Some(session) = self.session.take() {
+            session.close().await;
+        }
+        self.remote_address.set(None);
+        // 控制器可能已下載新的程式，重新讀取標籤與範本
+        self.templates.clear();
+        self.symbols.clear();
+        self.session().await.map(|_| ())
+    }
+
+    async fn update_config(
+        &mut self,
+        new_config: &EthernetIpConfig,
+    ) -> Result<(), ConnectionError> {
+        self.config = new_config.clone();
+        self.set_ttl();
+        self.reconnect().await
+    }
+}
+
+/// 以記錄下來的 `SendRRData` 回覆解碼點位
+///
+/// `frame` 為包含 `Read Tag` 或 `Read Tag Fragmented` 回覆的完整封裝（含 24 個位元組的封裝標頭），
+/// `target` 為點位的 `address` ，讀取的元素數量為 1 ；沒有控制器可以讀取結構範本，結構只能解碼 `STRING`
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::{ethernet_ip::EthernetIpConnection, fixture::Fixture};
+///
+/// let fixture = Fixture::from_json(r#"{
+///     "connection": "EthernetIp",
+///     "cases": [
+///         { "name": "DINT", "target": "Counter", "frame": "6f00 1a00 01020304 00000000 0000000000000000 00000000 00000000 0000 0200 0000 0000 b200 0a00 cc000000 c400 2a000000", "expected": 42 },
+///         { "name": "REAL", "target": "Motors[2].Speed", "frame": "6f00 1a00 01020304 00000000 0000000000000000 00000000 00000000 0000 0200 0000 0000 b200 0a00 cc000000 ca00 0000c03f", "expected": 1.5 },
+///         { "name": "位元", "target": "Status.3", "frame": "6f00 1a00 01020304 00000000 0000000000000000 00000000 00000000 0000 0200 0000 0000 b200 0a00 cc000000 c400 08000000", "expected": true },
+///         { "name": "找不到標籤", "target": "Missing", "frame": "6f00 1400 01020304 00000000 0000000000000000 00000000 00000000 0000 0200 0000 0000 b200 0400 cc000500" },
+///         { "name": "封裝錯誤", "target": "Counter", "frame": "6f00 0000 01020304 64000000 0000000000000000 00000000" }
+///     ]
+/// }"#).unwrap();
+///
+/// let report = fixture.run::<EthernetIpConnection>();
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+impl FrameDecoder for EthernetIpConnection {
+    fn decode_frame(target: &str, frame: &[u8]) -> Result<EthernetIpResponse, Box<dyn Error>> {
+        let request = EthernetIpRequest::parse(&TargetDefinition {
+            name: target.to_owned(),
+            device: None,
+            device_type: None,
+            address: target.to_owned(),
+            data_type: None,
+            auto_refresh: true,
+            default_status: None,
+            extra: serde_json::Map::new(),
+        })?;
+
+        let header = frame.get(..HEADER_LENGTH).ok_or_else(malformed)?;
+        let length = usize::from(u16::from_le_bytes([header[2], header[3]]));
+        if u16::from_le_bytes([header[0], header[1]]) != SEND_RR_DATA
+            || length != frame.len() - HEADER_LENGTH
+        {
+            return Err(malformed().into());
+        }
+        let status = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+        if status != 0 {
+            return Err(CipError::Encapsulation(status).into());
+        }
+
+        let reply = Reply::unconnected(&Bytes::copy_from_slice(&frame[HEADER_LENGTH..]))?;
+        if !matches!(reply.service & 0x7F, READ_TAG | READ_TAG_FRAGMENTED) {
+            return Err(malformed().into());
+        }
+        if reply.status != SUCCESS {
+            return Err(reply.error().into());
+        }
+        let data = TagData::parse(&reply.data)?;
+        Ok(request.respond(data, &Templates::default())?)
+    }
+}
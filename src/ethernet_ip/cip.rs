@@ -0,0 +1,695 @@
+//! CIP 資料型別、標籤路徑與結構（UDT）的解碼
+
+use std::fmt::Display;
+
+use serde_json::{Map, Value};
+
+use crate::HashMap;
+
+/// 結構的資料型別碼，後接 2 個位元組的結構代碼（structure handle）
+const STRUCTURE: u16 = 0x02A0;
+
+/// Logix 內建 `STRING` 的結構代碼
+const STRING_HANDLE: u16 = 0x0FCE;
+
+/// Logix 內建 `STRING` 的長度：`LEN`（DINT）與 82 個字元的 `DATA`（SINT 陣列）
+const STRING_SIZE: usize = 88;
+
+/// 基本資料型別
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::ethernet_ip::AtomicType;
+/// use serde_json::json;
+///
+/// let dint = AtomicType::parse("dint").unwrap();
+/// assert_eq!(dint, AtomicType::Dint);
+/// assert_eq!(dint.code(), 0xC4);
+/// assert_eq!(dint.decode(&[0x2A, 0x00, 0x00, 0x00]), Some(json!(42)));
+/// assert_eq!(dint.encode(&json!(-1)), Some(vec![0xFF; 4]));
+/// assert_eq!(AtomicType::Real.decode(&1.5_f32.to_le_bytes()), Some(json!(1.5)));
+/// assert_eq!(AtomicType::Sint.encode(&json!(200)), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AtomicType {
+    /// `BOOL`
+    Bool,
+    /// `SINT` ，有號 8 位元
+    Sint,
+    /// `INT` ，有號 16 位元
+    Int,
+    /// `DINT` ，有號 32 位元
+    Dint,
+    /// `LINT` ，有號 64 位元
+    Lint,
+    /// `USINT` ，無號 8 位元
+    Usint,
+    /// `UINT` ，無號 16 位元
+    Uint,
+    /// `UDINT` ，無號 32 位元
+    Udint,
+    /// `ULINT` ，無號 64 位元
+    Ulint,
+    /// `REAL` ，32 位元浮點數
+    Real,
+    /// `LREAL` ，64 位元浮點數
+    Lreal,
+    /// `BYTE` ，8 位元的位元字串
+    Byte,
+    /// `WORD` ，16 位元的位元字串
+    Word,
+    /// `DWORD` ，32 位元的位元字串，Logix 以此型別傳送 `BOOL` 陣列
+    Dword,
+    /// `LWORD` ，64 位元的位元字串
+    Lword,
+}
+
+impl AtomicType {
+    const ALL: [Self; 15] = [
+        Self::Bool,
+        Self::Sint,
+        Self::Int,
+        Self::Dint,
+        Self::Lint,
+        Self::Usint,
+        Self::Uint,
+        Self::Udint,
+        Self::Ulint,
+        Self::Real,
+        Self::Lreal,
+        Self::Byte,
+        Self::Word,
+        Self::Dword,
+        Self::Lword,
+    ];
+
+    /// 由資料型別碼轉換，不支援的型別回傳 [`None`]
+    #[must_use]
+    pub const fn from_code(code: u16) -> Option<Self> {
+        Some(match code {
+            0xC1 => Self::Bool,
+            0xC2 => Self::Sint,
+            0xC3 => Self::Int,
+            0xC4 => Self::Dint,
+            0xC5 => Self::Lint,
+            0xC6 => Self::Usint,
+            0xC7 => Self::Uint,
+            0xC8 => Self::Udint,
+            0xC9 => Self::Ulint,
+            0xCA => Self::Real,
+            0xCB => Self::Lreal,
+            0xD1 => Self::Byte,
+            0xD2 => Self::Word,
+            0xD3 => Self::Dword,
+            0xD4 => Self::Lword,
+            _ => return None,
+        })
+    }
+
+    /// 資料型別碼
+    #[must_use]
+    pub const fn code(self) -> u16 {
+        match self {
+            Self::Bool => 0xC1,
+            Self::Sint => 0xC2,
+            Self::Int => 0xC3,
+            Self::Dint => 0xC4,
+            Self::Lint => 0xC5,
+            Self::Usint => 0xC6,
+            Self::Uint => 0xC7,
+            Self::Udint => 0xC8,
+            Self::Ulint => 0xC9,
+            Self::Real => 0xCA,
+            Self::Lreal => 0xCB,
+            Self::Byte => 0xD1,
+            Self::Word => 0xD2,
+            Self::Dword => 0xD3,
+            Self::Lword => 0xD4,
+        }
+    }
+
+    /// 資料長度（位元組）
+    #[must_use]
+    pub const fn size(self) -> usize {
+        match self {
+            Self::Bool | Self::Sint | Self::Usint | Self::Byte => 1,
+            Self::Int | Self::Uint | Self::Word => 2,
+            Self::Dint | Self::Udint | Self::Real | Self::Dword => 4,
+            Self::Lint | Self::Ulint | Self::Lreal | Self::Lword => 8,
+        }
+    }
+
+    /// 以型別名稱解析，不分大小寫，如 `DINT` 、`real`
+    #[must_use]
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim();
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.to_string().eq_ignore_ascii_case(name))
+    }
+
+    /// 是否為整數或位元字串，可以存取其中的位元
+    const fn has_bits(self) -> bool {
+        !matches!(self, Self::Bool | Self::Real | Self::Lreal)
+    }
+
+    /// 解碼（little-endian），長度不足時回傳 [`None`] ，NaN 或無限大的浮點數為 [`Value::Null`]
+    ///
+    /// # 範例
+    /// 以內建的測試向量驗證：
+    /// ```rust
+    /// use device_state_exchange_lib::{ethernet_ip::AtomicType, vectors::{self, encode_hex}};
+    ///
+    /// let report = vectors::built_in_set("ethernet_ip/atomic").unwrap().verify(|frame, value| {
+    ///     let decoded = frame.split_first_chunk().and_then(|(code, data)| {
+    ///         let kind = AtomicType::from_code(u16::from_le_bytes(*code))?;
+    ///         Some((kind, data, kind.decode(data)?))
+    ///     });
+    ///     match decoded {
+    ///         None if value.is_null() => Ok(()),
+    ///         Some((kind, data, decoded))
+    ///             if value["type"] == kind.to_string() && decoded == value["value"] =>
+    ///         {
+    ///             match kind.encode(&decoded) {
+    ///                 Some(encoded) if encoded == data => Ok(()),
+    ///                 encoded => Err(format!("重新編碼為 {:?}", encoded.as_deref().map(encode_hex))),
+    ///             }
+    ///         }
+    ///         None => Err("無法解碼".to_owned()),
+    ///         Some((kind, _, decoded)) => Err(format!("解碼結果為 {kind} {decoded}")),
+    ///     }
+    /// });
+    /// assert!(report.is_complete(), "{report:?}");
+    /// ```
+    #[must_use]
+    pub fn decode(self, bytes: &[u8]) -> Option<Value> {
+        let bytes = bytes.get(..self.size())?;
+        let mut array = [0; 8];
+        array[..bytes.len()].copy_from_slice(bytes);
+        let unsigned = u64::from_le_bytes(array);
+        Some(match self {
+            Self::Bool => Value::Bool(bytes[0] != 0),
+            Self::Sint => Value::from(i8::from_le_bytes([bytes[0]])),
+            Self::Int => Value::from(i16::from_le_bytes([bytes[0], bytes[1]])),
+            Self::Dint => Value::from(i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+            Self::Lint => Value::from(i64::from_le_bytes(array)),
+            Self::Usint
+            | Self::Uint
+            | Self::Udint
+            | Self::Ulint
+            | Self::Byte
+            | Self::Word
+            | Self::Dword
+            | Self::Lword => Value::from(unsigned),
+            Self::Real => Value::from(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+            Self::Lreal => Value::from(f64::from_le_bytes(array)),
+        })
+    }
+
+    /// 取出整數或位元字串中的一個位元
+    pub(super) fn bit(self, bytes: &[u8], bit: u8) -> Option<Value> {
+        if !self.has_bits() || usize::from(bit) >= self.size() * 8 {
+            return None;
+        }
+        let byte = bytes.get(usize::from(bit / 8))?;
+        Some(Value::Bool(byte >> (bit % 8) & 1 == 1))
+    }
+
+    /// 是否可以存取位元 `bit`
+    pub(super) const fn accepts_bit(self, bit: u8) -> bool {
+        self.has_bits() && (bit as usize) < self.size() * 8
+    }
+
+    /// 編碼（little-endian），超出範圍或格式不符時回傳 [`None`]
+    #[must_use]
+    pub fn encode(self, value: &Value) -> Option<Vec<u8>> {
+        let signed = || value.as_i64();
+        let unsigned = || value.as_u64();
+        Some(match self {
+            Self::Bool => vec![u8::from(value.as_bool()?)],
+            Self::Sint => i8::try_from(signed()?).ok()?.to_le_bytes().to_vec(),
+            Self::Int => i16::try_from(signed()?).ok()?.to_le_bytes().to_vec(),
+            Self::Dint => i32::try_from(signed()?).ok()?.to_le_bytes().to_vec(),
+            Self::Lint => signed()?.to_le_bytes().to_vec(),
+            Self::Usint | Self::Byte => vec![u8::try_from(unsigned()?).ok()?],
+            Self::Uint | Self::Word => u16::try_from(unsigned()?).ok()?.to_le_bytes().to_vec(),
+            Self::Udint | Self::Dword => u32::try_from(unsigned()?).ok()?.to_le_bytes().to_vec(),
+            Self::Ulint | Self::Lword => unsigned()?.to_le_bytes().to_vec(),
+            #[expect(clippy::cast_possible_truncation)]
+            Self::Real => {
+                let value = value
+                    .as_f64()
+                    .filter(|value| value.abs() <= f64::from(f32::MAX))?;
+                (value as f32).to_le_bytes().to_vec()
+            }
+            Self::Lreal => value
+                .as_f64()
+                .filter(|value| value.is_finite())?
+                .to_le_bytes()
+                .to_vec(),
+        })
+    }
+}
+
+impl Display for AtomicType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Bool => "BOOL",
+            Self::Sint => "SINT",
+            Self::Int => "INT",
+            Self::Dint => "DINT",
+            Self::Lint => "LINT",
+            Self::Usint => "USINT",
+            Self::Uint => "UINT",
+            Self::Udint => "UDINT",
+            Self::Ulint => "ULINT",
+            Self::Real => "REAL",
+            Self::Lreal => "LREAL",
+            Self::Byte => "BYTE",
+            Self::Word => "WORD",
+            Self::Dword => "DWORD",
+            Self::Lword => "LWORD",
+        })
+    }
+}
+
+/// 讀取回覆中的資料型別
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TagType {
+    /// 基本資料型別
+    Atomic(AtomicType),
+    /// 結構（UDT 、`STRING` 等），數值為結構代碼（structure handle）
+    Structure(u16),
+}
+
+impl TagType {
+    /// 解析回覆開頭的資料型別
+    ///
+    /// # 回傳值
+    /// 資料型別與其長度；長度不足時回傳 [`None`] ，不支援的型別回傳型別碼
+    pub(super) fn parse(data: &[u8]) -> Option<Result<(Self, usize), u16>> {
+        let code = u16::from_le_bytes([*data.first()?, *data.get(1)?]);
+        if code == STRUCTURE {
+            let handle = u16::from_le_bytes([*data.get(2)?, *data.get(3)?]);
+            return Some(Ok((Self::Structure(handle), 4)));
+        }
+        Some(
+            AtomicType::from_code(code)
+                .map(|kind| (Self::Atomic(kind), 2))
+                .ok_or(code),
+        )
+    }
+}
+
+/// 標籤路徑的片段
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TagSegment {
+    /// 標籤或成員名稱
+    Name(String),
+    /// 陣列索引，多維陣列依維度排列
+    Index(Vec<u32>),
+}
+
+/// 標籤路徑，如 `Motors[2].Speed` 、`Program:MainProgram.Counter`
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::ethernet_ip::{TagPath, TagSegment};
+///
+/// let path = TagPath::parse("Program:Main.Motors[2, 1].Speed").unwrap();
+/// assert_eq!(path.program(), Some("Program:Main"));
+/// assert_eq!(path.root(), Some("Motors"));
+/// assert_eq!(path.segments[2], TagSegment::Index(vec![2, 1]));
+/// assert_eq!(path.to_string(), "Program:Main.Motors[2,1].Speed");
+///
+/// // ANSI 延伸符號片段，名稱長度為奇數時補上一個位元組
+/// assert_eq!(
+///     TagPath::parse("Count[300]").unwrap().encode(),
+///     [0x91, 0x05, b'C', b'o', b'u', b'n', b't', 0x00, 0x29, 0x00, 0x2C, 0x01],
+/// );
+/// assert!(TagPath::parse("Motors[").is_none());
+/// assert!(TagPath::parse("Motors.3").is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TagPath {
+    /// 片段
+    pub segments: Vec<TagSegment>,
+}
+
+impl TagPath {
+    /// 解析標籤路徑，格式錯誤時回傳 [`None`]
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut segments = Vec::new();
+        for (position, part) in text.trim().split('.').enumerate() {
+            let (name, indices) = match part.split_once('[') {
+                Some((name, indices)) => (name.trim(), Some(indices.strip_suffix(']')?)),
+                None => (part.trim(), None),
+            };
+            let bare = if position == 0 {
+                name.strip_prefix("Program:").unwrap_or(name)
+            } else {
+                name
+            };
+            if bare.is_empty()
+                || name.len() > usize::from(u8::MAX)
+                || bare.starts_with(|character: char| character.is_ascii_digit())
+                || !bare
+                    .chars()
+                    .all(|character| character.is_ascii_alphanumeric() || character == '_')
+            {
+                return None;
+            }
+            segments.push(TagSegment::Name(name.to_owned()));
+
+            // 程式範圍的標籤：`Program:名稱` 之後才是標籤名稱，不能直接接上索引
+            if let Some(indices) = indices {
+                if bare.len() != name.len() {
+                    return None;
+                }
+                let indices = indices
+                    .split(',')
+                    .map(|index| index.trim().parse().ok())
+                    .collect::<Option<Vec<u32>>>()?;
+                if indices.len() > 3 {
+                    return None;
+                }
+                segments.push(TagSegment::Index(indices));
+            }
+        }
+        Some(Self { segments })
+    }
+
+    /// 程式範圍標籤所屬的程式，如 `Program:MainProgram` ，控制器範圍的標籤為 [`None`]
+    #[must_use]
+    pub fn program(&self) -> Option<&str> {
+        match self.segments.first() {
+            Some(TagSegment::Name(name)) if name.starts_with("Program:") => Some(name),
+            _ => None,
+        }
+    }
+
+    /// 標籤名稱（不含程式與成員）
+    #[must_use]
+    pub fn root(&self) -> Option<&str> {
+        self.segments
+            .iter()
+            .skip(usize::from(self.program().is_some()))
+            .find_map(|segment| match segment {
+                TagSegment::Name(name) => Some(name.as_str()),
+                TagSegment::Index(_) => None,
+            })
+    }
+
+    /// 編碼為 CIP 請求路徑
+    #[must_use]
+    pub fn encode(&self) -> Vec<u8> {
+        let mut path = Vec::new();
+        for segment in &self.segments {
+            match segment {
+                TagSegment::Name(name) => symbolic(&mut path, name),
+                TagSegment::Index(indices) => {
+                    for index in indices {
+                        element(&mut path, *index);
+                    }
+                }
+            }
+        }
+        path
+    }
+}
+
+impl Display for TagPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (position, segment) in self.segments.iter().enumerate() {
+            match segment {
+                TagSegment::Name(name) if position == 0 => f.write_str(name)?,
+                TagSegment::Name(name) => write!(f, ".{name}")?,
+                TagSegment::Index(indices) => {
+                    let indices: Vec<String> = indices.iter().map(u32::to_string).collect();
+                    write!(f, "[{}]", indices.join(","))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// ANSI 延伸符號片段，名稱長度為奇數時補上一個位元組
+#[expect(clippy::cast_possible_truncation)]
+pub(super) fn symbolic(path: &mut Vec<u8>, name: &str) {
+    path.extend_from_slice(&[0x91, name.len() as u8]);
+    path.extend_from_slice(name.as_bytes());
+    if name.len() % 2 == 1 {
+        path.push(0);
+    }
+}
+
+/// 陣列索引的元素片段
+fn element(path: &mut Vec<u8>, index: u32) {
+    if let Ok(index) = u8::try_from(index) {
+        path.extend_from_slice(&[0x28, index]);
+    } else if let Ok(index) = u16::try_from(index) {
+        path.extend_from_slice(&[0x29, 0x00]);
+        path.extend_from_slice(&index.to_le_bytes());
+    } else {
+        path.extend_from_slice(&[0x2A, 0x00]);
+        path.extend_from_slice(&index.to_le_bytes());
+    }
+}
+
+/// 類別或執行個體的邏輯片段
+pub(super) fn logical(path: &mut Vec<u8>, segment: u8, value: u32) {
+    if let Ok(value) = u8::try_from(value) {
+        path.extend_from_slice(&[segment, value]);
+    } else if let Ok(value) = u16::try_from(value) {
+        path.extend_from_slice(&[segment | 0x01, 0x00]);
+        path.extend_from_slice(&value.to_le_bytes());
+    } else {
+        path.extend_from_slice(&[segment | 0x02, 0x00]);
+        path.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// 結構範本的成員
+#[derive(Debug, Clone)]
+pub(super) struct Member {
+    pub name: String,
+    /// 陣列長度，`BOOL` 成員為位元編號
+    pub info: u16,
+    /// 型別：第 15 位元表示結構，第 13 、14 位元為陣列維度，低 12 位元為基本型別碼或範本編號
+    pub kind: u16,
+    pub offset: usize,
+}
+
+impl Member {
+    pub const fn template(&self) -> Option<u16> {
+        if self.kind & 0x8000 == 0 {
+            None
+        } else {
+            Some(self.kind & 0x0FFF)
+        }
+    }
+
+    const fn is_array(&self) -> bool {
+        self.kind & 0x6000 != 0
+    }
+
+    /// 編譯器產生的隱藏成員，如存放 `BOOL` 成員的 SINT
+    fn is_hidden(&self) -> bool {
+        self.name.starts_with("ZZZZZZZZZZ") || self.name.starts_with("__")
+    }
+}
+
+/// 結構範本（Template 物件）
+#[derive(Debug, Clone)]
+pub(super) struct Template {
+    pub handle: u16,
+    /// 結構長度（位元組）
+    pub size: usize,
+    pub members: Vec<Member>,
+}
+
+impl Template {
+    /// 解析 Read Template 服務的回覆：每個成員 8 個位元組的定義，接著是以 `\0` 結尾的範本名稱與成員名稱
+    pub fn parse(handle: u16, size: usize, member_count: u16, data: &[u8]) -> Option<Self> {
+        let definitions = usize::from(member_count) * 8;
+        let mut names = data
+            .get(definitions..)?
+            .split(|byte| *byte == 0)
+            .map(|name| name.iter().copied().map(char::from).collect::<String>());
+        // 範本名稱，如 `MOTOR;n...`
+        names.next();
+
+        let members = data[..definitions]
+            .chunks_exact(8)
+            .map(|definition| Member {
+                name: names.next().unwrap_or_default(),
+                info: u16::from_le_bytes([definition[0], definition[1]]),
+                kind: u16::from_le_bytes([definition[2], definition[3]]),
+                offset: u32::from_le_bytes([
+                    definition[4],
+                    definition[5],
+                    definition[6],
+                    definition[7],
+                ]) as usize,
+            })
+            .collect();
+        Some(Self {
+            handle,
+            size,
+            members,
+        })
+    }
+
+    /// 字串型別（`STRING` 與自訂長度的字串）只有 `LEN`（DINT）與 `DATA`（SINT 陣列）兩個成員
+    ///
+    /// # 回傳值
+    /// `LEN` 與 `DATA` 的位置，以及 `DATA` 的長度
+    fn string_layout(&self) -> Option<(usize, usize, usize)> {
+        match self.members.as_slice() {
+            [length, data]
+                if length.name == "LEN"
+                    && length.kind == AtomicType::Dint.code()
+                    && data.name == "DATA"
+                    && data.kind & 0x0FFF == AtomicType::Sint.code()
+                    && data.is_array() =>
+            {
+                Some((length.offset, data.offset, usize::from(data.info)))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 解碼單一元素
+type Decoder<'a> = Box<dyn Fn(&[u8]) -> Option<Value> + 'a>;
+
+/// 已讀取的結構範本
+#[derive(Debug, Default)]
+pub(super) struct Templates {
+    /// 範本編號與範本
+    instances: HashMap<u16, Template>,
+    /// 結構代碼與範本編號
+    handles: HashMap<u16, u16>,
+}
+
+impl Templates {
+    /// 是否能解碼該結構
+    pub fn contains(&self, handle: u16) -> bool {
+        handle == STRING_HANDLE || self.handles.contains_key(&handle)
+    }
+
+    pub fn is_loaded(&self, instance: u16) -> bool {
+        self.instances.contains_key(&instance)
+    }
+
+    pub fn insert(&mut self, instance: u16, template: Template) {
+        self.handles.insert(template.handle, instance);
+        self.instances.insert(instance, template);
+    }
+
+    pub fn clear(&mut self) {
+        self.instances.clear();
+        self.handles.clear();
+    }
+
+    /// 解碼讀取的資料，`elements` 大於 1 時為 JSON 陣列，資料長度不足或缺少範本時回傳 [`None`]
+    pub fn decode(&self, kind: TagType, data: &[u8], elements: u16) -> Option<Value> {
+        let (size, decode): (usize, Decoder) = match kind {
+            TagType::Atomic(atomic) => (atomic.size(), Box::new(move |bytes| atomic.decode(bytes))),
+            TagType::Structure(handle) => match self
+                .handles
+                .get(&handle)
+                .and_then(|instance| self.instances.get(instance))
+            {
+                Some(template) => (
+                    template.size,
+                    Box::new(|bytes| self.structure(template, bytes)),
+                ),
+                None if handle == STRING_HANDLE => (
+                    STRING_SIZE,
+                    Box::new(|bytes| string(bytes, 0, 4, STRING_SIZE - 4)),
+                ),
+                None => return None,
+            },
+        };
+        if elements <= 1 {
+            return decode(data);
+        }
+        (0..usize::from(elements))
+            .map(|index| decode(data.get(index * size..)?))
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array)
+    }
+
+    /// 依範本將結構解碼為 JSON 物件，字串型別解碼為字串
+    fn structure(&self, template: &Template, bytes: &[u8]) -> Option<Value> {
+        if let Some((length, data, capacity)) = template.string_layout() {
+            return string(bytes, length, data, capacity);
+        }
+        let mut object = Map::new();
+        for member in template.members.iter().filter(|member| !member.is_hidden()) {
+            object.insert(member.name.clone(), self.member(member, bytes)?);
+        }
+        Some(Value::Object(object))
+    }
+
+    fn member(&self, member: &Member, bytes: &[u8]) -> Option<Value> {
+        let bytes = bytes.get(member.offset..)?;
+        let count = if member.is_array() {
+            usize::from(member.info)
+        } else {
+            1
+        };
+
+        let (size, decode): (usize, Decoder) = if let Some(instance) = member.template() {
+            let template = self.instances.get(&instance)?;
+            (
+                template.size,
+                Box::new(|bytes| self.structure(template, bytes)),
+            )
+        } else {
+            let atomic = AtomicType::from_code(member.kind & 0x0FFF)?;
+            match atomic {
+                // BOOL 成員存放在隱藏的 SINT 中，`info` 為位元編號
+                AtomicType::Bool if !member.is_array() => {
+                    let bit = u8::try_from(member.info).ok()?;
+                    return AtomicType::Usint.bit(bytes, bit);
+                }
+                // BOOL 陣列以 DWORD 陣列存放
+                AtomicType::Dword if member.is_array() => {
+                    let words = bytes.get(..count * 4)?;
+                    return Some(Value::Array(
+                        words
+                            .iter()
+                            .flat_map(|byte| (0..8).map(move |bit| byte >> bit & 1 == 1))
+                            .map(Value::Bool)
+                            .collect(),
+                    ));
+                }
+                _ => (atomic.size(), Box::new(move |bytes| atomic.decode(bytes))),
+            }
+        };
+        if !member.is_array() {
+            return decode(bytes);
+        }
+        (0..count)
+            .map(|index| decode(bytes.get(index * size..)?))
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array)
+    }
+}
+
+/// 解碼字串型別：`LEN` 為字元數，`DATA` 為 ISO 8859-1 字元
+fn string(bytes: &[u8], length: usize, data: usize, capacity: usize) -> Option<Value> {
+    let length = AtomicType::Dint.decode(bytes.get(length..)?)?.as_u64()?;
+    let length = usize::try_from(length).ok()?.min(capacity);
+    let text = bytes
+        .get(data..data + length)?
+        .iter()
+        .copied()
+        .map(char::from)
+        .collect::<String>();
+    Some(Value::String(text))
+}
@@ -0,0 +1,119 @@
+use std::time::SystemTime;
+
+use serde_json::Value;
+use tokio::sync::broadcast;
+
+use crate::skip::SkipReason;
+
+/// 預設事件佇列長度
+pub const DEFAULT_EVENT_CAPACITY: usize = 1024;
+
+/// 事件
+#[derive(Debug, Clone)]
+pub struct Event {
+    /// 事件發生時間
+    pub timestamp: SystemTime,
+    /// 事件內容
+    pub kind: EventKind,
+}
+
+/// 事件內容
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EventKind {
+    /// 序列埠裝置插入或拔除，參見 [`crate::serial::port::HotplugMonitor`]
+    Hotplug {
+        /// 連線識別名稱
+        connection: String,
+        /// 裝置名稱，拔除時為最後一次解析到的裝置名稱
+        port: String,
+        /// 變化類型
+        change: HotplugChange,
+    },
+    /// 設備時鐘偏差超過警示門檻，參見 [`crate::time_sync::TimeSyncPolicy::alarm_threshold`]
+    ClockSkew {
+        /// 連線識別名稱
+        connection: String,
+        /// 偏差毫秒數，正數代表設備時鐘較快
+        drift_ms: i64,
+    },
+    /// 點位回報值偏離期望值，參見 [`crate::twin::DeviceTwin`]
+    TwinDrift {
+        /// 連線識別名稱
+        connection: String,
+        /// 點位名稱
+        target: String,
+        /// 期望值
+        desired: Value,
+        /// 回報值
+        reported: Value,
+    },
+    /// 主程式略過點位的請求，參見 [`crate::skip`]
+    RequestSkipped {
+        /// 連線識別名稱
+        connection: String,
+        /// 點位名稱
+        target: String,
+        /// 略過請求的原因
+        reason: SkipReason,
+    },
+}
+
+/// 裝置插拔變化類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotplugChange {
+    /// 裝置出現（包含重新插入後取得不同裝置名稱的情況）
+    Arrived,
+    /// 裝置消失
+    Removed,
+}
+
+/// 事件匯流排
+///
+/// 利用 [`tokio::sync::broadcast`] 將事件傳遞給所有訂閱者，訂閱者處理速度過慢時，會收到 [`broadcast::error::RecvError::Lagged`] 並遺失最舊的事件
+///
+/// 本 struct 可直接複製，複製後的物件會發佈至同一個匯流排
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_CAPACITY)
+    }
+}
+
+impl EventBus {
+    /// 建立事件匯流排
+    ///
+    /// # 參數
+    /// - `capacity`：每個訂閱者最多可暫存的事件數量
+    ///
+    /// # Panics
+    /// `capacity` 為 0 時
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            sender: broadcast::Sender::new(capacity),
+        }
+    }
+
+    /// 發佈事件
+    ///
+    /// 事件時間為呼叫當下的時間，沒有訂閱者時事件會直接被丟棄
+    pub fn publish(&self, kind: EventKind) {
+        let _ = self.sender.send(Event {
+            timestamp: SystemTime::now(),
+            kind,
+        });
+    }
+
+    /// 訂閱事件
+    ///
+    /// 只會收到訂閱後發佈的事件
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+}
@@ -0,0 +1,241 @@
+//! 事件紀錄（SOE）讀取
+//!
+//! 保護電驛與部分 PLC 會在內部緩衝帶有時間戳記的事件（Sequence of Events），需要由主程式定期讀取且不可遺漏，
+//! 支援事件紀錄的連線可以實作 [`EventLogSource`] trait ，主程式再利用 [`EventLogReader`] 依游標（cursor）持續讀取新事件
+
+use std::{collections::VecDeque, hash::Hash, time::SystemTime};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{Connection, ConnectionError, HashSet};
+
+/// 預設記錄已讀取事件游標的數量，參見 [`EventLogReader::with_history()`]
+pub const DEFAULT_EVENT_LOG_HISTORY: usize = 1024;
+
+/// 事件紀錄來源（非必需）
+///
+/// 需為實作 [`Connection`] trait 的 struct/enum ，讀取事件紀錄的請求與一般請求共用同一個連線，主程式不會同時調用本 trait 的 function 與 [`Connection::request_process()`]
+#[expect(async_fn_in_trait)]
+pub trait EventLogSource: Connection {
+    /// 事件游標型別
+    ///
+    /// 用於標記事件在設備緩衝區中的位置，如序號、紀錄索引或設備時間戳記
+    type Cursor: Clone + Eq + Hash;
+
+    /// 讀取事件
+    ///
+    /// 設備一次可回傳的事件數量有限時，請在 [`EventLogBatch::more`] 標記是否還有未讀取的事件，主程式會繼續讀取直到沒有新事件為止
+    ///
+    /// 設備在重新連線後可能重複回傳已讀取過的事件，主程式會利用游標去除重複的事件，實作者不需要額外處理
+    ///
+    /// # 參數
+    /// - `since`：上次讀取到的最後一個事件游標，首次讀取時為 [`None`] ，此時請回傳設備緩衝區中所有的事件
+    ///
+    /// # 回傳值
+    /// 游標之後的事件，依發生順序排列，可回傳錯誤
+    async fn read_events(
+        &mut self,
+        since: Option<&Self::Cursor>,
+    ) -> Result<EventLogBatch<Self::Cursor>, ConnectionError>;
+}
+
+/// 單次讀取的事件
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventLogBatch<C> {
+    /// 事件，依發生順序排列
+    pub events: Vec<LoggedEvent<C>>,
+    /// 設備中是否還有未讀取的事件
+    pub more: bool,
+}
+
+/// 事件紀錄
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoggedEvent<C> {
+    /// 事件游標
+    pub cursor: C,
+    /// 設備記錄的事件發生時間
+    pub timestamp: SystemTime,
+    /// 事件內容
+    pub value: Value,
+}
+
+/// 事件紀錄讀取器
+///
+/// 記錄最後讀取到的游標，並去除重複回傳的事件，主程式可以將 [`EventLogReader::cursor()`] 保存下來，重新啟動後利用 [`EventLogReader::resume_from()`] 接續讀取
+///
+/// # 範例
+/// ```rust
+/// # use std::{error::Error, time::SystemTime};
+/// # use device_state_exchange_lib::*;
+/// use device_state_exchange_lib::event_log::{EventLogBatch, EventLogReader, EventLogSource, LoggedEvent};
+/// # #[derive(Debug, Clone)] struct Config;
+/// # impl ConnectionConfig for Config {}
+/// # #[derive(Debug, Clone)] struct Point;
+/// # impl Target for Point {}
+/// # #[derive(Debug, Clone)] struct Request;
+/// # impl DeviceStateRequest for Request {}
+/// # #[derive(Debug, Clone)] struct Response;
+/// # impl DeviceStateResponse for Response { fn to_value(&self) -> Result<std::borrow::Cow<'_, serde_json::Value>, value::ConversionError> { Ok(Default::default()) } }
+///
+/// struct Relay {
+///     buffer: Vec<u32>,
+/// }
+///
+/// impl EventLogSource for Relay {
+///     type Cursor = u32;
+///
+///     async fn read_events(&mut self, since: Option<&u32>) -> Result<EventLogBatch<u32>, ConnectionError> {
+///         // 設備忽略游標，每次都回傳整個緩衝區
+///         let events = self.buffer.iter().map(|&sequence| LoggedEvent {
+///             cursor: sequence,
+///             timestamp: SystemTime::UNIX_EPOCH,
+///             value: serde_json::json!({ "trip": sequence }),
+///         });
+///         Ok(EventLogBatch { events: events.collect(), more: false })
+///     }
+/// }
+/// # impl Connection for Relay {
+/// #     const NAMES: &[&str] = &["Relay"];
+/// #     type Config = Config;
+/// #     type Target = Point;
+/// #     type Request = Request;
+/// #     type Response = Response;
+/// #     type Result = ();
+/// #     async fn init(_: &Config) -> Result<ConnectionArtifact<Self>, ConnectionError> { unimplemented!() }
+/// #     fn init_targets(&mut self, _: &mut ConnectionStats, _: Vec<Point>) -> ConnectionTargets<Request, ()> { ConnectionTargets(Vec::new()) }
+/// #     async fn request_process(&mut self, _: Request) -> Result<(Response, bool), ConnectionError> { Ok((Response, true)) }
+/// #     async fn reconnect(&mut self) -> Result<(), ConnectionError> { Ok(()) }
+/// #     async fn update_config(&mut self, _: &Config) -> Result<(), ConnectionError> { Ok(()) }
+/// # }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let mut relay = Relay { buffer: vec![1, 2] };
+/// let mut reader = EventLogReader::new();
+///
+/// assert_eq!(reader.drain(&mut relay).await.unwrap().len(), 2);
+///
+/// relay.buffer.push(3);
+/// let events = reader.drain(&mut relay).await.unwrap();
+/// assert_eq!(events.iter().map(|event| event.cursor).collect::<Vec<_>>(), [3]);
+/// assert_eq!(reader.cursor(), Some(&3));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventLogReader<C> {
+    cursor: Option<C>,
+    seen: HashSet<C>,
+    history: VecDeque<C>,
+    capacity: usize,
+}
+
+impl<C: Clone + Eq + Hash> Default for EventLogReader<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clone + Eq + Hash> EventLogReader<C> {
+    /// 建立事件紀錄讀取器，從設備緩衝區的開頭開始讀取
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            cursor: None,
+            seen: HashSet::new(),
+            history: VecDeque::new(),
+            capacity: DEFAULT_EVENT_LOG_HISTORY,
+        }
+    }
+
+    /// 建立事件紀錄讀取器，從指定的游標之後開始讀取
+    ///
+    /// # 參數
+    /// - `cursor`：先前保存的 [`EventLogReader::cursor()`]
+    #[must_use]
+    pub fn resume_from(cursor: C) -> Self {
+        let mut reader = Self::new();
+        reader.remember(cursor.clone());
+        reader.cursor = Some(cursor);
+        reader
+    }
+
+    /// 設定記錄已讀取事件游標的數量，預設為 [`DEFAULT_EVENT_LOG_HISTORY`]
+    ///
+    /// 設備重複回傳的事件只要還在記錄範圍內就會被去除，請設定為大於設備緩衝區可容納的事件數量
+    #[must_use]
+    pub fn with_history(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        while self.history.len() > capacity {
+            self.forget_oldest();
+        }
+        self
+    }
+
+    /// 最後讀取到的事件游標，尚未讀取過任何事件時為 [`None`]
+    #[must_use]
+    pub const fn cursor(&self) -> Option<&C> {
+        self.cursor.as_ref()
+    }
+
+    /// 讀取所有新事件
+    ///
+    /// 重複調用 [`EventLogSource::read_events()`] 直到 [`EventLogBatch::more`] 為 `false` ，並去除已讀取過的事件
+    ///
+    /// 讀取途中發生錯誤時游標不會前進，本次已讀取的事件會在下次調用時再次回傳，不會遺失
+    ///
+    /// # 回傳值
+    /// 新事件，依發生順序排列
+    ///
+    /// # Errors
+    /// 讀取失敗時回傳 [`EventLogSource`] 實作者回傳的錯誤
+    pub async fn drain<S>(&mut self, source: &mut S) -> Result<Vec<LoggedEvent<C>>, ConnectionError>
+    where
+        S: EventLogSource<Cursor = C>,
+    {
+        let mut drained = Vec::new();
+        let mut drained_cursors = HashSet::new();
+        let mut cursor = self.cursor.clone();
+
+        loop {
+            let batch = source.read_events(cursor.as_ref()).await?;
+
+            for event in batch.events {
+                cursor = Some(event.cursor.clone());
+                if !self.seen.contains(&event.cursor)
+                    && drained_cursors.insert(event.cursor.clone())
+                {
+                    drained.push(event);
+                }
+            }
+
+            if !batch.more {
+                break;
+            }
+        }
+
+        for event in &drained {
+            self.remember(event.cursor.clone());
+        }
+        self.cursor = cursor;
+
+        Ok(drained)
+    }
+
+    fn remember(&mut self, cursor: C) {
+        if self.capacity == 0 || !self.seen.insert(cursor.clone()) {
+            return;
+        }
+
+        self.history.push_back(cursor);
+        while self.history.len() > self.capacity {
+            self.forget_oldest();
+        }
+    }
+
+    fn forget_oldest(&mut self) {
+        if let Some(oldest) = self.history.pop_front() {
+            self.seen.remove(&oldest);
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! 連線執行位置
+//!
+//! 所有連線預設在主程式的同一個 executor 上執行，若某個連線的實作會佔用執行緒（如同步讀寫緩慢的序列埠），
+//! 同一個 executor 上其他數百個快速的 TCP 連線都會受到延遲抖動的影響
+//!
+//! 實作者可以在 [`crate::Connection::init()`] 中透過 [`crate::ConnectionArtifact::run_on()`] 指定 [`ExecutionHint`] ，
+//! 主程式驅動 [`crate::Connection::request_process()`] 時，利用 [`RuntimePools::spawn()`] 依指定的位置執行連線的工作迴圈
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::execution::{ExecutionHint, RuntimePools};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let pools = RuntimePools::new();
+//!
+//! let serial = pools.spawn(&ExecutionHint::Dedicated, "boiler", || async { "boiler" }).unwrap();
+//! let modbus = pools.spawn(&ExecutionHint::Pool { name: "modbus".to_owned() }, "meter", || async { "meter" }).unwrap();
+//!
+//! assert_eq!(serial.join().await, Some("boiler"));
+//! assert_eq!(modbus.join().await, Some("meter"));
+//! assert_eq!(pools.pools(), ["modbus"]);
+//! # }
+//! ```
+
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    runtime::{Builder, Handle},
+    sync::{mpsc, oneshot},
+    task::JoinSet,
+};
+
+use crate::HashMap;
+
+/// 連線執行位置
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(tag = "placement", rename_all = "snake_case")]
+pub enum ExecutionHint {
+    /// 與其他連線共用主程式的 executor
+    #[default]
+    Shared,
+    /// 在專屬的執行緒上，以單執行緒 runtime 執行
+    Dedicated,
+    /// 在 [`tokio::task::spawn_blocking()`] 的執行緒上執行，適用於會阻塞執行緒的實作
+    Blocking,
+    /// 在指定名稱的 runtime 上執行，同名的連線共用同一個執行緒，可將特性相近的連線集中，與其他連線隔離
+    Pool {
+        /// runtime 名稱
+        name: String,
+    },
+}
+
+impl ExecutionHint {
+    /// 是否與主程式的 executor 隔離
+    #[must_use]
+    pub const fn is_isolated(&self) -> bool {
+        !matches!(self, Self::Shared)
+    }
+}
+
+type Job = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
+
+/// 連線執行環境
+///
+/// 依 [`ExecutionHint`] 執行連線的工作，[`ExecutionHint::Pool`] 的 runtime 會在第一次使用時建立，
+/// 本 struct 與其複本都被 drop 後，各 runtime 會在執行中的工作完成後結束
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一組 runtime
+#[derive(Debug, Clone, Default)]
+pub struct RuntimePools {
+    pools: Arc<Mutex<HashMap<String, mpsc::UnboundedSender<Job>>>>,
+}
+
+/// 連線工作的執行結果
+///
+/// 由 [`RuntimePools::spawn()`] 取得
+#[derive(Debug)]
+pub struct ExecutionHandle<T>(oneshot::Receiver<T>);
+
+impl<T> ExecutionHandle<T> {
+    /// 等待工作完成
+    ///
+    /// # 回傳值
+    /// 工作的回傳值，工作 panic 或所在的 runtime 已結束時為 [`None`]
+    pub async fn join(self) -> Option<T> {
+        self.0.await.ok()
+    }
+}
+
+impl RuntimePools {
+    /// 建立連線執行環境
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 依執行位置執行連線的工作
+    ///
+    /// 工作由 `make` 在目標執行緒上建立，[`ExecutionHint::Shared`] 與 [`ExecutionHint::Blocking`] 需要在 tokio runtime 中調用
+    ///
+    /// # 參數
+    /// - `hint`：執行位置，參見 [`crate::ConnectionArtifact::execution`]
+    /// - `connection`：連線識別名稱，用於 [`ExecutionHint::Dedicated`] 的執行緒名稱
+    /// - `make`：建立工作，通常為連線的請求處理迴圈
+    ///
+    /// # Errors
+    /// 無法建立執行緒或 runtime 時回傳 [`io::Error`]
+    ///
+    /// # Panics
+    /// `hint` 為 [`ExecutionHint::Shared`] 或 [`ExecutionHint::Blocking`] ，且不在 tokio runtime 中調用時
+    pub fn spawn<F, Fut>(
+        &self,
+        hint: &ExecutionHint,
+        connection: &str,
+        make: F,
+    ) -> io::Result<ExecutionHandle<Fut::Output>>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        let task = async move {
+            let _ = sender.send(make().await);
+        };
+
+        match hint {
+            ExecutionHint::Shared => drop(tokio::spawn(task)),
+            ExecutionHint::Blocking => {
+                let handle = Handle::current();
+                drop(tokio::task::spawn_blocking(move || handle.block_on(task)));
+            }
+            ExecutionHint::Dedicated => {
+                let runtime = Builder::new_current_thread().enable_all().build()?;
+                thread::Builder::new()
+                    .name(connection.to_owned())
+                    .spawn(move || runtime.block_on(task))?;
+            }
+            ExecutionHint::Pool { name } => {
+                let job: Job = Box::new(move || Box::pin(task));
+                self.pool(name)?
+                    .send(job)
+                    .map_err(|_| io::Error::other(format!("runtime {name} 已結束")))?;
+            }
+        }
+
+        Ok(ExecutionHandle(receiver))
+    }
+
+    /// 取得已建立的 runtime 名稱，依名稱排序
+    #[must_use]
+    pub fn pools(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .pools
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .cloned()
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    fn pool(&self, name: &str) -> io::Result<mpsc::UnboundedSender<Job>> {
+        let mut pools = self.pools.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(sender) = pools.get(name).filter(|sender| !sender.is_closed()) {
+            return Ok(sender.clone());
+        }
+
+        let (sender, mut jobs) = mpsc::unbounded_channel::<Job>();
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+        thread::Builder::new()
+            .name(name.to_owned())
+            .spawn(move || {
+                runtime.block_on(async move {
+                    let mut running = JoinSet::new();
+                    while let Some(job) = jobs.recv().await {
+                        running.spawn(job());
+                        while running.try_join_next().is_some() {}
+                    }
+                    while running.join_next().await.is_some() {}
+                });
+            })?;
+
+        pools.insert(name.to_owned(), sender.clone());
+        drop(pools);
+        Ok(sender)
+    }
+}
@@ -0,0 +1,356 @@
+//! 設備連線回歸測試
+//!
+//! 將設備回覆的原始封包與預期的數值記錄為測試資料（fixture），再以同一份資料反覆驗證設備連線的解碼邏輯，
+//! 修改解碼程式時即可發現與過去行為不一致的地方：
+//!
+//! - 設備連線實作 [`FrameDecoder`] ，將解碼邏輯與傳輸分開
+//! - 以 [`FixtureCase::record()`] 將實際的封包（如 [`crate::diagnostics::RawFrameStore`] 保留的 [`crate::diagnostics::RawFrame`]）與當下的解碼結果記錄下來，
+//!   序列化為 JSON 後存放於 `tests/fixtures/<設備連線>/` 目錄中
+//! - 在測試中調用 [`crate::connection_test_suite!`] ，產生讀取目錄中所有測試資料並逐一驗證的測試
+//!
+//! 測試資料的格式參見 [`Fixture`] ，封包以十六進位字串表示，空白字元會被忽略，驗證結果與 [`crate::vectors`] 相同，以 [`VectorReport`] 表示
+//!
+//! # 範例
+//! ```rust
+//! # use std::{borrow::Cow, error::Error};
+//! # use device_state_exchange_lib::*;
+//! use device_state_exchange_lib::fixture::{Fixture, FixtureCase, FrameDecoder};
+//! use serde_json::{Value, json};
+//!
+//! # #[derive(Debug, Clone)] struct Config;
+//! # impl ConnectionConfig for Config {}
+//! # #[derive(Debug, Clone)] struct Point;
+//! # impl Target for Point {}
+//! # #[derive(Debug, Clone)] struct Request;
+//! # impl DeviceStateRequest for Request {}
+//! #[derive(Debug, Clone)]
+//! struct Temperature(Option<f64>);
+//!
+//! impl DeviceStateResponse for Temperature {
+//!     fn to_value(&self) -> Result<Cow<'_, Value>, value::ConversionError> {
+//!         self.0.map(value::finite).transpose()?.map(Cow::Owned).ok_or_else(|| value::ConversionError::InvalidRaw("感測器未連接".to_owned()))
+//!     }
+//! }
+//!
+//! struct Thermostat;
+//! # impl Connection for Thermostat {
+//! #   const NAMES: &[&str] = &["Thermostat"];
+//! #   type Config = Config;
+//! #   type Target = Point;
+//! #   type Request = Request;
+//! #   type Response = Temperature;
+//! #   type Result = ();
+//! #   async fn init(_: &Config) -> Result<ConnectionArtifact<Self>, ConnectionError> { Ok(ConnectionArtifact::new(Self, ConnectionStats::new("COM1", None))) }
+//! #   fn init_targets(&mut self, _: &mut ConnectionStats, _: Vec<Point>) -> ConnectionTargets<Request, ()> { ConnectionTargets(Vec::new()) }
+//! #   async fn reconnect(&mut self) -> Result<(), ConnectionError> { Ok(()) }
+//! #   async fn update_config(&mut self, _: &Config) -> Result<(), ConnectionError> { Ok(()) }
+//! #   async fn request_process(&mut self, _: Request) -> Result<(Temperature, bool), ConnectionError> { unreachable!() }
+//! # }
+//!
+//! impl FrameDecoder for Thermostat {
+//!     fn decode_frame(_: &str, frame: &[u8]) -> Result<Temperature, Box<dyn Error>> {
+//!         let raw = i16::from_be_bytes(frame.try_into()?);
+//!         Ok(Temperature((raw != i16::MIN).then(|| f64::from(raw) / 10.0)))
+//!     }
+//! }
+//!
+//! // 記錄
+//! let frame = [0x00, 0xd7];
+//! let case = FixtureCase::record("21.5 度", "temperature", &frame, &Thermostat::decode_frame("temperature", &frame).unwrap());
+//! assert_eq!(case.expected, Some(json!(21.5)));
+//!
+//! // 驗證
+//! let fixture = Fixture::from_json(r#"{
+//!     "connection": "Thermostat",
+//!     "cases": [
+//!         { "name": "21.5 度", "target": "temperature", "frame": "00 d7", "expected": 21.5 },
+//!         { "name": "零下", "target": "temperature", "frame": "ff 9c", "expected": -10.0 },
+//!         { "name": "感測器未連接", "target": "temperature", "frame": "80 00" }
+//!     ]
+//! }"#).unwrap();
+//!
+//! let report = fixture.run::<Thermostat>();
+//! assert!(report.is_complete(), "{report:?}");
+//! assert_eq!(report.passed, 3);
+//! ```
+//!
+//! 在設備連線的 crate 中產生測試：
+//! ```rust,no_run
+//! # use device_state_exchange_lib::connection_test_suite;
+//! # struct Thermostat;
+//! // tests/fixtures.rs
+//! connection_test_suite!(Thermostat, fixtures = "tests/fixtures/thermostat");
+//! ```
+
+use std::{
+    error::Error,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    Connection, DeviceStateResponse,
+    vectors::{VectorFailure, VectorReport, decode_hex, encode_hex},
+};
+
+/// 可以直接解碼原始封包的設備連線
+///
+/// 將解碼邏輯與傳輸分開的設備連線實作本 trait 後，即可用 [`Fixture::run()`] 與 [`crate::connection_test_suite!`] 驗證解碼邏輯，而不需要連接實際的設備
+pub trait FrameDecoder: Connection {
+    /// 解碼原始封包
+    ///
+    /// # 參數
+    /// - `target`：點位名稱，參見 [`FixtureCase::target`]
+    /// - `frame`：設備回覆的原始封包
+    ///
+    /// # Errors
+    /// 封包無法解碼時
+    fn decode_frame(target: &str, frame: &[u8]) -> Result<Self::Response, Box<dyn Error>>;
+}
+
+/// 測試資料
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fixture {
+    /// 設備連線名稱，通常為 [`Connection::NAMES`] 的其中一個
+    pub connection: String,
+    /// 說明
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// 測試案例
+    pub cases: Vec<FixtureCase>,
+}
+
+/// 測試案例
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureCase {
+    /// 測試案例名稱
+    pub name: String,
+    /// 點位名稱，會傳入 [`FrameDecoder::decode_frame()`]
+    pub target: String,
+    /// 原始封包，以十六進位字串表示
+    pub frame: String,
+    /// 預期的 [`DeviceStateResponse::to_value()`] 結果，為 [`None`] 時預期解碼或轉換失敗
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<Value>,
+}
+
+impl FixtureCase {
+    /// 以目前的解碼結果記錄測試案例
+    ///
+    /// # 參數
+    /// - `name`：測試案例名稱
+    /// - `target`：點位名稱
+    /// - `frame`：原始封包
+    /// - `response`：目前的解碼結果，轉換失敗時 [`FixtureCase::expected`] 為 [`None`]
+    #[must_use]
+    pub fn record(
+        name: impl Into<String>,
+        target: impl Into<String>,
+        frame: &[u8],
+        response: &dyn DeviceStateResponse,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            target: target.into(),
+            frame: encode_hex(frame),
+            expected: response.to_value().ok().map(std::borrow::Cow::into_owned),
+        }
+    }
+}
+
+impl Fixture {
+    /// 載入 JSON 格式的測試資料
+    ///
+    /// # Errors
+    /// 格式錯誤時回傳 [`FixtureError::Parse`] ，任何封包不是有效的十六進位字串時回傳 [`FixtureError::InvalidHex`]
+    pub fn from_json(json: &str) -> Result<Self, FixtureError> {
+        let fixture: Self = serde_json::from_str(json)
+            .map_err(|error| FixtureError::Parse { path: None, error })?;
+
+        for case in &fixture.cases {
+            if decode_hex(&case.frame).is_err() {
+                return Err(FixtureError::InvalidHex {
+                    case: case.name.clone(),
+                    frame: case.frame.clone(),
+                });
+            }
+        }
+
+        Ok(fixture)
+    }
+
+    /// 載入目錄中所有 `.json` 測試資料，依檔名排序
+    ///
+    /// # Errors
+    /// 目錄或檔案無法讀取時回傳 [`FixtureError::Io`] ，其餘參見 [`Fixture::from_json()`]
+    pub fn load_dir(directory: impl AsRef<Path>) -> Result<Vec<Self>, FixtureError> {
+        let directory = directory.as_ref();
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |error| FixtureError::Io { path, error }
+        };
+
+        let mut paths = fs::read_dir(directory)
+            .map_err(io_error(directory))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(io_error(directory))?;
+        paths.retain(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        });
+        paths.sort();
+
+        paths
+            .into_iter()
+            .map(|path| {
+                let json = fs::read_to_string(&path).map_err(io_error(&path))?;
+                Self::from_json(&json).map_err(|error| match error {
+                    FixtureError::Parse { error, .. } => FixtureError::Parse {
+                        path: Some(path),
+                        error,
+                    },
+                    error => error,
+                })
+            })
+            .collect()
+    }
+
+    /// 以所有測試案例驗證設備連線的解碼邏輯
+    ///
+    /// # 回傳值
+    /// 驗證結果，[`VectorReport::codec`] 為 [`Fixture::connection`]
+    #[must_use]
+    pub fn run<T: FrameDecoder>(&self) -> VectorReport {
+        let mut report = VectorReport {
+            codec: self.connection.clone(),
+            passed: 0,
+            failures: Vec::new(),
+        };
+
+        for case in &self.cases {
+            match check::<T>(case) {
+                Ok(()) => report.passed += 1,
+                Err(message) => report.failures.push(VectorFailure {
+                    codec: self.connection.clone(),
+                    name: case.name.clone(),
+                    frame: case.frame.clone(),
+                    message,
+                }),
+            }
+        }
+
+        report
+    }
+}
+
+fn check<T: FrameDecoder>(case: &FixtureCase) -> Result<(), String> {
+    let frame = decode_hex(&case.frame).map_err(|error| error.to_string())?;
+    let actual = T::decode_frame(&case.target, &frame)
+        .map_err(|error| error.to_string())
+        .and_then(|response| {
+            response
+                .to_value()
+                .map(std::borrow::Cow::into_owned)
+                .map_err(|error| error.to_string())
+        });
+
+    match (&case.expected, actual) {
+        (Some(expected), Ok(actual)) if *expected == actual => Ok(()),
+        (Some(expected), Ok(actual)) => Err(format!("預期為 {expected}，實際為 {actual}")),
+        (Some(_), Err(error)) => Err(format!("解碼失敗：{error}")),
+        (None, Ok(actual)) => Err(format!("預期解碼失敗，實際為 {actual}")),
+        (None, Err(_)) => Ok(()),
+    }
+}
+
+/// 測試資料錯誤
+#[derive(Debug)]
+pub enum FixtureError {
+    /// 目錄或檔案無法讀取
+    Io {
+        /// 目錄或檔案路徑
+        path: PathBuf,
+        /// 錯誤
+        error: io::Error,
+    },
+    /// 格式錯誤
+    Parse {
+        /// 檔案路徑，以 [`Fixture::from_json()`] 載入時為 [`None`]
+        path: Option<PathBuf>,
+        /// 錯誤
+        error: serde_json::Error,
+    },
+    /// 封包不是有效的十六進位字串
+    InvalidHex {
+        /// 測試案例名稱
+        case: String,
+        /// 封包內容
+        frame: String,
+    },
+}
+
+impl Display for FixtureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io { path, error } => write!(f, "無法讀取測試資料 {}：{error}", path.display()),
+            Self::Parse {
+                path: Some(path),
+                error,
+            } => write!(f, "測試資料 {} 格式錯誤：{error}", path.display()),
+            Self::Parse { path: None, error } => write!(f, "測試資料格式錯誤：{error}"),
+            Self::InvalidHex { case, frame } => {
+                write!(
+                    f,
+                    "測試案例「{case}」的封包「{frame}」不是有效的十六進位字串"
+                )
+            }
+        }
+    }
+}
+
+impl Error for FixtureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io { error, .. } => Some(error),
+            Self::Parse { error, .. } => Some(error),
+            Self::InvalidHex { .. } => None,
+        }
+    }
+}
+
+/// 產生設備連線的回歸測試
+///
+/// 產生名為 `connection_test_suite` 的測試，讀取目錄（相對於 crate 根目錄）中所有測試資料，以 [`fixture::Fixture::run()`](crate::fixture::Fixture::run) 驗證，
+/// 任何測試案例未通過或目錄中沒有測試資料時失敗；同一個模組中只能調用一次，多個設備連線請分別放在不同的模組中
+///
+/// # 參數
+/// - 實作 [`fixture::FrameDecoder`](crate::fixture::FrameDecoder) 的設備連線
+/// - `fixtures`：測試資料目錄
+#[macro_export]
+macro_rules! connection_test_suite {
+    ($connection:ty, fixtures = $fixtures:literal) => {
+        #[test]
+        fn connection_test_suite() {
+            let directory = ::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($fixtures);
+            let fixtures = $crate::fixture::Fixture::load_dir(&directory)
+                .unwrap_or_else(|error| panic!("{error}"));
+            assert!(
+                !fixtures.is_empty(),
+                "{} 中沒有測試資料",
+                directory.display()
+            );
+
+            let failures: Vec<String> = fixtures
+                .iter()
+                .flat_map(|fixture| fixture.run::<$connection>().failures)
+                .map(|failure| failure.to_string())
+                .collect();
+            assert!(failures.is_empty(), "{}", failures.join("\n"));
+        }
+    };
+}
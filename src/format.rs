@@ -0,0 +1,177 @@
+//! 回覆格式
+//!
+//! [`crate::InitedTarget::result`] 記錄了向外部服務回傳資料時所需要的資訊，但如何將其與設備資料組合成外部界面看到的內容並沒有明確的約定，
+//! 實作 [`ResultFormatter`] trait 可以將這個步驟獨立出來，方便測試與替換
+//!
+//! 主程式取得設備回覆後，以 [`crate::InitedTarget::format()`] 產生外部界面的內容，再寫入 [`crate::StateStore`] ；
+//! 登記點位時則以 [`crate::InitedTarget::formatted_default()`] 取代 [`crate::InitedTarget::default_status`]
+//!
+//! 本模組提供兩種實作：
+//!
+//! - [`PassThrough`]：忽略 [`crate::InitedTarget::result`] ，直接輸出設備資料
+//! - [`WithResult`]：將可序列化的 [`crate::InitedTarget::result`] 與設備資料合併為一個物件
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{
+//!     DeviceStateRequest, InitedTarget,
+//!     format::{ResultFormatter, WithResult},
+//!     value::{ConversionError, DeviceData},
+//! };
+//! use serde_json::{Value, json};
+//!
+//! #[derive(Debug, Clone)]
+//! struct Request;
+//!
+//! impl DeviceStateRequest for Request {}
+//!
+//! /// 依點位設定的倍率換算
+//! #[derive(Debug)]
+//! struct Scaled;
+//!
+//! impl ResultFormatter<f64> for Scaled {
+//!     fn format(&self, scale: &f64, data: DeviceData) -> Result<Value, ConversionError> {
+//!         let raw = data.as_scalar().and_then(Value::as_f64).unwrap_or_default();
+//!         device_state_exchange_lib::value::finite(raw * scale)
+//!     }
+//! }
+//!
+//! let target = InitedTarget {
+//!     name: "電壓".to_owned(),
+//!     request: Request,
+//!     result: 0.1,
+//!     default_status: Some(json!(0)),
+//!     auto_refresh: true,
+//!     refresh_interval: None,
+//!     keep_raw_frames: None,
+//!     group: None,
+//!     safe_state: None,
+//!     array: None,
+//!     change: None,
+//!     statistics: None,
+//! };
+//!
+//! assert_eq!(target.format(&Scaled, DeviceData::from(json!(2205))).unwrap(), json!(220.5));
+//! assert_eq!(
+//!     WithResult.format(&"V", DeviceData::from(json!(220.5))).unwrap(),
+//!     json!({ "result": "V", "value": 220.5 }),
+//! );
+//! ```
+
+use std::fmt::Debug;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    DeviceStateRequest, InitedTarget,
+    value::{ConversionError, DeviceData, finite},
+};
+
+/// 回覆格式
+///
+/// 泛型 `RES` 為 [`crate::Connection::Result`] 的型別
+pub trait ResultFormatter<RES>: Debug + Send + Sync {
+    /// 將點位資訊與設備資料轉換為外部界面看到的內容
+    ///
+    /// # 參數
+    /// - `result`：點位的 [`crate::InitedTarget::result`]
+    /// - `data`：設備資料，參見 [`crate::DeviceStateResponse::to_data()`]
+    ///
+    /// # 回傳值
+    /// 外部界面看到的內容，可回傳錯誤
+    ///
+    /// # Errors
+    /// 設備資料無法轉換時回傳 [`ConversionError`]
+    fn format(&self, result: &RES, data: DeviceData) -> Result<Value, ConversionError>;
+
+    /// 將點位初始狀態轉換為外部界面看到的內容
+    ///
+    /// 預設實作會將初始狀態視為 [`DeviceData::Scalar`] 調用 [`ResultFormatter::format()`] ，轉換失敗時維持原本的初始狀態
+    ///
+    /// # 參數
+    /// - `result`：點位的 [`crate::InitedTarget::result`]
+    /// - `default_status`：點位的 [`crate::InitedTarget::default_status`]
+    fn format_default(&self, result: &RES, default_status: &Value) -> Value {
+        self.format(result, DeviceData::Scalar(default_status.clone()))
+            .unwrap_or_else(|_| default_status.clone())
+    }
+}
+
+/// 直接輸出設備資料
+///
+/// [`DeviceData::Scalar`] 原樣輸出，[`DeviceData::Array`] 與 [`DeviceData::Samples`] 輸出為數字陣列，[`DeviceData::Bytes`] 無法以 JSON 表示，會回傳 [`ConversionError::InvalidRaw`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PassThrough;
+
+impl<RES> ResultFormatter<RES> for PassThrough {
+    fn format(&self, _: &RES, data: DeviceData) -> Result<Value, ConversionError> {
+        match data {
+            DeviceData::Scalar(value) => Ok(value),
+            DeviceData::Array(samples) => samples
+                .into_iter()
+                .map(finite)
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Array),
+            DeviceData::Samples(samples) => samples
+                .iter()
+                .map(finite)
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Array),
+            DeviceData::Bytes(bytes) => Err(ConversionError::InvalidRaw(format!(
+                "{} 位元組的二進位資料無法以 JSON 表示",
+                bytes.len()
+            ))),
+        }
+    }
+
+    fn format_default(&self, _: &RES, default_status: &Value) -> Value {
+        default_status.clone()
+    }
+}
+
+/// 合併點位資訊與設備資料
+///
+/// 輸出 `{ "result": ..., "value": ... }` 格式的物件，`value` 的轉換方式與 [`PassThrough`] 相同
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WithResult;
+
+impl<RES: Serialize> ResultFormatter<RES> for WithResult {
+    fn format(&self, result: &RES, data: DeviceData) -> Result<Value, ConversionError> {
+        let result = serde_json::to_value(result)
+            .map_err(|error| ConversionError::Other(Box::new(error)))?;
+        let value = PassThrough.format(&(), data)?;
+
+        Ok(Value::Object(Map::from_iter([
+            ("result".to_owned(), result),
+            ("value".to_owned(), value),
+        ])))
+    }
+}
+
+impl<REQ: DeviceStateRequest, RES> InitedTarget<REQ, RES> {
+    /// 以指定的回覆格式產生外部界面看到的內容
+    ///
+    /// # Errors
+    /// 回傳 [`ResultFormatter::format()`] 的錯誤
+    pub fn format<F>(&self, formatter: &F, data: DeviceData) -> Result<Value, ConversionError>
+    where
+        F: ResultFormatter<RES> + ?Sized,
+    {
+        formatter.format(&self.result, data)
+    }
+
+    /// 以指定的回覆格式產生外部界面看到的初始狀態
+    ///
+    /// # 回傳值
+    /// 沒有設定 [`InitedTarget::default_status`] 時為 [`None`]
+    #[must_use]
+    pub fn formatted_default<F>(&self, formatter: &F) -> Option<Value>
+    where
+        F: ResultFormatter<RES> + ?Sized,
+    {
+        self.default_status
+            .as_ref()
+            .map(|default_status| formatter.format_default(&self.result, default_status))
+    }
+}
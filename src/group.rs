@@ -0,0 +1,193 @@
+//! 點位群組
+//!
+//! 點位通常依功能分群（如電能、狀態、診斷），實作者可以在 [`crate::Connection::init_targets()`] 中設定 [`InitedTarget::group`] ，
+//! 讓維運人員以功能單位操作點位，而不需要逐一處理：
+//!
+//! - 利用 [`TargetGroups`] 停用或啟用整個群組的自動更新，主程式排程時以 [`TargetGroups::should_poll()`] 取代 [`InitedTarget::auto_refresh`] 判斷
+//! - 利用 [`TargetGroups::request_refresh()`] 要求立即更新整個群組，主程式以 [`TargetGroups::take_refresh_requests()`] 取出後，
+//!   透過 [`ConnectionTargets::group_requests()`] 取得群組中所有點位的請求
+//! - 利用 [`ConnectionTargets::group_stats()`] 取得群組的加總/平均統計數據
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{ConnectionStats, ConnectionTargets, DeviceStateRequest, InitedTarget, group::TargetGroups};
+//!
+//! #[derive(Debug, Clone)]
+//! struct Request(u16);
+//!
+//! impl DeviceStateRequest for Request {}
+//!
+//! let mut statistics = ConnectionStats::new("COM1", None);
+//! let meter = statistics.insert_target(Some("1".to_owned()));
+//! let target = |name: &str, address, group: &str| InitedTarget {
+//!     name: name.to_owned(),
+//!     request: Request(address),
+//!     result: (),
+//!     default_status: None,
+//!     auto_refresh: true,
+//!     refresh_interval: None,
+//!     keep_raw_frames: None,
+//!     group: Some(group.to_owned()),
+//!     safe_state: None,
+//!     array: None,
+//!     change: None,
+//!     statistics: Some(meter.clone()),
+//! };
+//! let targets = ConnectionTargets(vec![
+//!     target("電能", 1, "energy"),
+//!     target("功率", 2, "energy"),
+//!     target("故障碼", 100, "diagnostics"),
+//! ]);
+//!
+//! let groups = TargetGroups::new();
+//! groups.disable("diagnostics");
+//! assert!(!groups.should_poll(&targets.0[2]));
+//!
+//! groups.request_refresh("energy");
+//! for group in groups.take_refresh_requests() {
+//!     assert_eq!(targets.group_requests(&group).len(), 2);
+//! }
+//!
+//! meter.record_success(20);
+//! assert_eq!(targets.groups(), ["diagnostics", "energy"]);
+//! assert_eq!(targets.group_stats("energy").total_polling_count, 1);
+//! ```
+
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+use crate::{
+    ConnectionTargets, DeviceStateRequest, HashSet, InitedTarget, Statistics, StatisticsSnapshot,
+};
+
+/// 點位群組控制
+///
+/// 記錄已停用的群組與待處理的立即更新要求
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份資料
+#[derive(Debug, Clone, Default)]
+pub struct TargetGroups {
+    disabled: Arc<RwLock<HashSet<String>>>,
+    refresh: Arc<Mutex<Vec<String>>>,
+}
+
+impl TargetGroups {
+    /// 建立群組控制，所有群組預設為啟用
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 停用群組的自動更新
+    pub fn disable(&self, group: impl Into<String>) {
+        self.disabled
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(group.into());
+    }
+
+    /// 啟用群組的自動更新
+    pub fn enable(&self, group: &str) {
+        self.disabled
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(group);
+    }
+
+    /// 群組是否啟用
+    #[must_use]
+    pub fn is_enabled(&self, group: &str) -> bool {
+        !self
+            .disabled
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(group)
+    }
+
+    /// 點位是否應自動更新
+    ///
+    /// 點位設定了 [`InitedTarget::auto_refresh`] ，且未分群或所屬的群組已啟用時為 `true`
+    #[must_use]
+    pub fn should_poll<REQ: DeviceStateRequest, RES>(
+        &self,
+        target: &InitedTarget<REQ, RES>,
+    ) -> bool {
+        target.auto_refresh
+            && target
+                .group
+                .as_deref()
+                .is_none_or(|group| self.is_enabled(group))
+    }
+
+    /// 要求立即更新整個群組
+    ///
+    /// 停用的群組也可以要求立即更新，同一個群組在取出前重複要求只會保留一筆
+    pub fn request_refresh(&self, group: impl Into<String>) {
+        let group = group.into();
+        let mut refresh = self.refresh.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if !refresh.contains(&group) {
+            refresh.push(group);
+        }
+    }
+
+    /// 取出所有待處理的立即更新要求，依要求順序排列
+    #[must_use]
+    pub fn take_refresh_requests(&self) -> Vec<String> {
+        std::mem::take(&mut *self.refresh.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+impl<REQ: DeviceStateRequest, RES> ConnectionTargets<REQ, RES> {
+    /// 取得所有群組名稱，依名稱排序
+    #[must_use]
+    pub fn groups(&self) -> Vec<&str> {
+        let mut groups: Vec<&str> = self
+            .0
+            .iter()
+            .filter_map(|target| target.group.as_deref())
+            .collect();
+        groups.sort_unstable();
+        groups.dedup();
+        groups
+    }
+
+    /// 列出群組中的點位
+    pub fn group<'a>(&'a self, group: &'a str) -> impl Iterator<Item = &'a InitedTarget<REQ, RES>> {
+        self.0
+            .iter()
+            .filter(move |target| target.group.as_deref() == Some(group))
+    }
+
+    /// 取得群組中所有點位的請求，供立即更新使用
+    ///
+    /// # 回傳值
+    /// 點位名稱與請求的複本，依點位順序排列
+    #[must_use]
+    pub fn group_requests(&self, group: &str) -> Vec<(String, REQ)> {
+        self.group(group)
+            .map(|target| (target.name.clone(), dyn_clone::clone(&target.request)))
+            .collect()
+    }
+
+    /// 取得群組的加總/平均統計數據
+    ///
+    /// 同一個設備上的點位共用一份統計數據（參見 [`crate::ConnectionStats::insert_target()`]），群組中的同一個設備只會計算一次
+    #[must_use]
+    pub fn group_stats(&self, group: &str) -> StatisticsSnapshot {
+        let mut statistics: Vec<&Arc<crate::TargetStats>> = Vec::new();
+
+        for target_statistics in self
+            .group(group)
+            .filter_map(|target| target.statistics.as_ref())
+        {
+            if !statistics
+                .iter()
+                .any(|counted| Arc::ptr_eq(counted, target_statistics))
+            {
+                statistics.push(target_statistics);
+            }
+        }
+
+        Statistics::aggregate(statistics.into_iter().map(Arc::as_ref)).snapshot()
+    }
+}
@@ -0,0 +1,214 @@
+//! 回覆時間分布
+//!
+//! [`crate::StatisticsSnapshot::average_response_ms`] 只有平均值，偶發的長時間回覆（如序列埠受干擾後的重送）會被大量正常的回覆稀釋，
+//! [`LatencyHistogram`] 以固定的對數區間記錄每次成功的回覆毫秒數，可以取得 p50/p95/p99 與最大值
+//!
+//! 區間以 2 的次方劃分，每個次方再均分為 4 個子區間，百分位數回傳所在區間的上限（不超過最大值），相對誤差不超過 25%；
+//! 超過約 4.6 小時（2<sup>24</sup> 毫秒）的回覆時間會歸入最後一個區間，最大值仍為實際的數值
+//!
+//! 分布不受 [`crate::Averaging`] 影響，記錄的是 [`crate::TargetStats::clear()`] 後的所有回覆
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::ConnectionStats;
+//!
+//! let mut statistics = ConnectionStats::new("COM1", None);
+//! let meter = statistics.insert_target(Some("1".to_owned()));
+//! for _ in 0..98 {
+//!     meter.record_success(10);
+//! }
+//! meter.record_success(400);
+//! meter.record_success(1_000);
+//!
+//! assert_eq!(meter.p50_response_ms(), 11);
+//! assert_eq!(meter.p99_response_ms(), 447);
+//! assert_eq!(meter.max_response_ms(), 1_000);
+//!
+//! let all = statistics.get_all_stats();
+//! assert_eq!(all.snapshot().p95_response_ms, 11);
+//! assert_eq!(all.max_response_ms(), 1_000);
+//! ```
+
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+/// 每個 2 的次方均分的子區間數量（以位元數表示）
+const SUB_BUCKET_BITS: u32 = 2;
+const SUB_BUCKETS: usize = 1 << SUB_BUCKET_BITS;
+/// 最後一個區間的次方
+const MAX_EXPONENT: u32 = 24;
+const BUCKETS: usize = SUB_BUCKETS * (MAX_EXPONENT - SUB_BUCKET_BITS + 2) as usize;
+
+/// 回覆時間分布
+///
+/// 注意，本物件與 [`crate::Statistics`] 相同，需要跨線程存取時，請利用 [`std::sync::Arc`] 智慧指針
+#[derive(Debug)]
+pub struct LatencyHistogram {
+    buckets: [AtomicU64; BUCKETS],
+    max_ms: AtomicI64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            max_ms: AtomicI64::new(0),
+        }
+    }
+}
+
+impl LatencyHistogram {
+    /// 記錄一次回覆時間
+    ///
+    /// # 參數
+    /// - `response_ms`：回覆毫秒數，小於 0 時視為 0
+    pub fn record(&self, response_ms: i64) {
+        let response_ms = response_ms.max(0);
+        self.buckets[bucket_index(response_ms)].fetch_add(1, Ordering::Relaxed);
+        self.max_ms.fetch_max(response_ms, Ordering::Relaxed);
+    }
+
+    /// 記錄的回覆次數
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// 最大回覆毫秒數，沒有任何紀錄時為 0
+    #[must_use]
+    pub fn max_ms(&self) -> i64 {
+        self.max_ms.load(Ordering::Relaxed)
+    }
+
+    /// 取得百分位數
+    ///
+    /// # 參數
+    /// - `quantile`：介於 0 至 1 之間的比例（如 p95 為 `0.95`），超出範圍時以 0 或 1 計算
+    ///
+    /// # 回傳值
+    /// 百分位數所在區間的上限毫秒數，不超過最大回覆毫秒數；沒有任何紀錄時為 0
+    #[must_use]
+    #[expect(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub fn percentile(&self, quantile: f64) -> i64 {
+        let counts: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .collect();
+        let total: u64 = counts.iter().sum();
+        if total == 0 {
+            return 0;
+        }
+
+        let rank = ((quantile.clamp(0.0, 1.0) * total as f64).ceil() as u64).clamp(1, total);
+        let mut cumulative = 0;
+        let index = counts
+            .iter()
+            .position(|count| {
+                cumulative += count;
+                cumulative >= rank
+            })
+            .unwrap_or(BUCKETS - 1);
+        upper_bound(index).min(self.max_ms())
+    }
+
+    /// 加入另一個分布的紀錄
+    pub fn merge(&self, other: &Self) {
+        for (bucket, other) in self.buckets.iter().zip(&other.buckets) {
+            bucket.fetch_add(other.load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+        self.max_ms.fetch_max(other.max_ms(), Ordering::Relaxed);
+    }
+
+    /// 取得各區間的紀錄次數，用於匯出後以 [`LatencyHistogram::restore()`] 還原
+    #[must_use]
+    pub fn buckets(&self) -> LatencyBuckets {
+        let mut counts: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .collect();
+        let len = counts
+            .iter()
+            .rposition(|count| *count > 0)
+            .map_or(0, |index| index + 1);
+        counts.truncate(len);
+        LatencyBuckets {
+            counts,
+            max_ms: self.max_ms(),
+        }
+    }
+
+    /// 以 [`LatencyHistogram::buckets()`] 取得的紀錄取代目前的紀錄，超出範圍的區間會歸入最後一個區間
+    pub fn restore(&self, buckets: &LatencyBuckets) {
+        self.clear();
+        for (index, count) in buckets.counts.iter().enumerate() {
+            self.buckets[index.min(BUCKETS - 1)].fetch_add(*count, Ordering::Relaxed);
+        }
+        self.max_ms.store(buckets.max_ms, Ordering::Relaxed);
+    }
+
+    /// 清除所有紀錄
+    pub fn clear(&self) {
+        for bucket in &self.buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        self.max_ms.store(0, Ordering::Relaxed);
+    }
+}
+
+/// 回覆時間分布的區間紀錄
+///
+/// 由 [`LatencyHistogram::buckets()`] 產生，省略最後沒有紀錄的區間
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencyBuckets {
+    /// 各區間的紀錄次數
+    pub counts: Vec<u64>,
+    /// 最大回覆毫秒數
+    pub max_ms: i64,
+}
+
+impl LatencyBuckets {
+    /// 是否沒有任何紀錄
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+}
+
+#[expect(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+fn bucket_index(response_ms: i64) -> usize {
+    let value = response_ms as u64;
+    if value < SUB_BUCKETS as u64 {
+        return value as usize;
+    }
+
+    let exponent = value.ilog2().min(MAX_EXPONENT);
+    if exponent == MAX_EXPONENT && value >> MAX_EXPONENT > 1 {
+        return BUCKETS - 1;
+    }
+    let sub_bucket = (value >> (exponent - SUB_BUCKET_BITS)) as usize & (SUB_BUCKETS - 1);
+    SUB_BUCKETS * (exponent - SUB_BUCKET_BITS + 1) as usize + sub_bucket
+}
+
+#[expect(clippy::cast_possible_wrap, clippy::cast_possible_truncation)]
+const fn upper_bound(index: usize) -> i64 {
+    if index < SUB_BUCKETS {
+        return index as i64;
+    }
+    if index == BUCKETS - 1 {
+        return i64::MAX;
+    }
+
+    let exponent = (index / SUB_BUCKETS) as u32 + SUB_BUCKET_BITS - 1;
+    let sub_bucket = (index % SUB_BUCKETS) as i64;
+    ((SUB_BUCKETS as i64 + sub_bucket + 1) << (exponent - SUB_BUCKET_BITS)) - 1
+}
@@ -0,0 +1,1234 @@
+//! HTTP/REST 輪詢參考實作（需啟用 `http` feature）
+//!
+//! 以 HTTP GET 輪詢只提供 JSON REST 端點的感測器，[`HttpPollConnection`] 以 HTTP/1.1 連線至設備，點位為 URL 與 JSONPath 選擇器：
+//!
+//! - 同一輪輪詢中讀取相同 URL 的自動更新點位共用一次請求，回覆保留更新間隔的一半，期間內的其他點位直接使用該回覆
+//! - 設備允許時保持連線（keep-alive），不同主機的 URL 各自建立連線，沿用的連線已被設備關閉時會重新連線後再送出一次
+//! - 驗證方式（Basic 、Bearer token 、API key 標頭）在 [`HttpPollConfig::auth`] 設定，參見 [`HttpAuth`]
+//!
+//! 本實作只支援未加密的 `http://` ，需要 HTTPS 的設備請透過反向代理連線；本實作為唯讀，寫入請求會回傳 [`crate::command::UnsupportedWrite`]
+//!
+//! 點位設定：
+//!
+//! | 欄位 | 說明 |
+//! | --- | --- |
+//! | `address` | URL ，以 `/` 開頭時接在 [`HttpPollConfig::base_url`] 的路徑之後，也可以是完整的 `http://` URL |
+//! | `selector` | 非必填，JSONPath 選擇器（如 `$.sensors[0].temperature`），支援的語法參見 [`JsonPath`] ；未設定時點位的數值為整個回覆 |
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{
+//!     TargetDefinition,
+//!     http::{HttpAuth, HttpPollConfig, HttpPollRequest, HttpUrl},
+//! };
+//! use serde_json::json;
+//!
+//! let config: HttpPollConfig = serde_json::from_value(json!({
+//!     "base_url": "http://192.168.1.40:8080/api",
+//!     "auth": { "type": "api_key", "key": "s3cr3t" },
+//! }))
+//! .unwrap();
+//! assert_eq!(
+//!     config.auth.as_ref().unwrap().header(),
+//!     ("X-API-Key".to_owned(), "s3cr3t".to_owned())
+//! );
+//!
+//! let base = HttpUrl::parse(&config.base_url).unwrap();
+//! let definition: TargetDefinition = serde_json::from_value(json!({
+//!     "name": "室溫",
+//!     "address": "/sensors?room=1",
+//!     "selector": "$.sensors[0].temperature",
+//! }))
+//! .unwrap();
+//! let request = HttpPollRequest::parse(&definition, &base).unwrap();
+//! assert_eq!(request.url.to_string(), "http://192.168.1.40:8080/api/sensors?room=1");
+//!
+//! let body = json!({ "sensors": [{ "temperature": 23.5 }, { "temperature": 24.0 }] });
+//! assert_eq!(request.selector.unwrap().evaluate(&body), Some(json!(23.5)));
+//! ```
+
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    error::Error,
+    fmt::{Debug, Display},
+    sync::Arc,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+
+use crate::{
+    Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+    ConnectionTargets, DeviceStateRequest, DeviceStateResponse, HashMap, InitedTarget,
+    RemoteAddress, Target, TargetDefinition,
+    cache::{CacheLookup, CachePolicy, ResponseCache},
+    definition::InvalidTarget,
+    fixture::FrameDecoder,
+    transport::tcp::{self, ProxyConfig, TcpEndpoint},
+    value::ConversionError,
+};
+
+/// HTTP 預設的 TCP 埠號
+pub const DEFAULT_PORT: u16 = 80;
+
+/// 預設的更新間隔
+pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 預設的回覆逾時
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 預設的 API key 標頭名稱
+pub const DEFAULT_API_KEY_HEADER: &str = "X-API-Key";
+
+/// 可接受的回覆標頭長度上限
+pub const MAX_HEADER_SIZE: usize = 64 * 1024;
+
+/// 可接受的回覆內容長度上限
+pub const MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+const fn default_update_interval() -> Duration {
+    DEFAULT_UPDATE_INTERVAL
+}
+
+const fn default_timeout() -> Duration {
+    DEFAULT_TIMEOUT
+}
+
+fn default_api_key_header() -> String {
+    DEFAULT_API_KEY_HEADER.to_owned()
+}
+
+/// 驗證方式
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HttpAuth {
+    /// HTTP Basic 驗證（RFC 7617）
+    Basic {
+        /// 帳號
+        username: String,
+        /// 密碼
+        password: String,
+    },
+    /// Bearer token（RFC 6750）
+    Bearer {
+        /// token
+        token: String,
+    },
+    /// 以自訂標頭傳送 API key
+    ApiKey {
+        /// 標頭名稱，預設為 [`DEFAULT_API_KEY_HEADER`]
+        #[serde(default = "default_api_key_header")]
+        header: String,
+        /// API key
+        key: String,
+    },
+}
+
+impl HttpAuth {
+    /// 產生驗證用的標頭名稱與內容
+    #[must_use]
+    pub fn header(&self) -> (String, String) {
+        match self {
+            Self::Basic { username, password } => (
+                "Authorization".to_owned(),
+                format!(
+                    "Basic {}",
+                    crate::base64::encode(format!("{username}:{password}").as_bytes())
+                ),
+            ),
+            Self::Bearer { token } => ("Authorization".to_owned(), format!("Bearer {token}")),
+            Self::ApiKey { header, key } => (header.clone(), key.clone()),
+        }
+    }
+}
+
+impl Debug for HttpAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .field("password", &"***")
+                .finish(),
+            Self::Bearer { .. } => f.debug_struct("Bearer").field("token", &"***").finish(),
+            Self::ApiKey { header, .. } => f
+                .debug_struct("ApiKey")
+                .field("header", header)
+                .field("key", &"***")
+                .finish(),
+        }
+    }
+}
+
+/// 連線參數
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpPollConfig {
+    /// 設備的基底 URL ，如 `http://192.168.1.40:8080/api` ，以 `/` 開頭的點位位址接在其路徑之後
+    pub base_url: String,
+    /// 代理伺服器，未設定時直接連線
+    #[serde(default)]
+    pub proxy: Option<ProxyConfig>,
+    /// 驗證方式，未設定時不驗證
+    #[serde(default)]
+    pub auth: Option<HttpAuth>,
+    /// 每個請求額外附加的標頭
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// 更新間隔，序列化時以毫秒數表示，預設為 [`DEFAULT_UPDATE_INTERVAL`]
+    #[serde(
+        rename = "update_interval_ms",
+        with = "crate::millis",
+        default = "default_update_interval"
+    )]
+    pub update_interval: Duration,
+    /// 回覆逾時，序列化時以毫秒數表示，預設為 [`DEFAULT_TIMEOUT`]
+    #[serde(
+        rename = "timeout_ms",
+        with = "crate::millis",
+        default = "default_timeout"
+    )]
+    pub timeout: Duration,
+    /// 最高重試次數，參見 [`ConnectionArtifact::max_retry_count`]
+    #[serde(default)]
+    pub max_retry_count: Option<u32>,
+}
+
+impl HttpPollConfig {
+    /// 產生每個請求附加的標頭，包含 [`Self::headers`] 與驗證用的標頭
+    ///
+    /// # Errors
+    /// 標頭名稱不是有效的 token 或內容包含換行等控制字元時回傳 [`ConnectionError::InvalidConfig`]
+    pub fn request_headers(&self) -> Result<Vec<(String, String)>, ConnectionError> {
+        let headers: Vec<(String, String)> = self
+            .headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .chain(self.auth.as_ref().map(HttpAuth::header))
+            .collect();
+        for (name, value) in &headers {
+            let valid_name = !name.is_empty()
+                && name
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte));
+            if !valid_name {
+                return Err(ConnectionError::InvalidConfig(format!(
+                    "無效的標頭名稱「{name}」"
+                )));
+            }
+            if value
+                .chars()
+                .any(|character| character.is_control() && character != '\t')
+            {
+                return Err(ConnectionError::InvalidConfig(format!(
+                    "標頭「{name}」的內容包含控制字元"
+                )));
+            }
+        }
+        Ok(headers)
+    }
+}
+
+impl ConnectionConfig for HttpPollConfig {}
+
+/// `http://` URL
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HttpUrl {
+    /// 主機名稱或 IP 位址，IPv6 位址不含方括號
+    pub host: String,
+    /// 埠號，未指定時為 [`DEFAULT_PORT`]
+    pub port: u16,
+    /// 路徑與查詢字串，至少為 `/`
+    pub path: String,
+}
+
+impl HttpUrl {
+    /// 解析 `http://` URL ，`#` 之後的片段會被捨棄
+    ///
+    /// # Errors
+    /// 無法解析、使用 `https://` 或包含帳號密碼時回傳原因
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let scheme = text.split_once("://").map(|(scheme, _)| scheme);
+        let rest = match scheme {
+            Some(scheme) if scheme.eq_ignore_ascii_case("http") => &text[scheme.len() + 3..],
+            Some(scheme) if scheme.eq_ignore_ascii_case("https") => {
+                return Err("不支援 HTTPS ，請透過反向代理連線".to_owned());
+            }
+            _ => return Err(format!("無效的 URL「{text}」")),
+        };
+        let rest = rest.split_once('#').map_or(rest, |(rest, _)| rest);
+        let split = rest.find(['/', '?']).unwrap_or(rest.len());
+        let (authority, path) = rest.split_at(split);
+        if authority.contains('@') {
+            return Err("URL 不可包含帳號密碼，請改用 auth 設定".to_owned());
+        }
+
+        let (host, port) = if let Some(bracketed) = authority.strip_prefix('[') {
+            let (host, port) = bracketed
+                .split_once(']')
+                .ok_or_else(|| format!("無效的主機「{authority}」"))?;
+            (host, port.strip_prefix(':'))
+        } else {
+            match authority.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            }
+        };
+        if host.is_empty() {
+            return Err(format!("URL「{text}」沒有主機"));
+        }
+        let port = port
+            .map(|port| port.parse().map_err(|_| format!("無效的埠號「{port}」")))
+            .transpose()?
+            .unwrap_or(DEFAULT_PORT);
+
+        let path = match path {
+            "" => "/".to_owned(),
+            path if path.starts_with('?') => format!("/{path}"),
+            path => path.to_owned(),
+        };
+        Self::check_path(&path)?;
+        Ok(Self {
+            host: host.to_owned(),
+            port,
+            path,
+        })
+    }
+
+    /// 解析點位位址，以 `/` 開頭時接在本 URL 的路徑（不含查詢字串）之後，其他為完整的 URL
+    ///
+    /// # Errors
+    /// 同 [`Self::parse()`]
+    pub fn join(&self, reference: &str) -> Result<Self, String> {
+        let reference = reference.trim();
+        if !reference.starts_with('/') {
+            return Self::parse(reference);
+        }
+        let base = self
+            .path
+            .split_once('?')
+            .map_or(self.path.as_str(), |(path, _)| path);
+        let reference = reference
+            .split_once('#')
+            .map_or(reference, |(reference, _)| reference);
+        let path = format!("{}{reference}", base.trim_end_matches('/'));
+        Self::check_path(&path)?;
+        Ok(Self {
+            host: self.host.clone(),
+            port: self.port,
+            path,
+        })
+    }
+
+    fn check_path(path: &str) -> Result<(), String> {
+        if path
+            .chars()
+            .any(|character| character.is_whitespace() || character.is_control())
+        {
+            return Err(format!(
+                "路徑「{path}」包含空白或控制字元，請先進行百分比編碼"
+            ));
+        }
+        Ok(())
+    }
+
+    /// `Host` 標頭的內容，埠號為 [`DEFAULT_PORT`] 時省略
+    #[must_use]
+    pub fn authority(&self) -> String {
+        let host = if self.host.contains(':') {
+            Cow::Owned(format!("[{}]", self.host))
+        } else {
+            Cow::Borrowed(self.host.as_str())
+        };
+        if self.port == DEFAULT_PORT {
+            host.into_owned()
+        } else {
+            format!("{host}:{}", self.port)
+        }
+    }
+
+    /// 連線目標，同一個連線目標的 URL 共用連線
+    fn origin(&self) -> (String, u16) {
+        (self.host.clone(), self.port)
+    }
+}
+
+impl Display for HttpUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "http://{}{}", self.authority(), self.path)
+    }
+}
+
+/// JSONPath 的一個步驟
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    /// 物件成員，`.name` 或 `['name']`
+    Child(String),
+    /// 陣列元素，`[0]` ，負數由結尾起算
+    Index(i64),
+    /// 所有成員或元素，`.*` 或 `[*]`
+    Wildcard,
+    /// 節點本身與所有子孫節點，`..` ，後面必定接著其他步驟
+    Descendants,
+}
+
+/// JSONPath 選擇器
+///
+/// 支援 RFC 9535 中常用的語法：以 `$` 開頭，之後接著 `.name` 、`['name']` 、`[0]`（負數由結尾起算）、`.*` 與 `[*]`，
+/// 以及遞迴搜尋的 `..name` 、`..*` 、`..[0]` ；不支援篩選條件與切片
+///
+/// 不包含 `*` 與 `..` 的選擇器最多選出一個節點，其他選擇器的結果為陣列，參見 [`Self::evaluate()`]
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::http::JsonPath;
+/// use serde_json::json;
+///
+/// let body = json!({
+///     "site": { "name": "A 棟" },
+///     "sensors": [
+///         { "id": "t1", "reading": { "value": 23.5 } },
+///         { "id": "t2", "reading": { "value": 24.0 } },
+///     ],
+/// });
+///
+/// let path = JsonPath::parse("$['site'].name").unwrap();
+/// assert_eq!(path.evaluate(&body), Some(json!("A 棟")));
+/// assert_eq!(JsonPath::parse("$.sensors[-1].id").unwrap().evaluate(&body), Some(json!("t2")));
+/// assert_eq!(
+///     JsonPath::parse("$.sensors[*].reading.value").unwrap().evaluate(&body),
+///     Some(json!([23.5, 24.0]))
+/// );
+/// assert_eq!(JsonPath::parse("$..value").unwrap().evaluate(&body), Some(json!([23.5, 24.0])));
+/// assert_eq!(JsonPath::parse("$.sensors[5]").unwrap().evaluate(&body), None);
+/// assert!(JsonPath::parse("sensors[0]").is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPath {
+    text: String,
+    segments: Vec<Segment>,
+}
+
+impl JsonPath {
+    /// 解析選擇器，無法解析時回傳 [`None`]
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let mut rest = text.strip_prefix('$')?;
+        let mut segments = Vec::new();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix("..") {
+                segments.push(Segment::Descendants);
+                let (segment, after) = if after.starts_with('[') {
+                    Self::bracket(after)?
+                } else {
+                    Self::dotted(after)?
+                };
+                segments.push(segment);
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix('.') {
+                let (segment, after) = Self::dotted(after)?;
+                segments.push(segment);
+                rest = after;
+            } else if rest.starts_with('[') {
+                let (segment, after) = Self::bracket(rest)?;
+                segments.push(segment);
+                rest = after;
+            } else {
+                return None;
+            }
+        }
+        Some(Self {
+            text: text.to_owned(),
+            segments,
+        })
+    }
+
+    /// 解析 `.` 之後的成員名稱或 `*`
+    fn dotted(text: &str) -> Option<(Segment, &str)> {
+        if let Some(rest) = text.strip_prefix('*') {
+            return Some((Segment::Wildcard, rest));
+        }
+        let end = text.find(['.', '[']).unwrap_or(text.len());
+        let (name, rest) = text.split_at(end);
+        (!name.is_empty()).then(|| (Segment::Child(name.to_owned()), rest))
+    }
+
+    /// 解析 `[...]`
+    fn bracket(text: &str) -> Option<(Segment, &str)> {
+        let inner = text.strip_prefix('[')?.trim_start();
+        if let Some(rest) = inner.strip_prefix('*') {
+            return Some((Segment::Wildcard, rest.trim_start().strip_prefix(']')?));
+        }
+        if let Some(quote) = inner
+            .chars()
+            .next()
+            .filter(|quote| matches!(quote, '\'' | '"'))
+        {
+            let mut name = String::new();
+            let mut characters = inner[1..].char_indices();
+            while let Some((index, character)) = characters.next() {
+                match character {
+                    '\\' => name.push(characters.next()?.1),
+                    character if character == quote => {
+                        let rest = inner[1 + index + 1..].trim_start().strip_prefix(']')?;
+                        return Some((Segment::Child(name), rest));
+                    }
+                    character => name.push(character),
+                }
+            }
+            return None;
+        }
+        let (index, rest) = inner.split_once(']')?;
+        Some((Segment::Index(index.trim().parse().ok()?), rest))
+    }
+
+    /// 是否最多只會選出一個節點（不包含 `*` 與 `..`）
+    #[must_use]
+    pub fn is_singular(&self) -> bool {
+        self.segments
+            .iter()
+            .all(|segment| matches!(segment, Segment::Child(_) | Segment::Index(_)))
+    }
+
+    /// 選擇器的步驟
+    #[must_use]
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// 選出所有符合的節點，依文件順序排列
+    #[must_use]
+    pub fn select<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        let mut nodes = vec![root];
+        for segment in &self.segments {
+            nodes = match segment {
+                Segment::Child(name) => nodes
+                    .into_iter()
+                    .filter_map(|node| node.as_object()?.get(name))
+                    .collect(),
+                Segment::Index(index) => nodes
+                    .into_iter()
+                    .filter_map(|node| {
+                        let array = node.as_array()?;
+                        let index = if *index < 0 {
+                            array
+                                .len()
+                                .checked_sub(usize::try_from(index.unsigned_abs()).ok()?)?
+                        } else {
+                            usize::try_from(*index).ok()?
+                        };
+                        array.get(index)
+                    })
+                    .collect(),
+                Segment::Wildcard => nodes.into_iter().flat_map(children).collect(),
+                Segment::Descendants => {
+                    let mut expanded = Vec::new();
+                    for node in nodes {
+                        descendants(node, &mut expanded);
+                    }
+                    expanded
+                }
+            };
+        }
+        nodes
+    }
+
+    /// 取得選擇結果
+    ///
+    /// # 回傳值
+    /// [`Self::is_singular()`] 為 `true` 時為選出的節點，沒有符合的節點時為 [`None`] ；
+    /// 其他選擇器的結果為包含所有符合節點的陣列，沒有符合的節點時同樣為 [`None`]
+    #[must_use]
+    pub fn evaluate(&self, root: &Value) -> Option<Value> {
+        let nodes = self.select(root);
+        if self.is_singular() {
+            return nodes.first().map(|node| (*node).clone());
+        }
+        (!nodes.is_empty()).then(|| Value::Array(nodes.into_iter().cloned().collect()))
+    }
+}
+
+impl Display for JsonPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+fn children(node: &Value) -> Vec<&Value> {
+    match node {
+        Value::Array(array) => array.iter().collect(),
+        Value::Object(object) => object.values().collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn descendants<'a>(node: &'a Value, output: &mut Vec<&'a Value>) {
+    output.push(node);
+    for child in children(node) {
+        descendants(child, output);
+    }
+}
+
+/// 設備回覆的錯誤
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HttpError {
+    /// 回覆的狀態碼不是 2xx
+    Status {
+        /// 狀態碼
+        status: u16,
+        /// 狀態說明
+        reason: String,
+    },
+    /// 回覆的內容不是 JSON
+    InvalidJson(String),
+    /// 選擇器沒有選出任何節點
+    NoMatch(String),
+}
+
+impl Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Status { status, reason } => write!(f, "設備回覆錯誤：{status} {reason}"),
+            Self::InvalidJson(reason) => write!(f, "回覆的內容不是 JSON ：{reason}"),
+            Self::NoMatch(selector) => write!(f, "選擇器 {selector} 沒有符合的內容"),
+        }
+    }
+}
+
+impl Error for HttpError {}
+
+fn malformed() -> ConnectionError {
+    ConnectionError::Protocol("無法解析的 HTTP 回覆".to_owned())
+}
+
+/// HTTP 回覆
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpReply {
+    /// 狀態碼
+    pub status: u16,
+    /// 狀態說明
+    pub reason: String,
+    /// 標頭，名稱維持設備回覆的大小寫
+    pub headers: Vec<(String, String)>,
+    /// 內容，已移除分塊傳輸的編碼
+    pub body: Vec<u8>,
+    /// 回覆後連線是否可以繼續使用
+    pub keep_alive: bool,
+}
+
+impl HttpReply {
+    /// 取得標頭內容，名稱不分大小寫，有多個同名標頭時為第一個
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(candidate, _)| candidate.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// 解析完整的回覆（狀態列、標頭與內容），規則同 [`HttpClient::get()`] ，回覆之後多餘的資料會被忽略
+    ///
+    /// # Errors
+    /// 回覆不完整時回傳 [`ConnectionError::Io`] ，不符合通訊協定或超過 [`MAX_HEADER_SIZE`] 、[`MAX_BODY_SIZE`] 的回覆回傳 [`ConnectionError::Protocol`]
+    ///
+    /// # 範例
+    /// 以內建的測試向量驗證：
+    /// ```rust
+    /// use device_state_exchange_lib::{http::HttpReply, vectors};
+    /// use serde_json::json;
+    ///
+    /// let report = vectors::built_in_set("http/reply").unwrap().verify(|frame, value| {
+    ///     match HttpReply::parse(frame) {
+    ///         Ok(reply) if json!({
+    ///             "status": reply.status,
+    ///             "body": String::from_utf8_lossy(&reply.body),
+    ///             "keep_alive": reply.keep_alive,
+    ///         }) == *value => Ok(()),
+    ///         Err(_) if value.is_null() => Ok(()),
+    ///         result => Err(format!("解析結果為 {result:?}")),
+    ///     }
+    /// });
+    /// assert!(report.is_complete(), "{report:?}");
+    /// ```
+    pub fn parse(bytes: &[u8]) -> Result<Self, ConnectionError> {
+        let mut client = HttpClient {
+            stream: BufReader::new(bytes),
+            timeout: Duration::ZERO,
+        };
+        let mut reply = std::pin::pin!(client.reply());
+        // 內容已在記憶體中，讀取不會等待
+        match reply.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(result) => result,
+            Poll::Pending => Err(malformed()),
+        }
+    }
+
+    /// 狀態碼為 2xx 時將內容解析為 JSON
+    fn json(&self) -> Result<Value, ConnectionError> {
+        if !(200..300).contains(&self.status) {
+            return Err(ConnectionError::custom(HttpError::Status {
+                status: self.status,
+                reason: self.reason.clone(),
+            }));
+        }
+        serde_json::from_slice(&self.body)
+            .map_err(|error| ConnectionError::custom(HttpError::InvalidJson(error.to_string())))
+    }
+
+    /// 是否包含以 `,` 分隔的標頭內容，不分大小寫（如 `Connection: keep-alive, Upgrade`）
+    fn has_token(&self, name: &str, token: &str) -> bool {
+        self.headers
+            .iter()
+            .filter(|(candidate, _)| candidate.eq_ignore_ascii_case(name))
+            .flat_map(|(_, value)| value.split(','))
+            .any(|candidate| candidate.trim().eq_ignore_ascii_case(token))
+    }
+}
+
+/// HTTP/1.1 用戶端
+///
+/// 一次只處理一個請求，回覆的內容支援 `Content-Length` 、分塊傳輸（chunked）與以關閉連線結束三種形式；
+/// 逾時或回覆不符合通訊協定時，連線的狀態無法確定，請捨棄本用戶端並重新連線
+///
+/// # 範例
+/// ```rust
+/// use std::time::Duration;
+/// use device_state_exchange_lib::http::{HttpClient, HttpUrl};
+/// use tokio::io::{AsyncReadExt, AsyncWriteExt};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let (client, mut device) = tokio::io::duplex(1024);
+/// // 模擬設備
+/// tokio::spawn(async move {
+///     let mut request = vec![0; 512];
+///     let length = device.read(&mut request).await.unwrap();
+///     let request = String::from_utf8_lossy(&request[..length]);
+///     assert!(request.starts_with("GET /status HTTP/1.1\r\nHost: 10.0.0.8:8080\r\n"));
+///     assert!(request.contains("\r\nAuthorization: Bearer abc\r\n"));
+///
+///     device
+///         .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n")
+///         .await
+///         .unwrap();
+///     device.write_all(b"9\r\n{\"on\":tru\r\n2\r\ne}\r\n0\r\n\r\n").await.unwrap();
+/// });
+///
+/// let mut client = HttpClient::new(client, Duration::from_secs(1));
+/// let url = HttpUrl::parse("http://10.0.0.8:8080/status").unwrap();
+/// let headers = [("Authorization".to_owned(), "Bearer abc".to_owned())];
+/// let reply = client.get(&url, &headers).await.unwrap();
+/// assert_eq!(reply.status, 200);
+/// assert_eq!(reply.header("content-type"), Some("application/json"));
+/// assert_eq!(reply.body, br#"{"on":true}"#);
+/// assert!(reply.keep_alive);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct HttpClient<T> {
+    stream: BufReader<T>,
+    timeout: Duration,
+}
+
+impl<T: AsyncRead + AsyncWrite + Unpin> HttpClient<T> {
+    /// 建立用戶端
+    ///
+    /// # 參數
+    /// - `stream`：已連線至設備的連線
+    /// - `timeout`：送出請求至收到完整回覆的逾時
+    #[must_use]
+    pub fn new(stream: T, timeout: Duration) -> Self {
+        Self {
+            stream: BufReader::new(stream),
+            timeout,
+        }
+    }
+
+    /// 送出 GET 請求
+    ///
+    /// # 參數
+    /// - `url`：請求的 URL ，主機與埠號只用於 `Host` 標頭
+    /// - `headers`：額外附加的標頭，參見 [`HttpPollConfig::request_headers()`]
+    ///
+    /// # Errors
+    /// 逾時回傳 [`ConnectionError::Timeout`] ，讀寫失敗或設備在回覆前關閉連線時回傳 [`ConnectionError::Io`] ，
+    /// 不符合通訊協定或超過 [`MAX_HEADER_SIZE`] 、[`MAX_BODY_SIZE`] 的回覆回傳 [`ConnectionError::Protocol`] ；
+    /// 狀態碼不是 2xx 時仍回傳 [`HttpReply`]
+    pub async fn get(
+        &mut self,
+        url: &HttpUrl,
+        headers: &[(String, String)],
+    ) -> Result<HttpReply, ConnectionError> {
+        let mut request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}\r\nAccept: application/json\r\n",
+            url.path,
+            url.authority()
+        );
+        for (name, value) in headers {
+            request.push_str(name);
+            request.push_str(": ");
+            request.push_str(value);
+            request.push_str("\r\n");
+        }
+        request.push_str("\r\n");
+
+        tokio::time::timeout(self.timeout, async {
+            self.stream.write_all(request.as_bytes()).await?;
+            self.stream.flush().await?;
+            self.reply().await
+        })
+        .await?
+    }
+}
+
+impl<T: AsyncRead + Unpin> HttpClient<T> {
+    async fn reply(&mut self) -> Result<HttpReply, ConnectionError> {
+        // 略過 1xx 的暫時回覆
+        let (version, status, reason, headers) = loop {
+            let head = self.head().await?;
+            if !(100..200).contains(&head.1) {
+                break head;
+            }
+        };
+        let mut reply = HttpReply {
+            status,
+            reason,
+            headers,
+            body: Vec::new(),
+            keep_alive: false,
+        };
+        reply.keep_alive = if version == "HTTP/1.0" {
+            reply.has_token("Connection", "keep-alive")
+        } else {
+            !reply.has_token("Connection", "close")
+        };
+
+        if status == 204 || status == 304 {
+            return Ok(reply);
+        }
+        if reply.has_token("Transfer-Encoding", "chunked") {
+            reply.body = self.chunked().await?;
+        } else if let Some(length) = reply.header("Content-Length") {
+            let length: usize = length.trim().parse().map_err(|_| malformed())?;
+            if length > MAX_BODY_SIZE {
+                return Err(too_large());
+            }
+            reply.body = vec![0; length];
+            self.stream.read_exact(&mut reply.body).await?;
+        } else {
+            reply.keep_alive = false;
+            (&mut self.stream)
+                .take(u64::try_from(MAX_BODY_SIZE).unwrap_or(u64::MAX) + 1)
+                .read_to_end(&mut reply.body)
+                .await?;
+            if reply.body.len() > MAX_BODY_SIZE {
+                return Err(too_large());
+            }
+        }
+        Ok(reply)
+    }
+
+    /// 讀取狀態列與標頭
+    async fn head(
+        &mut self,
+    ) -> Result<(String, u16, String, Vec<(String, String)>), ConnectionError> {
+        let mut budget = MAX_HEADER_SIZE;
+        let status_line = self.line(&mut budget).await?;
+        let mut parts = status_line.splitn(3, ' ');
+        let version = parts
+            .next()
+            .filter(|version| version.starts_with("HTTP/1."));
+        let status = parts.next().and_then(|status| status.parse().ok());
+        let (Some(version), Some(status)) = (version, status) else {
+            return Err(malformed());
+        };
+        let reason = parts.next().unwrap_or_default().to_owned();
+
+        let mut headers = Vec::new();
+        loop {
+            let line = self.line(&mut budget).await?;
+            if line.is_empty() {
+                return Ok((version.to_owned(), status, reason, headers));
+            }
+            let (name, value) = line.split_once(':').ok_or_else(malformed)?;
+            headers.push((name.trim().to_owned(), value.trim().to_owned()));
+        }
+    }
+
+    /// 讀取一行，不含行尾的 CRLF
+    async fn line(&mut self, budget: &mut usize) -> Result<String, ConnectionError> {
+        let mut line = Vec::new();
+        let limit = u64::try_from(*budget).unwrap_or(u64::MAX);
+        let length = (&mut self.stream)
+            .take(limit)
+            .read_until(b'\n', &mut line)
+            .await?;
+        if length == 0 {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        if line.pop() != Some(b'\n') {
+            return Err(ConnectionError::Protocol("回覆的標頭過長".to_owned()));
+        }
+        *budget -= length;
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        String::from_utf8(line).map_err(|_| malformed())
+    }
+
+    /// 讀取分塊傳輸的內容，並略過結尾的 trailer
+    async fn chunked(&mut self) -> Result<Vec<u8>, ConnectionError> {
+        let mut body = Vec::new();
+        let mut budget = MAX_HEADER_SIZE;
+        loop {
+            let line = self.line(&mut budget).await?;
+            let size = line.split_once(';').map_or(line.as_str(), |(size, _)| size);
+            let size = usize::from_str_radix(size.trim(), 16).map_err(|_| malformed())?;
+            if size == 0 {
+                break;
+            }
+            if body.len() + size > MAX_BODY_SIZE {
+                return Err(too_large());
+            }
+            let start = body.len();
+            body.resize(start + size, 0);
+            self.stream.read_exact(&mut body[start..]).await?;
+            if !self.line(&mut budget).await?.is_empty() {
+                return Err(malformed());
+            }
+        }
+        while !self.line(&mut budget).await?.is_empty() {}
+        Ok(body)
+    }
+}
+
+fn too_large() -> ConnectionError {
+    ConnectionError::Protocol("回覆的內容過大".to_owned())
+}
+
+/// 點位
+#[derive(Debug, Clone)]
+pub struct HttpPollTarget(pub TargetDefinition);
+
+impl Target for HttpPollTarget {}
+
+/// 讀取請求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpPollRequest {
+    /// 請求的 URL
+    pub url: HttpUrl,
+    /// 選擇器，未設定時點位的數值為整個回覆
+    pub selector: Option<JsonPath>,
+}
+
+impl DeviceStateRequest for HttpPollRequest {}
+
+impl HttpPollRequest {
+    /// 由通用點位定義轉換，欄位說明參見 [`crate::http`]
+    ///
+    /// # 參數
+    /// - `definition`：點位定義
+    /// - `base`：[`HttpPollConfig::base_url`] 解析後的 URL
+    ///
+    /// # Errors
+    /// URL 或選擇器無效時回傳 [`InvalidTarget`]
+    pub fn parse(definition: &TargetDefinition, base: &HttpUrl) -> Result<Self, InvalidTarget> {
+        let invalid = |reason: String| InvalidTarget {
+            target: definition.name.clone(),
+            reason,
+        };
+
+        let url = base.join(&definition.address).map_err(invalid)?;
+        let selector = definition
+            .extra
+            .get("selector")
+            .map(|selector| {
+                selector
+                    .as_str()
+                    .and_then(JsonPath::parse)
+                    .ok_or_else(|| invalid(format!("無效的 selector ：{selector}")))
+            })
+            .transpose()?;
+        Ok(Self { url, selector })
+    }
+
+    fn respond(&self, body: &Value) -> Result<HttpPollResponse, ConnectionError> {
+        let value = match &self.selector {
+            Some(selector) => selector
+                .evaluate(body)
+                .ok_or_else(|| ConnectionError::custom(HttpError::NoMatch(selector.to_string())))?,
+            None => body.clone(),
+        };
+        Ok(HttpPollResponse { value })
+    }
+}
+
+/// 讀取回覆
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpPollResponse {
+    /// 選擇器選出的數值
+    pub value: Value,
+}
+
+impl DeviceStateResponse for HttpPollResponse {
+    fn to_value(&self) -> Result<Cow<'_, Value>, ConversionError> {
+        Ok(Cow::Borrowed(&self.value))
+    }
+}
+
+/// HTTP 輪詢連線
+///
+/// 多個自動更新點位讀取相同 URL 時，回覆會保留更新間隔的一半，期間內的其他點位直接使用該回覆，並跳過等待間隔
+pub struct HttpPollConnection {
+    config: HttpPollConfig,
+    base: HttpUrl,
+    headers: Vec<(String, String)>,
+    clients: HashMap<(String, u16), HttpClient<TcpStream>>,
+    bodies: ResponseCache<Arc<Value>>,
+    shared: Vec<String>,
+    remote_address: RemoteAddress,
+    rejected: Vec<InvalidTarget>,
+}
+
+impl HttpPollConnection {
+    /// 初始化點位時被略過的點位
+    #[must_use]
+    pub fn rejected_targets(&self) -> &[InvalidTarget] {
+        &self.rejected
+    }
+
+    fn prepare(
+        config: &HttpPollConfig,
+    ) -> Result<(HttpUrl, Vec<(String, String)>), ConnectionError> {
+        let base = HttpUrl::parse(&config.base_url).map_err(ConnectionError::InvalidConfig)?;
+        Ok((base, config.request_headers()?))
+    }
+
+    fn set_ttl(&self) {
+        let policy = CachePolicy {
+            ttl: self.config.update_interval / 2,
+            stale_while_revalidate: Duration::ZERO,
+        };
+        for url in &self.shared {
+            self.bodies.enable(url.clone(), policy);
+        }
+    }
+
+    /// 連線至 URL 的主機
+    async fn connect(&self, url: &HttpUrl) -> Result<HttpClient<TcpStream>, ConnectionError> {
+        let endpoint = TcpEndpoint {
+            host: url.host.clone(),
+            port: url.port,
+            proxy: self.config.proxy.clone(),
+        };
+        let stream = tokio::time::timeout(self.config.timeout, tcp::connect(&endpoint)).await??;
+        stream.set_nodelay(true)?;
+        if url.origin() == self.base.origin() {
+            self.remote_address.set(stream.peer_addr().ok());
+        }
+        Ok(HttpClient::new(stream, self.config.timeout))
+    }
+
+    /// 送出請求，連線不存在時先建立連線，回覆後不能繼續使用或發生錯誤的連線會被關閉
+    async fn exchange(&mut self, url: &HttpUrl) -> Result<HttpReply, ConnectionError> {
+        let origin = url.origin();
+        if !self.clients.contains_key(&origin) {
+            let client = self.connect(url).await?;
+            self.clients.insert(origin.clone(), client);
+        }
+        let client = self
+            .clients
+            .get_mut(&origin)
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotConnected))?;
+        let result = client.get(url, &self.headers).await;
+        if !matches!(&result, Ok(reply) if reply.keep_alive) {
+            self.clients.remove(&origin);
+        }
+        result
+    }
+
+    async fn fetch(&mut self, url: &HttpUrl) -> Result<Value, ConnectionError> {
+        let reused = self.clients.contains_key(&url.origin());
+        let reply = match self.exchange(url).await {
+            Ok(reply) => Some(reply),
+            // 設備可能已關閉閒置的連線
+            Err(ConnectionError::Io(_)) if reused => None,
+            Err(error) => return Err(error),
+        };
+        let reply = match reply {
+            Some(reply) => reply,
+            None => self.exchange(url).await?,
+        };
+        reply.json()
+    }
+}
+
+impl Connection for HttpPollConnection {
+    const NAMES: &[&str] = &["Http", "Rest"];
+    type Config = HttpPollConfig;
+    type Target = HttpPollTarget;
+    type Request = HttpPollRequest;
+    type Response = HttpPollResponse;
+    type Result = ();
+
+    async fn init(config: &HttpPollConfig) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+        let (base, headers) = Self::prepare(config)?;
+        let statistics = ConnectionStats::new(format!("{}:{}", base.host, base.port), None);
+
+        let mut connection = Self {
+            config: config.clone(),
+            base,
+            headers,
+            clients: HashMap::default(),
+            bodies: ResponseCache::new(),
+            shared: Vec::new(),
+            remote_address: statistics.remote_address.clone(),
+            rejected: Vec::new(),
+        };
+        connection.reconnect().await?;
+
+        let artifact = ConnectionArtifact::new(connection, statistics)
+            .update_every(config.update_interval)
+            .timeout_after(config.timeout);
+
+        Ok(match config.max_retry_count {
+            Some(count) => artifact.retry_up_to(count),
+            None => artifact,
+        })
+    }
+
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<HttpPollTarget>,
+    ) -> ConnectionTargets<HttpPollRequest, ()> {
+        let mut parsed = Vec::with_capacity(targets.len());
+        for HttpPollTarget(definition) in targets {
+            match HttpPollRequest::parse(&definition, &self.base) {
+                Ok(request) => parsed.push((definition, request)),
+                Err(error) => self.rejected.push(error),
+            }
+        }
+
+        let mut polled: Vec<String> = parsed
+            .iter()
+            .filter(|(definition, _)| definition.auto_refresh)
+            .map(|(_, request)| request.url.to_string())
+            .collect();
+        polled.sort_unstable();
+        self.shared = polled
+            .chunk_by(|left, right| left == right)
+            .filter(|urls| urls.len() > 1)
+            .map(|urls| urls[0].clone())
+            .collect();
+        self.set_ttl();
+
+        ConnectionTargets(
+            parsed
+                .into_iter()
+                .map(|(definition, request)| InitedTarget {
+                    name: definition.name,
+                    request,
+                    result: (),
+                    default_status: definition.default_status,
+                    auto_refresh: definition.auto_refresh,
+                    refresh_interval: None,
+                    keep_raw_frames: None,
+                    group: None,
+                    safe_state: None,
+                    array: None,
+                    change: None,
+                    statistics: Some(connection_statistics.insert_target(definition.device)),
+                })
+                .collect(),
+        )
+    }
+
+    async fn request_process(
+        &mut self,
+        request: HttpPollRequest,
+    ) -> Result<(HttpPollResponse, bool), ConnectionError> {
+        let key = request.url.to_string();
+        let (body, wait) = match self.bodies.lookup(&key, None) {
+            CacheLookup::Fresh(body) => (body, false),
+            CacheLookup::Stale { .. } | CacheLookup::Miss => {
+                let body = Arc::new(self.fetch(&request.url).await?);
+                self.bodies.store(&key, Arc::clone(&body));
+                (body, true)
+            }
+        };
+        Ok((request.respond(&body)?, wait))
+    }
+
+    async fn disconnect(&mut self) -> Result<(), ConnectionError> {
+        self.clients.clear();
+        self.remote_address.set(None);
+        Ok(())
+    }
+
+    /// 關閉所有連線後重新連線至 [`HttpPollConfig::base_url`] 的主機，其他主機在下一次請求時連線
+    async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+        self.clients.clear();
+        self.remote_address.set(None);
+        let client = self.connect(&self.base).await?;
+        self.clients.insert(self.base.origin(), client);
+        Ok(())
+    }
+
+    async fn update_config(&mut self, new_config: &HttpPollConfig) -> Result<(), ConnectionError> {
+        let (base, headers) = Self::prepare(new_config)?;
+        self.config = new_config.clone();
+        self.base = base;
+        self.headers = headers;
+        self.set_ttl();
+        self.reconnect().await
+    }
+}
+
+/// 以記錄下來的 HTTP 回覆解碼點位
+///
+/// `frame` 為完整的回覆（狀態列、標頭與內容），`target` 為點位的 `selector` ，空字串表示整個回覆
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::{fixture::Fixture, http::HttpPollConnection, vectors::encode_hex};
+/// use serde_json::json;
+///
+/// let ok = encode_hex(b"HTTP/1.1 200 OK\r\nContent-Length: 43\r\n\r\n{\"sensors\":[{\"temperature\":23.5},{\"on\":1}]}");
+/// let not_found = encode_hex(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+/// let fixture = Fixture::from_json(&json!({
+///     "connection": "Http",
+///     "cases": [
+///         { "name": "選擇器", "target": "$.sensors[0].temperature", "frame": ok, "expected": 23.5 },
+///         { "name": "沒有符合的內容", "target": "$.sensors[5]", "frame": ok },
+///         { "name": "整個回覆", "target": "", "frame": ok, "expected": { "sensors": [{ "temperature": 23.5 }, { "on": 1 }] } },
+///         { "name": "狀態碼", "target": "", "frame": not_found },
+///     ],
+/// }).to_string()).unwrap();
+///
+/// let report = fixture.run::<HttpPollConnection>();
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+impl FrameDecoder for HttpPollConnection {
+    fn decode_frame(target: &str, frame: &[u8]) -> Result<HttpPollResponse, Box<dyn Error>> {
+        let selector = match target {
+            "" => None,
+            target => {
+                Some(JsonPath::parse(target).ok_or_else(|| format!("無效的 selector ：{target}"))?)
+            }
+        };
+        let request = HttpPollRequest {
+            url: HttpUrl::parse("http://localhost/")?,
+            selector,
+        };
+        Ok(request.respond(&HttpReply::parse(frame)?.json()?)?)
+    }
+}
@@ -0,0 +1,1050 @@
+//! KNX/IP 參考實作（需啟用 `knx` feature）
+//!
+//! 以 KNXnet/IP 通道（tunneling）連線至 KNX IP 介面或路由器，讀寫樓宇自動化設備（照明、空調、百葉）的群組位址：
+//!
+//! - 匯流排上的群組電報（`GroupValue_Write` 、`GroupValue_Response`）由背景工作接收並保留最新的一筆，
+//!   主程式輪詢自動更新點位時直接回傳最新的電報，不會在匯流排上產生額外的流量
+//! - 尚未收到電報，或電報超過 [`KnxConfig::stale_after`] 時，送出 `GroupValue_Read` 並等待設備回覆
+//! - 寫入經由 [`Connection::write_preprocess()`] 依點位的 DPT 編碼後送出 `GroupValue_Write` ，等待 IP 介面確認（`L_Data.con`）
+//! - 背景工作每 [`HEARTBEAT_INTERVAL`] 送出 `CONNECTIONSTATE_REQUEST` 維持通道，IP 介面沒有回應或中斷通道時，下一個請求會回傳錯誤並重新連線
+//!
+//! 點位設定：
+//!
+//! | 欄位 | 說明 |
+//! | --- | --- |
+//! | `address` | 群組位址，如 `1/2/3` ，也可以使用兩層（`1/515`）或數字（`2563`）格式 |
+//! | `dpt` | 資料點類型，如 `1.001` 、`9.001` 、`DPST-5-1` ，支援的類型參見 [`Dpt`] |
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{
+//!     TargetDefinition,
+//!     knx::{Dpt, GroupAddress, KnxConfig, KnxRequest},
+//! };
+//! use serde_json::json;
+//!
+//! let config: KnxConfig = serde_json::from_value(json!({ "host": "192.168.1.50" })).unwrap();
+//! assert_eq!(config.port, 3671);
+//!
+//! let definition: TargetDefinition = serde_json::from_value(json!({
+//!     "name": "會議室溫度",
+//!     "address": "3/1/10",
+//!     "dpt": "9.001",
+//! }))
+//! .unwrap();
+//! let request = KnxRequest::parse(&definition).unwrap();
+//! assert_eq!(request.group, GroupAddress::parse("3/1/10").unwrap());
+//! assert_eq!(request.group, GroupAddress::parse("3/266").unwrap());
+//! assert_eq!(request.group.to_string(), "3/1/10");
+//! assert_eq!(request.dpt, Dpt::parse("9.001").unwrap());
+//! ```
+
+mod dpt;
+
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::Display,
+    net::SocketAddr,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{net::UdpSocket, sync::Notify, task::JoinHandle, time::Instant};
+
+pub use self::dpt::Dpt;
+use crate::{
+    Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+    ConnectionTargets, DeviceStateRequest, DeviceStateResponse, DeviceStateWrite, HashMap,
+    InitedTarget, RemoteAddress, RequestContext, Target, TargetDefinition,
+    definition::InvalidTarget, fixture::FrameDecoder, value::ConversionError,
+};
+
+/// KNXnet/IP 預設的 UDP 埠號
+pub const DEFAULT_PORT: u16 = 3671;
+
+/// 預設的更新間隔
+pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 預設的回覆逾時
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 維持通道的 `CONNECTIONSTATE_REQUEST` 間隔，IP 介面在 120 秒沒有收到時會中斷通道
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_mins(1);
+
+/// 等待 `CONNECTIONSTATE_RESPONSE` 的逾時
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `CONNECTIONSTATE_REQUEST` 沒有回應時的嘗試次數
+const HEARTBEAT_ATTEMPTS: u8 = 3;
+
+/// 等待 `TUNNELING_ACK` 的逾時，逾時後重送一次
+const ACK_TIMEOUT: Duration = Duration::from_secs(1);
+
+const CONNECT_REQUEST: u16 = 0x0205;
+const CONNECT_RESPONSE: u16 = 0x0206;
+const CONNECTIONSTATE_REQUEST: u16 = 0x0207;
+const CONNECTIONSTATE_RESPONSE: u16 = 0x0208;
+const DISCONNECT_REQUEST: u16 = 0x0209;
+const DISCONNECT_RESPONSE: u16 = 0x020A;
+const TUNNELING_REQUEST: u16 = 0x0420;
+const TUNNELING_ACK: u16 = 0x0421;
+
+/// 以 NAT 模式連線的 HPAI（位址與埠號皆為 0 ，IP 介面回覆至封包的來源）
+const HPAI_NAT: [u8; 8] = [0x08, 0x01, 0, 0, 0, 0, 0, 0];
+
+/// cEMI 訊息代碼
+const L_DATA_REQ: u8 = 0x11;
+const L_DATA_CON: u8 = 0x2E;
+const L_DATA_IND: u8 = 0x29;
+
+/// APCI
+const GROUP_VALUE_READ: u16 = 0x000;
+const GROUP_VALUE_RESPONSE: u16 = 0x040;
+const GROUP_VALUE_WRITE: u16 = 0x080;
+
+const fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+const fn default_update_interval() -> Duration {
+    DEFAULT_UPDATE_INTERVAL
+}
+
+const fn default_timeout() -> Duration {
+    DEFAULT_TIMEOUT
+}
+
+/// 連線參數
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnxConfig {
+    /// IP 介面的主機名稱或 IP 位址
+    pub host: String,
+    /// 埠號，預設為 [`DEFAULT_PORT`]
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// 更新間隔，序列化時以毫秒數表示，預設為 [`DEFAULT_UPDATE_INTERVAL`]
+    #[serde(
+        rename = "update_interval_ms",
+        with = "crate::millis",
+        default = "default_update_interval"
+    )]
+    pub update_interval: Duration,
+    /// 回覆逾時，序列化時以毫秒數表示，預設為 [`DEFAULT_TIMEOUT`]
+    #[serde(
+        rename = "timeout_ms",
+        with = "crate::millis",
+        default = "default_timeout"
+    )]
+    pub timeout: Duration,
+    /// 群組電報的有效時間，序列化時以毫秒數表示，超過時以 `GroupValue_Read` 重新讀取；未設定時一直使用最新的電報
+    #[serde(rename = "stale_after_ms", with = "crate::millis::option", default)]
+    pub stale_after: Option<Duration>,
+    /// 最高重試次數，參見 [`ConnectionArtifact::max_retry_count`]
+    #[serde(default)]
+    pub max_retry_count: Option<u32>,
+}
+
+impl ConnectionConfig for KnxConfig {}
+
+/// 群組位址
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GroupAddress(pub u16);
+
+impl GroupAddress {
+    /// 解析三層（`主群組/中群組/子群組`，5/3/8 位元）、兩層（`主群組/子群組`，5/11 位元）或數字格式的群組位址
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let parts = text
+            .trim()
+            .split('/')
+            .map(|part| part.trim().parse::<u16>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        let address = match parts.as_slice() {
+            [main, middle, sub] if *main < 32 && *middle < 8 && *sub < 256 => {
+                (main << 11) | (middle << 8) | sub
+            }
+            [main, sub] if *main < 32 && *sub < 2048 => (main << 11) | sub,
+            [address] => *address,
+            _ => return None,
+        };
+        Some(Self(address))
+    }
+}
+
+impl Display for GroupAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}",
+            self.0 >> 11,
+            (self.0 >> 8) & 0x07,
+            self.0 & 0xFF
+        )
+    }
+}
+
+/// 個別位址（`區域.線路.設備`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IndividualAddress(pub u16);
+
+impl Display for IndividualAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}",
+            self.0 >> 12,
+            (self.0 >> 8) & 0x0F,
+            self.0 & 0xFF
+        )
+    }
+}
+
+/// 群組電報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupTelegram {
+    /// 送出電報的設備
+    pub source: IndividualAddress,
+    /// 資料，6 位元以內的數值為 1 個位元組
+    pub data: Vec<u8>,
+    /// 收到電報的時間
+    pub received: Instant,
+}
+
+/// IP 介面回覆的錯誤
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KnxError {
+    /// 建立通道失敗，如 `0x24`（`E_NO_MORE_CONNECTIONS`）表示 IP 介面的通道已用完
+    Connect {
+        /// 狀態碼
+        status: u8,
+    },
+    /// `TUNNELING_ACK` 的狀態碼不為 0
+    Tunneling {
+        /// 狀態碼
+        status: u8,
+    },
+    /// IP 介面回覆電報無法送上匯流排（`L_Data.con` 的確認位元為錯誤）
+    NotConfirmed {
+        /// 群組位址
+        group: GroupAddress,
+    },
+}
+
+impl Display for KnxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Connect { status } => {
+                let reason = match status {
+                    0x22 => "不支援的連線類型",
+                    0x23 => "不支援的連線選項",
+                    0x24 => "通道已用完",
+                    0x29 => "不支援的通道層級",
+                    _ => "未知的錯誤",
+                };
+                write!(f, "建立 KNX 通道失敗（0x{status:02X}）：{reason}")
+            }
+            Self::Tunneling { status } => write!(f, "IP 介面拒絕電報（0x{status:02X}）"),
+            Self::NotConfirmed { group } => write!(f, "群組位址 {group} 的電報無法送上匯流排"),
+        }
+    }
+}
+
+impl Error for KnxError {}
+
+fn malformed() -> ConnectionError {
+    ConnectionError::Protocol("無法解析的 KNXnet/IP 封包".to_owned())
+}
+
+fn not_connected() -> ConnectionError {
+    std::io::Error::from(std::io::ErrorKind::NotConnected).into()
+}
+
+/// 產生 KNXnet/IP 封包
+fn frame(service: u16, body: &[u8]) -> Vec<u8> {
+    let length = u16::try_from(body.len() + 6).unwrap_or(u16::MAX);
+    let mut frame = Vec::with_capacity(body.len() + 6);
+    frame.extend([0x06, 0x10]);
+    frame.extend(service.to_be_bytes());
+    frame.extend(length.to_be_bytes());
+    frame.extend(body);
+    frame
+}
+
+/// 解析 KNXnet/IP 封包，回傳服務類型與內容
+fn parse_frame(datagram: &[u8]) -> Option<(u16, &[u8])> {
+    let [0x06, 0x10, s1, s2, l1, l2, body @ ..] = datagram else {
+        return None;
+    };
+    let length = usize::from(u16::from_be_bytes([*l1, *l2]));
+    let body = body.get(..length.checked_sub(6)?)?;
+    Some((u16::from_be_bytes([*s1, *s2]), body))
+}
+
+/// 產生群組電報的 cEMI `L_Data.req`
+///
+/// 6 位元以內的數值放在 APCI 的低 6 位元中，其他數值接在 APCI 之後
+fn group_request(group: GroupAddress, apci: u16, data: &[u8], short: bool) -> Vec<u8> {
+    let [high, low] = group.0.to_be_bytes();
+    let [apci_high, apci_low] = apci.to_be_bytes();
+    // 標準訊框、不重送、廣播、低優先權；群組位址、路由計數 6
+    let mut cemi = vec![L_DATA_REQ, 0x00, 0xBC, 0xE0, 0x00, 0x00, high, low];
+    if short {
+        cemi.extend([
+            1,
+            apci_high,
+            apci_low | (data.first().copied().unwrap_or_default() & 0x3F),
+        ]);
+    } else {
+        cemi.push(u8::try_from(data.len() + 1).unwrap_or(u8::MAX));
+        cemi.extend([apci_high, apci_low]);
+        cemi.extend(data);
+    }
+    cemi
+}
+
+/// cEMI 群組電報
+struct Cemi {
+    code: u8,
+    confirmed: bool,
+    source: IndividualAddress,
+    group: GroupAddress,
+    apci: u16,
+    data: Vec<u8>,
+}
+
+impl Cemi {
+    /// 解析 cEMI ，不是群組電報時回傳 [`None`]
+    fn parse(cemi: &[u8]) -> Option<Self> {
+        let [code, info_length, rest @ ..] = cemi else {
+            return None;
+        };
+        let [ctrl1, ctrl2, s1, s2, d1, d2, length, apdu @ ..] =
+            rest.get(usize::from(*info_length)..)?
+        else {
+            return None;
+        };
+        if ctrl2 & 0x80 == 0 {
+            return None;
+        }
+        let length = usize::from(*length);
+        let apdu = apdu.get(..=length)?;
+        let apci = u16::from_be_bytes([apdu[0] & 0x03, *apdu.get(1)?]) & 0x03C0;
+        let data = if length == 1 {
+            vec![apdu[1] & 0x3F]
+        } else {
+            apdu[2..].to_vec()
+        };
+        Some(Self {
+            code: *code,
+            confirmed: ctrl1 & 0x01 == 0,
+            source: IndividualAddress(u16::from_be_bytes([*s1, *s2])),
+            group: GroupAddress(u16::from_be_bytes([*d1, *d2])),
+            apci,
+            data,
+        })
+    }
+}
+
+/// 與背景工作共用的狀態
+#[derive(Debug)]
+struct Shared {
+    telegrams: Mutex<HashMap<GroupAddress, GroupTelegram>>,
+    /// 最近一次 `TUNNELING_ACK` 的序號與狀態碼
+    ack: Mutex<Option<(u8, u8)>>,
+    /// 最近一次 `L_Data.con` 的群組位址與是否成功
+    confirmation: Mutex<Option<(GroupAddress, bool)>>,
+    connected: AtomicBool,
+    notify: Notify,
+}
+
+impl Shared {
+    fn received(&self, cemi: Cemi) {
+        match cemi.code {
+            L_DATA_IND if matches!(cemi.apci, GROUP_VALUE_RESPONSE | GROUP_VALUE_WRITE) => {
+                self.store(cemi);
+            }
+            L_DATA_CON => {
+                *self
+                    .confirmation
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) = Some((cemi.group, cemi.confirmed));
+                // 本連線寫入的數值同樣更新最新的電報
+                if cemi.confirmed && cemi.apci == GROUP_VALUE_WRITE {
+                    self.store(cemi);
+                }
+            }
+            _ => {}
+        }
+        self.notify.notify_waiters();
+    }
+
+    fn store(&self, cemi: Cemi) {
+        self.telegrams
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                cemi.group,
+                GroupTelegram {
+                    source: cemi.source,
+                    data: cemi.data,
+                    received: Instant::now(),
+                },
+            );
+    }
+}
+
+/// 背景工作，接收 IP 介面的封包並維持通道，通道中斷時結束
+async fn drive(socket: Arc<UdpSocket>, shared: Arc<Shared>, channel: u8) {
+    let mut buffer = [0; 512];
+    let mut expected: u8 = 0;
+    let mut missed = 0;
+    let mut deadline = Instant::now() + HEARTBEAT_INTERVAL;
+    loop {
+        tokio::select! {
+            received = socket.recv(&mut buffer) => {
+                let Ok(length) = received else {
+                    break;
+                };
+                let Some((service, body)) = parse_frame(&buffer[..length]) else {
+                    continue;
+                };
+                match (service, body) {
+                    (TUNNELING_REQUEST, [0x04, id, sequence, _, cemi @ ..]) if *id == channel => {
+                        // 重複的電報只回覆確認
+                        if *sequence == expected || *sequence == expected.wrapping_sub(1) {
+                            let ack = frame(TUNNELING_ACK, &[0x04, channel, *sequence, 0x00]);
+                            let _ = socket.send(&ack).await;
+                        }
+                        if *sequence == expected {
+                            expected = expected.wrapping_add(1);
+                            if let Some(cemi) = Cemi::parse(cemi) {
+                                shared.received(cemi);
+                            }
+                        }
+                    }
+                    (TUNNELING_ACK, [0x04, id, sequence, status, ..]) if *id == channel => {
+                        *shared.ack.lock().unwrap_or_else(PoisonError::into_inner) =
+                            Some((*sequence, *status));
+                        shared.notify.notify_waiters();
+                    }
+                    (CONNECTIONSTATE_RESPONSE, [id, status, ..]) if *id == channel => {
+                        if *status != 0 {
+                            break;
+                        }
+                        missed = 0;
+                        deadline = Instant::now() + HEARTBEAT_INTERVAL;
+                    }
+                    (DISCONNECT_REQUEST, [id, ..]) if *id == channel => {
+                        let _ = socket.send(&frame(DISCONNECT_RESPONSE, &[channel, 0])).await;
+                        break;
+                    }
+                    (DISCONNECT_RESPONSE, [id, ..]) if *id == channel => break,
+                    _ => {}
+                }
+            }
+            () = tokio::time::sleep_until(deadline) => {
+                if missed >= HEARTBEAT_ATTEMPTS {
+                    break;
+                }
+                let mut body = vec![channel, 0];
+                body.extend(HPAI_NAT);
+                let _ = socket.send(&frame(CONNECTIONSTATE_REQUEST, &body)).await;
+                missed += 1;
+                deadline = Instant::now() + HEARTBEAT_TIMEOUT;
+            }
+        }
+    }
+    shared.connected.store(false, Ordering::Relaxed);
+    shared.notify.notify_waiters();
+}
+
+/// KNXnet/IP 通道
+///
+/// 建立通道後由背景工作接收匯流排上的群組電報並維持通道，一次只送出一個電報，本物件被釋放時背景工作隨之結束
+///
+/// # 範例
+/// ```rust
+/// use std::time::Duration;
+/// use device_state_exchange_lib::knx::{Dpt, GroupAddress, KnxTunnel};
+/// use serde_json::json;
+/// use tokio::net::UdpSocket;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// // 模擬 IP 介面
+/// let interface = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+/// let address = interface.local_addr().unwrap();
+/// tokio::spawn(async move {
+///     let mut buffer = [0; 256];
+///     let (_, peer) = interface.recv_from(&mut buffer).await.unwrap();
+///     assert_eq!(buffer[2..4], [0x02, 0x05]); // CONNECT_REQUEST
+///     // CONNECT_RESPONSE ：通道 7 ，個別位址 1.1.250
+///     let mut response = vec![0x06, 0x10, 0x02, 0x06, 0x00, 0x14, 0x07, 0x00];
+///     response.extend([0x08, 0x01, 127, 0, 0, 1, 0x0E, 0x57, 0x04, 0x04, 0x11, 0xFA]);
+///     interface.send_to(&response, peer).await.unwrap();
+///
+///     // 匯流排上 1.1.5 寫入 3/1/10 = 22.5 °C
+///     let mut request = vec![0x06, 0x10, 0x04, 0x20, 0x00, 0x17, 0x04, 0x07, 0x00, 0x00];
+///     request.extend([0x29, 0x00, 0xBC, 0xE0, 0x11, 0x05, 0x19, 0x0A, 0x03, 0x00, 0x80, 0x0C, 0x65]);
+///     interface.send_to(&request, peer).await.unwrap();
+///     let (length, _) = interface.recv_from(&mut buffer).await.unwrap();
+///     assert_eq!(buffer[..length], [0x06, 0x10, 0x04, 0x21, 0x00, 0x0A, 0x04, 0x07, 0x00, 0x00]);
+/// });
+///
+/// let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+/// socket.connect(address).await.unwrap();
+/// let tunnel = KnxTunnel::connect(socket, Duration::from_secs(1)).await.unwrap();
+/// assert_eq!(tunnel.address().to_string(), "1.1.250");
+///
+/// let group = GroupAddress::parse("3/1/10").unwrap();
+/// tokio::time::sleep(Duration::from_millis(100)).await;
+/// let telegram = tunnel.latest(group).unwrap();
+/// assert_eq!(telegram.source.to_string(), "1.1.5");
+/// assert_eq!(Dpt::parse("9.001").unwrap().decode(&telegram.data).unwrap(), json!(22.5));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct KnxTunnel {
+    socket: Arc<UdpSocket>,
+    channel: u8,
+    address: IndividualAddress,
+    sequence: u8,
+    timeout: Duration,
+    shared: Arc<Shared>,
+    task: JoinHandle<()>,
+}
+
+impl KnxTunnel {
+    /// 建立通道（`CONNECT_REQUEST`），並在背景接收封包
+    ///
+    /// # 參數
+    /// - `socket`：已以 [`UdpSocket::connect()`] 連線至 IP 介面的 UDP socket
+    /// - `timeout`：回覆逾時
+    ///
+    /// # Errors
+    /// 逾時回傳 [`ConnectionError::Timeout`] ，IP 介面拒絕時回傳包含 [`KnxError::Connect`] 的 [`ConnectionError::Custom`]
+    pub async fn connect(socket: UdpSocket, timeout: Duration) -> Result<Self, ConnectionError> {
+        let mut body = Vec::with_capacity(20);
+        body.extend(HPAI_NAT);
+        body.extend(HPAI_NAT);
+        // 通道連線、鏈結層通道
+        body.extend([0x04, 0x04, 0x02, 0x00]);
+        socket.send(&frame(CONNECT_REQUEST, &body)).await?;
+
+        let deadline = Instant::now() + timeout;
+        let mut buffer = [0; 256];
+        let (channel, address) = loop {
+            let length = tokio::time::timeout_at(deadline, socket.recv(&mut buffer)).await??;
+            let Some((CONNECT_RESPONSE, body)) = parse_frame(&buffer[..length]) else {
+                continue;
+            };
+            match body {
+                [channel, 0, _hpai @ .., 0x04, 0x04, high, low] => {
+                    break (
+                        *channel,
+                        IndividualAddress(u16::from_be_bytes([*high, *low])),
+                    );
+                }
+                [_, 0, ..] => return Err(malformed()),
+                [_, status, ..] => {
+                    return Err(ConnectionError::custom(KnxError::Connect {
+                        status: *status,
+                    }));
+                }
+                _ => return Err(malformed()),
+            }
+        };
+
+        let socket = Arc::new(socket);
+        let shared = Arc::new(Shared {
+            telegrams: Mutex::new(HashMap::default()),
+            ack: Mutex::new(None),
+            confirmation: Mutex::new(None),
+            connected: AtomicBool::new(true),
+            notify: Notify::new(),
+        });
+        let task = tokio::spawn(drive(Arc::clone(&socket), Arc::clone(&shared), channel));
+        Ok(Self {
+            socket,
+            channel,
+            address,
+            sequence: 0,
+            timeout,
+            shared,
+            task,
+        })
+    }
+
+    /// IP 介面指派給本通道的個別位址
+    #[must_use]
+    pub const fn address(&self) -> IndividualAddress {
+        self.address
+    }
+
+    /// 通道是否仍然有效
+    #[must_use]
+    pub fn is_connected(&self) -> bool {
+        self.shared.connected.load(Ordering::Relaxed)
+    }
+
+    /// 群組位址最新的電報，尚未收到時為 [`None`]
+    #[must_use]
+    pub fn latest(&self, group: GroupAddress) -> Option<GroupTelegram> {
+        self.shared
+            .telegrams
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&group)
+            .cloned()
+    }
+
+    /// 送出 `GroupValue_Read` 並等待設備回覆
+    ///
+    /// # Errors
+    /// 逾時回傳 [`ConnectionError::Timeout`] ，通道已中斷時回傳 [`ConnectionError::Io`] ，
+    /// IP 介面拒絕時回傳包含 [`KnxError`] 的 [`ConnectionError::Custom`]
+    pub async fn read(&mut self, group: GroupAddress) -> Result<GroupTelegram, ConnectionError> {
+        let since = Instant::now();
+        self.send(&group_request(group, GROUP_VALUE_READ, &[], true))
+            .await?;
+        let shared = Arc::clone(&self.shared);
+        self.wait(|| {
+            shared
+                .telegrams
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(&group)
+                .filter(|telegram| telegram.received >= since)
+                .cloned()
+        })
+        .await
+    }
+
+    /// 送出 `GroupValue_Write` 並等待 IP 介面確認電報已送上匯流排
+    ///
+    /// # 參數
+    /// - `group`：群組位址
+    /// - `data`：資料，參見 [`Dpt::encode()`]
+    /// - `short`：是否為 6 位元以內的數值，參見 [`Dpt::is_short()`]
+    ///
+    /// # Errors
+    /// 同 [`Self::read()`]，IP 介面回覆無法送上匯流排時回傳包含 [`KnxError::NotConfirmed`] 的 [`ConnectionError::Custom`]
+    pub async fn write(
+        &mut self,
+        group: GroupAddress,
+        data: &[u8],
+        short: bool,
+    ) -> Result<(), ConnectionError> {
+        *self
+            .shared
+            .confirmation
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = None;
+        self.send(&group_request(group, GROUP_VALUE_WRITE, data, short))
+            .await?;
+        let shared = Arc::clone(&self.shared);
+        let confirmed = self
+            .wait(|| {
+                shared
+                    .confirmation
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .filter(|(address, _)| *address == group)
+                    .map(|(_, confirmed)| confirmed)
+            })
+            .await?;
+        if confirmed {
+            Ok(())
+        } else {
+            Err(ConnectionError::custom(KnxError::NotConfirmed { group }))
+        }
+    }
+
+    /// 中斷通道（`DISCONNECT_REQUEST`），IP 介面沒有回覆時同樣視為已中斷
+    pub async fn close(&mut self) {
+        if self.is_connected() {
+            let mut body = vec![self.channel, 0];
+            body.extend(HPAI_NAT);
+            if self
+                .socket
+                .send(&frame(DISCONNECT_REQUEST, &body))
+                .await
+                .is_ok()
+            {
+                // 等待背景工作收到 `DISCONNECT_RESPONSE`
+                let _ = self.wait(|| None::<()>).await;
+            }
+        }
+        self.task.abort();
+        self.shared.connected.store(false, Ordering::Relaxed);
+    }
+
+    /// 以 `TUNNELING_REQUEST` 送出 cEMI ，等待 `TUNNELING_ACK` ，逾時後重送一次
+    async fn send(&mut self, cemi: &[u8]) -> Result<(), ConnectionError> {
+        if !self.is_connected() {
+            return Err(not_connected());
+        }
+        let sequence = self.sequence;
+        let mut body = vec![0x04, self.channel, sequence, 0x00];
+        body.extend(cemi);
+        let request = frame(TUNNELING_REQUEST, &body);
+
+        let shared = Arc::clone(&self.shared);
+        for _ in 0..2 {
+            self.socket.send(&request).await?;
+            let acked = tokio::time::timeout(ACK_TIMEOUT, async {
+                loop {
+                    let notified = shared.notify.notified();
+                    let ack = *shared.ack.lock().unwrap_or_else(PoisonError::into_inner);
+                    if let Some((acked, status)) = ack
+                        && acked == sequence
+                    {
+                        return Ok(status);
+                    }
+                    if !shared.connected.load(Ordering::Relaxed) {
+                        return Err(not_connected());
+                    }
+                    notified.await;
+                }
+            })
+            .await;
+            match acked {
+                Ok(Ok(0)) => {
+                    self.sequence = sequence.wrapping_add(1);
+                    return Ok(());
+                }
+                Ok(Ok(status)) => {
+                    return Err(ConnectionError::custom(KnxError::Tunneling { status }));
+                }
+                Ok(Err(error)) => return Err(error),
+                Err(_) => {}
+            }
+        }
+        Err(ConnectionError::Timeout)
+    }
+
+    /// 等待背景工作收到符合條件的封包，通道中斷時回傳錯誤
+    async fn wait<T>(&self, mut check: impl FnMut() -> Option<T>) -> Result<T, ConnectionError> {
+        let shared = &self.shared;
+        tokio::time::timeout(self.timeout, async {
+            loop {
+                let notified = shared.notify.notified();
+                if let Some(result) = check() {
+                    return Ok(result);
+                }
+                if !shared.connected.load(Ordering::Relaxed) {
+                    return Err(not_connected());
+                }
+                notified.await;
+            }
+        })
+        .await?
+    }
+}
+
+impl Drop for KnxTunnel {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// 點位
+#[derive(Debug, Clone)]
+pub struct KnxTarget(pub TargetDefinition);
+
+impl Target for KnxTarget {}
+
+/// 讀取請求
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KnxRequest {
+    /// 群組位址
+    pub group: GroupAddress,
+    /// 資料點類型
+    pub dpt: Dpt,
+}
+
+impl DeviceStateRequest for KnxRequest {}
+
+impl KnxRequest {
+    /// 由通用點位定義轉換，欄位說明參見 [`crate::knx`]
+    ///
+    /// # Errors
+    /// 群組位址或資料點類型無效時回傳 [`InvalidTarget`]
+    pub fn parse(definition: &TargetDefinition) -> Result<Self, InvalidTarget> {
+        let invalid = |reason: String| InvalidTarget {
+            target: definition.name.clone(),
+            reason,
+        };
+
+        let group = GroupAddress::parse(&definition.address)
+            .ok_or_else(|| invalid(format!("無效的群組位址「{}」", definition.address)))?;
+        let dpt = match definition.extra.get("dpt") {
+            Some(dpt) => dpt
+                .as_str()
+                .and_then(Dpt::parse)
+                .ok_or_else(|| invalid(format!("無效或不支援的 dpt ：{dpt}")))?,
+            None => return Err(invalid("缺少 dpt".to_owned())),
+        };
+        Ok(Self { group, dpt })
+    }
+}
+
+/// 讀取回覆
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnxResponse {
+    /// 群組位址
+    pub group: GroupAddress,
+    /// 資料點類型
+    pub dpt: Dpt,
+    /// 送出電報的設備
+    pub source: IndividualAddress,
+    /// 電報的資料
+    pub data: Vec<u8>,
+}
+
+impl DeviceStateResponse for KnxResponse {
+    /// 依 [`Self::dpt`] 解碼，參見 [`Dpt::decode()`]
+    fn to_value(&self) -> Result<Cow<'_, Value>, ConversionError> {
+        self.dpt.decode(&self.data).map(Cow::Owned)
+    }
+}
+
+/// 寫入請求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KnxWrite {
+    /// 群組位址
+    pub group: GroupAddress,
+    /// 編碼後的資料
+    pub data: Vec<u8>,
+    /// 是否為 6 位元以內的數值
+    pub short: bool,
+}
+
+impl DeviceStateWrite for KnxWrite {}
+
+/// KNX/IP 連線
+pub struct KnxConnection {
+    config: KnxConfig,
+    tunnel: Option<KnxTunnel>,
+    remote_address: RemoteAddress,
+    rejected: Vec<InvalidTarget>,
+}
+
+impl KnxConnection {
+    /// 初始化點位時被略過的點位
+    #[must_use]
+    pub fn rejected_targets(&self) -> &[InvalidTarget] {
+        &self.rejected
+    }
+
+    async fn open(config: &KnxConfig) -> Result<(KnxTunnel, SocketAddr), ConnectionError> {
+        let peer = tokio::net::lookup_host((config.host.as_str(), config.port))
+            .await?
+            .next()
+            .ok_or_else(|| {
+                ConnectionError::InvalidConfig(format!("無法解析主機名稱「{}」", config.host))
+            })?;
+        let bind: SocketAddr = if peer.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(bind).await?;
+        socket.connect(peer).await?;
+        Ok((KnxTunnel::connect(socket, config.timeout).await?, peer))
+    }
+
+    async fn close(&mut self) {
+        if let Some(mut tunnel) = self.tunnel.take() {
+            tunnel.close().await;
+        }
+        self.remote_address.set(None);
+    }
+
+    fn tunnel(&mut self) -> Result<&mut KnxTunnel, ConnectionError> {
+        self.tunnel
+            .as_mut()
+            .filter(|tunnel| tunnel.is_connected())
+            .ok_or_else(not_connected)
+    }
+}
+
+impl Connection for KnxConnection {
+    const NAMES: &[&str] = &["Knx"];
+    type Config = KnxConfig;
+    type Target = KnxTarget;
+    type Request = KnxRequest;
+    type Response = KnxResponse;
+    type Result = ();
+
+    async fn init(config: &KnxConfig) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+        let (tunnel, peer) = Self::open(config).await?;
+        let statistics = ConnectionStats::new(format!("{}:{}", config.host, config.port), None);
+        statistics.remote_address.set(Some(peer));
+
+        let artifact = ConnectionArtifact::new(
+            Self {
+                config: config.clone(),
+                tunnel: Some(tunnel),
+                remote_address: statistics.remote_address.clone(),
+                rejected: Vec::new(),
+            },
+            statistics,
+        )
+        .update_every(config.update_interval)
+        .timeout_after(config.timeout);
+
+        Ok(match config.max_retry_count {
+            Some(count) => artifact.retry_up_to(count),
+            None => artifact,
+        })
+    }
+
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<KnxTarget>,
+    ) -> ConnectionTargets<KnxRequest, ()> {
+        let mut inited = Vec::with_capacity(targets.len());
+        for KnxTarget(definition) in targets {
+            match KnxRequest::parse(&definition) {
+                Ok(request) => inited.push(InitedTarget {
+                    name: definition.name,
+                    request,
+                    result: (),
+                    default_status: definition.default_status,
+                    auto_refresh: definition.auto_refresh,
+                    refresh_interval: None,
+                    keep_raw_frames: None,
+                    group: None,
+                    safe_state: None,
+                    array: None,
+                    change: None,
+                    statistics: Some(connection_statistics.insert_target(definition.device)),
+                }),
+                Err(error) => self.rejected.push(error),
+            }
+        }
+        ConnectionTargets(inited)
+    }
+
+    async fn request_process(
+        &mut self,
+        request: KnxRequest,
+    ) -> Result<(KnxResponse, bool), ConnectionError> {
+        let stale_after = self.config.stale_after;
+        let tunnel = self.tunnel()?;
+        let telegram = match tunnel.latest(request.group) {
+            Some(telegram)
+                if stale_after
+                    .is_none_or(|stale_after| telegram.received.elapsed() <= stale_after) =>
+            {
+                telegram
+            }
+            _ => tunnel.read(request.group).await?,
+        };
+        Ok((
+            KnxResponse {
+                group: request.group,
+                dpt: request.dpt,
+                source: telegram.source,
+                data: telegram.data,
+            },
+            true,
+        ))
+    }
+
+    fn write_preprocess(
+        &self,
+        request: KnxRequest,
+        value: Value,
+        _: &RequestContext,
+    ) -> Result<Box<dyn DeviceStateWrite>, ConnectionError> {
+        let data = request.dpt.encode(&value).ok_or_else(|| {
+            ConnectionError::InvalidConfig(format!("無效的設定值（DPT {}）：{value}", request.dpt))
+        })?;
+        Ok(Box::new(KnxWrite {
+            group: request.group,
+            data,
+            short: request.dpt.is_short(),
+        }))
+    }
+
+    async fn write_process(
+        &mut self,
+        write: Box<dyn DeviceStateWrite>,
+    ) -> Result<Option<KnxResponse>, ConnectionError> {
+        let write = write
+            .downcast::<KnxWrite>()
+            .map_err(|_| ConnectionError::Protocol("非預期的寫入請求".to_owned()))?;
+        let tunnel = self.tunnel()?;
+        tunnel.write(write.group, &write.data, write.short).await?;
+        Ok(None)
+    }
+
+    async fn disconnect(&mut self) -> Result<(), ConnectionError> {
+        self.close().await;
+        Ok(())
+    }
+
+    async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+        self.close().await;
+        let (tunnel, peer) = Self::open(&self.config).await?;
+        self.tunnel = Some(tunnel);
+        self.remote_address.set(Some(peer));
+        Ok(())
+    }
+
+    async fn update_config(&mut self, new_config: &KnxConfig) -> Result<(), ConnectionError> {
+        self.config = new_config.clone();
+        self.reconnect().await
+    }
+}
+
+/// 以記錄下來的 `TUNNELING_REQUEST` 封包解碼點位
+///
+/// `frame` 為包含群組電報（`GroupValue_Write` 或 `GroupValue_Response`）的 KNXnet/IP 封包，`target` 為點位的 `dpt` ，
+/// 群組位址取自封包
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::{fixture::Fixture, knx::KnxConnection};
+///
+/// let fixture = Fixture::from_json(r#"{
+///     "connection": "Knx",
+///     "cases": [
+///         { "name": "溫度", "target": "9.001", "frame": "0610 0420 0017 04 01 00 00 2900 bce0 1105 190a 03 0080 0c65", "expected": 22.5 },
+///         { "name": "開關", "target": "1.001", "frame": "0610 0420 0015 04 01 01 00 2900 bce0 1105 0801 01 0081", "expected": true },
+///         { "name": "資料長度不符", "target": "9.001", "frame": "0610 0420 0015 04 01 01 00 2900 bce0 1105 0801 01 0081" },
+///         { "name": "GroupValue_Read", "target": "9.001", "frame": "0610 0420 0015 04 01 02 00 2900 bce0 1105 190a 01 0000" }
+///     ]
+/// }"#).unwrap();
+///
+/// let report = fixture.run::<KnxConnection>();
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+impl FrameDecoder for KnxConnection {
+    fn decode_frame(target: &str, frame: &[u8]) -> Result<KnxResponse, Box<dyn Error>> {
+        let dpt = Dpt::parse(target).ok_or_else(|| format!("不支援的 DPT「{target}」"))?;
+        let cemi = match parse_frame(frame) {
+            Some((TUNNELING_REQUEST, [0x04, _, _, _, cemi @ ..])) => Cemi::parse(cemi),
+            _ => None,
+        }
+        .filter(|cemi| {
+            cemi.code != L_DATA_REQ && matches!(cemi.apci, GROUP_VALUE_RESPONSE | GROUP_VALUE_WRITE)
+        })
+        .ok_or_else(malformed)?;
+        Ok(KnxResponse {
+            group: cemi.group,
+            dpt,
+            source: cemi.source,
+            data: cemi.data,
+        })
+    }
+}
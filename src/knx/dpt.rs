@@ -0,0 +1,356 @@
+//! KNX 資料點類型（DPT）的編碼與解碼
+
+use std::fmt::Display;
+
+use serde_json::{Value, json};
+
+use crate::value::ConversionError;
+
+/// 資料點類型（DPT）
+///
+/// 支援的主類型：
+///
+/// | 主類型 | 數值 |
+/// | --- | --- |
+/// | 1 | 布林 |
+/// | 2 | `{ "control": bool, "value": bool }` |
+/// | 3 | 調光、百葉 `{ "increase": bool, "step": 0-7 }` ，`step` 為 0 時停止 |
+/// | 5 | 無號 8 位元，5.001 換算為 0 至 100 的百分比，5.003 換算為 0 至 360 的角度 |
+/// | 6 、7 、8 | 有號 8 位元、無號 16 位元、有號 16 位元 |
+/// | 9 | 16 位元浮點數，設備回覆無效數值（`0x7FFF`）時為 `null` |
+/// | 10 | 時間 `HH:MM:SS` ，寫入時星期為 0（未指定） |
+/// | 11 | 日期 `YYYY-MM-DD` ，年份為 1990 至 2089 |
+/// | 12 、13 、14 | 無號 32 位元、有號 32 位元、32 位元浮點數 |
+/// | 16 | 最多 14 個字元的字串（ISO 8859-1） |
+/// | 17 | 場景編號 0 至 63 |
+/// | 18 | 場景控制 `{ "learn": bool, "scene": 0-63 }` |
+/// | 20 | 列舉，無號 8 位元 |
+/// | 232 | RGB 顏色 `[r, g, b]` |
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::knx::Dpt;
+/// use serde_json::json;
+///
+/// let temperature = Dpt::parse("9.001").unwrap();
+/// assert_eq!(temperature.decode(&[0x0C, 0x65]).unwrap(), json!(22.5));
+/// assert_eq!(temperature.encode(&json!(22.5)), Some(vec![0x0C, 0x65]));
+///
+/// let percent = Dpt::parse("DPST-5-1").unwrap();
+/// assert_eq!(percent.to_string(), "5.001");
+/// assert_eq!(percent.decode(&[0xFF]).unwrap(), json!(100));
+///
+/// let switch = Dpt::parse("1.001").unwrap();
+/// assert!(switch.is_short());
+/// assert_eq!(switch.encode(&json!(true)), Some(vec![0x01]));
+/// assert_eq!(Dpt::parse("10.001").unwrap().decode(&[0x8E, 0x1E, 0x00]).unwrap(), json!("14:30:00"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Dpt {
+    /// 主類型
+    pub main: u16,
+    /// 子類型
+    pub sub: u16,
+}
+
+impl Dpt {
+    /// 解析 `9.001` 、`9` 、`DPT9.001` 或 ETS 格式的 `DPST-9-1` 、`DPT-9` ，不支援的主類型回傳 [`None`]
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (main, sub) = text
+            .strip_prefix("DPST-")
+            .or_else(|| text.strip_prefix("DPT-"))
+            .map_or_else(
+                || {
+                    let rest = text.strip_prefix("DPT").unwrap_or(text);
+                    rest.split_once('.').unwrap_or((rest, "0"))
+                },
+                |rest| rest.split_once('-').unwrap_or((rest, "0")),
+            );
+        let dpt = Self {
+            main: main.parse().ok()?,
+            sub: sub.parse().ok()?,
+        };
+        dpt.size().map(|_| dpt)
+    }
+
+    /// 資料長度（位元組），6 位元以內的類型為 0 ，不支援的類型為 [`None`]
+    #[must_use]
+    pub const fn size(self) -> Option<usize> {
+        Some(match self.main {
+            1..=3 => 0,
+            5 | 6 | 17 | 18 | 20 => 1,
+            7..=9 => 2,
+            10 | 11 | 232 => 3,
+            12..=14 => 4,
+            16 => 14,
+            _ => return None,
+        })
+    }
+
+    /// 是否為 6 位元以內的類型，數值直接放在 APCI 的低 6 位元中
+    #[must_use]
+    pub fn is_short(self) -> bool {
+        self.size() == Some(0)
+    }
+
+    /// 解碼
+    ///
+    /// # 參數
+    /// - `data`：電報的資料，6 位元以內的類型為 1 個位元組（只使用低 6 位元）
+    ///
+    /// # Errors
+    /// 資料長度不符或內容無效時回傳 [`ConversionError::InvalidRaw`]
+    ///
+    /// # 範例
+    /// 以內建的測試向量驗證：
+    /// ```rust
+    /// use device_state_exchange_lib::{knx::Dpt, vectors::{self, encode_hex}};
+    ///
+    /// let report = vectors::built_in_set("knx/dpt").unwrap().verify(|frame, value| {
+    ///     let dpt = value["dpt"].as_str().and_then(Dpt::parse).ok_or("不支援的 DPT")?;
+    ///     let decoded = dpt.decode(frame).map_err(|error| error.to_string())?;
+    ///     if decoded != value["value"] {
+    ///         return Err(format!("解碼結果為 {decoded}"));
+    ///     }
+    ///     match dpt.encode(&decoded) {
+    ///         Some(encoded) if encoded == frame => Ok(()),
+    ///         encoded => Err(format!("重新編碼為 {:?}", encoded.as_deref().map(encode_hex))),
+    ///     }
+    /// });
+    /// assert!(report.is_complete(), "{report:?}");
+    /// ```
+    pub fn decode(self, data: &[u8]) -> Result<Value, ConversionError> {
+        let invalid =
+            || ConversionError::InvalidRaw(format!("無法以 DPT {self} 解碼 {}", hex(data)));
+        let size = self.size().ok_or_else(invalid)?;
+        let bytes = if size == 0 {
+            data.get(..1).ok_or_else(invalid)?
+        } else {
+            data.get(..size)
+                .filter(|_| data.len() == size)
+                .ok_or_else(invalid)?
+        };
+        let byte = bytes[0];
+        let word = || u16::from_be_bytes([bytes[0], bytes[1]]);
+        let double = || [bytes[0], bytes[1], bytes[2], bytes[3]];
+
+        Ok(match (self.main, self.sub) {
+            (1, _) => json!(byte & 0x01 == 1),
+            (2, _) => json!({ "control": byte & 0x02 != 0, "value": byte & 0x01 != 0 }),
+            (3, _) => json!({ "increase": byte & 0x08 != 0, "step": byte & 0x07 }),
+            (5, 1) => json!((u32::from(byte) * 100 + 127) / 255),
+            (5, 3) => json!((u32::from(byte) * 360 + 127) / 255),
+            (5 | 20, _) => json!(byte),
+            (6, _) => json!(i8::from_be_bytes([byte])),
+            (7, _) => json!(word()),
+            (8, _) => json!(i16::from_be_bytes([bytes[0], bytes[1]])),
+            (9, _) => decode_float16(word()).map_or(Value::Null, Value::from),
+            (10, _) => {
+                let (hour, minute, second) = (byte & 0x1F, bytes[1] & 0x3F, bytes[2] & 0x3F);
+                if hour > 23 || minute > 59 || second > 59 {
+                    return Err(invalid());
+                }
+                json!(format!("{hour:02}:{minute:02}:{second:02}"))
+            }
+            (11, _) => {
+                let (day, month, year) = (byte & 0x1F, bytes[1] & 0x0F, bytes[2] & 0x7F);
+                if !(1..=31).contains(&day) || !(1..=12).contains(&month) || year > 99 {
+                    return Err(invalid());
+                }
+                let year = if year < 90 { 2000 } else { 1900 } + u16::from(year);
+                json!(format!("{year:04}-{month:02}-{day:02}"))
+            }
+            (12, _) => json!(u32::from_be_bytes(double())),
+            (13, _) => json!(i32::from_be_bytes(double())),
+            (14, _) => {
+                let value = f32::from_be_bytes(double());
+                if !value.is_finite() {
+                    return Err(ConversionError::NonFinite(f64::from(value)));
+                }
+                json!(value)
+            }
+            (16, _) => {
+                let end = bytes
+                    .iter()
+                    .position(|byte| *byte == 0)
+                    .unwrap_or(bytes.len());
+                json!(
+                    bytes[..end]
+                        .iter()
+                        .copied()
+                        .map(char::from)
+                        .collect::<String>()
+                )
+            }
+            (17, _) => json!(byte & 0x3F),
+            (18, _) => json!({ "learn": byte & 0x80 != 0, "scene": byte & 0x3F }),
+            (232, _) => json!(bytes),
+            _ => return Err(invalid()),
+        })
+    }
+
+    /// 編碼，數值的格式與 [`Self::decode()`] 的結果相同，超出範圍或格式不符時回傳 [`None`]
+    #[must_use]
+    pub fn encode(self, value: &Value) -> Option<Vec<u8>> {
+        let unsigned = |max: u64| value.as_u64().filter(|value| *value <= max);
+        let signed =
+            |min: i64, max: i64| value.as_i64().filter(|value| (min..=max).contains(value));
+        let field = |name: &str| value.get(name);
+
+        Some(match (self.main, self.sub) {
+            (1, _) => vec![u8::from(value.as_bool()?)],
+            (2, _) => vec![
+                (u8::from(field("control")?.as_bool()?) << 1)
+                    | u8::from(field("value")?.as_bool()?),
+            ],
+            (3, _) => {
+                let step = u8::try_from(field("step")?.as_u64().filter(|step| *step <= 7)?).ok()?;
+                vec![(u8::from(field("increase")?.as_bool()?) << 3) | step]
+            }
+            (5, 1) => vec![scale_to_byte(value.as_f64()?, 100.0)?],
+            (5, 3) => vec![scale_to_byte(value.as_f64()?, 360.0)?],
+            (5 | 20, _) => vec![u8::try_from(unsigned(0xFF)?).ok()?],
+            (6, _) => i8::try_from(signed(-128, 127)?)
+                .ok()?
+                .to_be_bytes()
+                .to_vec(),
+            (7, _) => u16::try_from(unsigned(0xFFFF)?)
+                .ok()?
+                .to_be_bytes()
+                .to_vec(),
+            (8, _) => i16::try_from(signed(-32_768, 32_767)?)
+                .ok()?
+                .to_be_bytes()
+                .to_vec(),
+            (9, _) => encode_float16(value.as_f64()?)?.to_be_bytes().to_vec(),
+            (10, _) => encode_time(value.as_str()?)?,
+            (11, _) => encode_date(value.as_str()?)?,
+            (12, _) => u32::try_from(unsigned(u64::from(u32::MAX))?)
+                .ok()?
+                .to_be_bytes()
+                .to_vec(),
+            (13, _) => i32::try_from(signed(i64::from(i32::MIN), i64::from(i32::MAX))?)
+                .ok()?
+                .to_be_bytes()
+                .to_vec(),
+            #[expect(clippy::cast_possible_truncation)]
+            (14, _) => {
+                let value = value
+                    .as_f64()
+                    .filter(|value| value.abs() <= f64::from(f32::MAX))?;
+                (value as f32).to_be_bytes().to_vec()
+            }
+            (16, _) => {
+                let mut bytes = value
+                    .as_str()?
+                    .chars()
+                    .map(|character| u8::try_from(u32::from(character)).ok())
+                    .collect::<Option<Vec<u8>>>()?;
+                if bytes.len() > 14 {
+                    return None;
+                }
+                bytes.resize(14, 0);
+                bytes
+            }
+            (17, _) => vec![u8::try_from(unsigned(63)?).ok()?],
+            (18, _) => {
+                let scene =
+                    u8::try_from(field("scene")?.as_u64().filter(|scene| *scene <= 63)?).ok()?;
+                vec![(u8::from(field("learn")?.as_bool()?) << 7) | scene]
+            }
+            (232, _) => match value.as_array()?.as_slice() {
+                [red, green, blue] => [red, green, blue]
+                    .into_iter()
+                    .map(|part| u8::try_from(part.as_u64()?).ok())
+                    .collect::<Option<_>>()?,
+                _ => return None,
+            },
+            _ => return None,
+        })
+    }
+}
+
+impl Display for Dpt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{:03}", self.main, self.sub)
+    }
+}
+
+/// 編碼 DPT 10 的時間 `HH:MM` 或 `HH:MM:SS` ，星期為 0（未指定）
+fn encode_time(text: &str) -> Option<Vec<u8>> {
+    let mut parts = text.split(':').map(|part| part.parse::<u8>().ok());
+    let hour = parts.next()??;
+    let minute = parts.next()??;
+    let second = parts.next().unwrap_or(Some(0))?;
+    if parts.next().is_some() || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    Some(vec![hour, minute, second])
+}
+
+/// 編碼 DPT 11 的日期 `YYYY-MM-DD`
+fn encode_date(text: &str) -> Option<Vec<u8>> {
+    let mut parts = text.split('-').map(|part| part.parse::<u16>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some()
+        || !(1990..=2089).contains(&year)
+        || !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+    {
+        return None;
+    }
+    [day, month, year % 100]
+        .into_iter()
+        .map(|part| u8::try_from(part).ok())
+        .collect()
+}
+
+/// 將 0 至 `max` 的數值換算為 0 至 255
+#[expect(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn scale_to_byte(value: f64, max: f64) -> Option<u8> {
+    (0.0..=max)
+        .contains(&value)
+        .then(|| (value * 255.0 / max).round() as u8)
+}
+
+/// 解碼 DPT 9 的 16 位元浮點數：`0.01 × M × 2^E` ，M 為 12 位元的二補數，無效數值回傳 [`None`]
+fn decode_float16(raw: u16) -> Option<f64> {
+    if raw == 0x7FFF {
+        return None;
+    }
+    let mut mantissa = i32::from(raw & 0x07FF);
+    if raw & 0x8000 != 0 {
+        mantissa -= 0x0800;
+    }
+    let exponent = (raw >> 11) & 0x0F;
+    Some(f64::from(mantissa << exponent) / 100.0)
+}
+
+/// 編碼 DPT 9 的 16 位元浮點數，選擇能容納數值的最小指數
+#[expect(clippy::cast_possible_truncation)]
+fn encode_float16(value: f64) -> Option<u16> {
+    if !value.is_finite() {
+        return None;
+    }
+    let scaled = value * 100.0;
+    (0..16_u16).find_map(|exponent| {
+        let mantissa = (scaled / f64::from(1_u32 << exponent)).round();
+        if !(-2048.0..=2047.0).contains(&mantissa) {
+            return None;
+        }
+        let mantissa = mantissa as i32;
+        let sign = u16::from(mantissa < 0) << 15;
+        let bits = u16::try_from(mantissa & 0x07FF).ok()?;
+        Some(sign | (exponent << 11) | bits)
+    })
+}
+
+pub(super) fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
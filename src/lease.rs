@@ -0,0 +1,345 @@
+//! 獨佔存取租約
+//!
+//! 校正、試運轉等作業期間，技術人員的工具需要獨佔設備，主程式的自動更新與其他外部界面的寫入都會干擾作業，
+//! 工具可以透過 [`LeaseManager::acquire_exclusive()`] 取得連線的租約：
+//!
+//! - 租約有效期間，主程式排程前應檢查 [`LeaseManager::is_suspended()`] ，暫停該連線的自動更新
+//! - 外部界面的寫入指令先經過 [`LeaseManager::admit_write()`] ，非租約持有者的寫入依 [`ContendedWritePolicy`] 排隊或拒絕
+//! - 租約到期或釋放後自動恢復，排隊中的寫入指令由 [`LeaseManager::release()`] 或 [`LeaseManager::poll_expired()`] 交還主程式
+//!
+//! # 範例
+//! ```rust
+//! use std::time::Duration;
+//! use device_state_exchange_lib::{WriteCommand, lease::{LeaseManager, WriteAdmission}};
+//! use serde_json::json;
+//!
+//! let leases = LeaseManager::new();
+//! let lease = leases.acquire_exclusive("boiler", "calibration-tool", Duration::from_secs(15 * 60)).unwrap();
+//! assert!(leases.is_suspended("boiler"));
+//! assert!(leases.acquire_exclusive("boiler", "another-tool", Duration::from_secs(60)).is_err());
+//!
+//! let setpoint = WriteCommand { target: "setpoint".to_owned(), value: json!(80), client: None, correlation: None };
+//! assert_eq!(leases.admit_write("boiler", Some("calibration-tool"), setpoint.clone()), WriteAdmission::Allowed(setpoint.clone()));
+//! assert_eq!(leases.admit_write("boiler", Some("dashboard"), setpoint), WriteAdmission::Queued);
+//!
+//! // 作業完成，排隊中的寫入指令交還主程式加入 `CommandQueue`
+//! let queued = leases.release(&lease);
+//! assert_eq!(queued.len(), 1);
+//! assert!(!leases.is_suspended("boiler"));
+//! ```
+
+use std::{
+    error::Error,
+    fmt::Display,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{HashMap, WriteCommand};
+
+/// 租約期間，非租約持有者的寫入指令處理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContendedWritePolicy {
+    /// 排隊至租約結束後再交還主程式
+    #[default]
+    Queue,
+    /// 直接拒絕
+    Reject,
+}
+
+/// 寫入指令的處理結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteAdmission {
+    /// 沒有租約，或寫入者為租約持有者，請直接加入 [`crate::CommandQueue`]
+    Allowed(WriteCommand),
+    /// 已排隊至租約結束
+    Queued,
+    /// 已拒絕
+    Rejected {
+        /// 租約持有者
+        holder: String,
+    },
+}
+
+/// 租約
+///
+/// 由 [`LeaseManager::acquire_exclusive()`] 取得，用於續約與釋放
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lease {
+    id: u64,
+    /// 連線識別名稱
+    pub connection: String,
+    /// 租約持有者
+    pub holder: String,
+    /// 到期時間
+    pub expires_at: Instant,
+}
+
+/// 租約錯誤
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LeaseError {
+    /// 連線的租約正被其他持有者使用
+    Held {
+        /// 連線識別名稱
+        connection: String,
+        /// 租約持有者
+        holder: String,
+        /// 剩餘時間
+        remaining: Duration,
+    },
+    /// 租約已到期或已釋放
+    Expired {
+        /// 連線識別名稱
+        connection: String,
+    },
+}
+
+impl Display for LeaseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Held {
+                connection,
+                holder,
+                remaining,
+            } => write!(
+                f,
+                "連線 {connection} 正被 {holder} 獨佔，剩餘 {} 秒",
+                remaining.as_secs()
+            ),
+            Self::Expired { connection } => write!(f, "連線 {connection} 的租約已到期或已釋放"),
+        }
+    }
+}
+
+impl Error for LeaseError {}
+
+/// 獨佔存取租約管理
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份租約
+#[derive(Debug, Clone, Default)]
+pub struct LeaseManager {
+    leases: Arc<Mutex<HashMap<String, ActiveLease>>>,
+    next_id: Arc<AtomicU64>,
+    policy: ContendedWritePolicy,
+}
+
+#[derive(Debug)]
+struct ActiveLease {
+    id: u64,
+    holder: String,
+    expires_at: Instant,
+    queued: Vec<WriteCommand>,
+}
+
+impl ActiveLease {
+    fn is_expired(&self, now: Instant) -> bool {
+        now >= self.expires_at
+    }
+}
+
+impl LeaseManager {
+    /// 建立租約管理，非租約持有者的寫入指令預設為 [`ContendedWritePolicy::Queue`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 設定非租約持有者的寫入指令處理方式
+    #[must_use]
+    pub const fn with_write_policy(mut self, policy: ContendedWritePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// 取得連線的獨佔存取租約
+    ///
+    /// 同一個持有者重複取得時，視為續約
+    ///
+    /// # 參數
+    /// - `connection_id`：連線識別名稱
+    /// - `holder`：租約持有者，如工具名稱或使用者帳號
+    /// - `ttl`：租約有效時間
+    ///
+    /// # Errors
+    /// 租約正被其他持有者使用時回傳 [`LeaseError::Held`]
+    pub fn acquire_exclusive(
+        &self,
+        connection_id: &str,
+        holder: &str,
+        ttl: Duration,
+    ) -> Result<Lease, LeaseError> {
+        let now = Instant::now();
+        let mut leases = self.leases.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(active) = leases
+            .get_mut(connection_id)
+            .filter(|active| !active.is_expired(now))
+        {
+            if active.holder != holder {
+                return Err(LeaseError::Held {
+                    connection: connection_id.to_owned(),
+                    holder: active.holder.clone(),
+                    remaining: active.expires_at - now,
+                });
+            }
+
+            active.expires_at = now + ttl;
+            return Ok(Lease {
+                id: active.id,
+                connection: connection_id.to_owned(),
+                holder: holder.to_owned(),
+                expires_at: active.expires_at,
+            });
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let queued = leases
+            .remove(connection_id)
+            .map(|expired| expired.queued)
+            .unwrap_or_default();
+        leases.insert(
+            connection_id.to_owned(),
+            ActiveLease {
+                id,
+                holder: holder.to_owned(),
+                expires_at: now + ttl,
+                queued,
+            },
+        );
+        drop(leases);
+
+        Ok(Lease {
+            id,
+            connection: connection_id.to_owned(),
+            holder: holder.to_owned(),
+            expires_at: now + ttl,
+        })
+    }
+
+    /// 續約
+    ///
+    /// # 參數
+    /// - `lease`：租約
+    /// - `ttl`：自現在起的有效時間
+    ///
+    /// # Errors
+    /// 租約已到期或已釋放時回傳 [`LeaseError::Expired`]
+    pub fn renew(&self, lease: &Lease, ttl: Duration) -> Result<Lease, LeaseError> {
+        let now = Instant::now();
+
+        self.leases
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(&lease.connection)
+            .filter(|active| active.id == lease.id && !active.is_expired(now))
+            .map(|active| {
+                active.expires_at = now + ttl;
+                Lease {
+                    expires_at: active.expires_at,
+                    ..lease.clone()
+                }
+            })
+            .ok_or_else(|| LeaseError::Expired {
+                connection: lease.connection.clone(),
+            })
+    }
+
+    /// 釋放租約
+    ///
+    /// # 回傳值
+    /// 租約期間排隊的寫入指令，請交由主程式加入 [`crate::CommandQueue`] ，租約已被其他持有者取得時為空陣列
+    #[must_use]
+    pub fn release(&self, lease: &Lease) -> Vec<WriteCommand> {
+        let mut leases = self.leases.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if leases
+            .get(&lease.connection)
+            .is_some_and(|active| active.id == lease.id)
+        {
+            leases
+                .remove(&lease.connection)
+                .map(|active| active.queued)
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// 移除所有已到期的租約
+    ///
+    /// 主程式應定期調用（如每次排程時），讓到期的租約交還排隊中的寫入指令
+    ///
+    /// # 回傳值
+    /// 到期租約排隊的寫入指令，請交由主程式加入 [`crate::CommandQueue`]
+    #[must_use]
+    pub fn poll_expired(&self) -> Vec<WriteCommand> {
+        let now = Instant::now();
+
+        self.leases
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extract_if(|_, active| active.is_expired(now))
+            .flat_map(|(_, active)| active.queued)
+            .collect()
+    }
+
+    /// 連線的自動更新是否應暫停
+    #[must_use]
+    pub fn is_suspended(&self, connection_id: &str) -> bool {
+        self.holder(connection_id).is_some()
+    }
+
+    /// 目前的租約持有者，沒有有效的租約時為 [`None`]
+    #[must_use]
+    pub fn holder(&self, connection_id: &str) -> Option<String> {
+        let now = Instant::now();
+
+        self.leases
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(connection_id)
+            .filter(|active| !active.is_expired(now))
+            .map(|active| active.holder.clone())
+    }
+
+    /// 檢查寫入指令
+    ///
+    /// # 參數
+    /// - `connection_id`：連線識別名稱
+    /// - `writer`：寫入者，與 [`LeaseManager::acquire_exclusive()`] 的 `holder` 相同時視為租約持有者，無法識別時請傳入 [`None`]
+    /// - `command`：寫入指令
+    #[must_use]
+    pub fn admit_write(
+        &self,
+        connection_id: &str,
+        writer: Option<&str>,
+        command: WriteCommand,
+    ) -> WriteAdmission {
+        let now = Instant::now();
+
+        match self
+            .leases
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(connection_id)
+            .filter(|active| !active.is_expired(now) && writer != Some(active.holder.as_str()))
+        {
+            None => WriteAdmission::Allowed(command),
+            Some(active) => match self.policy {
+                ContendedWritePolicy::Queue => {
+                    active.queued.push(command);
+                    WriteAdmission::Queued
+                }
+                ContendedWritePolicy::Reject => WriteAdmission::Rejected {
+                    holder: active.holder.clone(),
+                },
+            },
+        }
+    }
+}
@@ -1,13 +1,135 @@
 use std::{
+    borrow::Cow,
+    collections::{BTreeMap, VecDeque},
     fmt::Debug,
-    sync::{Arc, atomic::AtomicI64},
+    net::SocketAddr,
+    sync::{Arc, Mutex, PoisonError, RwLock},
+    time::Duration,
 };
 
 use downcast_rs::{DowncastSync, impl_downcast};
 use dyn_clone::{DynClone, clone_trait_object};
-use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+/// 供 derive macro 產生的程式碼使用，不屬於公開 API
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod __private {
+    pub use bytes;
+    pub use serde_json;
+}
+
+#[cfg(feature = "hashbrown")]
+pub(crate) use hashbrown::{HashMap, HashSet};
+#[cfg(not(feature = "hashbrown"))]
+pub(crate) use std::collections::{HashMap, HashSet};
+
+pub mod auth;
+#[cfg(feature = "bacnet")]
+pub mod bacnet;
+pub mod blocking;
+pub mod bucket;
+pub mod budget;
+pub mod cache;
+#[cfg(feature = "canbus")]
+pub mod canbus;
+pub mod capability;
+pub mod catch_up;
+pub mod clock;
+pub mod codec;
+pub mod command;
+pub mod composite;
+pub mod compression;
+pub mod concurrency;
+pub mod context;
+pub mod cov;
+pub mod definition;
+pub mod delivery;
+pub mod delta;
+pub mod diagnostics;
+pub mod discovery;
+#[cfg(feature = "dnp3")]
+pub mod dnp3;
+pub mod driver_state;
+pub mod encoding;
+pub mod envelope;
+pub mod error;
+#[cfg(feature = "ethernet-ip")]
+pub mod ethernet_ip;
+pub mod event;
+pub mod event_log;
+pub mod execution;
+pub mod fixture;
+pub mod format;
+pub mod group;
+pub mod histogram;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "knx")]
+pub mod knx;
+pub mod lease;
+pub mod lifecycle;
+pub mod loadgen;
+#[cfg(feature = "mbus")]
+pub mod mbus;
+pub mod memory;
+pub mod migration;
+#[cfg(any(feature = "modbus-rtu", feature = "modbus-tcp"))]
+pub mod modbus;
+#[cfg(feature = "modbus-rtu")]
+pub mod modbus_rtu;
+#[cfg(feature = "modbus-tcp")]
+pub mod modbus_tcp;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+pub mod multi;
+#[cfg(feature = "opcua")]
+pub mod opcua;
+pub mod persistence;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+#[cfg(feature = "proto")]
+pub mod proto;
+pub mod queue;
+pub mod registry;
+pub mod reload;
+#[cfg(feature = "axum")]
+pub mod rest;
+pub mod retry;
+#[cfg(feature = "s7")]
+pub mod s7;
+pub mod safe_state;
+pub mod sampling;
+pub mod scheduler;
+pub mod serial;
+pub mod session;
+pub mod settle;
+pub mod skip;
+#[cfg(feature = "snmp")]
+pub mod snmp;
+pub mod state;
+pub mod template;
+pub mod tenant;
+pub mod time_sync;
+pub mod timing;
+pub mod transport;
+pub mod twin;
+pub mod validation;
+pub mod value;
+pub mod vectors;
+pub mod windowed;
+
+pub use command::{CommandQueue, WriteCommand};
+pub use context::{ClientId, CorrelationId, RequestContext};
+pub use definition::TargetDefinition;
+#[cfg(feature = "derive")]
+pub use device_state_exchange_derive::{DeviceStateRequest, DeviceStateResponse, Target};
+pub use error::ConnectionError;
+pub use event::{Event, EventBus, EventKind};
+pub use state::{StateStore, TargetState};
+pub use tenant::{Tenant, TenantId, Tenants};
+
 /// 硬體設備連線設定
 ///
 /// 實作本 trait 的 struct/enum 代表其定義了主程式連線至硬體時所需要的各項資訊
@@ -31,6 +153,7 @@ use serde_json::Value;
 ///
 /// 定義 Modbus RTU 連線時，需要讓用戶指定調變速率（又稱鮑率 baud rate）、數據位（data bits）、同位（parity） 和停止位（stop bits），這時可以建立一個 struct 包含以上資訊，並實作本 trait ：
 /// ```rust
+/// # use device_state_exchange_lib::ConnectionConfig;
 /// #[derive(Debug)]
 /// struct ExampleModbusConnectionConfig {
 ///     baud_rate: u32,
@@ -62,8 +185,9 @@ pub trait ConnectionConfig: Debug + Send + Sync + 'static {}
 /// # 範例
 /// Modbus RTU 點位被儲存於 JSON 格式的資料中，利用 [`serde_json::Value`] 型別儲存，供後續處理使用，這時可以建立一個 struct 包含以上資訊，並實作本 trait ：
 /// ```rust
-/// #[derive(Debug)]
-/// struct ExampleModbusTarget(serde_json::Value)
+/// # use device_state_exchange_lib::Target;
+/// #[derive(Debug, Clone)]
+/// struct ExampleModbusTarget(serde_json::Value);
 ///
 /// impl Target for ExampleModbusTarget {}
 /// ```
@@ -92,7 +216,10 @@ clone_trait_object!(Target);
 /// # 範例
 /// Modbus RTU 存取某個 Register 需要定義 Modbus ID 、指令碼、資料地址與資料長度，並在後處理時根據預先定義的資料類型，進行資料型別轉換，這時可以建立一個 struct 包含以上資訊，並實作本 trait ：
 /// ```rust
-/// #[derive(Debug)]
+/// # use device_state_exchange_lib::DeviceStateRequest;
+/// # #[derive(Debug, Clone)]
+/// # enum DataType { U16 }
+/// #[derive(Debug, Clone)]
 /// struct ExampleModbusRequest {
 ///     id: u8,
 ///     function_code: u8,
@@ -125,18 +252,35 @@ clone_trait_object!(DeviceStateRequest);
 /// - `'static` lifetime：標記引用需要在程式運行期間均有效
 /// - dyn-compatible：要求實作後依然保持可以利用[動態分派 (dynamic dispatch)](https://zh.wikipedia.org/zh-tw/动态分派)
 ///
+/// 回覆在主程式中會被多次複製（如寫入狀態儲存區、包裝為信封、保留原始封包），請將原始資料等較大的內容存放於 [`bytes::Bytes`] 或 [`Arc`] 中，
+/// 讓複製只需要增加參考計數，而不是重新配置記憶體
+///
 /// # 範例
-/// Modbus RTU 存取某個 Register 後會得到多個 Modbus Word (一個 Word 為兩個 byte，可以利用 [`u16`] 儲存) 作為回覆值，基於實用性考慮，預留一個欄位供後處理進行資料型別轉換後，結果的存放位置，這時可以建立一個 struct 包含以上資訊，並實作本 trait ：
+/// Modbus RTU 存取某個 Register 後會得到多個 Modbus Word (一個 Word 為兩個 byte) 作為回覆值，為避免每次輪詢都配置新的 `Vec<u16>` ，
+/// 直接保留由 [`transport::frame::FrameReader`] 切出的 [`bytes::Bytes`] ，需要時再以 [`u16::from_be_bytes()`] 讀取，
+/// 基於實用性考慮，預留一個欄位供後處理進行資料型別轉換後，結果的存放位置，這時可以建立一個 struct 包含以上資訊，並實作本 trait ：
 /// ```rust
-/// #[derive(Debug)]
+/// # use std::borrow::Cow;
+/// # use device_state_exchange_lib::{DeviceStateResponse, value::ConversionError};
+/// # use serde_json::Value;
+/// use bytes::Bytes;
+///
+/// #[derive(Debug, Clone)]
 /// struct ExampleModbusResponse {
-///     raw_words: Vec<u16>,
+///     raw_words: Bytes,
 ///     processed_value: Option<serde_json::Value>,
 /// }
 ///
 /// impl DeviceStateResponse for ExampleModbusResponse {
-///     fn to_value(&self) -> serde_json::Value {
-///         Value::from(self.processed_value)
+///     fn to_value(&self) -> Result<Cow<'_, Value>, ConversionError> {
+///         self.processed_value
+///             .as_ref()
+///             .map(Cow::Borrowed)
+///             .ok_or_else(|| ConversionError::InvalidRaw(format!("尚未轉換：{:02x?}", self.raw_words)))
+///     }
+///
+///     fn raw(&self) -> Option<Bytes> {
+///         Some(self.raw_words.clone())
 ///     }
 /// }
 /// ```
@@ -144,11 +288,97 @@ pub trait DeviceStateResponse: Debug + Send + Sync + DowncastSync + DynClone + '
     /// 轉換為 [`serde_json`](https://crates.io/crates/serde_json) 的 [`serde_json::Value`]
     ///
     /// 本 method 用於方便後續程式邏輯將回傳值透過網路進行傳輸。
-    fn to_value(&self) -> Value;
+    ///
+    /// 回覆中已經存有 [`Value`] 時，請回傳 [`Cow::Borrowed`] 以避免複製；轉換失敗（如 NaN 、無法解析的原始資料）時請回傳錯誤，
+    /// 主程式會將點位標記為 [`value::Quality::Bad`] ，而不是寫入錯誤或空白的數值，浮點數可利用 [`value::finite()`] 轉換
+    ///
+    /// # Errors
+    /// 無法轉換時回傳 [`value::ConversionError`]
+    fn to_value(&self) -> Result<Cow<'_, Value>, value::ConversionError>;
+
+    /// 轉換為 [`serde_json::Value`] ，失敗時回傳 [`Value::Null`]
+    ///
+    /// 提供給只需要數值、不需要處理轉換錯誤的舊程式使用，新程式請使用 [`DeviceStateResponse::to_value()`]
+    fn to_value_lossy(&self) -> Value {
+        self.to_value().map_or(Value::Null, Cow::into_owned)
+    }
+
+    /// 轉換為 [`value::DeviceData`]
+    ///
+    /// 預設實作會將 [`DeviceStateResponse::to_value()`] 的結果包裝為 [`value::DeviceData::Scalar`] ，
+    /// 回覆為二進位資料（如影像、波形）時，請覆寫本 method 回傳 [`value::DeviceData::Bytes`] 或 [`value::DeviceData::Array`] ，避免轉換為 [`Value`] 的額外負擔
+    ///
+    /// # Errors
+    /// 無法轉換時回傳 [`value::ConversionError`]
+    fn to_data(&self) -> Result<value::DeviceData, value::ConversionError> {
+        self.to_value()
+            .map(|value| value::DeviceData::Scalar(value.into_owned()))
+    }
+
+    /// 原始封包（非必需）
+    ///
+    /// 回覆由設備回傳的位元組轉換而來時，實作者可以在此回傳原始的位元組，
+    /// 點位設定了 [`InitedTarget::keep_raw_frames`] 時，主程式會將其保留於 [`diagnostics::RawFrameStore`] ，用於診斷轉換錯誤
+    ///
+    /// 回傳的 [`bytes::Bytes`] 應與回覆共用記憶體（如 [`transport::frame::FrameReader::read_frame()`] 的結果），保留時不會複製資料
+    fn raw(&self) -> Option<bytes::Bytes> {
+        None
+    }
+
+    /// 利用指定的編碼器序列化
+    ///
+    /// 將 [`DeviceStateResponse::to_value()`] 的結果交由編碼器轉換為位元組，參見 [`encoding`]
+    ///
+    /// # 參數
+    /// - `encoder`：編碼器，如 [`encoding::JsonEncoder`]
+    ///
+    /// # Errors
+    /// 回覆無法轉換或編碼失敗時回傳 [`encoding::EncodingError`]
+    fn serialize_with(
+        &self,
+        encoder: &dyn encoding::ResponseEncoder,
+    ) -> Result<Vec<u8>, encoding::EncodingError> {
+        encoder.encode(self.to_value()?.as_ref())
+    }
 }
 impl_downcast!(DeviceStateResponse);
 clone_trait_object!(DeviceStateResponse);
 
+/// 設備寫入請求
+///
+/// 實作本 trait 的 struct/enum 代表寫入設備（如設定值、線圈、致動器指令）時需要哪些資料
+///
+/// 實作本 trait 的 struct/enum 會在 [`Connection::write_preprocess()`] function 中由點位的 [`Connection::Request`] 與欲寫入的新狀態轉換而來，
+/// 在 [`Connection::write_process()`] 作為執行參數，並在 [`Connection::write_postprocess()`] 中作為後處理時可用的參數，
+/// 兩者收到的是 `Box<dyn DeviceStateWrite>` ，請利用 `downcast()` 或 `downcast_ref()` 取回原本的型別
+///
+/// # 實作要求
+///
+/// 實作本 trait 的 struct/enum 必須同時實作 [`Debug`], [`Send`] 和 [`Sync`] 三個 trait 、持有 `'static` lifetime 且維持 [dyn-compatible](https://doc.rust-lang.org/reference/items/traits.html#dyn-compatibility)
+///
+/// - [`Debug`]：可以輸出偵錯用資訊
+/// - [`Send`]：可以被傳送至其他線程（編譯器會自動判斷是否適用，不需要手動實作）
+/// - [`Sync`]：可以被分享給其他線程（編譯器會自動判斷是否適用，不需要手動實作）
+/// - `'static` lifetime：標記引用需要在程式運行期間均有效
+/// - dyn-compatible：要求實作後依然保持可以利用[動態分派 (dynamic dispatch)](https://zh.wikipedia.org/zh-tw/动态分派)
+///
+/// # 範例
+/// Modbus RTU 寫入單一暫存器需要定義 Modbus ID 、資料地址與欲寫入的數值，這時可以建立一個 struct 包含以上資訊，並實作本 trait ：
+/// ```rust
+/// # use device_state_exchange_lib::DeviceStateWrite;
+/// #[derive(Debug, Clone)]
+/// struct ExampleModbusWrite {
+///     id: u8,
+///     address: u16,
+///     value: u16,
+/// }
+///
+/// impl DeviceStateWrite for ExampleModbusWrite {}
+/// ```
+pub trait DeviceStateWrite: Debug + Send + Sync + DowncastSync + DynClone + 'static {}
+impl_downcast!(DeviceStateWrite);
+clone_trait_object!(DeviceStateWrite);
+
 /// 設備連線定義
 ///
 /// 實作本 trait 的 struct/enum 代表著一種硬體，主程式在編譯期會自動掃描有實作本 trait
@@ -183,7 +413,8 @@ pub trait Connection: Sized + Send + 'static {
     /// - 連線中的設備有「A」與「B」設備型態夾雜在一起 ✅
     /// - 連線中的設備有「A」、「B」與「C」設備型態夾雜在一起 ❌
     /// - 連線中的設備有「A」與「C」設備型態夾雜在一起 ❌
-    /// - 連線定義和其他連線定義衝突 ⚠️ 👉 沒有定義其行為，如果編譯期沒有噴錯，那運行期就會變成先搶先贏，所以請不要這麼做
+    /// - 連線定義和其他連線定義衝突 ⚠️ 👉 沒有定義其行為，如果編譯期沒有噴錯，那運行期就會變成先搶先贏，所以請不要這麼做；
+    ///   利用 [`registry::ConnectionRegistry`] 管理連線時，衝突的連線定義會在登記時回傳錯誤
     const NAMES: &[&str];
 
     /// 定義連線參數的型別
@@ -219,10 +450,8 @@ pub trait Connection: Sized + Send + 'static {
     /// - `config`：連線參數的引用（指派到 [`Self::Config`] 的型別）
     ///
     /// # 回傳值
-    /// 「連線產品」、「最大重試次數」（非必需）及「執行間隔」，可回傳錯誤
-    async fn init(
-        config: &Self::Config,
-    ) -> Result<ConnectionArtifact<Self>, Box<dyn std::error::Error>>;
+    /// 「連線產品」、「最大重試次數」（非必需）、「執行間隔」、「保持連線間隔」（非必需）、「閒置中斷時間」（非必需）、「統計數據設定」及「設備探索報告」（非必需），可回傳錯誤
+    async fn init(config: &Self::Config) -> Result<ConnectionArtifact<Self>, ConnectionError>;
 
     /// 初始化點位
     ///
@@ -239,6 +468,8 @@ pub trait Connection: Sized + Send + 'static {
     /// 兩個不同類型點位的陣列，不可回傳錯誤
     ///
     /// 如有無法正常處理的點位，請在顯示完錯誤訊息後跳過，沒有傳入的點位會直接被主程式忽略
+    ///
+    /// 建立點位統計數據前，建議利用 [`validation::detect_address_conflicts()`] 檢查是否有相同設備編號被設定為不同設備型態的點位
     fn init_targets(
         &mut self,
         connection_statistics: &mut ConnectionStats,
@@ -262,10 +493,33 @@ pub trait Connection: Sized + Send + 'static {
         &self,
         request: Self::Request,
         new_status: Option<Value>,
-    ) -> Result<Self::Request, Box<dyn std::error::Error>> {
+    ) -> Result<Self::Request, ConnectionError> {
         Ok(request)
     }
 
+    /// 依請求資訊預處理（非必需）
+    ///
+    /// 主程式處理外部服務的請求時會調用此 function 取代 [`Connection::preprocess()`] ，實作者可以依請求來源調整請求，或拒絕沒有權限的請求
+    ///
+    /// 預設實作會忽略請求資訊並調用 [`Connection::preprocess()`]
+    ///
+    /// # 參數
+    /// - `request`：傳入的請求
+    /// - `new_status`：將被更新的新狀態
+    /// - `context`：請求資訊，參見 [`WriteCommand::context()`]
+    ///
+    /// # 回傳值
+    /// 新的與 [`Self::Request`] 相同型別的請求，可回傳錯誤
+    #[expect(clippy::missing_errors_doc)]
+    fn preprocess_with_context(
+        &self,
+        request: Self::Request,
+        new_status: Option<Value>,
+        context: &RequestContext,
+    ) -> Result<Self::Request, ConnectionError> {
+        self.preprocess(request, new_status)
+    }
+
     /// 處理請求
     ///
     /// 主程式在準備好請求後，會在指定的間隔調用此 function ，實作者需要在這個 function 中定義如何與設備進行資料交換
@@ -274,7 +528,8 @@ pub trait Connection: Sized + Send + 'static {
     ///
     /// 此 function 是 async function ，其間隔時間與逾時設定和 [`ConnectionArtifact::update_interval`] 的設定值相同
     ///
-    /// 如遇到作業系統時鐘校時、硬體性能不足等其他不可抗力因素導致無法在間隔時間內處理到請求，主程式將會直接跳過指令不執行
+    /// 間隔以單調時鐘計算（參見 [`clock::ticker()`]），作業系統時鐘校時不會影響請求的執行；如遇到硬體性能不足等因素導致無法在間隔時間內處理完請求，
+    /// 下一次請求會在處理完成後重新計算間隔，不會被跳過
     ///
     /// # 參數
     /// - `request`：傳入的請求
@@ -285,11 +540,11 @@ pub trait Connection: Sized + Send + 'static {
     async fn request_process(
         &mut self,
         request: Self::Request,
-    ) -> Result<(Self::Response, bool), Box<dyn std::error::Error>>;
+    ) -> Result<(Self::Response, bool), ConnectionError>;
 
     /// 後處理（非必需）
     ///
-    /// 主程式會在接收到來自設備的狀態後，於儲存前調用此 function
+    /// 主程式會在接收到來自設備的狀態後，於儲存前調用此 function ，並將結果利用 [`envelope::EnvelopeSource`] 包裝後交給事件接收端
     ///
     /// 此 function 並不是 async function ，請不要在此處執行需要長時間等待的邏輯
     ///
@@ -305,17 +560,167 @@ pub trait Connection: Sized + Send + 'static {
         &self,
         request: Self::Request,
         response: Self::Response,
-    ) -> Result<Self::Response, Box<dyn std::error::Error>> {
+    ) -> Result<Self::Response, ConnectionError> {
+        Ok(response)
+    }
+
+    /// 寫入預處理（非必需）
+    ///
+    /// 主程式從 [`CommandQueue`] 取出寫入指令後，會調用此 function 將點位的請求與欲寫入的新狀態轉換為寫入請求，實作者可以在此處檢查數值範圍、
+    /// 換算工程單位，或依請求來源拒絕沒有權限的寫入，參見 [`command::execute()`]
+    ///
+    /// 預設實作會回傳 [`command::UnsupportedWrite`] ，不支援寫入的連線不需要實作
+    ///
+    /// 此 function 並不是 async function ，請不要在此處執行需要長時間等待的邏輯
+    ///
+    /// # 參數
+    /// - `request`：點位的請求
+    /// - `value`：欲寫入的新狀態
+    /// - `context`：請求資訊，參見 [`WriteCommand::context()`]
+    ///
+    /// # 回傳值
+    /// 寫入請求，可回傳錯誤
+    #[expect(clippy::missing_errors_doc)]
+    fn write_preprocess(
+        &self,
+        request: Self::Request,
+        value: Value,
+        context: &RequestContext,
+    ) -> Result<Box<dyn DeviceStateWrite>, ConnectionError> {
+        Err(ConnectionError::custom(command::UnsupportedWrite))
+    }
+
+    /// 處理寫入請求（非必需）
+    ///
+    /// 主程式在 [`Connection::write_preprocess()`] 成功後調用此 function ，實作者需要在這個 function 中定義如何將寫入請求送至設備，
+    /// 與 [`Connection::request_process()`] 共用同一個連線，主程式不會同時調用兩者
+    ///
+    /// 預設實作會回傳 [`command::UnsupportedWrite`]
+    ///
+    /// # 參數
+    /// - `write`：由 [`Connection::write_preprocess()`] 產生的寫入請求
+    ///
+    /// # 回傳值
+    /// 設備回讀的狀態（如寫入後回覆的暫存器內容），設備不回覆狀態時為 [`None`] ，可回傳錯誤
+    async fn write_process(
+        &mut self,
+        write: Box<dyn DeviceStateWrite>,
+    ) -> Result<Option<Self::Response>, ConnectionError> {
+        Err(ConnectionError::custom(command::UnsupportedWrite))
+    }
+
+    /// 寫入後處理（非必需）
+    ///
+    /// 主程式會在 [`Connection::write_process()`] 成功後，於儲存回讀的狀態前調用此 function
+    ///
+    /// 此 function 並不是 async function ，請不要在此處執行需要長時間等待的邏輯
+    ///
+    /// # 參數
+    /// - `write`：寫入請求
+    /// - `response`：設備回讀的狀態
+    ///
+    /// # 回傳值
+    /// 新的回讀狀態，可回傳錯誤
+    #[expect(clippy::missing_errors_doc)]
+    fn write_postprocess(
+        &self,
+        write: &dyn DeviceStateWrite,
+        response: Option<Self::Response>,
+    ) -> Result<Option<Self::Response>, ConnectionError> {
         Ok(response)
     }
 
+    /// 保持連線（非必需）
+    ///
+    /// 主程式會在連線閒置（沒有外部服務請求，也沒有需要自動更新的點位）超過 [`ConnectionArtifact::keepalive_interval`] 時調用此 function ，
+    /// 實作者可以在此處送出協定中不影響設備狀態的請求（如讀取單一暫存器、協定定義的心跳封包），避免閒置的連線被 NAT 或設備中斷
+    ///
+    /// 本 function 的逾時與 [`ConnectionArtifact::timeout`] 相同，回傳錯誤時視為一次失敗的請求，會累加至 [`ConnectionArtifact::max_retry_count`] 的失敗次數
+    ///
+    /// # 回傳值
+    /// 無，可回傳錯誤
+    async fn keepalive(&mut self) -> Result<(), ConnectionError> {
+        Ok(())
+    }
+
+    /// 執行診斷指令（非必需）
+    ///
+    /// 主程式會在接收到維運工具的診斷請求時調用此 function ，與 [`Connection::request_process()`] 共用同一個連線，主程式不會同時調用兩者
+    ///
+    /// 預設實作會回傳 [`diagnostics::UnsupportedDiagnostics`] ，實作者只需處理協定支援的指令，其餘指令請同樣回傳該錯誤
+    ///
+    /// # 參數
+    /// - `command`：診斷指令，參見 [`diagnostics::DiagnosticsCommand`]
+    ///
+    /// # 回傳值
+    /// 診斷結果，格式由實作者決定，可回傳錯誤
+    async fn diagnostics(
+        &mut self,
+        command: diagnostics::DiagnosticsCommand,
+    ) -> Result<Value, ConnectionError> {
+        Err(ConnectionError::custom(
+            diagnostics::UnsupportedDiagnostics(command),
+        ))
+    }
+
+    /// 中斷連線（非必需）
+    ///
+    /// 主程式會在連線閒置超過 [`ConnectionArtifact::idle_timeout`] 時調用此 function ，實作者需要在此處關閉與設備的連線（如關閉 socket 、掛斷數據機），
+    /// 但保留重新連線所需的設定
+    ///
+    /// 中斷連線後，主程式會在下一個需要處理的請求（外部服務請求或自動更新）送出前，先調用 [`Connection::reconnect()`] 重新建立連線，
+    /// 重新連線失敗時，該請求視為失敗
+    ///
+    /// 未設定 [`ConnectionArtifact::idle_timeout`] 時，主程式不會調用此 function
+    ///
+    /// # 回傳值
+    /// 無，可回傳錯誤，回傳錯誤時主程式仍會視為連線已中斷
+    async fn disconnect(&mut self) -> Result<(), ConnectionError> {
+        Ok(())
+    }
+
     /// 重新連線
     ///
-    /// 主程式會在失敗次數大於 [`ConnectionArtifact::max_retry_count`] 後調用此 function
+    /// 主程式會在失敗次數大於 [`ConnectionArtifact::max_retry_count`] 後調用此 function ，
+    /// 設定了 [`ConnectionArtifact::idle_timeout`] 時，也會在閒置中斷連線後，有新的請求需要處理時調用
     ///
     /// # 回傳值
     /// 無，可回傳錯誤
-    async fn reconnect(&mut self) -> Result<(), Box<dyn std::error::Error>>;
+    async fn reconnect(&mut self) -> Result<(), ConnectionError>;
+
+    /// 擷取工作階段狀態（非必需）
+    ///
+    /// 主程式會在中斷連線前或決定重新連線時，利用 [`session::ReconnectHint::capture()`] 調用此 function ，
+    /// 並將結果傳入 [`Connection::reconnect_with()`] ，協定帶有可續用的工作階段（如安全通道、收發序號）時，請在此處回傳續用所需的狀態
+    ///
+    /// 此 function 並不是 async function ，請不要在此處執行需要長時間等待的邏輯
+    ///
+    /// # 回傳值
+    /// 工作階段狀態，格式由實作者決定，沒有可續用的狀態時為 [`None`]
+    fn session_state(&self) -> Option<Value> {
+        None
+    }
+
+    /// 依重新連線提示重新連線（非必需）
+    ///
+    /// 主程式需要重新連線時會調用此 function 取代 [`Connection::reconnect()`] ，實作者可以先嘗試以 [`session::ReconnectHint::session`] 續用工作階段，
+    /// 失敗時再調用 [`Connection::reconnect()`] 完整重新連線
+    ///
+    /// 預設實作會忽略提示並調用 [`Connection::reconnect()`]
+    ///
+    /// # 參數
+    /// - `hint`：重新連線提示，參見 [`session::ReconnectHint`]
+    ///
+    /// # 回傳值
+    /// 是否續用了原本的工作階段，可回傳錯誤
+    async fn reconnect_with(
+        &mut self,
+        hint: session::ReconnectHint,
+    ) -> Result<session::ReconnectOutcome, ConnectionError> {
+        self.reconnect()
+            .await
+            .map(|()| session::ReconnectOutcome::Cold)
+    }
 
     /// 利用新傳入的設定檔重新連線
     ///
@@ -323,10 +728,7 @@ pub trait Connection: Sized + Send + 'static {
     ///
     /// # 回傳值
     /// 無，可回傳錯誤
-    async fn update_config(
-        &mut self,
-        new_config: &Self::Config,
-    ) -> Result<(), Box<dyn std::error::Error>>;
+    async fn update_config(&mut self, new_config: &Self::Config) -> Result<(), ConnectionError>;
 }
 
 /// 設備連線產品
@@ -341,16 +743,385 @@ pub struct ConnectionArtifact<T: Connection> {
     ///
     /// 程式會在失敗次數累加到等於此處設定的數值後，嘗試利用 [`Connection::reconnect()`] function 重新建立連線，如未定義本數值，則不會自動重新建立連線
     pub max_retry_count: Option<u32>,
+    /// 重新連線前的等待策略
+    ///
+    /// 預設為 [`retry::RetryPolicy::Immediate`] ，主程式會在每次調用 [`Connection::reconnect()`] 前依此處的策略等待，參見 [`retry::reconnect()`]
+    pub retry_policy: retry::RetryPolicy,
     /// 更新間隔
     ///
-    /// 程式會依據此處設定的數字，以毫秒為單位作為間隔去處理請求
-    pub update_interval: u64,
+    /// 程式會依據此處設定的時間作為間隔去處理請求
+    pub update_interval: Duration,
     /// 逾時
     ///
-    /// 程式會依據此處設定的數字，以毫秒為單位，當操作所需時間大於此處設定值時終止操作
-    pub timeout: u64,
+    /// 當操作所需時間大於此處設定的時間時，程式會終止操作
+    pub timeout: Duration,
+    /// 保持連線間隔
+    ///
+    /// 非必填，連線閒置超過此處設定的時間時，程式會調用 [`Connection::keepalive()`] ，如未定義本數值，則不會主動保持連線
+    pub keepalive_interval: Option<Duration>,
+    /// 閒置中斷時間
+    ///
+    /// 非必填，適用於撥接、計量計費等不適合長時間保持連線的線路，連線閒置超過此處設定的時間時，程式會調用 [`Connection::disconnect()`] 中斷連線，
+    /// 並在下一個請求需要處理時調用 [`Connection::reconnect()`] 重新建立連線，如未定義本數值，則連線會一直保持
+    ///
+    /// 同時設定 [`ConnectionArtifact::keepalive_interval`] 時，只有在保持連線間隔小於本數值時才會調用 [`Connection::keepalive()`]
+    pub idle_timeout: Option<Duration>,
+    /// 同時處理中的請求數量上限
+    ///
+    /// 主程式批次、管線化或平行發送請求時，同時處理中的請求不會超過此處設定的數量，超過的請求會排隊等待，小於 1 時視為 1
+    ///
+    /// 主程式會在登記至 [`ConnectionStatsRegistry`] 前，以本數值建立 [`concurrency::InFlightLimiter`] 並取代 [`ConnectionStats::in_flight`]
+    pub max_in_flight: usize,
+    /// 執行位置
+    ///
+    /// 預設為 [`execution::ExecutionHint::Shared`] ，會阻塞執行緒或回覆緩慢的連線可以要求在隔離的執行緒上執行，
+    /// 主程式會利用 [`execution::RuntimePools::spawn()`] 依此設定執行調用 [`Connection::request_process()`] 的工作迴圈
+    pub execution: execution::ExecutionHint,
+    /// 暖機時間
+    ///
+    /// 非必填，設備在上電或重新連線後需要一段時間才會回覆正確的數值時設定，[`Connection::init()`] 與 [`Connection::reconnect()`] 成功後的這段時間內，
+    /// 主程式會依 [`ConnectionArtifact::settle_policy`] 處理取得的數值，參見 [`settle::WarmupWindow`]
+    pub warmup: Option<Duration>,
+    /// 暖機期間的數值處理方式
+    pub settle_policy: settle::SettlePolicy,
+    /// 輪詢預算
+    ///
+    /// 非必填，線路在每個更新間隔內能處理的輪詢次數，頻寬有限的線路（如低鮑率的序列埠）設定後，
+    /// 主程式會依 [`ConnectionStats::budget`] 的權重在點位之間分配輪詢次數，參見 [`budget::BusBudget::plan()`]
+    pub poll_budget: Option<u32>,
+    /// 追趕期間的請求間隔
+    ///
+    /// 非必填，設備能承受的最短請求間隔，設定後主程式會在 [`Connection::init()`] 與 [`Connection::reconnect()`] 成功後，
+    /// 以此間隔連續輪詢所有自動更新點位一次，再恢復 [`ConnectionArtifact::update_interval`] ，參見 [`catch_up::CatchUp`]
+    pub catch_up_gap: Option<Duration>,
     /// 連線統計數據
     pub statistics: ConnectionStats,
+    /// 統計數據設定
+    ///
+    /// 主程式會在調用 [`Connection::init_targets()`] 前，利用 [`ConnectionStats::set_config()`] 套用至 [`ConnectionArtifact::statistics`]
+    pub stats_config: StatsConfig,
+    /// 設備探索報告
+    ///
+    /// 非必填，如在初始化時自動偵測了連線參數（如利用 [`serial::probe_line_settings()`] 偵測序列埠線路參數），請將結果放在此處回報給主程式
+    pub discovery: Option<discovery::DiscoveryReport>,
+}
+
+/// 預設更新間隔，參見 [`ConnectionArtifact::update_interval`]
+pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 預設逾時，參見 [`ConnectionArtifact::timeout`]
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+impl<T: Connection> ConnectionArtifact<T> {
+    /// 建立設備連線產品
+    ///
+    /// 更新間隔為 [`DEFAULT_UPDATE_INTERVAL`] ，逾時為 [`DEFAULT_TIMEOUT`] ，其餘非必填的設定均不啟用，可再利用 `update_every()` 等 method 設定
+    ///
+    /// # 參數
+    /// - `artifact`：成功建立的設備連線
+    /// - `statistics`：連線統計數據
+    ///
+    /// # 範例
+    /// ```rust
+    /// # use std::{error::Error, time::Duration};
+    /// # use device_state_exchange_lib::*;
+    /// # #[derive(Debug, Clone)] struct Config;
+    /// # impl ConnectionConfig for Config {}
+    /// # #[derive(Debug, Clone)] struct Point;
+    /// # impl Target for Point {}
+    /// # #[derive(Debug, Clone)] struct Request;
+    /// # impl DeviceStateRequest for Request {}
+    /// # #[derive(Debug, Clone)] struct Response;
+    /// # impl DeviceStateResponse for Response { fn to_value(&self) -> Result<std::borrow::Cow<'_, serde_json::Value>, value::ConversionError> { Ok(Default::default()) } }
+    /// struct Device;
+    ///
+    /// impl Connection for Device {
+    /// #   const NAMES: &[&str] = &["Device"];
+    /// #   type Config = Config;
+    /// #   type Target = Point;
+    /// #   type Request = Request;
+    /// #   type Response = Response;
+    /// #   type Result = ();
+    ///     async fn init(_: &Config) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+    ///         Ok(ConnectionArtifact::new(Device, ConnectionStats::new("COM1", None))
+    ///             .update_every(Duration::from_millis(500))
+    ///             .timeout_after(Duration::from_millis(200))
+    ///             .retry_up_to(3))
+    ///     }
+    /// #   fn init_targets(&mut self, _: &mut ConnectionStats, _: Vec<Point>) -> ConnectionTargets<Request, ()> { ConnectionTargets(Vec::new()) }
+    /// #   async fn request_process(&mut self, _: Request) -> Result<(Response, bool), ConnectionError> { Ok((Response, true)) }
+    /// #   async fn reconnect(&mut self) -> Result<(), ConnectionError> { Ok(()) }
+    /// #   async fn update_config(&mut self, _: &Config) -> Result<(), ConnectionError> { Ok(()) }
+    /// }
+    ///
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() {
+    /// let artifact = Device::init(&Config).await.unwrap();
+    /// assert_eq!(artifact.update_interval, Duration::from_millis(500));
+    /// assert_eq!(artifact.max_retry_count, Some(3));
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn new(artifact: T, statistics: ConnectionStats) -> Self {
+        Self {
+            artifact,
+            max_retry_count: None,
+            retry_policy: retry::RetryPolicy::Immediate,
+            update_interval: DEFAULT_UPDATE_INTERVAL,
+            timeout: DEFAULT_TIMEOUT,
+            keepalive_interval: None,
+            idle_timeout: None,
+            max_in_flight: concurrency::DEFAULT_MAX_IN_FLIGHT,
+            execution: execution::ExecutionHint::Shared,
+            warmup: None,
+            settle_policy: settle::SettlePolicy::MarkUncertain,
+            poll_budget: None,
+            catch_up_gap: None,
+            statistics,
+            stats_config: StatsConfig::default(),
+            discovery: None,
+        }
+    }
+
+    /// 以毫秒數建立設備連線產品
+    ///
+    /// 提供給以毫秒數設定間隔的舊程式使用，新程式請使用 [`ConnectionArtifact::new()`]
+    ///
+    /// # 參數
+    /// - `artifact`：成功建立的設備連線
+    /// - `update_interval_ms`：更新間隔毫秒數
+    /// - `timeout_ms`：逾時毫秒數
+    /// - `statistics`：連線統計數據
+    #[must_use]
+    pub fn from_millis(
+        artifact: T,
+        update_interval_ms: u64,
+        timeout_ms: u64,
+        statistics: ConnectionStats,
+    ) -> Self {
+        Self::new(artifact, statistics)
+            .update_every(Duration::from_millis(update_interval_ms))
+            .timeout_after(Duration::from_millis(timeout_ms))
+    }
+
+    /// 設定更新間隔，參見 [`ConnectionArtifact::update_interval`]
+    #[must_use]
+    pub const fn update_every(mut self, interval: Duration) -> Self {
+        self.update_interval = interval;
+        self
+    }
+
+    /// 設定逾時，參見 [`ConnectionArtifact::timeout`]
+    #[must_use]
+    pub const fn timeout_after(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 設定最高重試次數，參見 [`ConnectionArtifact::max_retry_count`]
+    #[must_use]
+    pub const fn retry_up_to(mut self, count: u32) -> Self {
+        self.max_retry_count = Some(count);
+        self
+    }
+
+    /// 設定重新連線前的等待策略，參見 [`ConnectionArtifact::retry_policy`]
+    #[must_use]
+    pub fn retry_with(mut self, policy: retry::RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// 設定保持連線間隔，參見 [`ConnectionArtifact::keepalive_interval`]
+    #[must_use]
+    pub const fn keepalive_every(mut self, interval: Duration) -> Self {
+        self.keepalive_interval = Some(interval);
+        self
+    }
+
+    /// 設定閒置中斷時間，參見 [`ConnectionArtifact::idle_timeout`]
+    #[must_use]
+    pub const fn disconnect_after_idle(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// 設定同時處理中的請求數量上限，參見 [`ConnectionArtifact::max_in_flight`]
+    #[must_use]
+    pub const fn limit_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = max_in_flight;
+        self
+    }
+
+    /// 設定執行位置，參見 [`ConnectionArtifact::execution`]
+    #[must_use]
+    pub fn run_on(mut self, execution: execution::ExecutionHint) -> Self {
+        self.execution = execution;
+        self
+    }
+
+    /// 設定暖機時間與暖機期間的數值處理方式，參見 [`ConnectionArtifact::warmup`]
+    #[must_use]
+    pub const fn warm_up(mut self, warmup: Duration, policy: settle::SettlePolicy) -> Self {
+        self.warmup = Some(warmup);
+        self.settle_policy = policy;
+        self
+    }
+
+    /// 設定每個更新間隔的輪詢次數，參見 [`ConnectionArtifact::poll_budget`]
+    #[must_use]
+    pub const fn budget_polls(mut self, slots: u32) -> Self {
+        self.poll_budget = Some(slots);
+        self
+    }
+
+    /// 設定追趕期間的請求間隔，參見 [`ConnectionArtifact::catch_up_gap`]
+    #[must_use]
+    pub const fn catch_up_every(mut self, gap: Duration) -> Self {
+        self.catch_up_gap = Some(gap);
+        self
+    }
+
+    /// 設定統計數據設定，參見 [`ConnectionArtifact::stats_config`]
+    #[must_use]
+    pub const fn with_stats_config(mut self, config: StatsConfig) -> Self {
+        self.stats_config = config;
+        self
+    }
+
+    /// 設定設備探索報告，參見 [`ConnectionArtifact::discovery`]
+    #[must_use]
+    pub fn with_discovery(mut self, report: discovery::DiscoveryReport) -> Self {
+        self.discovery = Some(report);
+        self
+    }
+}
+
+/// base64 編碼，以人類可讀的格式（如 JSON）序列化 [`bytes::Bytes`] 時使用，二進位格式維持原本的位元組
+pub(crate) mod base64 {
+    use bytes::Bytes;
+    use serde::{Deserialize, Deserializer, Serializer, de};
+
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    pub fn encode(input: &[u8]) -> String {
+        let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+        for chunk in input.chunks(3) {
+            let bytes = [
+                chunk[0],
+                chunk.get(1).copied().unwrap_or(0),
+                chunk.get(2).copied().unwrap_or(0),
+            ];
+            let indices = [
+                bytes[0] >> 2,
+                ((bytes[0] & 0b11) << 4) | (bytes[1] >> 4),
+                ((bytes[1] & 0b1111) << 2) | (bytes[2] >> 6),
+                bytes[2] & 0b11_1111,
+            ];
+
+            for (position, index) in indices.into_iter().enumerate() {
+                if position <= chunk.len() {
+                    output.push(char::from(ALPHABET[usize::from(index)]));
+                } else {
+                    output.push('=');
+                }
+            }
+        }
+        output
+    }
+
+    pub fn decode(input: &str) -> Option<Vec<u8>> {
+        let input = input.trim_end_matches('=');
+        let mut output = Vec::with_capacity(input.len() * 3 / 4);
+        let mut buffer = 0_u32;
+        let mut bits = 0;
+
+        for c in input.bytes() {
+            let index = ALPHABET.iter().position(|candidate| *candidate == c)?;
+            buffer = ((buffer << 6) | u32::try_from(index).ok()?) & 0xffff;
+            bits += 6;
+
+            if bits >= 8 {
+                bits -= 8;
+                output.push(u8::try_from((buffer >> bits) & 0xff).ok()?);
+            }
+        }
+
+        Some(output)
+    }
+
+    pub fn serialize<S: Serializer>(bytes: &Bytes, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&encode(bytes))
+        } else {
+            serializer.serialize_bytes(bytes)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Bytes, D::Error> {
+        if deserializer.is_human_readable() {
+            let encoded = String::deserialize(deserializer)?;
+            decode(&encoded)
+                .map(Bytes::from)
+                .ok_or_else(|| de::Error::custom("不是有效的 base64 字串"))
+        } else {
+            Bytes::deserialize(deserializer)
+        }
+    }
+}
+
+/// 以微秒數（整數）序列化 [`Duration`] ，用於毫秒精度不足的耗時統計
+pub(crate) mod micros {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(u64::try_from(duration.as_micros()).unwrap_or(u64::MAX))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_micros)
+    }
+}
+
+/// 以毫秒數（整數）序列化 [`Duration`] ，讓設定檔維持以毫秒表示時間的格式
+pub(crate) mod millis {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+
+    /// 以毫秒數序列化 `Option<Duration>`
+    pub mod option {
+        use std::time::Duration;
+
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        #[expect(clippy::ref_option)]
+        pub fn serialize<S: Serializer>(
+            duration: &Option<Duration>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match duration {
+                Some(duration) => super::serialize(duration, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Duration>, D::Error> {
+            Option::<u64>::deserialize(deserializer).map(|millis| millis.map(Duration::from_millis))
+        }
+    }
 }
 
 /// 設備連線所屬的點位
@@ -380,7 +1151,7 @@ where
     pub request: REQ,
     /// 向外部服務回傳資料時，所需要的資訊
     ///
-    /// 當程式處理完請求後，會依程式定義將結果儲存至本資料結構中
+    /// 當程式處理完請求後，會依程式定義將結果儲存至本資料結構中，轉換方式可以利用 [`format::ResultFormatter`] 明確定義
     pub result: RES,
     /// 點位初始狀態
     ///
@@ -388,21 +1159,276 @@ where
     pub default_status: Option<Value>,
     /// 是否要自動更新
     pub auto_refresh: bool,
+    /// 自動更新間隔
+    ///
+    /// 非必填，未設定時使用 [`ConnectionArtifact::update_interval`] ，參見 [`scheduler::Scheduler`]
+    pub refresh_interval: Option<Duration>,
+    /// 保留原始封包數量
+    ///
+    /// 非必填，設定後主程式會利用 [`diagnostics::RawFrameStore`] 保留本點位最近 N 筆 [`DeviceStateResponse::raw()`] 的內容，供診斷轉換錯誤時使用
+    pub keep_raw_frames: Option<usize>,
+    /// 點位群組
+    ///
+    /// 非必填，如 `energy` 、`status` 、`diagnostics` ，同一群組的點位可以一起停用、立即更新或統計，參見 [`group::TargetGroups`]
+    pub group: Option<String>,
+    /// 安全狀態
+    ///
+    /// 非必填，僅適用於可寫入的點位（如設定值），主程式正常結束或偵測到上游控制中斷時，會將點位寫入此狀態（如將設定值歸零），參見 [`safe_state`]
+    pub safe_state: Option<Value>,
+    /// 陣列格式
+    ///
+    /// 非必填，僅適用於回覆為陣列的點位（如諧波、波形），設備連線可以在 [`DeviceStateResponse::to_data()`] 中以 [`codec::ArrayLayout::decode()`] 將原始資料轉換為 [`value::Samples`] ，參見 [`codec::ArrayLayout`]
+    pub array: Option<codec::ArrayLayout>,
+    /// 數值變化通知
+    ///
+    /// 非必填，設定後外部服務可以利用 [`InitedTarget::on_change()`] 訂閱本點位的數值變化，參見 [`cov`]
+    pub change: Option<cov::ChangeNotifier>,
     /// 點位統計數據
     ///
-    /// 非必填，如果需要記錄設備連線狀態，請在 [`Connection::init_targets()`] 的 `connection_statistics` 參數中初始化新的 [`TargetStats`] ，並利用 [`Arc::clone()`] 方法複製一份指針至此
+    /// 非必填，如果需要記錄設備連線狀態，請在 [`Connection::init_targets()`] 中利用 `connection_statistics` 參數的 [`ConnectionStats::insert_target()`] 取得統計數據並指派至此
     pub statistics: Option<Arc<TargetStats>>,
 }
 
-/// 連線統計數據
+impl<REQ, RES> InitedTarget<REQ, RES>
+where
+    REQ: DeviceStateRequest,
+{
+    /// 訂閱點位的數值變化
+    ///
+    /// # 回傳值
+    /// 數值變化的接收端，點位未設定 [`InitedTarget::change`] 時為 [`None`]
+    #[must_use]
+    pub fn on_change(&self) -> Option<tokio::sync::broadcast::Receiver<cov::ChangeEvent>> {
+        self.change.as_ref().map(cov::ChangeNotifier::subscribe)
+    }
+
+    /// 依 [`Connection::postprocess()`] 產生的回覆通知數值變化
+    ///
+    /// # 回傳值
+    /// 數值是否有變化，點位未設定 [`InitedTarget::change`] 時為 `false`
+    ///
+    /// # Errors
+    /// 回傳 [`DeviceStateResponse::to_value()`] 的錯誤
+    pub fn notify_change(
+        &self,
+        response: &impl DeviceStateResponse,
+    ) -> Result<bool, value::ConversionError> {
+        let Some(change) = &self.change else {
+            return Ok(false);
+        };
+        Ok(change.update(&self.name, response.to_value()?.into_owned()))
+    }
+}
+
+/// 連線統計數據
+///
+/// 點位統計數據以設備編號分類存放，請利用 [`ConnectionStats::insert_target()`] 新增，內部使用的 map 型別不屬於公開 API
 #[derive(Debug, Clone)]
 pub struct ConnectionStats {
     pub port_target: String,
     pub port_note: Option<String>,
-    pub targets: HashMap<TargetAddressNumber, Arc<TargetStats>>,
+    /// 連線標籤
+    ///
+    /// 非必填，如 `site` 、`panel` ，會與點位標籤（參見 [`TargetStats::set_label()`]）合併後用於 [`ConnectionStatsRegistry::aggregate()`]
+    pub labels: Labels,
+    targets: HashMap<TargetAddressNumber, Arc<TargetStats>>,
+    config: StatsConfig,
+    /// 目前使用中的遠端位址
+    ///
+    /// 非必填，網路連線的實作者可以在每次建立連線後更新，參見 [`RemoteAddress`]
+    pub remote_address: RemoteAddress,
+    /// 作業系統時鐘調整偵測
+    ///
+    /// 非必填，主程式可以在排程執行時調用 [`clock::ClockMonitor::check()`] ，調整次數會顯示於統計數據快照中
+    pub clock: clock::ClockMonitor,
+    /// 時間區間計數
+    ///
+    /// 非必填，主程式可以在每次輪詢後記錄，保留的區間會顯示於統計數據快照中，參見 [`bucket::BucketedCounters`]
+    pub buckets: bucket::BucketedCounters,
+    /// 同時處理中請求數量限制
+    ///
+    /// 預設上限為 [`concurrency::DEFAULT_MAX_IN_FLIGHT`] ，參見 [`ConnectionArtifact::max_in_flight`] ，處理中與排隊中的請求數量會顯示於統計數據快照中
+    pub in_flight: concurrency::InFlightLimiter,
+    /// 管線佇列計量
+    ///
+    /// 非必填，主程式可以將各管線階段之間的 [`queue::BoundedQueue`] 登記於此，佇列深度會顯示於統計數據快照中
+    pub queues: queue::QueueGauges,
+    /// 輪詢預算
+    ///
+    /// 非必填，設定 [`ConnectionArtifact::poll_budget`] 時，主程式依此處的權重分配輪詢次數，分配與完成的次數會顯示於統計數據快照中
+    pub budget: budget::BusBudget,
+    /// 處理階段耗時統計
+    ///
+    /// 非必填，主程式可以在每個請求完成後記錄各處理階段的耗時，參見 [`timing::StageStats::record()`]
+    pub stages: timing::StageStats,
+    /// 重新連線統計
+    ///
+    /// 主程式在每次調用 [`Connection::reconnect()`] 後記錄，連續失敗次數用於計算下一次的等待時間，參見 [`retry::reconnect()`]
+    pub reconnects: retry::ReconnectStats,
 }
 
 impl ConnectionStats {
+    /// 建立沒有點位的連線統計數據
+    ///
+    /// # 參數
+    /// - `port_target`：連線目標，如 `COM1` 、`192.168.1.10:502`
+    /// - `port_note`：連線備註
+    ///
+    /// # 範例
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use device_state_exchange_lib::ConnectionStats;
+    ///
+    /// let mut statistics = ConnectionStats::new("COM1", None);
+    /// let device = statistics.insert_target(Some("1".to_owned()));
+    /// device.record_success(12);
+    ///
+    /// assert!(Arc::ptr_eq(&device, &statistics.insert_target(Some("1".to_owned()))));
+    /// assert_eq!(statistics.targets().count(), 1);
+    /// assert_eq!(statistics.snapshot().summary.total_polling_count, 1);
+    /// ```
+    #[must_use]
+    pub fn new(port_target: impl Into<String>, port_note: Option<String>) -> Self {
+        Self {
+            port_target: port_target.into(),
+            port_note,
+            labels: Labels::new(),
+            targets: HashMap::default(),
+            config: StatsConfig::default(),
+            remote_address: RemoteAddress::default(),
+            clock: clock::ClockMonitor::default(),
+            buckets: bucket::BucketedCounters::default(),
+            in_flight: concurrency::InFlightLimiter::default(),
+            queues: queue::QueueGauges::default(),
+            budget: budget::BusBudget::default(),
+            stages: timing::StageStats::default(),
+            reconnects: retry::ReconnectStats::default(),
+        }
+    }
+
+    /// 新增點位統計數據
+    ///
+    /// 同一個設備上的點位共用一份統計數據，設備編號已存在時會回傳既有的統計數據
+    ///
+    /// # 參數
+    /// - `address_number`：設備編號，參見 [`TargetAddressNumber`]
+    ///
+    /// # 回傳值
+    /// 點位統計數據，請指派至 [`InitedTarget::statistics`]
+    pub fn insert_target(&mut self, address_number: TargetAddressNumber) -> Arc<TargetStats> {
+        let config = self.config;
+        Arc::clone(
+            self.targets
+                .entry(address_number)
+                .or_insert_with(|| Arc::new(TargetStats::with_config(config))),
+        )
+    }
+
+    /// 套用統計數據設定
+    ///
+    /// 設定只會套用至之後利用 [`ConnectionStats::insert_target()`] 新增的點位統計數據，請在調用 [`Connection::init_targets()`] 前套用
+    ///
+    /// # 範例
+    /// ```rust
+    /// use device_state_exchange_lib::{Averaging, ConnectionStats, StatsConfig};
+    ///
+    /// let mut statistics = ConnectionStats::new("COM1", None);
+    /// statistics.set_config(StatsConfig { averaging: Averaging::Window { samples: 2 }, ..StatsConfig::default() });
+    ///
+    /// let device = statistics.insert_target(None);
+    /// for response_ms in [100, 10, 20] {
+    ///     device.record_success(response_ms);
+    /// }
+    /// assert_eq!(device.snapshot().average_response_ms, 15);
+    /// ```
+    pub const fn set_config(&mut self, config: StatsConfig) {
+        self.config = config;
+    }
+
+    /// 移除點位統計數據
+    pub fn remove_target(
+        &mut self,
+        address_number: &TargetAddressNumber,
+    ) -> Option<Arc<TargetStats>> {
+        self.targets.remove(address_number)
+    }
+
+    /// 只保留仍在使用中的點位統計數據
+    ///
+    /// 執行期間移除點位後（如 [`Connection::update_config()`] 後重新調用 [`Connection::init_targets()`]），
+    /// 已不存在的點位統計數據仍會留在本 struct 中並影響加總/平均統計數據，主程式應在點位更新後調用本 method ，
+    /// 並將結果重新登記至 [`ConnectionStatsRegistry`]
+    ///
+    /// # 參數
+    /// - `targets`：目前的點位，[`InitedTarget::statistics`] 指向的統計數據會被保留
+    ///
+    /// # 回傳值
+    /// 被移除的點位統計數據的最終快照，需要保存歷史紀錄時，可自行存放
+    ///
+    /// # 範例
+    /// ```rust
+    /// use device_state_exchange_lib::{ConnectionStats, ConnectionTargets, DeviceStateRequest, InitedTarget};
+    ///
+    /// #[derive(Debug, Clone)]
+    /// struct Request;
+    ///
+    /// impl DeviceStateRequest for Request {}
+    ///
+    /// let mut statistics = ConnectionStats::new("COM1", None);
+    /// let kept = statistics.insert_target(Some("1".to_owned()));
+    /// statistics.insert_target(Some("2".to_owned())).record_failure();
+    ///
+    /// let targets = ConnectionTargets(vec![InitedTarget {
+    ///     name: "temperature".to_owned(),
+    ///     request: Request,
+    ///     result: (),
+    ///     default_status: None,
+    ///     auto_refresh: true,
+    ///     refresh_interval: None,
+    ///     keep_raw_frames: None,
+    ///     group: None,
+    ///     safe_state: None,
+    ///     array: None,
+    ///     change: None,
+    ///     statistics: Some(kept),
+    /// }]);
+    ///
+    /// let removed = statistics.retain_targets(&targets);
+    /// assert_eq!(removed[0].address_number.as_deref(), Some("2"));
+    /// assert_eq!(removed[0].statistics.failed_poll_count, 1);
+    /// assert_eq!(statistics.snapshot().summary.failed_poll_count, 0);
+    /// ```
+    pub fn retain_targets<REQ, RES>(
+        &mut self,
+        targets: &ConnectionTargets<REQ, RES>,
+    ) -> Vec<TargetStatsSnapshot>
+    where
+        REQ: DeviceStateRequest,
+    {
+        let mut removed = Vec::new();
+
+        self.targets.retain(|address_number, statistics| {
+            let active = targets
+                .0
+                .iter()
+                .filter_map(|target| target.statistics.as_ref())
+                .any(|target_statistics| Arc::ptr_eq(target_statistics, statistics));
+
+            if !active {
+                removed.push(statistics.target_snapshot(address_number));
+            }
+
+            active
+        });
+
+        removed
+    }
+
+    /// 列出所有點位統計數據
+    pub fn targets(&self) -> impl Iterator<Item = (&TargetAddressNumber, &Arc<TargetStats>)> {
+        self.targets.iter()
+    }
+
     /// 取得點位統計數據
     #[must_use]
     pub fn get_target(&self, address_number: &Option<String>) -> Option<&Arc<TargetStats>> {
@@ -415,64 +1441,312 @@ impl ConnectionStats {
     /// 加總/平均統計數據，參見 [`Statistics`]
     #[must_use]
     pub fn get_all_stats(&self) -> Statistics {
-        self.targets
-            .values()
-            .fold(Statistics::default(), |accumulator, next_target| {
-                accumulator.failed_poll_count.fetch_add(
-                    next_target
-                        .0
-                        .failed_poll_count
-                        .load(std::sync::atomic::Ordering::Relaxed),
-                    std::sync::atomic::Ordering::Relaxed,
-                );
-
-                accumulator.total_polling_count.fetch_add(
-                    next_target
-                        .0
-                        .total_polling_count
-                        .load(std::sync::atomic::Ordering::Relaxed),
-                    std::sync::atomic::Ordering::Relaxed,
-                );
-
-                let _ = accumulator.average_response_ms.fetch_update(
-                    std::sync::atomic::Ordering::Relaxed,
-                    std::sync::atomic::Ordering::Relaxed,
-                    |average_response_ms| {
-                        let current_total_polling_count = accumulator
-                            .total_polling_count
-                            .load(std::sync::atomic::Ordering::Relaxed);
-                        let current_failed_polling_count = accumulator
-                            .failed_poll_count
-                            .load(std::sync::atomic::Ordering::Relaxed);
-                        let next_total_polling_count = next_target
-                            .0
-                            .total_polling_count
-                            .load(std::sync::atomic::Ordering::Relaxed);
-                        let next_failed_polling_count = next_target
-                            .0
-                            .failed_poll_count
-                            .load(std::sync::atomic::Ordering::Relaxed);
-                        let next_average_response_ms = next_target
-                            .0
-                            .average_response_ms
-                            .load(std::sync::atomic::Ordering::Relaxed);
-
-                        let current_success_count =
-                            current_total_polling_count - current_failed_polling_count;
-                        let next_success_count =
-                            next_total_polling_count - next_failed_polling_count;
-
-                        Some(
-                            ((average_response_ms * current_success_count)
-                                + (next_average_response_ms * next_success_count))
-                                / (current_success_count + next_success_count),
-                        )
-                    },
-                );
-
-                accumulator
-            })
+        Statistics::aggregate(self.targets.values().map(Arc::as_ref))
+    }
+
+    /// 取得連線統計數據快照
+    ///
+    /// # 回傳
+    /// 可序列化的連線統計數據，包含加總/平均統計數據與各點位統計數據，參見 [`ConnectionStatsSnapshot`]
+    #[must_use]
+    pub fn snapshot(&self) -> ConnectionStatsSnapshot {
+        ConnectionStatsSnapshot {
+            port_target: self.port_target.clone(),
+            port_note: self.port_note.clone(),
+            labels: self.labels.clone(),
+            remote_address: self.remote_address.get(),
+            clock_adjusted: self.clock.adjusted_count(),
+            buckets: self.buckets.snapshot(),
+            in_flight: self.in_flight.snapshot(),
+            queues: self.queues.snapshot(),
+            budget: self.budget.snapshot(),
+            stages: self.stages.snapshot(),
+            reconnects: self.reconnects.snapshot(),
+            summary: self.get_all_stats().snapshot(),
+            targets: self
+                .targets
+                .iter()
+                .map(|(address_number, target_stats)| target_stats.target_snapshot(address_number))
+                .collect(),
+        }
+    }
+
+    /// 匯出點位統計數據
+    ///
+    /// 與 [`state::StateStore::export_state()`] 搭配使用，讓備援主機接手後，統計數據可以延續原主機的累計值；
+    /// 只會匯出各點位的累計統計數據與標籤，時間區間計數、處理中請求數量等執行期間的數據不會匯出
+    ///
+    /// # 範例
+    /// ```rust
+    /// use device_state_exchange_lib::ConnectionStats;
+    ///
+    /// let mut primary = ConnectionStats::new("COM1", None);
+    /// let device = primary.insert_target(Some("1".to_owned()));
+    /// device.set_label("device", "meter");
+    /// device.record_success(12);
+    /// device.record_failure();
+    ///
+    /// let transferred = serde_json::to_string(&primary.export_state()).unwrap();
+    ///
+    /// let mut standby = ConnectionStats::new("COM1", None);
+    /// standby.import_state(serde_json::from_str(&transferred).unwrap()).unwrap();
+    /// assert_eq!(standby.snapshot().summary, primary.snapshot().summary);
+    /// assert_eq!(standby.get_target(&Some("1".to_owned())).unwrap().labels(), device.labels());
+    /// ```
+    #[must_use]
+    pub fn export_state(&self) -> StatsExport {
+        StatsExport {
+            version: state::STATE_EXPORT_VERSION,
+            targets: self
+                .targets
+                .iter()
+                .map(|(address_number, target_stats)| target_stats.target_snapshot(address_number))
+                .collect(),
+        }
+    }
+
+    /// 匯入 [`ConnectionStats::export_state()`] 匯出的點位統計數據
+    ///
+    /// 設備編號已存在時，以匯入的累計值與標籤取代，並保留既有的 [`Arc`] ，已指派至 [`InitedTarget::statistics`] 的統計數據會一併更新
+    ///
+    /// # Errors
+    /// 匯出資料的版本較新時回傳 [`state::UnsupportedExportVersion`]
+    pub fn import_state(
+        &mut self,
+        export: StatsExport,
+    ) -> Result<(), state::UnsupportedExportVersion> {
+        state::check_export_version(export.version)?;
+
+        for target in export.targets {
+            self.insert_target(target.address_number).restore(
+                target.statistics,
+                &target.latency,
+                target.labels,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// 使用中的遠端位址
+///
+/// 主機名稱可能解析出多個位址，且每次重新連線時解析的結果都可能不同（如動態 DNS），
+/// 實作者可以在 [`Connection::init()`] 與 [`Connection::reconnect()`] 建立連線後，將實際連上的位址記錄於此，供外部界面查詢
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件（包含登記至 [`ConnectionStatsRegistry`] 的 [`ConnectionStats`]）會存取同一份資料
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::RemoteAddress;
+///
+/// let remote_address = RemoteAddress::default();
+/// let shared = remote_address.clone();
+///
+/// remote_address.set(Some("[2001:db8::1]:502".parse().unwrap()));
+/// assert_eq!(shared.get(), Some("[2001:db8::1]:502".parse().unwrap()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RemoteAddress(Arc<RwLock<Option<SocketAddr>>>);
+
+impl RemoteAddress {
+    /// 更新使用中的遠端位址，連線中斷時請設為 [`None`]
+    pub fn set(&self, address: Option<SocketAddr>) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = address;
+    }
+
+    /// 取得使用中的遠端位址
+    #[must_use]
+    pub fn get(&self) -> Option<SocketAddr> {
+        *self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// 連線統計數據登記表
+///
+/// 以連線識別名稱存放各連線的 [`ConnectionStats`] ，供外部界面查詢
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份登記表
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionStatsRegistry {
+    connections: Arc<RwLock<HashMap<String, ConnectionStats>>>,
+}
+
+impl ConnectionStatsRegistry {
+    /// 建立空的連線統計數據登記表
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登記連線統計數據
+    ///
+    /// # 參數
+    /// - `id`：連線識別名稱
+    /// - `statistics`：連線統計數據，[`ConnectionStats`] 中的點位統計數據以 [`Arc`] 共享，登記後仍會持續反映最新數值
+    pub fn insert(&self, id: impl Into<String>, statistics: ConnectionStats) {
+        self.connections
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(id.into(), statistics);
+    }
+
+    /// 移除連線統計數據
+    pub fn remove(&self, id: &str) -> Option<ConnectionStats> {
+        self.connections
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(id)
+    }
+
+    /// 取得連線統計數據快照
+    #[must_use]
+    pub fn snapshot(&self, id: &str) -> Option<ConnectionStatsSnapshot> {
+        self.connections
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(id)
+            .map(ConnectionStats::snapshot)
+    }
+
+    /// 依標籤加總統計數據
+    ///
+    /// 點位的標籤為連線標籤（[`ConnectionStats::labels`]）與點位標籤（[`TargetStats::set_label()`]）合併的結果，名稱相同時以點位標籤為準
+    ///
+    /// # 參數
+    /// - `selector`：需要符合的標籤，點位需包含所有指定的標籤及數值，為空時加總所有點位
+    ///
+    /// # 回傳值
+    /// 符合條件點位的加總/平均統計數據
+    ///
+    /// # 範例
+    /// ```rust
+    /// use device_state_exchange_lib::{ConnectionStats, ConnectionStatsRegistry, Labels};
+    ///
+    /// let registry = ConnectionStatsRegistry::new();
+    ///
+    /// for (port, panel) in [("COM1", "A"), ("COM2", "B")] {
+    ///     let mut statistics = ConnectionStats::new(port, None);
+    ///     statistics.labels.insert("site".to_owned(), "taipei".to_owned());
+    ///     statistics.labels.insert("panel".to_owned(), panel.to_owned());
+    ///
+    ///     let meter = statistics.insert_target(Some("1".to_owned()));
+    ///     meter.set_label("device", "meter");
+    ///     meter.record_failure();
+    ///
+    ///     registry.insert(port, statistics);
+    /// }
+    ///
+    /// let site = Labels::from([("site".to_owned(), "taipei".to_owned())]);
+    /// assert_eq!(registry.aggregate(&site).failed_poll_count, 2);
+    ///
+    /// let panel = Labels::from([("panel".to_owned(), "A".to_owned()), ("device".to_owned(), "meter".to_owned())]);
+    /// assert_eq!(registry.aggregate(&panel).failed_poll_count, 1);
+    /// ```
+    #[must_use]
+    pub fn aggregate(&self, selector: &Labels) -> StatisticsSnapshot {
+        Statistics::aggregate(
+            self.connections
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .values()
+                .flat_map(|connection| {
+                    connection.targets.values().filter(|target| {
+                        let target_labels = target.labels();
+                        selector.iter().all(|(key, value)| {
+                            target_labels
+                                .get(key)
+                                .or_else(|| connection.labels.get(key))
+                                == Some(value)
+                        })
+                    })
+                })
+                .map(Arc::as_ref),
+        )
+        .snapshot()
     }
+
+    /// 取得所有連線統計數據快照
+    #[must_use]
+    pub fn snapshot_all(&self) -> std::collections::HashMap<String, ConnectionStatsSnapshot> {
+        self.connections
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(id, statistics)| (id.clone(), statistics.snapshot()))
+            .collect()
+    }
+}
+
+/// 連線統計數據快照
+///
+/// 本 struct 由 [`ConnectionStats::snapshot()`] 產生，數值為取得快照當下的統計數據，不會隨後續請求更新
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionStatsSnapshot {
+    pub port_target: String,
+    pub port_note: Option<String>,
+    /// 連線標籤
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: Labels,
+    /// 使用中的遠端位址，參見 [`RemoteAddress`]
+    pub remote_address: Option<SocketAddr>,
+    /// 偵測到作業系統時鐘被調整的次數，參見 [`clock::ClockMonitor`]
+    pub clock_adjusted: u64,
+    /// 時間區間計數，依時間由舊到新排列，參見 [`bucket::BucketedCounters`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub buckets: Vec<bucket::BucketSnapshot>,
+    /// 處理中與排隊中的請求數量，參見 [`concurrency::InFlightLimiter`]
+    #[serde(default)]
+    pub in_flight: concurrency::InFlightSnapshot,
+    /// 各管線佇列的深度，參見 [`queue::QueueGauges`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub queues: BTreeMap<String, queue::QueueSnapshot>,
+    /// 各點位與點位群組分配與完成的輪詢次數，參見 [`budget::BusBudget`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub budget: BTreeMap<String, budget::BudgetSnapshot>,
+    /// 各處理階段的耗時統計，參見 [`timing::StageStats`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub stages: BTreeMap<timing::Stage, timing::StageSnapshot>,
+    /// 重新連線統計，沒有重新連線紀錄時不會序列化，參見 [`retry::ReconnectStats`]
+    #[serde(default, skip_serializing_if = "retry::ReconnectSnapshot::is_empty")]
+    pub reconnects: retry::ReconnectSnapshot,
+    /// 加總/平均統計數據
+    pub summary: StatisticsSnapshot,
+    /// 各點位統計數據
+    pub targets: Vec<TargetStatsSnapshot>,
+}
+
+/// 點位統計數據快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetStatsSnapshot {
+    /// 設備編號，參見 [`TargetAddressNumber`]
+    pub address_number: TargetAddressNumber,
+    /// 點位標籤，不包含連線標籤
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: Labels,
+    /// 統計數據
+    #[serde(flatten)]
+    pub statistics: StatisticsSnapshot,
+    /// 回覆快取命中統計，點位未使用快取時不會序列化
+    #[serde(default, skip_serializing_if = "cache::CacheStatsSnapshot::is_empty")]
+    pub cache: cache::CacheStatsSnapshot,
+    /// 寫入統計，點位沒有寫入紀錄時不會序列化
+    #[serde(default, skip_serializing_if = "command::WriteStatsSnapshot::is_empty")]
+    pub writes: command::WriteStatsSnapshot,
+    /// 回覆時間分布，用於匯入後還原百分位數，點位沒有成功的回覆時不會序列化
+    #[serde(default, skip_serializing_if = "histogram::LatencyBuckets::is_empty")]
+    pub latency: histogram::LatencyBuckets,
+}
+
+/// 點位統計數據匯出資料
+///
+/// 由 [`ConnectionStats::export_state()`] 產生
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsExport {
+    /// 匯出格式版本，參見 [`state::STATE_EXPORT_VERSION`]
+    pub version: u32,
+    /// 各點位統計數據
+    pub targets: Vec<TargetStatsSnapshot>,
 }
 
 /// 連線統計數據設備編號
@@ -481,87 +1755,471 @@ impl ConnectionStats {
 /// 設備，請填入該設備的 Modbus device ID
 pub type TargetAddressNumber = Option<String>;
 
+/// 統計數據設定
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatsConfig {
+    /// 平均回覆毫秒數的計算方式
+    #[serde(default)]
+    pub averaging: Averaging,
+    /// 滾動時間窗的區間設定，參見 [`windowed`]
+    #[serde(default)]
+    pub window: windowed::WindowConfig,
+}
+
+/// 平均回覆毫秒數的計算方式
+///
+/// 程式長時間運行後，程式啟動以來的平均值幾乎不會再變動，無法反映設備目前的狀況，可依需求改用指數移動平均或滑動視窗
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "algorithm", rename_all = "snake_case")]
+pub enum Averaging {
+    /// 程式啟動以來的平均值
+    #[default]
+    Lifetime,
+    /// 指數移動平均（EMA）
+    Ema {
+        /// 新樣本的權重，介於 0 至 1 ，越大越快反映最新的回覆時間
+        alpha: f64,
+    },
+    /// 最近 N 次成功請求的平均值
+    Window {
+        /// 樣本數量，小於 1 時視為 1
+        samples: usize,
+    },
+}
+
+/// 統計數據標籤
+///
+/// 以名稱/數值表示統計數據所屬的層級（如 `site` → `panel` → `bus` → `device`），依名稱排序
+pub type Labels = BTreeMap<String, String>;
+
 /// 點位統計數據
 #[derive(Debug, Default)]
-pub struct TargetStats(Statistics);
+pub struct TargetStats {
+    statistics: Statistics,
+    labels: RwLock<Labels>,
+    averaging: Averaging,
+    windowed: windowed::WindowedStats,
+    cache: cache::CacheCounters,
+    writes: command::WriteCounters,
+}
 
 impl TargetStats {
-    /// 記錄請求成功
+    /// 建立指定平均演算法的點位統計數據
+    ///
+    /// 一般情況下請利用 [`ConnectionStats::insert_target()`] 建立，演算法會依 [`ConnectionStats::set_config()`] 的設定
+    #[must_use]
+    pub fn new(averaging: Averaging) -> Self {
+        Self {
+            averaging,
+            ..Self::default()
+        }
+    }
+
+    /// 建立指定設定的點位統計數據，參見 [`StatsConfig`]
+    #[must_use]
+    pub fn with_config(config: StatsConfig) -> Self {
+        Self {
+            averaging: config.averaging,
+            windowed: windowed::WindowedStats::with_config(config.window),
+            ..Self::default()
+        }
+    }
+
+    /// 記錄一次輪詢的結果
+    ///
+    /// 輪詢次數、失敗次數與平均回覆毫秒數在同一個鎖內更新，多個線程同時記錄時不會遺失紀錄，
+    /// 讀取時也不會取得只更新一半的數值
+    ///
+    /// # 參數
+    /// - `outcome`：輪詢結果
+    ///
+    /// # 範例
+    /// ```rust
+    /// use std::{sync::Arc, thread};
+    ///
+    /// use device_state_exchange_lib::{Outcome, TargetStats};
+    ///
+    /// let target = Arc::new(TargetStats::default());
+    /// thread::scope(|scope| {
+    ///     for thread in 0..8 {
+    ///         let target = &target;
+    ///         scope.spawn(move || {
+    ///             for _ in 0..1_000 {
+    ///                 target.record(if thread % 2 == 0 { Outcome::Success { response_ms: 10 * thread } } else { Outcome::Failure });
+    ///             }
+    ///         });
+    ///     }
+    /// });
+    ///
+    /// let snapshot = target.snapshot();
+    /// assert_eq!((snapshot.total_polling_count, snapshot.failed_poll_count), (8_000, 4_000));
+    /// // 成功的回覆時間為 0 、 20 、 40 、 60 毫秒各 1000 次
+    /// assert_eq!(snapshot.average_response_ms, 30);
+    /// ```
+    pub fn record(&self, outcome: Outcome) {
+        if let Outcome::Success { response_ms } = outcome {
+            self.statistics.latency.record(response_ms);
+        }
+        self.windowed.record(outcome);
+        self.statistics
+            .totals
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .record(outcome, self.averaging);
+    }
+
+    /// 記錄請求成功，參見 [`TargetStats::record()`]
     ///
     /// # 參數
     /// - `response_ms`: 本次請求所花費的毫秒數
     pub fn record_success(&self, response_ms: i64) {
-        let orig_polling_count = self
-            .0
-            .total_polling_count
-            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-
-        let orig_failed_poll_count = self
-            .0
-            .failed_poll_count
-            .load(std::sync::atomic::Ordering::Relaxed);
-
-        let orig_response_ms = self
-            .0
-            .average_response_ms
-            .load(std::sync::atomic::Ordering::Relaxed);
-
-        let new_response_ms = ((orig_response_ms * (orig_polling_count - orig_failed_poll_count))
-            + response_ms)
-            / (orig_polling_count + 1);
-
-        self.0
-            .average_response_ms
-            .store(new_response_ms, std::sync::atomic::Ordering::Relaxed);
+        self.record(Outcome::Success { response_ms });
     }
 
-    /// 記錄請求失敗
+    /// 記錄請求失敗，參見 [`TargetStats::record()`]
     pub fn record_failure(&self) {
-        self.0
-            .total_polling_count
-            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-
-        self.0
-            .failed_poll_count
-            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.record(Outcome::Failure);
     }
 
     pub fn get_latest_value(&self) -> (i64, i64, i64) {
+        let snapshot = self.snapshot();
         (
-            self.0
-                .failed_poll_count
-                .load(std::sync::atomic::Ordering::Relaxed),
-            self.0
-                .total_polling_count
-                .load(std::sync::atomic::Ordering::Relaxed),
-            self.0
-                .average_response_ms
-                .load(std::sync::atomic::Ordering::Relaxed),
+            snapshot.failed_poll_count,
+            snapshot.total_polling_count,
+            snapshot.average_response_ms,
         )
     }
 
+    /// 取得點位統計數據快照
+    #[must_use]
+    pub fn snapshot(&self) -> StatisticsSnapshot {
+        self.statistics.snapshot()
+    }
+
+    /// 最近時間窗內的成功率，參見 [`windowed::WindowedStats::success_rate()`]
+    ///
+    /// # 參數
+    /// - `window`：時間窗長度，最長為 [`StatsConfig::window`] 的保留範圍
+    #[must_use]
+    pub fn success_rate(&self, window: Duration) -> Option<f64> {
+        self.windowed.success_rate(window)
+    }
+
+    /// 最近時間窗內的平均回覆毫秒數，參見 [`windowed::WindowedStats::avg_response_ms()`]
+    ///
+    /// # 參數
+    /// - `window`：時間窗長度，最長為 [`StatsConfig::window`] 的保留範圍
+    #[must_use]
+    pub fn avg_response_ms(&self, window: Duration) -> Option<i64> {
+        self.windowed.avg_response_ms(window)
+    }
+
+    /// 滾動時間窗統計，參見 [`windowed`]
+    #[must_use]
+    pub const fn windowed(&self) -> &windowed::WindowedStats {
+        &self.windowed
+    }
+
+    /// 回覆毫秒數的中位數（p50），參見 [`Statistics::p50_response_ms()`]
+    #[must_use]
+    pub fn p50_response_ms(&self) -> i64 {
+        self.statistics.p50_response_ms()
+    }
+
+    /// 回覆毫秒數的第 95 百分位數（p95），參見 [`Statistics::p95_response_ms()`]
+    #[must_use]
+    pub fn p95_response_ms(&self) -> i64 {
+        self.statistics.p95_response_ms()
+    }
+
+    /// 回覆毫秒數的第 99 百分位數（p99），參見 [`Statistics::p99_response_ms()`]
+    #[must_use]
+    pub fn p99_response_ms(&self) -> i64 {
+        self.statistics.p99_response_ms()
+    }
+
+    /// 最大回覆毫秒數，參見 [`Statistics::max_response_ms()`]
+    #[must_use]
+    pub fn max_response_ms(&self) -> i64 {
+        self.statistics.max_response_ms()
+    }
+
+    /// 取得回覆快取命中統計快照，參見 [`cache::ResponseCache::lookup()`]
+    #[must_use]
+    pub fn cache_snapshot(&self) -> cache::CacheStatsSnapshot {
+        self.cache.snapshot()
+    }
+
+    /// 記錄寫入成功，寫入不會計入輪詢的統計數據
+    ///
+    /// # 參數
+    /// - `response_ms`: 本次寫入所花費的毫秒數
+    pub fn record_write_success(&self, response_ms: i64) {
+        self.writes.record(true, response_ms);
+    }
+
+    /// 記錄寫入失敗
+    pub fn record_write_failure(&self) {
+        self.writes.record(false, 0);
+    }
+
+    /// 取得寫入統計快照，參見 [`command::execute()`]
+    #[must_use]
+    pub fn write_snapshot(&self) -> command::WriteStatsSnapshot {
+        self.writes.snapshot()
+    }
+
+    /// 設定點位標籤
+    ///
+    /// 同一個設備上的點位共用一份統計數據，標籤請以設備為單位設定
+    ///
+    /// # 參數
+    /// - `key`：標籤名稱，已存在時會以新的數值取代
+    /// - `value`：標籤數值
+    pub fn set_label(&self, key: impl Into<String>, value: impl Into<String>) {
+        self.labels
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key.into(), value.into());
+    }
+
+    /// 取得點位標籤的複本
+    #[must_use]
+    pub fn labels(&self) -> Labels {
+        self.labels
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn restore(
+        &self,
+        statistics: StatisticsSnapshot,
+        latency: &histogram::LatencyBuckets,
+        labels: Labels,
+    ) {
+        self.clear();
+        self.statistics
+            .totals
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .restore(statistics);
+        self.statistics.latency.restore(latency);
+        *self.labels.write().unwrap_or_else(PoisonError::into_inner) = labels;
+    }
+
+    fn target_snapshot(&self, address_number: &TargetAddressNumber) -> TargetStatsSnapshot {
+        TargetStatsSnapshot {
+            address_number: address_number.clone(),
+            labels: self.labels(),
+            statistics: self.snapshot(),
+            cache: self.cache_snapshot(),
+            writes: self.write_snapshot(),
+            latency: self.statistics.latency.buckets(),
+        }
+    }
+
     pub fn clear(&self) {
-        self.0
-            .failed_poll_count
-            .store(i64::default(), std::sync::atomic::Ordering::Relaxed);
-        self.0
-            .total_polling_count
-            .store(i64::default(), std::sync::atomic::Ordering::Relaxed);
-        self.0
-            .average_response_ms
-            .store(i64::default(), std::sync::atomic::Ordering::Relaxed);
+        self.cache.clear();
+        self.writes.clear();
+        self.statistics.latency.clear();
+        self.windowed.clear();
+        *self
+            .statistics
+            .totals
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Totals::default();
     }
 }
 
+#[expect(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn exponential_moving_average(average: i64, sample: i64, alpha: f64) -> i64 {
+    alpha
+        .mul_add(sample as f64 - average as f64, average as f64)
+        .round() as i64
+}
+
+/// 輪詢結果，參見 [`TargetStats::record()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// 請求成功
+    Success {
+        /// 本次請求所花費的毫秒數
+        response_ms: i64,
+    },
+    /// 請求失敗
+    Failure,
+}
+
 /// 統計數據
 ///
-/// 注意，本物件雖然實作 [`Send`] 和 [`Sync`] trait ，但因為 [Atomic 的特殊性](https://doc.rust-lang.org/stable/std/sync/atomic/index.html)，需要跨線程存取時，請利用 [`Arc`] 智慧指針
+/// 所有數值由同一個鎖保護，需要跨線程存取時，請利用 [`Arc`] 智慧指針
 #[derive(Debug, Default)]
 pub struct Statistics {
+    totals: Mutex<Totals>,
+    /// 回覆時間分布
+    latency: histogram::LatencyHistogram,
+}
+
+/// 以同一個鎖更新的統計數據
+#[derive(Debug, Default)]
+struct Totals {
+    /// 失敗的輪詢次數
+    failed_poll_count: i64,
+    /// 總輪詢次數
+    total_polling_count: i64,
+    /// 平均回覆毫秒數
+    average_response_ms: i64,
+    /// 成功的回覆毫秒數總和，用於 [`Averaging::Lifetime`]
+    response_sum: i64,
+    /// 最近的回覆毫秒數，用於 [`Averaging::Window`]
+    window: VecDeque<i64>,
+}
+
+impl Totals {
+    const fn success_count(&self) -> i64 {
+        self.total_polling_count - self.failed_poll_count
+    }
+
+    fn record(&mut self, outcome: Outcome, averaging: Averaging) {
+        let first_success = self.success_count() == 0;
+        self.total_polling_count += 1;
+        let Outcome::Success { response_ms } = outcome else {
+            self.failed_poll_count += 1;
+            return;
+        };
+
+        self.response_sum = self.response_sum.saturating_add(response_ms);
+        self.average_response_ms = match averaging {
+            Averaging::Lifetime => self.response_sum / self.success_count(),
+            Averaging::Ema { .. } if first_success => response_ms,
+            Averaging::Ema { alpha } => {
+                exponential_moving_average(self.average_response_ms, response_ms, alpha)
+            }
+            Averaging::Window { samples } => {
+                self.window.push_back(response_ms);
+                let excess = self.window.len().saturating_sub(samples.max(1));
+                self.window.drain(..excess);
+                self.window.iter().sum::<i64>()
+                    / i64::try_from(self.window.len()).unwrap_or(i64::MAX)
+            }
+        };
+    }
+
+    fn restore(&mut self, statistics: StatisticsSnapshot) {
+        *self = Self {
+            failed_poll_count: statistics.failed_poll_count,
+            total_polling_count: statistics.total_polling_count,
+            average_response_ms: statistics.average_response_ms,
+            response_sum: 0,
+            window: VecDeque::new(),
+        };
+        self.response_sum = self
+            .average_response_ms
+            .saturating_mul(self.success_count());
+    }
+}
+
+impl Statistics {
+    /// 加總多個點位的統計數據，平均回覆毫秒數以成功次數加權
+    fn aggregate<'a>(targets: impl IntoIterator<Item = &'a TargetStats>) -> Self {
+        let aggregated = Self::default();
+        let mut totals = Totals::default();
+        for target in targets {
+            aggregated.latency.merge(&target.statistics.latency);
+            let statistics = target.snapshot();
+            let success_count = statistics.total_polling_count - statistics.failed_poll_count;
+            totals.failed_poll_count += statistics.failed_poll_count;
+            totals.total_polling_count += statistics.total_polling_count;
+            totals.response_sum = totals
+                .response_sum
+                .saturating_add(statistics.average_response_ms * success_count);
+        }
+        totals.average_response_ms = totals
+            .response_sum
+            .checked_div(totals.success_count())
+            .unwrap_or_default();
+        *aggregated
+            .totals
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = totals;
+        aggregated
+    }
+
+    /// 取得統計數據快照
+    #[must_use]
+    pub fn snapshot(&self) -> StatisticsSnapshot {
+        let (failed_poll_count, total_polling_count, average_response_ms) = {
+            let totals = self.totals.lock().unwrap_or_else(PoisonError::into_inner);
+            (
+                totals.failed_poll_count,
+                totals.total_polling_count,
+                totals.average_response_ms,
+            )
+        };
+        StatisticsSnapshot {
+            failed_poll_count,
+            total_polling_count,
+            average_response_ms,
+            p50_response_ms: self.p50_response_ms(),
+            p95_response_ms: self.p95_response_ms(),
+            p99_response_ms: self.p99_response_ms(),
+            max_response_ms: self.max_response_ms(),
+        }
+    }
+
+    /// 回覆時間分布，參見 [`histogram`]
+    #[must_use]
+    pub const fn latency(&self) -> &histogram::LatencyHistogram {
+        &self.latency
+    }
+
+    /// 回覆毫秒數的中位數（p50），參見 [`histogram::LatencyHistogram::percentile()`]
+    #[must_use]
+    pub fn p50_response_ms(&self) -> i64 {
+        self.latency.percentile(0.5)
+    }
+
+    /// 回覆毫秒數的第 95 百分位數（p95），參見 [`histogram::LatencyHistogram::percentile()`]
+    #[must_use]
+    pub fn p95_response_ms(&self) -> i64 {
+        self.latency.percentile(0.95)
+    }
+
+    /// 回覆毫秒數的第 99 百分位數（p99），參見 [`histogram::LatencyHistogram::percentile()`]
+    #[must_use]
+    pub fn p99_response_ms(&self) -> i64 {
+        self.latency.percentile(0.99)
+    }
+
+    /// 最大回覆毫秒數
+    #[must_use]
+    pub fn max_response_ms(&self) -> i64 {
+        self.latency.max_ms()
+    }
+}
+
+/// 統計數據快照
+///
+/// 本 struct 由 [`Statistics::snapshot()`] 產生，為不含 Atomic 的純數值，可直接序列化後透過網路傳輸
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatisticsSnapshot {
     /// 失敗的輪詢次數
-    failed_poll_count: AtomicI64,
+    pub failed_poll_count: i64,
     /// 總輪詢次數
-    total_polling_count: AtomicI64,
+    pub total_polling_count: i64,
     /// 平均回覆毫秒數
-    average_response_ms: AtomicI64,
+    pub average_response_ms: i64,
+    /// 回覆毫秒數的中位數，參見 [`histogram`]
+    #[serde(default)]
+    pub p50_response_ms: i64,
+    /// 回覆毫秒數的第 95 百分位數
+    #[serde(default)]
+    pub p95_response_ms: i64,
+    /// 回覆毫秒數的第 99 百分位數
+    #[serde(default)]
+    pub p99_response_ms: i64,
+    /// 最大回覆毫秒數
+    #[serde(default)]
+    pub max_response_ms: i64,
 }
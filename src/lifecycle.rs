@@ -0,0 +1,306 @@
+//! 生命週期事件
+//!
+//! 嵌入本 crate 的主程式經常需要依連線的實際狀態安排後續工作（如就緒探針、分階段啟動），而不是以固定的等待時間猜測，
+//! 主程式在各個階段調用 [`LifecycleEvents`] 的對應 method 回報進度，需要的地方再以 [`LifecycleEvents::subscribe()`] 訂閱事件，
+//! 或以 [`LifecycleEvents::wait_for()`] 等待到達指定的 [`LifecyclePhase`] ：
+//!
+//! - [`LifecycleEvents::connection_initialized()`]：[`crate::Connection::init()`] 與 [`crate::Connection::init_targets()`] 完成後
+//! - [`LifecycleEvents::poll_cycle_completed()`]：連線的所有點位都已輪詢一次後
+//! - [`LifecycleEvents::connection_failed()`]：連線初始化或重新連線失敗後
+//! - [`LifecycleEvents::connection_recovered()`]：失敗的連線恢復後
+//!
+//! 所有宣告的連線都完成初始化時發佈 [`LifecycleKind::AllInitialized`] ，都完成第一輪輪詢時發佈 [`LifecycleKind::Ready`] ，
+//! 兩者都只會發佈一次；初始化失敗的連線會讓生命週期停留在 [`LifecyclePhase::Starting`] ，直到該連線完成初始化
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::lifecycle::{LifecycleEvents, LifecycleKind, LifecyclePhase};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let lifecycle = LifecycleEvents::new(["plc", "meter"]);
+//! let mut events = lifecycle.subscribe();
+//!
+//! lifecycle.connection_initialized("plc");
+//! lifecycle.connection_failed("meter", "連線逾時");
+//! assert_eq!(lifecycle.phase(), LifecyclePhase::Starting);
+//!
+//! lifecycle.connection_initialized("meter");
+//! lifecycle.poll_cycle_completed("plc");
+//! lifecycle.poll_cycle_completed("meter");
+//! lifecycle.wait_for(LifecyclePhase::Ready).await;
+//!
+//! let mut kinds = Vec::new();
+//! while let Ok(event) = events.try_recv() {
+//!     kinds.push(event.kind);
+//! }
+//! assert_eq!(kinds[1], LifecycleKind::ConnectionFailed { connection: "meter".to_owned(), reason: "連線逾時".to_owned() });
+//! assert_eq!(kinds[2], LifecycleKind::ConnectionRecovered { connection: "meter".to_owned() });
+//! assert_eq!(kinds[4], LifecycleKind::AllInitialized);
+//! assert_eq!(kinds.last(), Some(&LifecycleKind::Ready));
+//! assert!(lifecycle.failed_connections().is_empty());
+//! # }
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, PoisonError},
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, watch};
+
+use crate::event::DEFAULT_EVENT_CAPACITY;
+
+/// 生命週期階段
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecyclePhase {
+    /// 尚有連線未完成初始化
+    #[default]
+    Starting,
+    /// 所有連線都已完成初始化，但尚未完成第一輪輪詢
+    Initialized,
+    /// 所有連線都已完成第一輪輪詢
+    Ready,
+}
+
+/// 生命週期事件
+#[derive(Debug, Clone)]
+pub struct LifecycleEvent {
+    /// 事件發生時間
+    pub timestamp: SystemTime,
+    /// 事件內容
+    pub kind: LifecycleKind,
+}
+
+/// 生命週期事件內容
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LifecycleKind {
+    /// 連線完成初始化，每個連線只會發佈一次
+    ConnectionInitialized {
+        /// 連線識別名稱
+        connection: String,
+    },
+    /// 所有宣告的連線都已完成初始化
+    AllInitialized,
+    /// 連線完成第一輪輪詢，每個連線只會發佈一次
+    FirstPollCompleted {
+        /// 連線識別名稱
+        connection: String,
+    },
+    /// 所有宣告的連線都已完成第一輪輪詢
+    Ready,
+    /// 連線進入失敗狀態，恢復前不會重複發佈
+    ConnectionFailed {
+        /// 連線識別名稱
+        connection: String,
+        /// 失敗原因
+        reason: String,
+    },
+    /// 失敗的連線已恢復
+    ConnectionRecovered {
+        /// 連線識別名稱
+        connection: String,
+    },
+}
+
+#[derive(Debug, Default)]
+struct ConnectionProgress {
+    initialized: bool,
+    polled: bool,
+    failed: bool,
+}
+
+/// 生命週期事件
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份資料
+#[derive(Debug, Clone)]
+pub struct LifecycleEvents {
+    sender: broadcast::Sender<LifecycleEvent>,
+    phase: Arc<watch::Sender<LifecyclePhase>>,
+    connections: Arc<Mutex<BTreeMap<String, ConnectionProgress>>>,
+}
+
+impl LifecycleEvents {
+    /// 建立生命週期事件
+    ///
+    /// # 參數
+    /// - `connections`：所有連線的識別名稱，未宣告的連線在第一次回報時自動加入，但不會影響已經到達的階段
+    #[must_use]
+    pub fn new(connections: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            sender: broadcast::Sender::new(DEFAULT_EVENT_CAPACITY),
+            phase: Arc::new(watch::Sender::new(LifecyclePhase::Starting)),
+            connections: Arc::new(Mutex::new(
+                connections
+                    .into_iter()
+                    .map(|connection| (connection.into(), ConnectionProgress::default()))
+                    .collect(),
+            )),
+        }
+    }
+
+    /// 訂閱事件
+    ///
+    /// 只會收到訂閱後發佈的事件，訂閱前已到達的階段請以 [`LifecycleEvents::phase()`] 確認
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<LifecycleEvent> {
+        self.sender.subscribe()
+    }
+
+    /// 目前的生命週期階段
+    #[must_use]
+    pub fn phase(&self) -> LifecyclePhase {
+        *self.phase.borrow()
+    }
+
+    /// 等待到達指定的生命週期階段，已經到達時立即返回
+    pub async fn wait_for(&self, phase: LifecyclePhase) {
+        let mut receiver = self.phase.subscribe();
+        // 傳送端由本 struct 持有，不會在等待期間關閉
+        let _ = receiver.wait_for(|current| *current >= phase).await;
+    }
+
+    /// 目前處於失敗狀態的連線識別名稱
+    #[must_use]
+    pub fn failed_connections(&self) -> Vec<String> {
+        self.connections
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|(_, progress)| progress.failed)
+            .map(|(connection, _)| connection.clone())
+            .collect()
+    }
+
+    /// 回報連線完成初始化
+    ///
+    /// 連線原本處於失敗狀態時，會一併發佈 [`LifecycleKind::ConnectionRecovered`]
+    pub fn connection_initialized(&self, connection: &str) {
+        self.report(connection, |progress, kinds| {
+            recover(connection, progress, kinds);
+            if !progress.initialized {
+                progress.initialized = true;
+                kinds.push(LifecycleKind::ConnectionInitialized {
+                    connection: connection.to_owned(),
+                });
+            }
+        });
+    }
+
+    /// 回報連線完成一輪輪詢，連線尚未回報初始化時視為已完成初始化
+    pub fn poll_cycle_completed(&self, connection: &str) {
+        self.report(connection, |progress, kinds| {
+            if !progress.initialized {
+                progress.initialized = true;
+                kinds.push(LifecycleKind::ConnectionInitialized {
+                    connection: connection.to_owned(),
+                });
+            }
+            if !progress.polled {
+                progress.polled = true;
+                kinds.push(LifecycleKind::FirstPollCompleted {
+                    connection: connection.to_owned(),
+                });
+            }
+        });
+    }
+
+    /// 回報連線進入失敗狀態，已處於失敗狀態時不會重複發佈
+    pub fn connection_failed(&self, connection: &str, reason: impl Into<String>) {
+        let reason = reason.into();
+        self.report(connection, |progress, kinds| {
+            if !progress.failed {
+                progress.failed = true;
+                kinds.push(LifecycleKind::ConnectionFailed {
+                    connection: connection.to_owned(),
+                    reason,
+                });
+            }
+        });
+    }
+
+    /// 回報失敗的連線已恢復，連線不在失敗狀態時不會發佈
+    pub fn connection_recovered(&self, connection: &str) {
+        self.report(connection, |progress, kinds| {
+            recover(connection, progress, kinds);
+        });
+    }
+
+    fn report(
+        &self,
+        connection: &str,
+        update: impl FnOnce(&mut ConnectionProgress, &mut Vec<LifecycleKind>),
+    ) {
+        let kinds = apply(
+            &mut self
+                .connections
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+            &self.phase,
+            connection,
+            update,
+        );
+
+        let timestamp = SystemTime::now();
+        for kind in kinds {
+            let _ = self.sender.send(LifecycleEvent { timestamp, kind });
+        }
+    }
+}
+
+/// 更新連線進度，並在持有鎖期間推進階段，避免同時回報的連線重複發佈階段事件
+fn apply(
+    connections: &mut BTreeMap<String, ConnectionProgress>,
+    phase: &watch::Sender<LifecyclePhase>,
+    connection: &str,
+    update: impl FnOnce(&mut ConnectionProgress, &mut Vec<LifecycleKind>),
+) -> Vec<LifecycleKind> {
+    let mut kinds = Vec::new();
+    update(
+        connections.entry(connection.to_owned()).or_default(),
+        &mut kinds,
+    );
+    phase.send_if_modified(|phase| advance(connections, phase, &mut kinds));
+    kinds
+}
+
+fn recover(connection: &str, progress: &mut ConnectionProgress, kinds: &mut Vec<LifecycleKind>) {
+    if progress.failed {
+        progress.failed = false;
+        kinds.push(LifecycleKind::ConnectionRecovered {
+            connection: connection.to_owned(),
+        });
+    }
+}
+
+/// 依連線進度推進階段，並加入到達階段的事件
+///
+/// # 回傳值
+/// 階段是否有變化
+fn advance(
+    connections: &BTreeMap<String, ConnectionProgress>,
+    phase: &mut LifecyclePhase,
+    kinds: &mut Vec<LifecycleKind>,
+) -> bool {
+    let current = *phase;
+
+    if *phase == LifecyclePhase::Starting
+        && connections.values().all(|progress| progress.initialized)
+    {
+        *phase = LifecyclePhase::Initialized;
+        kinds.push(LifecycleKind::AllInitialized);
+    }
+    if *phase == LifecyclePhase::Initialized && connections.values().all(|progress| progress.polled)
+    {
+        *phase = LifecyclePhase::Ready;
+        kinds.push(LifecycleKind::Ready);
+    }
+
+    *phase != current
+}
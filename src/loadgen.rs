@@ -0,0 +1,337 @@
+//! 外部請求負載產生
+//!
+//! 規劃部署規模時，需要知道設備連線在大量外部請求下的表現，[`run_load()`] 模擬多個外部界面的使用者，
+//! 依 [`LoadProfile`] 的讀寫比例與到達間隔分佈，對設備連線（通常是以模擬器實作的 [`crate::Connection`]）送出請求，
+//! 並以本函式庫的統計型別回報結果：
+//!
+//! - 讀取與寫入的成功/失敗次數與平均回覆時間，參見 [`crate::StatisticsSnapshot`]
+//! - 排隊、預處理與設備存取各階段的耗時，參見 [`crate::timing`]
+//! - 延遲百分位數與吞吐量，參見 [`LoadReport`]
+//!
+//! 請求依排定的到達時間依序送出，前一個請求尚未完成時，後續的請求會開始排隊，排隊的時間會計入延遲，與實際部署的情況相同
+//!
+//! 讀取請求直接傳入 [`crate::Connection::request_process()`] ，寫入請求會先經過 [`crate::Connection::preprocess()`] ，
+//! `new_status` 為 [`LoadProfile::write_value`]
+//!
+//! # 範例
+//! ```rust
+//! # use std::{borrow::Cow, error::Error, time::Duration};
+//! # use device_state_exchange_lib::*;
+//! use device_state_exchange_lib::loadgen::{Arrival, LoadProfile, run_load};
+//!
+//! # #[derive(Debug, Clone)] struct Config;
+//! # impl ConnectionConfig for Config {}
+//! # #[derive(Debug, Clone)] struct Point;
+//! # impl Target for Point {}
+//! # #[derive(Debug, Clone)] struct Request;
+//! # impl DeviceStateRequest for Request {}
+//! # #[derive(Debug, Clone)] struct Response;
+//! # impl DeviceStateResponse for Response { fn to_value(&self) -> Result<Cow<'_, serde_json::Value>, value::ConversionError> { Ok(Cow::Owned(serde_json::Value::Null)) } }
+//! /// 以固定延遲回覆的模擬器
+//! struct Simulator;
+//!
+//! impl Connection for Simulator {
+//! #   const NAMES: &[&str] = &["Simulator"];
+//! #   type Config = Config;
+//! #   type Target = Point;
+//! #   type Request = Request;
+//! #   type Response = Response;
+//! #   type Result = ();
+//! #   async fn init(_: &Config) -> Result<ConnectionArtifact<Self>, ConnectionError> { Ok(ConnectionArtifact::new(Self, ConnectionStats::new("simulator", None))) }
+//! #   fn init_targets(&mut self, _: &mut ConnectionStats, _: Vec<Point>) -> ConnectionTargets<Request, ()> { ConnectionTargets(Vec::new()) }
+//! #   async fn reconnect(&mut self) -> Result<(), ConnectionError> { Ok(()) }
+//! #   async fn update_config(&mut self, _: &Config) -> Result<(), ConnectionError> { Ok(()) }
+//!     async fn request_process(&mut self, _: Request) -> Result<(Response, bool), ConnectionError> {
+//!         tokio::time::sleep(Duration::from_millis(1)).await;
+//!         Ok((Response, true))
+//!     }
+//! }
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let profile = LoadProfile {
+//!     clients: 4,
+//!     requests_per_client: 5,
+//!     arrival: Arrival::Poisson { mean_interval: Duration::from_millis(2) },
+//!     ..LoadProfile::default()
+//! };
+//! let requests = vec![Request, Request];
+//!
+//! let report = run_load(&mut Simulator, requests, &profile).await;
+//! assert_eq!(report.requests, 20);
+//! assert_eq!(report.reads.total_polling_count + report.writes.total_polling_count, 20);
+//! assert!(report.latency.p99 >= Duration::from_millis(1));
+//! # }
+//! ```
+
+use std::{collections::BTreeMap, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::time::Instant;
+
+use crate::{
+    Averaging, Connection, StatisticsSnapshot, TargetStats,
+    timing::{Stage, StageSnapshot, StageStats, StageTimings},
+};
+
+/// 請求到達間隔分佈
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "distribution", rename_all = "snake_case")]
+pub enum Arrival {
+    /// 固定間隔，各使用者的第一個請求會平均錯開
+    Constant {
+        /// 同一個使用者的請求間隔
+        #[serde(rename = "interval_ms", with = "crate::millis")]
+        interval: Duration,
+    },
+    /// 卜瓦松過程，間隔為指數分佈，模擬彼此獨立的使用者
+    Poisson {
+        /// 同一個使用者的平均請求間隔
+        #[serde(rename = "mean_interval_ms", with = "crate::millis")]
+        mean_interval: Duration,
+    },
+    /// 突發請求，每次同時送出多個請求，模擬儀表板同時更新多個點位
+    Burst {
+        /// 每次突發的請求數量，小於 1 時視為 1
+        size: u32,
+        /// 同一個使用者的突發間隔
+        #[serde(rename = "interval_ms", with = "crate::millis")]
+        interval: Duration,
+    },
+}
+
+impl Default for Arrival {
+    fn default() -> Self {
+        Self::Constant {
+            interval: Duration::from_millis(100),
+        }
+    }
+}
+
+/// 負載設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoadProfile {
+    /// 模擬的使用者數量
+    pub clients: u32,
+    /// 每個使用者送出的請求數量
+    pub requests_per_client: u32,
+    /// 讀取請求的權重
+    pub reads: u32,
+    /// 寫入請求的權重，與 `reads` 皆為 0 時只送出讀取請求
+    pub writes: u32,
+    /// 到達間隔分佈
+    pub arrival: Arrival,
+    /// 寫入請求的新狀態
+    pub write_value: Value,
+    /// 亂數種子，相同的種子會產生相同的請求順序
+    pub seed: u64,
+}
+
+impl Default for LoadProfile {
+    fn default() -> Self {
+        Self {
+            clients: 1,
+            requests_per_client: 100,
+            reads: 9,
+            writes: 1,
+            arrival: Arrival::default(),
+            write_value: Value::Null,
+            seed: 1,
+        }
+    }
+}
+
+/// 延遲百分位數，延遲包含排隊的時間
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    /// 中位數，序列化時以微秒數表示
+    #[serde(rename = "p50_us", with = "crate::micros")]
+    pub p50: Duration,
+    /// 第 90 百分位數，序列化時以微秒數表示
+    #[serde(rename = "p90_us", with = "crate::micros")]
+    pub p90: Duration,
+    /// 第 99 百分位數，序列化時以微秒數表示
+    #[serde(rename = "p99_us", with = "crate::micros")]
+    pub p99: Duration,
+    /// 最大值，序列化時以微秒數表示
+    #[serde(rename = "max_us", with = "crate::micros")]
+    pub max: Duration,
+}
+
+impl LatencyPercentiles {
+    fn from_samples(mut samples: Vec<Duration>) -> Self {
+        samples.sort_unstable();
+        let percentile = |percent: usize| {
+            samples
+                .get(samples.len().saturating_sub(1) * percent / 100)
+                .copied()
+                .unwrap_or_default()
+        };
+
+        Self {
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: samples.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// 負載測試結果
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LoadReport {
+    /// 送出的請求數量
+    pub requests: u64,
+    /// 從第一個請求排定的時間至最後一個請求完成的時間，序列化時以毫秒數表示
+    #[serde(rename = "elapsed_ms", with = "crate::millis")]
+    pub elapsed: Duration,
+    /// 每秒完成的請求數量
+    pub throughput: f64,
+    /// 延遲百分位數
+    pub latency: LatencyPercentiles,
+    /// 讀取請求統計
+    pub reads: StatisticsSnapshot,
+    /// 寫入請求統計
+    pub writes: StatisticsSnapshot,
+    /// 各處理階段的耗時統計
+    pub stages: BTreeMap<Stage, StageSnapshot>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Scheduled {
+    at: Duration,
+    write: bool,
+    target: usize,
+}
+
+/// 以負載設定對設備連線送出請求
+///
+/// # 參數
+/// - `connection`：設備連線
+/// - `requests`：可供選擇的請求，每次隨機選擇其中一個，可由 [`crate::ConnectionTargets`] 的點位複製，為空時不會送出任何請求
+/// - `profile`：負載設定
+pub async fn run_load<T: Connection>(
+    connection: &mut T,
+    requests: Vec<T::Request>,
+    profile: &LoadProfile,
+) -> LoadReport {
+    let schedule = schedule(profile, requests.len());
+    let reads = TargetStats::new(Averaging::Lifetime);
+    let writes = TargetStats::new(Averaging::Lifetime);
+    let stages = StageStats::default();
+    let mut latencies = Vec::with_capacity(schedule.len());
+    let started = Instant::now();
+
+    for scheduled in &schedule {
+        let arrival = started + scheduled.at;
+        tokio::time::sleep_until(arrival).await;
+
+        let began = Instant::now();
+        let mut timings = StageTimings::from_iter([(Stage::Queueing, began - arrival)]);
+        let request = dyn_clone::clone(&requests[scheduled.target]);
+        let request = if scheduled.write {
+            let request = connection
+                .preprocess(request, Some(profile.write_value.clone()))
+                .map_err(|error| error.to_string());
+            timings.add(Stage::Preprocess, began.elapsed());
+            request
+        } else {
+            Ok(request)
+        };
+
+        let io_started = Instant::now();
+        let succeeded = match request {
+            Ok(request) => connection.request_process(request).await.is_ok(),
+            Err(_) => false,
+        };
+        timings.add(Stage::DeviceIo, io_started.elapsed());
+
+        let statistics = if scheduled.write { &writes } else { &reads };
+        if succeeded {
+            statistics
+                .record_success(i64::try_from(timings.total().as_millis()).unwrap_or(i64::MAX));
+        } else {
+            statistics.record_failure();
+        }
+
+        latencies.push(timings.total());
+        stages.record(&timings);
+    }
+
+    let elapsed = started.elapsed();
+
+    LoadReport {
+        requests: schedule.len() as u64,
+        elapsed,
+        throughput: if elapsed.is_zero() {
+            0.0
+        } else {
+            f64::from(u32::try_from(schedule.len()).unwrap_or(u32::MAX)) / elapsed.as_secs_f64()
+        },
+        latency: LatencyPercentiles::from_samples(latencies),
+        reads: reads.snapshot(),
+        writes: writes.snapshot(),
+        stages: stages.snapshot(),
+    }
+}
+
+/// 依負載設定排定所有請求的到達時間、讀寫與點位，依到達時間排序
+fn schedule(profile: &LoadProfile, targets: usize) -> Vec<Scheduled> {
+    if targets == 0 {
+        return Vec::new();
+    }
+
+    let mut random = SplitMix64(profile.seed);
+    let weights = u64::from(profile.reads) + u64::from(profile.writes);
+    let mut schedule = Vec::new();
+
+    for client in 0..profile.clients {
+        let mut at = match profile.arrival {
+            Arrival::Constant { interval } => interval * client / profile.clients.max(1),
+            Arrival::Poisson { .. } | Arrival::Burst { .. } => Duration::ZERO,
+        };
+
+        for index in 0..profile.requests_per_client {
+            at += match profile.arrival {
+                Arrival::Constant { interval } if index > 0 => interval,
+                Arrival::Burst { size, interval } if index > 0 && index % size.max(1) == 0 => {
+                    interval
+                }
+                Arrival::Poisson { mean_interval } => {
+                    mean_interval.mul_f64(-(1.0 - random.next_f64()).ln())
+                }
+                Arrival::Constant { .. } | Arrival::Burst { .. } => Duration::ZERO,
+            };
+
+            schedule.push(Scheduled {
+                at,
+                write: weights > 0 && random.next_u64() % weights >= u64::from(profile.reads),
+                target: usize::try_from(random.next_u64() % targets as u64).unwrap_or_default(),
+            });
+        }
+    }
+
+    schedule.sort_by_key(|scheduled| scheduled.at);
+    schedule
+}
+
+/// 產生可重現請求順序的亂數
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    const fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// 介於 0（含）至 1（不含）的亂數
+    #[expect(clippy::cast_precision_loss)]
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+}
@@ -0,0 +1,1206 @@
+//! M-Bus 與無線 M-Bus 參考實作（需啟用 `mbus` feature）
+//!
+//! 讀取熱能表、水表、電表等儀表（EN 13757），[`MbusConnection`] 以 TCP 連線至 M-Bus 閘道器（透通的序列埠轉乙太網路設備）或無線 M-Bus 接收器，
+//! 依 [`MbusConfig::mode`] 運作：
+//!
+//! - 有線（[`MbusMode::Wired`]）：主站依序輪詢儀表，以主要位址（0 至 250）或次要位址（8 位數儀表編號）指定儀表，
+//!   儀表的資料記錄分成多個電報時會連續讀取並合併；同一個儀表的電報會保留更新間隔的一半，期間內的其他點位直接使用該電報
+//! - 無線（[`MbusMode::Wireless`]）：儀表定期主動廣播，接收器將收到的訊框（以長度位元組開頭、已移除 CRC）轉送至本連線，
+//!   點位使用該儀表最新的電報，超過 [`MbusConfig::stale_after`] 未更新時回傳 [`MbusError::Stale`]
+//!
+//! 加密的電報（模式 5 ，AES-128-CBC）需要在點位設定儀表的金鑰；其他設備可以直接使用 [`MbusClient`] 或 [`decode_wireless()`]
+//!
+//! 點位設定：
+//!
+//! | 欄位 | 說明 |
+//! | --- | --- |
+//! | `address` | 儀表位址，`primary:5` 為主要位址，`secondary:12345678` 為次要位址（儀表編號），無線 M-Bus 只能使用次要位址 |
+//! | `record` | 非必填，資料記錄的索引（數字），或物理量名稱（如 `energy` 、`volume`），參見 [`RecordSelector`]；未設定時點位的數值為整個電報 |
+//! | `storage` | 非必填，以物理量選擇資料記錄時的儲存編號，預設為 0（目前的數值） |
+//! | `tariff` | 非必填，以物理量選擇資料記錄時的費率，預設為 0 |
+//! | `key` | 非必填，儀表的 AES-128 金鑰，32 個十六進位字元 |
+//!
+//! 點位數值的格式參見 [`Telegram::to_value()`] 與 [`Record::to_value()`] ，選擇了資料記錄時為該記錄換算倍率後的數值
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{
+//!     TargetDefinition,
+//!     mbus::{MbusConfig, MbusMode, MbusRequest, MeterAddress, RecordSelector},
+//! };
+//! use serde_json::json;
+//!
+//! let config: MbusConfig = serde_json::from_value(json!({
+//!     "host": "10.0.0.30",
+//!     "port": 10001,
+//!     "update_interval_ms": 900000,
+//! }))
+//! .unwrap();
+//! assert_eq!(config.mode, MbusMode::Wired);
+//!
+//! let definition: TargetDefinition = serde_json::from_value(json!({
+//!     "name": "熱能",
+//!     "address": "secondary:12345678",
+//!     "record": "energy",
+//! }))
+//! .unwrap();
+//! let request = MbusRequest::parse(&definition).unwrap();
+//! assert_eq!(request.meter, MeterAddress::Secondary(12345678));
+//! assert_eq!(
+//!     request.record,
+//!     Some(RecordSelector::Quantity { quantity: "energy".to_owned(), storage: 0, tariff: 0 })
+//! );
+//! assert_eq!(request.meter.to_string(), "secondary:12345678");
+//! ```
+
+mod records;
+
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::{Debug, Display},
+    io::ErrorKind,
+    net::SocketAddr,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    net::{TcpStream, tcp::OwnedReadHalf},
+    sync::Notify,
+    task::JoinHandle,
+    time::Instant,
+};
+
+pub use self::records::{Function, Record, Telegram};
+use self::records::{Header, Parsed, decode_id, encode_id, malformed};
+use crate::{
+    Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+    ConnectionTargets, DeviceStateRequest, DeviceStateResponse, HashMap, InitedTarget,
+    RemoteAddress, Target, TargetDefinition,
+    definition::InvalidTarget,
+    fixture::FrameDecoder,
+    transport::{
+        frame::FrameReader,
+        tcp::{self, TcpEndpoint},
+    },
+    value::ConversionError,
+};
+
+/// 預設的更新間隔
+pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_mins(1);
+
+/// 預設的回覆逾時，M-Bus 常見的調變速率為 2400 ，長電報需要約 1 秒
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 多電報回覆的電報數量上限
+pub const MAX_TELEGRAMS: usize = 16;
+
+/// 單字元確認
+const ACK: u8 = 0xE5;
+/// 短訊框起始字元
+const SHORT_START: u8 = 0x10;
+/// 長訊框起始字元
+const LONG_START: u8 = 0x68;
+/// 訊框結束字元
+const STOP: u8 = 0x16;
+
+/// 初始化儀表（`SND_NKE`）
+const SND_NKE: u8 = 0x40;
+/// 傳送資料（`SND_UD`）
+const SND_UD: u8 = 0x53;
+/// 請求第 2 類資料（`REQ_UD2`），FCB 位元為 0x20
+const REQ_UD2: u8 = 0x5B;
+const FCB: u8 = 0x20;
+
+/// 以次要位址選擇的儀表
+const NETWORK_ADDRESS: u8 = 0xFD;
+
+/// 選擇次要位址
+const CI_SELECT: u8 = 0x52;
+/// 應用層：長標頭
+const CI_LONG_HEADER: u8 = 0x72;
+/// 應用層：沒有標頭
+const CI_NO_HEADER: u8 = 0x78;
+/// 應用層：短標頭
+const CI_SHORT_HEADER: u8 = 0x7A;
+/// 延伸鏈結層（ELL ，2 個位元組）
+const CI_EXTENDED_LINK: u8 = 0x8C;
+
+const fn default_update_interval() -> Duration {
+    DEFAULT_UPDATE_INTERVAL
+}
+
+const fn default_timeout() -> Duration {
+    DEFAULT_TIMEOUT
+}
+
+/// 運作模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MbusMode {
+    /// 有線 M-Bus ，經由閘道器輪詢儀表
+    #[default]
+    Wired,
+    /// 無線 M-Bus ，接收儀表主動廣播的電報
+    Wireless,
+}
+
+/// 連線參數
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MbusConfig {
+    /// 閘道器或接收器的位址，可經由代理伺服器連線
+    #[serde(flatten)]
+    pub endpoint: TcpEndpoint,
+    /// 運作模式，預設為 [`MbusMode::Wired`]
+    #[serde(default)]
+    pub mode: MbusMode,
+    /// 更新間隔，序列化時以毫秒數表示，預設為 [`DEFAULT_UPDATE_INTERVAL`]
+    #[serde(
+        rename = "update_interval_ms",
+        with = "crate::millis",
+        default = "default_update_interval"
+    )]
+    pub update_interval: Duration,
+    /// 回覆逾時，序列化時以毫秒數表示，預設為 [`DEFAULT_TIMEOUT`] ；無線 M-Bus 為等待第一個電報的時間
+    #[serde(
+        rename = "timeout_ms",
+        with = "crate::millis",
+        default = "default_timeout"
+    )]
+    pub timeout: Duration,
+    /// 無線 M-Bus 電報的有效時間，序列化時以毫秒數表示，未設定時不檢查
+    #[serde(rename = "stale_after_ms", with = "crate::millis::option", default)]
+    pub stale_after: Option<Duration>,
+    /// 最高重試次數，參見 [`ConnectionArtifact::max_retry_count`]
+    #[serde(default)]
+    pub max_retry_count: Option<u32>,
+}
+
+impl ConnectionConfig for MbusConfig {}
+
+/// 儀表位址
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MeterAddress {
+    /// 主要位址（0 至 250）
+    Primary(u8),
+    /// 次要位址（8 位數儀表編號）
+    Secondary(u32),
+}
+
+impl MeterAddress {
+    /// 解析 `primary:5` 或 `secondary:12345678` 格式的位址
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let (kind, address) = text.trim().split_once(':')?;
+        let address = address.trim();
+        match kind.trim() {
+            "primary" => address
+                .parse()
+                .ok()
+                .filter(|address| *address <= 250)
+                .map(Self::Primary),
+            "secondary"
+                if address.len() == 8 && address.bytes().all(|byte| byte.is_ascii_digit()) =>
+            {
+                address.parse().ok().map(Self::Secondary)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for MeterAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Primary(address) => write!(f, "primary:{address}"),
+            Self::Secondary(id) => write!(f, "secondary:{id:08}"),
+        }
+    }
+}
+
+/// 資料記錄的選擇方式
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RecordSelector {
+    /// 電報中的索引，多電報回覆依合併後的順序計算
+    Index(usize),
+    /// 第一個符合物理量、儲存編號與費率的瞬時值
+    Quantity {
+        /// 物理量，參見 [`Record::quantity`]
+        quantity: String,
+        /// 儲存編號
+        storage: u64,
+        /// 費率
+        tariff: u32,
+    },
+}
+
+impl RecordSelector {
+    /// 找出電報中符合的資料記錄
+    ///
+    /// # 回傳值
+    /// 資料記錄的索引，沒有符合的資料記錄時為 [`None`]
+    #[must_use]
+    pub fn find(&self, telegram: &Telegram) -> Option<usize> {
+        match self {
+            Self::Index(index) => (*index < telegram.records.len()).then_some(*index),
+            Self::Quantity {
+                quantity,
+                storage,
+                tariff,
+            } => telegram.records.iter().position(|record| {
+                record.quantity == *quantity
+                    && record.storage == *storage
+                    && record.tariff == *tariff
+                    && record.function == Function::Instantaneous
+            }),
+        }
+    }
+}
+
+impl Display for RecordSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Index(index) => write!(f, "#{index}"),
+            Self::Quantity {
+                quantity,
+                storage,
+                tariff,
+            } => write!(f, "{quantity}（儲存編號 {storage} ，費率 {tariff}）"),
+        }
+    }
+}
+
+/// 儀表的 AES-128 金鑰
+#[derive(Clone, PartialEq, Eq)]
+pub struct MeterKey([u8; 16]);
+
+impl MeterKey {
+    /// 解析 32 個十六進位字元的金鑰
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if text.len() != 32 || !text.is_ascii() {
+            return None;
+        }
+        let mut key = [0; 16];
+        for (byte, pair) in key.iter_mut().zip(text.as_bytes().chunks(2)) {
+            *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+        }
+        Some(Self(key))
+    }
+}
+
+impl From<[u8; 16]> for MeterKey {
+    fn from(key: [u8; 16]) -> Self {
+        Self(key)
+    }
+}
+
+impl Debug for MeterKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("MeterKey(***)")
+    }
+}
+
+/// 請求無法完成的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MbusError {
+    /// 電報中沒有符合的資料記錄
+    UnknownRecord {
+        /// 儀表編號
+        id: u32,
+        /// 資料記錄的選擇方式
+        record: RecordSelector,
+    },
+    /// 電報已加密，但點位沒有設定金鑰
+    MissingKey {
+        /// 儀表編號
+        id: u32,
+    },
+    /// 解密失敗，通常是金鑰錯誤
+    DecryptionFailed {
+        /// 儀表編號
+        id: u32,
+    },
+    /// 不支援的加密模式
+    UnsupportedEncryption {
+        /// 儀表編號
+        id: u32,
+        /// 加密模式
+        mode: u16,
+    },
+    /// 不支援的應用層格式（CI 欄位）
+    UnsupportedCi(u8),
+    /// 超過有效時間未收到儀表的電報
+    Stale {
+        /// 儀表編號
+        id: u32,
+    },
+}
+
+impl Display for MbusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownRecord { id, record } => {
+                write!(f, "儀表 {id:08} 的電報中沒有資料記錄 {record}")
+            }
+            Self::MissingKey { id } => write!(f, "儀表 {id:08} 的電報已加密，但沒有設定金鑰"),
+            Self::DecryptionFailed { id } => write!(f, "儀表 {id:08} 的電報解密失敗，請檢查金鑰"),
+            Self::UnsupportedEncryption { id, mode } => {
+                write!(f, "儀表 {id:08} 使用不支援的加密模式 {mode}")
+            }
+            Self::UnsupportedCi(ci) => write!(f, "不支援的應用層格式（CI = 0x{ci:02X}）"),
+            Self::Stale { id } => write!(f, "超過有效時間未收到儀表 {id:08} 的電報"),
+        }
+    }
+}
+
+impl Error for MbusError {}
+
+/// 點位
+#[derive(Debug, Clone)]
+pub struct MbusTarget(pub TargetDefinition);
+
+impl Target for MbusTarget {}
+
+/// 讀取請求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MbusRequest {
+    /// 儀表位址
+    pub meter: MeterAddress,
+    /// 資料記錄，為 [`None`] 時回覆整個電報
+    pub record: Option<RecordSelector>,
+    /// 儀表的金鑰
+    pub key: Option<MeterKey>,
+}
+
+impl DeviceStateRequest for MbusRequest {}
+
+impl MbusRequest {
+    /// 由通用點位定義轉換，欄位說明參見 [`crate::mbus`]
+    ///
+    /// # Errors
+    /// 位址、資料記錄或金鑰無效時回傳 [`InvalidTarget`]
+    pub fn parse(definition: &TargetDefinition) -> Result<Self, InvalidTarget> {
+        let invalid = |reason: String| InvalidTarget {
+            target: definition.name.clone(),
+            reason,
+        };
+        let number = |field: &str| {
+            definition.extra.get(field).map_or(Ok(0), |value| {
+                value
+                    .as_u64()
+                    .ok_or_else(|| invalid(format!("{field} 需為非負整數：{value}")))
+            })
+        };
+
+        let meter = MeterAddress::parse(&definition.address)
+            .ok_or_else(|| invalid(format!("無效的儀表位址「{}」", definition.address)))?;
+        let record = match definition.extra.get("record") {
+            None => None,
+            Some(Value::String(quantity)) => Some(RecordSelector::Quantity {
+                quantity: quantity.clone(),
+                storage: number("storage")?,
+                tariff: u32::try_from(number("tariff")?)
+                    .map_err(|_| invalid("tariff 超出範圍".to_owned()))?,
+            }),
+            Some(record) => Some(RecordSelector::Index(
+                record
+                    .as_u64()
+                    .and_then(|index| usize::try_from(index).ok())
+                    .ok_or_else(|| invalid(format!("record 需為索引或物理量名稱：{record}")))?,
+            )),
+        };
+        let key = definition
+            .extra
+            .get("key")
+            .map(|key| {
+                key.as_str()
+                    .and_then(MeterKey::parse)
+                    .ok_or_else(|| invalid("key 需為 32 個十六進位字元".to_owned()))
+            })
+            .transpose()?;
+
+        Ok(Self { meter, record, key })
+    }
+
+    /// 由電報產生回覆
+    fn respond(&self, telegram: Arc<Telegram>) -> Result<MbusResponse, ConnectionError> {
+        let record = self
+            .record
+            .as_ref()
+            .map(|record| {
+                record.find(&telegram).ok_or_else(|| {
+                    ConnectionError::custom(MbusError::UnknownRecord {
+                        id: telegram.id,
+                        record: record.clone(),
+                    })
+                })
+            })
+            .transpose()?;
+        Ok(MbusResponse { telegram, record })
+    }
+}
+
+/// 讀取回覆
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MbusResponse {
+    /// 儀表的電報
+    pub telegram: Arc<Telegram>,
+    /// 點位選擇的資料記錄索引
+    pub record: Option<usize>,
+}
+
+impl MbusResponse {
+    /// 點位選擇的資料記錄
+    #[must_use]
+    pub fn record(&self) -> Option<&Record> {
+        self.telegram.records.get(self.record?)
+    }
+}
+
+impl DeviceStateResponse for MbusResponse {
+    fn to_value(&self) -> Result<Cow<'_, Value>, ConversionError> {
+        Ok(self.record().map_or_else(
+            || Cow::Owned(self.telegram.to_value()),
+            |record| Cow::Borrowed(&record.value),
+        ))
+    }
+}
+
+/// 解碼無線 M-Bus 訊框
+///
+/// # 參數
+/// - `frame`：不含長度位元組與 CRC 的訊框，依序為控制欄位、製造商、位址、CI 欄位與應用層資料
+/// - `key`：儀表的金鑰，電報未加密時可為 [`None`]
+///
+/// # Errors
+/// 訊框無法解析時回傳 [`ConnectionError::Protocol`] ，加密相關的錯誤回傳包含 [`MbusError`] 的 [`ConnectionError::Custom`]
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::mbus::decode_wireless;
+/// use serde_json::json;
+///
+/// let frame = [
+///     0x44, 0x2D, 0x2C, 0x78, 0x56, 0x34, 0x12, 0x1B, 0x16, // 控制欄位、製造商 KAM 、編號 12345678 、版本、冷水表
+///     0x7A, 0x2A, 0x00, 0x00, 0x00, // 短標頭：存取編號、狀態、未加密
+///     0x04, 0x13, 0x39, 0x30, 0x00, 0x00, // 體積 12345 L
+/// ];
+/// let telegram = decode_wireless(&frame, None).unwrap();
+/// assert_eq!((telegram.id, telegram.manufacturer.as_str()), (12345678, "KAM"));
+/// assert_eq!(telegram.records[0].quantity, "volume");
+/// assert_eq!(telegram.records[0].value, json!(12.345));
+/// assert_eq!(telegram.to_value()["medium"], "cold_water");
+/// ```
+///
+/// 以內建的測試向量驗證：
+/// ```rust
+/// use device_state_exchange_lib::{mbus::decode_wireless, vectors};
+///
+/// let report = vectors::built_in_set("mbus/wireless").unwrap().verify(|frame, value| {
+///     match decode_wireless(frame, None) {
+///         Ok(telegram) if telegram.to_value() == *value => Ok(()),
+///         Err(_) if value.is_null() => Ok(()),
+///         Ok(telegram) => Err(format!("解析結果為 {}", telegram.to_value())),
+///         Err(error) => Err(format!("解析失敗：{error}")),
+///     }
+/// });
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+pub fn decode_wireless(frame: &[u8], key: Option<&MeterKey>) -> Result<Telegram, ConnectionError> {
+    let [
+        _control,
+        m0,
+        m1,
+        a0,
+        a1,
+        a2,
+        a3,
+        version,
+        medium,
+        ci,
+        rest @ ..,
+    ] = frame
+    else {
+        return Err(malformed());
+    };
+    let (mut ci, mut rest) = (*ci, rest);
+    if ci == CI_EXTENDED_LINK {
+        let [_, _, next, tail @ ..] = rest else {
+            return Err(malformed());
+        };
+        (ci, rest) = (*next, tail);
+    }
+    let link = Header {
+        id: decode_id(&[*a0, *a1, *a2, *a3]).ok_or_else(malformed)?,
+        manufacturer: u16::from_le_bytes([*m0, *m1]),
+        version: *version,
+        medium: *medium,
+        access_number: 0,
+        status: 0,
+        configuration: 0,
+    };
+    let (header, data) = match ci {
+        CI_SHORT_HEADER => {
+            let [access_number, status, c0, c1, data @ ..] = rest else {
+                return Err(malformed());
+            };
+            let header = Header {
+                access_number: *access_number,
+                status: *status,
+                configuration: u16::from_le_bytes([*c0, *c1]),
+                ..link
+            };
+            (header, data)
+        }
+        CI_LONG_HEADER if rest.len() >= 12 => (Header::long(&rest[..12])?, &rest[12..]),
+        CI_NO_HEADER => (link, rest),
+        ci => return Err(ConnectionError::custom(MbusError::UnsupportedCi(ci))),
+    };
+    decode(header, data, key)
+}
+
+/// 解密並解析資料記錄
+fn decode(
+    header: Header,
+    data: &[u8],
+    key: Option<&MeterKey>,
+) -> Result<Telegram, ConnectionError> {
+    let mut data = data.to_vec();
+    header.decrypt(&mut data, key.map(|key| &key.0))?;
+    Ok(header.telegram(Parsed::parse(&data)?))
+}
+
+/// 有線 M-Bus 的回覆
+enum Reply {
+    Ack,
+    /// 長訊框的控制欄位、位址、CI 欄位與資料
+    Long {
+        control: u8,
+        address: u8,
+        ci: u8,
+        data: Vec<u8>,
+    },
+}
+
+/// 有線 M-Bus 主站
+///
+/// 一次只處理一個請求，回覆逾時或內容不符時會捨棄緩衝區中殘留的資料
+///
+/// # 範例
+/// ```rust
+/// use std::time::Duration;
+/// use device_state_exchange_lib::mbus::{MbusClient, MeterAddress};
+/// use serde_json::json;
+/// use tokio::io::{AsyncReadExt, AsyncWriteExt};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let (master, mut meter) = tokio::io::duplex(256);
+/// // 模擬主要位址為 5 的熱能表
+/// tokio::spawn(async move {
+///     let mut request = [0; 5];
+///     meter.read_exact(&mut request).await.unwrap();
+///     assert_eq!(request, [0x10, 0x40, 0x05, 0x45, 0x16]); // SND_NKE
+///     meter.write_all(&[0xE5]).await.unwrap();
+///
+///     meter.read_exact(&mut request).await.unwrap();
+///     assert_eq!(request[..3], [0x10, 0x7B, 0x05]); // REQ_UD2
+///     let mut body = vec![0x08, 0x05, 0x72];
+///     body.extend([0x78, 0x56, 0x34, 0x12, 0x2D, 0x2C, 0x01, 0x04, 0x07, 0x00, 0x00, 0x00]);
+///     body.extend([0x04, 0x06, 0x39, 0x30, 0x00, 0x00]); // 能量 12345 kWh
+///     body.extend([0x02, 0x5A, 0xBC, 0x02]); // 供水溫度 70.0 °C
+///     let checksum = body.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+///     let mut frame = vec![0x68, body.len() as u8, body.len() as u8, 0x68];
+///     frame.extend(body);
+///     frame.extend([checksum, 0x16]);
+///     meter.write_all(&frame).await.unwrap();
+/// });
+///
+/// let mut client = MbusClient::new(master, Duration::from_secs(1));
+/// let telegram = client.read(MeterAddress::Primary(5), None).await.unwrap();
+/// assert_eq!(telegram.id, 12345678);
+/// assert_eq!(telegram.records[0].unit, Some("Wh"));
+/// assert_eq!(telegram.records[0].value, json!(12345000));
+/// assert_eq!(telegram.records[1].quantity, "flow_temperature");
+/// assert_eq!(telegram.records[1].value, json!(70.0));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MbusClient<T> {
+    stream: FrameReader<T>,
+    timeout: Duration,
+}
+
+impl<T: AsyncRead + AsyncWrite + Unpin> MbusClient<T> {
+    /// 建立主站
+    ///
+    /// # 參數
+    /// - `stream`：閘道器連線、序列埠或其他資料來源
+    /// - `timeout`：每個回覆的逾時
+    #[must_use]
+    pub fn new(stream: T, timeout: Duration) -> Self {
+        Self {
+            stream: FrameReader::new(stream),
+            timeout,
+        }
+    }
+
+    /// 讀取儀表的所有資料記錄
+    ///
+    /// 以主要位址讀取時先初始化儀表（`SND_NKE`），以次要位址讀取時先選擇儀表，之後以 `REQ_UD2` 讀取電報，
+    /// 儀表表示還有資料記錄時會繼續讀取，最多 [`MAX_TELEGRAMS`] 個電報
+    ///
+    /// # Errors
+    /// 逾時回傳 [`ConnectionError::Timeout`] ，讀寫失敗回傳 [`ConnectionError::Io`] ，
+    /// 不符合通訊協定的回覆回傳 [`ConnectionError::Protocol`] ，加密相關的錯誤回傳包含 [`MbusError`] 的 [`ConnectionError::Custom`]
+    pub async fn read(
+        &mut self,
+        meter: MeterAddress,
+        key: Option<&MeterKey>,
+    ) -> Result<Telegram, ConnectionError> {
+        let address = match meter {
+            MeterAddress::Primary(address) => {
+                self.expect_ack(&short_frame(SND_NKE, address)).await?;
+                address
+            }
+            MeterAddress::Secondary(id) => {
+                let mut selection = Vec::with_capacity(8);
+                selection.extend_from_slice(&encode_id(id));
+                // 製造商、版本與媒介不限
+                selection.extend_from_slice(&[0xFF; 4]);
+                self.expect_ack(&long_frame(SND_UD, NETWORK_ADDRESS, CI_SELECT, &selection))
+                    .await?;
+                NETWORK_ADDRESS
+            }
+        };
+
+        // SND_NKE 與選擇之後的第一個請求 FCB 為 1
+        let mut fcb = FCB;
+        let mut telegram: Option<Telegram> = None;
+        for _ in 0..MAX_TELEGRAMS {
+            let Reply::Long {
+                control,
+                address: reply_address,
+                ci,
+                data,
+            } = self.transact(&short_frame(REQ_UD2 | fcb, address)).await?
+            else {
+                return Err(malformed());
+            };
+            fcb ^= FCB;
+            // RSP_UD ，忽略 ACD 與 DFC 位元
+            if control & 0xCF != 0x08 || (address != NETWORK_ADDRESS && reply_address != address) {
+                return Err(malformed());
+            }
+            if ci != CI_LONG_HEADER {
+                return Err(ConnectionError::custom(MbusError::UnsupportedCi(ci)));
+            }
+            let header = Header::long(data.get(..12).ok_or_else(malformed)?)?;
+            if let MeterAddress::Secondary(id) = meter
+                && header.id != id
+            {
+                return Err(ConnectionError::Protocol(format!(
+                    "回覆的儀表編號 {:08} 與請求不符",
+                    header.id
+                )));
+            }
+
+            let mut body = data[12..].to_vec();
+            header.decrypt(&mut body, key.map(|key| &key.0))?;
+            let records = Parsed::parse(&body)?;
+            let more = records.more;
+            match &mut telegram {
+                Some(telegram) => {
+                    telegram.records.extend(records.records);
+                    telegram.manufacturer_data = records.manufacturer_data;
+                }
+                None => telegram = Some(header.telegram(records)),
+            }
+            if !more {
+                break;
+            }
+        }
+        telegram.ok_or_else(malformed)
+    }
+
+    async fn expect_ack(&mut self, frame: &[u8]) -> Result<(), ConnectionError> {
+        match self.transact(frame).await? {
+            Reply::Ack => Ok(()),
+            Reply::Long { .. } => Err(malformed()),
+        }
+    }
+
+    /// 送出訊框並等待回覆
+    async fn transact(&mut self, frame: &[u8]) -> Result<Reply, ConnectionError> {
+        let result = tokio::time::timeout(self.timeout, self.exchange(frame)).await;
+        let result = match result {
+            Ok(result) => result,
+            Err(elapsed) => Err(elapsed.into()),
+        };
+        if result.is_err() {
+            self.stream.discard();
+        }
+        result
+    }
+
+    async fn exchange(&mut self, frame: &[u8]) -> Result<Reply, ConnectionError> {
+        self.stream.discard();
+        let stream = self.stream.get_mut();
+        stream.write_all(frame).await?;
+        stream.flush().await?;
+
+        match self.stream.read_frame(1).await?[0] {
+            ACK => Ok(Reply::Ack),
+            LONG_START => {
+                let header = self.stream.read_frame(3).await?;
+                let length = usize::from(header[0]);
+                if header[0] != header[1] || header[2] != LONG_START || length < 3 {
+                    return Err(malformed());
+                }
+                parse_long(&self.stream.read_frame(length + 2).await?)
+            }
+            start => Err(ConnectionError::Protocol(format!(
+                "無法解析的 M-Bus 訊框起始字元 0x{start:02X}"
+            ))),
+        }
+    }
+}
+
+/// 解析長訊框標頭之後的內容（控制欄位至結束字元），並驗證檢查碼
+fn parse_long(body: &[u8]) -> Result<Reply, ConnectionError> {
+    let [control, address, ci, .., sum, STOP] = body else {
+        return Err(ConnectionError::Protocol("M-Bus 檢查碼錯誤".to_owned()));
+    };
+    let length = body.len() - 2;
+    if *sum != checksum(&body[..length]) {
+        return Err(ConnectionError::Protocol("M-Bus 檢查碼錯誤".to_owned()));
+    }
+    Ok(Reply::Long {
+        control: *control,
+        address: *address,
+        ci: *ci,
+        data: body[3..length].to_vec(),
+    })
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, byte| sum.wrapping_add(*byte))
+}
+
+const fn short_frame(control: u8, address: u8) -> [u8; 5] {
+    [
+        SHORT_START,
+        control,
+        address,
+        control.wrapping_add(address),
+        STOP,
+    ]
+}
+
+fn long_frame(control: u8, address: u8, ci: u8, data: &[u8]) -> Vec<u8> {
+    let length = u8::try_from(data.len() + 3).unwrap_or(u8::MAX);
+    let mut frame = Vec::with_capacity(data.len() + 9);
+    frame.extend_from_slice(&[LONG_START, length, length, LONG_START, control, address, ci]);
+    frame.extend_from_slice(data);
+    frame.push(checksum(&frame[4..]));
+    frame.push(STOP);
+    frame
+}
+
+/// 無線 M-Bus 收到的訊框
+#[derive(Debug, Clone)]
+struct Received {
+    frame: Arc<[u8]>,
+    at: Instant,
+}
+
+/// 各儀表最新的無線 M-Bus 訊框，由背景工作更新
+#[derive(Debug, Default)]
+struct Frames {
+    latest: Mutex<HashMap<u32, Received>>,
+    connected: AtomicBool,
+    notify: Notify,
+}
+
+impl Frames {
+    fn received(&self, frame: &[u8]) {
+        // 以鏈結層位址的儀表編號區分儀表
+        let Some(id) = frame.get(3..7).and_then(decode_id) else {
+            return;
+        };
+        self.latest
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                id,
+                Received {
+                    frame: frame.into(),
+                    at: Instant::now(),
+                },
+            );
+        self.notify.notify_waiters();
+    }
+
+    fn get(&self, id: u32) -> Option<Received> {
+        self.latest
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&id)
+            .cloned()
+    }
+}
+
+/// 背景工作，持續接收訊框，連線中斷時結束
+async fn drive(mut reader: FrameReader<OwnedReadHalf>, frames: Arc<Frames>) {
+    loop {
+        let Ok(length) = reader.read_frame(1).await else {
+            break;
+        };
+        let Ok(frame) = reader.read_frame(usize::from(length[0])).await else {
+            break;
+        };
+        frames.received(&frame);
+    }
+    frames.connected.store(false, Ordering::Relaxed);
+    frames.notify.notify_waiters();
+}
+
+/// 與無線 M-Bus 接收器的一次連線
+#[derive(Debug)]
+struct Session {
+    task: JoinHandle<()>,
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// 與閘道器或接收器的連線
+#[derive(Debug)]
+enum Link {
+    Wired(MbusClient<TcpStream>),
+    /// 背景工作在連線關閉時隨之結束
+    Wireless(#[expect(dead_code)] Session),
+}
+
+/// M-Bus 連線
+pub struct MbusConnection {
+    config: MbusConfig,
+    link: Option<Link>,
+    frames: Arc<Frames>,
+    /// 有線 M-Bus 最近讀取的電報
+    telegrams: HashMap<MeterAddress, (Arc<Telegram>, Instant)>,
+    remote_address: RemoteAddress,
+    rejected: Vec<InvalidTarget>,
+}
+
+impl MbusConnection {
+    /// 初始化點位時被略過的點位
+    #[must_use]
+    pub fn rejected_targets(&self) -> &[InvalidTarget] {
+        &self.rejected
+    }
+
+    async fn open(
+        config: &MbusConfig,
+        frames: &Arc<Frames>,
+    ) -> Result<(Link, Option<SocketAddr>), ConnectionError> {
+        let stream = tokio::time::timeout(config.timeout, tcp::connect(&config.endpoint)).await??;
+        stream.set_nodelay(true)?;
+        let peer = stream.peer_addr().ok();
+        let link = match config.mode {
+            MbusMode::Wired => Link::Wired(MbusClient::new(stream, config.timeout)),
+            MbusMode::Wireless => {
+                let (read, write) = stream.into_split();
+                frames.connected.store(true, Ordering::Relaxed);
+                let frames = Arc::clone(frames);
+                let task = tokio::spawn(async move {
+                    // 保留寫入端，避免接收器視為連線結束
+                    let _write = write;
+                    drive(FrameReader::new(read), frames).await;
+                });
+                Link::Wireless(Session { task })
+            }
+        };
+        Ok((link, peer))
+    }
+
+    /// 取得連線，連線已中斷時先重新連線
+    async fn link(&mut self) -> Result<&mut Link, ConnectionError> {
+        if matches!(self.link, Some(Link::Wireless(_)))
+            && !self.frames.connected.load(Ordering::Relaxed)
+        {
+            self.close();
+        }
+        if self.link.is_none() {
+            let (link, peer) = Self::open(&self.config, &self.frames).await?;
+            self.remote_address.set(peer);
+            self.link = Some(link);
+        }
+        self.link
+            .as_mut()
+            .ok_or_else(|| std::io::Error::from(ErrorKind::NotConnected).into())
+    }
+
+    fn close(&mut self) {
+        self.link = None;
+        self.frames.connected.store(false, Ordering::Relaxed);
+        self.remote_address.set(None);
+    }
+
+    /// 讀取有線 M-Bus 儀表，同一個儀表的電報保留更新間隔的一半
+    ///
+    /// # 回傳值
+    /// 電報，以及是否實際讀取了儀表
+    async fn read_wired(
+        &mut self,
+        request: &MbusRequest,
+    ) -> Result<(Arc<Telegram>, bool), ConnectionError> {
+        let ttl = self.config.update_interval / 2;
+        if let Some((telegram, at)) = self.telegrams.get(&request.meter)
+            && at.elapsed() < ttl
+        {
+            return Ok((Arc::clone(telegram), false));
+        }
+
+        let Link::Wired(client) = self.link().await? else {
+            return Err(std::io::Error::from(ErrorKind::NotConnected).into());
+        };
+        let result = client.read(request.meter, request.key.as_ref()).await;
+        if let Err(ConnectionError::Io(_)) = &result {
+            self.close();
+        }
+        let telegram = Arc::new(result?);
+        self.telegrams
+            .insert(request.meter, (Arc::clone(&telegram), Instant::now()));
+        Ok((telegram, true))
+    }
+
+    /// 取得無線 M-Bus 儀表最新的電報，尚未收到時等待第一個電報
+    async fn read_wireless(&mut self, request: &MbusRequest) -> Result<Telegram, ConnectionError> {
+        let MeterAddress::Secondary(id) = request.meter else {
+            return Err(ConnectionError::InvalidConfig(
+                "無線 M-Bus 只能以次要位址指定儀表".to_owned(),
+            ));
+        };
+        self.link().await?;
+
+        let frames = &self.frames;
+        let received = tokio::time::timeout(self.config.timeout, async {
+            loop {
+                let notified = frames.notify.notified();
+                if let Some(received) = frames.get(id) {
+                    return Ok(received);
+                }
+                if !frames.connected.load(Ordering::Relaxed) {
+                    return Err(std::io::Error::from(ErrorKind::NotConnected));
+                }
+                notified.await;
+            }
+        })
+        .await??;
+        if self
+            .config
+            .stale_after
+            .is_some_and(|stale_after| received.at.elapsed() > stale_after)
+        {
+            return Err(ConnectionError::custom(MbusError::Stale { id }));
+        }
+
+        decode_wireless(&received.frame, request.key.as_ref())
+    }
+}
+
+impl Connection for MbusConnection {
+    const NAMES: &[&str] = &["Mbus", "WirelessMbus"];
+    type Config = MbusConfig;
+    type Target = MbusTarget;
+    type Request = MbusRequest;
+    type Response = MbusResponse;
+    type Result = ();
+
+    async fn init(config: &MbusConfig) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+        let frames = Arc::new(Frames::default());
+        let (link, peer) = Self::open(config, &frames).await?;
+        let statistics = ConnectionStats::new(
+            format!("{}:{}", config.endpoint.host, config.endpoint.port),
+            None,
+        );
+        statistics.remote_address.set(peer);
+
+        let artifact = ConnectionArtifact::new(
+            Self {
+                config: config.clone(),
+                link: Some(link),
+                frames,
+                telegrams: HashMap::default(),
+                remote_address: statistics.remote_address.clone(),
+                rejected: Vec::new(),
+            },
+            statistics,
+        )
+        .update_every(config.update_interval)
+        .timeout_after(config.timeout);
+
+        Ok(match config.max_retry_count {
+            Some(count) => artifact.retry_up_to(count),
+            None => artifact,
+        })
+    }
+
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<MbusTarget>,
+    ) -> ConnectionTargets<MbusRequest, ()> {
+        let mut inited = Vec::with_capacity(targets.len());
+        for MbusTarget(definition) in targets {
+            let request = MbusRequest::parse(&definition).and_then(|request| {
+                if self.config.mode == MbusMode::Wireless
+                    && let MeterAddress::Primary(_) = request.meter
+                {
+                    return Err(InvalidTarget {
+                        target: definition.name.clone(),
+                        reason: "無線 M-Bus 只能以次要位址指定儀表".to_owned(),
+                    });
+                }
+                Ok(request)
+            });
+            match request {
+                Ok(request) => inited.push(InitedTarget {
+                    name: definition.name,
+                    request,
+                    result: (),
+                    default_status: definition.default_status,
+                    auto_refresh: definition.auto_refresh,
+                    refresh_interval: None,
+                    keep_raw_frames: None,
+                    group: None,
+                    safe_state: None,
+                    array: None,
+                    change: None,
+                    statistics: Some(connection_statistics.insert_target(definition.device)),
+                }),
+                Err(error) => self.rejected.push(error),
+            }
+        }
+        ConnectionTargets(inited)
+    }
+
+    async fn request_process(
+        &mut self,
+        request: MbusRequest,
+    ) -> Result<(MbusResponse, bool), ConnectionError> {
+        let (telegram, wait) = match self.config.mode {
+            MbusMode::Wired => self.read_wired(&request).await?,
+            MbusMode::Wireless => (Arc::new(self.read_wireless(&request).await?), true),
+        };
+        Ok((request.respond(telegram)?, wait))
+    }
+
+    async fn disconnect(&mut self) -> Result<(), ConnectionError> {
+        self.close();
+        Ok(())
+    }
+
+    async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+        self.close();
+        self.link().await.map(|_| ())
+    }
+
+    async fn update_config(&mut self, new_config: &MbusConfig) -> Result<(), ConnectionError> {
+        self.close();
+        self.config = new_config.clone();
+        self.telegrams.clear();
+        self.frames
+            .latest
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        self.link().await.map(|_| ())
+    }
+}
+
+/// 以記錄下來的訊框解碼點位
+///
+/// `frame` 為有線 M-Bus 的 `RSP_UD` 長訊框（長標頭），或是以長度位元組開頭、已移除 CRC 的無線 M-Bus 訊框，只支援未加密的電報；
+/// `target` 為點位的 `record`（資料記錄的索引或物理量名稱，儲存編號與費率為 0），空字串表示整個電報
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::{fixture::Fixture, mbus::MbusConnection};
+///
+/// let fixture = Fixture::from_json(r#"{
+///     "connection": "Mbus",
+///     "cases": [
+///         { "name": "有線，能量", "target": "energy", "frame": "68 19 19 68 08 05 72 78563412 2d2c 01 04 07 00 0000 04 06 39300000 02 5a bc02 85 16", "expected": 12345000 },
+///         { "name": "有線，索引", "target": "1", "frame": "68 19 19 68 08 05 72 78563412 2d2c 01 04 07 00 0000 04 06 39300000 02 5a bc02 85 16", "expected": 70.0 },
+///         { "name": "無線", "target": "volume", "frame": "14 44 2d2c 78563412 1b 16 7a 2a 00 0000 04 13 39300000", "expected": 12.345 },
+///         { "name": "沒有資料記錄", "target": "power", "frame": "14 44 2d2c 78563412 1b 16 7a 2a 00 0000 04 13 39300000" },
+///         { "name": "檢查碼錯誤", "target": "energy", "frame": "68 19 19 68 08 05 72 78563412 2d2c 01 04 07 00 0000 04 06 39300000 02 5a bc02 86 16" }
+///     ]
+/// }"#).unwrap();
+///
+/// let report = fixture.run::<MbusConnection>();
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+impl FrameDecoder for MbusConnection {
+    fn decode_frame(target: &str, frame: &[u8]) -> Result<MbusResponse, Box<dyn Error>> {
+        let telegram = match frame {
+            [LONG_START, length, repeated, LONG_START, body @ ..]
+                if length == repeated && usize::from(*length) + 2 == body.len() =>
+            {
+                let Reply::Long {
+                    control, ci, data, ..
+                } = parse_long(body)?
+                else {
+                    return Err(malformed().into());
+                };
+                if control & 0xCF != 0x08 {
+                    return Err(malformed().into());
+                }
+                if ci != CI_LONG_HEADER {
+                    return Err(ConnectionError::custom(MbusError::UnsupportedCi(ci)).into());
+                }
+                decode(
+                    Header::long(data.get(..12).ok_or_else(malformed)?)?,
+                    &data[12..],
+                    None,
+                )?
+            }
+            [length, frame @ ..] if usize::from(*length) == frame.len() => {
+                decode_wireless(frame, None)?
+            }
+            _ => return Err(malformed().into()),
+        };
+
+        let record = match target {
+            "" => None,
+            target => Some(target.parse().map_or_else(
+                |_| RecordSelector::Quantity {
+                    quantity: target.to_owned(),
+                    storage: 0,
+                    tariff: 0,
+                },
+                RecordSelector::Index,
+            )),
+        };
+        let request = MbusRequest {
+            meter: MeterAddress::Secondary(telegram.id),
+            record,
+            key: None,
+        };
+        Ok(request.respond(Arc::new(telegram))?)
+    }
+}
@@ -0,0 +1,724 @@
+//! M-Bus 應用層（EN 13757-3）：資料記錄的 DIF/VIF 解碼與加密
+
+use std::fmt::{Display, Write};
+
+use cbc::cipher::{BlockDecryptMut, KeyIvInit, block_padding::NoPadding};
+use serde_json::{Map, Value, json};
+
+use crate::{ConnectionError, value};
+
+/// 每個資料記錄的 DIFE 與 VIFE 數量上限（EN 13757-3）
+const MAX_EXTENSIONS: usize = 10;
+
+/// DIF ：後面還有資料記錄，需要再次讀取（多電報回覆）
+const DIF_MORE_RECORDS: u8 = 0x1F;
+
+/// DIF ：填充位元組
+const DIF_IDLE_FILLER: u8 = 0x2F;
+
+pub(super) fn malformed() -> ConnectionError {
+    ConnectionError::Protocol("無法解析的 M-Bus 電報".to_owned())
+}
+
+/// 資料記錄的數值種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Function {
+    /// 瞬時值
+    Instantaneous,
+    /// 最大值
+    Maximum,
+    /// 最小值
+    Minimum,
+    /// 錯誤狀態期間的數值
+    Error,
+}
+
+impl Function {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Instantaneous => "instantaneous",
+            Self::Maximum => "maximum",
+            Self::Minimum => "minimum",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl Display for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// 資料記錄
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    /// 數值種類
+    pub function: Function,
+    /// 儲存編號，0 為目前的數值，其他為歷史數值（如月結值）
+    pub storage: u64,
+    /// 費率
+    pub tariff: u32,
+    /// 子單元
+    pub subunit: u16,
+    /// 物理量，如 `energy` 、`volume` 、`flow_temperature` ，VIF 為純文字時為該文字
+    pub quantity: String,
+    /// 單位，沒有單位時為 [`None`]
+    pub unit: Option<&'static str>,
+    /// 已依 VIF 換算倍率的數值，時間點為 ISO 8601 字串
+    pub value: Value,
+}
+
+impl Record {
+    /// 轉換為 JSON 物件
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        json!({
+            "quantity": self.quantity,
+            "unit": self.unit,
+            "value": self.value,
+            "function": self.function.as_str(),
+            "storage": self.storage,
+            "tariff": self.tariff,
+            "subunit": self.subunit,
+        })
+    }
+}
+
+/// 電報
+///
+/// 多電報回覆的資料記錄會依序合併為一個電報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Telegram {
+    /// 儀表編號（次要位址），如 `12345678`
+    pub id: u32,
+    /// 製造商代碼，如 `KAM`
+    pub manufacturer: String,
+    /// 版本
+    pub version: u8,
+    /// 媒介（儀表類型），參見 [`Telegram::medium_name()`]
+    pub medium: u8,
+    /// 存取編號，每次傳送遞增
+    pub access_number: u8,
+    /// 狀態位元組，0 表示正常
+    pub status: u8,
+    /// 資料記錄
+    pub records: Vec<Record>,
+    /// 製造商自訂資料（DIF 0x0F 之後的內容）
+    pub manufacturer_data: Vec<u8>,
+}
+
+impl Telegram {
+    /// 媒介名稱，未知的媒介為 [`None`]
+    #[must_use]
+    pub const fn medium_name(&self) -> Option<&'static str> {
+        Some(match self.medium {
+            0x00 => "other",
+            0x01 => "oil",
+            0x02 => "electricity",
+            0x03 => "gas",
+            0x04 => "heat",
+            0x05 => "steam",
+            0x06 => "warm_water",
+            0x07 => "water",
+            0x08 => "heat_cost_allocator",
+            0x09 => "compressed_air",
+            0x0A | 0x0B => "cooling",
+            0x0C => "heat_inlet",
+            0x0D => "heat_cooling",
+            0x0E => "bus",
+            0x15 => "hot_water",
+            0x16 => "cold_water",
+            0x17 => "dual_water",
+            0x18 => "pressure",
+            0x19 => "ad_converter",
+            0x1A => "smoke_detector",
+            0x1B => "room_sensor",
+            0x1C => "gas_detector",
+            0x20 => "breaker",
+            0x21 => "valve",
+            0x25 => "customer_unit",
+            0x28 => "waste_water",
+            0x29 => "garbage",
+            0x31 => "radio_converter",
+            _ => return None,
+        })
+    }
+
+    /// 轉換為 JSON 物件
+    ///
+    /// 包含 `id` 、`manufacturer` 、`version` 、`medium` 、`access_number` 、`status` 與 `records` ，
+    /// 各資料記錄的格式參見 [`Record::to_value()`]
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        let mut object = Map::new();
+        object.insert("id".to_owned(), json!(format!("{:08}", self.id)));
+        object.insert("manufacturer".to_owned(), json!(self.manufacturer));
+        object.insert("version".to_owned(), json!(self.version));
+        object.insert(
+            "medium".to_owned(),
+            self.medium_name()
+                .map_or_else(|| json!(self.medium), |name| json!(name)),
+        );
+        object.insert("access_number".to_owned(), json!(self.access_number));
+        object.insert("status".to_owned(), json!(self.status));
+        object.insert(
+            "records".to_owned(),
+            self.records.iter().map(Record::to_value).collect(),
+        );
+        Value::Object(object)
+    }
+}
+
+/// 解碼 BCD 編碼的儀表編號（低位元組在前）
+pub(super) fn decode_id(bytes: &[u8]) -> Option<u32> {
+    bytes.iter().rev().try_fold(0u32, |id, byte| {
+        let (high, low) = (byte >> 4, byte & 0x0F);
+        (high < 10 && low < 10).then(|| id * 100 + u32::from(high) * 10 + u32::from(low))
+    })
+}
+
+/// 編碼為 BCD 的儀表編號（低位元組在前）
+pub(super) fn encode_id(id: u32) -> [u8; 4] {
+    let mut bytes = [0; 4];
+    let mut rest = id;
+    for byte in &mut bytes {
+        let low = rest % 10;
+        let high = (rest / 10) % 10;
+        rest /= 100;
+        *byte = u8::try_from(high << 4 | low).unwrap_or_default();
+    }
+    bytes
+}
+
+/// 解碼製造商代碼
+pub(super) fn decode_manufacturer(code: u16) -> String {
+    [10, 5, 0]
+        .into_iter()
+        .map(|shift| char::from(b'@' + u8::try_from((code >> shift) & 0x1F).unwrap_or_default()))
+        .collect()
+}
+
+/// 資料區段的讀取器
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8], ConnectionError> {
+        if self.data.len() < length {
+            return Err(malformed());
+        }
+        let (taken, rest) = self.data.split_at(length);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, ConnectionError> {
+        Ok(self.take(1)?[0])
+    }
+}
+
+/// 以小端序解碼有號整數
+fn integer(bytes: &[u8]) -> i64 {
+    let mut value = [0; 8];
+    value[..bytes.len()].copy_from_slice(bytes);
+    let unsigned = u64::from_le_bytes(value);
+    let shift = 64 - bytes.len() * 8;
+    // 算術右移以延伸符號位元
+    i64::from_le_bytes((unsigned << shift).to_le_bytes()) >> shift
+}
+
+/// 解碼 BCD（低位元組在前），最高位數為 0xF 時為負數
+fn bcd(bytes: &[u8]) -> Option<i64> {
+    let mut negative = false;
+    let mut value: i64 = 0;
+    for (position, byte) in bytes.iter().rev().enumerate() {
+        let (high, low) = (byte >> 4, byte & 0x0F);
+        let high = if position == 0 && high == 0x0F {
+            negative = true;
+            0
+        } else {
+            high
+        };
+        if high > 9 || low > 9 {
+            return None;
+        }
+        value = value * 100 + i64::from(high) * 10 + i64::from(low);
+    }
+    Some(if negative { -value } else { value })
+}
+
+/// 以 10 的次方換算數值，換算後不是整數時轉換為浮點數
+#[expect(clippy::cast_precision_loss)]
+fn scale(raw: i64, exponent: i32) -> Value {
+    if exponent == 0 {
+        return Value::from(raw);
+    }
+    if exponent > 0
+        && let Some(scaled) = 10i64
+            .checked_pow(exponent.unsigned_abs())
+            .and_then(|factor| raw.checked_mul(factor))
+    {
+        return Value::from(scaled);
+    }
+    let scaled = if exponent > 0 {
+        raw as f64 * 10f64.powi(exponent)
+    } else {
+        raw as f64 / 10f64.powi(-exponent)
+    };
+    value::finite(scaled).unwrap_or(Value::Null)
+}
+
+/// 日與月的欄位是否有效
+const fn valid_date(day: u8, month: u8) -> bool {
+    let (day, month) = (day & 0x1F, month & 0x0F);
+    day != 0 && month != 0 && month <= 12
+}
+
+/// 日期（Type G ，2 個位元組）與日期時間（Type F ，4 個位元組；Type I ，6 個位元組）
+fn date_time(bytes: &[u8]) -> Value {
+    let year = |low: u8, high: u8| 2000 + u16::from((low & 0xE0) >> 5 | (high & 0xF0) >> 1);
+    match *bytes {
+        [day, month] => {
+            if !valid_date(day, month) {
+                return Value::Null;
+            }
+            json!(format!(
+                "{:04}-{:02}-{:02}",
+                year(day, month),
+                month & 0x0F,
+                day & 0x1F
+            ))
+        }
+        [minute, hour, day, month] => {
+            // 最高位元為無效旗標
+            if minute & 0x80 != 0 || !valid_date(day, month) {
+                return Value::Null;
+            }
+            json!(format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}",
+                year(day, month),
+                month & 0x0F,
+                day & 0x1F,
+                hour & 0x1F,
+                minute & 0x3F
+            ))
+        }
+        [second, minute, hour, day, month, _] => {
+            if minute & 0x80 != 0 || !valid_date(day, month) {
+                return Value::Null;
+            }
+            json!(format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                year(day, month),
+                month & 0x0F,
+                day & 0x1F,
+                hour & 0x1F,
+                minute & 0x3F,
+                second & 0x3F
+            ))
+        }
+        _ => Value::Null,
+    }
+}
+
+/// 時間長度的單位
+const fn duration_unit(code: u8) -> &'static str {
+    match code & 0x03 {
+        0 => "s",
+        1 => "min",
+        2 => "h",
+        _ => "d",
+    }
+}
+
+/// 數值的意義
+#[derive(Debug, Clone)]
+struct Meaning {
+    quantity: String,
+    unit: Option<&'static str>,
+    exponent: i32,
+    /// 數值為時間點
+    time_point: bool,
+}
+
+impl Meaning {
+    fn new(quantity: &str, unit: Option<&'static str>, exponent: i32) -> Self {
+        Self {
+            quantity: quantity.to_owned(),
+            unit,
+            exponent,
+            time_point: false,
+        }
+    }
+}
+
+/// 主要 VIF 表
+fn primary(vif: u8) -> Meaning {
+    let n = i32::from(vif & 0x07);
+    let nn = i32::from(vif & 0x03);
+    match vif {
+        0x00..=0x07 => Meaning::new("energy", Some("Wh"), n - 3),
+        0x08..=0x0F => Meaning::new("energy", Some("J"), n),
+        0x10..=0x17 => Meaning::new("volume", Some("m³"), n - 6),
+        0x18..=0x1F => Meaning::new("mass", Some("kg"), n - 3),
+        0x20..=0x23 => Meaning::new("on_time", Some(duration_unit(vif)), 0),
+        0x24..=0x27 => Meaning::new("operating_time", Some(duration_unit(vif)), 0),
+        0x28..=0x2F => Meaning::new("power", Some("W"), n - 3),
+        0x30..=0x37 => Meaning::new("power", Some("J/h"), n),
+        0x38..=0x3F => Meaning::new("volume_flow", Some("m³/h"), n - 6),
+        0x40..=0x47 => Meaning::new("volume_flow", Some("m³/min"), n - 7),
+        0x48..=0x4F => Meaning::new("volume_flow", Some("m³/s"), n - 9),
+        0x50..=0x57 => Meaning::new("mass_flow", Some("kg/h"), n - 3),
+        0x58..=0x5B => Meaning::new("flow_temperature", Some("°C"), nn - 3),
+        0x5C..=0x5F => Meaning::new("return_temperature", Some("°C"), nn - 3),
+        0x60..=0x63 => Meaning::new("temperature_difference", Some("K"), nn - 3),
+        0x64..=0x67 => Meaning::new("external_temperature", Some("°C"), nn - 3),
+        0x68..=0x6B => Meaning::new("pressure", Some("bar"), nn - 3),
+        0x6C | 0x6D => Meaning {
+            time_point: true,
+            ..Meaning::new(if vif == 0x6C { "date" } else { "date_time" }, None, 0)
+        },
+        0x6E => Meaning::new("hca_units", None, 0),
+        0x70..=0x73 => Meaning::new("averaging_duration", Some(duration_unit(vif)), 0),
+        0x74..=0x77 => Meaning::new("actuality_duration", Some(duration_unit(vif)), 0),
+        0x78 => Meaning::new("fabrication_number", None, 0),
+        0x79 => Meaning::new("enhanced_identification", None, 0),
+        0x7A => Meaning::new("bus_address", None, 0),
+        0x7E => Meaning::new("any", None, 0),
+        0x7F => Meaning::new("manufacturer_specific", None, 0),
+        _ => Meaning::new("unknown", None, 0),
+    }
+}
+
+/// 延伸 VIF 表（VIF 0xFD 之後的第一個 VIFE）
+fn extension_fd(vife: u8) -> Meaning {
+    let nn = i32::from(vife & 0x03);
+    let nnnn = i32::from(vife & 0x0F);
+    match vife {
+        0x00..=0x03 => Meaning::new("credit", None, nn - 3),
+        0x04..=0x07 => Meaning::new("debit", None, nn - 3),
+        0x08 => Meaning::new("access_number", None, 0),
+        0x09 => Meaning::new("medium", None, 0),
+        0x0A => Meaning::new("manufacturer", None, 0),
+        0x0B => Meaning::new("parameter_set", None, 0),
+        0x0C => Meaning::new("model_version", None, 0),
+        0x0D => Meaning::new("hardware_version", None, 0),
+        0x0E => Meaning::new("firmware_version", None, 0),
+        0x0F => Meaning::new("software_version", None, 0),
+        0x10 => Meaning::new("customer_location", None, 0),
+        0x11 => Meaning::new("customer", None, 0),
+        0x16 => Meaning::new("password", None, 0),
+        0x17 => Meaning::new("error_flags", None, 0),
+        0x1A => Meaning::new("digital_output", None, 0),
+        0x1B => Meaning::new("digital_input", None, 0),
+        0x1C => Meaning::new("baud_rate", Some("Bd"), 0),
+        0x1D => Meaning::new("response_delay", None, 0),
+        0x1E => Meaning::new("retry", None, 0),
+        0x3A => Meaning::new("dimensionless", None, 0),
+        0x40..=0x4F => Meaning::new("voltage", Some("V"), nnnn - 9),
+        0x50..=0x5F => Meaning::new("current", Some("A"), nnnn - 12),
+        0x60 => Meaning::new("reset_counter", None, 0),
+        0x61 => Meaning::new("cumulation_counter", None, 0),
+        _ => Meaning::new("unknown", None, 0),
+    }
+}
+
+/// 延伸 VIF 表（VIF 0xFB 之後的第一個 VIFE）
+fn extension_fb(vife: u8) -> Meaning {
+    let n = i32::from(vife & 0x01);
+    match vife {
+        0x00 | 0x01 => Meaning::new("energy", Some("MWh"), n - 1),
+        0x08 | 0x09 => Meaning::new("energy", Some("GJ"), n - 1),
+        0x10 | 0x11 => Meaning::new("volume", Some("m³"), n + 2),
+        0x18 | 0x19 => Meaning::new("mass", Some("t"), n + 2),
+        0x28 | 0x29 => Meaning::new("power", Some("MW"), n - 1),
+        0x30 | 0x31 => Meaning::new("power", Some("GJ/h"), n - 1),
+        _ => Meaning::new("unknown", None, 0),
+    }
+}
+
+/// 解析資料記錄
+pub(super) struct Parsed {
+    pub(super) records: Vec<Record>,
+    pub(super) manufacturer_data: Vec<u8>,
+    /// 還有資料記錄，需要再次讀取
+    pub(super) more: bool,
+}
+
+impl Parsed {
+    pub(super) fn parse(data: &[u8]) -> Result<Self, ConnectionError> {
+        let mut reader = Reader { data };
+        let mut parsed = Self {
+            records: Vec::new(),
+            manufacturer_data: Vec::new(),
+            more: false,
+        };
+        while !reader.data.is_empty() {
+            let dif = reader.u8()?;
+            if dif == DIF_IDLE_FILLER {
+                continue;
+            }
+            if dif & 0x0F == 0x0F {
+                parsed.more = dif == DIF_MORE_RECORDS;
+                parsed.manufacturer_data = reader.data.to_vec();
+                break;
+            }
+            parsed.records.push(Self::record(&mut reader, dif)?);
+        }
+        Ok(parsed)
+    }
+
+    fn record(reader: &mut Reader<'_>, dif: u8) -> Result<Record, ConnectionError> {
+        let function = match (dif >> 4) & 0x03 {
+            0 => Function::Instantaneous,
+            1 => Function::Maximum,
+            2 => Function::Minimum,
+            _ => Function::Error,
+        };
+        let mut storage = u64::from((dif >> 6) & 0x01);
+        let mut tariff = 0;
+        let mut subunit = 0;
+        let mut extension = dif & 0x80 != 0;
+        for position in 0..MAX_EXTENSIONS {
+            if !extension {
+                break;
+            }
+            let dife = reader.u8()?;
+            storage |= u64::from(dife & 0x0F) << (1 + position * 4);
+            tariff |= u32::from((dife >> 4) & 0x03) << (position * 2);
+            subunit |= u16::from((dife >> 6) & 0x01) << position;
+            extension = dife & 0x80 != 0;
+        }
+        if extension {
+            return Err(malformed());
+        }
+
+        let vif = reader.u8()?;
+        let mut meaning = if vif & 0x7F == 0x7C {
+            // 純文字 VIF ，文字以相反的順序傳送
+            let length = usize::from(reader.u8()?);
+            let text: String = reader
+                .take(length)?
+                .iter()
+                .rev()
+                .map(|byte| char::from(*byte))
+                .collect();
+            Meaning::new(&text, None, 0)
+        } else {
+            primary(vif & 0x7F)
+        };
+        let mut vifes = Vec::new();
+        let mut extension = vif & 0x80 != 0;
+        while extension {
+            if vifes.len() == MAX_EXTENSIONS {
+                return Err(malformed());
+            }
+            let vife = reader.u8()?;
+            vifes.push(vife & 0x7F);
+            extension = vife & 0x80 != 0;
+        }
+        let combinable = match vif {
+            0xFD | 0xFB => {
+                let (first, rest) = vifes.split_first().ok_or_else(malformed)?;
+                meaning = if vif == 0xFD {
+                    extension_fd(*first)
+                } else {
+                    extension_fb(*first)
+                };
+                rest
+            }
+            _ => &vifes[..],
+        };
+        // 倍率修正（E1110nnn ，10 的 nnn-6 次方），其餘可組合的 VIFE 不影響數值
+        for vife in combinable {
+            if vife & 0x78 == 0x70 {
+                meaning.exponent += i32::from(vife & 0x07) - 6;
+            }
+        }
+
+        let value = Self::value(reader, dif & 0x0F, &meaning)?;
+        Ok(Record {
+            function,
+            storage,
+            tariff,
+            subunit,
+            quantity: meaning.quantity,
+            unit: meaning.unit,
+            value,
+        })
+    }
+
+    fn value(
+        reader: &mut Reader<'_>,
+        coding: u8,
+        meaning: &Meaning,
+    ) -> Result<Value, ConnectionError> {
+        // BCD 每個位元組 2 位數：0x09 為 2 位、0x0A 為 4 位、0x0B 為 6 位、0x0C 為 8 位、0x0E 為 12 位
+        let length = match coding {
+            0x00 | 0x08 => return Ok(Value::Null),
+            0x0D => return Self::variable(reader, meaning),
+            0x01 | 0x09 => 1,
+            0x02 | 0x0A => 2,
+            0x03 | 0x0B => 3,
+            0x04 | 0x05 | 0x0C => 4,
+            0x06 | 0x0E => 6,
+            0x07 => 8,
+            _ => return Err(malformed()),
+        };
+        let bytes = reader.take(length)?;
+        Ok(match coding {
+            _ if meaning.time_point => date_time(bytes),
+            0x05 => {
+                let raw = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                value::finite(f64::from(raw) * 10f64.powi(meaning.exponent)).unwrap_or(Value::Null)
+            }
+            0x09..=0x0C | 0x0E => {
+                bcd(bytes).map_or(Value::Null, |raw| scale(raw, meaning.exponent))
+            }
+            _ => scale(integer(bytes), meaning.exponent),
+        })
+    }
+
+    /// 可變長度資料（LVAR）
+    fn variable(reader: &mut Reader<'_>, meaning: &Meaning) -> Result<Value, ConnectionError> {
+        let lvar = reader.u8()?;
+        Ok(match lvar {
+            // ASCII 字串，以相反的順序傳送
+            0x00..=0xBF => {
+                let text: String = reader
+                    .take(usize::from(lvar))?
+                    .iter()
+                    .rev()
+                    .map(|byte| char::from(*byte))
+                    .collect();
+                Value::String(text)
+            }
+            // 正數與負數 BCD ，長度為位元組數
+            0xC0..=0xDF => {
+                let bytes = reader.take(usize::from(lvar & 0x0F))?;
+                bcd(bytes).map_or(Value::Null, |raw| {
+                    scale(if lvar >= 0xD0 { -raw } else { raw }, meaning.exponent)
+                })
+            }
+            // 二進位數值
+            0xE0..=0xEF => {
+                let bytes = reader.take(usize::from(lvar & 0x0F))?;
+                if bytes.len() > 8 {
+                    Value::String(bytes.iter().fold(String::new(), |mut text, byte| {
+                        let _ = write!(text, "{byte:02X}");
+                        text
+                    }))
+                } else if bytes.is_empty() {
+                    Value::Null
+                } else {
+                    scale(integer(bytes), meaning.exponent)
+                }
+            }
+            _ => return Err(malformed()),
+        })
+    }
+}
+
+/// 應用層標頭
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Header {
+    pub(super) id: u32,
+    pub(super) manufacturer: u16,
+    pub(super) version: u8,
+    pub(super) medium: u8,
+    pub(super) access_number: u8,
+    pub(super) status: u8,
+    pub(super) configuration: u16,
+}
+
+impl Header {
+    /// 長標頭（CI 0x72）的位元組，依序為編號、製造商、版本與媒介
+    pub(super) fn address(&self) -> [u8; 8] {
+        let mut address = [0; 8];
+        address[..2].copy_from_slice(&self.manufacturer.to_le_bytes());
+        address[2..6].copy_from_slice(&encode_id(self.id));
+        address[6] = self.version;
+        address[7] = self.medium;
+        address
+    }
+
+    /// 解析長標頭（CI 0x72 之後的 12 個位元組）
+    pub(super) fn long(bytes: &[u8]) -> Result<Self, ConnectionError> {
+        let [
+            id @ ..,
+            m0,
+            m1,
+            version,
+            medium,
+            access_number,
+            status,
+            c0,
+            c1,
+        ] = bytes
+        else {
+            return Err(malformed());
+        };
+        Ok(Self {
+            id: decode_id(id).ok_or_else(malformed)?,
+            manufacturer: u16::from_le_bytes([*m0, *m1]),
+            version: *version,
+            medium: *medium,
+            access_number: *access_number,
+            status: *status,
+            configuration: u16::from_le_bytes([*c0, *c1]),
+        })
+    }
+
+    /// 組合電報
+    pub(super) fn telegram(self, records: Parsed) -> Telegram {
+        Telegram {
+            id: self.id,
+            manufacturer: decode_manufacturer(self.manufacturer),
+            version: self.version,
+            medium: self.medium,
+            access_number: self.access_number,
+            status: self.status,
+            records: records.records,
+            manufacturer_data: records.manufacturer_data,
+        }
+    }
+
+    /// 依組態欄位解密資料記錄
+    ///
+    /// 目前支援模式 5（AES-128-CBC ，IV 為製造商、編號、版本、媒介與 8 個存取編號）
+    pub(super) fn decrypt(
+        &self,
+        data: &mut [u8],
+        key: Option<&[u8; 16]>,
+    ) -> Result<(), ConnectionError> {
+        let mode = (self.configuration >> 8) & 0x1F;
+        match mode {
+            0 => Ok(()),
+            5 => {
+                let key = key.ok_or_else(|| {
+                    ConnectionError::custom(super::MbusError::MissingKey { id: self.id })
+                })?;
+                let length = usize::from((self.configuration >> 4) & 0x0F) * 16;
+                let encrypted = data.get_mut(..length).ok_or_else(malformed)?;
+                let mut iv = [self.access_number; 16];
+                iv[..8].copy_from_slice(&self.address());
+                cbc::Decryptor::<aes::Aes128>::new(key.into(), &iv.into())
+                    .decrypt_padded_mut::<NoPadding>(encrypted)
+                    .map_err(|_| malformed())?;
+                // 解密成功時開頭為兩個填充位元組
+                if encrypted.starts_with(&[DIF_IDLE_FILLER, DIF_IDLE_FILLER]) {
+                    Ok(())
+                } else {
+                    Err(ConnectionError::custom(
+                        super::MbusError::DecryptionFailed { id: self.id },
+                    ))
+                }
+            }
+            mode => Err(ConnectionError::custom(
+                super::MbusError::UnsupportedEncryption { id: self.id, mode },
+            )),
+        }
+    }
+}
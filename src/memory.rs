@@ -0,0 +1,349 @@
+//! 記憶體配額
+//!
+//! 在記憶體有限的閘道器（如 256 MB）上，原始封包保留區、請求紀錄與管線佇列等選用功能必須共用固定的記憶體預算，
+//! 否則同時啟用多個功能時可能耗盡記憶體，主程式可以利用 [`MemoryGovernor`] 依 [`MemoryBudget`] 為各子系統設定位元組上限：
+//!
+//! - 以 [`MemoryGovernor::quota()`] 取得子系統的 [`MemoryQuota`] ，再傳入 [`crate::diagnostics::RawFrameStore::with_quota()`] 、
+//!   [`crate::diagnostics::RequestJournal::with_quota()`] 或 [`crate::queue::BoundedQueue::with_quota()`]
+//! - 子系統保留資料前以 [`MemoryQuota::charge_evicting()`] 取得 [`MemoryCharge`] ，超出上限時先捨棄自己最舊的資料，仍無法容納時放棄保留新資料
+//! - [`MemoryCharge`] 與資料一起保存，資料被捨棄時自動歸還配額
+//! - 以 [`MemoryGovernor::usage()`] 取得目前的使用量，供統計數據或健康檢查使用
+//!
+//! 使用量為估計值，只計算資料本身的大小，不包含配置器與容器的額外負擔，設定上限時請保留餘裕
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::memory::{MemoryBudget, MemoryGovernor};
+//!
+//! let budget: MemoryBudget = serde_json::from_value(serde_json::json!({
+//!     "total_bytes": 1024,
+//!     "subsystems": { "journal": 600, "frames": 600 },
+//! }))
+//! .unwrap();
+//! let governor = MemoryGovernor::new(budget);
+//! let journal = governor.quota("journal");
+//! let frames = governor.quota("frames");
+//!
+//! let mut kept = vec![journal.try_charge(300).unwrap(), journal.try_charge(300).unwrap()];
+//! assert!(journal.try_charge(1).is_err());
+//!
+//! // 子系統仍有餘裕，但已超出總上限
+//! let error = frames.try_charge(500).unwrap_err();
+//! assert_eq!(error.available, 424);
+//!
+//! // 超出上限時捨棄最舊的資料
+//! let charge = journal.charge_evicting(200, || !kept.is_empty() && { kept.remove(0); true }).unwrap();
+//! kept.push(charge);
+//!
+//! let usage = governor.usage();
+//! assert_eq!(usage.used, 500);
+//! assert_eq!(usage.subsystems["journal"].evictions, 1);
+//! assert_eq!(usage.subsystems["frames"].rejected, 1);
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt::Display,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+};
+
+use serde::{Deserialize, Serialize};
+
+/// 記憶體預算
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryBudget {
+    /// 所有子系統合計的位元組上限，為 [`None`] 時不限制
+    #[serde(
+        rename = "total_bytes",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub total: Option<usize>,
+    /// 各子系統的位元組上限，未列出的子系統只受總上限限制
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub subsystems: BTreeMap<String, usize>,
+}
+
+#[derive(Debug)]
+struct Counters {
+    limit: Option<usize>,
+    used: AtomicUsize,
+    peak: AtomicUsize,
+    evictions: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl Counters {
+    const fn new(limit: Option<usize>) -> Self {
+        Self {
+            limit,
+            used: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+            evictions: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// 在上限內增加使用量，超出上限時回傳剩餘的位元組數
+    fn reserve(&self, bytes: usize) -> Result<(), usize> {
+        let limit = self.limit.unwrap_or(usize::MAX);
+        self.used
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |used| {
+                used.checked_add(bytes).filter(|total| *total <= limit)
+            })
+            .map(|used| {
+                self.peak.fetch_max(used + bytes, Ordering::Relaxed);
+            })
+            .map_err(|used| limit.saturating_sub(used))
+    }
+
+    fn release(&self, bytes: usize) {
+        self.used.fetch_sub(bytes, Ordering::AcqRel);
+    }
+
+    fn usage(&self) -> SubsystemUsage {
+        SubsystemUsage {
+            used: self.used.load(Ordering::Relaxed),
+            limit: self.limit,
+            peak: self.peak.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct GovernorInner {
+    budget: MemoryBudget,
+    total: Counters,
+    subsystems: Mutex<BTreeMap<String, Arc<Counters>>>,
+}
+
+/// 記憶體配額管理
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份資料
+#[derive(Debug, Clone)]
+pub struct MemoryGovernor(Arc<GovernorInner>);
+
+impl Default for MemoryGovernor {
+    fn default() -> Self {
+        Self::new(MemoryBudget::default())
+    }
+}
+
+impl MemoryGovernor {
+    /// 建立記憶體配額管理
+    #[must_use]
+    pub fn new(budget: MemoryBudget) -> Self {
+        Self(Arc::new(GovernorInner {
+            total: Counters::new(budget.total),
+            budget,
+            subsystems: Mutex::default(),
+        }))
+    }
+
+    /// 記憶體預算
+    #[must_use]
+    pub fn budget(&self) -> &MemoryBudget {
+        &self.0.budget
+    }
+
+    /// 取得子系統的配額，同一個子系統多次取得時共用同一份使用量
+    #[must_use]
+    pub fn quota(&self, subsystem: &str) -> MemoryQuota {
+        let counters = Arc::clone(
+            self.0
+                .subsystems
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(subsystem.to_owned())
+                .or_insert_with(|| {
+                    Arc::new(Counters::new(
+                        self.0.budget.subsystems.get(subsystem).copied(),
+                    ))
+                }),
+        );
+
+        MemoryQuota {
+            governor: Arc::clone(&self.0),
+            subsystem: subsystem.into(),
+            counters,
+        }
+    }
+
+    /// 取得目前的使用量
+    #[must_use]
+    pub fn usage(&self) -> MemoryUsage {
+        let total = self.0.total.usage();
+
+        MemoryUsage {
+            used: total.used,
+            limit: total.limit,
+            peak: total.peak,
+            subsystems: self
+                .0
+                .subsystems
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .iter()
+                .map(|(subsystem, counters)| (subsystem.clone(), counters.usage()))
+                .collect(),
+        }
+    }
+}
+
+/// 子系統的記憶體配額
+///
+/// 由 [`MemoryGovernor::quota()`] 取得，本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份使用量
+#[derive(Debug, Clone)]
+pub struct MemoryQuota {
+    governor: Arc<GovernorInner>,
+    subsystem: Arc<str>,
+    counters: Arc<Counters>,
+}
+
+impl MemoryQuota {
+    /// 子系統名稱
+    #[must_use]
+    pub fn subsystem(&self) -> &str {
+        &self.subsystem
+    }
+
+    /// 在子系統與總上限內保留記憶體
+    ///
+    /// # 參數
+    /// - `bytes`：資料的估計大小
+    ///
+    /// # Errors
+    /// 超出子系統或總上限時回傳 [`QuotaExceeded`]
+    pub fn try_charge(&self, bytes: usize) -> Result<MemoryCharge, QuotaExceeded> {
+        self.charge(bytes).inspect_err(|_| {
+            self.counters.rejected.fetch_add(1, Ordering::Relaxed);
+        })
+    }
+
+    /// 保留記憶體，超出上限時調用 `evict` 捨棄子系統最舊的資料後重試
+    ///
+    /// # 參數
+    /// - `bytes`：資料的估計大小
+    /// - `evict`：捨棄一筆資料（並歸還其 [`MemoryCharge`]），已經沒有資料可以捨棄時回傳 `false`
+    ///
+    /// # Errors
+    /// 捨棄所有資料後仍超出上限時回傳 [`QuotaExceeded`]
+    pub fn charge_evicting(
+        &self,
+        bytes: usize,
+        mut evict: impl FnMut() -> bool,
+    ) -> Result<MemoryCharge, QuotaExceeded> {
+        loop {
+            match self.charge(bytes) {
+                Ok(charge) => return Ok(charge),
+                Err(error) => {
+                    if !evict() {
+                        self.counters.rejected.fetch_add(1, Ordering::Relaxed);
+                        return Err(error);
+                    }
+                    self.counters.evictions.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+
+    fn charge(&self, bytes: usize) -> Result<MemoryCharge, QuotaExceeded> {
+        let exceeded = |available| QuotaExceeded {
+            subsystem: self.subsystem.to_string(),
+            requested: bytes,
+            available,
+        };
+
+        self.counters.reserve(bytes).map_err(exceeded)?;
+        if let Err(available) = self.governor.total.reserve(bytes) {
+            self.counters.release(bytes);
+            return Err(exceeded(available));
+        }
+
+        Ok(MemoryCharge {
+            quota: self.clone(),
+            bytes,
+        })
+    }
+}
+
+/// 已保留的記憶體
+///
+/// 與資料一起保存，drop 時歸還配額
+#[derive(Debug)]
+pub struct MemoryCharge {
+    quota: MemoryQuota,
+    bytes: usize,
+}
+
+impl MemoryCharge {
+    /// 保留的位元組數
+    #[must_use]
+    pub const fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+impl Drop for MemoryCharge {
+    fn drop(&mut self) {
+        self.quota.counters.release(self.bytes);
+        self.quota.governor.total.release(self.bytes);
+    }
+}
+
+/// 超出記憶體配額
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaExceeded {
+    /// 子系統名稱
+    pub subsystem: String,
+    /// 要求的位元組數
+    pub requested: usize,
+    /// 超出的上限（子系統或總上限）剩餘的位元組數
+    pub available: usize,
+}
+
+impl Display for QuotaExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "子系統「{}」超出記憶體配額：要求 {} 位元組，剩餘 {} 位元組",
+            self.subsystem, self.requested, self.available
+        )
+    }
+}
+
+impl Error for QuotaExceeded {}
+
+/// 記憶體使用量
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryUsage {
+    /// 所有子系統合計的使用量
+    pub used: usize,
+    /// 總上限
+    pub limit: Option<usize>,
+    /// 合計使用量的最高值
+    pub peak: usize,
+    /// 各子系統的使用量
+    pub subsystems: BTreeMap<String, SubsystemUsage>,
+}
+
+/// 子系統的記憶體使用量
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubsystemUsage {
+    /// 使用量
+    pub used: usize,
+    /// 上限
+    pub limit: Option<usize>,
+    /// 使用量的最高值
+    pub peak: usize,
+    /// 為了容納新資料而捨棄舊資料的次數
+    pub evictions: u64,
+    /// 無法容納而放棄保留新資料的次數
+    pub rejected: u64,
+}
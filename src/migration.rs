@@ -0,0 +1,256 @@
+//! 設定檔版本升級
+//!
+//! 連線設定（[`crate::Connection::Config`]）的格式會隨著版本演進，舊版本程式儲存的 JSON 設定檔在升級後可能無法直接反序列化，
+//! 本模組提供版本標記與逐步升級的機制：
+//!
+//! - 儲存設定時利用 [`ConfigMigrator::store()`] 加上版本標記（[`VERSION_KEY`] 欄位）
+//! - 每次調整設定格式時，將目前版本加 1 ，並實作一個 [`ConfigMigration`] ，將上一個版本的設定轉換為新版本
+//! - 讀取設定時利用 [`ConfigMigrator::load()`] ，依序套用所需的升級步驟後，再反序列化為目前的設定型別
+//!
+//! 沒有版本標記的設定檔視為版本 0 ，即加入版本標記前的格式
+//!
+//! # 範例
+//! ```rust
+//! use std::error::Error;
+//! use device_state_exchange_lib::{ConnectionConfig, migration::{ConfigMigration, ConfigMigrator}};
+//! use serde::{Deserialize, Serialize};
+//! use serde_json::{Map, Value, json};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Config {
+//!     port: String,
+//!     baud_rate: u32,
+//!     parity: String,
+//! }
+//!
+//! impl ConnectionConfig for Config {}
+//!
+//! /// 版本 1 ：`baud` 改名為 `baud_rate`
+//! #[derive(Debug)]
+//! struct RenameBaud;
+//!
+//! impl ConfigMigration for RenameBaud {
+//!     fn source_version(&self) -> u32 { 0 }
+//!
+//!     fn migrate(&self, mut config: Map<String, Value>) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+//!         let baud = config.remove("baud").ok_or("缺少 baud 欄位")?;
+//!         config.insert("baud_rate".to_owned(), baud);
+//!         Ok(config)
+//!     }
+//! }
+//!
+//! /// 版本 2 ：新增 `parity` 欄位
+//! #[derive(Debug)]
+//! struct AddParity;
+//!
+//! impl ConfigMigration for AddParity {
+//!     fn source_version(&self) -> u32 { 1 }
+//!
+//!     fn migrate(&self, mut config: Map<String, Value>) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>> {
+//!         config.entry("parity").or_insert_with(|| json!("none"));
+//!         Ok(config)
+//!     }
+//! }
+//!
+//! let migrator = ConfigMigrator::new(2).with_migration(RenameBaud).with_migration(AddParity);
+//!
+//! // 加入版本標記前儲存的設定檔
+//! let config: Config = migrator.load(json!({ "port": "COM1", "baud": 9600 })).unwrap();
+//! assert_eq!(config, Config { port: "COM1".to_owned(), baud_rate: 9600, parity: "none".to_owned() });
+//!
+//! let stored = migrator.store(&config).unwrap();
+//! assert_eq!(stored["config_version"], json!(2));
+//! assert_eq!(migrator.load::<Config>(stored).unwrap(), config);
+//! ```
+
+use std::{error::Error, fmt::Display};
+
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+
+use crate::ConnectionConfig;
+
+/// 設定檔中的版本標記欄位名稱
+pub const VERSION_KEY: &str = "config_version";
+
+/// 設定檔升級步驟
+///
+/// 實作本 trait 的 struct/enum 代表將設定檔由某個版本升級至下一個版本（`source_version() + 1`）的步驟，
+/// 傳入與回傳的設定均不包含版本標記，由 [`ConfigMigrator`] 處理
+pub trait ConfigMigration: std::fmt::Debug + Send + Sync + 'static {
+    /// 升級前的版本
+    fn source_version(&self) -> u32;
+
+    /// 將設定升級至下一個版本
+    ///
+    /// # Errors
+    /// 設定內容無法升級時回傳錯誤，錯誤訊息會包含於 [`MigrationError::Failed`] 中
+    fn migrate(
+        &self,
+        config: Map<String, Value>,
+    ) -> Result<Map<String, Value>, Box<dyn Error + Send + Sync>>;
+}
+
+/// 設定檔升級錯誤
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationError {
+    /// 設定檔不是 JSON object
+    NotAnObject,
+    /// 版本標記不是非負整數
+    InvalidVersion(Value),
+    /// 設定檔版本比目前版本新，可能由較新版本的程式儲存
+    Unsupported {
+        /// 設定檔版本
+        found: u32,
+        /// 目前版本
+        current: u32,
+    },
+    /// 缺少由指定版本升級的步驟
+    MissingMigration(u32),
+    /// 升級步驟失敗
+    Failed {
+        /// 升級前的版本
+        from: u32,
+        /// 錯誤訊息
+        reason: String,
+    },
+    /// 設定檔無法序列化或反序列化
+    Serde(String),
+}
+
+impl Display for MigrationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotAnObject => write!(f, "設定檔必須為 JSON object"),
+            Self::InvalidVersion(version) => write!(f, "無效的設定檔版本：{version}"),
+            Self::Unsupported { found, current } => {
+                write!(f, "設定檔版本 {found} 比目前版本 {current} 新")
+            }
+            Self::MissingMigration(from) => write!(f, "缺少由版本 {from} 升級的步驟"),
+            Self::Failed { from, reason } => write!(f, "由版本 {from} 升級失敗：{reason}"),
+            Self::Serde(reason) => write!(f, "設定檔格式錯誤：{reason}"),
+        }
+    }
+}
+
+impl Error for MigrationError {}
+
+/// 設定檔升級器
+#[derive(Debug)]
+pub struct ConfigMigrator {
+    current_version: u32,
+    migrations: Vec<Box<dyn ConfigMigration>>,
+}
+
+impl ConfigMigrator {
+    /// 建立沒有升級步驟的設定檔升級器
+    ///
+    /// # 參數
+    /// - `current_version`：目前的設定檔版本
+    #[must_use]
+    pub const fn new(current_version: u32) -> Self {
+        Self {
+            current_version,
+            migrations: Vec::new(),
+        }
+    }
+
+    /// 加入升級步驟
+    ///
+    /// 同一個版本有多個升級步驟時，以最後加入的為準
+    #[must_use]
+    pub fn with_migration(mut self, migration: impl ConfigMigration) -> Self {
+        self.migrations
+            .retain(|existing| existing.source_version() != migration.source_version());
+        self.migrations.push(Box::new(migration));
+        self
+    }
+
+    /// 目前的設定檔版本
+    #[must_use]
+    pub const fn current_version(&self) -> u32 {
+        self.current_version
+    }
+
+    /// 將設定檔升級至目前版本
+    ///
+    /// # 回傳值
+    /// 升級後的設定檔，包含目前版本的版本標記
+    ///
+    /// # Errors
+    /// 設定檔格式錯誤、版本比目前版本新、缺少升級步驟或升級步驟失敗時回傳 [`MigrationError`]
+    pub fn upgrade(&self, config: Value) -> Result<Value, MigrationError> {
+        let Value::Object(mut config) = config else {
+            return Err(MigrationError::NotAnObject);
+        };
+
+        let mut version = match config.remove(VERSION_KEY) {
+            None => 0,
+            Some(version) => version
+                .as_u64()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or(MigrationError::InvalidVersion(version))?,
+        };
+
+        if version > self.current_version {
+            return Err(MigrationError::Unsupported {
+                found: version,
+                current: self.current_version,
+            });
+        }
+
+        while version < self.current_version {
+            let migration = self
+                .migrations
+                .iter()
+                .find(|migration| migration.source_version() == version)
+                .ok_or(MigrationError::MissingMigration(version))?;
+
+            config = migration
+                .migrate(config)
+                .map_err(|error| MigrationError::Failed {
+                    from: version,
+                    reason: error.to_string(),
+                })?;
+            version += 1;
+        }
+
+        config.insert(VERSION_KEY.to_owned(), Value::from(version));
+        Ok(Value::Object(config))
+    }
+
+    /// 將設定檔升級至目前版本，並反序列化為設定型別
+    ///
+    /// # Errors
+    /// 同 [`ConfigMigrator::upgrade()`] ，升級後無法反序列化時回傳 [`MigrationError::Serde`]
+    pub fn load<C>(&self, config: Value) -> Result<C, MigrationError>
+    where
+        C: ConnectionConfig + DeserializeOwned,
+    {
+        let Value::Object(mut config) = self.upgrade(config)? else {
+            return Err(MigrationError::NotAnObject);
+        };
+        config.remove(VERSION_KEY);
+
+        serde_json::from_value(Value::Object(config))
+            .map_err(|error| MigrationError::Serde(error.to_string()))
+    }
+
+    /// 將設定序列化，並加上目前版本的版本標記
+    ///
+    /// # Errors
+    /// 設定無法序列化或序列化後不是 JSON object 時回傳 [`MigrationError`]
+    pub fn store<C>(&self, config: &C) -> Result<Value, MigrationError>
+    where
+        C: ConnectionConfig + Serialize,
+    {
+        let Value::Object(mut config) = serde_json::to_value(config)
+            .map_err(|error| MigrationError::Serde(error.to_string()))?
+        else {
+            return Err(MigrationError::NotAnObject);
+        };
+
+        config.insert(VERSION_KEY.to_owned(), Value::from(self.current_version));
+        Ok(Value::Object(config))
+    }
+}
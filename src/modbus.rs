@@ -0,0 +1,849 @@
+//! Modbus 共用型別（需啟用 `modbus-rtu` 或 `modbus-tcp` feature）
+//!
+//! [`crate::modbus_rtu`] 與 [`crate::modbus_tcp`] 共用本模組的點位、請求與回覆型別，兩者只有傳輸層不同，
+//! 同一份點位設定可以在 RTU 與 TCP 之間切換（如設備改經由閘道器連線）
+//!
+//! 點位由 [`TargetDefinition`] 轉換，參見 [`RegisterPoint::parse()`] ，無法轉換的點位會被略過，並記錄於各連線的 `rejected_targets()`；
+//! 同一設備、同一資料表中位址相近的自動更新點位會合併為一次讀取（參見 [`plan_batches()`]），
+//! 同一輪輪詢中的其他點位直接使用該次讀取的結果，不會再次佔用線路
+//!
+//! 點位設定：
+//!
+//! | 欄位 | 說明 |
+//! | --- | --- |
+//! | `device` | 設備編號（unit id），1 至 247 ，未設定時為 1 |
+//! | `address` | `資料表:位址`（資料表為 `coil` 、`di` 、`ir` 、`hr` ，位址由 0 開始，可使用 `0x` 前綴），或 Modicon 格式（如 `40001` 、`300101` ，由 1 開始） |
+//! | `data_type` | `bool` 、`u16` 、`i16` 、`u32` 、`i32` 、`f32` 、`f64` ，線圈與離散輸入只支援 `bool` ，未設定時為 `bool` 或 `u16` |
+//! | `byte_order` | 多暫存器數值的排列方式，參見 [`ByteOrder`] ，未設定時為大端序 |
+//! | `scale` | 讀取後乘上的倍率，寫入時會先除以倍率 |
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{TargetDefinition, modbus::{RegisterPoint, Table, plan_batches}};
+//! use serde_json::json;
+//!
+//! let definitions: Vec<TargetDefinition> = serde_json::from_value(json!([
+//!     { "name": "電壓", "device": "1", "address": "hr:0", "data_type": "f32", "byte_order": "word_swapped" },
+//!     { "name": "電流", "device": "1", "address": "40003", "data_type": "f32", "byte_order": "word_swapped", "scale": 0.1 },
+//!     { "name": "運轉", "device": "1", "address": "coil:0" },
+//!     { "name": "溫度", "device": "2", "address": "ir:10", "data_type": "i16", "scale": 0.1 },
+//! ]))
+//! .unwrap();
+//!
+//! let points: Vec<RegisterPoint> = definitions.iter().map(|definition| RegisterPoint::parse(definition).unwrap()).collect();
+//! assert_eq!((points[1].table, points[1].address), (Table::HoldingRegister, 2));
+//!
+//! // 電壓與電流合併為一次讀取
+//! let batches = plan_batches(&points, 125, 0);
+//! assert_eq!(batches.len(), 3);
+//! let holding = batches.iter().find(|batch| batch.table == Table::HoldingRegister).unwrap();
+//! assert_eq!((holding.unit, holding.start, holding.count), (1, 0, 4));
+//! assert!(holding.contains(&points[0]) && holding.contains(&points[1]));
+//! ```
+
+use std::{borrow::Cow, error::Error, fmt::Display, time::Duration};
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    ConnectionError, ConnectionStats, ConnectionTargets, DeviceStateRequest, DeviceStateResponse,
+    DeviceStateWrite, InitedTarget, Target, TargetDefinition,
+    cache::{CacheLookup, CachePolicy, ResponseCache},
+    codec::{ArrayLayout, ByteOrder, ElementType},
+    command::UnsupportedWrite,
+    value::{self, ConversionError},
+};
+
+pub use crate::definition::InvalidTarget;
+
+/// 單次讀取暫存器數量的上限，參見 Modbus 規範的 Read Holding Registers
+pub const MAX_READ_REGISTERS: u16 = 125;
+
+/// 單次讀取線圈或離散輸入數量的上限
+pub const MAX_READ_BITS: u16 = 2000;
+
+/// 預設的設備編號
+pub const DEFAULT_UNIT: u8 = 1;
+
+/// 資料表
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Table {
+    /// 線圈（0xxxx），可讀寫
+    Coil,
+    /// 離散輸入（1xxxx），唯讀
+    DiscreteInput,
+    /// 輸入暫存器（3xxxx），唯讀
+    InputRegister,
+    /// 保持暫存器（4xxxx），可讀寫
+    HoldingRegister,
+}
+
+impl Table {
+    /// 讀取用的功能碼
+    #[must_use]
+    pub const fn read_function(self) -> u8 {
+        match self {
+            Self::Coil => 0x01,
+            Self::DiscreteInput => 0x02,
+            Self::HoldingRegister => 0x03,
+            Self::InputRegister => 0x04,
+        }
+    }
+
+    /// 是否以位元為單位
+    #[must_use]
+    pub const fn is_bit(self) -> bool {
+        matches!(self, Self::Coil | Self::DiscreteInput)
+    }
+
+    /// 單次讀取數量的上限
+    #[must_use]
+    pub const fn max_read(self) -> u16 {
+        if self.is_bit() {
+            MAX_READ_BITS
+        } else {
+            MAX_READ_REGISTERS
+        }
+    }
+
+    fn from_prefix(prefix: &str) -> Option<Self> {
+        match prefix {
+            "coil" | "co" => Some(Self::Coil),
+            "di" | "discrete" => Some(Self::DiscreteInput),
+            "ir" | "input" => Some(Self::InputRegister),
+            "hr" | "holding" => Some(Self::HoldingRegister),
+            _ => None,
+        }
+    }
+
+    const fn from_modicon(digit: u8) -> Option<Self> {
+        match digit {
+            b'0' => Some(Self::Coil),
+            b'1' => Some(Self::DiscreteInput),
+            b'3' => Some(Self::InputRegister),
+            b'4' => Some(Self::HoldingRegister),
+            _ => None,
+        }
+    }
+}
+
+/// 點位的資料型別
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    /// 布林值，暫存器不為 0 時為 `true`
+    Bool,
+    /// 數值
+    Number(ElementType),
+}
+
+impl ValueType {
+    /// 佔用的暫存器（或位元）數量
+    #[must_use]
+    #[expect(clippy::cast_possible_truncation)]
+    pub const fn registers(self) -> u16 {
+        match self {
+            Self::Bool => 1,
+            Self::Number(element_type) => (element_type.size() / 2) as u16,
+        }
+    }
+
+    fn parse(data_type: &str) -> Option<Self> {
+        Some(match data_type {
+            "bool" => Self::Bool,
+            "u16" => Self::Number(ElementType::U16),
+            "i16" => Self::Number(ElementType::I16),
+            "u32" => Self::Number(ElementType::U32),
+            "i32" => Self::Number(ElementType::I32),
+            "f32" => Self::Number(ElementType::F32),
+            "f64" => Self::Number(ElementType::F64),
+            _ => return None,
+        })
+    }
+}
+
+/// 點位
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegisterPoint {
+    /// 設備編號（unit id）
+    pub unit: u8,
+    /// 資料表
+    pub table: Table,
+    /// 位址，由 0 開始
+    pub address: u16,
+    /// 資料型別
+    pub value_type: ValueType,
+    /// 多暫存器數值的排列方式
+    pub byte_order: ByteOrder,
+    /// 倍率
+    pub scale: Option<f64>,
+}
+
+impl RegisterPoint {
+    /// 由通用點位定義轉換，欄位說明參見 [`crate::modbus`]
+    ///
+    /// # Errors
+    /// 設備編號、位址、資料型別或其他欄位無效時回傳 [`InvalidTarget`]
+    pub fn parse(definition: &TargetDefinition) -> Result<Self, InvalidTarget> {
+        let invalid = |reason: String| InvalidTarget {
+            target: definition.name.clone(),
+            reason,
+        };
+
+        let unit = match &definition.device {
+            None => DEFAULT_UNIT,
+            Some(device) => device
+                .parse()
+                .ok()
+                .filter(|unit| (1..=247).contains(unit))
+                .ok_or_else(|| invalid(format!("設備編號「{device}」不是 1 至 247 的整數")))?,
+        };
+        let (table, address) = parse_address(&definition.address)
+            .ok_or_else(|| invalid(format!("無效的位址「{}」", definition.address)))?;
+        let value_type = match definition.data_type.as_deref() {
+            None if table.is_bit() => ValueType::Bool,
+            None => ValueType::Number(ElementType::U16),
+            Some(data_type) => ValueType::parse(data_type)
+                .filter(|value_type| !table.is_bit() || *value_type == ValueType::Bool)
+                .ok_or_else(|| invalid(format!("資料表不支援資料型別「{data_type}」")))?,
+        };
+        if u32::from(address) + u32::from(value_type.registers()) > 0x1_0000 {
+            return Err(invalid("位址超出範圍".to_owned()));
+        }
+        let byte_order = definition
+            .extra
+            .get("byte_order")
+            .map(|byte_order| serde_json::from_value(byte_order.clone()))
+            .transpose()
+            .map_err(|error| invalid(format!("無效的 byte_order ：{error}")))?
+            .unwrap_or_default();
+        let scale = definition
+            .extra
+            .get("scale")
+            .map(|scale| {
+                scale
+                    .as_f64()
+                    .filter(|scale| scale.is_normal())
+                    .ok_or_else(|| invalid(format!("無效的 scale ：{scale}")))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            unit,
+            table,
+            address,
+            value_type,
+            byte_order,
+            scale,
+        })
+    }
+
+    /// 由批次讀取的資料中取出本點位的數值
+    ///
+    /// # 參數
+    /// - `batch`：包含本點位的批次讀取範圍
+    /// - `data`：主站讀取該範圍時回覆的資料
+    ///
+    /// # Errors
+    /// 本點位不在讀取範圍內、資料長度不足或數值無法以 JSON 表示時回傳 [`ConversionError`]
+    pub fn decode(
+        &self,
+        batch: &ReadBatch,
+        data: &Bytes,
+    ) -> Result<ModbusResponse, ConversionError> {
+        if !batch.contains(self) {
+            return Err(ConversionError::InvalidRaw(format!(
+                "位址 {} 不在讀取範圍內",
+                self.address
+            )));
+        }
+        let offset = usize::from(self.address - batch.start);
+
+        if self.table.is_bit() {
+            let byte = data
+                .get(offset / 8)
+                .ok_or_else(|| ConversionError::InvalidRaw("回覆長度不足".to_owned()))?;
+            return Ok(ModbusResponse {
+                value: Value::Bool(byte >> (offset % 8) & 1 == 1),
+                raw: data.slice(offset / 8..=offset / 8),
+            });
+        }
+
+        let range = offset * 2..(offset + usize::from(self.value_type.registers())) * 2;
+        if range.end > data.len() {
+            return Err(ConversionError::InvalidRaw("回覆長度不足".to_owned()));
+        }
+        let raw = data.slice(range);
+        let value = match self.value_type {
+            ValueType::Bool => Value::Bool(raw.iter().any(|byte| *byte != 0)),
+            ValueType::Number(element_type) => {
+                let layout = ArrayLayout {
+                    element_type,
+                    length: 1,
+                    byte_order: self.byte_order,
+                };
+                let sample = layout.decode(raw.clone())?.to_vec()[0];
+                match (element_type, self.scale) {
+                    (_, Some(scale)) => value::finite(sample * scale)?,
+                    (ElementType::F32 | ElementType::F64, None) => value::finite(sample)?,
+                    (_, None) => Value::from(to_integer(sample)),
+                }
+            }
+        };
+
+        Ok(ModbusResponse { value, raw })
+    }
+
+    /// 將欲寫入的數值轉換為寫入內容
+    ///
+    /// # 回傳值
+    /// 寫入內容，數值型別不符或超出資料型別的範圍時為 [`None`]
+    #[must_use]
+    pub fn encode(&self, value: &Value) -> Option<WritePayload> {
+        if self.table.is_bit() {
+            return value.as_bool().map(WritePayload::Coil);
+        }
+
+        let bytes = match self.value_type {
+            ValueType::Bool => u16::from(value.as_bool()?).to_be_bytes().to_vec(),
+            ValueType::Number(element_type) => {
+                let number = value.as_f64()? / self.scale.unwrap_or(1.0);
+                let mut bytes = encode_number(element_type, number)?;
+                reorder(&mut bytes, self.byte_order);
+                bytes
+            }
+        };
+
+        Some(WritePayload::Registers(
+            bytes
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect(),
+        ))
+    }
+}
+
+/// 解析位址
+///
+/// # 回傳值
+/// 資料表與由 0 開始的位址，格式無效時為 [`None`]
+fn parse_address(address: &str) -> Option<(Table, u16)> {
+    let address = address.trim();
+    if let Some((prefix, number)) = address.split_once(':') {
+        let table = Table::from_prefix(&prefix.trim().to_ascii_lowercase())?;
+        let number = number.trim();
+        let number = match number
+            .strip_prefix("0x")
+            .or_else(|| number.strip_prefix("0X"))
+        {
+            Some(hex) => u16::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return Some((table, number));
+    }
+
+    if !matches!(address.len(), 5 | 6) || !address.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let table = Table::from_modicon(address.as_bytes()[0])?;
+    let number: u32 = address[1..].parse().ok()?;
+    Some((table, u16::try_from(number.checked_sub(1)?).ok()?))
+}
+
+#[expect(clippy::cast_possible_truncation)]
+const fn to_integer(sample: f64) -> i64 {
+    sample as i64
+}
+
+#[expect(clippy::cast_possible_truncation)]
+fn encode_number(element_type: ElementType, number: f64) -> Option<Vec<u8>> {
+    if !number.is_finite() {
+        return None;
+    }
+    let integer = || {
+        let rounded = number.round();
+        (rounded >= -(2_f64.powi(63)) && rounded < 2_f64.powi(63)).then_some(rounded as i64)
+    };
+
+    Some(match element_type {
+        ElementType::U16 => u16::try_from(integer()?).ok()?.to_be_bytes().to_vec(),
+        ElementType::I16 => i16::try_from(integer()?).ok()?.to_be_bytes().to_vec(),
+        ElementType::U32 => u32::try_from(integer()?).ok()?.to_be_bytes().to_vec(),
+        ElementType::I32 => i32::try_from(integer()?).ok()?.to_be_bytes().to_vec(),
+        ElementType::F32 => (number as f32).to_be_bytes().to_vec(),
+        ElementType::F64 => number.to_be_bytes().to_vec(),
+    })
+}
+
+/// 將大端序的位元組轉換為指定的排列方式，轉換前後互為反函數
+fn reorder(bytes: &mut [u8], byte_order: ByteOrder) {
+    match byte_order {
+        ByteOrder::BigEndian => {}
+        ByteOrder::LittleEndian => bytes.reverse(),
+        ByteOrder::WordSwapped => {
+            let words = bytes.len() / 2;
+            for word in 0..words / 2 {
+                for offset in 0..2 {
+                    bytes.swap(word * 2 + offset, (words - 1 - word) * 2 + offset);
+                }
+            }
+        }
+    }
+}
+
+/// 批次讀取範圍
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReadBatch {
+    /// 設備編號
+    pub unit: u8,
+    /// 資料表
+    pub table: Table,
+    /// 起始位址
+    pub start: u16,
+    /// 讀取數量
+    pub count: u16,
+}
+
+impl ReadBatch {
+    /// 只包含單一點位的讀取範圍
+    #[must_use]
+    pub const fn single(point: &RegisterPoint) -> Self {
+        Self {
+            unit: point.unit,
+            table: point.table,
+            start: point.address,
+            count: point.value_type.registers(),
+        }
+    }
+
+    /// 是否完整包含點位
+    #[must_use]
+    pub fn contains(&self, point: &RegisterPoint) -> bool {
+        let range = u32::from(self.start)..u32::from(self.start) + u32::from(self.count);
+        let first = u32::from(point.address);
+        let last = first + u32::from(point.value_type.registers()) - 1;
+        self.unit == point.unit
+            && self.table == point.table
+            && range.contains(&first)
+            && range.contains(&last)
+    }
+
+    fn key(self) -> String {
+        format!(
+            "{}:{:?}:{}:{}",
+            self.unit, self.table, self.start, self.count
+        )
+    }
+}
+
+/// 規劃批次讀取
+///
+/// 依設備編號、資料表與位址排序後，將相鄰的點位合併，合併後的讀取數量不超過 `max_registers` 與 [`Table::max_read()`]
+///
+/// # 參數
+/// - `points`：點位
+/// - `max_registers`：單次讀取數量的上限，小於 1 時視為 1
+/// - `max_gap`：可以跨越的未使用位址數量
+///
+/// # 回傳值
+/// 讀取範圍，每個點位都會被其中一個範圍完整包含
+#[must_use]
+pub fn plan_batches(points: &[RegisterPoint], max_registers: u16, max_gap: u16) -> Vec<ReadBatch> {
+    let mut points: Vec<ReadBatch> = points.iter().map(ReadBatch::single).collect();
+    points.sort_unstable_by_key(|point| (point.unit, point.table, point.start));
+
+    let mut batches: Vec<ReadBatch> = Vec::new();
+    for point in points {
+        let limit = u32::from(max_registers.clamp(1, point.table.max_read()));
+        let end = u32::from(point.start) + u32::from(point.count);
+
+        if let Some(batch) = batches.last_mut().filter(|batch| {
+            let batch_end = u32::from(batch.start) + u32::from(batch.count);
+            batch.unit == point.unit
+                && batch.table == point.table
+                && u32::from(point.start) <= batch_end + u32::from(max_gap)
+                && end.max(batch_end) - u32::from(batch.start) <= limit
+        }) {
+            let batch_end = u32::from(batch.start) + u32::from(batch.count);
+            batch.count =
+                u16::try_from(end.max(batch_end) - u32::from(batch.start)).unwrap_or(u16::MAX);
+        } else {
+            batches.push(point);
+        }
+    }
+    batches
+}
+
+/// 寫入內容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WritePayload {
+    /// 寫入單一線圈（功能碼 0x05）
+    Coil(bool),
+    /// 寫入暫存器，單一暫存器使用功能碼 0x06 ，多個暫存器使用功能碼 0x10
+    Registers(Vec<u16>),
+}
+
+/// 例外回覆
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModbusException {
+    /// 請求的功能碼
+    pub function: u8,
+    /// 例外碼，如 `0x02`（Illegal Data Address）
+    pub code: u8,
+}
+
+impl Display for ModbusException {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self.code {
+            0x01 => "不支援的功能碼",
+            0x02 => "無效的位址",
+            0x03 => "無效的數值",
+            0x04 => "設備故障",
+            0x05 => "處理中",
+            0x06 => "設備忙碌",
+            0x0a => "閘道器路徑無效",
+            0x0b => "閘道器後的設備沒有回應",
+            _ => "未知的例外",
+        };
+        write!(
+            f,
+            "設備回覆例外（功能碼 0x{:02x}，例外碼 0x{:02x}）：{reason}",
+            self.function, self.code
+        )
+    }
+}
+
+impl Error for ModbusException {}
+
+/// 點位
+#[derive(Debug, Clone)]
+pub struct ModbusTarget(pub TargetDefinition);
+
+impl Target for ModbusTarget {}
+
+/// 讀取請求
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModbusRequest {
+    /// 點位
+    pub point: RegisterPoint,
+    /// 包含點位的讀取範圍
+    pub batch: ReadBatch,
+}
+
+impl DeviceStateRequest for ModbusRequest {}
+
+/// 讀取回覆
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModbusResponse {
+    /// 轉換後的數值
+    pub value: Value,
+    /// 點位的原始資料
+    pub raw: Bytes,
+}
+
+impl DeviceStateResponse for ModbusResponse {
+    fn to_value(&self) -> Result<Cow<'_, Value>, ConversionError> {
+        Ok(Cow::Borrowed(&self.value))
+    }
+
+    fn raw(&self) -> Option<Bytes> {
+        Some(self.raw.clone())
+    }
+}
+
+/// 寫入請求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModbusWrite {
+    /// 設備編號
+    pub unit: u8,
+    /// 位址
+    pub address: u16,
+    /// 寫入內容
+    pub payload: WritePayload,
+    /// 包含點位的讀取範圍，寫入後會清除該範圍的讀取結果
+    pub batch: ReadBatch,
+}
+
+impl DeviceStateWrite for ModbusWrite {}
+
+/// 讀取請求的 PDU
+pub(crate) const fn read_pdu(batch: ReadBatch) -> [u8; 5] {
+    let [start_high, start_low] = batch.start.to_be_bytes();
+    let [count_high, count_low] = batch.count.to_be_bytes();
+    [
+        batch.table.read_function(),
+        start_high,
+        start_low,
+        count_high,
+        count_low,
+    ]
+}
+
+/// 檢查讀取回覆的 PDU 長度，回傳其中的資料
+pub(crate) fn read_data(batch: ReadBatch, pdu: &Bytes) -> Result<Bytes, ConnectionError> {
+    let expected = if batch.table.is_bit() {
+        usize::from(batch.count).div_ceil(8)
+    } else {
+        usize::from(batch.count) * 2
+    };
+    if pdu.len() != expected + 2 || usize::from(pdu[1]) != expected {
+        return Err(ConnectionError::Protocol(format!(
+            "回覆的資料長度應為 {expected} 位元組"
+        )));
+    }
+    Ok(pdu.slice(2..))
+}
+
+/// 寫入請求的 PDU
+pub(crate) fn write_pdu(address: u16, payload: &WritePayload) -> Result<Vec<u8>, ConnectionError> {
+    let [address_high, address_low] = address.to_be_bytes();
+    Ok(match payload {
+        WritePayload::Coil(state) => {
+            vec![
+                0x05,
+                address_high,
+                address_low,
+                if *state { 0xff } else { 0x00 },
+                0x00,
+            ]
+        }
+        WritePayload::Registers(registers) if registers.len() == 1 => {
+            let [high, low] = registers[0].to_be_bytes();
+            vec![0x06, address_high, address_low, high, low]
+        }
+        WritePayload::Registers(registers) => {
+            let count = u16::try_from(registers.len())
+                .ok()
+                .filter(|count| (1..=123).contains(count))
+                .ok_or_else(|| {
+                    ConnectionError::Protocol("單次寫入的暫存器數量需為 1 至 123".to_owned())
+                })?;
+            let [count_high, count_low] = count.to_be_bytes();
+            let mut request = vec![0x10, address_high, address_low, count_high, count_low];
+            request.push(count_low * 2);
+            request.extend(registers.iter().flat_map(|register| register.to_be_bytes()));
+            request
+        }
+    })
+}
+
+/// 檢查寫入的回覆，設備會回覆請求 PDU 的前 5 個位元組
+pub(crate) fn check_write(request: &[u8], response: &[u8]) -> Result<(), ConnectionError> {
+    if response != &request[..5] {
+        return Err(ConnectionError::Protocol("寫入的回覆與請求不符".to_owned()));
+    }
+    Ok(())
+}
+
+/// 檢查回覆 PDU 的功能碼
+///
+/// # Errors
+/// 設備回覆例外時回傳包含 [`ModbusException`] 的 [`ConnectionError::Custom`] ，功能碼與請求不符時回傳 [`ConnectionError::Protocol`]
+pub(crate) fn check_function(function: u8, response: &[u8]) -> Result<(), ConnectionError> {
+    match response {
+        [code, exception, ..] if code & 0x80 != 0 => {
+            Err(ConnectionError::custom(ModbusException {
+                function: code & 0x7f,
+                code: *exception,
+            }))
+        }
+        [code, ..] if *code == function => Ok(()),
+        [code, ..] => Err(ConnectionError::Protocol(format!(
+            "回覆的功能碼 0x{code:02x} 與請求不符"
+        ))),
+        [] => Err(ConnectionError::Protocol("回覆沒有功能碼".to_owned())),
+    }
+}
+
+/// 以讀取回覆的 PDU 解碼點位，供各傳輸層的 [`crate::fixture::FrameDecoder`] 實作共用
+///
+/// `target` 為點位的 `address` ，可在其後以 `/` 加上 `data_type`（如 `hr:0/f32`）；
+/// 讀取範圍由點位的位址開始，長度取自回覆的位元組數
+pub(crate) fn decode_read_response(
+    target: &str,
+    unit: u8,
+    pdu: &Bytes,
+) -> Result<ModbusResponse, Box<dyn Error>> {
+    let (address, data_type) = match target.split_once('/') {
+        Some((address, data_type)) => (address, Some(data_type.to_owned())),
+        None => (target, None),
+    };
+    let point = RegisterPoint::parse(&TargetDefinition {
+        name: target.to_owned(),
+        device: Some(unit.to_string()),
+        device_type: None,
+        address: address.to_owned(),
+        data_type,
+        auto_refresh: true,
+        default_status: None,
+        extra: serde_json::Map::new(),
+    })?;
+
+    check_function(point.table.read_function(), pdu)?;
+    let byte_count = pdu
+        .get(1)
+        .copied()
+        .ok_or_else(|| ConnectionError::Protocol("回覆沒有資料長度".to_owned()))?;
+    let count = if point.table.is_bit() {
+        u16::from(byte_count) * 8
+    } else {
+        u16::from(byte_count) / 2
+    };
+    let batch = ReadBatch {
+        unit,
+        table: point.table,
+        start: point.address,
+        count,
+    };
+    let data = read_data(batch, pdu)?;
+    Ok(point.decode(&batch, &data)?)
+}
+
+/// 批次讀取的規劃與讀取結果，供各傳輸層的連線共用
+///
+/// 同一個讀取範圍的讀取結果會保留一段時間（通常為更新間隔的一半），期間內的其他點位直接使用該結果
+#[derive(Debug, Default)]
+pub(crate) struct BatchReads {
+    cache: ResponseCache<Bytes>,
+    batches: Vec<ReadBatch>,
+    probe: Option<ReadBatch>,
+    ttl: Duration,
+    rejected: Vec<InvalidTarget>,
+}
+
+impl BatchReads {
+    /// 轉換點位並規劃批次讀取，無法轉換的點位會被略過並記錄
+    pub(crate) fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<ModbusTarget>,
+        max_registers: u16,
+        max_gap: u16,
+    ) -> ConnectionTargets<ModbusRequest, ()> {
+        let mut parsed = Vec::with_capacity(targets.len());
+        for ModbusTarget(definition) in targets {
+            match RegisterPoint::parse(&definition) {
+                Ok(point) => parsed.push((definition, point)),
+                Err(error) => self.rejected.push(error),
+            }
+        }
+
+        self.probe = parsed.first().map(|(_, point)| ReadBatch::single(point));
+        self.batches = plan_batches(
+            &parsed
+                .iter()
+                .filter(|(definition, _)| definition.auto_refresh)
+                .map(|(_, point)| *point)
+                .collect::<Vec<_>>(),
+            max_registers,
+            max_gap,
+        );
+        self.set_ttl(self.ttl);
+
+        ConnectionTargets(
+            parsed
+                .into_iter()
+                .map(|(definition, point)| InitedTarget {
+                    name: definition.name,
+                    request: ModbusRequest {
+                        point,
+                        batch: self
+                            .batches
+                            .iter()
+                            .find(|batch| definition.auto_refresh && batch.contains(&point))
+                            .copied()
+                            .unwrap_or_else(|| ReadBatch::single(&point)),
+                    },
+                    result: (),
+                    default_status: definition.default_status,
+                    auto_refresh: definition.auto_refresh,
+                    refresh_interval: None,
+                    keep_raw_frames: None,
+                    group: None,
+                    safe_state: None,
+                    array: None,
+                    change: None,
+                    statistics: Some(connection_statistics.insert_target(definition.device)),
+                })
+                .collect(),
+        )
+    }
+
+    /// 設定讀取結果的保留時間
+    pub(crate) fn set_ttl(&mut self, ttl: Duration) {
+        self.ttl = ttl;
+        let policy = CachePolicy {
+            ttl,
+            stale_while_revalidate: Duration::ZERO,
+        };
+        for batch in &self.batches {
+            self.cache.enable(batch.key(), policy);
+        }
+    }
+
+    /// 取得尚未過期的讀取結果
+    pub(crate) fn cached(&self, batch: ReadBatch) -> Option<Bytes> {
+        match self.cache.lookup(&batch.key(), None) {
+            CacheLookup::Fresh(data) => Some(data),
+            CacheLookup::Stale { .. } | CacheLookup::Miss => None,
+        }
+    }
+
+    /// 記錄讀取結果
+    pub(crate) fn store(&self, batch: ReadBatch, data: Bytes) {
+        self.cache.store(&batch.key(), data);
+    }
+
+    /// 清除讀取結果
+    pub(crate) fn invalidate(&self, batch: ReadBatch) {
+        self.cache.invalidate(&batch.key());
+    }
+
+    /// 第一個點位的讀取範圍，用於保持連線
+    #[cfg_attr(not(feature = "modbus-tcp"), expect(dead_code))]
+    pub(crate) const fn probe(&self) -> Option<ReadBatch> {
+        self.probe
+    }
+
+    pub(crate) fn rejected(&self) -> &[InvalidTarget] {
+        &self.rejected
+    }
+}
+
+impl ModbusRequest {
+    /// 由讀取範圍的資料取出回覆
+    pub(crate) fn respond(&self, data: &Bytes) -> Result<ModbusResponse, ConnectionError> {
+        self.point
+            .decode(&self.batch, data)
+            .map_err(|error| ConnectionError::Protocol(error.to_string()))
+    }
+
+    /// 轉換為寫入請求，參見 [`crate::Connection::write_preprocess()`]
+    pub(crate) fn write(self, value: &Value) -> Result<Box<dyn DeviceStateWrite>, ConnectionError> {
+        if !matches!(self.point.table, Table::Coil | Table::HoldingRegister) {
+            return Err(ConnectionError::custom(UnsupportedWrite));
+        }
+        let payload = self
+            .point
+            .encode(value)
+            .ok_or_else(|| ConnectionError::InvalidConfig(format!("無效的設定值：{value}")))?;
+
+        Ok(Box::new(ModbusWrite {
+            unit: self.point.unit,
+            address: self.point.address,
+            payload,
+            batch: self.batch,
+        }))
+    }
+}
+
+impl ModbusWrite {
+    /// 取回 [`ModbusRequest::write()`] 產生的寫入請求
+    pub(crate) fn downcast(write: Box<dyn DeviceStateWrite>) -> Result<Box<Self>, ConnectionError> {
+        write
+            .downcast::<Self>()
+            .map_err(|_| ConnectionError::Protocol("非預期的寫入請求".to_owned()))
+    }
+}
@@ -0,0 +1,521 @@
+//! Modbus RTU 參考實作（需啟用 `modbus-rtu` feature）
+//!
+//! 本 crate 的文件以 Modbus RTU 為例說明各項功能，本模組提供可直接使用的 [`ModbusRtuConnection`] ，
+//! 同時作為實作其他協定時的範本，以及驗證主程式行為的基準：
+//!
+//! - 設定：[`ModbusRtuConfig`] ，序列埠以 [`PortSelector`] 指定，每次連線與重新連線時都會重新解析
+//! - 點位與批次讀取：與 [`crate::modbus_tcp`] 共用，參見 [`crate::modbus`] ，無法轉換的點位記錄於 [`ModbusRtuConnection::rejected_targets()`]
+//! - 封包：[`ModbusRtuClient`] 處理 CRC 、框架間隔與例外回覆，可搭配任何實作 [`AsyncRead`] 與 [`AsyncWrite`] 的資料來源，
+//!   封包格式的測試向量參見 [`split_adu()`]
+//! - 回歸測試：[`ModbusRtuConnection`] 實作 [`FrameDecoder`] ，可以用記錄下來的回覆封包驗證解碼邏輯
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{modbus::MAX_READ_REGISTERS, modbus_rtu::ModbusRtuConfig, serial::Parity};
+//! use serde_json::json;
+//!
+//! let config: ModbusRtuConfig = serde_json::from_value(json!({
+//!     "port": { "by": "usb", "vid": 1027, "pid": 24577 },
+//!     "baud_rate": 19200,
+//!     "data_bits": 8,
+//!     "parity": "Even",
+//!     "stop_bits": 1,
+//! }))
+//! .unwrap();
+//! assert_eq!(config.settings.parity, Parity::Even);
+//! assert_eq!(config.max_batch_registers, MAX_READ_REGISTERS);
+//! ```
+
+use std::{error::Error, io::ErrorKind, time::Duration};
+
+use bytes::{Bytes, BytesMut};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio_serial::{SerialPortBuilderExt, SerialStream};
+
+use crate::{
+    Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+    ConnectionTargets, DeviceStateWrite, RequestContext,
+    fixture::FrameDecoder,
+    modbus::{
+        self, BatchReads, InvalidTarget, MAX_READ_REGISTERS, ModbusRequest, ModbusResponse,
+        ModbusTarget, ModbusWrite, ReadBatch, WritePayload,
+    },
+    serial::{Parity, SerialSettings, port::PortSelector},
+    transport::frame::FrameReader,
+};
+
+/// 預設的更新間隔
+pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 預設的回覆逾時
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(500);
+
+const fn default_update_interval() -> Duration {
+    DEFAULT_UPDATE_INTERVAL
+}
+
+const fn default_timeout() -> Duration {
+    DEFAULT_TIMEOUT
+}
+
+const fn default_max_batch_registers() -> u16 {
+    MAX_READ_REGISTERS
+}
+
+/// 連線參數
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModbusRtuConfig {
+    /// 序列埠
+    pub port: PortSelector,
+    /// 線路參數
+    #[serde(flatten)]
+    pub settings: SerialSettings,
+    /// 更新間隔，序列化時以毫秒數表示，預設為 [`DEFAULT_UPDATE_INTERVAL`]
+    #[serde(
+        rename = "update_interval_ms",
+        with = "crate::millis",
+        default = "default_update_interval"
+    )]
+    pub update_interval: Duration,
+    /// 回覆逾時，序列化時以毫秒數表示，預設為 [`DEFAULT_TIMEOUT`]
+    #[serde(
+        rename = "timeout_ms",
+        with = "crate::millis",
+        default = "default_timeout"
+    )]
+    pub timeout: Duration,
+    /// 最高重試次數，參見 [`ConnectionArtifact::max_retry_count`]
+    #[serde(default)]
+    pub max_retry_count: Option<u32>,
+    /// 批次讀取的暫存器數量上限，預設為 [`MAX_READ_REGISTERS`] ，設備無法處理長讀取時請調低
+    #[serde(default = "default_max_batch_registers")]
+    pub max_batch_registers: u16,
+    /// 批次讀取時可以跨越的未使用位址數量，預設為 0 ，只合併連續的位址
+    #[serde(default)]
+    pub max_batch_gap: u16,
+}
+
+impl ConnectionConfig for ModbusRtuConfig {}
+
+/// 計算 Modbus RTU 的 CRC-16 ，傳送時以小端序附加於封包尾端
+#[must_use]
+pub fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0xffff, |crc, byte| {
+        (0..8).fold(crc ^ u16::from(*byte), |crc, _| {
+            if crc & 1 == 1 {
+                (crc >> 1) ^ 0xa001
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+/// 拆解 Modbus RTU 封包（ADU），並驗證 CRC
+///
+/// # 回傳值
+/// 設備編號與 PDU（功能碼與資料）
+///
+/// # Errors
+/// 封包長度不足或 CRC 錯誤時回傳 [`ConnectionError::Protocol`]
+///
+/// # 範例
+/// 以內建的測試向量驗證：
+/// ```rust
+/// use bytes::Bytes;
+/// use device_state_exchange_lib::{modbus_rtu::{crc16, split_adu}, vectors::{self, encode_hex}};
+/// use serde_json::json;
+///
+/// let report = vectors::built_in_set("modbus_rtu/adu").unwrap().verify(|frame, value| {
+///     let body = &frame[..frame.len().saturating_sub(2)];
+///     if json!(crc16(body)) != value["crc"] {
+///         return Err(format!("CRC 為 {}", crc16(body)));
+///     }
+///     match (split_adu(&Bytes::copy_from_slice(frame)), value["pdu"].as_str()) {
+///         (Ok((unit, pdu)), Some(expected)) if json!(unit) == value["unit"] && encode_hex(&pdu) == expected => Ok(()),
+///         (Err(_), None) => Ok(()),
+///         (result, _) => Err(format!("拆解結果為 {result:?}")),
+///     }
+/// });
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+pub fn split_adu(adu: &Bytes) -> Result<(u8, Bytes), ConnectionError> {
+    if adu.len() < 4 {
+        return Err(ConnectionError::Protocol("封包長度不足".to_owned()));
+    }
+    let (body, checksum) = adu.split_at(adu.len() - 2);
+    if crc16(body).to_le_bytes() != checksum {
+        return Err(ConnectionError::Protocol("CRC 錯誤".to_owned()));
+    }
+    Ok((adu[0], adu.slice(1..adu.len() - 2)))
+}
+
+/// 封包之間的最短靜默時間
+///
+/// Modbus RTU 以 3.5 個字元時間的靜默區分封包，調變速率高於 19200 時固定為 1.75 毫秒
+#[must_use]
+pub fn frame_gap(settings: &SerialSettings) -> Duration {
+    if settings.baud_rate > 19200 || settings.baud_rate == 0 {
+        return Duration::from_micros(1750);
+    }
+    let bits_per_char = 1
+        + u64::from(settings.data_bits)
+        + u64::from(settings.parity != Parity::None)
+        + u64::from(settings.stop_bits);
+    Duration::from_micros(bits_per_char * 3_500_000 / u64::from(settings.baud_rate))
+}
+
+/// Modbus RTU 主站
+///
+/// 一次只處理一個請求，回覆逾時、CRC 錯誤或內容不符時會捨棄緩衝區中殘留的資料
+///
+/// # 範例
+/// ```rust
+/// use std::time::Duration;
+/// use device_state_exchange_lib::{modbus::{ReadBatch, Table}, modbus_rtu::{ModbusRtuClient, crc16}};
+/// use tokio::io::{AsyncReadExt, AsyncWriteExt};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// assert_eq!(crc16(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x0a]).to_le_bytes(), [0xc5, 0xcd]);
+///
+/// let (master, mut slave) = tokio::io::duplex(256);
+/// // 模擬設備，只回覆第一個請求
+/// tokio::spawn(async move {
+///     let mut request = [0; 8];
+///     slave.read_exact(&mut request).await.unwrap();
+///     assert_eq!(request[..6], [0x01, 0x03, 0x00, 0x00, 0x00, 0x02]);
+///
+///     let mut response = vec![0x01, 0x03, 0x04, 0x00, 0x2a, 0x01, 0x00];
+///     response.extend(crc16(&response).to_le_bytes());
+///     slave.write_all(&response).await.unwrap();
+///     tokio::io::copy(&mut slave, &mut tokio::io::sink()).await.unwrap();
+/// });
+///
+/// let mut client = ModbusRtuClient::new(master, Duration::from_millis(500));
+/// let batch = ReadBatch { unit: 1, table: Table::HoldingRegister, start: 0, count: 2 };
+/// assert_eq!(client.read(&batch).await.unwrap()[..], [0x00, 0x2a, 0x01, 0x00]);
+///
+/// // 設備沒有回應
+/// assert!(client.read(&batch).await.unwrap_err().is_timeout());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ModbusRtuClient<T> {
+    stream: FrameReader<T>,
+    timeout: Duration,
+    frame_gap: Duration,
+}
+
+impl<T: AsyncRead + AsyncWrite + Unpin> ModbusRtuClient<T> {
+    /// 建立主站
+    ///
+    /// # 參數
+    /// - `stream`：序列埠或其他資料來源
+    /// - `timeout`：回覆逾時
+    #[must_use]
+    pub fn new(stream: T, timeout: Duration) -> Self {
+        Self {
+            stream: FrameReader::new(stream),
+            timeout,
+            frame_gap: Duration::ZERO,
+        }
+    }
+
+    /// 設定送出請求前的靜默時間，參見 [`frame_gap()`]
+    #[must_use]
+    pub const fn with_frame_gap(mut self, frame_gap: Duration) -> Self {
+        self.frame_gap = frame_gap;
+        self
+    }
+
+    /// 讀取線圈、離散輸入或暫存器
+    ///
+    /// # 回傳值
+    /// 回覆的資料，線圈與離散輸入每個位元組包含 8 個位元（低位元在前），暫存器以大端序排列
+    ///
+    /// # Errors
+    /// 逾時回傳 [`ConnectionError::Timeout`] ，序列埠讀寫失敗回傳 [`ConnectionError::Io`] ，
+    /// 設備回覆例外時回傳包含 [`modbus::ModbusException`] 的 [`ConnectionError::Custom`] ，其他不符合通訊協定的回覆回傳 [`ConnectionError::Protocol`]
+    pub async fn read(&mut self, batch: &ReadBatch) -> Result<Bytes, ConnectionError> {
+        let response = self.transact(batch.unit, &modbus::read_pdu(*batch)).await?;
+        modbus::read_data(*batch, &response)
+    }
+
+    /// 寫入線圈或保持暫存器
+    ///
+    /// # Errors
+    /// 參見 [`ModbusRtuClient::read()`]
+    pub async fn write(
+        &mut self,
+        unit: u8,
+        address: u16,
+        payload: &WritePayload,
+    ) -> Result<(), ConnectionError> {
+        let request = modbus::write_pdu(address, payload)?;
+        let response = self.transact(unit, &request).await?;
+        modbus::check_write(&request, &response)
+    }
+
+    /// 送出請求並等待回覆
+    ///
+    /// # 參數
+    /// - `unit`：設備編號
+    /// - `pdu`：功能碼與資料
+    ///
+    /// # 回傳值
+    /// 回覆的功能碼與資料，已驗證 CRC 與設備編號
+    ///
+    /// # Errors
+    /// 參見 [`ModbusRtuClient::read()`]
+    pub async fn transact(&mut self, unit: u8, pdu: &[u8]) -> Result<Bytes, ConnectionError> {
+        let result = tokio::time::timeout(self.timeout, self.exchange(unit, pdu)).await;
+        let result = match result {
+            Ok(result) => result,
+            Err(elapsed) => Err(elapsed.into()),
+        };
+        if result.is_err() {
+            self.stream.discard();
+        }
+        result
+    }
+
+    async fn exchange(&mut self, unit: u8, pdu: &[u8]) -> Result<Bytes, ConnectionError> {
+        let mut request = Vec::with_capacity(pdu.len() + 3);
+        request.push(unit);
+        request.extend_from_slice(pdu);
+        request.extend(crc16(&request).to_le_bytes());
+
+        self.stream.discard();
+        if !self.frame_gap.is_zero() {
+            tokio::time::sleep(self.frame_gap).await;
+        }
+        let stream = self.stream.get_mut();
+        stream.write_all(&request).await?;
+        stream.flush().await?;
+
+        let mut frame = BytesMut::from(&self.stream.read_frame(2).await?[..]);
+        let function = frame[1];
+        let remaining = if function & 0x80 != 0 {
+            1
+        } else {
+            match function {
+                0x01..=0x04 => {
+                    let count = self.stream.read_frame(1).await?;
+                    frame.extend_from_slice(&count);
+                    usize::from(count[0])
+                }
+                0x05 | 0x06 | 0x0f | 0x10 => 4,
+                _ => {
+                    return Err(ConnectionError::Protocol(format!(
+                        "無法解析的功能碼 0x{function:02x}"
+                    )));
+                }
+            }
+        };
+        frame.extend_from_slice(&self.stream.read_frame(remaining + 2).await?);
+
+        let (received_unit, response) = split_adu(&frame.freeze())?;
+        if received_unit != unit {
+            return Err(ConnectionError::Protocol(format!(
+                "回覆的設備編號 {received_unit} 與請求不符"
+            )));
+        }
+        modbus::check_function(pdu[0], &response)?;
+        Ok(response)
+    }
+}
+
+/// Modbus RTU 設備連線
+///
+/// 同一個讀取範圍的讀取結果會保留更新間隔的一半，期間內的其他點位直接使用該結果，並跳過等待間隔
+pub struct ModbusRtuConnection {
+    config: ModbusRtuConfig,
+    client: Option<ModbusRtuClient<SerialStream>>,
+    batches: BatchReads,
+}
+
+impl ModbusRtuConnection {
+    /// 初始化點位時被略過的點位
+    #[must_use]
+    pub fn rejected_targets(&self) -> &[InvalidTarget] {
+        self.batches.rejected()
+    }
+
+    fn open(
+        config: &ModbusRtuConfig,
+    ) -> Result<(String, ModbusRtuClient<SerialStream>), ConnectionError> {
+        let path = config.port.resolve().map_err(|error| {
+            ConnectionError::Io(std::io::Error::new(ErrorKind::NotFound, error))
+        })?;
+        let settings = config.settings;
+        let data_bits = match settings.data_bits {
+            5 => tokio_serial::DataBits::Five,
+            6 => tokio_serial::DataBits::Six,
+            7 => tokio_serial::DataBits::Seven,
+            8 => tokio_serial::DataBits::Eight,
+            data_bits => {
+                return Err(ConnectionError::InvalidConfig(format!(
+                    "不支援的數據位 {data_bits}"
+                )));
+            }
+        };
+        let stop_bits = match settings.stop_bits {
+            1 => tokio_serial::StopBits::One,
+            2 => tokio_serial::StopBits::Two,
+            stop_bits => {
+                return Err(ConnectionError::InvalidConfig(format!(
+                    "不支援的停止位 {stop_bits}"
+                )));
+            }
+        };
+        let parity = match settings.parity {
+            Parity::None => tokio_serial::Parity::None,
+            Parity::Odd => tokio_serial::Parity::Odd,
+            Parity::Even => tokio_serial::Parity::Even,
+        };
+
+        let stream = tokio_serial::new(&path, settings.baud_rate)
+            .data_bits(data_bits)
+            .stop_bits(stop_bits)
+            .parity(parity)
+            .open_native_async()
+            .map_err(std::io::Error::from)?;
+        let client =
+            ModbusRtuClient::new(stream, config.timeout).with_frame_gap(frame_gap(&settings));
+        Ok((path, client))
+    }
+
+    fn client(&mut self) -> Result<&mut ModbusRtuClient<SerialStream>, ConnectionError> {
+        self.client
+            .as_mut()
+            .ok_or_else(|| std::io::Error::from(ErrorKind::NotConnected).into())
+    }
+}
+
+impl Connection for ModbusRtuConnection {
+    const NAMES: &[&str] = &["ModbusRtu"];
+    type Config = ModbusRtuConfig;
+    type Target = ModbusTarget;
+    type Request = ModbusRequest;
+    type Response = ModbusResponse;
+    type Result = ();
+
+    async fn init(config: &ModbusRtuConfig) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+        let (path, client) = Self::open(config)?;
+        let artifact = ConnectionArtifact::new(
+            Self {
+                config: config.clone(),
+                client: Some(client),
+                batches: BatchReads::default(),
+            },
+            ConnectionStats::new(path, Some(config.settings.to_string())),
+        )
+        .update_every(config.update_interval)
+        .timeout_after(config.timeout);
+
+        Ok(match config.max_retry_count {
+            Some(count) => artifact.retry_up_to(count),
+            None => artifact,
+        })
+    }
+
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<ModbusTarget>,
+    ) -> ConnectionTargets<ModbusRequest, ()> {
+        self.batches.set_ttl(self.config.update_interval / 2);
+        self.batches.init_targets(
+            connection_statistics,
+            targets,
+            self.config.max_batch_registers,
+            self.config.max_batch_gap,
+        )
+    }
+
+    async fn request_process(
+        &mut self,
+        request: ModbusRequest,
+    ) -> Result<(ModbusResponse, bool), ConnectionError> {
+        if let Some(data) = self.batches.cached(request.batch) {
+            return Ok((request.respond(&data)?, false));
+        }
+
+        let client = self.client()?;
+        let data = client.read(&request.batch).await?;
+        self.batches.store(request.batch, data.clone());
+        Ok((request.respond(&data)?, true))
+    }
+
+    fn write_preprocess(
+        &self,
+        request: ModbusRequest,
+        value: Value,
+        _: &RequestContext,
+    ) -> Result<Box<dyn DeviceStateWrite>, ConnectionError> {
+        request.write(&value)
+    }
+
+    async fn write_process(
+        &mut self,
+        write: Box<dyn DeviceStateWrite>,
+    ) -> Result<Option<ModbusResponse>, ConnectionError> {
+        let write = ModbusWrite::downcast(write)?;
+        self.batches.invalidate(write.batch);
+        let client = self.client()?;
+        client
+            .write(write.unit, write.address, &write.payload)
+            .await?;
+        Ok(None)
+    }
+
+    async fn disconnect(&mut self) -> Result<(), ConnectionError> {
+        self.client = None;
+        Ok(())
+    }
+
+    async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+        self.client = None;
+        self.client = Some(Self::open(&self.config)?.1);
+        Ok(())
+    }
+
+    async fn update_config(&mut self, new_config: &ModbusRtuConfig) -> Result<(), ConnectionError> {
+        self.config = new_config.clone();
+        self.batches.set_ttl(self.config.update_interval / 2);
+        self.reconnect().await
+    }
+}
+
+/// 以讀取回覆的封包解碼點位
+///
+/// `target` 為點位的 `address` ，可在其後以 `/` 加上 `data_type`（如 `hr:0/f32`），欄位說明參見 [`crate::modbus`] ；
+/// 設備編號取自封包，讀取範圍由點位的位址開始，長度取自回覆的位元組數
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::{fixture::Fixture, modbus_rtu::ModbusRtuConnection};
+///
+/// let fixture = Fixture::from_json(r#"{
+///     "connection": "ModbusRtu",
+///     "cases": [
+///         { "name": "保持暫存器", "target": "hr:0", "frame": "01 03 02 002a 399b", "expected": 42 },
+///         { "name": "浮點數", "target": "40001/f32", "frame": "01 03 04 41ac 0000 2e2e", "expected": 21.5 },
+///         { "name": "線圈", "target": "coil:2", "frame": "11 01 01 01 9488", "expected": true },
+///         { "name": "例外回覆", "target": "hr:0", "frame": "01 83 02 c0f1" },
+///         { "name": "CRC 錯誤", "target": "hr:0", "frame": "01 03 02 002a 399c" }
+///     ]
+/// }"#).unwrap();
+///
+/// let report = fixture.run::<ModbusRtuConnection>();
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+impl FrameDecoder for ModbusRtuConnection {
+    fn decode_frame(target: &str, frame: &[u8]) -> Result<ModbusResponse, Box<dyn Error>> {
+        let (unit, pdu) = split_adu(&Bytes::copy_from_slice(frame))?;
+        modbus::decode_read_response(target, unit, &pdu)
+    }
+}
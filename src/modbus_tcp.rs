@@ -0,0 +1,513 @@
+//! Modbus TCP 參考實作（需啟用 `modbus-tcp` feature）
+//!
+//! 與 [`crate::modbus_rtu`] 共用點位、請求與回覆型別（參見 [`crate::modbus`]），同一份點位設定可以直接沿用，差異在於傳輸層：
+//!
+//! - 多設備：閘道器後方的多個設備共用同一個 TCP 連線，以點位的設備編號（unit id）區分
+//! - 交易編號：每個請求帶有遞增的交易編號，逾時後才抵達的舊回覆會被捨棄，不會被誤認為下一個請求的回覆
+//! - 保持連線：設定 [`ModbusTcpConfig::keepalive_interval`] 後，連線閒置時會讀取第一個點位，避免連線被 NAT 或設備中斷
+//! - 自動重新連線：讀寫失敗或對方關閉連線後，下一個請求會先重新連線（參見 [`crate::transport::tcp::connect()`]），
+//!   不需要等到失敗次數超過 [`crate::ConnectionArtifact::max_retry_count`]
+//! - 回歸測試：封包格式的測試向量參見 [`split_adu()`] ，[`ModbusTcpConnection`] 實作 [`FrameDecoder`]
+//!
+//! # 範例
+//! ```rust
+//! use std::time::Duration;
+//! use device_state_exchange_lib::modbus_tcp::ModbusTcpConfig;
+//! use serde_json::json;
+//!
+//! let config: ModbusTcpConfig = serde_json::from_value(json!({
+//!     "host": "192.168.1.10",
+//!     "port": 502,
+//!     "keepalive_interval_ms": 30000,
+//! }))
+//! .unwrap();
+//! assert_eq!(config.endpoint.port, 502);
+//! assert_eq!(config.keepalive_interval, Some(Duration::from_secs(30)));
+//! ```
+
+use std::{error::Error, io::ErrorKind, net::SocketAddr, time::Duration};
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use crate::{
+    Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+    ConnectionTargets, DeviceStateWrite, RemoteAddress, RequestContext,
+    fixture::FrameDecoder,
+    modbus::{
+        self, BatchReads, InvalidTarget, MAX_READ_REGISTERS, ModbusRequest, ModbusResponse,
+        ModbusTarget, ModbusWrite, ReadBatch, WritePayload,
+    },
+    transport::{
+        frame::FrameReader,
+        tcp::{self, TcpEndpoint},
+    },
+};
+
+/// 預設的更新間隔
+pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 預設的回覆逾時，同時作為建立連線的逾時
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// MBAP 標頭長度（交易編號、協定編號、長度與設備編號）
+const MBAP_HEADER_LENGTH: usize = 7;
+
+const fn default_update_interval() -> Duration {
+    DEFAULT_UPDATE_INTERVAL
+}
+
+const fn default_timeout() -> Duration {
+    DEFAULT_TIMEOUT
+}
+
+const fn default_max_batch_registers() -> u16 {
+    MAX_READ_REGISTERS
+}
+
+/// 連線參數
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModbusTcpConfig {
+    /// 連線目標，可經由代理伺服器連線
+    #[serde(flatten)]
+    pub endpoint: TcpEndpoint,
+    /// 更新間隔，序列化時以毫秒數表示，預設為 [`DEFAULT_UPDATE_INTERVAL`]
+    #[serde(
+        rename = "update_interval_ms",
+        with = "crate::millis",
+        default = "default_update_interval"
+    )]
+    pub update_interval: Duration,
+    /// 回覆逾時，序列化時以毫秒數表示，預設為 [`DEFAULT_TIMEOUT`]
+    #[serde(
+        rename = "timeout_ms",
+        with = "crate::millis",
+        default = "default_timeout"
+    )]
+    pub timeout: Duration,
+    /// 保持連線間隔，序列化時以毫秒數表示，參見 [`ConnectionArtifact::keepalive_interval`]
+    #[serde(
+        rename = "keepalive_interval_ms",
+        with = "crate::millis::option",
+        default
+    )]
+    pub keepalive_interval: Option<Duration>,
+    /// 最高重試次數，參見 [`ConnectionArtifact::max_retry_count`]
+    #[serde(default)]
+    pub max_retry_count: Option<u32>,
+    /// 批次讀取的暫存器數量上限，預設為 [`MAX_READ_REGISTERS`] ，設備無法處理長讀取時請調低
+    #[serde(default = "default_max_batch_registers")]
+    pub max_batch_registers: u16,
+    /// 批次讀取時可以跨越的未使用位址數量，預設為 0 ，只合併連續的位址
+    #[serde(default)]
+    pub max_batch_gap: u16,
+}
+
+impl ConnectionConfig for ModbusTcpConfig {}
+
+/// 解析 MBAP 標頭
+///
+/// # 回傳值
+/// 交易編號、設備編號與 PDU 的長度
+fn parse_header(header: &[u8]) -> Result<(u16, u8, usize), ConnectionError> {
+    let length = usize::from(u16::from_be_bytes([header[4], header[5]]));
+    if header[2..4] != [0, 0] || !(2..=254).contains(&length) {
+        return Err(ConnectionError::Protocol("無效的 MBAP 標頭".to_owned()));
+    }
+    Ok((
+        u16::from_be_bytes([header[0], header[1]]),
+        header[6],
+        length - 1,
+    ))
+}
+
+/// 拆解 Modbus TCP 封包（ADU），並驗證 MBAP 標頭
+///
+/// # 回傳值
+/// 交易編號、設備編號與 PDU（功能碼與資料）
+///
+/// # Errors
+/// 協定編號不為 0 、長度欄位無效或與封包長度不符時回傳 [`ConnectionError::Protocol`]
+///
+/// # 範例
+/// 以內建的測試向量驗證：
+/// ```rust
+/// use bytes::Bytes;
+/// use device_state_exchange_lib::{modbus_tcp::split_adu, vectors::{self, encode_hex}};
+/// use serde_json::json;
+///
+/// let report = vectors::built_in_set("modbus_tcp/adu").unwrap().verify(|frame, value| {
+///     match (split_adu(&Bytes::copy_from_slice(frame)), value["pdu"].as_str()) {
+///         (Ok((transaction, unit, pdu)), Some(expected))
+///             if json!(transaction) == value["transaction"] && json!(unit) == value["unit"] && encode_hex(&pdu) == expected => Ok(()),
+///         (Err(_), None) => Ok(()),
+///         (result, _) => Err(format!("拆解結果為 {result:?}")),
+///     }
+/// });
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+pub fn split_adu(adu: &Bytes) -> Result<(u16, u8, Bytes), ConnectionError> {
+    if adu.len() < MBAP_HEADER_LENGTH {
+        return Err(ConnectionError::Protocol("封包長度不足".to_owned()));
+    }
+    let (transaction, unit, length) = parse_header(adu)?;
+    if adu.len() != MBAP_HEADER_LENGTH + length {
+        return Err(ConnectionError::Protocol(
+            "封包長度與 MBAP 標頭不符".to_owned(),
+        ));
+    }
+    Ok((transaction, unit, adu.slice(MBAP_HEADER_LENGTH..)))
+}
+
+/// Modbus TCP 主站
+///
+/// 一次只處理一個請求，請求的設備編號可以不同；交易編號不符的回覆會被捨棄，並繼續等待本次請求的回覆
+///
+/// # 範例
+/// ```rust
+/// use std::time::Duration;
+/// use device_state_exchange_lib::{modbus::{ModbusException, ReadBatch, Table}, modbus_tcp::ModbusTcpClient};
+/// use tokio::io::{AsyncReadExt, AsyncWriteExt};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let (master, mut gateway) = tokio::io::duplex(256);
+/// // 模擬閘道器，後方有設備 1 與設備 2
+/// tokio::spawn(async move {
+///     let mut request = [0; 12];
+///     gateway.read_exact(&mut request).await.unwrap();
+///     assert_eq!(request[6..], [0x01, 0x03, 0x00, 0x00, 0x00, 0x01]);
+///     // 先送出一個過期的回覆，再送出本次請求的回覆
+///     gateway.write_all(&[0xff, 0xff, 0, 0, 0, 5, 0x01, 0x03, 0x02, 0x00, 0x01]).await.unwrap();
+///     gateway.write_all(&[request[0], request[1], 0, 0, 0, 5, 0x01, 0x03, 0x02, 0x00, 0x2a]).await.unwrap();
+///
+///     gateway.read_exact(&mut request).await.unwrap();
+///     assert_eq!(request[6], 0x02);
+///     // 位址無效
+///     gateway.write_all(&[request[0], request[1], 0, 0, 0, 3, 0x02, 0x83, 0x02]).await.unwrap();
+/// });
+///
+/// let mut client = ModbusTcpClient::new(master, Duration::from_millis(500));
+/// let batch = ReadBatch { unit: 1, table: Table::HoldingRegister, start: 0, count: 1 };
+/// assert_eq!(client.read(&batch).await.unwrap()[..], [0x00, 0x2a]);
+///
+/// let error = client.read(&ReadBatch { unit: 2, ..batch }).await.unwrap_err();
+/// assert_eq!(error.downcast_ref(), Some(&ModbusException { function: 0x03, code: 0x02 }));
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ModbusTcpClient<T> {
+    stream: FrameReader<T>,
+    timeout: Duration,
+    transaction: u16,
+    /// 逾時時已讀取標頭、尚未讀取內容的回覆長度
+    pending: usize,
+}
+
+impl<T: AsyncRead + AsyncWrite + Unpin> ModbusTcpClient<T> {
+    /// 建立主站
+    ///
+    /// # 參數
+    /// - `stream`：TCP 連線或其他資料來源
+    /// - `timeout`：回覆逾時
+    #[must_use]
+    pub fn new(stream: T, timeout: Duration) -> Self {
+        Self {
+            stream: FrameReader::new(stream),
+            timeout,
+            transaction: 0,
+            pending: 0,
+        }
+    }
+
+    /// 讀取線圈、離散輸入或暫存器
+    ///
+    /// # 回傳值
+    /// 回覆的資料，線圈與離散輸入每個位元組包含 8 個位元（低位元在前），暫存器以大端序排列
+    ///
+    /// # Errors
+    /// 逾時回傳 [`ConnectionError::Timeout`] ，連線讀寫失敗回傳 [`ConnectionError::Io`] ，
+    /// 設備回覆例外時回傳包含 [`modbus::ModbusException`] 的 [`ConnectionError::Custom`] ，其他不符合通訊協定的回覆回傳 [`ConnectionError::Protocol`]
+    pub async fn read(&mut self, batch: &ReadBatch) -> Result<Bytes, ConnectionError> {
+        let response = self.transact(batch.unit, &modbus::read_pdu(*batch)).await?;
+        modbus::read_data(*batch, &response)
+    }
+
+    /// 寫入線圈或保持暫存器
+    ///
+    /// # Errors
+    /// 參見 [`ModbusTcpClient::read()`]
+    pub async fn write(
+        &mut self,
+        unit: u8,
+        address: u16,
+        payload: &WritePayload,
+    ) -> Result<(), ConnectionError> {
+        let request = modbus::write_pdu(address, payload)?;
+        let response = self.transact(unit, &request).await?;
+        modbus::check_write(&request, &response)
+    }
+
+    /// 送出請求並等待回覆
+    ///
+    /// # 參數
+    /// - `unit`：設備編號
+    /// - `pdu`：功能碼與資料
+    ///
+    /// # 回傳值
+    /// 回覆的功能碼與資料，已驗證交易編號與設備編號
+    ///
+    /// # Errors
+    /// 參見 [`ModbusTcpClient::read()`]
+    pub async fn transact(&mut self, unit: u8, pdu: &[u8]) -> Result<Bytes, ConnectionError> {
+        // 逾時時保留緩衝區中的資料，稍後抵達的回覆讀取完整後會依交易編號捨棄
+        tokio::time::timeout(self.timeout, self.exchange(unit, pdu)).await?
+    }
+
+    async fn exchange(&mut self, unit: u8, pdu: &[u8]) -> Result<Bytes, ConnectionError> {
+        self.transaction = self.transaction.wrapping_add(1);
+        let length = u16::try_from(pdu.len() + 1)
+            .map_err(|_| ConnectionError::Protocol("請求過長".to_owned()))?;
+
+        let mut request = Vec::with_capacity(MBAP_HEADER_LENGTH + pdu.len());
+        request.extend(self.transaction.to_be_bytes());
+        request.extend([0, 0]);
+        request.extend(length.to_be_bytes());
+        request.push(unit);
+        request.extend_from_slice(pdu);
+
+        let stream = self.stream.get_mut();
+        stream.write_all(&request).await?;
+        stream.flush().await?;
+
+        loop {
+            if self.pending > 0 {
+                self.stream.read_frame(self.pending).await?;
+                self.pending = 0;
+            }
+
+            let header = self.stream.read_frame(MBAP_HEADER_LENGTH).await?;
+            let (transaction, received_unit, length) = parse_header(&header)?;
+            self.pending = length;
+            let response = self.stream.read_frame(self.pending).await?;
+            self.pending = 0;
+
+            // 先前逾時的請求的回覆
+            if transaction != self.transaction {
+                continue;
+            }
+            if received_unit != unit {
+                return Err(ConnectionError::Protocol(format!(
+                    "回覆的設備編號 {received_unit} 與請求不符"
+                )));
+            }
+            modbus::check_function(pdu[0], &response)?;
+            return Ok(response);
+        }
+    }
+}
+
+/// Modbus TCP 設備連線
+///
+/// 同一個讀取範圍的讀取結果會保留更新間隔的一半，期間內的其他點位直接使用該結果，並跳過等待間隔
+pub struct ModbusTcpConnection {
+    config: ModbusTcpConfig,
+    client: Option<ModbusTcpClient<TcpStream>>,
+    batches: BatchReads,
+    remote_address: RemoteAddress,
+}
+
+impl ModbusTcpConnection {
+    /// 初始化點位時被略過的點位
+    #[must_use]
+    pub fn rejected_targets(&self) -> &[InvalidTarget] {
+        self.batches.rejected()
+    }
+
+    async fn open(
+        config: &ModbusTcpConfig,
+    ) -> Result<(ModbusTcpClient<TcpStream>, Option<SocketAddr>), ConnectionError> {
+        let stream = tokio::time::timeout(config.timeout, tcp::connect(&config.endpoint)).await??;
+        stream.set_nodelay(true)?;
+        let peer = stream.peer_addr().ok();
+        Ok((ModbusTcpClient::new(stream, config.timeout), peer))
+    }
+
+    /// 取得主站，連線已中斷時先重新連線
+    async fn client(&mut self) -> Result<&mut ModbusTcpClient<TcpStream>, ConnectionError> {
+        if self.client.is_none() {
+            let (client, peer) = Self::open(&self.config).await?;
+            self.remote_address.set(peer);
+            self.client = Some(client);
+        }
+        self.client
+            .as_mut()
+            .ok_or_else(|| std::io::Error::from(ErrorKind::NotConnected).into())
+    }
+
+    /// 讀寫失敗或回覆無法解析時關閉連線，讓下一個請求重新連線
+    ///
+    /// 逾時與例外回覆不影響連線，逾時後才抵達的回覆會依交易編號捨棄
+    fn settle<T>(&mut self, result: Result<T, ConnectionError>) -> Result<T, ConnectionError> {
+        if matches!(
+            result,
+            Err(ConnectionError::Io(_) | ConnectionError::Protocol(_))
+        ) {
+            self.client = None;
+            self.remote_address.set(None);
+        }
+        result
+    }
+}
+
+impl Connection for ModbusTcpConnection {
+    const NAMES: &[&str] = &["ModbusTcp"];
+    type Config = ModbusTcpConfig;
+    type Target = ModbusTarget;
+    type Request = ModbusRequest;
+    type Response = ModbusResponse;
+    type Result = ();
+
+    async fn init(config: &ModbusTcpConfig) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+        let (client, peer) = Self::open(config).await?;
+        let statistics = ConnectionStats::new(
+            format!("{}:{}", config.endpoint.host, config.endpoint.port),
+            None,
+        );
+        statistics.remote_address.set(peer);
+
+        let artifact = ConnectionArtifact::new(
+            Self {
+                config: config.clone(),
+                client: Some(client),
+                batches: BatchReads::default(),
+                remote_address: statistics.remote_address.clone(),
+            },
+            statistics,
+        )
+        .update_every(config.update_interval)
+        .timeout_after(config.timeout);
+        let artifact = match config.keepalive_interval {
+            Some(interval) => artifact.keepalive_every(interval),
+            None => artifact,
+        };
+
+        Ok(match config.max_retry_count {
+            Some(count) => artifact.retry_up_to(count),
+            None => artifact,
+        })
+    }
+
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<ModbusTarget>,
+    ) -> ConnectionTargets<ModbusRequest, ()> {
+        self.batches.set_ttl(self.config.update_interval / 2);
+        self.batches.init_targets(
+            connection_statistics,
+            targets,
+            self.config.max_batch_registers,
+            self.config.max_batch_gap,
+        )
+    }
+
+    async fn request_process(
+        &mut self,
+        request: ModbusRequest,
+    ) -> Result<(ModbusResponse, bool), ConnectionError> {
+        if let Some(data) = self.batches.cached(request.batch) {
+            return Ok((request.respond(&data)?, false));
+        }
+
+        let client = self.client().await?;
+        let result = client.read(&request.batch).await;
+        let data = self.settle(result)?;
+        self.batches.store(request.batch, data.clone());
+        Ok((request.respond(&data)?, true))
+    }
+
+    fn write_preprocess(
+        &self,
+        request: ModbusRequest,
+        value: Value,
+        _: &RequestContext,
+    ) -> Result<Box<dyn DeviceStateWrite>, ConnectionError> {
+        request.write(&value)
+    }
+
+    async fn write_process(
+        &mut self,
+        write: Box<dyn DeviceStateWrite>,
+    ) -> Result<Option<ModbusResponse>, ConnectionError> {
+        let write = ModbusWrite::downcast(write)?;
+        self.batches.invalidate(write.batch);
+        let client = self.client().await?;
+        let result = client
+            .write(write.unit, write.address, &write.payload)
+            .await;
+        self.settle(result)?;
+        Ok(None)
+    }
+
+    async fn keepalive(&mut self) -> Result<(), ConnectionError> {
+        let Some(batch) = self.batches.probe() else {
+            return Ok(());
+        };
+        let client = self.client().await?;
+        let result = client.read(&batch).await;
+        self.settle(result).map(|_| ())
+    }
+
+    async fn disconnect(&mut self) -> Result<(), ConnectionError> {
+        self.client = None;
+        self.remote_address.set(None);
+        Ok(())
+    }
+
+    async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+        self.client = None;
+        self.remote_address.set(None);
+        self.client().await.map(|_| ())
+    }
+
+    async fn update_config(&mut self, new_config: &ModbusTcpConfig) -> Result<(), ConnectionError> {
+        self.config = new_config.clone();
+        self.batches.set_ttl(self.config.update_interval / 2);
+        self.reconnect().await
+    }
+}
+
+/// 以讀取回覆的封包解碼點位
+///
+/// `target` 的格式與 [`crate::modbus_rtu::ModbusRtuConnection`] 相同，為點位的 `address` ，可在其後以 `/` 加上 `data_type`；
+/// 設備編號取自 MBAP 標頭，交易編號會被忽略
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::{fixture::Fixture, modbus_tcp::ModbusTcpConnection};
+///
+/// let fixture = Fixture::from_json(r#"{
+///     "connection": "ModbusTcp",
+///     "cases": [
+///         { "name": "保持暫存器", "target": "hr:0", "frame": "0001 0000 0005 01 03 02 002a", "expected": 42 },
+///         { "name": "輸入暫存器", "target": "ir:10/i16", "frame": "0002 0000 0005 02 04 02 ff9c", "expected": -100 },
+///         { "name": "例外回覆", "target": "hr:0", "frame": "0003 0000 0003 01 83 02" },
+///         { "name": "長度不符", "target": "hr:0", "frame": "0004 0000 0006 01 03 02 002a" }
+///     ]
+/// }"#).unwrap();
+///
+/// let report = fixture.run::<ModbusTcpConnection>();
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+impl FrameDecoder for ModbusTcpConnection {
+    fn decode_frame(target: &str, frame: &[u8]) -> Result<ModbusResponse, Box<dyn Error>> {
+        let (_, unit, pdu) = split_adu(&Bytes::copy_from_slice(frame))?;
+        modbus::decode_read_response(target, unit, &pdu)
+    }
+}
@@ -0,0 +1,802 @@
+//! MQTT 橋接（需啟用 `mqtt` feature）
+//!
+//! 許多設備會主動將狀態發佈至 MQTT ，而不是等待輪詢，[`MqttConnection`] 訂閱各點位的狀態主題，將收到的最新訊息存放於 [`ValueCache`] ，
+//! [`Connection::request_process()`] 直接由快取取得數值，寫入時則發佈至點位的命令主題：
+//!
+//! - 訊息內容：可解析為 JSON 時以 JSON 處理，否則視為字串（去除前後空白），參見 [`decode_payload()`] ；
+//!   [`MqttConnection`] 實作 [`FrameDecoder`] ，可以用記錄下來的訊息驗證解碼邏輯
+//! - 遺囑訊息（LWT）：設定 [`MqttConfig::will`] 後，本連線異常中斷時由 broker 發佈離線訊息，每次連線成功時發佈上線訊息，
+//!   以 [`Connection::disconnect()`] 正常中斷時也會發佈離線訊息
+//! - 設備可用性：點位設定了 `availability_topic` 時，該主題最新的訊息為離線訊息時，點位的請求回傳 [`MqttUnavailable::Offline`]
+//! - 重新訂閱：與 broker 的連線中斷後會在背景自動重新連線，每次連線成功後都會重新訂閱所有主題，不依賴 broker 保留的工作階段
+//!
+//! 點位設定：
+//!
+//! | 欄位 | 說明 |
+//! | --- | --- |
+//! | `address` | 狀態主題 |
+//! | `value_path` | 數值在訊息中的位置，可使用 JSON Pointer（如 `/sensor/temperature`）或以 `.` 分隔（如 `sensor.temperature`），未設定時為整個訊息 |
+//! | `command_topic` | 寫入時發佈的主題，未設定時為 `{address}/set` |
+//! | `retain` | 寫入時是否要求 broker 保留訊息，未設定時為 `false` |
+//! | `availability_topic` | 設備可用性主題 |
+//! | `payload_offline` | 設備離線時可用性主題的訊息，未設定時為 `offline` |
+//! | `stale_after_ms` | 訊息的有效期限，未設定時使用 [`MqttConfig::stale_after`] |
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{TargetDefinition, mqtt::{MqttConfig, MqttRequest, decode_payload}};
+//! use serde_json::json;
+//!
+//! let config: MqttConfig = serde_json::from_value(json!({
+//!     "host": "broker.local",
+//!     "client_id": "gateway-01",
+//!     "will": { "topic": "gateway-01/status" },
+//! }))
+//! .unwrap();
+//! assert_eq!(config.port, 1883);
+//! assert_eq!(config.will.unwrap().offline_payload, "offline");
+//!
+//! let definition: TargetDefinition = serde_json::from_value(json!({
+//!     "name": "溫度",
+//!     "address": "plant/boiler/state",
+//!     "value_path": "sensor.temperature",
+//!     "availability_topic": "plant/boiler/availability",
+//! }))
+//! .unwrap();
+//! let request = MqttRequest::from_definition(&definition);
+//! assert_eq!(request.command_topic, "plant/boiler/state/set");
+//!
+//! let message = decode_payload(br#"{ "sensor": { "temperature": 71.5 } }"#);
+//! assert_eq!(request.extract(&message).unwrap(), json!(71.5));
+//! assert_eq!(decode_payload(b" ON \n"), json!("ON"));
+//! ```
+
+use std::{
+    borrow::Cow,
+    collections::BTreeSet,
+    error::Error,
+    fmt::Display,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use rumqttc::{
+    AsyncClient, Event, EventLoop, LastWill, MqttOptions, Outgoing, Packet, QoS, SubscribeFilter,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{sync::Notify, task::JoinHandle};
+
+use crate::{
+    Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+    ConnectionTargets, DeviceStateRequest, DeviceStateResponse, DeviceStateWrite, InitedTarget,
+    RequestContext, Target, TargetDefinition,
+    cache::ValueCache,
+    fixture::FrameDecoder,
+    value::{ConversionError, Quality},
+};
+
+/// 預設的 broker 連接埠
+pub const DEFAULT_PORT: u16 = 1883;
+
+/// 預設的更新間隔
+pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 預設的逾時，用於建立連線與等待點位的第一個訊息
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 預設的 MQTT keep alive 間隔
+pub const DEFAULT_KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+/// 與 broker 的連線中斷後，重新連線前的等待時間
+pub const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// 送往背景工作的請求佇列長度
+const REQUEST_CAPACITY: usize = 64;
+
+const fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+const fn default_update_interval() -> Duration {
+    DEFAULT_UPDATE_INTERVAL
+}
+
+const fn default_timeout() -> Duration {
+    DEFAULT_TIMEOUT
+}
+
+const fn default_keep_alive() -> Duration {
+    DEFAULT_KEEP_ALIVE
+}
+
+fn default_offline_payload() -> String {
+    "offline".to_owned()
+}
+
+fn default_online_payload() -> String {
+    "online".to_owned()
+}
+
+const fn default_will_retain() -> bool {
+    true
+}
+
+/// 連線參數
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MqttConfig {
+    /// broker 主機名稱或 IP 位址
+    pub host: String,
+    /// broker 連接埠，預設為 [`DEFAULT_PORT`]
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// 用戶端識別碼，同一個 broker 上不可重複
+    pub client_id: String,
+    /// 帳號密碼，未設定時不驗證
+    #[serde(default)]
+    pub credentials: Option<MqttCredentials>,
+    /// 訂閱與發佈使用的服務品質（QoS）
+    #[serde(default)]
+    pub qos: MqttQos,
+    /// 更新間隔，序列化時以毫秒數表示，預設為 [`DEFAULT_UPDATE_INTERVAL`]
+    #[serde(
+        rename = "update_interval_ms",
+        with = "crate::millis",
+        default = "default_update_interval"
+    )]
+    pub update_interval: Duration,
+    /// 逾時，序列化時以毫秒數表示，預設為 [`DEFAULT_TIMEOUT`]
+    #[serde(
+        rename = "timeout_ms",
+        with = "crate::millis",
+        default = "default_timeout"
+    )]
+    pub timeout: Duration,
+    /// MQTT keep alive 間隔，序列化時以毫秒數表示，以秒為單位向上取整，為 0 時停用，預設為 [`DEFAULT_KEEP_ALIVE`]
+    #[serde(
+        rename = "keep_alive_ms",
+        with = "crate::millis",
+        default = "default_keep_alive"
+    )]
+    pub keep_alive: Duration,
+    /// 訊息的有效期限，序列化時以毫秒數表示，超過期限沒有收到新訊息時，點位的請求回傳 [`MqttUnavailable::Stale`] ；未設定時不會過期
+    #[serde(rename = "stale_after_ms", with = "crate::millis::option", default)]
+    pub stale_after: Option<Duration>,
+    /// 本連線的遺囑訊息
+    #[serde(default)]
+    pub will: Option<MqttWill>,
+}
+
+impl ConnectionConfig for MqttConfig {}
+
+impl MqttConfig {
+    fn options(&self) -> MqttOptions {
+        let mut options = MqttOptions::new(&self.client_id, &self.host, self.port);
+        let keep_alive = Duration::from_secs(
+            u64::try_from(self.keep_alive.as_millis().div_ceil(1000)).unwrap_or(u64::MAX),
+        );
+        options.set_keep_alive(keep_alive).set_clean_session(true);
+        if let Some(credentials) = &self.credentials {
+            options.set_credentials(&credentials.username, &credentials.password);
+        }
+        if let Some(will) = &self.will {
+            options.set_last_will(LastWill::new(
+                &will.topic,
+                will.offline_payload.as_bytes(),
+                self.qos.into(),
+                will.retain,
+            ));
+        }
+        options
+    }
+}
+
+/// broker 帳號密碼
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MqttCredentials {
+    /// 帳號
+    pub username: String,
+    /// 密碼
+    pub password: String,
+}
+
+impl std::fmt::Debug for MqttCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MqttCredentials")
+            .field("username", &self.username)
+            .field("password", &"***")
+            .finish()
+    }
+}
+
+/// 服務品質（QoS）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MqttQos {
+    /// 最多一次（QoS 0）
+    #[default]
+    AtMostOnce,
+    /// 至少一次（QoS 1）
+    AtLeastOnce,
+    /// 恰好一次（QoS 2）
+    ExactlyOnce,
+}
+
+impl From<MqttQos> for QoS {
+    fn from(qos: MqttQos) -> Self {
+        match qos {
+            MqttQos::AtMostOnce => Self::AtMostOnce,
+            MqttQos::AtLeastOnce => Self::AtLeastOnce,
+            MqttQos::ExactlyOnce => Self::ExactlyOnce,
+        }
+    }
+}
+
+/// 遺囑訊息設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MqttWill {
+    /// 主題
+    pub topic: String,
+    /// 離線訊息，預設為 `offline`
+    #[serde(default = "default_offline_payload")]
+    pub offline_payload: String,
+    /// 上線訊息，預設為 `online`
+    #[serde(default = "default_online_payload")]
+    pub online_payload: String,
+    /// 是否要求 broker 保留訊息，預設為 `true`
+    #[serde(default = "default_will_retain")]
+    pub retain: bool,
+}
+
+/// 解析訊息內容
+///
+/// # 回傳值
+/// 可解析為 JSON 時為解析結果，否則為去除前後空白的字串（無效的 UTF-8 會被取代）
+///
+/// # 範例
+/// 以內建的測試向量驗證：
+/// ```rust
+/// use device_state_exchange_lib::{mqtt::decode_payload, vectors};
+///
+/// let report = vectors::built_in_set("mqtt/payload").unwrap().verify(|frame, value| {
+///     let decoded = decode_payload(frame);
+///     if decoded == *value { Ok(()) } else { Err(format!("解析結果為 {decoded}")) }
+/// });
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+#[must_use]
+pub fn decode_payload(payload: &[u8]) -> Value {
+    serde_json::from_slice(payload)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(payload).trim().to_owned()))
+}
+
+/// 將數值轉換為發佈的訊息內容，字串直接發佈，其他數值以 JSON 發佈
+fn encode_payload(value: &Value) -> Vec<u8> {
+    match value {
+        Value::String(text) => text.as_bytes().to_vec(),
+        value => value.to_string().into_bytes(),
+    }
+}
+
+/// 點位無法取得數值的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MqttUnavailable {
+    /// 超過有效期限沒有收到新訊息
+    Stale {
+        /// 狀態主題
+        topic: String,
+    },
+    /// 設備可用性主題回報離線
+    Offline {
+        /// 可用性主題
+        topic: String,
+    },
+}
+
+impl Display for MqttUnavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stale { topic } => write!(f, "主題「{topic}」的訊息已超過有效期限"),
+            Self::Offline { topic } => write!(f, "主題「{topic}」回報設備離線"),
+        }
+    }
+}
+
+impl Error for MqttUnavailable {}
+
+/// 點位
+#[derive(Debug, Clone)]
+pub struct MqttTarget(pub TargetDefinition);
+
+impl Target for MqttTarget {}
+
+/// 設備可用性設定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Availability {
+    /// 可用性主題
+    pub topic: String,
+    /// 設備離線時的訊息，已以 [`decode_payload()`] 解析
+    pub offline: Value,
+}
+
+/// 讀取請求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MqttRequest {
+    /// 狀態主題
+    pub topic: String,
+    /// 數值在訊息中的位置（JSON Pointer），為 [`None`] 時為整個訊息
+    pub value_path: Option<String>,
+    /// 命令主題
+    pub command_topic: String,
+    /// 寫入時是否要求 broker 保留訊息
+    pub retain: bool,
+    /// 設備可用性
+    pub availability: Option<Availability>,
+    /// 訊息的有效期限，為 [`None`] 時使用 [`MqttConfig::stale_after`]
+    pub stale_after: Option<Duration>,
+}
+
+impl DeviceStateRequest for MqttRequest {}
+
+impl MqttRequest {
+    /// 由通用點位定義轉換，欄位說明參見 [`crate::mqtt`] ，型別不符的欄位會被忽略
+    #[must_use]
+    pub fn from_definition(definition: &TargetDefinition) -> Self {
+        let text = |key: &str| {
+            definition
+                .extra
+                .get(key)
+                .and_then(Value::as_str)
+                .map(str::to_owned)
+        };
+
+        Self {
+            topic: definition.address.clone(),
+            value_path: text("value_path")
+                .filter(|path| !path.is_empty())
+                .map(|path| {
+                    if path.starts_with('/') {
+                        path
+                    } else {
+                        format!("/{}", path.replace('.', "/"))
+                    }
+                }),
+            command_topic: text("command_topic")
+                .unwrap_or_else(|| format!("{}/set", definition.address)),
+            retain: definition
+                .extra
+                .get("retain")
+                .and_then(Value::as_bool)
+                .unwrap_or_default(),
+            availability: text("availability_topic").map(|topic| Availability {
+                topic,
+                offline: decode_payload(
+                    text("payload_offline")
+                        .as_deref()
+                        .unwrap_or("offline")
+                        .as_bytes(),
+                ),
+            }),
+            stale_after: definition
+                .extra
+                .get("stale_after_ms")
+                .and_then(Value::as_u64)
+                .map(Duration::from_millis),
+        }
+    }
+
+    /// 由訊息中取出數值
+    ///
+    /// # Errors
+    /// 訊息中沒有 [`MqttRequest::value_path`] 指定的欄位時回傳 [`ConversionError::InvalidRaw`]
+    pub fn extract(&self, message: &Value) -> Result<Value, ConversionError> {
+        self.value_path.as_ref().map_or_else(
+            || Ok(message.clone()),
+            |path| {
+                message
+                    .pointer(path)
+                    .cloned()
+                    .ok_or_else(|| ConversionError::InvalidRaw(format!("訊息中沒有「{path}」")))
+            },
+        )
+    }
+}
+
+/// 讀取回覆
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MqttResponse {
+    /// 數值
+    pub value: Value,
+}
+
+impl DeviceStateResponse for MqttResponse {
+    fn to_value(&self) -> Result<Cow<'_, Value>, ConversionError> {
+        Ok(Cow::Borrowed(&self.value))
+    }
+}
+
+/// 寫入請求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MqttWrite {
+    /// 命令主題
+    pub topic: String,
+    /// 訊息內容
+    pub payload: Vec<u8>,
+    /// 是否要求 broker 保留訊息
+    pub retain: bool,
+}
+
+impl DeviceStateWrite for MqttWrite {}
+
+/// 訂閱的主題與收到的最新訊息，與背景工作共用
+#[derive(Debug)]
+struct Subscriptions {
+    messages: ValueCache,
+    topics: Mutex<BTreeSet<String>>,
+    connected: AtomicBool,
+    notify: Notify,
+}
+
+impl Subscriptions {
+    fn new(stale_after: Option<Duration>) -> Self {
+        let messages = ValueCache::new();
+        Self {
+            messages: match stale_after {
+                Some(stale_after) => messages.with_stale_after(stale_after),
+                None => messages,
+            },
+            topics: Mutex::new(BTreeSet::new()),
+            connected: AtomicBool::new(false),
+            notify: Notify::new(),
+        }
+    }
+
+    fn filters(&self, qos: QoS) -> Vec<SubscribeFilter> {
+        self.topics
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|topic| SubscribeFilter::new(topic.clone(), qos))
+            .collect()
+    }
+
+    /// 連線成功後重新訂閱所有主題，並發佈上線訊息
+    fn connected(&self, client: &AsyncClient, config: &MqttConfig) {
+        let filters = self.filters(config.qos.into());
+        if !filters.is_empty() {
+            let _ = client.try_subscribe_many(filters);
+        }
+        if let Some(will) = &config.will {
+            let _ = client.try_publish(
+                &will.topic,
+                config.qos.into(),
+                will.retain,
+                will.online_payload.as_bytes(),
+            );
+        }
+        self.connected.store(true, Ordering::Relaxed);
+        self.notify.notify_waiters();
+    }
+
+    fn received(&self, topic: &str, payload: &[u8]) {
+        self.messages
+            .update(topic, decode_payload(payload), Quality::Good);
+        self.notify.notify_waiters();
+    }
+}
+
+/// 背景工作，持續處理與 broker 之間的封包，連線中斷時會自動重新連線，送出中斷連線的封包後結束
+async fn drive(
+    mut eventloop: EventLoop,
+    client: AsyncClient,
+    subscriptions: Arc<Subscriptions>,
+    config: MqttConfig,
+) {
+    loop {
+        match eventloop.poll().await {
+            Ok(Event::Incoming(Packet::ConnAck(_))) => subscriptions.connected(&client, &config),
+            Ok(Event::Incoming(Packet::Publish(publish))) => {
+                subscriptions.received(&publish.topic, &publish.payload);
+            }
+            Ok(Event::Outgoing(Outgoing::Disconnect)) => return,
+            Ok(_) => {}
+            Err(_) => {
+                subscriptions.connected.store(false, Ordering::Relaxed);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        }
+    }
+}
+
+/// 與 broker 的一次連線
+#[derive(Debug)]
+struct Session {
+    client: AsyncClient,
+    task: JoinHandle<()>,
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// MQTT 橋接連線
+pub struct MqttConnection {
+    config: MqttConfig,
+    session: Option<Session>,
+    subscriptions: Arc<Subscriptions>,
+}
+
+impl MqttConnection {
+    /// 連線至 broker ，並在背景持續處理封包
+    async fn open(
+        config: &MqttConfig,
+        subscriptions: &Arc<Subscriptions>,
+    ) -> Result<Session, ConnectionError> {
+        let (client, mut eventloop) = AsyncClient::new(config.options(), REQUEST_CAPACITY);
+        tokio::time::timeout(config.timeout, async {
+            loop {
+                match eventloop.poll().await {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => return Ok(()),
+                    Ok(_) => {}
+                    Err(error) => return Err(ConnectionError::custom(error)),
+                }
+            }
+        })
+        .await??;
+        subscriptions.connected(&client, config);
+
+        let task = tokio::spawn(drive(
+            eventloop,
+            client.clone(),
+            Arc::clone(subscriptions),
+            config.clone(),
+        ));
+        Ok(Session { client, task })
+    }
+
+    /// 中斷與 broker 的連線，設定了遺囑訊息時先發佈離線訊息
+    async fn close(&mut self) {
+        if let Some(mut session) = self.session.take() {
+            if let Some(will) = &self.config.will {
+                let _ = session
+                    .client
+                    .publish(
+                        &will.topic,
+                        self.config.qos.into(),
+                        will.retain,
+                        will.offline_payload.as_bytes(),
+                    )
+                    .await;
+            }
+            let _ = session.client.disconnect().await;
+            let _ = tokio::time::timeout(self.config.timeout, &mut session.task).await;
+        }
+        self.subscriptions.connected.store(false, Ordering::Relaxed);
+    }
+
+    fn session(&self) -> Result<&Session, ConnectionError> {
+        self.session
+            .as_ref()
+            .filter(|_| self.subscriptions.connected.load(Ordering::Relaxed))
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotConnected).into())
+    }
+}
+
+impl Connection for MqttConnection {
+    const NAMES: &[&str] = &["Mqtt"];
+    type Config = MqttConfig;
+    type Target = MqttTarget;
+    type Request = MqttRequest;
+    type Response = MqttResponse;
+    type Result = ();
+
+    async fn init(config: &MqttConfig) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+        let subscriptions = Arc::new(Subscriptions::new(config.stale_after));
+        let session = Self::open(config, &subscriptions).await?;
+
+        Ok(ConnectionArtifact::new(
+            Self {
+                config: config.clone(),
+                session: Some(session),
+                subscriptions,
+            },
+            ConnectionStats::new(format!("{}:{}", config.host, config.port), None),
+        )
+        .update_every(config.update_interval)
+        .timeout_after(config.timeout))
+    }
+
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<MqttTarget>,
+    ) -> ConnectionTargets<MqttRequest, ()> {
+        let targets: Vec<_> = targets
+            .into_iter()
+            .map(|MqttTarget(definition)| {
+                let request = MqttRequest::from_definition(&definition);
+                (definition, request)
+            })
+            .collect();
+
+        {
+            let mut topics = self
+                .subscriptions
+                .topics
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            for (_, request) in &targets {
+                topics.insert(request.topic.clone());
+                topics.extend(
+                    request
+                        .availability
+                        .iter()
+                        .map(|availability| availability.topic.clone()),
+                );
+            }
+        }
+        for (_, request) in &targets {
+            if let Some(stale_after) = request.stale_after {
+                self.subscriptions
+                    .messages
+                    .set_stale_after(request.topic.clone(), Some(stale_after));
+            }
+        }
+        if let Some(session) = &self.session {
+            let filters = self.subscriptions.filters(self.config.qos.into());
+            if !filters.is_empty() {
+                let _ = session.client.try_subscribe_many(filters);
+            }
+        }
+
+        ConnectionTargets(
+            targets
+                .into_iter()
+                .map(|(definition, request)| InitedTarget {
+                    name: definition.name,
+                    request,
+                    result: (),
+                    default_status: definition.default_status,
+                    auto_refresh: definition.auto_refresh,
+                    refresh_interval: None,
+                    keep_raw_frames: None,
+                    group: None,
+                    safe_state: None,
+                    array: None,
+                    change: None,
+                    statistics: Some(connection_statistics.insert_target(definition.device)),
+                })
+                .collect(),
+        )
+    }
+
+    async fn request_process(
+        &mut self,
+        request: MqttRequest,
+    ) -> Result<(MqttResponse, bool), ConnectionError> {
+        self.session()?;
+        if let Some(availability) = &request.availability
+            && self
+                .subscriptions
+                .messages
+                .get(&availability.topic)
+                .is_some_and(|cached| cached.value == availability.offline)
+        {
+            return Err(ConnectionError::custom(MqttUnavailable::Offline {
+                topic: availability.topic.clone(),
+            }));
+        }
+
+        // 尚未收到訊息時，等待第一個訊息
+        let subscriptions = &self.subscriptions;
+        let cached = tokio::time::timeout(self.config.timeout, async {
+            loop {
+                let notified = subscriptions.notify.notified();
+                if let Some(cached) = subscriptions.messages.get(&request.topic) {
+                    return cached;
+                }
+                notified.await;
+            }
+        })
+        .await?;
+        if cached.is_stale() {
+            return Err(ConnectionError::custom(MqttUnavailable::Stale {
+                topic: request.topic,
+            }));
+        }
+
+        let value = request
+            .extract(&cached.value)
+            .map_err(|error| ConnectionError::Protocol(error.to_string()))?;
+        Ok((MqttResponse { value }, true))
+    }
+
+    fn write_preprocess(
+        &self,
+        request: MqttRequest,
+        value: Value,
+        _: &RequestContext,
+    ) -> Result<Box<dyn DeviceStateWrite>, ConnectionError> {
+        Ok(Box::new(MqttWrite {
+            topic: request.command_topic,
+            payload: encode_payload(&value),
+            retain: request.retain,
+        }))
+    }
+
+    async fn write_process(
+        &mut self,
+        write: Box<dyn DeviceStateWrite>,
+    ) -> Result<Option<MqttResponse>, ConnectionError> {
+        let write = write
+            .downcast::<MqttWrite>()
+            .map_err(|_| ConnectionError::Protocol("非預期的寫入請求".to_owned()))?;
+
+        let client = self.session()?.client.clone();
+        client
+            .publish(
+                write.topic,
+                self.config.qos.into(),
+                write.retain,
+                write.payload,
+            )
+            .await
+            .map_err(ConnectionError::custom)?;
+        Ok(None)
+    }
+
+    async fn disconnect(&mut self) -> Result<(), ConnectionError> {
+        self.close().await;
+        Ok(())
+    }
+
+    async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+        self.close().await;
+        self.session = Some(Self::open(&self.config, &self.subscriptions).await?);
+        Ok(())
+    }
+
+    async fn update_config(&mut self, new_config: &MqttConfig) -> Result<(), ConnectionError> {
+        self.close().await;
+        self.config = new_config.clone();
+        self.session = Some(Self::open(&self.config, &self.subscriptions).await?);
+        Ok(())
+    }
+}
+
+/// 以記錄下來的訊息內容解碼點位
+///
+/// `target` 為點位的 `value_path` ，空字串表示整個訊息
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::{fixture::Fixture, mqtt::MqttConnection};
+///
+/// let fixture = Fixture::from_json(r#"{
+///     "connection": "Mqtt",
+///     "cases": [
+///         { "name": "巢狀欄位", "target": "sensor.temperature", "frame": "7b2273656e736f72223a7b2274656d7065726174757265223a37312e357d7d", "expected": 71.5 },
+///         { "name": "整個訊息", "target": "", "frame": "204f4e200a", "expected": "ON" },
+///         { "name": "缺少欄位", "target": "/sensor/humidity", "frame": "7b2273656e736f72223a7b2274656d7065726174757265223a37312e357d7d" }
+///     ]
+/// }"#).unwrap();
+///
+/// let report = fixture.run::<MqttConnection>();
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+impl FrameDecoder for MqttConnection {
+    fn decode_frame(target: &str, frame: &[u8]) -> Result<MqttResponse, Box<dyn Error>> {
+        let request = MqttRequest::from_definition(&serde_json::from_value(serde_json::json!({
+            "name": target,
+            "address": "",
+            "value_path": target,
+        }))?);
+        let value = request.extract(&decode_payload(frame))?;
+        Ok(MqttResponse { value })
+    }
+}
@@ -0,0 +1,238 @@
+//! 多點位回覆
+//!
+//! 部分通訊協定一次請求會回傳整筆紀錄（如 SNMP GETBULK 或 S7 的整個資料區塊），內容涵蓋多個點位，
+//! 實作者可以在 [`crate::Connection::request_process()`] 中回傳 [`MultiResponse`] ，以點位名稱分別存放各點位的數值，
+//! 主程式收到回覆後以 [`DeviceStateResponse::downcast_ref()`] 取得 [`MultiResponse`] ，再以 [`MultiResponse::apply()`] 拆分至宣告的成員點位：
+//!
+//! - 回覆中有數值的成員會以 [`StateStore::update()`] 寫入狀態
+//! - 回覆中轉換失敗或缺少的成員會標記為 [`Quality::Bad`] ，並保留原本的數值
+//! - 各成員的 [`TargetStats`] 分別記錄本次請求的結果，多個成員共用同一份統計數據時只記錄一次，任一成員失敗即記錄為失敗
+//! - 回覆中未宣告為成員的點位會被忽略
+//!
+//! # 範例
+//! ```rust
+//! # use std::{error::Error, sync::Arc};
+//! # use device_state_exchange_lib::*;
+//! use device_state_exchange_lib::{multi::MultiResponse, value::Quality};
+//! use serde_json::json;
+//!
+//! # #[derive(Debug, Clone)] struct Request;
+//! # impl DeviceStateRequest for Request {}
+//! let mut statistics = ConnectionStats::new("192.168.1.10:161", None);
+//! let device = statistics.insert_target(Some("1".to_owned()));
+//! let targets = ConnectionTargets(
+//!     ["uptime", "in_octets", "out_octets"]
+//!         .map(|name| InitedTarget { name: name.to_owned(), request: Request, result: (), default_status: None, auto_refresh: true, refresh_interval: None, keep_raw_frames: None, group: None, safe_state: None, array: None, change: None, statistics: Some(Arc::clone(&device)) })
+//!         .into(),
+//! );
+//! let store = StateStore::new();
+//! for target in &targets.0 {
+//!     store.register(&target.name, None);
+//! }
+//!
+//! // 一次 GETBULK 請求的回覆
+//! let response = MultiResponse::new()
+//!     .with("uptime", json!(86400))
+//!     .with("in_octets", json!(1_048_576))
+//!     .with_error("out_octets", "noSuchInstance")
+//!     .with("if_speed", json!(1_000_000_000));
+//! let response: Box<dyn DeviceStateResponse> = Box::new(response);
+//!
+//! let report = response
+//!     .downcast_ref::<MultiResponse>()
+//!     .unwrap()
+//!     .apply(&targets, ["uptime", "in_octets", "out_octets"], &store, 35);
+//!
+//! assert_eq!(report.updated, ["uptime", "in_octets"]);
+//! assert_eq!(report.failed, ["out_octets"]);
+//! assert_eq!(report.ignored, ["if_speed"]);
+//! assert_eq!(store.read("in_octets").unwrap().value, Some(json!(1_048_576)));
+//! assert_eq!(store.read("out_octets").unwrap().quality, Quality::Bad);
+//!
+//! // 三個成員共用同一份統計數據，只記錄一次請求
+//! assert_eq!(statistics.snapshot().summary.total_polling_count, 1);
+//! assert_eq!(statistics.snapshot().summary.failed_poll_count, 1);
+//! ```
+
+use std::{borrow::Cow, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{
+    ConnectionTargets, DeviceStateRequest, DeviceStateResponse, TargetStats,
+    state::StateStore,
+    value::{self, Quality},
+};
+
+/// 多點位回覆
+///
+/// 以點位名稱存放各點位的數值或錯誤訊息，依加入的順序排列
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MultiResponse {
+    members: Vec<(String, Result<Value, String>)>,
+}
+
+impl MultiResponse {
+    /// 建立空的多點位回覆
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 加入點位的數值
+    #[must_use]
+    pub fn with(mut self, name: impl Into<String>, value: Value) -> Self {
+        self.insert(name, Ok(value));
+        self
+    }
+
+    /// 加入點位的錯誤，如設備回覆該點位不存在或無法解析
+    #[must_use]
+    pub fn with_error(mut self, name: impl Into<String>, error: impl Into<String>) -> Self {
+        self.insert(name, Err(error.into()));
+        self
+    }
+
+    /// 加入點位的數值或錯誤，點位已存在時會取代原本的內容
+    pub fn insert(&mut self, name: impl Into<String>, result: Result<Value, String>) {
+        let name = name.into();
+        match self.members.iter_mut().find(|(member, _)| *member == name) {
+            Some((_, member)) => *member = result,
+            None => self.members.push((name, result)),
+        }
+    }
+
+    /// 取得點位的數值，點位不存在時為 [`None`]
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<Result<&Value, &str>> {
+        self.members
+            .iter()
+            .find(|(member, _)| member == name)
+            .map(|(_, result)| result.as_ref().map_err(String::as_str))
+    }
+
+    /// 回覆中的點位名稱
+    pub fn targets(&self) -> impl Iterator<Item = &str> {
+        self.members.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// 將回覆拆分至成員點位
+    ///
+    /// # 參數
+    /// - `targets`：連線的點位，用於取得成員的 [`crate::InitedTarget::statistics`]
+    /// - `members`：宣告由本次請求更新的成員點位名稱
+    /// - `store`：點位狀態儲存區，成員未登記時不會寫入狀態
+    /// - `response_ms`：本次請求所花費的毫秒數
+    ///
+    /// # 回傳值
+    /// 各成員的處理結果
+    pub fn apply<REQ: DeviceStateRequest, RES>(
+        &self,
+        targets: &ConnectionTargets<REQ, RES>,
+        members: impl IntoIterator<Item = impl AsRef<str>>,
+        store: &StateStore,
+        response_ms: i64,
+    ) -> MultiReport {
+        let mut report = MultiReport::default();
+        let mut statistics: Vec<(&Arc<TargetStats>, bool)> = Vec::new();
+        let mut declared = Vec::new();
+
+        for member in members {
+            let name = member.as_ref();
+            declared.push(name.to_owned());
+
+            let succeeded = match self.get(name) {
+                Some(Ok(value)) => {
+                    let _ = store.update(name, value.clone());
+                    report.updated.push(name.to_owned());
+                    true
+                }
+                Some(Err(_)) => {
+                    let _ = store.set_quality(name, Quality::Bad);
+                    report.failed.push(name.to_owned());
+                    false
+                }
+                None => {
+                    let _ = store.set_quality(name, Quality::Bad);
+                    report.missing.push(name.to_owned());
+                    false
+                }
+            };
+
+            let Some(target_statistics) = targets
+                .0
+                .iter()
+                .find(|target| target.name == name)
+                .and_then(|target| target.statistics.as_ref())
+            else {
+                continue;
+            };
+            match statistics
+                .iter_mut()
+                .find(|(recorded, _)| Arc::ptr_eq(recorded, target_statistics))
+            {
+                Some((_, recorded)) => *recorded &= succeeded,
+                None => statistics.push((target_statistics, succeeded)),
+            }
+        }
+
+        for (target_statistics, succeeded) in statistics {
+            if succeeded {
+                target_statistics.record_success(response_ms);
+            } else {
+                target_statistics.record_failure();
+            }
+        }
+
+        report.ignored = self
+            .targets()
+            .filter(|name| !declared.iter().any(|member| member == name))
+            .map(ToOwned::to_owned)
+            .collect();
+
+        report
+    }
+}
+
+impl<N: Into<String>> FromIterator<(N, Value)> for MultiResponse {
+    fn from_iter<I: IntoIterator<Item = (N, Value)>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(Self::new(), |response, (name, value)| {
+                response.with(name, value)
+            })
+    }
+}
+
+impl DeviceStateResponse for MultiResponse {
+    /// 轉換為以點位名稱為鍵的 JSON 物件，錯誤的點位為 [`Value::Null`]
+    fn to_value(&self) -> Result<Cow<'_, Value>, value::ConversionError> {
+        Ok(Cow::Owned(Value::Object(
+            self.members
+                .iter()
+                .map(|(name, result)| (name.clone(), result.clone().unwrap_or(Value::Null)))
+                .collect::<Map<_, _>>(),
+        )))
+    }
+}
+
+/// [`MultiResponse::apply()`] 的處理結果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultiReport {
+    /// 已寫入數值的成員
+    pub updated: Vec<String>,
+    /// 回覆中為錯誤的成員，已標記為 [`Quality::Bad`]
+    pub failed: Vec<String>,
+    /// 回覆中缺少的成員，已標記為 [`Quality::Bad`]
+    pub missing: Vec<String>,
+    /// 回覆中未宣告為成員的點位
+    pub ignored: Vec<String>,
+}
+
+impl MultiReport {
+    /// 是否所有成員都已寫入數值
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.failed.is_empty() && self.missing.is_empty()
+    }
+}
@@ -0,0 +1,2030 @@
+//! OPC UA 用戶端參考實作（需啟用 `opcua` feature）
+//!
+//! 以 OPC UA Binary（`opc.tcp`）連線至 OPC UA 伺服器，點位為節點（`NodeId`）的 Value 屬性：
+//!
+//! - 輪詢：同一輪輪詢中的自動更新點位以一個 `Read` 請求合併讀取（參見 [`OpcUaConfig::max_nodes_per_read`]），
+//!   讀取結果保留更新間隔的一半，期間內的其他點位直接使用該結果
+//! - 訂閱：設定 [`OpcUaConfig::publishing_interval`] 後，自動更新點位改為訂閱的監看項目（monitored item），
+//!   主程式輪詢時以 `Publish` 取回伺服器通知的變化並回傳最新數值，尚未收到數值的點位直接讀取一次；伺服器不支援訂閱時改回輪詢
+//! - 寫入：`Write` ，資料型別依點位的 `data_type` 決定，未設定時先讀取節點目前的數值以取得型別
+//! - 安全性：支援 `None` 、`Basic256Sha256` 與 `Aes128_Sha256_RsaOaep` 安全原則的簽章與加密（參見 [`SecurityConfig`]），
+//!   以及匿名或帳號密碼登入
+//!
+//! 使用簽章或加密時需以 [`SecurityConfig::server_certificate`] 指定信任的伺服器憑證，伺服器提供的憑證需與其相同且在有效期間內
+//!
+//! 點位設定：
+//!
+//! | 欄位 | 說明 |
+//! | --- | --- |
+//! | `address` | 節點，如 `ns=2;s=Boiler.Temperature` 、`i=2258`，參見 [`NodeId::parse()`] |
+//! | `data_type` | 寫入時使用的資料型別，參見 [`UaType`] |
+//! | `subscribe` | 是否以訂閱取代輪詢，未設定時為 `true` |
+//!
+//! # 範例
+//! ```rust
+//! use std::time::Duration;
+//! use device_state_exchange_lib::{
+//!     TargetDefinition,
+//!     opcua::{NodeId, OpcUaConfig, OpcUaPoint, SecurityMode, SecurityPolicy, UaType, UaValue},
+//! };
+//! use serde_json::json;
+//!
+//! let config: OpcUaConfig = serde_json::from_value(json!({
+//!     "endpoint_url": "opc.tcp://192.168.1.40:4840",
+//!     "publishing_interval_ms": 500,
+//!     "security": {
+//!         "policy": "basic256_sha256",
+//!         "mode": "sign_and_encrypt",
+//!         "certificate": "pki/client.der",
+//!         "private_key": "pki/client.pem",
+//!         "server_certificate": "pki/server.der",
+//!     },
+//! }))
+//! .unwrap();
+//! assert_eq!(config.publishing_interval, Some(Duration::from_millis(500)));
+//! assert_eq!(config.security.policy, SecurityPolicy::Basic256Sha256);
+//! assert_eq!(config.security.mode, SecurityMode::SignAndEncrypt);
+//!
+//! let definition: TargetDefinition = serde_json::from_value(json!({
+//!     "name": "鍋爐溫度",
+//!     "address": "ns=2;s=Boiler.Temperature",
+//!     "data_type": "double",
+//! }))
+//! .unwrap();
+//! let point = OpcUaPoint::parse(&definition).unwrap();
+//! assert_eq!(point.node, NodeId::String { namespace: 2, id: "Boiler.Temperature".to_owned() });
+//! assert_eq!(point.data_type, Some(UaType::Double));
+//! assert_eq!(NodeId::parse("i=2258").unwrap(), NodeId::numeric(2258));
+//! assert_eq!(
+//!     NodeId::parse("ns=1;g=09087E75-8E5E-499B-954F-F2A9603DB28A").unwrap().to_string(),
+//!     "ns=1;g=09087e75-8e5e-499b-954f-f2a9603db28a",
+//! );
+//!
+//! assert_eq!(UaValue::from_json(&json!(21.5), UaType::Float), Some(UaValue::Float(21.5)));
+//! assert_eq!(UaValue::from_json(&json!(300), UaType::Byte), None);
+//! assert_eq!(
+//!     UaValue::DateTime(133_497_792_000_000_000).to_json(),
+//!     json!("2024-01-15T08:00:00.000Z"),
+//! );
+//! ```
+
+mod codec;
+mod security;
+
+use std::{borrow::Cow, error::Error, io::ErrorKind, net::SocketAddr, sync::Arc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{
+    io::AsyncWriteExt,
+    net::TcpStream,
+    time::{Instant, timeout},
+};
+
+pub use self::{
+    codec::{DataValue, NodeId, StatusCode, UaType, UaValue},
+    security::{SecurityConfig, SecurityMode, SecurityPolicy, SymmetricKeys},
+};
+use self::{
+    codec::{Reader, Writer, malformed},
+    security::{
+        BLOCK_SIZE, Credentials, NONCE_LENGTH, SYMMETRIC_SIGNATURE_LENGTH, ServerCertificate,
+    },
+};
+use crate::{
+    Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+    ConnectionTargets, DeviceStateRequest, DeviceStateResponse, DeviceStateWrite, HashMap, HashSet,
+    InitedTarget, RemoteAddress, RequestContext, Target, TargetDefinition,
+    cache::{CacheLookup, CachePolicy, ResponseCache},
+    definition::InvalidTarget,
+    fixture::FrameDecoder,
+    millis,
+    transport::{
+        frame::FrameReader,
+        tcp::{self, ProxyConfig, TcpEndpoint},
+    },
+    value::ConversionError,
+};
+
+/// OPC UA 預設的 TCP 埠號
+pub const DEFAULT_PORT: u16 = 4840;
+
+/// 預設的更新間隔
+pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 預設的回覆逾時
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 預設的工作階段逾時
+pub const DEFAULT_SESSION_TIMEOUT: Duration = Duration::from_mins(1);
+
+/// 預設的單次 `Read` 請求節點數量上限
+pub const DEFAULT_MAX_NODES_PER_READ: u16 = 100;
+
+/// 預設的用戶端應用程式 URI
+pub const DEFAULT_APPLICATION_URI: &str = "urn:device-state-exchange-lib";
+
+const APPLICATION_NAME: &str = "device-state-exchange-lib";
+
+/// 接收與傳送緩衝區大小，即單一區塊的長度上限
+const BUFFER_SIZE: u32 = 65_535;
+
+/// 規格要求的最小緩衝區大小
+const MIN_BUFFER_SIZE: usize = 8192;
+
+/// 要求的安全通道權杖有效期限（毫秒），權杖會在有效期限的四分之三時更新
+const CHANNEL_LIFETIME_MS: u32 = 3_600_000;
+
+/// 單次輪詢連續送出 `Publish` 的次數上限，伺服器回覆還有更多通知時會繼續送出
+const MAX_PUBLISH_ROUNDS: usize = 8;
+
+const RSA_SHA256: &str = "http://www.w3.org/2001/04/xmldsig-more#rsa-sha256";
+const RSA_OAEP: &str = "http://www.w3.org/2001/04/xmlenc#rsa-oaep";
+
+const SERVICE_FAULT: u32 = 397;
+const GET_ENDPOINTS_REQUEST: u32 = 428;
+const GET_ENDPOINTS_RESPONSE: u32 = 431;
+const OPEN_SECURE_CHANNEL_REQUEST: u32 = 446;
+const OPEN_SECURE_CHANNEL_RESPONSE: u32 = 449;
+const CLOSE_SECURE_CHANNEL_REQUEST: u32 = 452;
+const CREATE_SESSION_REQUEST: u32 = 461;
+const CREATE_SESSION_RESPONSE: u32 = 464;
+const ACTIVATE_SESSION_REQUEST: u32 = 467;
+const ACTIVATE_SESSION_RESPONSE: u32 = 470;
+const CLOSE_SESSION_REQUEST: u32 = 473;
+const CLOSE_SESSION_RESPONSE: u32 = 476;
+const READ_REQUEST: u32 = 631;
+const READ_RESPONSE: u32 = 634;
+const WRITE_REQUEST: u32 = 673;
+const WRITE_RESPONSE: u32 = 676;
+const CREATE_MONITORED_ITEMS_REQUEST: u32 = 751;
+const CREATE_MONITORED_ITEMS_RESPONSE: u32 = 754;
+const CREATE_SUBSCRIPTION_REQUEST: u32 = 787;
+const CREATE_SUBSCRIPTION_RESPONSE: u32 = 790;
+const DATA_CHANGE_NOTIFICATION: u32 = 811;
+const STATUS_CHANGE_NOTIFICATION: u32 = 820;
+const PUBLISH_REQUEST: u32 = 826;
+const PUBLISH_RESPONSE: u32 = 829;
+const DELETE_SUBSCRIPTIONS_REQUEST: u32 = 847;
+const DELETE_SUBSCRIPTIONS_RESPONSE: u32 = 850;
+const ANONYMOUS_IDENTITY_TOKEN: u32 = 321;
+const USER_NAME_IDENTITY_TOKEN: u32 = 324;
+
+/// `Server_ServerStatus_State` ，保持連線時讀取
+const SERVER_STATE: u32 = 2259;
+
+/// Value 屬性
+const VALUE_ATTRIBUTE: u32 = 13;
+
+/// `TimestampsToReturn.Both`
+const TIMESTAMPS_BOTH: u32 = 2;
+
+fn default_application_uri() -> String {
+    DEFAULT_APPLICATION_URI.to_owned()
+}
+
+const fn default_session_timeout() -> Duration {
+    DEFAULT_SESSION_TIMEOUT
+}
+
+const fn default_update_interval() -> Duration {
+    DEFAULT_UPDATE_INTERVAL
+}
+
+const fn default_timeout() -> Duration {
+    DEFAULT_TIMEOUT
+}
+
+const fn default_max_nodes_per_read() -> u16 {
+    DEFAULT_MAX_NODES_PER_READ
+}
+
+/// OPC UA 連線設定
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpcUaConfig {
+    /// 伺服器端點，如 `opc.tcp://192.168.1.40:4840`，未指定埠號時為 4840
+    pub endpoint_url: String,
+    /// 代理伺服器，未設定時直接連線
+    #[serde(default)]
+    pub proxy: Option<ProxyConfig>,
+    /// 安全設定，預設不簽章、不加密
+    #[serde(default)]
+    pub security: SecurityConfig,
+    /// 帳號密碼，未設定時以匿名登入
+    #[serde(default)]
+    pub credentials: Option<OpcUaCredentials>,
+    /// 用戶端的應用程式 URI ，預設為 [`DEFAULT_APPLICATION_URI`]
+    #[serde(default = "default_application_uri")]
+    pub application_uri: String,
+    /// 工作階段逾時，連線閒置時會以此間隔的一半讀取伺服器狀態
+    #[serde(
+        rename = "session_timeout_ms",
+        with = "millis",
+        default = "default_session_timeout"
+    )]
+    pub session_timeout: Duration,
+    /// 更新間隔
+    #[serde(
+        rename = "update_interval_ms",
+        with = "millis",
+        default = "default_update_interval"
+    )]
+    pub update_interval: Duration,
+    /// 回覆逾時
+    #[serde(rename = "timeout_ms", with = "millis", default = "default_timeout")]
+    pub timeout: Duration,
+    /// 訂閱的發布間隔，設定後自動更新點位改以訂閱取得數值，未設定時輪詢
+    #[serde(rename = "publishing_interval_ms", with = "millis::option", default)]
+    pub publishing_interval: Option<Duration>,
+    /// 單次 `Read` 請求的節點數量上限，也用於建立監看項目
+    #[serde(default = "default_max_nodes_per_read")]
+    pub max_nodes_per_read: u16,
+    /// 最大重試次數，未設定時使用 [`crate::ConnectionArtifact`] 的預設值
+    #[serde(default)]
+    pub max_retry_count: Option<u32>,
+}
+
+impl ConnectionConfig for OpcUaConfig {}
+
+impl OpcUaConfig {
+    /// 由端點 URL 取得 TCP 連線目標
+    fn tcp_endpoint(&self) -> Result<TcpEndpoint, ConnectionError> {
+        let invalid =
+            || ConnectionError::InvalidConfig(format!("無效的端點「{}」", self.endpoint_url));
+        let authority = self
+            .endpoint_url
+            .strip_prefix("opc.tcp://")
+            .and_then(|rest| rest.split('/').next())
+            .ok_or_else(invalid)?;
+        let (host, port) = match authority.strip_prefix('[') {
+            Some(bracketed) => {
+                let (host, rest) = bracketed.split_once(']').ok_or_else(invalid)?;
+                (host, rest.strip_prefix(':'))
+            }
+            None => match authority.rsplit_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            },
+        };
+        let port = port
+            .map_or(Ok(DEFAULT_PORT), str::parse)
+            .map_err(|_| invalid())?;
+        if host.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(TcpEndpoint {
+            host: host.to_owned(),
+            port,
+            proxy: self.proxy.clone(),
+        })
+    }
+}
+
+/// 帳號密碼
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OpcUaCredentials {
+    /// 帳號
+    pub username: String,
+    /// 密碼
+    pub password: String,
+}
+
+impl std::fmt::Debug for OpcUaCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpcUaCredentials")
+            .field("username", &self.username)
+            .field("password", &"***")
+            .finish()
+    }
+}
+
+/// 讀取請求標頭之後的回覆標頭
+///
+/// # Errors
+/// 服務失敗（包含 `ServiceFault`）時回傳包含 [`StatusCode`] 的 [`ConnectionError::Custom`] ，
+/// 回覆型別不符時回傳 [`ConnectionError::Protocol`]
+fn response_header(reader: &mut Reader, expected: u32) -> Result<(), ConnectionError> {
+    let type_id = reader.node_id()?;
+    reader.i64()?;
+    reader.u32()?;
+    let result = StatusCode(reader.u32()?);
+    reader.diagnostic_info()?;
+    reader.strings()?;
+    reader.extension_object()?;
+
+    if result.is_bad() {
+        return Err(ConnectionError::custom(result));
+    }
+    if type_id == NodeId::numeric(SERVICE_FAULT) || type_id != NodeId::numeric(expected) {
+        return Err(ConnectionError::Protocol(format!(
+            "非預期的回覆型別 {type_id}"
+        )));
+    }
+    Ok(())
+}
+
+/// `ReadValueId` ，讀取 Value 屬性
+fn read_value_id(writer: &mut Writer, node: &NodeId) {
+    writer.node_id(node);
+    writer.u32(VALUE_ATTRIBUTE);
+    writer.string(None);
+    writer.u16(0);
+    writer.string(None);
+}
+
+/// 用戶端送出與接收訊息使用的對稱金鑰
+#[derive(Debug)]
+struct ChannelKeys {
+    local: SymmetricKeys,
+    remote: SymmetricKeys,
+    /// 更新權杖前的權杖編號與伺服器金鑰，伺服器在收到使用新權杖的訊息前仍會使用
+    previous: Option<(u32, SymmetricKeys)>,
+}
+
+/// 安全通道的安全設定
+#[derive(Debug)]
+struct Security {
+    policy: SecurityPolicy,
+    mode: SecurityMode,
+    credentials: Credentials,
+    server: ServerCertificate,
+    keys: Option<ChannelKeys>,
+}
+
+/// 安全通道（UA Secure Conversation）
+#[derive(Debug)]
+struct Channel {
+    stream: FrameReader<TcpStream>,
+    peer: Option<SocketAddr>,
+    timeout: Duration,
+    /// 伺服器的接收緩衝區大小，即送出區塊的長度上限
+    send_buffer: usize,
+    id: u32,
+    token_id: u32,
+    renew_at: Instant,
+    sequence: u32,
+    request_id: u32,
+    request_handle: u32,
+    security: Option<Security>,
+    /// 逾時時已讀取標頭、尚未讀取內容的區塊長度
+    pending: usize,
+}
+
+impl Channel {
+    /// 建立 TCP 連線、交換 Hello/Acknowledge 並開啟安全通道
+    async fn open(
+        endpoint: &TcpEndpoint,
+        endpoint_url: &str,
+        timeout: Duration,
+        security: Option<Security>,
+    ) -> Result<Self, ConnectionError> {
+        let stream = tokio::time::timeout(timeout, tcp::connect(endpoint)).await??;
+        stream.set_nodelay(true)?;
+        let peer = stream.peer_addr().ok();
+
+        let mut channel = Self {
+            stream: FrameReader::new(stream),
+            peer,
+            timeout,
+            send_buffer: MIN_BUFFER_SIZE,
+            id: 0,
+            token_id: 0,
+            renew_at: Instant::now(),
+            sequence: 0,
+            request_id: 0,
+            request_handle: 0,
+            security,
+            pending: 0,
+        };
+        tokio::time::timeout(timeout, channel.hello(endpoint_url)).await??;
+        tokio::time::timeout(timeout, channel.open_secure_channel(false)).await??;
+        Ok(channel)
+    }
+
+    async fn hello(&mut self, endpoint_url: &str) -> Result<(), ConnectionError> {
+        let mut message = Writer::default();
+        message.bytes(b"HELF");
+        message.u32(0);
+        message.u32(0);
+        message.u32(BUFFER_SIZE);
+        message.u32(BUFFER_SIZE);
+        message.u32(0);
+        message.u32(0);
+        message.string(Some(endpoint_url));
+        let length = u32::try_from(message.0.len()).map_err(|_| malformed())?;
+        message.0[4..8].copy_from_slice(&length.to_le_bytes());
+        self.write(&message.0).await?;
+
+        let chunk = self.read_chunk().await?;
+        match &chunk[..3] {
+            b"ACK" => {
+                let mut reader = Reader::new(&chunk[8..]);
+                reader.u32()?;
+                let receive_buffer = usize::try_from(reader.u32()?).map_err(|_| malformed())?;
+                if receive_buffer < MIN_BUFFER_SIZE {
+                    return Err(ConnectionError::Protocol(format!(
+                        "伺服器的接收緩衝區過小：{receive_buffer}"
+                    )));
+                }
+                self.send_buffer = receive_buffer.min(BUFFER_SIZE as usize);
+                Ok(())
+            }
+            b"ERR" => Err(transport_error(&chunk)),
+            _ => Err(malformed()),
+        }
+    }
+
+    /// 開啟或更新安全通道
+    async fn open_secure_channel(&mut self, renew: bool) -> Result<(), ConnectionError> {
+        let client_nonce = if self.security.is_some() {
+            security::nonce()
+        } else {
+            Vec::new()
+        };
+        let mode = self
+            .security
+            .as_ref()
+            .map_or(SecurityMode::None, |security| security.mode);
+
+        let mut body = self.begin(OPEN_SECURE_CHANNEL_REQUEST, &NodeId::NULL);
+        body.u32(0);
+        body.u32(u32::from(renew));
+        body.u32(mode.number());
+        body.byte_string(Some(&client_nonce));
+        body.u32(CHANNEL_LIFETIME_MS);
+
+        let request_id = self.next_request_id();
+        let message = self.seal_asymmetric(request_id, &body.0)?;
+        self.write(&message).await?;
+        let response = self.receive(request_id).await?;
+
+        let mut reader = Reader::new(&response);
+        response_header(&mut reader, OPEN_SECURE_CHANNEL_RESPONSE)?;
+        reader.u32()?;
+        let channel_id = reader.u32()?;
+        let token_id = reader.u32()?;
+        reader.i64()?;
+        let lifetime = reader.u32()?;
+        let server_nonce = reader.byte_string()?.unwrap_or_default();
+
+        if let Some(security) = &mut self.security {
+            if server_nonce.len() != NONCE_LENGTH {
+                return Err(ConnectionError::Protocol("伺服器隨機數長度錯誤".to_owned()));
+            }
+            let local = SymmetricKeys::derive(security.policy, &server_nonce, &client_nonce)?;
+            let remote = SymmetricKeys::derive(security.policy, &client_nonce, &server_nonce)?;
+            let previous = security
+                .keys
+                .take()
+                .map(|keys| (self.token_id, keys.remote));
+            security.keys = Some(ChannelKeys {
+                local,
+                remote,
+                previous,
+            });
+        }
+        self.id = channel_id;
+        self.token_id = token_id;
+        self.renew_at = Instant::now() + Duration::from_millis(u64::from(lifetime) * 3 / 4);
+        Ok(())
+    }
+
+    /// 盡力關閉安全通道，伺服器不會回覆
+    async fn close(&mut self) {
+        let body = self.begin(CLOSE_SECURE_CHANNEL_REQUEST, &NodeId::NULL);
+        let _ = tokio::time::timeout(self.timeout, self.send(*b"CLO", &body.0)).await;
+        let _ = self.stream.get_mut().shutdown().await;
+    }
+
+    /// 開始編碼服務請求：型別與請求標頭
+    fn begin(&mut self, type_id: u32, authentication_token: &NodeId) -> Writer {
+        self.request_handle = self.request_handle.wrapping_add(1);
+        let mut writer = Writer::default();
+        writer.node_id(&NodeId::numeric(type_id));
+        writer.node_id(authentication_token);
+        writer.i64(codec::now());
+        writer.u32(self.request_handle);
+        writer.u32(0);
+        writer.string(None);
+        writer.u32(u32::try_from(self.timeout.as_millis()).unwrap_or(u32::MAX));
+        writer.null_extension_object();
+        writer
+    }
+
+    /// 送出服務請求並等待回覆，權杖即將到期時先更新
+    async fn request(&mut self, body: &[u8], wait: Duration) -> Result<Vec<u8>, ConnectionError> {
+        timeout(wait, async {
+            if Instant::now() >= self.renew_at {
+                self.open_secure_channel(true).await?;
+            }
+            let request_id = self.send(*b"MSG", body).await?;
+            self.receive(request_id).await
+        })
+        .await?
+    }
+
+    const fn next_request_id(&mut self) -> u32 {
+        self.request_id = self.request_id.wrapping_add(1);
+        if self.request_id == 0 {
+            self.request_id = 1;
+        }
+        self.request_id
+    }
+
+    /// 序號在接近上限時回到 1 ，參見 OPC UA Part 6 第 6.7.2.4 節
+    const fn next_sequence(&mut self) -> u32 {
+        self.sequence = if self.sequence >= u32::MAX - 1024 {
+            1
+        } else {
+            self.sequence + 1
+        };
+        self.sequence
+    }
+
+    async fn write(&mut self, message: &[u8]) -> Result<(), ConnectionError> {
+        let stream = self.stream.get_mut();
+        stream.write_all(message).await?;
+        stream.flush().await?;
+        Ok(())
+    }
+
+    /// 單一區塊可容納的內容長度
+    fn max_chunk_body(&self) -> usize {
+        let header = 16 + 8;
+        match self.security.as_ref().map(|security| security.mode) {
+            None | Some(SecurityMode::None) => self.send_buffer - header,
+            Some(SecurityMode::Sign) => self.send_buffer - header - SYMMETRIC_SIGNATURE_LENGTH,
+            Some(SecurityMode::SignAndEncrypt) => {
+                (self.send_buffer - 16) / BLOCK_SIZE * BLOCK_SIZE
+                    - 8
+                    - SYMMETRIC_SIGNATURE_LENGTH
+                    - 1
+            }
+        }
+    }
+
+    /// 以對稱加密送出訊息，內容過長時分為多個區塊
+    async fn send(&mut self, kind: [u8; 3], body: &[u8]) -> Result<u32, ConnectionError> {
+        let request_id = self.next_request_id();
+        let chunks: Vec<&[u8]> = if body.is_empty() {
+            vec![body]
+        } else {
+            body.chunks(self.max_chunk_body()).collect()
+        };
+
+        let mut message = Vec::with_capacity(body.len() + chunks.len() * 128);
+        for (index, chunk) in chunks.iter().enumerate() {
+            let chunk_type = if index + 1 == chunks.len() {
+                b'F'
+            } else {
+                b'C'
+            };
+            let sequence = self.next_sequence();
+            message.extend(self.seal_symmetric(kind, chunk_type, sequence, request_id, chunk)?);
+        }
+        self.write(&message).await?;
+        Ok(request_id)
+    }
+
+    fn seal_symmetric(
+        &self,
+        kind: [u8; 3],
+        chunk_type: u8,
+        sequence: u32,
+        request_id: u32,
+        body: &[u8],
+    ) -> Result<Vec<u8>, ConnectionError> {
+        let mut message = Writer(Vec::with_capacity(body.len() + 96));
+        message.bytes(&kind);
+        message.u8(chunk_type);
+        message.u32(0);
+        message.u32(self.id);
+        message.u32(self.token_id);
+        message.u32(sequence);
+        message.u32(request_id);
+        message.bytes(body);
+
+        let keys = self
+            .security
+            .as_ref()
+            .and_then(|security| Some((security.mode, security.keys.as_ref()?)));
+        let Some((mode, keys)) = keys else {
+            set_message_size(&mut message.0)?;
+            return Ok(message.0);
+        };
+        keys.local.seal(mode, message.0)
+    }
+
+    /// 以非對稱加密編碼 `OpenSecureChannel` 請求
+    fn seal_asymmetric(
+        &mut self,
+        request_id: u32,
+        body: &[u8],
+    ) -> Result<Vec<u8>, ConnectionError> {
+        let sequence = self.next_sequence();
+        let mut message = Writer::default();
+        message.bytes(b"OPNF");
+        message.u32(0);
+        message.u32(self.id);
+
+        let mut plain = Writer(Vec::with_capacity(body.len() + 8));
+        plain.u32(sequence);
+        plain.u32(request_id);
+        plain.bytes(body);
+
+        let Some(security) = &self.security else {
+            message.string(Some(SecurityPolicy::None.uri()));
+            message.byte_string(None);
+            message.byte_string(None);
+            message.bytes(&plain.0);
+            set_message_size(&mut message.0)?;
+            return Ok(message.0);
+        };
+        message.string(Some(security.policy.uri()));
+        message.byte_string(Some(&security.credentials.certificate));
+        message.byte_string(Some(&security.server.thumbprint()));
+
+        let signature_length = security.credentials.signature_length();
+        let plain_block = security.server.plain_block_length();
+        let extra_padding = security.server.key_length() > 256;
+        let unpadded = plain.0.len() + 1 + usize::from(extra_padding) + signature_length;
+        let padding = (plain_block - unpadded % plain_block) % plain_block;
+        let [low, high] = u16::try_from(padding)
+            .map_err(|_| malformed())?
+            .to_le_bytes();
+        plain.0.resize(plain.0.len() + padding + 1, low);
+        if extra_padding {
+            plain.u8(high);
+        }
+
+        let encrypted_length =
+            (plain.0.len() + signature_length) / plain_block * security.server.key_length();
+        let size = u32::try_from(message.0.len() + encrypted_length).map_err(|_| malformed())?;
+        message.0[4..8].copy_from_slice(&size.to_le_bytes());
+
+        let mut signed = message.0.clone();
+        signed.extend_from_slice(&plain.0);
+        let signature = security.credentials.sign(&signed)?;
+        plain.bytes(&signature);
+        let encrypted = security.server.encrypt(&plain.0)?;
+        message.bytes(&encrypted);
+        Ok(message.0)
+    }
+
+    /// 讀取一個完整的區塊
+    async fn read_chunk(&mut self) -> Result<Vec<u8>, ConnectionError> {
+        if self.pending > 0 {
+            self.stream.read_frame(self.pending).await?;
+            self.pending = 0;
+        }
+
+        let header = self.stream.read_frame(8).await?;
+        let size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        if !(8..=BUFFER_SIZE).contains(&size) {
+            return Err(ConnectionError::Protocol(format!("無效的區塊長度 {size}")));
+        }
+        self.pending = size as usize - 8;
+        let rest = self.stream.read_frame(self.pending).await?;
+        self.pending = 0;
+
+        let mut chunk = Vec::with_capacity(size as usize);
+        chunk.extend_from_slice(&header);
+        chunk.extend_from_slice(&rest);
+        Ok(chunk)
+    }
+
+    /// 等待指定請求的回覆，先前逾時的請求的回覆會被捨棄
+    async fn receive(&mut self, request_id: u32) -> Result<Vec<u8>, ConnectionError> {
+        let mut body = Vec::new();
+        loop {
+            let chunk = self.read_chunk().await?;
+            let (received_id, part) = match &chunk[..3] {
+                b"MSG" | b"CLO" => self.open_symmetric(chunk.clone())?,
+                b"OPN" => self.open_asymmetric(&chunk)?,
+                b"ERR" => return Err(transport_error(&chunk)),
+                _ => return Err(malformed()),
+            };
+            if received_id != request_id {
+                continue;
+            }
+            match chunk[3] {
+                b'C' => body.extend_from_slice(&part),
+                b'F' => {
+                    body.extend_from_slice(&part);
+                    return Ok(body);
+                }
+                b'A' => {
+                    let mut reader = Reader::new(&part);
+                    let status = StatusCode(reader.u32()?);
+                    return Err(ConnectionError::custom(status));
+                }
+                _ => return Err(malformed()),
+            }
+        }
+    }
+
+    /// 解密並驗證對稱加密的區塊
+    ///
+    /// # 回傳值
+    /// 請求編號與區塊內容
+    fn open_symmetric(&self, mut chunk: Vec<u8>) -> Result<(u32, Vec<u8>), ConnectionError> {
+        if chunk.len() < 24 {
+            return Err(malformed());
+        }
+        if let Some(security) = &self.security
+            && let Some(keys) = &security.keys
+        {
+            let token_id = u32::from_le_bytes([chunk[12], chunk[13], chunk[14], chunk[15]]);
+            let remote = match &keys.previous {
+                _ if token_id == self.token_id => &keys.remote,
+                Some((previous, remote)) if *previous == token_id => remote,
+                _ => {
+                    return Err(ConnectionError::Protocol(format!(
+                        "未知的安全權杖 {token_id}"
+                    )));
+                }
+            };
+            chunk = remote.open(security.mode, chunk)?;
+        }
+        let request_id = u32::from_le_bytes([chunk[20], chunk[21], chunk[22], chunk[23]]);
+        Ok((request_id, chunk.split_off(24)))
+    }
+
+    /// 解密並驗證 `OpenSecureChannel` 回覆
+    fn open_asymmetric(&self, chunk: &[u8]) -> Result<(u32, Vec<u8>), ConnectionError> {
+        let mut reader = Reader::new(chunk.get(12..).ok_or_else(malformed)?);
+        let policy = reader.string()?.unwrap_or_default();
+        reader.byte_string()?;
+        reader.byte_string()?;
+        let header_length = chunk.len() - reader.remaining();
+
+        let expected = self
+            .security
+            .as_ref()
+            .map_or(SecurityPolicy::None, |security| security.policy);
+        if policy != expected.uri() {
+            return Err(ConnectionError::Protocol(format!(
+                "伺服器回覆的安全原則「{policy}」與請求不符"
+            )));
+        }
+
+        let plain = match &self.security {
+            None => chunk[header_length..].to_vec(),
+            Some(security) => {
+                let plain = security.credentials.decrypt(&chunk[header_length..])?;
+                let signed_length = plain
+                    .len()
+                    .checked_sub(security.server.key_length())
+                    .ok_or_else(malformed)?;
+                let mut signed = chunk[..header_length].to_vec();
+                signed.extend_from_slice(&plain[..signed_length]);
+                security.server.verify(&signed, &plain[signed_length..])?;
+
+                let padding = if security.credentials.signature_length() > 256 {
+                    let high = usize::from(*plain.get(signed_length - 1).ok_or_else(malformed)?);
+                    let low = usize::from(*plain.get(signed_length - 2).ok_or_else(malformed)?);
+                    ((high << 8) | low) + 2
+                } else {
+                    usize::from(*plain.get(signed_length - 1).ok_or_else(malformed)?) + 1
+                };
+                let end = signed_length.checked_sub(padding).ok_or_else(malformed)?;
+                plain[..end].to_vec()
+            }
+        };
+        if plain.len() < 8 {
+            return Err(malformed());
+        }
+        let request_id = u32::from_le_bytes([plain[4], plain[5], plain[6], plain[7]]);
+        Ok((request_id, plain[8..].to_vec()))
+    }
+}
+
+fn set_message_size(message: &mut [u8]) -> Result<(), ConnectionError> {
+    let size = u32::try_from(message.len()).map_err(|_| malformed())?;
+    message[4..8].copy_from_slice(&size.to_le_bytes());
+    Ok(())
+}
+
+/// 解析 `ERR` 訊息
+fn transport_error(chunk: &[u8]) -> ConnectionError {
+    let mut reader = Reader::new(chunk.get(8..).unwrap_or_default());
+    match reader.u32() {
+        Ok(status) => ConnectionError::custom(StatusCode(status)),
+        Err(error) => error,
+    }
+}
+
+/// 伺服器端點
+#[derive(Debug, Clone)]
+struct EndpointDescription {
+    certificate: Option<Vec<u8>>,
+    mode: u32,
+    policy_uri: String,
+    tokens: Vec<UserTokenPolicy>,
+}
+
+/// 伺服器接受的登入方式
+#[derive(Debug, Clone)]
+struct UserTokenPolicy {
+    policy_id: String,
+    /// 0 為匿名，1 為帳號密碼
+    token_type: u32,
+    security_policy_uri: Option<String>,
+}
+
+fn endpoint_description(reader: &mut Reader) -> Result<EndpointDescription, ConnectionError> {
+    reader.string()?;
+    // ApplicationDescription
+    reader.string()?;
+    reader.string()?;
+    reader.localized_text()?;
+    reader.u32()?;
+    reader.string()?;
+    reader.string()?;
+    reader.strings()?;
+
+    let certificate = reader.byte_string()?;
+    let mode = reader.u32()?;
+    let policy_uri = reader.string()?.unwrap_or_default();
+    let mut tokens = Vec::new();
+    for _ in 0..reader.length()? {
+        let policy_id = reader.string()?.unwrap_or_default();
+        let token_type = reader.u32()?;
+        reader.string()?;
+        reader.string()?;
+        let security_policy_uri = reader.string()?.filter(|uri| !uri.is_empty());
+        tokens.push(UserTokenPolicy {
+            policy_id,
+            token_type,
+            security_policy_uri,
+        });
+    }
+    reader.string()?;
+    reader.u8()?;
+
+    Ok(EndpointDescription {
+        certificate,
+        mode,
+        policy_uri,
+        tokens,
+    })
+}
+
+/// `Publish` 的回覆
+#[derive(Debug, Clone, PartialEq)]
+struct Publication {
+    subscription: u32,
+    sequence: u32,
+    /// 是否包含通知，只有包含通知的訊息需要確認
+    notified: bool,
+    more: bool,
+    /// 用戶端代碼與變化後的數值
+    changes: Vec<(u32, DataValue)>,
+    status: Option<StatusCode>,
+}
+
+/// OPC UA 用戶端
+///
+/// 建立安全通道與工作階段後提供 `Read` 、`Write` 等服務，所有請求依序處理
+///
+/// # 範例
+/// ```rust,no_run
+/// use device_state_exchange_lib::opcua::{NodeId, OpcUaClient, OpcUaConfig, UaValue};
+/// use serde_json::json;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let config: OpcUaConfig = serde_json::from_value(json!({
+///     "endpoint_url": "opc.tcp://192.168.1.40:4840",
+/// }))
+/// .unwrap();
+/// let mut client = OpcUaClient::connect(&config).await.unwrap();
+///
+/// let setpoint = NodeId::parse("ns=2;s=Boiler.Setpoint").unwrap();
+/// let values = client.read(std::slice::from_ref(&setpoint)).await.unwrap();
+/// println!("{}", values[0].value.to_json());
+///
+/// let results = client.write(&[(setpoint, UaValue::Double(65.0))]).await.unwrap();
+/// assert!(!results[0].is_bad());
+/// client.close().await;
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct OpcUaClient {
+    channel: Channel,
+    authentication_token: NodeId,
+}
+
+impl OpcUaClient {
+    /// 連線至伺服器並建立工作階段
+    ///
+    /// 先以不加密的安全通道取得伺服器端點，使用簽章或加密時再以端點提供的伺服器憑證開啟新的安全通道
+    ///
+    /// # Errors
+    /// 設定錯誤、伺服器沒有符合安全設定的端點或不支援設定的登入方式時回傳 [`ConnectionError::InvalidConfig`] ，
+    /// 伺服器拒絕請求時回傳包含 [`StatusCode`] 的 [`ConnectionError::Custom`] ，其他錯誤參見 [`OpcUaClient::read()`]
+    pub async fn connect(config: &OpcUaConfig) -> Result<Self, ConnectionError> {
+        let endpoint = config.tcp_endpoint()?;
+        let credentials = config.security.load()?;
+
+        let mut client = Self {
+            channel: Channel::open(&endpoint, &config.endpoint_url, config.timeout, None).await?,
+            authentication_token: NodeId::NULL,
+        };
+        let description = client
+            .get_endpoints(&config.endpoint_url)
+            .await?
+            .into_iter()
+            .find(|description| {
+                description.policy_uri == config.security.policy.uri()
+                    && description.mode == config.security.mode.number()
+            })
+            .ok_or_else(|| {
+                ConnectionError::InvalidConfig(format!(
+                    "伺服器沒有安全原則為 {:?} 、安全模式為 {:?} 的端點",
+                    config.security.policy, config.security.mode
+                ))
+            })?;
+
+        if let Some(credentials) = credentials {
+            let server = ServerCertificate::parse(
+                description.certificate.as_deref().unwrap_or_default(),
+                credentials.trusted.as_deref(),
+            )?;
+            client.channel.close().await;
+            let security = Security {
+                policy: config.security.policy,
+                mode: config.security.mode,
+                credentials,
+                server,
+                keys: None,
+            };
+            client.channel = Channel::open(
+                &endpoint,
+                &config.endpoint_url,
+                config.timeout,
+                Some(security),
+            )
+            .await?;
+        }
+
+        client.create_session(config, &description).await?;
+        Ok(client)
+    }
+
+    /// 伺服器位址
+    #[must_use]
+    pub const fn peer_addr(&self) -> Option<SocketAddr> {
+        self.channel.peer
+    }
+
+    /// 送出請求並等待回覆
+    async fn call(&mut self, body: &Writer) -> Result<Vec<u8>, ConnectionError> {
+        self.channel.request(&body.0, self.channel.timeout).await
+    }
+
+    async fn get_endpoints(
+        &mut self,
+        endpoint_url: &str,
+    ) -> Result<Vec<EndpointDescription>, ConnectionError> {
+        let mut body = self
+            .channel
+            .begin(GET_ENDPOINTS_REQUEST, &self.authentication_token);
+        body.string(Some(endpoint_url));
+        body.i32(-1);
+        body.i32(-1);
+
+        let response = self.call(&body).await?;
+        let mut reader = Reader::new(&response);
+        response_header(&mut reader, GET_ENDPOINTS_RESPONSE)?;
+        (0..reader.length()?)
+            .map(|_| endpoint_description(&mut reader))
+            .collect()
+    }
+
+    async fn create_session(
+        &mut self,
+        config: &OpcUaConfig,
+        description: &EndpointDescription,
+    ) -> Result<(), ConnectionError> {
+        let client_nonce = security::nonce();
+        let mut body = self.channel.begin(CREATE_SESSION_REQUEST, &NodeId::NULL);
+        // ApplicationDescription
+        body.string(Some(&config.application_uri));
+        body.string(Some(DEFAULT_APPLICATION_URI));
+        body.u8(0x02);
+        body.string(Some(APPLICATION_NAME));
+        body.u32(1);
+        body.string(None);
+        body.string(None);
+        body.i32(-1);
+
+        body.string(None);
+        body.string(Some(&config.endpoint_url));
+        body.string(Some(APPLICATION_NAME));
+        body.byte_string(Some(&client_nonce));
+        body.byte_string(
+            self.channel
+                .security
+                .as_ref()
+                .map(|security| security.credentials.certificate.as_slice()),
+        );
+        body.f64(config.session_timeout.as_secs_f64() * 1000.0);
+        body.u32(0);
+
+        let response = self.call(&body).await?;
+        let mut reader = Reader::new(&response);
+        response_header(&mut reader, CREATE_SESSION_RESPONSE)?;
+        reader.node_id()?;
+        let authentication_token = reader.node_id()?;
+        reader.f64()?;
+        let server_nonce = reader.byte_string()?.unwrap_or_default();
+        reader.byte_string()?;
+        for _ in 0..reader.length()? {
+            endpoint_description(&mut reader)?;
+        }
+        for _ in 0..reader.length()? {
+            reader.byte_string()?;
+            reader.byte_string()?;
+        }
+        reader.string()?;
+        let server_signature = reader.byte_string()?.unwrap_or_default();
+
+        if let Some(security) = &self.channel.security {
+            let mut signed = security.credentials.certificate.clone();
+            signed.extend_from_slice(&client_nonce);
+            security.server.verify(&signed, &server_signature)?;
+        }
+        self.authentication_token = authentication_token;
+        self.activate_session(config, description, &server_nonce)
+            .await
+    }
+
+    async fn activate_session(
+        &mut self,
+        config: &OpcUaConfig,
+        description: &EndpointDescription,
+        server_nonce: &[u8],
+    ) -> Result<(), ConnectionError> {
+        let mut body = self
+            .channel
+            .begin(ACTIVATE_SESSION_REQUEST, &self.authentication_token);
+        if let Some(security) = &self.channel.security {
+            let mut signed = security.server.der.clone();
+            signed.extend_from_slice(server_nonce);
+            body.string(Some(RSA_SHA256));
+            body.byte_string(Some(&security.credentials.sign(&signed)?));
+        } else {
+            body.string(None);
+            body.byte_string(None);
+        }
+        body.i32(0);
+        body.i32(0);
+        self.identity_token(&mut body, config, description, server_nonce)?;
+        body.string(None);
+        body.byte_string(None);
+
+        let response = self.call(&body).await?;
+        let mut reader = Reader::new(&response);
+        response_header(&mut reader, ACTIVATE_SESSION_RESPONSE)
+    }
+
+    /// 編碼登入使用的 `UserIdentityToken`
+    fn identity_token(
+        &self,
+        body: &mut Writer,
+        config: &OpcUaConfig,
+        description: &EndpointDescription,
+        server_nonce: &[u8],
+    ) -> Result<(), ConnectionError> {
+        let token_type = u32::from(config.credentials.is_some());
+        let policy = description
+            .tokens
+            .iter()
+            .find(|policy| policy.token_type == token_type)
+            .ok_or_else(|| {
+                ConnectionError::InvalidConfig(if config.credentials.is_some() {
+                    "伺服器端點不接受帳號密碼登入".to_owned()
+                } else {
+                    "伺服器端點不接受匿名登入".to_owned()
+                })
+            })?;
+
+        let mut token = Writer::default();
+        token.string(Some(&policy.policy_id));
+        let Some(credentials) = &config.credentials else {
+            body.extension_object(ANONYMOUS_IDENTITY_TOKEN, &token.0);
+            return Ok(());
+        };
+        token.string(Some(&credentials.username));
+
+        let uri = policy
+            .security_policy_uri
+            .as_deref()
+            .unwrap_or(&description.policy_uri);
+        if uri == SecurityPolicy::None.uri() {
+            token.byte_string(Some(credentials.password.as_bytes()));
+            token.string(None);
+        } else if [
+            SecurityPolicy::Basic256Sha256.uri(),
+            SecurityPolicy::Aes128Sha256RsaOaep.uri(),
+            "http://opcfoundation.org/UA/SecurityPolicy#Basic256",
+        ]
+        .contains(&uri)
+        {
+            let server = match &self.channel.security {
+                Some(security) => security.server.clone(),
+                None => ServerCertificate::parse(
+                    description.certificate.as_deref().unwrap_or_default(),
+                    None,
+                )?,
+            };
+            let mut secret = Writer::default();
+            secret.length(credentials.password.len() + server_nonce.len());
+            secret.bytes(credentials.password.as_bytes());
+            secret.bytes(server_nonce);
+            token.byte_string(Some(&server.encrypt(&secret.0)?));
+            token.string(Some(RSA_OAEP));
+        } else {
+            return Err(ConnectionError::InvalidConfig(format!(
+                "不支援密碼加密使用的安全原則「{uri}」"
+            )));
+        }
+        body.extension_object(USER_NAME_IDENTITY_TOKEN, &token.0);
+        Ok(())
+    }
+
+    /// 讀取節點的 Value 屬性
+    ///
+    /// # 回傳值
+    /// 依節點順序排列的數值，個別節點的錯誤放在 [`DataValue::status`]
+    ///
+    /// # Errors
+    /// 逾時回傳 [`ConnectionError::Timeout`] ，連線讀寫失敗回傳 [`ConnectionError::Io`] ，
+    /// 服務失敗時回傳包含 [`StatusCode`] 的 [`ConnectionError::Custom`] ，其他不符合通訊協定的回覆回傳 [`ConnectionError::Protocol`]
+    pub async fn read(&mut self, nodes: &[NodeId]) -> Result<Vec<DataValue>, ConnectionError> {
+        let mut body = self.channel.begin(READ_REQUEST, &self.authentication_token);
+        body.f64(0.0);
+        body.u32(TIMESTAMPS_BOTH);
+        body.length(nodes.len());
+        for node in nodes {
+            read_value_id(&mut body, node);
+        }
+
+        let response = self.call(&body).await?;
+        let mut reader = Reader::new(&response);
+        response_header(&mut reader, READ_RESPONSE)?;
+        let values = (0..reader.length()?)
+            .map(|_| reader.data_value())
+            .collect::<Result<Vec<_>, _>>()?;
+        if values.len() != nodes.len() {
+            return Err(ConnectionError::Protocol(
+                "回覆的數值數量與請求不符".to_owned(),
+            ));
+        }
+        Ok(values)
+    }
+
+    /// 寫入節點的 Value 屬性
+    ///
+    /// # 回傳值
+    /// 依節點順序排列的結果
+    ///
+    /// # Errors
+    /// 參見 [`OpcUaClient::read()`]
+    pub async fn write(
+        &mut self,
+        values: &[(NodeId, UaValue)],
+    ) -> Result<Vec<StatusCode>, ConnectionError> {
+        let mut body = self
+            .channel
+            .begin(WRITE_REQUEST, &self.authentication_token);
+        body.length(values.len());
+        for (node, value) in values {
+            body.node_id(node);
+            body.u32(VALUE_ATTRIBUTE);
+            body.string(None);
+            body.data_value(value);
+        }
+
+        let response = self.call(&body).await?;
+        let mut reader = Reader::new(&response);
+        response_header(&mut reader, WRITE_RESPONSE)?;
+        let results = reader.status_codes()?;
+        if results.len() != values.len() {
+            return Err(ConnectionError::Protocol(
+                "回覆的結果數量與請求不符".to_owned(),
+            ));
+        }
+        Ok(results)
+    }
+
+    /// 盡力關閉工作階段與安全通道，伺服器沒有回應時直接中斷連線
+    pub async fn close(&mut self) {
+        let mut body = self
+            .channel
+            .begin(CLOSE_SESSION_REQUEST, &self.authentication_token);
+        body.bool(true);
+        if let Ok(response) = self.call(&body).await {
+            let _ = response_header(&mut Reader::new(&response), CLOSE_SESSION_RESPONSE);
+        }
+        self.channel.close().await;
+    }
+
+    /// 建立訂閱
+    ///
+    /// # 回傳值
+    /// 訂閱編號
+    async fn create_subscription(
+        &mut self,
+        interval: Duration,
+        lifetime_count: u32,
+    ) -> Result<u32, ConnectionError> {
+        let mut body = self
+            .channel
+            .begin(CREATE_SUBSCRIPTION_REQUEST, &self.authentication_token);
+        body.f64(interval.as_secs_f64() * 1000.0);
+        body.u32(lifetime_count);
+        // 沒有變化時每個發布間隔都回覆 keep-alive ，`Publish` 最多等待一個發布間隔
+        body.u32(1);
+        body.u32(0);
+        body.bool(true);
+        body.u8(0);
+
+        let response = self.call(&body).await?;
+        let mut reader = Reader::new(&response);
+        response_header(&mut reader, CREATE_SUBSCRIPTION_RESPONSE)?;
+        reader.u32()
+    }
+
+    /// 刪除訂閱，不檢查個別訂閱的結果
+    async fn delete_subscriptions(&mut self, subscriptions: &[u32]) -> Result<(), ConnectionError> {
+        let mut body = self
+            .channel
+            .begin(DELETE_SUBSCRIPTIONS_REQUEST, &self.authentication_token);
+        body.length(subscriptions.len());
+        for subscription in subscriptions {
+            body.u32(*subscription);
+        }
+
+        let response = self.call(&body).await?;
+        response_header(&mut Reader::new(&response), DELETE_SUBSCRIPTIONS_RESPONSE)
+    }
+
+    /// 建立監看項目
+    ///
+    /// # 參數
+    /// - `subscription`：訂閱編號
+    /// - `items`：用戶端代碼與節點
+    /// - `sampling`：取樣間隔
+    async fn create_monitored_items(
+        &mut self,
+        subscription: u32,
+        items: &[(u32, NodeId)],
+        sampling: Duration,
+    ) -> Result<Vec<StatusCode>, ConnectionError> {
+        let mut body = self
+            .channel
+            .begin(CREATE_MONITORED_ITEMS_REQUEST, &self.authentication_token);
+        body.u32(subscription);
+        body.u32(TIMESTAMPS_BOTH);
+        body.length(items.len());
+        for (handle, node) in items {
+            read_value_id(&mut body, node);
+            body.u32(2);
+            body.u32(*handle);
+            body.f64(sampling.as_secs_f64() * 1000.0);
+            body.null_extension_object();
+            body.u32(1);
+            body.bool(true);
+        }
+
+        let response = self.call(&body).await?;
+        let mut reader = Reader::new(&response);
+        response_header(&mut reader, CREATE_MONITORED_ITEMS_RESPONSE)?;
+        let mut results = Vec::with_capacity(items.len());
+        for _ in 0..reader.length()? {
+            results.push(StatusCode(reader.u32()?));
+            reader.u32()?;
+            reader.f64()?;
+            reader.u32()?;
+            reader.extension_object()?;
+        }
+        Ok(results)
+    }
+
+    /// 取回訂閱的通知
+    ///
+    /// # 參數
+    /// - `acknowledgement`：確認收到的訂閱編號與序號
+    /// - `wait`：等待回覆的時間，需大於發布間隔
+    async fn publish(
+        &mut self,
+        acknowledgement: Option<(u32, u32)>,
+        wait: Duration,
+    ) -> Result<Publication, ConnectionError> {
+        let mut body = self
+            .channel
+            .begin(PUBLISH_REQUEST, &self.authentication_token);
+        body.length(usize::from(acknowledgement.is_some()));
+        if let Some((subscription, sequence)) = acknowledgement {
+            body.u32(subscription);
+            body.u32(sequence);
+        }
+
+        let response = self.channel.request(&body.0, wait).await?;
+        let mut reader = Reader::new(&response);
+        response_header(&mut reader, PUBLISH_RESPONSE)?;
+        let subscription = reader.u32()?;
+        for _ in 0..reader.length()? {
+            reader.u32()?;
+        }
+        let more = reader.bool()?;
+        let sequence = reader.u32()?;
+        reader.i64()?;
+
+        let mut publication = Publication {
+            subscription,
+            sequence,
+            notified: false,
+            more,
+            changes: Vec::new(),
+            status: None,
+        };
+        for _ in 0..reader.length()? {
+            publication.notified = true;
+            let (type_id, data) = reader.extension_object()?;
+            let mut notification = Reader::new(&data);
+            if type_id == NodeId::numeric(DATA_CHANGE_NOTIFICATION) {
+                for _ in 0..notification.length()? {
+                    let handle = notification.u32()?;
+                    publication
+                        .changes
+                        .push((handle, notification.data_value()?));
+                }
+            } else if type_id == NodeId::numeric(STATUS_CHANGE_NOTIFICATION) {
+                publication.status = Some(StatusCode(notification.u32()?));
+            }
+        }
+        Ok(publication)
+    }
+}
+
+/// 點位
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpcUaPoint {
+    /// 節點
+    pub node: NodeId,
+    /// 寫入時使用的資料型別
+    pub data_type: Option<UaType>,
+    /// 是否以訂閱取代輪詢
+    pub subscribe: bool,
+}
+
+impl OpcUaPoint {
+    /// 由通用點位定義轉換，欄位說明參見 [`crate::opcua`]
+    ///
+    /// # Errors
+    /// 節點或其他欄位無效時回傳 [`InvalidTarget`]
+    pub fn parse(definition: &TargetDefinition) -> Result<Self, InvalidTarget> {
+        let invalid = |reason: String| InvalidTarget {
+            target: definition.name.clone(),
+            reason,
+        };
+
+        let node = NodeId::parse(&definition.address)
+            .ok_or_else(|| invalid(format!("無效的節點「{}」", definition.address)))?;
+        let data_type = definition
+            .data_type
+            .as_deref()
+            .map(|data_type| {
+                serde_json::from_value(Value::String(data_type.to_owned()))
+                    .map_err(|_| invalid(format!("不支援的資料型別「{data_type}」")))
+            })
+            .transpose()?;
+        let subscribe = definition
+            .extra
+            .get("subscribe")
+            .map(|subscribe| {
+                subscribe
+                    .as_bool()
+                    .ok_or_else(|| invalid(format!("無效的 subscribe ：{subscribe}")))
+            })
+            .transpose()?
+            .unwrap_or(true);
+
+        Ok(Self {
+            node,
+            data_type,
+            subscribe,
+        })
+    }
+}
+
+/// 合併讀取的節點
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadBatch {
+    /// 節點，依順序排列
+    pub nodes: Arc<[NodeId]>,
+}
+
+impl ReadBatch {
+    fn key(&self) -> String {
+        self.nodes[0].to_string()
+    }
+}
+
+/// 點位
+#[derive(Debug, Clone)]
+pub struct OpcUaTarget(pub TargetDefinition);
+
+impl Target for OpcUaTarget {}
+
+/// 讀取請求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpcUaRequest {
+    /// 點位
+    pub point: OpcUaPoint,
+    /// 包含點位的合併讀取，單獨讀取與訂閱的點位為 [`None`]
+    pub batch: Option<ReadBatch>,
+    /// 是否以訂閱取得數值
+    pub monitored: bool,
+}
+
+impl DeviceStateRequest for OpcUaRequest {}
+
+/// 讀取回覆
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpcUaResponse {
+    /// 節點的數值與時間戳記
+    pub value: DataValue,
+}
+
+impl DeviceStateResponse for OpcUaResponse {
+    fn to_value(&self) -> Result<Cow<'_, Value>, ConversionError> {
+        Ok(Cow::Owned(self.value.value.to_json()))
+    }
+}
+
+impl OpcUaResponse {
+    /// 節點狀態為 Bad 時回傳錯誤
+    fn checked(value: DataValue) -> Result<Self, ConnectionError> {
+        if value.status.is_bad() {
+            return Err(ConnectionError::custom(value.status));
+        }
+        Ok(Self { value })
+    }
+}
+
+/// 寫入請求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpcUaWrite {
+    /// 節點
+    pub node: NodeId,
+    /// 設定值
+    pub value: Value,
+    /// 資料型別，未設定時依節點目前的數值決定
+    pub data_type: Option<UaType>,
+    /// 包含點位的合併讀取，寫入後會清除該次讀取的結果
+    pub batch: Option<ReadBatch>,
+}
+
+impl DeviceStateWrite for OpcUaWrite {}
+
+/// 目前的訂閱
+#[derive(Debug, Clone, Copy)]
+struct Subscription {
+    id: u32,
+    /// 尚未確認的通知序號
+    acknowledge: Option<u32>,
+    published: Option<Instant>,
+}
+
+/// OPC UA 伺服器連線
+///
+/// 合併讀取的結果會保留更新間隔的一半，期間內的其他點位直接使用該結果，並跳過等待間隔
+pub struct OpcUaConnection {
+    config: OpcUaConfig,
+    client: Option<OpcUaClient>,
+    reads: ResponseCache<Arc<[DataValue]>>,
+    batches: Vec<ReadBatch>,
+    /// 監看的節點，用戶端代碼為索引加 1
+    monitored: Vec<NodeId>,
+    subscription: Option<Subscription>,
+    /// 待刪除的訂閱
+    retired: Vec<u32>,
+    /// 伺服器不支援訂閱
+    subscription_unsupported: bool,
+    /// 監看節點的最新數值
+    monitored_values: HashMap<NodeId, DataValue>,
+    remote_address: RemoteAddress,
+    rejected: Vec<InvalidTarget>,
+}
+
+impl OpcUaConnection {
+    /// 初始化點位時被略過的點位
+    #[must_use]
+    pub fn rejected_targets(&self) -> &[InvalidTarget] {
+        &self.rejected
+    }
+
+    /// 取得用戶端，連線已中斷時先重新連線
+    async fn client(&mut self) -> Result<&mut OpcUaClient, ConnectionError> {
+        if self.client.is_none() {
+            let client = OpcUaClient::connect(&self.config).await?;
+            self.remote_address.set(client.peer_addr());
+            self.client = Some(client);
+        }
+        self.client
+            .as_mut()
+            .ok_or_else(|| std::io::Error::from(ErrorKind::NotConnected).into())
+    }
+
+    /// 讀寫失敗、回覆無法解析或工作階段失效時關閉連線，讓下一個請求重新連線
+    ///
+    /// 逾時與個別節點的錯誤不影響連線，逾時後才抵達的回覆會依請求編號捨棄
+    fn settle<T>(&mut self, result: Result<T, ConnectionError>) -> Result<T, ConnectionError> {
+        let lost = match &result {
+            Ok(_) => false,
+            Err(ConnectionError::Io(_) | ConnectionError::Protocol(_)) => true,
+            Err(error) => error
+                .downcast_ref::<StatusCode>()
+                .is_some_and(|status| status.is_session_lost()),
+        };
+        if lost {
+            self.client = None;
+            self.remote_address.set(None);
+            // 工作階段關閉後伺服器會刪除訂閱
+            self.subscription = None;
+            self.retired.clear();
+            self.monitored_values.clear();
+        }
+        result
+    }
+
+    fn set_ttl(&self) {
+        let policy = CachePolicy {
+            ttl: self.config.update_interval / 2,
+            stale_while_revalidate: Duration::ZERO,
+        };
+        for batch in &self.batches {
+            self.reads.enable(batch.key(), policy);
+        }
+    }
+
+    /// 捨棄目前的訂閱，下一次請求時重新建立
+    fn reset_subscription(&mut self) {
+        if let Some(subscription) = self.subscription.take() {
+            self.retired.push(subscription.id);
+        }
+        self.monitored_values.clear();
+    }
+
+    /// 建立訂閱與監看項目
+    ///
+    /// # 回傳值
+    /// 伺服器不支援訂閱時回傳 `false` ，改以輪詢讀取
+    async fn subscribe(&mut self) -> Result<bool, ConnectionError> {
+        if self.subscription_unsupported {
+            return Ok(false);
+        }
+        if self.subscription.is_some() {
+            return Ok(true);
+        }
+
+        let interval = self.config.publishing_interval.unwrap_or_default();
+        // 兩次 `Publish` 之間最長為一個更新間隔，有效期限保留三倍的餘裕
+        let cycles = self.config.update_interval.as_millis() / interval.as_millis().max(1) + 1;
+        let lifetime_count = u32::try_from(cycles * 3).unwrap_or(u32::MAX).max(3);
+
+        let retired = std::mem::take(&mut self.retired);
+        let client = self.client().await?;
+        if !retired.is_empty() {
+            // 刪除失敗的訂閱會在有效期限後由伺服器刪除
+            let _ = client.delete_subscriptions(&retired).await;
+        }
+        let result = client.create_subscription(interval, lifetime_count).await;
+        let id = match self.settle(result) {
+            Ok(id) => id,
+            Err(error) if error.downcast_ref::<StatusCode>().is_some() => {
+                self.subscription_unsupported = true;
+                return Ok(false);
+            }
+            Err(error) => return Err(error),
+        };
+
+        let items: Vec<(u32, NodeId)> = (1..).zip(self.monitored.iter().cloned()).collect();
+        for chunk in items.chunks(usize::from(self.config.max_nodes_per_read.max(1))) {
+            let client = self.client().await?;
+            let result = client.create_monitored_items(id, chunk, interval).await;
+            if let Err(error) = self.settle(result) {
+                self.retired.push(id);
+                return Err(error);
+            }
+        }
+        self.subscription = Some(Subscription {
+            id,
+            acknowledge: None,
+            published: None,
+        });
+        Ok(true)
+    }
+
+    /// 送出 `Publish` 並更新監看節點的數值
+    async fn publish(&mut self) -> Result<(), ConnectionError> {
+        let wait = self.config.timeout + self.config.publishing_interval.unwrap_or_default();
+        for _ in 0..MAX_PUBLISH_ROUNDS {
+            let Some(subscription) = self.subscription.as_mut() else {
+                return Ok(());
+            };
+            let current = subscription.id;
+            let acknowledgement = subscription
+                .acknowledge
+                .take()
+                .map(|sequence| (current, sequence));
+
+            let client = self.client().await?;
+            let result = client.publish(acknowledgement, wait).await;
+            let publication = match self.settle(result) {
+                Ok(publication) => publication,
+                Err(error)
+                    if error.downcast_ref::<StatusCode>().is_some_and(|status| {
+                        matches!(
+                            *status,
+                            StatusCode::BAD_SUBSCRIPTION_ID_INVALID
+                                | StatusCode::BAD_NO_SUBSCRIPTION
+                        )
+                    }) =>
+                {
+                    self.reset_subscription();
+                    return Ok(());
+                }
+                Err(error) => return Err(error),
+            };
+
+            let Some(subscription) = self.subscription.as_mut() else {
+                return Ok(());
+            };
+            subscription.published = Some(Instant::now());
+            // 已捨棄的訂閱在刪除前仍可能回覆通知
+            if publication.subscription == current {
+                if publication.notified {
+                    subscription.acknowledge = Some(publication.sequence);
+                }
+                for (handle, value) in publication.changes {
+                    let node = usize::try_from(handle)
+                        .ok()
+                        .and_then(|handle| self.monitored.get(handle.checked_sub(1)?));
+                    if let Some(node) = node {
+                        self.monitored_values.insert(node.clone(), value);
+                    }
+                }
+                if publication.status.is_some_and(StatusCode::is_bad) {
+                    self.reset_subscription();
+                    return Ok(());
+                }
+            }
+            if !publication.more {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// 以訂閱取得數值，每個發布間隔最多送出一次 `Publish` ，尚未收到數值時直接讀取
+    ///
+    /// # 回傳值
+    /// 伺服器不支援訂閱時回傳 [`None`] ，改以輪詢讀取
+    async fn monitored_value(
+        &mut self,
+        node: &NodeId,
+    ) -> Result<Option<DataValue>, ConnectionError> {
+        if !self.subscribe().await? {
+            return Ok(None);
+        }
+        let interval = self.config.publishing_interval.unwrap_or_default();
+        let due = self.subscription.is_some_and(|subscription| {
+            subscription
+                .published
+                .is_none_or(|published| published.elapsed() >= interval)
+        });
+        if due {
+            self.publish().await?;
+        }
+        if let Some(value) = self.monitored_values.get(node) {
+            return Ok(Some(value.clone()));
+        }
+
+        let value = self.read_one(node).await?;
+        if !value.status.is_bad() {
+            self.monitored_values.insert(node.clone(), value.clone());
+        }
+        Ok(Some(value))
+    }
+
+    async fn read_one(&mut self, node: &NodeId) -> Result<DataValue, ConnectionError> {
+        let client = self.client().await?;
+        let result = client.read(std::slice::from_ref(node)).await;
+        self.settle(result)?.pop().ok_or_else(malformed)
+    }
+}
+
+/// 去除重複的節點，保留原本的順序
+fn unique(nodes: impl Iterator<Item = NodeId>) -> Vec<NodeId> {
+    let mut seen: HashSet<NodeId> = HashSet::default();
+    nodes.filter(|node| seen.insert(node.clone())).collect()
+}
+
+impl Connection for OpcUaConnection {
+    const NAMES: &[&str] = &["OpcUa"];
+    type Config = OpcUaConfig;
+    type Target = OpcUaTarget;
+    type Request = OpcUaRequest;
+    type Response = OpcUaResponse;
+    type Result = ();
+
+    async fn init(config: &OpcUaConfig) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+        let client = OpcUaClient::connect(config).await?;
+        let statistics = ConnectionStats::new(config.endpoint_url.clone(), None);
+        statistics.remote_address.set(client.peer_addr());
+
+        let artifact = ConnectionArtifact::new(
+            Self {
+                config: config.clone(),
+                client: Some(client),
+                reads: ResponseCache::new(),
+                batches: Vec::new(),
+                monitored: Vec::new(),
+                subscription: None,
+                retired: Vec::new(),
+                subscription_unsupported: false,
+                monitored_values: HashMap::default(),
+                remote_address: statistics.remote_address.clone(),
+                rejected: Vec::new(),
+            },
+            statistics,
+        )
+        .update_every(config.update_interval)
+        // `Publish` 最多等待一個發布間隔
+        .timeout_after(config.timeout + config.publishing_interval.unwrap_or_default())
+        .keepalive_every(config.session_timeout / 2);
+
+        Ok(match config.max_retry_count {
+            Some(count) => artifact.retry_up_to(count),
+            None => artifact,
+        })
+    }
+
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<OpcUaTarget>,
+    ) -> ConnectionTargets<OpcUaRequest, ()> {
+        let mut parsed = Vec::with_capacity(targets.len());
+        for OpcUaTarget(definition) in targets {
+            match OpcUaPoint::parse(&definition) {
+                Ok(point) => {
+                    let monitored = definition.auto_refresh
+                        && point.subscribe
+                        && self.config.publishing_interval.is_some();
+                    parsed.push((definition, point, monitored));
+                }
+                Err(error) => self.rejected.push(error),
+            }
+        }
+
+        let polled = unique(
+            parsed
+                .iter()
+                .filter(|(definition, _, monitored)| definition.auto_refresh && !monitored)
+                .map(|(_, point, _)| point.node.clone()),
+        );
+        self.batches = polled
+            .chunks(usize::from(self.config.max_nodes_per_read.max(1)))
+            .filter(|nodes| nodes.len() > 1)
+            .map(|nodes| ReadBatch {
+                nodes: nodes.into(),
+            })
+            .collect();
+        self.set_ttl();
+
+        self.monitored = unique(
+            parsed
+                .iter()
+                .filter(|(_, _, monitored)| *monitored)
+                .map(|(_, point, _)| point.node.clone()),
+        );
+        self.reset_subscription();
+
+        ConnectionTargets(
+            parsed
+                .into_iter()
+                .map(|(definition, point, monitored)| InitedTarget {
+                    name: definition.name,
+                    request: OpcUaRequest {
+                        batch: self
+                            .batches
+                            .iter()
+                            .find(|batch| {
+                                definition.auto_refresh
+                                    && !monitored
+                                    && batch.nodes.contains(&point.node)
+                            })
+                            .cloned(),
+                        point,
+                        monitored,
+                    },
+                    result: (),
+                    default_status: definition.default_status,
+                    auto_refresh: definition.auto_refresh,
+                    refresh_interval: None,
+                    keep_raw_frames: None,
+                    group: None,
+                    safe_state: None,
+                    array: None,
+                    change: None,
+                    statistics: Some(connection_statistics.insert_target(definition.device)),
+                })
+                .collect(),
+        )
+    }
+
+    async fn request_process(
+        &mut self,
+        request: OpcUaRequest,
+    ) -> Result<(OpcUaResponse, bool), ConnectionError> {
+        if request.monitored
+            && let Some(value) = self.monitored_value(&request.point.node).await?
+        {
+            return Ok((OpcUaResponse::checked(value)?, true));
+        }
+
+        let Some(batch) = &request.batch else {
+            let value = self.read_one(&request.point.node).await?;
+            return Ok((OpcUaResponse::checked(value)?, true));
+        };
+        let position = batch
+            .nodes
+            .iter()
+            .position(|node| *node == request.point.node)
+            .ok_or_else(|| ConnectionError::Protocol("點位不在合併讀取中".to_owned()))?;
+
+        let (values, wait) = match self.reads.lookup(&batch.key(), None) {
+            CacheLookup::Fresh(values) => (values, false),
+            CacheLookup::Stale { .. } | CacheLookup::Miss => {
+                let client = self.client().await?;
+                let result = client.read(&batch.nodes).await;
+                let values: Arc<[_]> = self.settle(result)?.into();
+                self.reads.store(&batch.key(), values.clone());
+                (values, true)
+            }
+        };
+        Ok((OpcUaResponse::checked(values[position].clone())?, wait))
+    }
+
+    fn write_preprocess(
+        &self,
+        request: OpcUaRequest,
+        value: Value,
+        _: &RequestContext,
+    ) -> Result<Box<dyn DeviceStateWrite>, ConnectionError> {
+        if let Some(data_type) = request.point.data_type
+            && UaValue::from_json(&value, data_type).is_none()
+        {
+            return Err(ConnectionError::InvalidConfig(format!(
+                "無效的設定值：{value}"
+            )));
+        }
+
+        Ok(Box::new(OpcUaWrite {
+            node: request.point.node,
+            value,
+            data_type: request.point.data_type,
+            batch: request.batch,
+        }))
+    }
+
+    async fn write_process(
+        &mut self,
+        write: Box<dyn DeviceStateWrite>,
+    ) -> Result<Option<OpcUaResponse>, ConnectionError> {
+        let write = write
+            .downcast::<OpcUaWrite>()
+            .map_err(|_| ConnectionError::Protocol("非預期的寫入請求".to_owned()))?;
+        if let Some(batch) = &write.batch {
+            self.reads.invalidate(&batch.key());
+        }
+        self.monitored_values.remove(&write.node);
+
+        let data_type = match write.data_type {
+            Some(data_type) => data_type,
+            None => UaType::of(&self.read_one(&write.node).await?.value).ok_or_else(|| {
+                ConnectionError::InvalidConfig(format!(
+                    "無法判斷節點「{}」的資料型別，請設定 data_type",
+                    write.node
+                ))
+            })?,
+        };
+        let value = UaValue::from_json(&write.value, data_type).ok_or_else(|| {
+            ConnectionError::InvalidConfig(format!("無效的設定值：{}", write.value))
+        })?;
+
+        let client = self.client().await?;
+        let result = client.write(&[(write.node.clone(), value)]).await;
+        let status = self.settle(result)?.pop().ok_or_else(malformed)?;
+        if status.is_bad() {
+            return Err(ConnectionError::custom(status));
+        }
+        Ok(None)
+    }
+
+    async fn keepalive(&mut self) -> Result<(), ConnectionError> {
+        self.read_one(&NodeId::numeric(SERVER_STATE))
+            .await
+            .map(|_| ())
+    }
+
+    async fn disconnect(&mut self) -> Result<(), ConnectionError> {
+        if let Some(mut client) = self.client.take() {
+            client.close().await;
+        }
+        self.remote_address.set(None);
+        self.subscription = None;
+        self.retired.clear();
+        self.monitored_values.clear();
+        Ok(())
+    }
+
+    async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+        self.client = None;
+        self.remote_address.set(None);
+        self.subscription = None;
+        self.retired.clear();
+        self.monitored_values.clear();
+        self.client().await.map(|_| ())
+    }
+
+    async fn update_config(&mut self, new_config: &OpcUaConfig) -> Result<(), ConnectionError> {
+        self.config = new_config.clone();
+        self.subscription_unsupported = false;
+        self.set_ttl();
+        self.disconnect().await?;
+        self.client().await.map(|_| ())
+    }
+}
+
+/// 以記錄下來的 `Read` 回覆解碼點位
+///
+/// `frame` 為安全模式 `None` 的單一區塊 `MSG` 訊息（包含訊息標頭），內容為只讀取一個節點的 `ReadResponse` ，或是 `ERR` 訊息；
+/// 點位由封包內容決定，`target` 不會被使用
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::{fixture::Fixture, opcua::OpcUaConnection};
+///
+/// let fixture = Fixture::from_json(r#"{
+///     "connection": "OpcUa",
+///     "cases": [
+///         {
+///             "name": "Double",
+///             "target": "ns=2;s=Tank",
+///             "frame": "4d534746 46000000 01000000 01000000 01000000 01000000 01007a02 0000000000000000 01000000 00000000 00 00000000 000000 01000000 010b0000000000803540 ffffffff",
+///             "expected": 21.5
+///         },
+///         {
+///             "name": "BadNodeIdUnknown",
+///             "target": "ns=2;s=Tank",
+///             "frame": "4d534746 41000000 01000000 01000000 01000000 01000000 01007a02 0000000000000000 01000000 00000000 00 00000000 000000 01000000 0200003480 ffffffff"
+///         },
+///         { "name": "ERR 訊息", "target": "ns=2;s=Tank", "frame": "45525246 10000000 00000a80 ffffffff" }
+///     ]
+/// }"#).unwrap();
+///
+/// let report = fixture.run::<OpcUaConnection>();
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+impl FrameDecoder for OpcUaConnection {
+    fn decode_frame(_target: &str, frame: &[u8]) -> Result<OpcUaResponse, Box<dyn Error>> {
+        let size = frame
+            .get(4..8)
+            .and_then(|size| size.try_into().ok())
+            .map(u32::from_le_bytes)
+            .ok_or_else(malformed)?;
+        if usize::try_from(size).ok() != Some(frame.len()) {
+            return Err(malformed().into());
+        }
+        let body = match &frame[..4] {
+            b"MSGF" => frame.get(24..).ok_or_else(malformed)?,
+            b"ERRF" => return Err(transport_error(frame).into()),
+            _ => return Err(malformed().into()),
+        };
+
+        let mut reader = Reader::new(body);
+        response_header(&mut reader, READ_RESPONSE)?;
+        if reader.length()? != 1 {
+            return Err(ConnectionError::Protocol("回覆的數值數量與請求不符".to_owned()).into());
+        }
+        Ok(OpcUaResponse::checked(reader.data_value()?)?)
+    }
+}
@@ -0,0 +1,1181 @@
+//! OPC UA 二進位編碼（OPC UA Part 6 ，UA Binary）
+
+use std::{
+    error::Error,
+    fmt::{Display, Write},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::ConnectionError;
+
+/// 1601-01-01 至 1970-01-01 的 100 奈秒數
+const UNIX_EPOCH_TICKS: i64 = 116_444_736_000_000_000;
+
+/// 陣列長度與字串長度的上限，避免錯誤的長度欄位造成大量配置
+const MAX_LENGTH: usize = 16 * 1024 * 1024;
+
+pub(super) fn malformed() -> ConnectionError {
+    ConnectionError::Protocol("無法解析的 OPC UA 訊息".to_owned())
+}
+
+/// 節點識別碼
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum NodeId {
+    /// 數字識別碼，如 `i=2258`
+    Numeric {
+        /// 命名空間索引
+        namespace: u16,
+        /// 識別碼
+        id: u32,
+    },
+    /// 字串識別碼，如 `ns=2;s=Boiler.Temperature`
+    String {
+        /// 命名空間索引
+        namespace: u16,
+        /// 識別碼
+        id: String,
+    },
+    /// GUID 識別碼，如 `ns=1;g=09087e75-8e5e-499b-954f-f2a9603db28a`
+    Guid {
+        /// 命名空間索引
+        namespace: u16,
+        /// 識別碼，依文字表示的順序排列
+        id: [u8; 16],
+    },
+    /// 位元組字串識別碼，如 `ns=1;b=M/RbKBsRVkePCePcx24oRA==`
+    Opaque {
+        /// 命名空間索引
+        namespace: u16,
+        /// 識別碼
+        id: Vec<u8>,
+    },
+}
+
+impl NodeId {
+    /// 空節點（`i=0`）
+    pub const NULL: Self = Self::Numeric {
+        namespace: 0,
+        id: 0,
+    };
+
+    /// 命名空間 0 的數字節點
+    #[must_use]
+    pub const fn numeric(id: u32) -> Self {
+        Self::Numeric { namespace: 0, id }
+    }
+
+    /// 解析 OPC UA 的文字表示，如 `ns=2;s=Boiler.Temperature` 、`i=2258`，未指定命名空間時為 0
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (namespace, identifier) = match text.strip_prefix("ns=") {
+            Some(rest) => {
+                let (namespace, identifier) = rest.split_once(';')?;
+                (namespace.parse().ok()?, identifier)
+            }
+            None => (0, text),
+        };
+        let (kind, id) = identifier.split_once('=')?;
+        Some(match kind {
+            "i" => Self::Numeric {
+                namespace,
+                id: id.parse().ok()?,
+            },
+            "s" => Self::String {
+                namespace,
+                id: id.to_owned(),
+            },
+            "g" => Self::Guid {
+                namespace,
+                id: parse_guid(id)?,
+            },
+            "b" => Self::Opaque {
+                namespace,
+                id: crate::base64::decode(id)?,
+            },
+            _ => return None,
+        })
+    }
+
+    /// 以 UA Binary 編碼，數字識別碼使用可容納的最短編碼（two-byte 、four-byte 或 numeric）
+    ///
+    /// # 範例
+    /// ```rust
+    /// use device_state_exchange_lib::opcua::NodeId;
+    ///
+    /// assert_eq!(NodeId::numeric(85).to_binary(), [0x00, 0x55]);
+    /// assert_eq!(NodeId::parse("ns=2;i=1025").unwrap().to_binary(), [0x01, 0x02, 0x01, 0x04]);
+    /// assert_eq!(
+    ///     NodeId::parse("ns=300;i=70000").unwrap().to_binary(),
+    ///     [0x02, 0x2c, 0x01, 0x70, 0x11, 0x01, 0x00],
+    /// );
+    /// assert_eq!(
+    ///     NodeId::parse("ns=2;s=Tank").unwrap().to_binary(),
+    ///     [0x03, 0x02, 0x00, 0x04, 0x00, 0x00, 0x00, b'T', b'a', b'n', b'k'],
+    /// );
+    /// // GUID 的前三個欄位以 little-endian 編碼
+    /// assert_eq!(
+    ///     NodeId::parse("ns=1;g=09087e75-8e5e-499b-954f-f2a9603db28a").unwrap().to_binary(),
+    ///     [
+    ///         0x04, 0x01, 0x00, 0x75, 0x7e, 0x08, 0x09, 0x5e, 0x8e, 0x9b, 0x49,
+    ///         0x95, 0x4f, 0xf2, 0xa9, 0x60, 0x3d, 0xb2, 0x8a,
+    ///     ],
+    /// );
+    ///
+    /// for text in ["i=85", "ns=2;i=1025", "ns=300;i=70000", "ns=2;s=Tank", "ns=1;g=09087e75-8e5e-499b-954f-f2a9603db28a", "ns=1;b=AAEC"] {
+    ///     let node = NodeId::parse(text).unwrap();
+    ///     assert_eq!(NodeId::from_binary(&node.to_binary()).unwrap(), node);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut writer = Writer::default();
+        writer.node_id(self);
+        writer.0
+    }
+
+    /// 解碼 UA Binary 編碼的節點識別碼
+    ///
+    /// # Errors
+    /// 編碼錯誤或有多餘的位元組時回傳 [`ConnectionError::Protocol`]
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, ConnectionError> {
+        let mut reader = Reader::new(bytes);
+        let node_id = reader.node_id()?;
+        reader.finish()?;
+        Ok(node_id)
+    }
+
+    const fn namespace(&self) -> u16 {
+        match self {
+            Self::Numeric { namespace, .. }
+            | Self::String { namespace, .. }
+            | Self::Guid { namespace, .. }
+            | Self::Opaque { namespace, .. } => *namespace,
+        }
+    }
+}
+
+impl Display for NodeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.namespace() != 0 {
+            write!(f, "ns={};", self.namespace())?;
+        }
+        match self {
+            Self::Numeric { id, .. } => write!(f, "i={id}"),
+            Self::String { id, .. } => write!(f, "s={id}"),
+            Self::Guid { id, .. } => write!(f, "g={}", format_guid(id)),
+            Self::Opaque { id, .. } => write!(f, "b={}", crate::base64::encode(id)),
+        }
+    }
+}
+
+fn parse_guid(text: &str) -> Option<[u8; 16]> {
+    let digits: Vec<u8> = text.bytes().filter(|byte| *byte != b'-').collect();
+    if digits.len() != 32 || text.len() != 36 {
+        return None;
+    }
+    let mut guid = [0; 16];
+    for (byte, pair) in guid.iter_mut().zip(digits.chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(guid)
+}
+
+fn format_guid(guid: &[u8; 16]) -> String {
+    let hex = |bytes: &[u8]| {
+        bytes.iter().fold(String::new(), |mut text, byte| {
+            let _ = write!(text, "{byte:02x}");
+            text
+        })
+    };
+    format!(
+        "{}-{}-{}-{}-{}",
+        hex(&guid[..4]),
+        hex(&guid[4..6]),
+        hex(&guid[6..8]),
+        hex(&guid[8..10]),
+        hex(&guid[10..])
+    )
+}
+
+/// 狀態碼
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StatusCode(pub u32);
+
+impl StatusCode {
+    /// 正常
+    pub const GOOD: Self = Self(0);
+    /// 節點不存在
+    pub const BAD_NODE_ID_UNKNOWN: Self = Self(0x8034_0000);
+    /// 工作階段已失效
+    pub const BAD_SESSION_ID_INVALID: Self = Self(0x8025_0000);
+    /// 工作階段已關閉
+    pub const BAD_SESSION_CLOSED: Self = Self(0x8026_0000);
+    /// 安全通道已失效
+    pub const BAD_SECURE_CHANNEL_ID_INVALID: Self = Self(0x8022_0000);
+    /// 訂閱已失效
+    pub const BAD_SUBSCRIPTION_ID_INVALID: Self = Self(0x8028_0000);
+    /// 沒有訂閱
+    pub const BAD_NO_SUBSCRIPTION: Self = Self(0x8079_0000);
+    /// 伺服器不支援此服務
+    pub const BAD_SERVICE_UNSUPPORTED: Self = Self(0x800b_0000);
+
+    /// 是否為 Bad 狀態
+    #[must_use]
+    pub const fn is_bad(self) -> bool {
+        self.0 & 0x8000_0000 != 0
+    }
+
+    /// 是否為 Uncertain 狀態
+    #[must_use]
+    pub const fn is_uncertain(self) -> bool {
+        self.0 & 0xc000_0000 == 0x4000_0000
+    }
+
+    /// 是否代表工作階段或安全通道已失效，需要重新連線
+    #[must_use]
+    pub const fn is_session_lost(self) -> bool {
+        matches!(
+            self,
+            Self::BAD_SESSION_ID_INVALID
+                | Self::BAD_SESSION_CLOSED
+                | Self::BAD_SECURE_CHANNEL_ID_INVALID
+        )
+    }
+
+    /// 狀態名稱，只包含常見的狀態碼
+    #[must_use]
+    pub const fn name(self) -> Option<&'static str> {
+        Some(match self.0 & 0xffff_0000 {
+            0x0000_0000 => "Good",
+            0x8001_0000 => "BadUnexpectedError",
+            0x8002_0000 => "BadInternalError",
+            0x8005_0000 => "BadCommunicationError",
+            0x800a_0000 => "BadTimeout",
+            0x800b_0000 => "BadServiceUnsupported",
+            0x8010_0000 => "BadTooManyOperations",
+            0x8013_0000 => "BadSecurityChecksFailed",
+            0x801a_0000 => "BadCertificateUntrusted",
+            0x801f_0000 => "BadUserAccessDenied",
+            0x8020_0000 => "BadIdentityTokenInvalid",
+            0x8021_0000 => "BadIdentityTokenRejected",
+            0x8022_0000 => "BadSecureChannelIdInvalid",
+            0x8025_0000 => "BadSessionIdInvalid",
+            0x8026_0000 => "BadSessionClosed",
+            0x8028_0000 => "BadSubscriptionIdInvalid",
+            0x8032_0000 => "BadWaitingForInitialData",
+            0x8033_0000 => "BadNodeIdInvalid",
+            0x8034_0000 => "BadNodeIdUnknown",
+            0x8035_0000 => "BadAttributeIdInvalid",
+            0x803a_0000 => "BadNotReadable",
+            0x803b_0000 => "BadNotWritable",
+            0x803c_0000 => "BadOutOfRange",
+            0x8056_0000 => "BadTooManySessions",
+            0x8074_0000 => "BadTypeMismatch",
+            0x8079_0000 => "BadNoSubscription",
+            _ => return None,
+        })
+    }
+}
+
+impl Display for StatusCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.name() {
+            Some(name) => write!(f, "{name}（0x{:08x}）", self.0),
+            None => write!(f, "狀態碼 0x{:08x}", self.0),
+        }
+    }
+}
+
+impl Error for StatusCode {}
+
+/// 寫入時使用的資料型別
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UaType {
+    /// Boolean
+    Boolean,
+    /// `SByte`
+    Sbyte,
+    /// Byte
+    Byte,
+    /// Int16
+    Int16,
+    /// `UInt16`
+    Uint16,
+    /// Int32
+    Int32,
+    /// `UInt32`
+    Uint32,
+    /// Int64
+    Int64,
+    /// `UInt64`
+    Uint64,
+    /// Float
+    Float,
+    /// Double
+    Double,
+    /// String
+    String,
+    /// `ByteString` ，JSON 中以 base64 字串表示
+    ByteString,
+}
+
+impl UaType {
+    /// 數值的資料型別，陣列以第一個元素為準
+    #[must_use]
+    pub fn of(value: &UaValue) -> Option<Self> {
+        Some(match value {
+            UaValue::Boolean(_) => Self::Boolean,
+            UaValue::SByte(_) => Self::Sbyte,
+            UaValue::Byte(_) => Self::Byte,
+            UaValue::Int16(_) => Self::Int16,
+            UaValue::UInt16(_) => Self::Uint16,
+            UaValue::Int32(_) => Self::Int32,
+            UaValue::UInt32(_) => Self::Uint32,
+            UaValue::Int64(_) => Self::Int64,
+            UaValue::UInt64(_) => Self::Uint64,
+            UaValue::Float(_) => Self::Float,
+            UaValue::Double(_) => Self::Double,
+            UaValue::String(_) => Self::String,
+            UaValue::ByteString(_) => Self::ByteString,
+            UaValue::Array(values) => return values.first().and_then(Self::of),
+            _ => return None,
+        })
+    }
+}
+
+/// 節點的數值（Variant）
+#[derive(Debug, Clone, PartialEq)]
+pub enum UaValue {
+    /// 空值
+    Empty,
+    /// Boolean
+    Boolean(bool),
+    /// `SByte`
+    SByte(i8),
+    /// Byte
+    Byte(u8),
+    /// Int16
+    Int16(i16),
+    /// `UInt16`
+    UInt16(u16),
+    /// Int32
+    Int32(i32),
+    /// `UInt32`
+    UInt32(u32),
+    /// Int64
+    Int64(i64),
+    /// `UInt64`
+    UInt64(u64),
+    /// Float
+    Float(f32),
+    /// Double
+    Double(f64),
+    /// String
+    String(String),
+    /// `DateTime` ，自 1601-01-01 起的 100 奈秒數
+    DateTime(i64),
+    /// Guid ，依文字表示的順序排列
+    Guid([u8; 16]),
+    /// `ByteString`
+    ByteString(Vec<u8>),
+    /// `XmlElement`
+    XmlElement(String),
+    /// `NodeId` 或 `ExpandedNodeId`（不含命名空間 URI）
+    NodeId(NodeId),
+    /// `StatusCode`
+    StatusCode(StatusCode),
+    /// `QualifiedName`
+    QualifiedName {
+        /// 命名空間索引
+        namespace: u16,
+        /// 名稱
+        name: String,
+    },
+    /// `LocalizedText`
+    LocalizedText {
+        /// 語系
+        locale: Option<String>,
+        /// 文字
+        text: Option<String>,
+    },
+    /// `ExtensionObject` ，保留原始編碼
+    ExtensionObject {
+        /// 型別的編碼節點
+        type_id: NodeId,
+        /// 二進位編碼的內容
+        body: Vec<u8>,
+    },
+    /// 陣列，多維陣列會被攤平
+    Array(Vec<Self>),
+}
+
+impl UaValue {
+    /// 轉換為 JSON
+    ///
+    /// `DateTime` 轉換為 RFC 3339 字串，`ByteString` 轉換為 base64 字串，`LocalizedText` 轉換為文字，
+    /// `ExtensionObject` 轉換為包含 `type_id` 與 base64 `body` 的物件，無法以 JSON 表示的浮點數（如 NaN）轉換為 `null`
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        match self {
+            Self::Empty => Value::Null,
+            Self::Boolean(value) => Value::Bool(*value),
+            Self::SByte(value) => Value::from(*value),
+            Self::Byte(value) => Value::from(*value),
+            Self::Int16(value) => Value::from(*value),
+            Self::UInt16(value) => Value::from(*value),
+            Self::Int32(value) => Value::from(*value),
+            Self::UInt32(value) => Value::from(*value),
+            Self::Int64(value) => Value::from(*value),
+            Self::UInt64(value) => Value::from(*value),
+            Self::Float(value) => Value::from(f64::from(*value)),
+            Self::Double(value) => Value::from(*value),
+            Self::String(text) | Self::XmlElement(text) => Value::String(text.clone()),
+            Self::DateTime(ticks) => format_date_time(*ticks).map_or(Value::Null, Value::String),
+            Self::Guid(guid) => Value::String(format_guid(guid)),
+            Self::ByteString(bytes) => Value::String(crate::base64::encode(bytes)),
+            Self::NodeId(node_id) => Value::String(node_id.to_string()),
+            Self::StatusCode(status) => Value::from(status.0),
+            Self::QualifiedName { namespace, name } => Value::String(format!("{namespace}:{name}")),
+            Self::LocalizedText { text, .. } => text.clone().map_or(Value::Null, Value::String),
+            Self::ExtensionObject { type_id, body } => Value::Object(Map::from_iter([
+                ("type_id".to_owned(), Value::String(type_id.to_string())),
+                (
+                    "body".to_owned(),
+                    Value::String(crate::base64::encode(body)),
+                ),
+            ])),
+            Self::Array(values) => values.iter().map(Self::to_json).collect(),
+        }
+    }
+
+    /// 由 JSON 轉換為寫入的數值
+    ///
+    /// # 參數
+    /// - `value`：設定值，陣列會轉換為 [`Self::Array`]
+    /// - `data_type`：資料型別
+    ///
+    /// # 回傳值
+    /// 設定值無法轉換為指定型別（如超出範圍）時回傳 [`None`]
+    #[must_use]
+    pub fn from_json(value: &Value, data_type: UaType) -> Option<Self> {
+        if let Value::Array(values) = value {
+            return values
+                .iter()
+                .map(|value| Self::from_json(value, data_type))
+                .collect::<Option<_>>()
+                .map(Self::Array);
+        }
+
+        let signed = || value.as_i64().or_else(|| value.as_bool().map(i64::from));
+        let unsigned = || value.as_u64().or_else(|| value.as_bool().map(u64::from));
+        Some(match data_type {
+            UaType::Boolean => Self::Boolean(value.as_bool().or_else(|| {
+                value
+                    .as_u64()
+                    .filter(|value| *value <= 1)
+                    .map(|value| value == 1)
+            })?),
+            UaType::Sbyte => Self::SByte(i8::try_from(signed()?).ok()?),
+            UaType::Byte => Self::Byte(u8::try_from(unsigned()?).ok()?),
+            UaType::Int16 => Self::Int16(i16::try_from(signed()?).ok()?),
+            UaType::Uint16 => Self::UInt16(u16::try_from(unsigned()?).ok()?),
+            UaType::Int32 => Self::Int32(i32::try_from(signed()?).ok()?),
+            UaType::Uint32 => Self::UInt32(u32::try_from(unsigned()?).ok()?),
+            UaType::Int64 => Self::Int64(signed()?),
+            UaType::Uint64 => Self::UInt64(unsigned()?),
+            #[expect(clippy::cast_possible_truncation)]
+            UaType::Float => Self::Float(value.as_f64().filter(|value| value.is_finite())? as f32),
+            UaType::Double => Self::Double(value.as_f64()?),
+            UaType::String => Self::String(value.as_str()?.to_owned()),
+            UaType::ByteString => Self::ByteString(crate::base64::decode(value.as_str()?)?),
+        })
+    }
+
+    /// 以 UA Binary 的 Variant 編碼
+    ///
+    /// # 範例
+    /// ```rust
+    /// use device_state_exchange_lib::opcua::{NodeId, StatusCode, UaValue};
+    ///
+    /// assert_eq!(UaValue::Boolean(true).to_binary(), [0x01, 0x01]);
+    /// assert_eq!(UaValue::Int16(-2).to_binary(), [0x04, 0xfe, 0xff]);
+    /// assert_eq!(UaValue::Float(1.5).to_binary(), [0x0a, 0x00, 0x00, 0xc0, 0x3f]);
+    /// assert_eq!(
+    ///     UaValue::Double(21.5).to_binary(),
+    ///     [0x0b, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, 0x35, 0x40],
+    /// );
+    /// assert_eq!(
+    ///     UaValue::String("OK".to_owned()).to_binary(),
+    ///     [0x0c, 0x02, 0x00, 0x00, 0x00, b'O', b'K'],
+    /// );
+    /// assert_eq!(
+    ///     UaValue::Array(vec![UaValue::UInt16(1), UaValue::UInt16(2)]).to_binary(),
+    ///     [0x85, 0x02, 0x00, 0x00, 0x00, 0x01, 0x00, 0x02, 0x00],
+    /// );
+    ///
+    /// for value in [
+    ///     UaValue::Empty,
+    ///     UaValue::SByte(-1),
+    ///     UaValue::UInt32(0xdead_beef),
+    ///     UaValue::Int64(i64::MIN),
+    ///     UaValue::UInt64(u64::MAX),
+    ///     UaValue::DateTime(133_497_792_000_000_000),
+    ///     UaValue::Guid([0x09, 0x08, 0x7e, 0x75, 0x8e, 0x5e, 0x49, 0x9b, 0x95, 0x4f, 0xf2, 0xa9, 0x60, 0x3d, 0xb2, 0x8a]),
+    ///     UaValue::ByteString(vec![0x00, 0xff]),
+    ///     UaValue::NodeId(NodeId::parse("ns=2;s=Tank").unwrap()),
+    ///     UaValue::StatusCode(StatusCode::BAD_NODE_ID_UNKNOWN),
+    ///     UaValue::QualifiedName { namespace: 1, name: "Level".to_owned() },
+    ///     UaValue::LocalizedText { locale: Some("zh-TW".to_owned()), text: Some("液位".to_owned()) },
+    ///     UaValue::ExtensionObject { type_id: NodeId::numeric(887), body: vec![0x01, 0x02] },
+    ///     UaValue::Array(vec![UaValue::String("a".to_owned()), UaValue::String("b".to_owned())]),
+    /// ] {
+    ///     assert_eq!(UaValue::from_binary(&value.to_binary()).unwrap(), value);
+    /// }
+    /// ```
+    #[must_use]
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut writer = Writer::default();
+        self.encode(&mut writer);
+        writer.0
+    }
+
+    /// 解碼 UA Binary 的 Variant ，多維陣列會被攤平
+    ///
+    /// # Errors
+    /// 編碼錯誤、型別不支援或有多餘的位元組時回傳 [`ConnectionError::Protocol`]
+    ///
+    /// # 範例
+    /// 以內建的測試向量驗證：
+    /// ```rust
+    /// use device_state_exchange_lib::{opcua::UaValue, vectors::{self, encode_hex}};
+    ///
+    /// let report = vectors::built_in_set("opcua/variant").unwrap().verify(|frame, value| {
+    ///     let decoded = UaValue::from_binary(frame).map_err(|error| error.to_string())?;
+    ///     if decoded.to_json() != *value {
+    ///         return Err(format!("解析結果為 {}", decoded.to_json()));
+    ///     }
+    ///     let encoded = decoded.to_binary();
+    ///     if encoded == frame { Ok(()) } else { Err(format!("重新編碼為 {}", encode_hex(&encoded))) }
+    /// });
+    /// assert!(report.is_complete(), "{report:?}");
+    /// ```
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, ConnectionError> {
+        let mut reader = Reader::new(bytes);
+        let value = reader.variant()?;
+        reader.finish()?;
+        Ok(value)
+    }
+
+    const fn type_id(&self) -> u8 {
+        match self {
+            Self::Empty | Self::Array(_) => 0,
+            Self::Boolean(_) => 1,
+            Self::SByte(_) => 2,
+            Self::Byte(_) => 3,
+            Self::Int16(_) => 4,
+            Self::UInt16(_) => 5,
+            Self::Int32(_) => 6,
+            Self::UInt32(_) => 7,
+            Self::Int64(_) => 8,
+            Self::UInt64(_) => 9,
+            Self::Float(_) => 10,
+            Self::Double(_) => 11,
+            Self::String(_) => 12,
+            Self::DateTime(_) => 13,
+            Self::Guid(_) => 14,
+            Self::ByteString(_) => 15,
+            Self::XmlElement(_) => 16,
+            Self::NodeId(_) => 17,
+            Self::StatusCode(_) => 19,
+            Self::QualifiedName { .. } => 20,
+            Self::LocalizedText { .. } => 21,
+            Self::ExtensionObject { .. } => 22,
+        }
+    }
+
+    /// 不含型別位元組的編碼
+    fn encode_scalar(&self, writer: &mut Writer) {
+        match self {
+            Self::Empty | Self::Array(_) => {}
+            Self::Boolean(value) => writer.u8(u8::from(*value)),
+            Self::SByte(value) => writer.bytes(&value.to_le_bytes()),
+            Self::Byte(value) => writer.u8(*value),
+            Self::Int16(value) => writer.bytes(&value.to_le_bytes()),
+            Self::UInt16(value) => writer.u16(*value),
+            Self::Int32(value) => writer.i32(*value),
+            Self::UInt32(value) => writer.u32(*value),
+            Self::Int64(value) => writer.i64(*value),
+            Self::UInt64(value) => writer.bytes(&value.to_le_bytes()),
+            Self::Float(value) => writer.bytes(&value.to_le_bytes()),
+            Self::Double(value) => writer.f64(*value),
+            Self::String(text) | Self::XmlElement(text) => writer.string(Some(text)),
+            Self::DateTime(ticks) => writer.i64(*ticks),
+            Self::Guid(guid) => writer.guid(guid),
+            Self::ByteString(bytes) => writer.byte_string(Some(bytes)),
+            Self::NodeId(node_id) => writer.node_id(node_id),
+            Self::StatusCode(status) => writer.u32(status.0),
+            Self::QualifiedName { namespace, name } => {
+                writer.u16(*namespace);
+                writer.string(Some(name));
+            }
+            Self::LocalizedText { locale, text } => {
+                writer.u8(u8::from(locale.is_some()) | (u8::from(text.is_some()) << 1));
+                if let Some(locale) = locale {
+                    writer.string(Some(locale));
+                }
+                if let Some(text) = text {
+                    writer.string(Some(text));
+                }
+            }
+            Self::ExtensionObject { type_id, body } => {
+                writer.node_id(type_id);
+                writer.u8(0x01);
+                writer.byte_string(Some(body));
+            }
+        }
+    }
+
+    /// 以 Variant 編碼
+    pub(super) fn encode(&self, writer: &mut Writer) {
+        match self {
+            Self::Array(values) => {
+                let type_id = values.first().map_or(0, Self::type_id);
+                writer.u8(type_id | 0x80);
+                writer.length(values.len());
+                for value in values {
+                    value.encode_scalar(writer);
+                }
+            }
+            value => {
+                writer.u8(value.type_id());
+                value.encode_scalar(writer);
+            }
+        }
+    }
+
+    fn decode_scalar(type_id: u8, reader: &mut Reader) -> Result<Self, ConnectionError> {
+        Ok(match type_id {
+            0 => Self::Empty,
+            1 => Self::Boolean(reader.u8()? != 0),
+            2 => Self::SByte(i8::from_le_bytes(reader.array()?)),
+            3 => Self::Byte(reader.u8()?),
+            4 => Self::Int16(i16::from_le_bytes(reader.array()?)),
+            5 => Self::UInt16(reader.u16()?),
+            6 => Self::Int32(reader.i32()?),
+            7 => Self::UInt32(reader.u32()?),
+            8 => Self::Int64(reader.i64()?),
+            9 => Self::UInt64(u64::from_le_bytes(reader.array()?)),
+            10 => Self::Float(f32::from_le_bytes(reader.array()?)),
+            11 => Self::Double(reader.f64()?),
+            12 => Self::String(reader.string()?.unwrap_or_default()),
+            13 => Self::DateTime(reader.i64()?),
+            14 => Self::Guid(reader.guid()?),
+            15 => Self::ByteString(reader.byte_string()?.unwrap_or_default()),
+            16 => Self::XmlElement(reader.string()?.unwrap_or_default()),
+            17 => Self::NodeId(reader.node_id()?),
+            18 => Self::NodeId(reader.expanded_node_id()?),
+            19 => Self::StatusCode(StatusCode(reader.u32()?)),
+            20 => Self::QualifiedName {
+                namespace: reader.u16()?,
+                name: reader.string()?.unwrap_or_default(),
+            },
+            21 => {
+                let (locale, text) = reader.localized_text()?;
+                Self::LocalizedText { locale, text }
+            }
+            22 => {
+                let (type_id, body) = reader.extension_object()?;
+                Self::ExtensionObject { type_id, body }
+            }
+            23 => reader.data_value()?.value,
+            24 => reader.variant()?,
+            25 => {
+                reader.diagnostic_info()?;
+                Self::Empty
+            }
+            type_id => {
+                return Err(ConnectionError::Protocol(format!(
+                    "不支援的 Variant 型別 {type_id}"
+                )));
+            }
+        })
+    }
+}
+
+/// 由 OPC UA `DateTime` 轉換為 RFC 3339 字串，`0` 與超出範圍的數值回傳 [`None`]
+fn format_date_time(ticks: i64) -> Option<String> {
+    if ticks <= 0 || ticks == i64::MAX {
+        return None;
+    }
+    let since_epoch = ticks - UNIX_EPOCH_TICKS;
+    let seconds = since_epoch.div_euclid(10_000_000);
+    let millis = since_epoch.rem_euclid(10_000_000) / 10_000;
+    let days = seconds.div_euclid(86_400);
+    let seconds_of_day = seconds.rem_euclid(86_400);
+
+    // 由 1970-01-01 起的天數換算日期（Howard Hinnant 的 civil_from_days）
+    let shifted = days + 719_468;
+    let era = shifted.div_euclid(146_097);
+    let day_of_era = shifted.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    Some(format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{millis:03}Z",
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60
+    ))
+}
+
+/// 目前時間的 OPC UA `DateTime`
+pub(super) fn now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .and_then(|elapsed| i64::try_from(elapsed.as_nanos() / 100).ok())
+        .map_or(0, |ticks| ticks + UNIX_EPOCH_TICKS)
+}
+
+/// 讀取結果（`DataValue`）
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataValue {
+    /// 數值
+    pub value: UaValue,
+    /// 狀態碼
+    pub status: StatusCode,
+    /// 來源時間戳記，自 1601-01-01 起的 100 奈秒數
+    pub source_timestamp: Option<i64>,
+    /// 伺服器時間戳記，自 1601-01-01 起的 100 奈秒數
+    pub server_timestamp: Option<i64>,
+}
+
+impl DataValue {
+    /// 解碼 UA Binary 的 `DataValue`
+    ///
+    /// # Errors
+    /// 編碼錯誤或有多餘的位元組時回傳 [`ConnectionError::Protocol`]
+    ///
+    /// # 範例
+    /// ```rust
+    /// use device_state_exchange_lib::opcua::{DataValue, StatusCode, UaValue};
+    ///
+    /// // 數值、狀態碼與來源時間戳記
+    /// let value = DataValue::from_binary(&[
+    ///     0x07, 0x06, 0x2a, 0x00, 0x00, 0x00, 0x00, 0x00, 0xa4, 0x40,
+    ///     0x00, 0x80, 0x80, 0x35, 0xa8, 0xd9, 0xda, 0x01,
+    /// ])
+    /// .unwrap();
+    /// assert_eq!(value.value, UaValue::Int32(42));
+    /// assert_eq!(value.status, StatusCode(0x40a4_0000));
+    /// assert!(value.status.is_uncertain());
+    /// assert_eq!(value.source_timestamp, Some(0x01da_d9a8_3580_8000));
+    /// assert_eq!(value.server_timestamp, None);
+    ///
+    /// assert_eq!(DataValue::from_binary(&[0x00]).unwrap().value, UaValue::Empty);
+    /// assert!(DataValue::from_binary(&[0x01, 0x06, 0x2a]).is_err());
+    /// ```
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, ConnectionError> {
+        let mut reader = Reader::new(bytes);
+        let value = reader.data_value()?;
+        reader.finish()?;
+        Ok(value)
+    }
+}
+
+/// 編碼器
+#[derive(Debug, Default)]
+pub(super) struct Writer(pub(super) Vec<u8>);
+
+impl Writer {
+    pub(super) fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    pub(super) fn bytes(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+
+    pub(super) fn bool(&mut self, value: bool) {
+        self.u8(u8::from(value));
+    }
+
+    pub(super) fn u16(&mut self, value: u16) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub(super) fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub(super) fn i32(&mut self, value: i32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub(super) fn i64(&mut self, value: i64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    pub(super) fn f64(&mut self, value: f64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    /// 陣列或字串長度
+    pub(super) fn length(&mut self, length: usize) {
+        self.i32(i32::try_from(length).unwrap_or(i32::MAX));
+    }
+
+    pub(super) fn string(&mut self, text: Option<&str>) {
+        self.byte_string(text.map(str::as_bytes));
+    }
+
+    pub(super) fn byte_string(&mut self, bytes: Option<&[u8]>) {
+        match bytes {
+            Some(bytes) => {
+                self.length(bytes.len());
+                self.bytes(bytes);
+            }
+            None => self.i32(-1),
+        }
+    }
+
+    pub(super) fn guid(&mut self, guid: &[u8; 16]) {
+        self.bytes(&[
+            guid[3], guid[2], guid[1], guid[0], guid[5], guid[4], guid[7], guid[6],
+        ]);
+        self.bytes(&guid[8..]);
+    }
+
+    pub(super) fn node_id(&mut self, node_id: &NodeId) {
+        match node_id {
+            NodeId::Numeric { namespace: 0, id } if *id <= 0xff => {
+                self.u8(0x00);
+                self.u8(u8::try_from(*id).unwrap_or_default());
+            }
+            NodeId::Numeric { namespace, id } if *namespace <= 0xff && *id <= 0xffff => {
+                self.u8(0x01);
+                self.u8(u8::try_from(*namespace).unwrap_or_default());
+                self.u16(u16::try_from(*id).unwrap_or_default());
+            }
+            NodeId::Numeric { namespace, id } => {
+                self.u8(0x02);
+                self.u16(*namespace);
+                self.u32(*id);
+            }
+            NodeId::String { namespace, id } => {
+                self.u8(0x03);
+                self.u16(*namespace);
+                self.string(Some(id));
+            }
+            NodeId::Guid { namespace, id } => {
+                self.u8(0x04);
+                self.u16(*namespace);
+                self.guid(id);
+            }
+            NodeId::Opaque { namespace, id } => {
+                self.u8(0x05);
+                self.u16(*namespace);
+                self.byte_string(Some(id));
+            }
+        }
+    }
+
+    /// 沒有內容的 `ExtensionObject`
+    pub(super) fn null_extension_object(&mut self) {
+        self.node_id(&NodeId::NULL);
+        self.u8(0x00);
+    }
+
+    /// 以 `ByteString` 包裝內容的 `ExtensionObject`
+    pub(super) fn extension_object(&mut self, type_id: u32, body: &[u8]) {
+        self.node_id(&NodeId::numeric(type_id));
+        self.u8(0x01);
+        self.byte_string(Some(body));
+    }
+
+    /// 只包含數值的 `DataValue`
+    pub(super) fn data_value(&mut self, value: &UaValue) {
+        self.u8(0x01);
+        value.encode(self);
+    }
+}
+
+/// 解碼器
+#[derive(Debug)]
+pub(super) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(super) const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub(super) fn take(&mut self, length: usize) -> Result<&'a [u8], ConnectionError> {
+        if self.data.len() < length {
+            return Err(malformed());
+        }
+        let (taken, rest) = self.data.split_at(length);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    /// 尚未讀取的長度
+    pub(super) const fn remaining(&self) -> usize {
+        self.data.len()
+    }
+
+    /// 確認已讀取所有內容
+    fn finish(&self) -> Result<(), ConnectionError> {
+        if self.data.is_empty() {
+            Ok(())
+        } else {
+            Err(malformed())
+        }
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ConnectionError> {
+        self.take(N)?.try_into().map_err(|_| malformed())
+    }
+
+    pub(super) fn u8(&mut self) -> Result<u8, ConnectionError> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    pub(super) fn bool(&mut self) -> Result<bool, ConnectionError> {
+        Ok(self.u8()? != 0)
+    }
+
+    pub(super) fn u16(&mut self) -> Result<u16, ConnectionError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    pub(super) fn u32(&mut self) -> Result<u32, ConnectionError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub(super) fn i32(&mut self) -> Result<i32, ConnectionError> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    pub(super) fn i64(&mut self) -> Result<i64, ConnectionError> {
+        Ok(i64::from_le_bytes(self.array()?))
+    }
+
+    pub(super) fn f64(&mut self) -> Result<f64, ConnectionError> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+
+    /// 陣列長度，`-1`（null）視為 0
+    pub(super) fn length(&mut self) -> Result<usize, ConnectionError> {
+        let length = self.i32()?;
+        if length < 0 {
+            return Ok(0);
+        }
+        usize::try_from(length)
+            .ok()
+            .filter(|length| *length <= MAX_LENGTH)
+            .ok_or_else(malformed)
+    }
+
+    pub(super) fn byte_string(&mut self) -> Result<Option<Vec<u8>>, ConnectionError> {
+        let length = self.i32()?;
+        if length < 0 {
+            return Ok(None);
+        }
+        let length = usize::try_from(length).map_err(|_| malformed())?;
+        Ok(Some(self.take(length)?.to_vec()))
+    }
+
+    pub(super) fn string(&mut self) -> Result<Option<String>, ConnectionError> {
+        Ok(self
+            .byte_string()?
+            .map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
+    }
+
+    /// 讀取 `LocalizedText` ，回傳語系與文字
+    pub(super) fn localized_text(
+        &mut self,
+    ) -> Result<(Option<String>, Option<String>), ConnectionError> {
+        let mask = self.u8()?;
+        let locale = if mask & 0x01 == 0 {
+            None
+        } else {
+            self.string()?
+        };
+        let text = if mask & 0x02 == 0 {
+            None
+        } else {
+            self.string()?
+        };
+        Ok((locale, text))
+    }
+
+    /// 字串陣列
+    pub(super) fn strings(&mut self) -> Result<Vec<String>, ConnectionError> {
+        (0..self.length()?)
+            .map(|_| Ok(self.string()?.unwrap_or_default()))
+            .collect()
+    }
+
+    fn guid(&mut self) -> Result<[u8; 16], ConnectionError> {
+        let wire: [u8; 16] = self.array()?;
+        let mut guid = wire;
+        guid[..4].copy_from_slice(&[wire[3], wire[2], wire[1], wire[0]]);
+        guid[4..8].copy_from_slice(&[wire[5], wire[4], wire[7], wire[6]]);
+        Ok(guid)
+    }
+
+    /// 讀取 `NodeId` ，回傳編碼位元組以便 `ExpandedNodeId` 判斷旗標
+    fn node_id_with_mask(&mut self) -> Result<(NodeId, u8), ConnectionError> {
+        let encoding = self.u8()?;
+        let node_id = match encoding & 0x0f {
+            0x00 => NodeId::numeric(u32::from(self.u8()?)),
+            0x01 => NodeId::Numeric {
+                namespace: u16::from(self.u8()?),
+                id: u32::from(self.u16()?),
+            },
+            0x02 => NodeId::Numeric {
+                namespace: self.u16()?,
+                id: self.u32()?,
+            },
+            0x03 => NodeId::String {
+                namespace: self.u16()?,
+                id: self.string()?.unwrap_or_default(),
+            },
+            0x04 => NodeId::Guid {
+                namespace: self.u16()?,
+                id: self.guid()?,
+            },
+            0x05 => NodeId::Opaque {
+                namespace: self.u16()?,
+                id: self.byte_string()?.unwrap_or_default(),
+            },
+            _ => return Err(malformed()),
+        };
+        Ok((node_id, encoding))
+    }
+
+    pub(super) fn node_id(&mut self) -> Result<NodeId, ConnectionError> {
+        self.node_id_with_mask().map(|(node_id, _)| node_id)
+    }
+
+    /// 讀取 `ExpandedNodeId` ，捨棄命名空間 URI 與伺服器索引
+    pub(super) fn expanded_node_id(&mut self) -> Result<NodeId, ConnectionError> {
+        let (node_id, encoding) = self.node_id_with_mask()?;
+        if encoding & 0x80 != 0 {
+            self.string()?;
+        }
+        if encoding & 0x40 != 0 {
+            self.u32()?;
+        }
+        Ok(node_id)
+    }
+
+    /// 讀取 `ExtensionObject` ，回傳型別的編碼節點與內容
+    pub(super) fn extension_object(&mut self) -> Result<(NodeId, Vec<u8>), ConnectionError> {
+        let type_id = self.node_id()?;
+        let body = match self.u8()? {
+            0x00 => Vec::new(),
+            0x01 => self.byte_string()?.unwrap_or_default(),
+            0x02 => self.string()?.unwrap_or_default().into_bytes(),
+            _ => return Err(malformed()),
+        };
+        Ok((type_id, body))
+    }
+
+    pub(super) fn variant(&mut self) -> Result<UaValue, ConnectionError> {
+        let mask = self.u8()?;
+        let type_id = mask & 0x3f;
+        if mask & 0x80 == 0 {
+            return UaValue::decode_scalar(type_id, self);
+        }
+        let length = self.length()?;
+        let values = (0..length)
+            .map(|_| UaValue::decode_scalar(type_id, self))
+            .collect::<Result<Vec<_>, _>>()?;
+        if mask & 0x40 != 0 {
+            // 多維陣列的維度
+            for _ in 0..self.length()? {
+                self.i32()?;
+            }
+        }
+        Ok(UaValue::Array(values))
+    }
+
+    pub(super) fn data_value(&mut self) -> Result<DataValue, ConnectionError> {
+        let mask = self.u8()?;
+        let value = if mask & 0x01 == 0 {
+            UaValue::Empty
+        } else {
+            self.variant()?
+        };
+        let status = if mask & 0x02 == 0 {
+            StatusCode::GOOD
+        } else {
+            StatusCode(self.u32()?)
+        };
+        let source_timestamp = if mask & 0x04 == 0 {
+            None
+        } else {
+            Some(self.i64()?)
+        };
+        if mask & 0x10 != 0 {
+            self.u16()?;
+        }
+        let server_timestamp = if mask & 0x08 == 0 {
+            None
+        } else {
+            Some(self.i64()?)
+        };
+        if mask & 0x20 != 0 {
+            self.u16()?;
+        }
+        Ok(DataValue {
+            value,
+            status,
+            source_timestamp,
+            server_timestamp,
+        })
+    }
+
+    /// 略過 `DiagnosticInfo`
+    pub(super) fn diagnostic_info(&mut self) -> Result<(), ConnectionError> {
+        let mask = self.u8()?;
+        for bit in [0x01, 0x02, 0x04, 0x08] {
+            if mask & bit != 0 {
+                self.i32()?;
+            }
+        }
+        if mask & 0x10 != 0 {
+            self.string()?;
+        }
+        if mask & 0x20 != 0 {
+            self.u32()?;
+        }
+        if mask & 0x40 != 0 {
+            self.diagnostic_info()?;
+        }
+        Ok(())
+    }
+
+    /// 狀態碼陣列
+    pub(super) fn status_codes(&mut self) -> Result<Vec<StatusCode>, ConnectionError> {
+        (0..self.length()?)
+            .map(|_| self.u32().map(StatusCode))
+            .collect()
+    }
+}
@@ -0,0 +1,657 @@
+//! OPC UA 安全性：憑證、非對稱加密與對稱加密（OPC UA Part 6 第 6.7 節）
+
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use cbc::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit, block_padding::NoPadding};
+use hmac::{Hmac, Mac};
+use rand_core::{OsRng, RngCore};
+use rsa::{
+    Oaep, Pkcs1v15Sign, RsaPrivateKey, RsaPublicKey, pkcs1::DecodeRsaPrivateKey,
+    pkcs8::DecodePrivateKey, pkcs8::DecodePublicKey, traits::PublicKeyParts,
+};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use x509_cert::{
+    Certificate,
+    der::{Decode, Encode},
+};
+
+use crate::ConnectionError;
+
+/// 隨機數長度
+pub(super) const NONCE_LENGTH: usize = 32;
+
+/// 對稱簽章長度（HMAC-SHA256）
+pub(super) const SYMMETRIC_SIGNATURE_LENGTH: usize = 32;
+
+/// AES 區塊大小
+pub(super) const BLOCK_SIZE: usize = 16;
+
+/// 對稱加密區塊的訊息標頭與安全標頭長度，之後的內容會被加密
+const SECURITY_HEADER_END: usize = 16;
+
+/// 對稱加密區塊的標頭長度（訊息標頭、安全標頭與序號標頭）
+const CHUNK_HEADER_LENGTH: usize = SECURITY_HEADER_END + 8;
+
+/// RSA-OAEP-SHA1 每個區塊的額外長度
+const OAEP_OVERHEAD: usize = 42;
+
+/// 安全原則
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityPolicy {
+    /// 不簽章、不加密
+    #[default]
+    None,
+    /// `Basic256Sha256`
+    Basic256Sha256,
+    /// `Aes128_Sha256_RsaOaep`
+    Aes128Sha256RsaOaep,
+}
+
+impl SecurityPolicy {
+    /// 安全原則的 URI
+    #[must_use]
+    pub const fn uri(self) -> &'static str {
+        match self {
+            Self::None => "http://opcfoundation.org/UA/SecurityPolicy#None",
+            Self::Basic256Sha256 => "http://opcfoundation.org/UA/SecurityPolicy#Basic256Sha256",
+            Self::Aes128Sha256RsaOaep => {
+                "http://opcfoundation.org/UA/SecurityPolicy#Aes128_Sha256_RsaOaep"
+            }
+        }
+    }
+
+    /// 對稱加密金鑰長度
+    const fn encrypting_key_length(self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::Basic256Sha256 => 32,
+            Self::Aes128Sha256RsaOaep => 16,
+        }
+    }
+}
+
+/// 訊息安全模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecurityMode {
+    /// 不簽章、不加密
+    #[default]
+    None,
+    /// 只簽章
+    Sign,
+    /// 簽章並加密
+    SignAndEncrypt,
+}
+
+impl SecurityMode {
+    /// `MessageSecurityMode` 的編碼
+    pub(super) const fn number(self) -> u32 {
+        match self {
+            Self::None => 1,
+            Self::Sign => 2,
+            Self::SignAndEncrypt => 3,
+        }
+    }
+}
+
+/// 安全設定
+///
+/// 安全原則與安全模式需同時為 `none` 或同時不為 `none` ；使用簽章或加密時需設定用戶端憑證與私鑰，
+/// 用戶端憑證需事先加入伺服器的信任清單
+///
+/// 使用簽章或加密時也需設定信任的伺服器憑證 [`SecurityConfig::server_certificate`] ，`GetEndpoints` 的回覆未經驗證，
+/// 直接信任其中的憑證無法防範中間人攻擊；確定要信任伺服器提供的任何憑證時，需明確設定 [`SecurityConfig::trust_any_server_certificate`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecurityConfig {
+    /// 安全原則，預設為 `none`
+    #[serde(default)]
+    pub policy: SecurityPolicy,
+    /// 安全模式，預設為 `none`
+    #[serde(default)]
+    pub mode: SecurityMode,
+    /// 用戶端憑證，DER 或 PEM 格式的 X.509 憑證檔案路徑，憑證的應用程式 URI 需與 [`super::OpcUaConfig::application_uri`] 相同
+    #[serde(default)]
+    pub certificate: Option<PathBuf>,
+    /// 用戶端私鑰，PKCS#8 或 PKCS#1 （DER 或 PEM）格式的 RSA 私鑰檔案路徑
+    #[serde(default)]
+    pub private_key: Option<PathBuf>,
+    /// 信任的伺服器憑證，DER 或 PEM 格式的 X.509 憑證檔案路徑，伺服器提供的憑證需與此憑證相同
+    #[serde(default)]
+    pub server_certificate: Option<PathBuf>,
+    /// 未設定 [`SecurityConfig::server_certificate`] 時，是否信任 `GetEndpoints` 回傳的任何伺服器憑證，預設為 `false`
+    ///
+    /// 僅適用於測試環境，啟用後無法防範中間人攻擊
+    #[serde(default)]
+    pub trust_any_server_certificate: bool,
+}
+
+impl SecurityConfig {
+    /// 檢查設定並讀取用戶端憑證與私鑰
+    ///
+    /// # 回傳值
+    /// 不使用簽章時回傳 [`None`]
+    ///
+    /// # Errors
+    /// 設定不一致、未設定信任的伺服器憑證，或檔案無法讀取、解析時回傳 [`ConnectionError::InvalidConfig`]
+    pub(super) fn load(&self) -> Result<Option<Credentials>, ConnectionError> {
+        match (self.policy, self.mode) {
+            (SecurityPolicy::None, SecurityMode::None) => return Ok(None),
+            (SecurityPolicy::None, _) | (_, SecurityMode::None) => {
+                return Err(ConnectionError::InvalidConfig(
+                    "安全原則與安全模式需同時為 none 或同時不為 none".to_owned(),
+                ));
+            }
+            _ => {}
+        }
+        let (Some(certificate), Some(private_key)) = (&self.certificate, &self.private_key) else {
+            return Err(ConnectionError::InvalidConfig(
+                "使用簽章或加密時需設定 certificate 與 private_key".to_owned(),
+            ));
+        };
+        if self.server_certificate.is_none() && !self.trust_any_server_certificate {
+            return Err(ConnectionError::InvalidConfig(
+                "使用簽章或加密時需設定 server_certificate ，或明確設定 trust_any_server_certificate"
+                    .to_owned(),
+            ));
+        }
+
+        let certificate = read_der(certificate)?;
+        let public_key = public_key(&certificate).map_err(|error| {
+            ConnectionError::InvalidConfig(format!("無法解析用戶端憑證：{error}"))
+        })?;
+        let key = read_private_key(private_key)?;
+        if key.to_public_key() != public_key {
+            return Err(ConnectionError::InvalidConfig(
+                "用戶端私鑰與憑證不符".to_owned(),
+            ));
+        }
+        let trusted = self
+            .server_certificate
+            .as_deref()
+            .map(read_der)
+            .transpose()?;
+
+        Ok(Some(Credentials {
+            certificate,
+            key,
+            trusted,
+        }))
+    }
+}
+
+/// 用戶端憑證與私鑰
+#[derive(Clone)]
+pub(super) struct Credentials {
+    /// DER 格式的用戶端憑證
+    pub(super) certificate: Vec<u8>,
+    pub(super) key: RsaPrivateKey,
+    /// 信任的伺服器憑證
+    pub(super) trusted: Option<Vec<u8>>,
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("certificate", &self.certificate.len())
+            .field("key", &"***")
+            .field("trusted", &self.trusted.is_some())
+            .finish()
+    }
+}
+
+impl Credentials {
+    /// 簽章長度
+    pub(super) fn signature_length(&self) -> usize {
+        self.key.size()
+    }
+
+    /// RSA-PKCS1-v1_5-SHA256 簽章
+    pub(super) fn sign(&self, data: &[u8]) -> Result<Vec<u8>, ConnectionError> {
+        self.key
+            .sign(Pkcs1v15Sign::new::<Sha256>(), &Sha256::digest(data))
+            .map_err(|error| ConnectionError::Protocol(format!("簽章失敗：{error}")))
+    }
+
+    /// 以 RSA-OAEP-SHA1 逐區塊解密
+    pub(super) fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, ConnectionError> {
+        let size = self.key.size();
+        if !data.len().is_multiple_of(size) {
+            return Err(ConnectionError::Protocol("加密區塊長度錯誤".to_owned()));
+        }
+        let mut plain = Vec::with_capacity(data.len());
+        for block in data.chunks(size) {
+            let decrypted = self
+                .key
+                .decrypt(Oaep::new::<Sha1>(), block)
+                .map_err(|error| ConnectionError::Protocol(format!("解密失敗：{error}")))?;
+            plain.extend(decrypted);
+        }
+        Ok(plain)
+    }
+}
+
+/// 伺服器憑證
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ServerCertificate {
+    /// DER 格式的憑證
+    pub(super) der: Vec<u8>,
+    key: RsaPublicKey,
+}
+
+impl ServerCertificate {
+    /// 解析伺服器憑證，並檢查是否為信任的憑證與是否在有效期間內
+    ///
+    /// # Errors
+    /// 無法解析時回傳 [`ConnectionError::Protocol`] ，與信任的憑證不同、尚未生效或已過期時回傳 [`ConnectionError::InvalidConfig`]
+    pub(super) fn parse(der: &[u8], trusted: Option<&[u8]>) -> Result<Self, ConnectionError> {
+        // 伺服器可能回傳憑證鏈，第一張為伺服器憑證
+        let der = first_certificate(der)
+            .ok_or_else(|| ConnectionError::Protocol("無法解析伺服器憑證".to_owned()))?
+            .to_vec();
+        if trusted.is_some_and(|trusted| trusted != der) {
+            return Err(ConnectionError::InvalidConfig(
+                "伺服器憑證與 server_certificate 不符".to_owned(),
+            ));
+        }
+        let certificate = Certificate::from_der(&der)
+            .map_err(|error| ConnectionError::Protocol(format!("無法解析伺服器憑證：{error}")))?;
+        check_validity(&certificate, SystemTime::now())?;
+        let key = public_key(&der)
+            .map_err(|error| ConnectionError::Protocol(format!("無法解析伺服器憑證：{error}")))?;
+        Ok(Self { der, key })
+    }
+
+    /// 憑證的 SHA-1 指紋
+    pub(super) fn thumbprint(&self) -> Vec<u8> {
+        Sha1::digest(&self.der).to_vec()
+    }
+
+    /// 公鑰長度
+    pub(super) fn key_length(&self) -> usize {
+        self.key.size()
+    }
+
+    /// 每個加密區塊可容納的明文長度
+    pub(super) fn plain_block_length(&self) -> usize {
+        self.key.size() - OAEP_OVERHEAD
+    }
+
+    /// 驗證 RSA-PKCS1-v1_5-SHA256 簽章
+    pub(super) fn verify(&self, data: &[u8], signature: &[u8]) -> Result<(), ConnectionError> {
+        self.key
+            .verify(
+                Pkcs1v15Sign::new::<Sha256>(),
+                &Sha256::digest(data),
+                signature,
+            )
+            .map_err(|_| ConnectionError::Protocol("伺服器簽章驗證失敗".to_owned()))
+    }
+
+    /// 以 RSA-OAEP-SHA1 逐區塊加密
+    pub(super) fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>, ConnectionError> {
+        let mut encrypted =
+            Vec::with_capacity(data.len().div_ceil(self.plain_block_length()) * self.key_length());
+        for block in data.chunks(self.plain_block_length()) {
+            let block = self
+                .key
+                .encrypt(&mut OsRng, Oaep::new::<Sha1>(), block)
+                .map_err(|error| ConnectionError::Protocol(format!("加密失敗：{error}")))?;
+            encrypted.extend(block);
+        }
+        Ok(encrypted)
+    }
+}
+
+/// 憑證鏈中的第一張憑證
+fn first_certificate(chain: &[u8]) -> Option<&[u8]> {
+    if chain.first() != Some(&0x30) {
+        return None;
+    }
+    let first = *chain.get(1)?;
+    let (header, length) = if first & 0x80 == 0 {
+        (2, usize::from(first))
+    } else {
+        let count = usize::from(first & 0x7f);
+        if count == 0 || count > 4 {
+            return None;
+        }
+        let length = chain
+            .get(2..2 + count)?
+            .iter()
+            .fold(0, |length, byte| (length << 8) | usize::from(*byte));
+        (2 + count, length)
+    };
+    chain.get(..header + length)
+}
+
+/// 檢查憑證在 `now` 時是否有效
+fn check_validity(certificate: &Certificate, now: SystemTime) -> Result<(), ConnectionError> {
+    let validity = &certificate.tbs_certificate.validity;
+    let now = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+    if now < validity.not_before.to_date_time().unix_duration() {
+        return Err(ConnectionError::InvalidConfig(format!(
+            "伺服器憑證尚未生效（生效時間 {}）",
+            validity.not_before
+        )));
+    }
+    if now > validity.not_after.to_date_time().unix_duration() {
+        return Err(ConnectionError::InvalidConfig(format!(
+            "伺服器憑證已過期（到期時間 {}）",
+            validity.not_after
+        )));
+    }
+    Ok(())
+}
+
+fn public_key(certificate: &[u8]) -> Result<RsaPublicKey, String> {
+    let certificate = Certificate::from_der(certificate).map_err(|error| error.to_string())?;
+    let info = certificate
+        .tbs_certificate
+        .subject_public_key_info
+        .to_der()
+        .map_err(|error| error.to_string())?;
+    RsaPublicKey::from_public_key_der(&info).map_err(|error| error.to_string())
+}
+
+/// 讀取 DER 或 PEM 格式的檔案
+///
+/// # 回傳值
+/// DER 內容與 PEM 的標籤，DER 檔案的標籤為 [`None`]
+fn read_file(path: &Path) -> Result<(Vec<u8>, Option<String>), ConnectionError> {
+    let invalid = |reason: String| {
+        ConnectionError::InvalidConfig(format!("無法讀取「{}」：{reason}", path.display()))
+    };
+    let content = std::fs::read(path).map_err(|error| invalid(error.to_string()))?;
+    let Ok(text) = std::str::from_utf8(&content) else {
+        return Ok((content, None));
+    };
+    let Some(begin) = text.find("-----BEGIN ") else {
+        return Ok((content, None));
+    };
+
+    let mut lines = text[begin..].lines();
+    let label = lines
+        .next()
+        .and_then(|line| line.strip_prefix("-----BEGIN "))
+        .and_then(|line| line.strip_suffix("-----"))
+        .ok_or_else(|| invalid("無效的 PEM 格式".to_owned()))?
+        .to_owned();
+    let body: String = lines
+        .take_while(|line| !line.starts_with("-----END "))
+        .map(str::trim)
+        .collect();
+    let der = crate::base64::decode(&body).ok_or_else(|| invalid("無效的 PEM 格式".to_owned()))?;
+    Ok((der, Some(label)))
+}
+
+fn read_der(path: &Path) -> Result<Vec<u8>, ConnectionError> {
+    read_file(path).map(|(der, _)| der)
+}
+
+fn read_private_key(path: &Path) -> Result<RsaPrivateKey, ConnectionError> {
+    let (der, label) = read_file(path)?;
+    let key = match label.as_deref() {
+        Some("RSA PRIVATE KEY") => {
+            RsaPrivateKey::from_pkcs1_der(&der).map_err(|error| error.to_string())
+        }
+        Some("PRIVATE KEY") => {
+            RsaPrivateKey::from_pkcs8_der(&der).map_err(|error| error.to_string())
+        }
+        Some(label) => Err(format!("不支援的 PEM 類型「{label}」")),
+        None => RsaPrivateKey::from_pkcs8_der(&der)
+            .or_else(|_| RsaPrivateKey::from_pkcs1_der(&der))
+            .map_err(|error| error.to_string()),
+    };
+    key.map_err(|reason| {
+        ConnectionError::InvalidConfig(format!("無法解析私鑰「{}」：{reason}", path.display()))
+    })
+}
+
+/// 產生隨機數
+pub(super) fn nonce() -> Vec<u8> {
+    let mut nonce = vec![0; NONCE_LENGTH];
+    OsRng.fill_bytes(&mut nonce);
+    nonce
+}
+
+fn hmac(key: &[u8]) -> Result<Hmac<Sha256>, ConnectionError> {
+    Hmac::new_from_slice(key).map_err(|_| ConnectionError::Protocol("無效的金鑰長度".to_owned()))
+}
+
+/// `P_SHA256` 金鑰衍生
+fn p_sha256(secret: &[u8], seed: &[u8], length: usize) -> Result<Vec<u8>, ConnectionError> {
+    let mac = hmac(secret)?;
+    let mut output = Vec::with_capacity(length + 32);
+    let mut a = mac.clone().chain_update(seed).finalize().into_bytes();
+    while output.len() < length {
+        output.extend(
+            mac.clone()
+                .chain_update(a)
+                .chain_update(seed)
+                .finalize()
+                .into_bytes(),
+        );
+        a = mac.clone().chain_update(a).finalize().into_bytes();
+    }
+    output.truncate(length);
+    Ok(output)
+}
+
+/// 一個方向的對稱金鑰，用於安全通道開啟後的訊息區塊（`MSG` 、`CLO`）
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::{
+///     opcua::{SecurityMode, SecurityPolicy, SymmetricKeys},
+///     vectors::encode_hex,
+/// };
+///
+/// let client_nonce = [0x11; 32];
+/// let server_nonce = [0x22; 32];
+/// let keys = SymmetricKeys::derive(SecurityPolicy::Basic256Sha256, &server_nonce, &client_nonce).unwrap();
+///
+/// // 訊息標頭（長度由 seal 填入）、安全通道編號 1 、權杖編號 2 、序號 3 、請求編號 4 與內容
+/// let mut chunk = b"MSGF\0\0\0\0".to_vec();
+/// for field in [1_u32, 2, 3, 4] {
+///     chunk.extend(field.to_le_bytes());
+/// }
+/// chunk.extend(b"hello");
+///
+/// let signed = keys.seal(SecurityMode::Sign, chunk.clone()).unwrap();
+/// assert_eq!(
+///     encode_hex(&signed),
+///     "4d5347463d000000010000000200000003000000040000006865\
+///      6c6c6f548ed551c9e2e64d419d1c7d4410c68f24ab509e51dad277bce396035e0089b1",
+/// );
+/// assert_eq!(keys.open(SecurityMode::Sign, signed).unwrap()[24..], *b"hello");
+///
+/// let encrypted = keys.seal(SecurityMode::SignAndEncrypt, chunk.clone()).unwrap();
+/// assert_eq!(
+///     encode_hex(&encrypted),
+///     "4d534746400000000100000002000000da6d8bd68db0607aca57537017cbda08\
+///      a55c522c7aea4041e684f23428f2995678f4a3ddf590028902c2d0e29569e289",
+/// );
+/// assert_eq!(keys.open(SecurityMode::SignAndEncrypt, encrypted.clone()).unwrap()[24..], *b"hello");
+///
+/// // 竄改任何位元組都會使簽章驗證失敗
+/// let mut tampered = encrypted;
+/// tampered[20] ^= 0x01;
+/// assert!(keys.open(SecurityMode::SignAndEncrypt, tampered).is_err());
+/// ```
+#[derive(Clone)]
+pub struct SymmetricKeys {
+    signer: Hmac<Sha256>,
+    encrypting: Vec<u8>,
+    iv: [u8; BLOCK_SIZE],
+}
+
+impl std::fmt::Debug for SymmetricKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SymmetricKeys").finish_non_exhaustive()
+    }
+}
+
+impl SymmetricKeys {
+    /// 由雙方的隨機數衍生金鑰
+    ///
+    /// 用戶端送出訊息使用的金鑰以伺服器隨機數為 `secret` 、用戶端隨機數為 `seed` ，伺服器送出訊息的金鑰則相反
+    ///
+    /// # Errors
+    /// 安全原則為 [`SecurityPolicy::None`] 時回傳 [`ConnectionError::Protocol`]
+    pub fn derive(
+        policy: SecurityPolicy,
+        secret: &[u8],
+        seed: &[u8],
+    ) -> Result<Self, ConnectionError> {
+        if policy == SecurityPolicy::None {
+            return Err(ConnectionError::Protocol(
+                "安全原則為 None 時沒有對稱金鑰".to_owned(),
+            ));
+        }
+        let encrypting_length = policy.encrypting_key_length();
+        let material = p_sha256(
+            secret,
+            seed,
+            SYMMETRIC_SIGNATURE_LENGTH + encrypting_length + BLOCK_SIZE,
+        )?;
+        let (signing, rest) = material.split_at(SYMMETRIC_SIGNATURE_LENGTH);
+        let (encrypting, iv) = rest.split_at(encrypting_length);
+        Ok(Self {
+            signer: hmac(signing)?,
+            encrypting: encrypting.to_vec(),
+            iv: iv
+                .try_into()
+                .map_err(|_| ConnectionError::Protocol("無效的金鑰長度".to_owned()))?,
+        })
+    }
+
+    /// 簽章並依安全模式加密一個訊息區塊
+    ///
+    /// `chunk` 為 16 位元組的訊息標頭（含權杖編號）、8 位元組的序號標頭與內容，加密時補上填充位元組，
+    /// 並將訊息長度寫入標頭；安全模式為 [`SecurityMode::None`] 時只寫入訊息長度
+    ///
+    /// # Errors
+    /// 區塊長度不足標頭長度或加密失敗時回傳 [`ConnectionError::Protocol`]
+    pub fn seal(&self, mode: SecurityMode, mut chunk: Vec<u8>) -> Result<Vec<u8>, ConnectionError> {
+        if chunk.len() < CHUNK_HEADER_LENGTH {
+            return Err(ConnectionError::Protocol("訊息區塊長度錯誤".to_owned()));
+        }
+        let signature_length = if mode == SecurityMode::None {
+            0
+        } else {
+            SYMMETRIC_SIGNATURE_LENGTH
+        };
+        if mode == SecurityMode::SignAndEncrypt {
+            let unpadded = chunk.len() - SECURITY_HEADER_END + 1 + signature_length;
+            let padding = (BLOCK_SIZE - unpadded % BLOCK_SIZE) % BLOCK_SIZE;
+            let padding_byte = u8::try_from(padding)
+                .map_err(|_| ConnectionError::Protocol("訊息區塊長度錯誤".to_owned()))?;
+            chunk.resize(chunk.len() + padding + 1, padding_byte);
+        }
+        let size = u32::try_from(chunk.len() + signature_length)
+            .map_err(|_| ConnectionError::Protocol("訊息區塊長度錯誤".to_owned()))?;
+        chunk[4..8].copy_from_slice(&size.to_le_bytes());
+        if mode == SecurityMode::None {
+            return Ok(chunk);
+        }
+        let signature = self.sign(&chunk);
+        chunk.extend(signature);
+        if mode == SecurityMode::SignAndEncrypt {
+            self.encrypt(&mut chunk[SECURITY_HEADER_END..])?;
+        }
+        Ok(chunk)
+    }
+
+    /// 依安全模式解密並驗證一個訊息區塊
+    ///
+    /// # 回傳值
+    /// 去除填充位元組與簽章後的區塊，包含標頭
+    ///
+    /// # Errors
+    /// 區塊長度錯誤、解密或簽章驗證失敗時回傳 [`ConnectionError::Protocol`]
+    pub fn open(&self, mode: SecurityMode, mut chunk: Vec<u8>) -> Result<Vec<u8>, ConnectionError> {
+        let malformed = || ConnectionError::Protocol("訊息區塊長度錯誤".to_owned());
+        if chunk.len() < CHUNK_HEADER_LENGTH {
+            return Err(malformed());
+        }
+        if mode == SecurityMode::None {
+            return Ok(chunk);
+        }
+        let encrypted = mode == SecurityMode::SignAndEncrypt;
+        if encrypted {
+            self.decrypt(&mut chunk[SECURITY_HEADER_END..])?;
+        }
+        let mut end = chunk
+            .len()
+            .checked_sub(SYMMETRIC_SIGNATURE_LENGTH)
+            .ok_or_else(malformed)?;
+        self.verify(&chunk[..end], &chunk[end..])?;
+        if encrypted {
+            let padding = usize::from(chunk[end - 1]);
+            end = end.checked_sub(padding + 1).ok_or_else(malformed)?;
+        }
+        if end < CHUNK_HEADER_LENGTH {
+            return Err(malformed());
+        }
+        chunk.truncate(end);
+        Ok(chunk)
+    }
+
+    /// HMAC-SHA256 簽章
+    fn sign(&self, data: &[u8]) -> Vec<u8> {
+        self.signer
+            .clone()
+            .chain_update(data)
+            .finalize()
+            .into_bytes()
+            .to_vec()
+    }
+
+    /// 驗證 HMAC-SHA256 簽章
+    fn verify(&self, data: &[u8], signature: &[u8]) -> Result<(), ConnectionError> {
+        self.signer
+            .clone()
+            .chain_update(data)
+            .verify_slice(signature)
+            .map_err(|_| ConnectionError::Protocol("訊息簽章驗證失敗".to_owned()))
+    }
+
+    /// 以 AES-CBC 加密，長度需為區塊大小的倍數
+    fn encrypt(&self, data: &mut [u8]) -> Result<(), ConnectionError> {
+        let length = data.len();
+        let result = match self.encrypting.len() {
+            16 => cbc::Encryptor::<aes::Aes128>::new_from_slices(&self.encrypting, &self.iv)
+                .ok()
+                .and_then(|cipher| cipher.encrypt_padded_mut::<NoPadding>(data, length).ok())
+                .map(|_| ()),
+            32 => cbc::Encryptor::<aes::Aes256>::new_from_slices(&self.encrypting, &self.iv)
+                .ok()
+                .and_then(|cipher| cipher.encrypt_padded_mut::<NoPadding>(data, length).ok())
+                .map(|_| ()),
+            _ => None,
+        };
+        result.ok_or_else(|| ConnectionError::Protocol("加密失敗".to_owned()))
+    }
+
+    /// 以 AES-CBC 解密，長度需為區塊大小的倍數
+    fn decrypt(&self, data: &mut [u8]) -> Result<(), ConnectionError> {
+        let result = match self.encrypting.len() {
+            16 => cbc::Decryptor::<aes::Aes128>::new_from_slices(&self.encrypting, &self.iv)
+                .ok()
+                .and_then(|cipher| cipher.decrypt_padded_mut::<NoPadding>(data).ok())
+                .map(|_| ()),
+            32 => cbc::Decryptor::<aes::Aes256>::new_from_slices(&self.encrypting, &self.iv)
+                .ok()
+                .and_then(|cipher| cipher.decrypt_padded_mut::<NoPadding>(data).ok())
+                .map(|_| ()),
+            _ => None,
+        };
+        result.ok_or_else(|| ConnectionError::Protocol("解密失敗".to_owned()))
+    }
+}
@@ -0,0 +1,731 @@
+//! 持久化儲存
+//!
+//! 點位最後狀態（參見 [`StateStore::export_state()`]）、最後已知數值（參見 [`ValueCache`]）、統計數據（參見 [`ConnectionStats::export_state()`]）
+//! 與待轉送的項目需要在重新啟動後延續時，主程式可以透過 [`StateBackend`] trait 存放，本 crate 不綁定特定的資料庫，嵌入的程式可以自行實作（如既有的 `RocksDB` 資料庫）：
+//!
+//! - [`MemoryBackend`]：存放於記憶體中，適用於測試或不需要跨程序保存的情境
+//! - [`FileBackend`]：以附加寫入的紀錄檔存放於單一檔案，開啟時重播紀錄，以 [`StateBackend::compact()`] 清除被覆寫的舊紀錄
+//! - `SqliteBackend`：存放於 `SQLite` 資料庫（需啟用 `sqlite` feature）
+//!
+//! 鍵值以 `/` 分隔命名空間（如 `stats/COM1`），[`StateBackend::scan()`] 依字典順序列出指定前綴的所有鍵值
+//!
+//! [`StateStore::save_to()`] 、[`ValueCache::save_to()`] 、[`ConnectionStats::save_to()`] 與對應的 `restore_from()` 以 JSON 格式存放匯出資料，
+//! 上游無法連線時需要暫存的項目可以利用 [`ForwardQueue`] 依序存放，其他資料可以利用 [`save()`] 與 [`load()`] 存放
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{ConnectionStats, StateStore, cache::ValueCache, persistence::{FileBackend, StateBackend}, value::Quality};
+//! use serde_json::json;
+//!
+//! let path = std::env::temp_dir().join(format!("persistence-doctest-{}.log", std::process::id()));
+//! # let _ = std::fs::remove_file(&path);
+//! let backend = FileBackend::open(&path).unwrap();
+//!
+//! let store = StateStore::new();
+//! store.register("temperature", None);
+//! let _ = store.update("temperature", json!(21.5));
+//! store.save_to(&backend).unwrap();
+//!
+//! let values = ValueCache::new();
+//! values.update("pressure", json!(1.2), Quality::Good);
+//! values.save_to(&backend).unwrap();
+//!
+//! let mut statistics = ConnectionStats::new("COM1", None);
+//! statistics.insert_target(Some("1".to_owned())).record_success(12);
+//! statistics.save_to(&backend).unwrap();
+//! statistics.save_to(&backend).unwrap();
+//! assert_eq!(backend.stale_records(), 1);
+//! backend.compact().unwrap();
+//! drop(backend);
+//!
+//! // 重新啟動後
+//! let backend = FileBackend::open(&path).unwrap();
+//! let restored = StateStore::new();
+//! assert_eq!(restored.restore_from(&backend).unwrap(), 1);
+//! assert_eq!(restored.get("temperature").unwrap().value, Some(json!(21.5)));
+//!
+//! let values = ValueCache::new();
+//! assert_eq!(values.restore_from(&backend).unwrap(), 1);
+//! assert_eq!(values.get("pressure").unwrap().value, json!(1.2));
+//!
+//! let mut restored = ConnectionStats::new("COM1", None);
+//! assert!(restored.restore_from(&backend).unwrap());
+//! assert_eq!(restored.snapshot().summary.total_polling_count, 1);
+//! assert_eq!(backend.scan("stats/").unwrap().len(), 1);
+//! # std::fs::remove_file(&path).unwrap();
+//! ```
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    error::Error,
+    fmt::Display,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError, RwLock},
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::{
+    ConnectionStats, StateStore,
+    cache::ValueCache,
+    state::{StateExport, UnsupportedExportVersion},
+    value::Quality,
+};
+
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteBackend;
+
+/// [`StateStore::save_to()`] 使用的鍵值
+pub const STATE_KEY: &str = "state";
+
+/// [`ValueCache::save_to()`] 使用的鍵值前綴
+pub const VALUES_PREFIX: &str = "values/";
+
+/// [`ConnectionStats::save_to()`] 使用的鍵值
+#[must_use]
+pub fn stats_key(port_target: &str) -> String {
+    format!("stats/{port_target}")
+}
+
+/// [`ForwardQueue`] 使用的鍵值前綴
+#[must_use]
+pub fn forward_prefix(queue: &str) -> String {
+    format!("forward/{queue}/")
+}
+
+/// 持久化儲存後端
+///
+/// 所有 method 都是同步的，後端可能阻塞（如寫入磁碟）時，主程式應在 [`tokio::task::spawn_blocking()`] 中調用
+pub trait StateBackend: Send + Sync {
+    /// 讀取鍵值，不存在時為 [`None`]
+    ///
+    /// # Errors
+    /// 後端讀取失敗時回傳 [`BackendError`]
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, BackendError>;
+
+    /// 寫入鍵值，已存在時會以新的內容取代
+    ///
+    /// # Errors
+    /// 後端寫入失敗時回傳 [`BackendError`]
+    fn put(&self, key: &str, value: &[u8]) -> Result<(), BackendError>;
+
+    /// 刪除鍵值，不存在時不做任何事
+    ///
+    /// # Errors
+    /// 後端寫入失敗時回傳 [`BackendError`]
+    fn delete(&self, key: &str) -> Result<(), BackendError>;
+
+    /// 依字典順序列出指定前綴的所有鍵值與內容
+    ///
+    /// # Errors
+    /// 後端讀取失敗時回傳 [`BackendError`]
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, BackendError>;
+
+    /// 整理儲存空間，如清除被覆寫或刪除的舊資料，預設實作不做任何事
+    ///
+    /// # Errors
+    /// 後端整理失敗時回傳 [`BackendError`]
+    fn compact(&self) -> Result<(), BackendError> {
+        Ok(())
+    }
+}
+
+/// 持久化儲存錯誤
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BackendError {
+    /// 讀寫檔案失敗
+    Io(std::io::Error),
+    /// 儲存的資料損毀
+    Corrupted {
+        /// 損毀的位置，如檔案路徑與行號
+        location: String,
+        /// 原因
+        reason: String,
+    },
+    /// 資料無法序列化或反序列化
+    Encoding(serde_json::Error),
+    /// 匯出資料的版本較新
+    Version(UnsupportedExportVersion),
+    /// 其他錯誤，供自行實作的後端使用
+    Other(Box<dyn Error + Send + Sync>),
+}
+
+impl Display for BackendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "持久化儲存讀寫失敗：{error}"),
+            Self::Corrupted { location, reason } => {
+                write!(f, "持久化儲存的資料損毀（{location}）：{reason}")
+            }
+            Self::Encoding(error) => write!(f, "持久化儲存的資料無法轉換：{error}"),
+            Self::Version(error) => write!(f, "{error}"),
+            Self::Other(error) => write!(f, "持久化儲存失敗：{error}"),
+        }
+    }
+}
+
+impl Error for BackendError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Encoding(error) => Some(error),
+            Self::Version(error) => Some(error),
+            Self::Other(error) => Some(error.as_ref()),
+            Self::Corrupted { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for BackendError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for BackendError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Encoding(error)
+    }
+}
+
+impl From<UnsupportedExportVersion> for BackendError {
+    fn from(error: UnsupportedExportVersion) -> Self {
+        Self::Version(error)
+    }
+}
+
+/// 以 JSON 格式寫入鍵值
+///
+/// # Errors
+/// 序列化或後端寫入失敗時回傳 [`BackendError`]
+pub fn save<T: Serialize + ?Sized>(
+    backend: &dyn StateBackend,
+    key: &str,
+    value: &T,
+) -> Result<(), BackendError> {
+    backend.put(key, &serde_json::to_vec(value)?)
+}
+
+/// 讀取以 [`save()`] 寫入的鍵值，不存在時為 [`None`]
+///
+/// # Errors
+/// 後端讀取或反序列化失敗時回傳 [`BackendError`]
+pub fn load<T: DeserializeOwned>(
+    backend: &dyn StateBackend,
+    key: &str,
+) -> Result<Option<T>, BackendError> {
+    backend
+        .get(key)?
+        .map(|bytes| serde_json::from_slice(&bytes))
+        .transpose()
+        .map_err(BackendError::from)
+}
+
+impl StateStore {
+    /// 將 [`StateStore::export_state()`] 的結果寫入 [`STATE_KEY`]
+    ///
+    /// # Errors
+    /// 序列化或後端寫入失敗時回傳 [`BackendError`]
+    pub fn save_to(&self, backend: &dyn StateBackend) -> Result<(), BackendError> {
+        save(backend, STATE_KEY, &self.export_state())
+    }
+
+    /// 讀取 [`StateStore::save_to()`] 寫入的資料並匯入，參見 [`StateStore::import_state()`]
+    ///
+    /// # 回傳值
+    /// 匯入的點位數量，後端沒有資料時為 0
+    ///
+    /// # Errors
+    /// 後端讀取、反序列化失敗或版本較新時回傳 [`BackendError`]
+    pub fn restore_from(&self, backend: &dyn StateBackend) -> Result<usize, BackendError> {
+        load::<StateExport>(backend, STATE_KEY)?
+            .map_or(Ok(0), |export| Ok(self.import_state(export)?))
+    }
+}
+
+/// [`ValueCache::save_to()`] 存放的單一點位數值
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredValue {
+    value: Value,
+    last_updated: SystemTime,
+    quality: Quality,
+}
+
+impl ValueCache {
+    /// 將所有點位的最後已知數值寫入 [`VALUES_PREFIX`] 之下，每個點位一個鍵值，已移除的點位會一併刪除
+    ///
+    /// # Errors
+    /// 序列化或後端讀寫失敗時回傳 [`BackendError`]
+    pub fn save_to(&self, backend: &dyn StateBackend) -> Result<(), BackendError> {
+        let mut saved = HashSet::new();
+        for (name, cached) in self.cached() {
+            let key = format!("{VALUES_PREFIX}{name}");
+            save(
+                backend,
+                &key,
+                &StoredValue {
+                    value: cached.value,
+                    last_updated: cached.last_updated,
+                    quality: cached.quality,
+                },
+            )?;
+            saved.insert(key);
+        }
+        for (key, _) in backend.scan(VALUES_PREFIX)? {
+            if !saved.contains(&key) {
+                backend.delete(&key)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 讀取 [`ValueCache::save_to()`] 寫入的數值並匯入，已有數值的點位不會被取代
+    ///
+    /// 匯入的數值沿用原本的更新時間，停機期間超過有效期限的數值會被視為已過期
+    ///
+    /// # 回傳值
+    /// 匯入的點位數量
+    ///
+    /// # Errors
+    /// 後端讀取或反序列化失敗時回傳 [`BackendError`]
+    pub fn restore_from(&self, backend: &dyn StateBackend) -> Result<usize, BackendError> {
+        let mut restored = 0;
+        for (key, bytes) in backend.scan(VALUES_PREFIX)? {
+            let stored: StoredValue = serde_json::from_slice(&bytes)?;
+            let name = key[VALUES_PREFIX.len()..].to_owned();
+            if self.restore(name, stored.value, stored.quality, stored.last_updated) {
+                restored += 1;
+            }
+        }
+        Ok(restored)
+    }
+}
+
+impl ConnectionStats {
+    /// 將 [`ConnectionStats::export_state()`] 的結果寫入 [`stats_key()`]
+    ///
+    /// # Errors
+    /// 序列化或後端寫入失敗時回傳 [`BackendError`]
+    pub fn save_to(&self, backend: &dyn StateBackend) -> Result<(), BackendError> {
+        save(backend, &stats_key(&self.port_target), &self.export_state())
+    }
+
+    /// 讀取 [`ConnectionStats::save_to()`] 寫入的資料並匯入，參見 [`ConnectionStats::import_state()`]
+    ///
+    /// # 回傳值
+    /// 後端是否有本連線的資料
+    ///
+    /// # Errors
+    /// 後端讀取、反序列化失敗或版本較新時回傳 [`BackendError`]
+    pub fn restore_from(&mut self, backend: &dyn StateBackend) -> Result<bool, BackendError> {
+        let Some(export) = load(backend, &stats_key(&self.port_target))? else {
+            return Ok(false);
+        };
+        self.import_state(export)?;
+        Ok(true)
+    }
+}
+
+/// 持久化的轉送佇列（store-and-forward）
+///
+/// 上游（如 MQTT broker 、歷史資料庫）無法連線時，主程式以 [`ForwardQueue::push()`] 將待轉送的項目寫入後端，
+/// 恢復連線後以 [`ForwardQueue::peek()`] 依序取出並轉送，轉送成功後以 [`ForwardQueue::ack()`] 刪除；
+/// 程序重新啟動後以 [`ForwardQueue::open()`] 開啟同名的佇列，尚未確認的項目會保留
+///
+/// 項目以 JSON 格式存放於 [`forward_prefix()`] 之下，鍵值結尾為補零的序號，依字典順序即為加入順序
+///
+/// # 範例
+/// ```rust
+/// use std::sync::Arc;
+/// use device_state_exchange_lib::persistence::{ForwardQueue, MemoryBackend};
+/// use serde_json::{Value, json};
+///
+/// let backend = Arc::new(MemoryBackend::new());
+/// let queue = ForwardQueue::<Value>::open(backend.clone(), "mqtt").unwrap();
+/// queue.push(&json!({ "temperature": 21.5 })).unwrap();
+/// queue.push(&json!({ "temperature": 21.7 })).unwrap();
+/// drop(queue);
+///
+/// // 重新啟動後
+/// let queue = ForwardQueue::<Value>::open(backend, "mqtt").unwrap();
+/// let pending = queue.peek(10).unwrap();
+/// assert_eq!(pending.len(), 2);
+/// assert_eq!(pending[0].1, json!({ "temperature": 21.5 }));
+///
+/// // 轉送成功後確認
+/// assert_eq!(queue.ack(pending[0].0).unwrap(), 1);
+/// assert_eq!(queue.len().unwrap(), 1);
+/// assert!(queue.push(&json!({ "temperature": 21.9 })).unwrap() > pending[1].0);
+/// ```
+pub struct ForwardQueue<T> {
+    backend: Arc<dyn StateBackend>,
+    prefix: String,
+    next: Mutex<u64>,
+    item: PhantomData<fn(T) -> T>,
+}
+
+impl<T> std::fmt::Debug for ForwardQueue<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ForwardQueue")
+            .field("prefix", &self.prefix)
+            .field("next", &self.next)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> ForwardQueue<T> {
+    /// 開啟轉送佇列，後端中已有同名佇列的項目時，新項目會接在其後
+    ///
+    /// # 參數
+    /// - `backend`：儲存後端
+    /// - `name`：佇列名稱，如輸出端名稱
+    ///
+    /// # Errors
+    /// 後端讀取失敗或鍵值格式錯誤時回傳 [`BackendError`]
+    pub fn open(backend: Arc<dyn StateBackend>, name: &str) -> Result<Self, BackendError> {
+        let prefix = forward_prefix(name);
+        let next = match backend.scan(&prefix)?.last() {
+            Some((key, _)) => sequence(&prefix, key)? + 1,
+            None => 0,
+        };
+        Ok(Self {
+            backend,
+            prefix,
+            next: Mutex::new(next),
+            item: PhantomData,
+        })
+    }
+
+    /// 加入項目
+    ///
+    /// # 回傳值
+    /// 項目的序號，用於 [`ForwardQueue::ack()`]
+    ///
+    /// # Errors
+    /// 序列化或後端寫入失敗時回傳 [`BackendError`]
+    pub fn push(&self, item: &T) -> Result<u64, BackendError> {
+        let mut next = self.next.lock().unwrap_or_else(PoisonError::into_inner);
+        let sequence = *next;
+        save(self.backend.as_ref(), &self.key(sequence), item)?;
+        *next += 1;
+        drop(next);
+        Ok(sequence)
+    }
+
+    /// 依加入順序取得最多 `limit` 個尚未確認的項目與序號，不會將項目移出佇列
+    ///
+    /// # Errors
+    /// 後端讀取、反序列化失敗或鍵值格式錯誤時回傳 [`BackendError`]
+    pub fn peek(&self, limit: usize) -> Result<Vec<(u64, T)>, BackendError> {
+        self.backend
+            .scan(&self.prefix)?
+            .into_iter()
+            .take(limit)
+            .map(|(key, bytes)| {
+                Ok((
+                    sequence(&self.prefix, &key)?,
+                    serde_json::from_slice(&bytes)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// 確認序號 `sequence`（含）以前的項目已轉送，並自後端刪除
+    ///
+    /// # 回傳值
+    /// 刪除的項目數量
+    ///
+    /// # Errors
+    /// 後端讀寫失敗或鍵值格式錯誤時回傳 [`BackendError`]
+    pub fn ack(&self, sequence: u64) -> Result<usize, BackendError> {
+        let mut deleted = 0;
+        for (key, _) in self.backend.scan(&self.prefix)? {
+            if self::sequence(&self.prefix, &key)? > sequence {
+                break;
+            }
+            self.backend.delete(&key)?;
+            deleted += 1;
+        }
+        Ok(deleted)
+    }
+
+    /// 尚未確認的項目數量
+    ///
+    /// # Errors
+    /// 後端讀取失敗時回傳 [`BackendError`]
+    pub fn len(&self) -> Result<usize, BackendError> {
+        Ok(self.backend.scan(&self.prefix)?.len())
+    }
+
+    /// 是否沒有尚未確認的項目
+    ///
+    /// # Errors
+    /// 後端讀取失敗時回傳 [`BackendError`]
+    pub fn is_empty(&self) -> Result<bool, BackendError> {
+        Ok(self.len()? == 0)
+    }
+
+    fn key(&self, sequence: u64) -> String {
+        format!("{}{sequence:020}", self.prefix)
+    }
+}
+
+/// 轉送佇列鍵值中的序號
+fn sequence(prefix: &str, key: &str) -> Result<u64, BackendError> {
+    key.strip_prefix(prefix)
+        .and_then(|sequence| sequence.parse().ok())
+        .ok_or_else(|| BackendError::Corrupted {
+            location: key.to_owned(),
+            reason: "轉送佇列的序號格式錯誤".to_owned(),
+        })
+}
+
+/// 記憶體儲存後端
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份資料
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBackend(Arc<RwLock<BTreeMap<String, Vec<u8>>>>);
+
+impl MemoryBackend {
+    /// 建立空的記憶體儲存後端
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StateBackend for MemoryBackend {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, BackendError> {
+        Ok(self
+            .0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+            .cloned())
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> Result<(), BackendError> {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key.to_owned(), value.to_vec());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), BackendError> {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(key);
+        Ok(())
+    }
+
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, BackendError> {
+        Ok(scan(
+            &self.0.read().unwrap_or_else(PoisonError::into_inner),
+            prefix,
+        ))
+    }
+}
+
+fn scan(entries: &BTreeMap<String, Vec<u8>>, prefix: &str) -> Vec<(String, Vec<u8>)> {
+    entries
+        .range(prefix.to_owned()..)
+        .take_while(|(key, _)| key.starts_with(prefix))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// 紀錄檔中的一筆紀錄，`value` 為 [`None`] 時代表刪除
+#[derive(Debug, Serialize, Deserialize)]
+struct LogRecord<'a> {
+    key: std::borrow::Cow<'a, str>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+}
+
+#[derive(Debug)]
+struct LogFile {
+    file: File,
+    entries: BTreeMap<String, Vec<u8>>,
+    records: usize,
+}
+
+/// 檔案儲存後端
+///
+/// 每次寫入或刪除都會在檔案結尾附加一行 JSON 紀錄並同步至磁碟，開啟時依序重播所有紀錄，讀取則直接使用記憶體中的資料；
+/// 寫入中途斷電造成最後一行不完整時，開啟時會略過該行
+///
+/// 同一個鍵值多次寫入會留下舊紀錄，請依 [`FileBackend::stale_records()`] 定期調用 [`StateBackend::compact()`] 改寫檔案
+#[derive(Debug)]
+pub struct FileBackend {
+    path: PathBuf,
+    log: Mutex<LogFile>,
+}
+
+impl FileBackend {
+    /// 開啟檔案儲存後端，檔案不存在時會建立
+    ///
+    /// # Errors
+    /// 檔案無法讀寫時回傳 [`BackendError::Io`] ，檔案中間的紀錄損毀時回傳 [`BackendError::Corrupted`]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, BackendError> {
+        let path = path.as_ref().to_path_buf();
+        let mut entries = BTreeMap::new();
+        let mut records = 0;
+
+        if path.exists() {
+            let lines = BufReader::new(File::open(&path)?)
+                .lines()
+                .collect::<Result<Vec<_>, _>>()?;
+            let last = lines.len().saturating_sub(1);
+
+            for (index, line) in lines.iter().enumerate() {
+                let record = match serde_json::from_str::<LogRecord<'_>>(line) {
+                    Ok(record) => record,
+                    // 寫入中途中斷的最後一行
+                    Err(_) if index == last => break,
+                    Err(error) => {
+                        return Err(BackendError::Corrupted {
+                            location: format!("{}:{}", path.display(), index + 1),
+                            reason: error.to_string(),
+                        });
+                    }
+                };
+                replay(&mut entries, record).map_err(|reason| BackendError::Corrupted {
+                    location: format!("{}:{}", path.display(), index + 1),
+                    reason,
+                })?;
+                records += 1;
+            }
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+
+        Ok(Self {
+            path,
+            log: Mutex::new(LogFile {
+                file,
+                entries,
+                records,
+            }),
+        })
+    }
+
+    /// 檔案路徑
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 檔案中已被覆寫或刪除的紀錄數量
+    #[must_use]
+    pub fn stale_records(&self) -> usize {
+        let log = self.log.lock().unwrap_or_else(PoisonError::into_inner);
+        log.records - log.entries.len()
+    }
+
+    fn append(&self, key: &str, value: Option<&[u8]>) -> Result<(), BackendError> {
+        append(
+            &mut self.log.lock().unwrap_or_else(PoisonError::into_inner),
+            key,
+            value,
+        )
+    }
+}
+
+fn replay(entries: &mut BTreeMap<String, Vec<u8>>, record: LogRecord<'_>) -> Result<(), String> {
+    match record.value {
+        Some(value) => {
+            let value = crate::base64::decode(&value).ok_or("base64 格式錯誤")?;
+            entries.insert(record.key.into_owned(), value);
+        }
+        None => {
+            entries.remove(record.key.as_ref());
+        }
+    }
+    Ok(())
+}
+
+fn append(log: &mut LogFile, key: &str, value: Option<&[u8]>) -> Result<(), BackendError> {
+    let mut line = serde_json::to_vec(&LogRecord {
+        key: key.into(),
+        value: value.map(crate::base64::encode),
+    })?;
+    line.push(b'\n');
+    log.file.write_all(&line)?;
+    log.file.sync_data()?;
+
+    match value {
+        Some(value) => log.entries.insert(key.to_owned(), value.to_vec()),
+        None => log.entries.remove(key),
+    };
+    log.records += 1;
+    Ok(())
+}
+
+fn rewrite(path: &Path, log: &mut LogFile) -> Result<(), BackendError> {
+    let temporary = path.with_extension("compact");
+    let mut file = File::create(&temporary)?;
+    for (key, value) in &log.entries {
+        let mut line = serde_json::to_vec(&LogRecord {
+            key: key.as_str().into(),
+            value: Some(crate::base64::encode(value)),
+        })?;
+        line.push(b'\n');
+        file.write_all(&line)?;
+    }
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&temporary, path)?;
+    log.file = OpenOptions::new().append(true).open(path)?;
+    log.records = log.entries.len();
+    Ok(())
+}
+
+impl StateBackend for FileBackend {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, BackendError> {
+        Ok(self
+            .log
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entries
+            .get(key)
+            .cloned())
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> Result<(), BackendError> {
+        self.append(key, Some(value))
+    }
+
+    fn delete(&self, key: &str) -> Result<(), BackendError> {
+        self.append(key, None)
+    }
+
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, BackendError> {
+        Ok(scan(
+            &self
+                .log
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entries,
+            prefix,
+        ))
+    }
+
+    fn compact(&self) -> Result<(), BackendError> {
+        rewrite(
+            &self.path,
+            &mut self.log.lock().unwrap_or_else(PoisonError::into_inner),
+        )
+    }
+}
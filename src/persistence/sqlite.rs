@@ -0,0 +1,118 @@
+use std::{
+    path::Path,
+    sync::{Mutex, PoisonError},
+};
+
+use rusqlite::{Connection, OptionalExtension, params};
+
+use super::{BackendError, StateBackend};
+
+/// `SQLite` 儲存後端（需啟用 `sqlite` feature）
+///
+/// 鍵值存放於資料庫中的 `entries` 資料表，每次寫入或刪除都是一筆獨立的交易；
+/// 資料庫可以與主程式的其他資料表共用，[`StateBackend::compact()`] 會以 `VACUUM` 整理整個資料庫
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::persistence::{SqliteBackend, StateBackend};
+///
+/// let backend = SqliteBackend::open_in_memory().unwrap();
+/// backend.put("stats/COM1", b"{}").unwrap();
+/// backend.put("stats/COM2", b"{}").unwrap();
+/// backend.put("state", b"{}").unwrap();
+/// backend.delete("stats/COM1").unwrap();
+///
+/// let keys: Vec<_> = backend.scan("stats/").unwrap().into_iter().map(|(key, _)| key).collect();
+/// assert_eq!(keys, ["stats/COM2"]);
+/// ```
+#[derive(Debug)]
+pub struct SqliteBackend(Mutex<Connection>);
+
+impl SqliteBackend {
+    /// 開啟資料庫檔案，檔案或資料表不存在時會建立
+    ///
+    /// # Errors
+    /// 檔案無法開啟或不是 `SQLite` 資料庫時回傳 [`BackendError::Other`]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, BackendError> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// 開啟記憶體中的資料庫，適用於測試
+    ///
+    /// # Errors
+    /// 資料庫無法建立時回傳 [`BackendError::Other`]
+    pub fn open_in_memory() -> Result<Self, BackendError> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    /// 使用已開啟的資料庫連線，資料表不存在時會建立
+    ///
+    /// # Errors
+    /// 資料表無法建立時回傳 [`BackendError::Other`]
+    pub fn with_connection(connection: Connection) -> Result<Self, BackendError> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS entries (key TEXT PRIMARY KEY, value BLOB NOT NULL) WITHOUT ROWID",
+        )?;
+        Ok(Self(Mutex::new(connection)))
+    }
+
+    fn connection(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl StateBackend for SqliteBackend {
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, BackendError> {
+        Ok(self
+            .connection()
+            .query_row("SELECT value FROM entries WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    }
+
+    fn put(&self, key: &str, value: &[u8]) -> Result<(), BackendError> {
+        self.connection().execute(
+            "INSERT INTO entries (key, value) VALUES (?1, ?2) ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> Result<(), BackendError> {
+        self.connection()
+            .execute("DELETE FROM entries WHERE key = ?1", [key])?;
+        Ok(())
+    }
+
+    fn scan(&self, prefix: &str) -> Result<Vec<(String, Vec<u8>)>, BackendError> {
+        Ok(scan(&self.connection(), prefix)?)
+    }
+
+    fn compact(&self) -> Result<(), BackendError> {
+        self.connection().execute_batch("VACUUM")?;
+        Ok(())
+    }
+}
+
+fn scan(connection: &Connection, prefix: &str) -> rusqlite::Result<Vec<(String, Vec<u8>)>> {
+    // 以二進位比較排序，與 `String` 的字典順序相同
+    let mut statement =
+        connection.prepare_cached("SELECT key, value FROM entries WHERE key >= ?1 ORDER BY key")?;
+    let mut rows = statement.query([prefix])?;
+    let mut entries = Vec::new();
+    while let Some(row) = rows.next()? {
+        let key: String = row.get(0)?;
+        if !key.starts_with(prefix) {
+            break;
+        }
+        entries.push((key, row.get(1)?));
+    }
+    Ok(entries)
+}
+
+impl From<rusqlite::Error> for BackendError {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::Other(Box::new(error))
+    }
+}
@@ -0,0 +1,177 @@
+//! Prometheus 指標輸出
+//!
+//! 以 Prometheus 文字格式輸出統計數據，主程式只需要將 [`ConnectionStats::encode_prometheus()`] 或
+//! [`ConnectionStatsRegistry::encode_prometheus()`] 的結果以 `text/plain; version=0.0.4` 回應 `/metrics` 請求，即可讓 Prometheus 直接抓取閘道器的統計數據
+//!
+//! 輸出的指標：
+//!
+//! - `dsx_poll_total`（counter）：總輪詢次數
+//! - `dsx_poll_failed_total`（counter）：失敗的輪詢次數
+//! - `dsx_avg_response_ms`（gauge）：平均回覆毫秒數
+//!
+//! 每個點位統計數據（參見 [`ConnectionStats::insert_target()`]）輸出一組指標，標籤包含：
+//!
+//! - `port`：[`ConnectionStats::port_target`]
+//! - `address`：設備編號，參見 [`crate::TargetAddressNumber`] ，未設定時為空字串
+//! - 連線標籤與點位標籤（參見 [`crate::TargetStats::set_label()`]），相同名稱時以點位標籤為準；
+//!   點位統計數據以設備為單位，需要以點位名稱區分時，請以 `target` 標籤設定點位名稱
+//!
+//! 標籤名稱中不符合 Prometheus 規則的字元會被取代為 `_`
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::ConnectionStats;
+//!
+//! let mut statistics = ConnectionStats::new("COM1", None);
+//! let meter = statistics.insert_target(Some("1".to_owned()));
+//! meter.set_label("target", "電壓");
+//! meter.record_success(12);
+//! meter.record_failure();
+//!
+//! let metrics = statistics.encode_prometheus();
+//! assert!(metrics.contains("# TYPE dsx_poll_total counter\n"));
+//! assert!(metrics.contains(r#"dsx_poll_total{port="COM1",address="1",target="電壓"} 2"#));
+//! assert!(metrics.contains(r#"dsx_poll_failed_total{port="COM1",address="1",target="電壓"} 1"#));
+//! ```
+
+use std::{fmt::Write, sync::PoisonError};
+
+use crate::{ConnectionStats, ConnectionStatsRegistry, Labels, StatisticsSnapshot};
+
+/// 指標定義
+struct Metric {
+    name: &'static str,
+    kind: &'static str,
+    help: &'static str,
+    value: fn(&StatisticsSnapshot) -> i64,
+}
+
+const METRICS: [Metric; 3] = [
+    Metric {
+        name: "dsx_poll_total",
+        kind: "counter",
+        help: "總輪詢次數",
+        value: |statistics| statistics.total_polling_count,
+    },
+    Metric {
+        name: "dsx_poll_failed_total",
+        kind: "counter",
+        help: "失敗的輪詢次數",
+        value: |statistics| statistics.failed_poll_count,
+    },
+    Metric {
+        name: "dsx_avg_response_ms",
+        kind: "gauge",
+        help: "平均回覆毫秒數",
+        value: |statistics| statistics.average_response_ms,
+    },
+];
+
+impl ConnectionStats {
+    /// 以 Prometheus 文字格式輸出統計數據，參見 [`crate::prometheus`]
+    #[must_use]
+    pub fn encode_prometheus(&self) -> String {
+        encode(&samples(self, None))
+    }
+}
+
+impl ConnectionStatsRegistry {
+    /// 以 Prometheus 文字格式輸出所有連線的統計數據，參見 [`crate::prometheus`]
+    ///
+    /// 除了 [`ConnectionStats::encode_prometheus()`] 的標籤外，另以 `connection` 標籤區分連線識別名稱
+    #[must_use]
+    pub fn encode_prometheus(&self) -> String {
+        let mut connections: Vec<(String, ConnectionStats)> = self
+            .connections
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(id, statistics)| (id.clone(), statistics.clone()))
+            .collect();
+        connections.sort_unstable_by(|(first, _), (second, _)| first.cmp(second));
+
+        let samples = connections
+            .iter()
+            .flat_map(|(id, statistics)| samples(statistics, Some(id)))
+            .collect::<Vec<_>>();
+        encode(&samples)
+    }
+}
+
+/// 依設備編號排序的標籤與統計數據
+fn samples(
+    statistics: &ConnectionStats,
+    connection: Option<&str>,
+) -> Vec<(String, StatisticsSnapshot)> {
+    let mut targets: Vec<_> = statistics.targets().collect();
+    targets.sort_unstable_by_key(|(address_number, _)| *address_number);
+
+    targets
+        .into_iter()
+        .map(|(address_number, target)| {
+            let mut labels = vec![
+                ("port".to_owned(), statistics.port_target.clone()),
+                (
+                    "address".to_owned(),
+                    address_number.clone().unwrap_or_default(),
+                ),
+            ];
+            if let Some(connection) = connection {
+                labels.insert(0, ("connection".to_owned(), connection.to_owned()));
+            }
+
+            let mut extra: Labels = statistics.labels.clone();
+            extra.extend(target.labels());
+            labels.extend(
+                extra
+                    .into_iter()
+                    .map(|(key, value)| (sanitize(&key), value))
+                    .filter(|(key, _)| !matches!(key.as_str(), "port" | "address" | "connection")),
+            );
+
+            let labels = labels
+                .iter()
+                .map(|(key, value)| format!("{key}=\"{}\"", escape(value)))
+                .collect::<Vec<_>>()
+                .join(",");
+            (labels, target.snapshot())
+        })
+        .collect()
+}
+
+fn encode(samples: &[(String, StatisticsSnapshot)]) -> String {
+    let mut output = String::new();
+    for Metric {
+        name,
+        kind,
+        help,
+        value,
+    } in METRICS
+    {
+        let _ = writeln!(output, "# HELP {name} {help}");
+        let _ = writeln!(output, "# TYPE {name} {kind}");
+        for (labels, statistics) in samples {
+            let _ = writeln!(output, "{name}{{{labels}}} {}", value(statistics));
+        }
+    }
+    output
+}
+
+/// 將標籤名稱轉換為 `[a-zA-Z_][a-zA-Z0-9_]*`
+fn sanitize(key: &str) -> String {
+    let mut sanitized: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if !sanitized.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        sanitized.insert(0, '_');
+    }
+    sanitized
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,334 @@
+//! Protobuf 訊息（需啟用 `proto` feature）
+//!
+//! 訊息定義位於 `proto/device_state.proto` ，本模組提供由 [`prost`](https://crates.io/crates/prost) 產生的型別，
+//! 以及本 crate 型別轉換為訊息的實作，讓點位狀態可以直接串流至 gRPC 、 Kafka 等使用 protobuf 的服務
+//!
+//! 產生的程式碼已放入版本控制，使用者不需要安裝 `protoc` ，修改訊息定義後，請利用 `prost-build` 重新產生 `src/proto/device_state_exchange.v1.rs`
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{proto, state::StateStore};
+//! use prost::Message;
+//!
+//! let store = StateStore::new();
+//! store.register("temperature", None);
+//! let _ = store.update("temperature", serde_json::json!(21.5));
+//!
+//! let change = proto::StateChange::from_state("temperature", &store.get("temperature").unwrap());
+//! let decoded = proto::StateChange::decode(change.encode_to_vec().as_slice()).unwrap();
+//!
+//! assert_eq!(decoded.quality(), proto::Quality::Good);
+//! assert_eq!(serde_json::Value::from(decoded.value.unwrap()), serde_json::json!(21.5));
+//! ```
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde_json::{Map, Number, Value};
+
+use crate::{
+    ConnectionStatsSnapshot, StatisticsSnapshot,
+    budget::BudgetSnapshot,
+    concurrency::InFlightSnapshot,
+    event::{self, Event, EventKind},
+    queue::QueueSnapshot,
+    skip,
+    state::TargetState,
+    timing::StageSnapshot,
+    value,
+};
+
+#[allow(clippy::all, clippy::pedantic, clippy::nursery)]
+mod generated {
+    include!("proto/device_state_exchange.v1.rs");
+}
+
+pub use generated::*;
+
+impl From<&Value> for DeviceValue {
+    fn from(value: &Value) -> Self {
+        let kind = match value {
+            Value::Null => device_value::Kind::NullValue(true),
+            Value::Bool(value) => device_value::Kind::BoolValue(*value),
+            Value::Number(number) => number.as_i64().map_or_else(
+                || {
+                    number.as_u64().map_or_else(
+                        || device_value::Kind::DoubleValue(number.as_f64().unwrap_or(f64::NAN)),
+                        device_value::Kind::UintValue,
+                    )
+                },
+                device_value::Kind::IntValue,
+            ),
+            Value::String(value) => device_value::Kind::StringValue(value.clone()),
+            Value::Array(values) => device_value::Kind::ListValue(ListValue {
+                values: values.iter().map(Self::from).collect(),
+            }),
+            Value::Object(fields) => device_value::Kind::MapValue(MapValue {
+                fields: fields
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Self::from(value)))
+                    .collect(),
+            }),
+        };
+
+        Self { kind: Some(kind) }
+    }
+}
+
+/// 無法以 JSON 表示的數值（NaN 、無限大）及未設定的數值會轉換為 [`Value::Null`]
+impl From<DeviceValue> for Value {
+    fn from(value: DeviceValue) -> Self {
+        match value.kind {
+            None | Some(device_value::Kind::NullValue(_)) => Self::Null,
+            Some(device_value::Kind::BoolValue(value)) => Self::Bool(value),
+            Some(device_value::Kind::IntValue(value)) => Self::Number(value.into()),
+            Some(device_value::Kind::UintValue(value)) => Self::Number(value.into()),
+            Some(device_value::Kind::DoubleValue(value)) => {
+                Number::from_f64(value).map_or(Self::Null, Self::Number)
+            }
+            Some(device_value::Kind::StringValue(value)) => Self::String(value),
+            Some(device_value::Kind::ListValue(list)) => {
+                Self::Array(list.values.into_iter().map(Self::from).collect())
+            }
+            Some(device_value::Kind::MapValue(map)) => Self::Object(
+                map.fields
+                    .into_iter()
+                    .map(|(key, value)| (key, Self::from(value)))
+                    .collect::<Map<_, _>>(),
+            ),
+        }
+    }
+}
+
+impl From<value::Quality> for Quality {
+    fn from(quality: value::Quality) -> Self {
+        match quality {
+            value::Quality::Good => Self::Good,
+            value::Quality::Uncertain => Self::Uncertain,
+            value::Quality::Bad => Self::Bad,
+            value::Quality::Stale => Self::Stale,
+        }
+    }
+}
+
+impl From<event::HotplugChange> for HotplugChange {
+    fn from(change: event::HotplugChange) -> Self {
+        match change {
+            event::HotplugChange::Arrived => Self::Arrived,
+            event::HotplugChange::Removed => Self::Removed,
+        }
+    }
+}
+
+impl From<skip::SkipReason> for SkipReason {
+    fn from(reason: skip::SkipReason) -> Self {
+        match reason {
+            skip::SkipReason::IntervalMissed => Self::IntervalMissed,
+            skip::SkipReason::ClockAdjusted => Self::ClockAdjusted,
+            skip::SkipReason::Paused => Self::Paused,
+            skip::SkipReason::CircuitOpen => Self::CircuitOpen,
+        }
+    }
+}
+
+impl From<StatisticsSnapshot> for Statistics {
+    fn from(statistics: StatisticsSnapshot) -> Self {
+        Self {
+            failed_poll_count: statistics.failed_poll_count,
+            total_polling_count: statistics.total_polling_count,
+            average_response_ms: statistics.average_response_ms,
+            p50_response_ms: statistics.p50_response_ms,
+            p95_response_ms: statistics.p95_response_ms,
+            p99_response_ms: statistics.p99_response_ms,
+            max_response_ms: statistics.max_response_ms,
+        }
+    }
+}
+
+impl From<InFlightSnapshot> for InFlight {
+    fn from(in_flight: InFlightSnapshot) -> Self {
+        let to_u64 = |count: usize| u64::try_from(count).unwrap_or(u64::MAX);
+
+        Self {
+            max_in_flight: to_u64(in_flight.max_in_flight),
+            in_flight: to_u64(in_flight.in_flight),
+            queued: to_u64(in_flight.queued),
+            peak_queued: to_u64(in_flight.peak_queued),
+        }
+    }
+}
+
+impl From<QueueSnapshot> for QueueDepth {
+    fn from(queue: QueueSnapshot) -> Self {
+        let to_u64 = |count: usize| u64::try_from(count).unwrap_or(u64::MAX);
+
+        Self {
+            capacity: to_u64(queue.capacity),
+            depth: to_u64(queue.depth),
+            peak_depth: to_u64(queue.peak_depth),
+            dropped: queue.dropped,
+            coalesced: queue.coalesced,
+        }
+    }
+}
+
+impl From<BudgetSnapshot> for BudgetShare {
+    fn from(budget: BudgetSnapshot) -> Self {
+        Self {
+            weight: budget.weight,
+            requested: budget.requested,
+            realized: budget.realized,
+            total_requested: budget.total_requested,
+            total_realized: budget.total_realized,
+        }
+    }
+}
+
+impl From<StageSnapshot> for StageLatency {
+    fn from(stage: StageSnapshot) -> Self {
+        let to_micros =
+            |duration: Duration| u64::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+
+        Self {
+            count: stage.count,
+            average_us: to_micros(stage.average),
+            max_us: to_micros(stage.max),
+            share_permille: stage.share_permille,
+        }
+    }
+}
+
+impl StateChange {
+    /// 由點位狀態建立狀態變更訊息
+    ///
+    /// # 參數
+    /// - `target`：點位名稱
+    /// - `state`：點位狀態，參見 [`crate::state::StateStore::get()`]
+    #[must_use]
+    pub fn from_state(target: &str, state: &TargetState) -> Self {
+        Self {
+            target: target.to_owned(),
+            value: state.value.as_ref().map(DeviceValue::from),
+            quality: Quality::from(state.quality).into(),
+            updated_at_ms: state.updated_at.map(unix_millis),
+        }
+    }
+}
+
+impl StatsSnapshot {
+    /// 由連線統計數據快照建立訊息
+    ///
+    /// # 參數
+    /// - `connection`：連線識別名稱
+    /// - `snapshot`：連線統計數據快照，參見 [`crate::ConnectionStats::snapshot()`]
+    #[must_use]
+    pub fn from_snapshot(connection: &str, snapshot: &ConnectionStatsSnapshot) -> Self {
+        Self {
+            connection: connection.to_owned(),
+            port_target: snapshot.port_target.clone(),
+            port_note: snapshot.port_note.clone(),
+            remote_address: snapshot.remote_address.map(|address| address.to_string()),
+            clock_adjusted: snapshot.clock_adjusted,
+            summary: Some(snapshot.summary.into()),
+            targets: snapshot
+                .targets
+                .iter()
+                .map(|target| TargetStatistics {
+                    address_number: target.address_number.clone(),
+                    statistics: Some(target.statistics.into()),
+                    labels: target.labels.clone().into_iter().collect(),
+                })
+                .collect(),
+            labels: snapshot.labels.clone().into_iter().collect(),
+            buckets: snapshot
+                .buckets
+                .iter()
+                .map(|bucket| CounterBucket {
+                    start_ms: unix_millis(bucket.start),
+                    polls: bucket.polls,
+                    failures: bucket.failures,
+                    bytes: bucket.bytes,
+                })
+                .collect(),
+            in_flight: Some(snapshot.in_flight.into()),
+            queues: snapshot
+                .queues
+                .iter()
+                .map(|(stage, queue)| (stage.clone(), (*queue).into()))
+                .collect(),
+            budget: snapshot
+                .budget
+                .iter()
+                .map(|(key, budget)| (key.clone(), (*budget).into()))
+                .collect(),
+            stages: snapshot
+                .stages
+                .iter()
+                .map(|(stage, latency)| (stage.as_str().to_owned(), (*latency).into()))
+                .collect(),
+        }
+    }
+}
+
+impl From<&Event> for AlarmEvent {
+    fn from(event: &Event) -> Self {
+        let (connection, kind) = match &event.kind {
+            EventKind::Hotplug {
+                connection,
+                port,
+                change,
+            } => (
+                connection.clone(),
+                alarm_event::Kind::Hotplug(Hotplug {
+                    port: port.clone(),
+                    change: HotplugChange::from(*change).into(),
+                }),
+            ),
+            EventKind::ClockSkew {
+                connection,
+                drift_ms,
+            } => (
+                connection.clone(),
+                alarm_event::Kind::ClockSkew(ClockSkew {
+                    drift_ms: *drift_ms,
+                }),
+            ),
+            EventKind::TwinDrift {
+                connection,
+                target,
+                desired,
+                reported,
+            } => (
+                connection.clone(),
+                alarm_event::Kind::TwinDrift(TwinDrift {
+                    target: target.clone(),
+                    desired: Some(desired.into()),
+                    reported: Some(reported.into()),
+                }),
+            ),
+            EventKind::RequestSkipped {
+                connection,
+                target,
+                reason,
+            } => (
+                connection.clone(),
+                alarm_event::Kind::RequestSkipped(RequestSkipped {
+                    target: target.clone(),
+                    reason: SkipReason::from(*reason).into(),
+                }),
+            ),
+        };
+
+        Self {
+            timestamp_ms: unix_millis(event.timestamp),
+            connection,
+            kind: Some(kind),
+        }
+    }
+}
+
+fn unix_millis(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => i64::try_from(after.as_millis()).unwrap_or(i64::MAX),
+        Err(before) => -i64::try_from(before.duration().as_millis()).unwrap_or(i64::MAX),
+    }
+}
@@ -0,0 +1,331 @@
+// This file is @generated by prost-build.
+/// 設備數值，對應 serde_json::Value
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeviceValue {
+    #[prost(oneof = "device_value::Kind", tags = "1, 2, 3, 4, 5, 6, 7, 8")]
+    pub kind: ::core::option::Option<device_value::Kind>,
+}
+/// Nested message and enum types in `DeviceValue`.
+pub mod device_value {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Kind {
+        #[prost(bool, tag = "1")]
+        NullValue(bool),
+        #[prost(bool, tag = "2")]
+        BoolValue(bool),
+        #[prost(int64, tag = "3")]
+        IntValue(i64),
+        #[prost(uint64, tag = "4")]
+        UintValue(u64),
+        #[prost(double, tag = "5")]
+        DoubleValue(f64),
+        #[prost(string, tag = "6")]
+        StringValue(::prost::alloc::string::String),
+        #[prost(message, tag = "7")]
+        ListValue(super::ListValue),
+        #[prost(message, tag = "8")]
+        MapValue(super::MapValue),
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListValue {
+    #[prost(message, repeated, tag = "1")]
+    pub values: ::prost::alloc::vec::Vec<DeviceValue>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MapValue {
+    #[prost(map = "string, message", tag = "1")]
+    pub fields: ::std::collections::HashMap<::prost::alloc::string::String, DeviceValue>,
+}
+/// 點位狀態變更
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StateChange {
+    #[prost(string, tag = "1")]
+    pub target: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub value: ::core::option::Option<DeviceValue>,
+    #[prost(enumeration = "Quality", tag = "3")]
+    pub quality: i32,
+    /// UNIX 時間（毫秒），尚未更新過時不設定
+    #[prost(int64, optional, tag = "4")]
+    pub updated_at_ms: ::core::option::Option<i64>,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Statistics {
+    #[prost(int64, tag = "1")]
+    pub failed_poll_count: i64,
+    #[prost(int64, tag = "2")]
+    pub total_polling_count: i64,
+    #[prost(int64, tag = "3")]
+    pub average_response_ms: i64,
+    #[prost(int64, tag = "4")]
+    pub p50_response_ms: i64,
+    #[prost(int64, tag = "5")]
+    pub p95_response_ms: i64,
+    #[prost(int64, tag = "6")]
+    pub p99_response_ms: i64,
+    #[prost(int64, tag = "7")]
+    pub max_response_ms: i64,
+}
+#[derive(Clone, PartialEq, Eq, ::prost::Message)]
+pub struct TargetStatistics {
+    #[prost(string, optional, tag = "1")]
+    pub address_number: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "2")]
+    pub statistics: ::core::option::Option<Statistics>,
+    /// 點位標籤，不包含連線標籤
+    #[prost(map = "string, string", tag = "3")]
+    pub labels: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+}
+/// 時間區間計數
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct CounterBucket {
+    /// 區間開始的 UNIX 時間（毫秒）
+    #[prost(int64, tag = "1")]
+    pub start_ms: i64,
+    #[prost(uint64, tag = "2")]
+    pub polls: u64,
+    #[prost(uint64, tag = "3")]
+    pub failures: u64,
+    #[prost(uint64, tag = "4")]
+    pub bytes: u64,
+}
+/// 同時處理中請求數量
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct InFlight {
+    #[prost(uint64, tag = "1")]
+    pub max_in_flight: u64,
+    #[prost(uint64, tag = "2")]
+    pub in_flight: u64,
+    #[prost(uint64, tag = "3")]
+    pub queued: u64,
+    #[prost(uint64, tag = "4")]
+    pub peak_queued: u64,
+}
+/// 管線佇列深度
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct QueueDepth {
+    #[prost(uint64, tag = "1")]
+    pub capacity: u64,
+    #[prost(uint64, tag = "2")]
+    pub depth: u64,
+    #[prost(uint64, tag = "3")]
+    pub peak_depth: u64,
+    #[prost(uint64, tag = "4")]
+    pub dropped: u64,
+    #[prost(uint64, tag = "5")]
+    pub coalesced: u64,
+}
+/// 輪詢預算
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct BudgetShare {
+    #[prost(uint32, tag = "1")]
+    pub weight: u32,
+    #[prost(uint64, tag = "2")]
+    pub requested: u64,
+    #[prost(uint64, tag = "3")]
+    pub realized: u64,
+    #[prost(uint64, tag = "4")]
+    pub total_requested: u64,
+    #[prost(uint64, tag = "5")]
+    pub total_realized: u64,
+}
+/// 處理階段耗時
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct StageLatency {
+    #[prost(uint32, tag = "1")]
+    pub count: u32,
+    #[prost(uint64, tag = "2")]
+    pub average_us: u64,
+    #[prost(uint64, tag = "3")]
+    pub max_us: u64,
+    #[prost(uint32, tag = "4")]
+    pub share_permille: u32,
+}
+/// 連線統計數據快照
+#[derive(Clone, PartialEq, Eq, ::prost::Message)]
+pub struct StatsSnapshot {
+    #[prost(string, tag = "1")]
+    pub connection: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub port_target: ::prost::alloc::string::String,
+    #[prost(string, optional, tag = "3")]
+    pub port_note: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(string, optional, tag = "4")]
+    pub remote_address: ::core::option::Option<::prost::alloc::string::String>,
+    #[prost(uint64, tag = "5")]
+    pub clock_adjusted: u64,
+    #[prost(message, optional, tag = "6")]
+    pub summary: ::core::option::Option<Statistics>,
+    #[prost(message, repeated, tag = "7")]
+    pub targets: ::prost::alloc::vec::Vec<TargetStatistics>,
+    /// 連線標籤
+    #[prost(map = "string, string", tag = "8")]
+    pub labels: ::std::collections::HashMap<
+        ::prost::alloc::string::String,
+        ::prost::alloc::string::String,
+    >,
+    /// 時間區間計數，依時間由舊到新排列
+    #[prost(message, repeated, tag = "9")]
+    pub buckets: ::prost::alloc::vec::Vec<CounterBucket>,
+    /// 處理中與排隊中的請求數量
+    #[prost(message, optional, tag = "10")]
+    pub in_flight: ::core::option::Option<InFlight>,
+    /// 各管線佇列的深度，以管線階段名稱索引
+    #[prost(map = "string, message", tag = "11")]
+    pub queues: ::std::collections::HashMap<::prost::alloc::string::String, QueueDepth>,
+    /// 各點位與點位群組分配與完成的輪詢次數，以點位或點位群組名稱索引
+    #[prost(map = "string, message", tag = "12")]
+    pub budget: ::std::collections::HashMap<::prost::alloc::string::String, BudgetShare>,
+    /// 各處理階段的耗時，以處理階段名稱索引
+    #[prost(map = "string, message", tag = "13")]
+    pub stages: ::std::collections::HashMap<::prost::alloc::string::String, StageLatency>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Hotplug {
+    #[prost(string, tag = "1")]
+    pub port: ::prost::alloc::string::String,
+    #[prost(enumeration = "HotplugChange", tag = "2")]
+    pub change: i32,
+}
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct ClockSkew {
+    #[prost(int64, tag = "1")]
+    pub drift_ms: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TwinDrift {
+    #[prost(string, tag = "1")]
+    pub target: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub desired: ::core::option::Option<DeviceValue>,
+    #[prost(message, optional, tag = "3")]
+    pub reported: ::core::option::Option<DeviceValue>,
+}
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct RequestSkipped {
+    #[prost(string, tag = "1")]
+    pub target: ::prost::alloc::string::String,
+    #[prost(enumeration = "SkipReason", tag = "2")]
+    pub reason: i32,
+}
+/// 警示事件
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AlarmEvent {
+    /// UNIX 時間（毫秒）
+    #[prost(int64, tag = "1")]
+    pub timestamp_ms: i64,
+    #[prost(string, tag = "2")]
+    pub connection: ::prost::alloc::string::String,
+    #[prost(oneof = "alarm_event::Kind", tags = "3, 4, 5, 6")]
+    pub kind: ::core::option::Option<alarm_event::Kind>,
+}
+/// Nested message and enum types in `AlarmEvent`.
+pub mod alarm_event {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Kind {
+        #[prost(message, tag = "3")]
+        Hotplug(super::Hotplug),
+        #[prost(message, tag = "4")]
+        ClockSkew(super::ClockSkew),
+        #[prost(message, tag = "5")]
+        TwinDrift(super::TwinDrift),
+        #[prost(message, tag = "6")]
+        RequestSkipped(super::RequestSkipped),
+    }
+}
+/// 數值品質
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Quality {
+    Good = 0,
+    Uncertain = 1,
+    Bad = 2,
+    Stale = 3,
+}
+impl Quality {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Good => "QUALITY_GOOD",
+            Self::Uncertain => "QUALITY_UNCERTAIN",
+            Self::Bad => "QUALITY_BAD",
+            Self::Stale => "QUALITY_STALE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "QUALITY_GOOD" => Some(Self::Good),
+            "QUALITY_UNCERTAIN" => Some(Self::Uncertain),
+            "QUALITY_BAD" => Some(Self::Bad),
+            "QUALITY_STALE" => Some(Self::Stale),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum HotplugChange {
+    Arrived = 0,
+    Removed = 1,
+}
+impl HotplugChange {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Arrived => "HOTPLUG_CHANGE_ARRIVED",
+            Self::Removed => "HOTPLUG_CHANGE_REMOVED",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "HOTPLUG_CHANGE_ARRIVED" => Some(Self::Arrived),
+            "HOTPLUG_CHANGE_REMOVED" => Some(Self::Removed),
+            _ => None,
+        }
+    }
+}
+/// 略過請求的原因
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum SkipReason {
+    IntervalMissed = 0,
+    ClockAdjusted = 1,
+    Paused = 2,
+    CircuitOpen = 3,
+}
+impl SkipReason {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::IntervalMissed => "SKIP_REASON_INTERVAL_MISSED",
+            Self::ClockAdjusted => "SKIP_REASON_CLOCK_ADJUSTED",
+            Self::Paused => "SKIP_REASON_PAUSED",
+            Self::CircuitOpen => "SKIP_REASON_CIRCUIT_OPEN",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "SKIP_REASON_INTERVAL_MISSED" => Some(Self::IntervalMissed),
+            "SKIP_REASON_CLOCK_ADJUSTED" => Some(Self::ClockAdjusted),
+            "SKIP_REASON_PAUSED" => Some(Self::Paused),
+            "SKIP_REASON_CIRCUIT_OPEN" => Some(Self::CircuitOpen),
+            _ => None,
+        }
+    }
+}
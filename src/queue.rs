@@ -0,0 +1,444 @@
+//! 管線佇列
+//!
+//! 主程式在輪詢、後處理與發佈之間若使用無上限的 channel ，負載過高時佇列會持續成長直到記憶體耗盡；
+//! 各階段之間請改用 [`BoundedQueue`] ，佇列已滿時依 [`OverflowPolicy`] 處理：
+//!
+//! - [`OverflowPolicy::Block`]：等待下游取出，讓壓力往上游傳遞
+//! - [`OverflowPolicy::DropOldest`]：捨棄最舊的項目，適用於只在意最新數值的階段
+//! - [`OverflowPolicy::CoalescePerTarget`]：以同一個點位的新項目取代佇列中的舊項目，佇列中每個點位最多只有一筆
+//!
+//! 記憶體有限時，可以改用 [`BoundedQueue::with_quota()`] 讓佇列同時受 [`crate::memory::MemoryQuota`] 限制，項目大小以 [`QueueItem::estimated_size()`] 估計
+//!
+//! 利用 [`BoundedQueue::gauge()`] 取得的 [`QueueGauge`] 登記至 [`crate::ConnectionStats::queues`] 後，佇列深度會顯示於統計數據快照中
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{ConnectionStats, WriteCommand, queue::{BoundedQueue, OverflowPolicy}};
+//! use serde_json::json;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let publish = BoundedQueue::new(2, OverflowPolicy::CoalescePerTarget);
+//! let statistics = ConnectionStats::new("COM1", None);
+//! statistics.queues.register("publish", publish.gauge());
+//!
+//! for value in [1, 2, 3] {
+//!     publish.push(WriteCommand { target: "setpoint".to_owned(), value: json!(value), client: None, correlation: None }).await.unwrap();
+//! }
+//! publish.push(WriteCommand { target: "mode".to_owned(), value: json!("auto"), client: None, correlation: None }).await.unwrap();
+//!
+//! let snapshot = &statistics.snapshot().queues["publish"];
+//! assert_eq!(snapshot.depth, 2);
+//! assert_eq!(snapshot.coalesced, 2);
+//! assert_eq!(publish.pop().await.unwrap().value, json!(3));
+//! # }
+//! ```
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    error::Error,
+    fmt::Display,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use crate::{
+    Event, WriteCommand,
+    memory::{MemoryCharge, MemoryQuota},
+};
+
+/// 佇列已滿時的處理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// 等待下游取出
+    #[default]
+    Block,
+    /// 捨棄最舊的項目
+    DropOldest,
+    /// 以同一個點位的新項目取代佇列中的舊項目，佇列中沒有同一個點位的項目時，捨棄最舊的項目
+    CoalescePerTarget,
+}
+
+/// 佇列項目
+///
+/// [`OverflowPolicy::CoalescePerTarget`] 以 [`QueueItem::target()`] 判斷項目是否屬於同一個點位，不屬於任何點位的項目不會被合併
+pub trait QueueItem: Send + 'static {
+    /// 項目所屬的點位名稱
+    fn target(&self) -> Option<&str> {
+        None
+    }
+
+    /// 項目佔用的估計位元組數，供 [`BoundedQueue::with_quota()`] 使用
+    ///
+    /// 預設實作只計算項目本身的大小，項目持有較大的堆積資料時請覆寫本 method
+    fn estimated_size(&self) -> usize {
+        size_of_val(self)
+    }
+}
+
+impl QueueItem for WriteCommand {
+    fn target(&self) -> Option<&str> {
+        Some(&self.target)
+    }
+
+    fn estimated_size(&self) -> usize {
+        size_of::<Self>() + self.target.len()
+    }
+}
+
+impl QueueItem for Event {}
+
+impl<T: Send + 'static> QueueItem for (String, T) {
+    fn target(&self) -> Option<&str> {
+        Some(&self.0)
+    }
+
+    fn estimated_size(&self) -> usize {
+        size_of::<Self>() + self.0.len()
+    }
+}
+
+/// 佇列已關閉
+///
+/// 佇列關閉後無法再加入項目，回傳未加入的項目
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueueClosed<T>(pub T);
+
+impl<T> Display for QueueClosed<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "佇列已關閉")
+    }
+}
+
+impl<T: std::fmt::Debug> Error for QueueClosed<T> {}
+
+/// 有上限的管線佇列
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一個佇列
+#[derive(Debug)]
+pub struct BoundedQueue<T>(Arc<BoundedQueueInner<T>>);
+
+#[derive(Debug)]
+struct BoundedQueueInner<T> {
+    items: Mutex<VecDeque<(T, Option<MemoryCharge>)>>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    quota: Option<MemoryQuota>,
+    closed: AtomicBool,
+    not_empty: Notify,
+    not_full: Notify,
+    gauge: QueueGauge,
+}
+
+impl<T> Clone for BoundedQueue<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T: QueueItem> BoundedQueue<T> {
+    /// 建立有上限的管線佇列
+    ///
+    /// # 參數
+    /// - `capacity`：佇列上限，小於 1 時視為 1
+    /// - `policy`：佇列已滿時的處理方式
+    #[must_use]
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        Self::build(capacity, policy, None)
+    }
+
+    /// 建立同時受記憶體配額限制的管線佇列
+    ///
+    /// 超出配額時，[`OverflowPolicy::Block`] 會等待下游取出，其他處理方式會捨棄最舊的項目；
+    /// 佇列中已經沒有項目仍無法容納時，新項目會被捨棄並計入 [`QueueSnapshot::dropped`]
+    ///
+    /// # 參數
+    /// - `capacity`：佇列上限，小於 1 時視為 1
+    /// - `policy`：佇列已滿時的處理方式
+    /// - `quota`：記憶體配額，參見 [`crate::memory`]
+    #[must_use]
+    pub fn with_quota(capacity: usize, policy: OverflowPolicy, quota: MemoryQuota) -> Self {
+        Self::build(capacity, policy, Some(quota))
+    }
+
+    fn build(capacity: usize, policy: OverflowPolicy, quota: Option<MemoryQuota>) -> Self {
+        let capacity = capacity.max(1);
+
+        Self(Arc::new(BoundedQueueInner {
+            items: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+            policy,
+            quota,
+            closed: AtomicBool::new(false),
+            not_empty: Notify::new(),
+            not_full: Notify::new(),
+            gauge: QueueGauge(Arc::new(QueueCounters {
+                capacity,
+                ..QueueCounters::default()
+            })),
+        }))
+    }
+
+    /// 加入項目
+    ///
+    /// 佇列已滿時依 [`OverflowPolicy`] 處理，只有 [`OverflowPolicy::Block`] 會等待
+    ///
+    /// # Errors
+    /// 佇列已關閉時回傳 [`QueueClosed`]
+    pub async fn push(&self, item: T) -> Result<(), QueueClosed<T>> {
+        let mut item = item;
+
+        loop {
+            let not_full = self.0.not_full.notified();
+            tokio::pin!(not_full);
+            not_full.as_mut().enable();
+
+            match self.try_push(item) {
+                Err(PushRejected::Full(rejected)) => item = rejected,
+                Err(PushRejected::Closed(rejected)) => return Err(QueueClosed(rejected)),
+                Ok(()) => return Ok(()),
+            }
+
+            not_full.await;
+        }
+    }
+
+    /// 不等待，嘗試加入項目
+    ///
+    /// # Errors
+    /// 佇列已關閉，或佇列已滿且處理方式為 [`OverflowPolicy::Block`] 時回傳 [`PushRejected`]
+    pub fn try_push(&self, item: T) -> Result<(), PushRejected<T>> {
+        if self.0.closed.load(Ordering::Acquire) {
+            return Err(PushRejected::Closed(item));
+        }
+
+        let counters = &self.0.gauge.0;
+        let mut items = self.0.items.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let charge = match &self.0.quota {
+            Some(quota) => {
+                let evict = self.0.policy != OverflowPolicy::Block;
+                let charged = quota.charge_evicting(item.estimated_size(), || {
+                    if !evict || items.pop_front().is_none() {
+                        return false;
+                    }
+                    counters.dropped.fetch_add(1, Ordering::Relaxed);
+                    true
+                });
+                match charged {
+                    Ok(charge) => Some(charge),
+                    Err(_) if !evict && !items.is_empty() => {
+                        return Err(PushRejected::Full(item));
+                    }
+                    Err(_) => {
+                        counters.dropped.fetch_add(1, Ordering::Relaxed);
+                        counters.depth.store(items.len(), Ordering::Relaxed);
+                        return Ok(());
+                    }
+                }
+            }
+            None => None,
+        };
+
+        if self.0.policy == OverflowPolicy::CoalescePerTarget
+            && let Some(target) = item.target()
+            && let Some(queued) = items
+                .iter_mut()
+                .find(|(queued, _)| queued.target() == Some(target))
+        {
+            *queued = (item, charge);
+            counters.coalesced.fetch_add(1, Ordering::Relaxed);
+            counters.depth.store(items.len(), Ordering::Relaxed);
+            return Ok(());
+        }
+
+        if items.len() >= self.0.capacity {
+            if self.0.policy == OverflowPolicy::Block {
+                return Err(PushRejected::Full(item));
+            }
+
+            items.pop_front();
+            counters.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+
+        items.push_back((item, charge));
+        counters.depth.store(items.len(), Ordering::Relaxed);
+        counters
+            .peak_depth
+            .fetch_max(items.len(), Ordering::Relaxed);
+        drop(items);
+
+        self.0.not_empty.notify_one();
+        Ok(())
+    }
+
+    /// 取出項目，佇列為空時等待
+    ///
+    /// # 回傳值
+    /// 佇列已關閉且所有項目都已取出時為 [`None`]
+    pub async fn pop(&self) -> Option<T> {
+        loop {
+            let not_empty = self.0.not_empty.notified();
+            tokio::pin!(not_empty);
+            not_empty.as_mut().enable();
+
+            if let Some(item) = self.try_pop() {
+                return Some(item);
+            }
+
+            if self.0.closed.load(Ordering::Acquire) {
+                return None;
+            }
+
+            not_empty.await;
+        }
+    }
+
+    /// 不等待，嘗試取出項目
+    #[must_use]
+    pub fn try_pop(&self) -> Option<T> {
+        let mut items = self.0.items.lock().unwrap_or_else(PoisonError::into_inner);
+        let (item, _) = items.pop_front()?;
+        self.0.gauge.0.depth.store(items.len(), Ordering::Relaxed);
+        drop(items);
+
+        self.0.not_full.notify_one();
+        Some(item)
+    }
+
+    /// 關閉佇列
+    ///
+    /// 關閉後無法再加入項目，等待中的 [`BoundedQueue::push()`] 會回傳錯誤，佇列中剩餘的項目仍可取出
+    pub fn close(&self) {
+        self.0.closed.store(true, Ordering::Release);
+        self.0.not_empty.notify_waiters();
+        self.0.not_full.notify_waiters();
+    }
+
+    /// 佇列中的項目數量
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.gauge.0.depth.load(Ordering::Relaxed)
+    }
+
+    /// 佇列是否為空
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 取得佇列深度計量，參見 [`QueueGauges::register()`]
+    #[must_use]
+    pub fn gauge(&self) -> QueueGauge {
+        self.0.gauge.clone()
+    }
+}
+
+/// 項目無法加入佇列
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PushRejected<T> {
+    /// 佇列已滿，回傳未加入的項目
+    Full(T),
+    /// 佇列已關閉，回傳未加入的項目
+    Closed(T),
+}
+
+impl<T> Display for PushRejected<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full(_) => write!(f, "佇列已滿"),
+            Self::Closed(_) => write!(f, "佇列已關閉"),
+        }
+    }
+}
+
+impl<T: std::fmt::Debug> Error for PushRejected<T> {}
+
+/// 佇列深度計量
+///
+/// 由 [`BoundedQueue::gauge()`] 取得，與佇列共用同一份計數
+#[derive(Debug, Clone)]
+pub struct QueueGauge(Arc<QueueCounters>);
+
+#[derive(Debug, Default)]
+struct QueueCounters {
+    capacity: usize,
+    depth: AtomicUsize,
+    peak_depth: AtomicUsize,
+    dropped: AtomicU64,
+    coalesced: AtomicU64,
+}
+
+impl QueueGauge {
+    /// 取得佇列深度快照
+    #[must_use]
+    pub fn snapshot(&self) -> QueueSnapshot {
+        QueueSnapshot {
+            capacity: self.0.capacity,
+            depth: self.0.depth.load(Ordering::Relaxed),
+            peak_depth: self.0.peak_depth.load(Ordering::Relaxed),
+            dropped: self.0.dropped.load(Ordering::Relaxed),
+            coalesced: self.0.coalesced.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 佇列深度快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueSnapshot {
+    /// 佇列上限
+    pub capacity: usize,
+    /// 佇列中的項目數量
+    pub depth: usize,
+    /// 曾經同時在佇列中的最大項目數量
+    pub peak_depth: usize,
+    /// 因佇列已滿被捨棄的項目數量
+    pub dropped: u64,
+    /// 被同一個點位的新項目取代的項目數量
+    pub coalesced: u64,
+}
+
+/// 連線的管線佇列計量
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件（包含登記至 [`crate::ConnectionStatsRegistry`] 的 [`crate::ConnectionStats`]）會存取同一份資料
+#[derive(Debug, Clone, Default)]
+pub struct QueueGauges(Arc<Mutex<BTreeMap<String, QueueGauge>>>);
+
+impl QueueGauges {
+    /// 登記管線佇列
+    ///
+    /// # 參數
+    /// - `stage`：管線階段名稱，如 `postprocess` 、`publish` ，已存在時會以新的佇列取代
+    /// - `gauge`：佇列深度計量，參見 [`BoundedQueue::gauge()`]
+    pub fn register(&self, stage: impl Into<String>, gauge: QueueGauge) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(stage.into(), gauge);
+    }
+
+    /// 取消登記管線佇列
+    pub fn unregister(&self, stage: &str) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(stage);
+    }
+
+    /// 取得各管線佇列的深度快照
+    #[must_use]
+    pub fn snapshot(&self) -> BTreeMap<String, QueueSnapshot> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(stage, gauge)| (stage.clone(), gauge.snapshot()))
+            .collect()
+    }
+}
@@ -0,0 +1,326 @@
+//! 連線登記表
+//!
+//! 主程式同時管理多種 [`Connection`] 實作時，可以利用 [`ConnectionRegistry`] 以連線識別名稱存放運行中的連線，
+//! 並在執行期間新增、移除或取代連線：
+//!
+//! - 以 [`ConnectionRegistry::get_mut()`] 取得指定型別的連線，調用 [`Connection::request_process()`] 等 method
+//! - 以 [`ConnectionRegistry::connections_for()`] 依設定檔中的設備型態（參見 [`Connection::NAMES`]）找出處理該設備型態的連線
+//! - 不同的連線定義在 [`Connection::NAMES`] 中宣告了相同的設備型態時，新增連線會回傳 [`RegistryError::ConflictingDeviceType`] ，
+//!   而不是讓先登記的連線定義「先搶先贏」
+//!
+//! 連線以 `&mut` 存取，需要在多個 task 之間共用時，請由主程式以 [`tokio::sync::Mutex`] 等方式包裝
+//!
+//! # 範例
+//! ```rust
+//! # use std::borrow::Cow;
+//! # use device_state_exchange_lib::*;
+//! use device_state_exchange_lib::registry::{ConnectionRegistry, RegistryError};
+//!
+//! # #[derive(Debug, Clone)] struct Config;
+//! # impl ConnectionConfig for Config {}
+//! # #[derive(Debug, Clone)] struct Point;
+//! # impl Target for Point {}
+//! # #[derive(Debug, Clone)] struct Request;
+//! # impl DeviceStateRequest for Request {}
+//! # #[derive(Debug, Clone)] struct Response;
+//! # impl DeviceStateResponse for Response { fn to_value(&self) -> Result<Cow<'_, serde_json::Value>, value::ConversionError> { Ok(Cow::Owned(serde_json::Value::Null)) } }
+//! # macro_rules! device {
+//! #     ($name:ident, $names:expr) => {
+//! #         struct $name { port: &'static str }
+//! #         impl Connection for $name {
+//! #             const NAMES: &[&str] = $names;
+//! #             type Config = Config;
+//! #             type Target = Point;
+//! #             type Request = Request;
+//! #             type Response = Response;
+//! #             type Result = ();
+//! #             async fn init(_: &Config) -> Result<ConnectionArtifact<Self>, ConnectionError> { unimplemented!() }
+//! #             fn init_targets(&mut self, _: &mut ConnectionStats, _: Vec<Point>) -> ConnectionTargets<Request, ()> { ConnectionTargets(Vec::new()) }
+//! #             async fn request_process(&mut self, _: Request) -> Result<(Response, bool), ConnectionError> { Ok((Response, true)) }
+//! #             async fn reconnect(&mut self) -> Result<(), ConnectionError> { Ok(()) }
+//! #             async fn update_config(&mut self, _: &Config) -> Result<(), ConnectionError> { Ok(()) }
+//! #         }
+//! #     };
+//! # }
+//! // `ModbusRtu` 處理「電錶」與「溫控器」，`Bacnet` 處理「空調」與「溫控器」
+//! # device!(ModbusRtu, &["電錶", "溫控器"]);
+//! # device!(Bacnet, &["空調", "溫控器"]);
+//! let mut registry = ConnectionRegistry::new();
+//! registry.insert("COM1", ModbusRtu { port: "COM1" }).unwrap();
+//! registry.insert("COM2", ModbusRtu { port: "COM2" }).unwrap();
+//! assert!(matches!(registry.insert("COM1", ModbusRtu { port: "COM1" }), Err(RegistryError::DuplicateId(_))));
+//!
+//! // 「溫控器」已由 `ModbusRtu` 處理
+//! let error = registry.insert("10.0.0.1", Bacnet { port: "10.0.0.1" }).unwrap_err();
+//! assert!(matches!(error, RegistryError::ConflictingDeviceType { ref device_type, .. } if device_type == "溫控器"));
+//!
+//! assert_eq!(registry.connections_for("電錶").collect::<Vec<_>>(), ["COM1", "COM2"]);
+//! assert_eq!(registry.get_mut::<ModbusRtu>("COM2").unwrap().port, "COM2");
+//! assert!(registry.get::<Bacnet>("COM2").is_none());
+//!
+//! // 執行期間取代連線
+//! let previous = registry.replace("COM2", ModbusRtu { port: "COM3" }).unwrap().unwrap();
+//! assert_eq!(previous.downcast::<ModbusRtu>().ok().unwrap().port, "COM2");
+//! registry.remove("COM1");
+//! assert_eq!(registry.ids().collect::<Vec<_>>(), ["COM2"]);
+//! ```
+
+use std::{
+    any::{Any, TypeId, type_name},
+    collections::BTreeMap,
+    error::Error,
+    fmt::Display,
+};
+
+use crate::Connection;
+
+/// 登記的連線
+///
+/// 由 [`ConnectionRegistry::remove()`] 或 [`ConnectionRegistry::replace()`] 取出，可以利用 [`RegisteredConnection::downcast()`] 取回原本的連線
+#[derive(Debug)]
+pub struct RegisteredConnection {
+    type_id: TypeId,
+    type_name: &'static str,
+    names: &'static [&'static str],
+    connection: Box<dyn Any + Send>,
+}
+
+impl RegisteredConnection {
+    fn new<T: Connection>(connection: T) -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            type_name: type_name::<T>(),
+            names: T::NAMES,
+            connection: Box::new(connection),
+        }
+    }
+
+    /// 連線定義的型別名稱，僅供偵錯使用
+    #[must_use]
+    pub const fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// 連線定義的設備型態名稱列表，參見 [`Connection::NAMES`]
+    #[must_use]
+    pub const fn names(&self) -> &'static [&'static str] {
+        self.names
+    }
+
+    /// 連線是否為指定的型別
+    #[must_use]
+    pub fn is<T: Connection>(&self) -> bool {
+        self.type_id == TypeId::of::<T>()
+    }
+
+    /// 取回原本的連線
+    ///
+    /// # Errors
+    /// 型別不符時回傳原本的 [`RegisteredConnection`]
+    pub fn downcast<T: Connection>(self) -> Result<T, Self> {
+        if !self.is::<T>() {
+            return Err(self);
+        }
+        let Self {
+            type_id,
+            type_name,
+            names,
+            connection,
+        } = self;
+        connection
+            .downcast()
+            .map(|connection| *connection)
+            .map_err(|connection| Self {
+                type_id,
+                type_name,
+                names,
+                connection,
+            })
+    }
+}
+
+/// 連線登記表
+///
+/// 以連線識別名稱存放運行中的連線，依識別名稱排序
+#[derive(Debug, Default)]
+pub struct ConnectionRegistry {
+    connections: BTreeMap<String, RegisteredConnection>,
+}
+
+impl ConnectionRegistry {
+    /// 建立空的連線登記表
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登記連線
+    ///
+    /// # 參數
+    /// - `id`：連線識別名稱
+    /// - `connection`：運行中的連線
+    ///
+    /// # Errors
+    /// 識別名稱已存在時回傳 [`RegistryError::DuplicateId`] ，
+    /// 其他連線定義已宣告相同的設備型態時回傳 [`RegistryError::ConflictingDeviceType`]
+    pub fn insert<T: Connection>(
+        &mut self,
+        id: impl Into<String>,
+        connection: T,
+    ) -> Result<(), RegistryError> {
+        let id = id.into();
+        if self.connections.contains_key(&id) {
+            return Err(RegistryError::DuplicateId(id));
+        }
+        self.check_conflicts::<T>(None)?;
+        self.connections
+            .insert(id, RegisteredConnection::new(connection));
+        Ok(())
+    }
+
+    /// 取代連線，識別名稱不存在時視為新增
+    ///
+    /// 新的連線可以是不同的連線定義，如設定檔變更了設備型態
+    ///
+    /// # 回傳值
+    /// 被取代的連線
+    ///
+    /// # Errors
+    /// 其他連線的連線定義已宣告相同的設備型態時回傳 [`RegistryError::ConflictingDeviceType`] ，此時不會取代連線
+    pub fn replace<T: Connection>(
+        &mut self,
+        id: impl Into<String>,
+        connection: T,
+    ) -> Result<Option<RegisteredConnection>, RegistryError> {
+        let id = id.into();
+        self.check_conflicts::<T>(Some(&id))?;
+        Ok(self
+            .connections
+            .insert(id, RegisteredConnection::new(connection)))
+    }
+
+    /// 移除連線
+    pub fn remove(&mut self, id: &str) -> Option<RegisteredConnection> {
+        self.connections.remove(id)
+    }
+
+    /// 取得指定型別的連線，識別名稱不存在或型別不符時為 [`None`]
+    #[must_use]
+    pub fn get<T: Connection>(&self, id: &str) -> Option<&T> {
+        self.connections
+            .get(id)
+            .and_then(|registered| registered.connection.downcast_ref())
+    }
+
+    /// 取得指定型別的連線的可變引用，識別名稱不存在或型別不符時為 [`None`]
+    #[must_use]
+    pub fn get_mut<T: Connection>(&mut self, id: &str) -> Option<&mut T> {
+        self.connections
+            .get_mut(id)
+            .and_then(|registered| registered.connection.downcast_mut())
+    }
+
+    /// 是否已登記指定的識別名稱
+    #[must_use]
+    pub fn contains(&self, id: &str) -> bool {
+        self.connections.contains_key(id)
+    }
+
+    /// 連線識別名稱
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.connections.keys().map(String::as_str)
+    }
+
+    /// 處理指定設備型態的連線識別名稱
+    ///
+    /// # 參數
+    /// - `device_type`：設定檔中的設備型態，參見 [`Connection::NAMES`]
+    pub fn connections_for<'a>(&'a self, device_type: &'a str) -> impl Iterator<Item = &'a str> {
+        self.connections
+            .iter()
+            .filter(move |(_, registered)| registered.names.contains(&device_type))
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// 處理指定設備型態的連線定義的型別名稱，沒有連線處理該設備型態時為 [`None`]
+    #[must_use]
+    pub fn definition_for(&self, device_type: &str) -> Option<&'static str> {
+        self.connections
+            .values()
+            .find(|registered| registered.names.contains(&device_type))
+            .map(RegisteredConnection::type_name)
+    }
+
+    /// 已登記的連線數量
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// 是否沒有登記任何連線
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.connections.is_empty()
+    }
+
+    /// 檢查其他連線定義是否已宣告 `T` 的設備型態
+    ///
+    /// # 參數
+    /// - `replacing`：將被取代的連線識別名稱，不列入檢查
+    fn check_conflicts<T: Connection>(&self, replacing: Option<&str>) -> Result<(), RegistryError> {
+        let conflict = self
+            .connections
+            .iter()
+            .filter(|(id, registered)| Some(id.as_str()) != replacing && !registered.is::<T>())
+            .find_map(|(_, registered)| {
+                T::NAMES
+                    .iter()
+                    .find(|name| registered.names.contains(name))
+                    .map(|name| (*name, registered.type_name))
+            });
+
+        match conflict {
+            Some((device_type, existing)) => Err(RegistryError::ConflictingDeviceType {
+                device_type: device_type.to_owned(),
+                existing,
+                conflicting: type_name::<T>(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 連線登記錯誤
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RegistryError {
+    /// 連線識別名稱已存在
+    DuplicateId(String),
+    /// 不同的連線定義宣告了相同的設備型態
+    ConflictingDeviceType {
+        /// 設備型態
+        device_type: String,
+        /// 已登記的連線定義的型別名稱
+        existing: &'static str,
+        /// 新增的連線定義的型別名稱
+        conflicting: &'static str,
+    },
+}
+
+impl Display for RegistryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DuplicateId(id) => write!(f, "連線「{id}」已存在"),
+            Self::ConflictingDeviceType {
+                device_type,
+                existing,
+                conflicting,
+            } => write!(
+                f,
+                "設備型態「{device_type}」已由 {existing} 處理，{conflicting} 不可重複宣告"
+            ),
+        }
+    }
+}
+
+impl Error for RegistryError {}
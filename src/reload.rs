@@ -0,0 +1,215 @@
+//! 點位熱更新
+//!
+//! 現場人員經常調整點位清單，連線參數則很少變動；若每次修改設定檔都調用 [`Connection::update_config()`] ，
+//! 連線會被重新建立，所有點位的狀態也要重新輪詢
+//!
+//! [`reload()`] 比較新舊設定：
+//!
+//! - 連線參數相同時，只重新調用 [`Connection::init_targets()`] ，並與目前的 [`ConnectionTargets`] 比較，
+//!   保留未變動的點位（包含其回覆資訊），加入新增與變動的點位，移除已不存在的點位
+//! - 連線參數不同時，才會先調用 [`Connection::update_config()`] 重新連線
+//!
+//! 回傳的 [`TargetDiff`] 列出新增、移除與變動的點位名稱，主程式可依此更新 [`crate::StateStore`] 等以點位名稱索引的資料
+//!
+//! # 範例
+//! ```rust
+//! # use std::{borrow::Cow, error::Error};
+//! # use device_state_exchange_lib::{*, reload::reload};
+//! #[derive(Debug, Clone, PartialEq)]
+//! struct Config { port: &'static str }
+//! # impl ConnectionConfig for Config {}
+//! # #[derive(Debug, Clone)] struct Point(&'static str, u16);
+//! # impl Target for Point {}
+//! #[derive(Debug, Clone, PartialEq)]
+//! struct Request { address: u16 }
+//! # impl DeviceStateRequest for Request {}
+//! # #[derive(Debug, Clone)] struct Response;
+//! # impl DeviceStateResponse for Response { fn to_value(&self) -> Result<Cow<'_, serde_json::Value>, value::ConversionError> { Ok(Cow::Owned(serde_json::Value::Null)) } }
+//! # #[derive(Default)] struct Meter { reconnects: usize }
+//! # impl Connection for Meter {
+//! #     const NAMES: &[&str] = &["Meter"];
+//! #     type Config = Config;
+//! #     type Target = Point;
+//! #     type Request = Request;
+//! #     type Response = Response;
+//! #     type Result = ();
+//! #     async fn init(_: &Config) -> Result<ConnectionArtifact<Self>, ConnectionError> { Ok(ConnectionArtifact::new(Self::default(), ConnectionStats::new("COM1", None))) }
+//! #     fn init_targets(&mut self, _: &mut ConnectionStats, targets: Vec<Point>) -> ConnectionTargets<Request, ()> {
+//! #         ConnectionTargets(targets.into_iter().map(|Point(name, address)| InitedTarget { name: name.to_owned(), request: Request { address }, result: (), default_status: None, auto_refresh: true, refresh_interval: None, keep_raw_frames: None, group: None, safe_state: None, array: None, change: None, statistics: None }).collect())
+//! #     }
+//! #     async fn request_process(&mut self, _: Request) -> Result<(Response, bool), ConnectionError> { Ok((Response, true)) }
+//! #     async fn reconnect(&mut self) -> Result<(), ConnectionError> { Ok(()) }
+//! #     async fn update_config(&mut self, _: &Config) -> Result<(), ConnectionError> { self.reconnects += 1; Ok(()) }
+//! # }
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let mut config = Config { port: "COM1" };
+//! let mut artifact = Meter::init(&config).await.unwrap();
+//! let mut targets = artifact.artifact.init_targets(
+//!     &mut artifact.statistics,
+//!     vec![Point("電壓", 1), Point("電流", 2)],
+//! );
+//!
+//! // 只修改點位清單，不會重新連線
+//! let report = reload(
+//!     &mut artifact.artifact,
+//!     &mut artifact.statistics,
+//!     &mut config,
+//!     Config { port: "COM1" },
+//!     &mut targets,
+//!     vec![Point("電壓", 1), Point("電流", 3), Point("功率", 4)],
+//! )
+//! .await
+//! .unwrap();
+//!
+//! assert!(!report.reconnected);
+//! assert_eq!(report.targets.added, ["功率"]);
+//! assert_eq!(report.targets.changed, ["電流"]);
+//! assert_eq!(artifact.artifact.reconnects, 0);
+//! assert_eq!(targets.0.len(), 3);
+//! # }
+//! ```
+
+use serde::Serialize;
+
+use crate::{
+    Connection, ConnectionError, ConnectionStats, ConnectionTargets, DeviceStateRequest, HashMap,
+    InitedTarget, TargetStatsSnapshot, cov::ChangeNotifier,
+};
+
+/// 點位清單差異
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TargetDiff {
+    /// 新增的點位名稱
+    pub added: Vec<String>,
+    /// 移除的點位名稱
+    pub removed: Vec<String>,
+    /// 請求、初始狀態或更新設定變動的點位名稱
+    pub changed: Vec<String>,
+}
+
+impl TargetDiff {
+    /// 點位清單是否沒有任何變動
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// 熱更新結果
+#[derive(Debug, Clone)]
+pub struct ReloadReport {
+    /// 連線參數是否變動，並已調用 [`Connection::update_config()`]
+    pub reconnected: bool,
+    /// 點位清單差異
+    pub targets: TargetDiff,
+    /// 已不再使用的點位統計數據的最終快照，參見 [`ConnectionStats::retain_targets()`]
+    pub removed_statistics: Vec<TargetStatsSnapshot>,
+}
+
+/// 以新的設定熱更新連線
+///
+/// 連線參數以 [`PartialEq`] 比較，點位以名稱對應，並比較 [`InitedTarget::request`] 、 [`InitedTarget::default_status`] 、
+/// [`InitedTarget::auto_refresh`] 、 [`InitedTarget::refresh_interval`] 、 [`InitedTarget::keep_raw_frames`] 、 [`InitedTarget::group`] 、 [`InitedTarget::safe_state`] 、 [`InitedTarget::array`]
+/// 與 [`InitedTarget::change`] 的不敏感帶判斷是否變動；未變動的點位會保留原本的 [`InitedTarget::change`] ，訂閱者不會中斷
+///
+/// # 參數
+/// - `connection`：目前的連線
+/// - `statistics`：連線統計數據
+/// - `config`：目前的連線參數，連線參數變動且更新成功後會被取代為 `new_config`
+/// - `new_config`：新的連線參數
+/// - `targets`：目前的點位，會被更新為新的點位清單
+/// - `new_targets`：新的點位清單
+///
+/// # Errors
+/// 回傳 [`Connection::update_config()`] 的錯誤，此時連線參數與點位均維持不變
+pub async fn reload<T>(
+    connection: &mut T,
+    statistics: &mut ConnectionStats,
+    config: &mut T::Config,
+    new_config: T::Config,
+    targets: &mut ConnectionTargets<T::Request, T::Result>,
+    new_targets: Vec<T::Target>,
+) -> Result<ReloadReport, ConnectionError>
+where
+    T: Connection,
+    T::Config: PartialEq,
+    T::Request: PartialEq,
+{
+    let reconnected = *config != new_config;
+
+    if reconnected {
+        connection.update_config(&new_config).await?;
+        *config = new_config;
+    }
+
+    let new_targets = connection.init_targets(statistics, new_targets);
+    let diff = apply_targets(targets, new_targets);
+    let removed_statistics = statistics.retain_targets(targets);
+
+    Ok(ReloadReport {
+        reconnected,
+        targets: diff,
+        removed_statistics,
+    })
+}
+
+/// 以新的點位清單取代目前的點位
+///
+/// 未變動的點位會保留目前的 [`InitedTarget`] ，點位順序與新的點位清單相同
+///
+/// # 參數
+/// - `targets`：目前的點位
+/// - `new_targets`：由 [`Connection::init_targets()`] 產生的新點位
+///
+/// # 回傳值
+/// 點位清單差異
+pub fn apply_targets<REQ, RES>(
+    targets: &mut ConnectionTargets<REQ, RES>,
+    new_targets: ConnectionTargets<REQ, RES>,
+) -> TargetDiff
+where
+    REQ: DeviceStateRequest + PartialEq,
+{
+    let mut previous: HashMap<String, InitedTarget<REQ, RES>> = targets
+        .0
+        .drain(..)
+        .map(|target| (target.name.clone(), target))
+        .collect();
+    let mut diff = TargetDiff::default();
+
+    for target in new_targets.0 {
+        match previous.remove(&target.name) {
+            None => {
+                diff.added.push(target.name.clone());
+                targets.0.push(target);
+            }
+            Some(current) if is_unchanged(&current, &target) => targets.0.push(current),
+            Some(_) => {
+                diff.changed.push(target.name.clone());
+                targets.0.push(target);
+            }
+        }
+    }
+
+    diff.removed = previous.into_keys().collect();
+    diff.removed.sort_unstable();
+    diff
+}
+
+fn is_unchanged<REQ, RES>(current: &InitedTarget<REQ, RES>, new: &InitedTarget<REQ, RES>) -> bool
+where
+    REQ: DeviceStateRequest + PartialEq,
+{
+    current.request == new.request
+        && current.default_status == new.default_status
+        && current.auto_refresh == new.auto_refresh
+        && current.refresh_interval == new.refresh_interval
+        && current.keep_raw_frames == new.keep_raw_frames
+        && current.group == new.group
+        && current.safe_state == new.safe_state
+        && current.array == new.array
+        && current.change.as_ref().map(ChangeNotifier::deadband)
+            == new.change.as_ref().map(ChangeNotifier::deadband)
+}
@@ -0,0 +1,406 @@
+//! 基於 [`axum`](https://crates.io/crates/axum) 的 REST 界面（需啟用 `axum` feature）
+//!
+//! 提供以下路由：
+//!
+//! | 方法 | 路徑 | 說明 |
+//! | --- | --- | --- |
+//! | `GET` | `/targets` | 取得所有點位狀態 |
+//! | `GET` | `/targets/{name}` | 取得單一點位狀態，連線中斷且離線處理方式為 [`crate::state::OfflinePolicy::Fail`] 時回傳 `503` |
+//! | `POST` | `/targets/{name}/write` | 將 request body 中的 JSON 作為新狀態，加入寫入指令佇列，請求來源取自 [`CLIENT_ID_HEADER`] 標頭，請求追蹤編號取自 [`CORRELATION_ID_HEADER`] 標頭 |
+//! | `GET` | `/targets/{name}/raw-frames` | 取得點位保留的原始封包，參見 [`crate::diagnostics::RawFrameStore`] |
+//! | `GET` | `/connections/{id}/stats` | 取得連線統計數據快照 |
+//! | `GET` | `/connections/{id}/journal` | 取得連線最近的請求紀錄，參見 [`crate::diagnostics::RequestJournal`] |
+//!
+//! 所有路由都會先以 [`ApiState::authorizer`] 檢查請求來源（取自 [`CLIENT_ID_HEADER`] 標頭）的權限，沒有權限時回傳 `403` ，
+//! `/targets` 只會列出有讀取權限的點位；連線相關的路由以連線識別名稱檢查 [`Action::Inspect`] 權限
+//!
+//! 多租戶環境請改用 [`tenant_router()`] ，上述路由會被掛載於 `/tenants/{tenant}` 之下，且只能存取該租戶的資料
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{CommandQueue, StateStore, rest};
+//!
+//! let store = StateStore::new();
+//! let commands = CommandQueue::new();
+//! let app = rest::router(rest::ApiState::new(store.clone(), commands.clone()));
+//! // 交由 axum::serve() 執行，或利用 Router::nest() 掛載
+//! # let _: axum::Router = app;
+//! ```
+
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+};
+use serde_json::Value;
+
+use crate::{
+    ClientId, CommandQueue, ConnectionStats, ConnectionStatsRegistry, ConnectionStatsSnapshot,
+    CorrelationId, StateStore, TargetState, Tenant, TenantId, Tenants, WriteCommand,
+    auth::{Action, AllowAll, Authorizer},
+    diagnostics::{JournalEntry, RawFrame, RawFrameStore, RequestJournal},
+    state::StateReadError,
+};
+
+/// 識別請求來源的 HTTP 標頭，參見 [`crate::WriteCommand::client`]
+///
+/// 驗證用戶端身分不在本界面的範圍內，請由前端的反向代理或 [`Router::layer()`] 驗證後設定本標頭
+pub const CLIENT_ID_HEADER: &str = "x-client-id";
+
+/// 請求追蹤編號的 HTTP 標頭，參見 [`crate::WriteCommand::correlation`]
+///
+/// 寫入請求未帶本標頭時，會以 [`CorrelationId::generate()`] 產生新的追蹤編號
+pub const CORRELATION_ID_HEADER: &str = "x-request-id";
+
+/// REST 界面共用狀態
+///
+/// 主程式需將與設備連線共用的 [`StateStore`] 、 [`CommandQueue`] 傳入，並利用 [`ApiState::add_connection()`] 登記連線統計數據
+#[derive(Debug, Clone)]
+pub struct ApiState {
+    /// 點位狀態儲存區
+    pub store: StateStore,
+    /// 寫入指令佇列
+    pub commands: CommandQueue,
+    /// 連線統計數據登記表
+    pub statistics: ConnectionStatsRegistry,
+    /// 原始封包保留區
+    pub raw_frames: RawFrameStore,
+    /// 請求紀錄保留區
+    pub journal: RequestJournal,
+    /// 讀寫權限檢查，預設為 [`AllowAll`]
+    pub authorizer: Arc<dyn Authorizer>,
+    /// 所屬租戶，權限檢查時傳入 [`Authorizer::check()`] ；不是多租戶界面時為 [`None`]
+    pub tenant: Option<TenantId>,
+}
+
+impl ApiState {
+    /// 建立 REST 界面共用狀態
+    #[must_use]
+    pub fn new(store: StateStore, commands: CommandQueue) -> Self {
+        Self {
+            store,
+            commands,
+            statistics: ConnectionStatsRegistry::new(),
+            raw_frames: RawFrameStore::new(),
+            journal: RequestJournal::new(),
+            authorizer: Arc::new(AllowAll),
+            tenant: None,
+        }
+    }
+
+    /// 設定讀寫權限檢查，參見 [`ApiState::authorizer`]
+    #[must_use]
+    pub fn with_authorizer(mut self, authorizer: impl Authorizer) -> Self {
+        self.authorizer = Arc::new(authorizer);
+        self
+    }
+
+    /// 登記連線統計數據
+    ///
+    /// # 參數
+    /// - `id`：連線識別名稱，即 `/connections/{id}/stats` 路徑中的 `id`
+    /// - `statistics`：連線統計數據，[`ConnectionStats`] 中的點位統計數據以 [`std::sync::Arc`] 共享，登記後仍會持續反映最新數值
+    pub fn add_connection(&self, id: impl Into<String>, statistics: ConnectionStats) {
+        self.statistics.insert(id, statistics);
+    }
+
+    /// 移除連線統計數據
+    #[must_use]
+    pub fn remove_connection(&self, id: &str) -> Option<ConnectionStats> {
+        self.statistics.remove(id)
+    }
+
+    fn authorize(
+        &self,
+        headers: &HeaderMap,
+        target: &str,
+        action: Action,
+    ) -> Result<(), StatusCode> {
+        if self
+            .authorizer
+            .check(
+                principal(headers).as_ref(),
+                self.tenant.as_ref(),
+                target,
+                action,
+            )
+            .is_allowed()
+        {
+            Ok(())
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+
+    fn list_targets(&self, headers: &HeaderMap) -> Json<HashMap<String, TargetState>> {
+        let principal = principal(headers);
+        let mut targets = self.store.snapshot();
+        targets.retain(|name, _| {
+            self.authorizer
+                .check(principal.as_ref(), self.tenant.as_ref(), name, Action::Read)
+                .is_allowed()
+        });
+
+        Json(targets)
+    }
+
+    fn get_target(&self, name: &str, headers: &HeaderMap) -> Result<Json<TargetState>, StatusCode> {
+        self.authorize(headers, name, Action::Read)?;
+
+        self.store
+            .read(name)
+            .map(Json)
+            .map_err(|error| match error {
+                StateReadError::NotRegistered(_) => StatusCode::NOT_FOUND,
+                StateReadError::Offline(_) => StatusCode::SERVICE_UNAVAILABLE,
+            })
+    }
+
+    fn write_target(&self, name: String, headers: &HeaderMap, value: Value) -> StatusCode {
+        if let Err(status) = self.authorize(headers, &name, Action::Write) {
+            return status;
+        }
+        if !self.store.contains(&name) {
+            return StatusCode::NOT_FOUND;
+        }
+
+        self.commands.push(WriteCommand {
+            target: name,
+            value,
+            client: principal(headers),
+            correlation: Some(
+                headers
+                    .get(CORRELATION_ID_HEADER)
+                    .and_then(|correlation| correlation.to_str().ok())
+                    .map_or_else(CorrelationId::generate, CorrelationId::new),
+            ),
+        });
+
+        StatusCode::ACCEPTED
+    }
+
+    fn raw_frames(
+        &self,
+        name: &str,
+        headers: &HeaderMap,
+    ) -> Result<Json<Vec<RawFrame>>, StatusCode> {
+        self.authorize(headers, name, Action::Read)?;
+
+        self.raw_frames
+            .frames(name)
+            .map(Json)
+            .ok_or(StatusCode::NOT_FOUND)
+    }
+
+    fn connection_stats(
+        &self,
+        id: &str,
+        headers: &HeaderMap,
+    ) -> Result<Json<ConnectionStatsSnapshot>, StatusCode> {
+        self.authorize(headers, id, Action::Inspect)?;
+
+        self.statistics
+            .snapshot(id)
+            .map(Json)
+            .ok_or(StatusCode::NOT_FOUND)
+    }
+
+    fn journal(
+        &self,
+        id: &str,
+        headers: &HeaderMap,
+    ) -> Result<Json<Vec<JournalEntry>>, StatusCode> {
+        self.authorize(headers, id, Action::Inspect)?;
+
+        self.journal
+            .entries(id)
+            .map(Json)
+            .ok_or(StatusCode::NOT_FOUND)
+    }
+}
+
+impl From<&Tenant> for ApiState {
+    fn from(tenant: &Tenant) -> Self {
+        Self {
+            store: tenant.store().clone(),
+            commands: tenant.commands().clone(),
+            statistics: tenant.statistics().clone(),
+            raw_frames: tenant.raw_frames().clone(),
+            journal: tenant.journal().clone(),
+            authorizer: Arc::new(AllowAll),
+            tenant: Some(tenant.id().clone()),
+        }
+    }
+}
+
+fn principal(headers: &HeaderMap) -> Option<ClientId> {
+    headers
+        .get(CLIENT_ID_HEADER)
+        .and_then(|client| client.to_str().ok())
+        .map(ClientId::new)
+}
+
+/// 建立 REST 路由
+///
+/// 回傳的 [`Router`] 可以直接交由 [`axum::serve()`] 執行，或利用 [`Router::nest()`] 掛載於既有的路由下
+pub fn router(state: ApiState) -> Router {
+    Router::new()
+        .route(
+            "/targets",
+            get(
+                |State(state): State<ApiState>, headers: HeaderMap| async move {
+                    state.list_targets(&headers)
+                },
+            ),
+        )
+        .route(
+            "/targets/{name}",
+            get(
+                |State(state): State<ApiState>, Path(name): Path<String>, headers: HeaderMap| async move {
+                    state.get_target(&name, &headers)
+                },
+            ),
+        )
+        .route(
+            "/targets/{name}/write",
+            post(
+                |State(state): State<ApiState>,
+                 Path(name): Path<String>,
+                 headers: HeaderMap,
+                 Json(value): Json<Value>| async move { state.write_target(name, &headers, value) },
+            ),
+        )
+        .route(
+            "/targets/{name}/raw-frames",
+            get(
+                |State(state): State<ApiState>, Path(name): Path<String>, headers: HeaderMap| async move {
+                    state.raw_frames(&name, &headers)
+                },
+            ),
+        )
+        .route(
+            "/connections/{id}/stats",
+            get(
+                |State(state): State<ApiState>, Path(id): Path<String>, headers: HeaderMap| async move {
+                    state.connection_stats(&id, &headers)
+                },
+            ),
+        )
+        .route(
+            "/connections/{id}/journal",
+            get(
+                |State(state): State<ApiState>, Path(id): Path<String>, headers: HeaderMap| async move {
+                    state.journal(&id, &headers)
+                },
+            ),
+        )
+        .with_state(state)
+}
+
+/// 建立多租戶 REST 路由
+///
+/// 路由與 [`router()`] 相同，但皆掛載於 `/tenants/{tenant}` 之下，不存在的租戶會回傳 `404 Not Found`
+///
+/// 所有請求均允許，需要檢查讀寫權限時請改用 [`tenant_router_with_authorizer()`]
+pub fn tenant_router(tenants: Tenants) -> Router {
+    tenant_router_with_authorizer(tenants, Arc::new(AllowAll))
+}
+
+/// 建立檢查讀寫權限的多租戶 REST 路由
+///
+/// 與 [`tenant_router()`] 相同，但所有租戶共用 `authorizer` 檢查讀寫權限，參見 [`ApiState::authorizer`] ；
+/// 請求存取的租戶會傳入 [`Authorizer::check()`] ，`authorizer` 需依租戶區分權限
+pub fn tenant_router_with_authorizer(tenants: Tenants, authorizer: Arc<dyn Authorizer>) -> Router {
+    Router::new()
+        .route(
+            "/tenants/{tenant}/targets",
+            get(
+                |State(tenants): State<TenantApi>,
+                 Path(tenant): Path<String>,
+                 headers: HeaderMap| async move {
+                    tenants
+                        .scoped(&tenant)
+                        .map(|state| state.list_targets(&headers))
+                },
+            ),
+        )
+        .route(
+            "/tenants/{tenant}/targets/{name}",
+            get(
+                |State(tenants): State<TenantApi>,
+                 Path((tenant, name)): Path<(String, String)>,
+                 headers: HeaderMap| async move {
+                    tenants.scoped(&tenant)?.get_target(&name, &headers)
+                },
+            ),
+        )
+        .route(
+            "/tenants/{tenant}/targets/{name}/write",
+            post(
+                |State(tenants): State<TenantApi>,
+                 Path((tenant, name)): Path<(String, String)>,
+                 headers: HeaderMap,
+                 Json(value): Json<Value>| async move {
+                    tenants.scoped(&tenant).map_or_else(
+                        |status| status,
+                        |state| state.write_target(name, &headers, value),
+                    )
+                },
+            ),
+        )
+        .route(
+            "/tenants/{tenant}/targets/{name}/raw-frames",
+            get(
+                |State(tenants): State<TenantApi>,
+                 Path((tenant, name)): Path<(String, String)>,
+                 headers: HeaderMap| async move {
+                    tenants.scoped(&tenant)?.raw_frames(&name, &headers)
+                },
+            ),
+        )
+        .route(
+            "/tenants/{tenant}/connections/{id}/stats",
+            get(
+                |State(tenants): State<TenantApi>,
+                 Path((tenant, id)): Path<(String, String)>,
+                 headers: HeaderMap| async move {
+                    tenants.scoped(&tenant)?.connection_stats(&id, &headers)
+                },
+            ),
+        )
+        .route(
+            "/tenants/{tenant}/connections/{id}/journal",
+            get(
+                |State(tenants): State<TenantApi>,
+                 Path((tenant, id)): Path<(String, String)>,
+                 headers: HeaderMap| async move {
+                    tenants.scoped(&tenant)?.journal(&id, &headers)
+                },
+            ),
+        )
+        .with_state(TenantApi {
+            tenants,
+            authorizer,
+        })
+}
+
+#[derive(Debug, Clone)]
+struct TenantApi {
+    tenants: Tenants,
+    authorizer: Arc<dyn Authorizer>,
+}
+
+impl TenantApi {
+    fn scoped(&self, tenant: &str) -> Result<ApiState, StatusCode> {
+        self.tenants
+            .get(&TenantId::new(tenant))
+            .map(|tenant| ApiState {
+                authorizer: Arc::clone(&self.authorizer),
+                ..ApiState::from(&tenant)
+            })
+            .ok_or(StatusCode::NOT_FOUND)
+    }
+}
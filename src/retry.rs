@@ -0,0 +1,246 @@
+//! 重新連線的等待策略
+//!
+//! 失敗次數達到 [`crate::ConnectionArtifact::max_retry_count`] 後立即調用 [`crate::Connection::reconnect()`] ，在不穩定的線路（如受干擾的序列埠）上
+//! 容易連續失敗並佔滿線路，實作者可以在 [`crate::Connection::init()`] 中透過 [`crate::ConnectionArtifact::retry_with()`] 設定 [`RetryPolicy`] ，
+//! 主程式在每次重新連線前依 [`RetryPolicy::delay()`] 等待，或直接利用 [`reconnect()`] 等待並重新連線
+//!
+//! 每次重新連線的結果會記錄在 [`crate::ConnectionStats::reconnects`] ，連續失敗次數同時作為下一次計算等待時間的依據，成功後歸零
+//!
+//! # 範例
+//! ```rust
+//! use std::time::Duration;
+//! use device_state_exchange_lib::{ConnectionStats, retry::RetryPolicy};
+//!
+//! let policy = RetryPolicy::Exponential { initial: Duration::from_millis(100), max: Duration::from_secs(1) };
+//! assert_eq!(policy.delay(1), Duration::from_millis(100));
+//! assert_eq!(policy.delay(3), Duration::from_millis(400));
+//! assert_eq!(policy.delay(10), Duration::from_secs(1));
+//!
+//! let jitter = RetryPolicy::ExponentialJitter { initial: Duration::from_millis(100), max: Duration::from_secs(1) };
+//! assert!((Duration::from_millis(200)..=Duration::from_millis(400)).contains(&jitter.delay(3)));
+//!
+//! let statistics = ConnectionStats::new("COM1", None);
+//! statistics.reconnects.record_failure(policy.delay(statistics.reconnects.next_attempt()));
+//! statistics.reconnects.record_failure(policy.delay(statistics.reconnects.next_attempt()));
+//! assert_eq!(statistics.reconnects.next_attempt(), 3);
+//!
+//! let snapshot = statistics.snapshot().reconnects;
+//! assert_eq!((snapshot.attempt_count, snapshot.failed_count), (2, 2));
+//! assert_eq!(snapshot.total_delay, Duration::from_millis(300));
+//! ```
+
+use std::{
+    fmt::Debug,
+    hash::{BuildHasher, RandomState},
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Connection, ConnectionError};
+
+/// 重新連線前的等待策略
+///
+/// 等待時間依連續失敗後的第幾次重新連線（由 1 開始，參見 [`ReconnectStats::next_attempt()`]）計算
+#[derive(Clone, Default)]
+#[non_exhaustive]
+pub enum RetryPolicy {
+    /// 不等待，立即重新連線
+    #[default]
+    Immediate,
+    /// 固定等待時間
+    Fixed(Duration),
+    /// 指數退避，第一次等待 `initial` ，之後每次加倍，最長為 `max`
+    Exponential {
+        /// 第一次的等待時間
+        initial: Duration,
+        /// 最長等待時間
+        max: Duration,
+    },
+    /// 隨機化的指數退避，等待時間為 [`RetryPolicy::Exponential`] 的一半至全部之間的隨機值，
+    /// 避免同一條線路上的多個連線同時重新連線
+    ExponentialJitter {
+        /// 第一次的等待時間
+        initial: Duration,
+        /// 最長等待時間
+        max: Duration,
+    },
+    /// 自訂策略，傳入第幾次重新連線，回傳等待時間
+    Custom(Arc<dyn Fn(u32) -> Duration + Send + Sync>),
+}
+
+impl Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Immediate => f.write_str("Immediate"),
+            Self::Fixed(delay) => f.debug_tuple("Fixed").field(delay).finish(),
+            Self::Exponential { initial, max } => f
+                .debug_struct("Exponential")
+                .field("initial", initial)
+                .field("max", max)
+                .finish(),
+            Self::ExponentialJitter { initial, max } => f
+                .debug_struct("ExponentialJitter")
+                .field("initial", initial)
+                .field("max", max)
+                .finish(),
+            Self::Custom(_) => f.debug_tuple("Custom").finish_non_exhaustive(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 建立自訂策略，參見 [`RetryPolicy::Custom`]
+    #[must_use]
+    pub fn custom(delay: impl Fn(u32) -> Duration + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(delay))
+    }
+
+    /// 計算重新連線前的等待時間
+    ///
+    /// # 參數
+    /// - `attempt`：連續失敗後的第幾次重新連線，由 1 開始，0 視為 1
+    #[must_use]
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let attempt = attempt.max(1);
+        match self {
+            Self::Immediate => Duration::ZERO,
+            Self::Fixed(delay) => *delay,
+            Self::Exponential { initial, max } => exponential(*initial, *max, attempt),
+            Self::ExponentialJitter { initial, max } => {
+                let ceiling = exponential(*initial, *max, attempt);
+                let floor = ceiling / 2;
+                let span =
+                    u64::try_from(ceiling.saturating_sub(floor).as_nanos()).unwrap_or(u64::MAX);
+                let random = RandomState::new().hash_one(attempt);
+                floor + Duration::from_nanos(random % span.saturating_add(1))
+            }
+            Self::Custom(delay) => delay(attempt),
+        }
+    }
+}
+
+fn exponential(initial: Duration, max: Duration, attempt: u32) -> Duration {
+    2_u32
+        .checked_pow(attempt - 1)
+        .and_then(|factor| initial.checked_mul(factor))
+        .map_or(max, |delay| delay.min(max))
+}
+
+/// 依等待策略等待後重新連線一次，並記錄結果
+///
+/// # 參數
+/// - `connection`：設備連線
+/// - `policy`：等待策略，通常為 [`crate::ConnectionArtifact::retry_policy`]
+/// - `statistics`：重新連線統計，通常為 [`crate::ConnectionStats::reconnects`]
+///
+/// # Errors
+/// 回傳 [`crate::Connection::reconnect()`] 的錯誤，主程式可以再次調用本 function ，等待時間會依連續失敗次數增加
+pub async fn reconnect<T: Connection>(
+    connection: &mut T,
+    policy: &RetryPolicy,
+    statistics: &ReconnectStats,
+) -> Result<(), ConnectionError> {
+    let delay = policy.delay(statistics.next_attempt());
+    if !delay.is_zero() {
+        tokio::time::sleep(delay).await;
+    }
+
+    let result = connection.reconnect().await;
+    match result {
+        Ok(()) => statistics.record_success(delay),
+        Err(_) => statistics.record_failure(delay),
+    }
+    result
+}
+
+/// 重新連線統計
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件（包含登記至 [`crate::ConnectionStatsRegistry`] 的 [`crate::ConnectionStats`]）會存取同一份資料
+#[derive(Debug, Clone, Default)]
+pub struct ReconnectStats(Arc<Mutex<ReconnectSnapshot>>);
+
+/// 重新連線統計快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconnectSnapshot {
+    /// 重新連線次數
+    pub attempt_count: u64,
+    /// 重新連線失敗次數
+    pub failed_count: u64,
+    /// 目前連續失敗的次數，重新連線成功後歸零
+    pub consecutive_failures: u32,
+    /// 最後一次重新連線前的等待時間，序列化時以毫秒數表示
+    #[serde(rename = "last_delay_ms", with = "crate::millis")]
+    pub last_delay: Duration,
+    /// 累計等待時間，序列化時以毫秒數表示
+    #[serde(rename = "total_delay_ms", with = "crate::millis")]
+    pub total_delay: Duration,
+}
+
+impl ReconnectSnapshot {
+    /// 是否沒有任何重新連線紀錄
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.attempt_count == 0
+    }
+}
+
+impl ReconnectStats {
+    /// 下一次重新連線是連續失敗後的第幾次，用於 [`RetryPolicy::delay()`]
+    #[must_use]
+    pub fn next_attempt(&self) -> u32 {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .consecutive_failures
+            .saturating_add(1)
+    }
+
+    /// 記錄重新連線成功
+    ///
+    /// # 參數
+    /// - `delay`：重新連線前的等待時間
+    pub fn record_success(&self, delay: Duration) {
+        record(
+            &mut self.0.lock().unwrap_or_else(PoisonError::into_inner),
+            delay,
+            true,
+        );
+    }
+
+    /// 記錄重新連線失敗
+    ///
+    /// # 參數
+    /// - `delay`：重新連線前的等待時間
+    pub fn record_failure(&self, delay: Duration) {
+        record(
+            &mut self.0.lock().unwrap_or_else(PoisonError::into_inner),
+            delay,
+            false,
+        );
+    }
+
+    /// 取得統計快照
+    #[must_use]
+    pub fn snapshot(&self) -> ReconnectSnapshot {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// 清除統計
+    pub fn reset(&self) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = ReconnectSnapshot::default();
+    }
+}
+
+const fn record(totals: &mut ReconnectSnapshot, delay: Duration, succeeded: bool) {
+    totals.attempt_count += 1;
+    totals.last_delay = delay;
+    totals.total_delay = totals.total_delay.saturating_add(delay);
+    if succeeded {
+        totals.consecutive_failures = 0;
+    } else {
+        totals.failed_count += 1;
+        totals.consecutive_failures = totals.consecutive_failures.saturating_add(1);
+    }
+}
@@ -0,0 +1,1318 @@
+//! Siemens S7 參考實作（需啟用 `s7` feature）
+//!
+//! 以 S7 通訊協定（ISO-on-TCP ，RFC 1006）讀寫 S7-300 、S7-400 、S7-1200 與 S7-1500 PLC 的資料區，埠號為 102 ：
+//!
+//! - 連線時協商 PDU 長度（參見 [`S7Config::pdu_size`]），讀寫請求的長度都不會超過協商的結果
+//! - 讀取：同一輪輪詢中的自動更新點位以一個 Read Var 請求合併讀取，每個請求最多 [`MAX_ITEMS_PER_REQUEST`] 個項目，
+//!   且請求與回覆都不超過 PDU 長度；讀取結果保留更新間隔的一半，期間內的其他點位直接使用該結果。
+//!   超過單一 PDU 的點位（如長字串、陣列）會分為多個請求讀取
+//! - 寫入：以 Write Var 寫入，位元位址只寫入該位元，超過單一 PDU 的資料分為多個請求寫入
+//! - 資料型別與大端序的解碼工具參見 [`data`]
+//!
+//! S7-1200/1500 需在 TIA Portal 中允許 PUT/GET 存取，並關閉 DB 的「最佳化區塊存取」，否則 PLC 會拒絕讀寫。
+//!
+//! 點位設定：
+//!
+//! | 欄位 | 說明 |
+//! | --- | --- |
+//! | `address` | 位址，參見 [`S7Address`] ，如 `DB1.DBX0.3` 、`DB1.DBW2` 、`DB10.16` 、`MW20` 、`I0.1` |
+//! | `data_type` | 資料型別，參見 [`S7Type`] ；未設定時位元位址為 `BOOL` ，其他位址依長度為 `BYTE` 、`WORD` 或 `DWORD` |
+//! | `elements` | 陣列元素數量，大於 1 時數值為 JSON 陣列，未設定時為 1 |
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{
+//!     TargetDefinition,
+//!     s7::{Area, S7Config, S7Request, S7Type},
+//! };
+//! use serde_json::json;
+//!
+//! let config: S7Config = serde_json::from_value(json!({
+//!     "host": "192.168.0.1",
+//!     "port": 102,
+//!     "slot": 1,
+//! }))
+//! .unwrap();
+//! assert_eq!(config.rack, 0);
+//! assert_eq!(config.remote_tsap(), 0x0101);
+//!
+//! let definition: TargetDefinition = serde_json::from_value(json!({
+//!     "name": "出水溫度",
+//!     "address": "DB10.DBD4",
+//!     "data_type": "REAL",
+//! }))
+//! .unwrap();
+//! let request = S7Request::parse(&definition).unwrap();
+//! assert_eq!(request.address.area, Area::DataBlock);
+//! assert_eq!(request.address.db, 10);
+//! assert_eq!(request.address.start, 4);
+//! assert_eq!(request.data_type, S7Type::Real);
+//!
+//! let definition: TargetDefinition = serde_json::from_value(json!({
+//!     "name": "水泵運轉",
+//!     "address": "M20.3",
+//! }))
+//! .unwrap();
+//! let request = S7Request::parse(&definition).unwrap();
+//! assert_eq!(request.address.bit, Some(3));
+//! assert_eq!(request.data_type, S7Type::Bool);
+//! ```
+
+pub mod data;
+
+use std::{
+    borrow::Cow, error::Error, fmt::Display, io::ErrorKind, net::SocketAddr, sync::Arc,
+    time::Duration,
+};
+
+use bytes::{Bytes, BytesMut};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{io::AsyncWriteExt, net::TcpStream};
+
+pub use self::data::S7Type;
+use crate::{
+    Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+    ConnectionTargets, DeviceStateRequest, DeviceStateResponse, DeviceStateWrite, InitedTarget,
+    RemoteAddress, RequestContext, Target, TargetDefinition,
+    cache::{CacheLookup, CachePolicy, ResponseCache},
+    definition::InvalidTarget,
+    fixture::FrameDecoder,
+    transport::{
+        frame::FrameReader,
+        tcp::{self, TcpEndpoint},
+    },
+    value::ConversionError,
+};
+
+/// 預設的更新間隔
+pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 預設的回覆逾時
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 預設要求的 PDU 長度，S7-1500 支援 960 ，S7-300 、S7-1200 通常協商為 240
+pub const DEFAULT_PDU_SIZE: u16 = 960;
+
+/// 可接受的最小 PDU 長度
+pub const MIN_PDU_SIZE: u16 = 64;
+
+/// 單一 Read Var 請求的項目數量上限
+pub const MAX_ITEMS_PER_REQUEST: usize = 20;
+
+/// TPKT 標頭長度
+const TPKT_LENGTH: usize = 4;
+
+/// COTP 資料封包標頭：長度、DT 、最後一個封包（EOT）
+const COTP_DATA: [u8; 3] = [0x02, 0xF0, 0x80];
+
+const COTP_CONNECTION_REQUEST: u8 = 0xE0;
+const COTP_CONNECTION_CONFIRM: u8 = 0xD0;
+
+const PROTOCOL_ID: u8 = 0x32;
+const JOB: u8 = 0x01;
+const ACK_DATA: u8 = 0x03;
+
+const SETUP_COMMUNICATION: u8 = 0xF0;
+const READ_VAR: u8 = 0x04;
+const WRITE_VAR: u8 = 0x05;
+
+/// Job 標頭長度
+const JOB_HEADER_LENGTH: usize = 10;
+/// Ack-Data 標頭長度（包含錯誤類別與代碼）
+const ACK_HEADER_LENGTH: usize = 12;
+/// 讀寫請求的參數標頭：功能碼、項目數量
+const PARAMETER_HEADER_LENGTH: usize = 2;
+/// 讀寫請求每個項目的參數長度
+const ITEM_LENGTH: usize = 12;
+/// 回覆與寫入資料中每個項目的標頭：回覆碼、傳輸大小、長度
+const DATA_HEADER_LENGTH: usize = 4;
+
+/// 項目的傳輸大小：位元
+const TRANSPORT_BIT: u8 = 0x01;
+/// 項目的傳輸大小：位元組
+const TRANSPORT_BYTE: u8 = 0x02;
+/// 資料的傳輸大小：位元，長度以位元計
+const DATA_BIT: u8 = 0x03;
+/// 資料的傳輸大小：位元組，長度以位元計
+const DATA_BYTE: u8 = 0x04;
+
+/// 單一項目的 Read Var 回覆中，資料以外的長度：Ack-Data 標頭、參數標頭與資料標頭
+const READ_OVERHEAD: u16 = 18;
+
+/// 項目成功的回覆碼
+const ITEM_SUCCESS: u8 = 0xFF;
+
+const fn default_update_interval() -> Duration {
+    DEFAULT_UPDATE_INTERVAL
+}
+
+const fn default_timeout() -> Duration {
+    DEFAULT_TIMEOUT
+}
+
+const fn default_pdu_size() -> u16 {
+    DEFAULT_PDU_SIZE
+}
+
+/// 連線參數
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct S7Config {
+    /// PLC 的位址，可經由代理伺服器連線
+    #[serde(flatten)]
+    pub endpoint: TcpEndpoint,
+    /// CPU 所在的機架，預設為 0
+    #[serde(default)]
+    pub rack: u8,
+    /// CPU 所在的槽位，S7-300 通常為 2 ，S7-1200/1500 為 0 或 1
+    pub slot: u8,
+    /// 遠端 TSAP ，設定後取代由機架與槽位計算的結果，用於 LOGO! 或透過 CP 模組連線的 PLC
+    #[serde(default)]
+    pub remote_tsap: Option<u16>,
+    /// 要求的 PDU 長度，實際長度由 PLC 決定，預設為 [`DEFAULT_PDU_SIZE`] ，不得小於 [`MIN_PDU_SIZE`]
+    #[serde(default = "default_pdu_size")]
+    pub pdu_size: u16,
+    /// 更新間隔，序列化時以毫秒數表示，預設為 [`DEFAULT_UPDATE_INTERVAL`]
+    #[serde(
+        rename = "update_interval_ms",
+        with = "crate::millis",
+        default = "default_update_interval"
+    )]
+    pub update_interval: Duration,
+    /// 回覆逾時，序列化時以毫秒數表示，預設為 [`DEFAULT_TIMEOUT`]
+    #[serde(
+        rename = "timeout_ms",
+        with = "crate::millis",
+        default = "default_timeout"
+    )]
+    pub timeout: Duration,
+    /// 最高重試次數，參見 [`ConnectionArtifact::max_retry_count`]
+    #[serde(default)]
+    pub max_retry_count: Option<u32>,
+}
+
+impl ConnectionConfig for S7Config {}
+
+impl S7Config {
+    /// 遠端 TSAP ：以 PG 連線存取機架與槽位上的 CPU
+    #[must_use]
+    pub fn remote_tsap(&self) -> u16 {
+        self.remote_tsap
+            .unwrap_or_else(|| 0x0100 | u16::from(self.rack) << 5 | u16::from(self.slot & 0x1F))
+    }
+}
+
+/// 資料區
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Area {
+    /// 輸入（`I` 、`E`）
+    Input,
+    /// 輸出（`Q` 、`A`）
+    Output,
+    /// 記憶體（`M`）
+    Merker,
+    /// 資料塊（`DB`）
+    DataBlock,
+}
+
+impl Area {
+    const fn code(self) -> u8 {
+        match self {
+            Self::Input => 0x81,
+            Self::Output => 0x82,
+            Self::Merker => 0x83,
+            Self::DataBlock => 0x84,
+        }
+    }
+}
+
+/// 位址中的存取長度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Width {
+    /// 位元（`X`）
+    Bit,
+    /// 位元組（`B`）
+    Byte,
+    /// 字組（`W`），2 個位元組
+    Word,
+    /// 雙字組（`D`），4 個位元組
+    Dword,
+}
+
+impl Width {
+    const fn letter(self) -> char {
+        match self {
+            Self::Bit => 'X',
+            Self::Byte => 'B',
+            Self::Word => 'W',
+            Self::Dword => 'D',
+        }
+    }
+}
+
+/// 位址
+///
+/// 支援 STEP 7 的絕對位址，也可以省略存取長度，直接以 `DB編號.位元組[.位元]` 表示：
+///
+/// | 格式 | 範例 |
+/// | --- | --- |
+/// | 資料塊 | `DB1.DBX0.3` 、`DB1.DBB2` 、`DB1.DBW4` 、`DB1.DBD6` 、`DB1.8` 、`DB1.8.1` |
+/// | 記憶體 | `M10.2` 、`MX10.2` 、`MB10` 、`MW10` 、`MD10` |
+/// | 輸入 | `I0.1` 、`IB0` 、`IW0` 、`ID0` ，也可以使用德文助記符 `E` |
+/// | 輸出 | `Q0.1` 、`QB0` 、`QW0` 、`QD0` ，也可以使用德文助記符 `A` |
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::s7::{Area, S7Address, Width};
+///
+/// let address = S7Address::parse("db5.dbx12.7").unwrap();
+/// assert_eq!((address.area, address.db, address.start, address.bit), (Area::DataBlock, 5, 12, Some(7)));
+/// assert_eq!(address.to_string(), "DB5.DBX12.7");
+///
+/// let address = S7Address::parse("EW4").unwrap();
+/// assert_eq!((address.area, address.width), (Area::Input, Some(Width::Word)));
+/// assert_eq!(address.to_string(), "IW4");
+///
+/// assert!(S7Address::parse("DB1.DBX0").is_none());
+/// assert!(S7Address::parse("MW10.1").is_none());
+/// assert!(S7Address::parse("M10.8").is_none());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct S7Address {
+    /// 資料區
+    pub area: Area,
+    /// 資料塊編號，其他資料區為 0
+    pub db: u16,
+    /// 起始位元組
+    pub start: u32,
+    /// 位元編號
+    pub bit: Option<u8>,
+    /// 存取長度，省略時為 [`None`]
+    pub width: Option<Width>,
+}
+
+impl S7Address {
+    /// 解析位址，不分大小寫，格式錯誤時回傳 [`None`]
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim().to_ascii_uppercase();
+        let (area, db, rest) = if let Some(rest) = text.strip_prefix("DB") {
+            let (db, rest) = rest.split_once('.')?;
+            let db = db.parse().ok()?;
+            (Area::DataBlock, db, rest.strip_prefix("DB").unwrap_or(rest))
+        } else {
+            let area = match text.chars().next()? {
+                'I' | 'E' => Area::Input,
+                'Q' | 'A' => Area::Output,
+                'M' => Area::Merker,
+                _ => return None,
+            };
+            (area, 0, &text[1..])
+        };
+
+        let width = match rest.chars().next()? {
+            'X' => Some(Width::Bit),
+            'B' => Some(Width::Byte),
+            'W' => Some(Width::Word),
+            'D' => Some(Width::Dword),
+            _ => None,
+        };
+        let rest = if width.is_some() { &rest[1..] } else { rest };
+        let (start, bit) = match rest.split_once('.') {
+            Some((start, bit)) => (start, Some(bit.parse().ok().filter(|bit| *bit <= 7)?)),
+            None => (rest, None),
+        };
+        // 位址欄位為位元位移，長度 3 個位元組
+        let start = start.parse().ok().filter(|start| *start < 1 << 21)?;
+        match (width, bit) {
+            (Some(Width::Bit), None)
+            | (Some(Width::Byte | Width::Word | Width::Dword), Some(_)) => None,
+            _ => Some(Self {
+                area,
+                db,
+                start,
+                bit,
+                width,
+            }),
+        }
+    }
+}
+
+impl Display for S7Address {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.area {
+            Area::DataBlock => write!(f, "DB{}.", self.db)?,
+            Area::Input => f.write_str("I")?,
+            Area::Output => f.write_str("Q")?,
+            Area::Merker => f.write_str("M")?,
+        }
+        if let Some(width) = self.width {
+            if self.area == Area::DataBlock {
+                f.write_str("DB")?;
+            }
+            write!(f, "{}", width.letter())?;
+        }
+        write!(f, "{}", self.start)?;
+        if let Some(bit) = self.bit {
+            write!(f, ".{bit}")?;
+        }
+        Ok(())
+    }
+}
+
+/// 請求無法完成的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum S7Error {
+    /// PLC 回覆的錯誤類別與代碼
+    Header {
+        /// 錯誤類別
+        class: u8,
+        /// 錯誤代碼
+        code: u8,
+    },
+    /// 讀寫項目的回覆碼
+    Item(u8),
+}
+
+impl Display for S7Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Header { class, code } => write!(
+                f,
+                "PLC 拒絕請求（錯誤類別 0x{class:02X} ，代碼 0x{code:02X}）"
+            ),
+            Self::Item(code) => {
+                let description = match code {
+                    0x01 => "硬體錯誤",
+                    0x03 => "不允許存取，請確認 PLC 允許 PUT/GET 並關閉 DB 的最佳化區塊存取",
+                    0x05 => "位址超出範圍",
+                    0x06 => "不支援的資料型別",
+                    0x07 => "資料型別不一致",
+                    0x0A => "物件不存在",
+                    _ => "未知的錯誤",
+                };
+                write!(f, "{description}（0x{code:02X}）")
+            }
+        }
+    }
+}
+
+impl Error for S7Error {}
+
+fn malformed() -> ConnectionError {
+    ConnectionError::Protocol("無法解析的 S7 回覆".to_owned())
+}
+
+/// 讀取的範圍
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct S7Read {
+    /// 資料區
+    pub area: Area,
+    /// 資料塊編號
+    pub db: u16,
+    /// 起始位元組
+    pub start: u32,
+    /// 長度（位元組）
+    pub length: u16,
+}
+
+impl S7Read {
+    /// 請求中的項目：以位元組讀取
+    const fn item(&self, start: u32, length: u16) -> [u8; ITEM_LENGTH] {
+        let address = (start * 8).to_be_bytes();
+        let length = length.to_be_bytes();
+        let db = self.db.to_be_bytes();
+        [
+            0x12,
+            0x0A,
+            0x10,
+            TRANSPORT_BYTE,
+            length[0],
+            length[1],
+            db[0],
+            db[1],
+            self.area.code(),
+            address[1],
+            address[2],
+            address[3],
+        ]
+    }
+
+    /// 回覆中的長度（含標頭與補齊的位元組）
+    fn reply_length(&self) -> usize {
+        DATA_HEADER_LENGTH + usize::from(self.length).next_multiple_of(2)
+    }
+}
+
+/// 依 PDU 長度將讀取分組，每組為一個 Read Var 請求；超過單一 PDU 的讀取自成一組
+fn plan(reads: &[S7Read], pdu_size: u16) -> Vec<std::ops::Range<usize>> {
+    let pdu_size = usize::from(pdu_size);
+    let mut groups = Vec::new();
+    let mut start = 0;
+    let (mut request, mut reply) = (0, 0);
+    for (index, read) in reads.iter().enumerate() {
+        let fits = index > start
+            && index - start < MAX_ITEMS_PER_REQUEST
+            && request + ITEM_LENGTH <= pdu_size
+            && reply + read.reply_length() <= pdu_size;
+        if !fits {
+            if index > start {
+                groups.push(start..index);
+            }
+            start = index;
+            request = JOB_HEADER_LENGTH + PARAMETER_HEADER_LENGTH;
+            reply = ACK_HEADER_LENGTH + PARAMETER_HEADER_LENGTH;
+        }
+        request += ITEM_LENGTH;
+        reply += read.reply_length();
+    }
+    if start < reads.len() {
+        groups.push(start..reads.len());
+    }
+    groups
+}
+
+/// 取出 Ack-Data 的參數與資料，`pdu` 需已確認為 Ack-Data
+fn ack_payload(pdu: &Bytes) -> Result<(Bytes, Bytes), ConnectionError> {
+    let header = pdu.get(..ACK_HEADER_LENGTH).ok_or_else(malformed)?;
+    let (class, code) = (header[10], header[11]);
+    if class != 0 || code != 0 {
+        return Err(ConnectionError::custom(S7Error::Header { class, code }));
+    }
+    let parameter_length = usize::from(u16::from_be_bytes([header[6], header[7]]));
+    let data_length = usize::from(u16::from_be_bytes([header[8], header[9]]));
+    let data_start = ACK_HEADER_LENGTH + parameter_length;
+    if pdu.len() < data_start + data_length {
+        return Err(malformed());
+    }
+    Ok((
+        pdu.slice(ACK_HEADER_LENGTH..data_start),
+        pdu.slice(data_start..data_start + data_length),
+    ))
+}
+
+/// 解析 Read Var 回覆的資料中的 `count` 個項目
+fn read_items(data: &Bytes, count: usize) -> Result<Vec<Result<Bytes, S7Error>>, ConnectionError> {
+    let mut results = Vec::with_capacity(count);
+    let mut cursor = 0;
+    for _ in 0..count {
+        let header = data
+            .get(cursor..cursor + DATA_HEADER_LENGTH)
+            .ok_or_else(malformed)?;
+        if header[0] != ITEM_SUCCESS {
+            results.push(Err(S7Error::Item(header[0])));
+            // 失敗的項目只有回覆碼與傳輸大小
+            cursor += DATA_HEADER_LENGTH;
+            continue;
+        }
+        let length = usize::from(u16::from_be_bytes([header[2], header[3]]));
+        let length = if matches!(header[1], DATA_BIT | DATA_BYTE) {
+            length.div_ceil(8)
+        } else {
+            length
+        };
+        let start = cursor + DATA_HEADER_LENGTH;
+        if data.len() < start + length {
+            return Err(malformed());
+        }
+        results.push(Ok(data.slice(start..start + length)));
+        cursor = (start + length).next_multiple_of(2);
+    }
+    Ok(results)
+}
+
+/// S7 通訊的工作階段
+struct Session {
+    stream: FrameReader<TcpStream>,
+    peer: Option<SocketAddr>,
+    /// 協商的 PDU 長度
+    pdu_size: u16,
+    reference: u16,
+    timeout: Duration,
+}
+
+impl Session {
+    async fn open(config: &S7Config) -> Result<Self, ConnectionError> {
+        let stream = tokio::time::timeout(config.timeout, tcp::connect(&config.endpoint)).await??;
+        stream.set_nodelay(true)?;
+        let peer = stream.peer_addr().ok();
+        let mut session = Self {
+            stream: FrameReader::new(stream),
+            peer,
+            pdu_size: config.pdu_size,
+            reference: 0,
+            timeout: config.timeout,
+        };
+        tokio::time::timeout(config.timeout, session.handshake(config))
+            .await
+            .map_err(ConnectionError::from)
+            .flatten()?;
+        Ok(session)
+    }
+
+    /// 建立 COTP 連線並協商 PDU 長度
+    async fn handshake(&mut self, config: &S7Config) -> Result<(), ConnectionError> {
+        let remote = config.remote_tsap().to_be_bytes();
+        // 目的與來源參考、類別 0 ，參數：TPDU 長度 1024 、來源 TSAP 、目的 TSAP
+        self.write_tpkt(&[
+            0x11,
+            COTP_CONNECTION_REQUEST,
+            0x00,
+            0x00,
+            0x00,
+            0x01,
+            0x00,
+            0xC0,
+            0x01,
+            0x0A,
+            0xC1,
+            0x02,
+            0x01,
+            0x00,
+            0xC2,
+            0x02,
+            remote[0],
+            remote[1],
+        ])
+        .await?;
+        let confirm = self.read_tpkt().await?;
+        if confirm.get(1).map(|kind| kind & 0xF0) != Some(COTP_CONNECTION_CONFIRM) {
+            return Err(ConnectionError::Protocol(
+                "PLC 拒絕 COTP 連線，請確認機架、槽位或 TSAP".to_owned(),
+            ));
+        }
+
+        // 呼叫端與被呼叫端的平行作業數量各 1
+        let mut parameter = vec![SETUP_COMMUNICATION, 0x00, 0x00, 0x01, 0x00, 0x01];
+        parameter.extend_from_slice(&config.pdu_size.to_be_bytes());
+        let (parameter, _) = self.exchange(&parameter, &[]).await?;
+        let negotiated = parameter.get(6..8).ok_or_else(malformed)?;
+        self.pdu_size = u16::from_be_bytes([negotiated[0], negotiated[1]]).min(config.pdu_size);
+        if self.pdu_size < MIN_PDU_SIZE {
+            return Err(ConnectionError::Protocol(format!(
+                "PDU 長度 {} 過小，至少需為 {MIN_PDU_SIZE}",
+                self.pdu_size
+            )));
+        }
+        Ok(())
+    }
+
+    async fn write_tpkt(&mut self, payload: &[u8]) -> Result<(), ConnectionError> {
+        let length = u16::try_from(TPKT_LENGTH + payload.len())
+            .map_err(|_| ConnectionError::Protocol("請求過長".to_owned()))?;
+        let mut frame = Vec::with_capacity(usize::from(length));
+        frame.extend_from_slice(&[0x03, 0x00]);
+        frame.extend_from_slice(&length.to_be_bytes());
+        frame.extend_from_slice(payload);
+        self.stream.get_mut().write_all(&frame).await?;
+        Ok(())
+    }
+
+    /// 讀取一個 TPKT 封包，回傳 COTP 標頭與其後的資料
+    async fn read_tpkt(&mut self) -> Result<Bytes, ConnectionError> {
+        let header = self.stream.read_frame(TPKT_LENGTH).await?;
+        if header[0] != 0x03 {
+            self.stream.discard();
+            return Err(malformed());
+        }
+        let length = usize::from(u16::from_be_bytes([header[2], header[3]]));
+        if length < TPKT_LENGTH + 2 {
+            self.stream.discard();
+            return Err(malformed());
+        }
+        Ok(self.stream.read_frame(length - TPKT_LENGTH).await?)
+    }
+
+    /// 送出 Job 並等待 Ack-Data
+    ///
+    /// # 回傳值
+    /// 回覆的參數與資料
+    async fn exchange(
+        &mut self,
+        parameter: &[u8],
+        data: &[u8],
+    ) -> Result<(Bytes, Bytes), ConnectionError> {
+        self.reference = self.reference.wrapping_add(1);
+        let (Ok(parameter_length), Ok(data_length)) =
+            (u16::try_from(parameter.len()), u16::try_from(data.len()))
+        else {
+            return Err(ConnectionError::Protocol("請求過長".to_owned()));
+        };
+        let mut payload =
+            Vec::with_capacity(COTP_DATA.len() + JOB_HEADER_LENGTH + parameter.len() + data.len());
+        payload.extend_from_slice(&COTP_DATA);
+        payload.extend_from_slice(&[PROTOCOL_ID, JOB, 0x00, 0x00]);
+        payload.extend_from_slice(&self.reference.to_be_bytes());
+        payload.extend_from_slice(&parameter_length.to_be_bytes());
+        payload.extend_from_slice(&data_length.to_be_bytes());
+        payload.extend_from_slice(parameter);
+        payload.extend_from_slice(data);
+        self.write_tpkt(&payload).await?;
+
+        // 回覆可能分為多個 COTP 資料封包，直到 EOT
+        let mut pdu = BytesMut::new();
+        loop {
+            let packet = self.read_tpkt().await?;
+            let header = usize::from(packet[0]) + 1;
+            if packet.get(1) != Some(&COTP_DATA[1]) || packet.len() < header {
+                self.stream.discard();
+                return Err(malformed());
+            }
+            pdu.extend_from_slice(&packet[header..]);
+            if packet[2] & 0x80 != 0 {
+                break;
+            }
+        }
+        let pdu = pdu.freeze();
+
+        let header = pdu.get(..ACK_HEADER_LENGTH).ok_or_else(malformed)?;
+        if header[0] != PROTOCOL_ID
+            || header[1] != ACK_DATA
+            || u16::from_be_bytes([header[4], header[5]]) != self.reference
+        {
+            self.stream.discard();
+            return Err(malformed());
+        }
+        ack_payload(&pdu)
+    }
+
+    async fn request(
+        &mut self,
+        parameter: &[u8],
+        data: &[u8],
+    ) -> Result<(Bytes, Bytes), ConnectionError> {
+        let timeout = self.timeout;
+        tokio::time::timeout(timeout, self.exchange(parameter, data))
+            .await
+            .map_err(ConnectionError::from)
+            .flatten()
+    }
+
+    /// 以一個 Read Var 請求讀取多個項目，`items` 為讀取的範圍、起始位元組與長度
+    async fn read_var(
+        &mut self,
+        items: &[(&S7Read, u32, u16)],
+    ) -> Result<Vec<Result<Bytes, S7Error>>, ConnectionError> {
+        let mut parameter = Vec::with_capacity(PARAMETER_HEADER_LENGTH + items.len() * ITEM_LENGTH);
+        parameter.extend_from_slice(&[
+            READ_VAR,
+            u8::try_from(items.len()).map_err(|_| malformed())?,
+        ]);
+        for (read, start, length) in items {
+            parameter.extend_from_slice(&read.item(*start, *length));
+        }
+        let (_, data) = self.request(&parameter, &[]).await?;
+        read_items(&data, items.len())
+    }
+
+    /// 以 Write Var 寫入單一項目
+    async fn write_var(
+        &mut self,
+        address: &S7Address,
+        start: u32,
+        bit: Option<u8>,
+        data: &[u8],
+    ) -> Result<(), ConnectionError> {
+        let (transport, data_transport, count, bits) = match bit {
+            Some(bit) => (TRANSPORT_BIT, DATA_BIT, 1, start * 8 + u32::from(bit)),
+            None => (
+                TRANSPORT_BYTE,
+                DATA_BYTE,
+                u16::try_from(data.len()).map_err(|_| malformed())?,
+                start * 8,
+            ),
+        };
+        let position = bits.to_be_bytes();
+        let count_bytes = count.to_be_bytes();
+        let db = address.db.to_be_bytes();
+        let parameter = [
+            WRITE_VAR,
+            1,
+            0x12,
+            0x0A,
+            0x10,
+            transport,
+            count_bytes[0],
+            count_bytes[1],
+            db[0],
+            db[1],
+            address.area.code(),
+            position[1],
+            position[2],
+            position[3],
+        ];
+        let length = if bit.is_some() { 1 } else { count * 8 };
+        let mut payload = Vec::with_capacity(DATA_HEADER_LENGTH + data.len());
+        payload.extend_from_slice(&[0x00, data_transport]);
+        payload.extend_from_slice(&length.to_be_bytes());
+        payload.extend_from_slice(data);
+
+        let (_, reply) = self.request(&parameter, &payload).await?;
+        match reply.first() {
+            Some(&ITEM_SUCCESS) => Ok(()),
+            Some(code) => Err(ConnectionError::custom(S7Error::Item(*code))),
+            None => Err(malformed()),
+        }
+    }
+}
+
+/// 合併讀取的範圍
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadBatch {
+    /// 範圍，依順序排列
+    pub reads: Arc<[S7Read]>,
+}
+
+impl ReadBatch {
+    fn key(&self) -> String {
+        let read = &self.reads[0];
+        format!("{:?}:{}:{}:{}", read.area, read.db, read.start, read.length)
+    }
+}
+
+/// 點位
+#[derive(Debug, Clone)]
+pub struct S7Target(pub TargetDefinition);
+
+impl Target for S7Target {}
+
+/// 讀取請求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct S7Request {
+    /// 位址
+    pub address: S7Address,
+    /// 資料型別
+    pub data_type: S7Type,
+    /// 陣列元素數量
+    pub elements: u16,
+    /// 包含點位的合併讀取，單獨讀取的點位為 [`None`]
+    pub batch: Option<ReadBatch>,
+}
+
+impl DeviceStateRequest for S7Request {}
+
+impl S7Request {
+    /// 由通用點位定義轉換，欄位說明參見 [`crate::s7`]
+    ///
+    /// # Errors
+    /// 位址、資料型別或元素數量無效，或資料型別與位址的存取長度不符時回傳 [`InvalidTarget`]
+    pub fn parse(definition: &TargetDefinition) -> Result<Self, InvalidTarget> {
+        let invalid = |reason: String| InvalidTarget {
+            target: definition.name.clone(),
+            reason,
+        };
+
+        let address = S7Address::parse(&definition.address)
+            .ok_or_else(|| invalid(format!("無效的位址「{}」", definition.address)))?;
+        let data_type = match definition.data_type.as_deref() {
+            Some(data_type) => S7Type::parse(data_type)
+                .ok_or_else(|| invalid(format!("不支援的資料型別「{data_type}」")))?,
+            None if address.bit.is_some() => S7Type::Bool,
+            None => match address.width {
+                Some(Width::Word) => S7Type::Word,
+                Some(Width::Dword) => S7Type::Dword,
+                _ => S7Type::Byte,
+            },
+        };
+        if (data_type == S7Type::Bool) != address.bit.is_some() {
+            return Err(invalid(format!(
+                "BOOL 需使用位元位址，位元位址只能使用 BOOL ：{address}"
+            )));
+        }
+        let width = match address.width {
+            Some(Width::Word) => Some(2),
+            Some(Width::Dword) => Some(4),
+            _ => None,
+        };
+        if width.is_some_and(|width| width != data_type.size()) {
+            return Err(invalid(format!("{data_type} 的長度與位址 {address} 不符")));
+        }
+        let elements = definition
+            .extra
+            .get("elements")
+            .map(|elements| {
+                elements
+                    .as_u64()
+                    .and_then(|elements| u16::try_from(elements).ok())
+                    .filter(|elements| *elements >= 1)
+                    .ok_or_else(|| invalid(format!("elements 需為 1 至 65535 ：{elements}")))
+            })
+            .transpose()?
+            .unwrap_or(1);
+        if data_type == S7Type::Bool && elements > 1 {
+            return Err(invalid("位元位址不能讀取多個元素".to_owned()));
+        }
+        if data_type.size() * usize::from(elements) > usize::from(u16::MAX) {
+            return Err(invalid("讀取的長度超過 65535 個位元組".to_owned()));
+        }
+
+        Ok(Self {
+            address,
+            data_type,
+            elements,
+            batch: None,
+        })
+    }
+
+    #[expect(clippy::cast_possible_truncation)]
+    fn read(&self) -> S7Read {
+        S7Read {
+            area: self.address.area,
+            db: self.address.db,
+            start: self.address.start,
+            length: (self.data_type.size() * usize::from(self.elements)) as u16,
+        }
+    }
+
+    /// 以讀取的資料回覆
+    fn respond(&self, raw: Bytes) -> Result<S7Response, ConnectionError> {
+        let value = self.decode(&raw).ok_or_else(|| {
+            ConnectionError::Protocol(format!("位址 {} 的資料長度不符", self.address))
+        })?;
+        Ok(S7Response {
+            value,
+            data_type: self.data_type,
+            raw,
+        })
+    }
+
+    /// 解碼讀取的資料
+    fn decode(&self, data: &[u8]) -> Option<Value> {
+        if let Some(bit) = self.address.bit {
+            return data::get_bool(data, 0, bit).map(Value::Bool);
+        }
+        if self.elements == 1 {
+            return self.data_type.decode(data);
+        }
+        data.chunks(self.data_type.size())
+            .take(usize::from(self.elements))
+            .map(|element| self.data_type.decode(element))
+            .collect::<Option<Vec<_>>>()
+            .filter(|elements| elements.len() == usize::from(self.elements))
+            .map(Value::Array)
+    }
+}
+
+/// 讀取回覆
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct S7Response {
+    /// 解碼後的數值，NaN 或無限大的浮點數為 [`Value::Null`]
+    pub value: Value,
+    /// 資料型別
+    pub data_type: S7Type,
+    /// 原始資料，位元位址為所在的位元組
+    pub raw: Bytes,
+}
+
+impl DeviceStateResponse for S7Response {
+    fn to_value(&self) -> Result<Cow<'_, Value>, ConversionError> {
+        let non_finite = match self.data_type {
+            S7Type::Real if self.value.is_null() => data::get_real(&self.raw, 0).map(f64::from),
+            S7Type::Lreal if self.value.is_null() => data::get_lreal(&self.raw, 0),
+            _ => return Ok(Cow::Borrowed(&self.value)),
+        };
+        Err(non_finite.map_or_else(
+            || ConversionError::InvalidRaw(format!("{} 的資料長度不足", self.data_type)),
+            ConversionError::NonFinite,
+        ))
+    }
+
+    fn raw(&self) -> Option<Bytes> {
+        Some(self.raw.clone())
+    }
+}
+
+/// 寫入請求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct S7Write {
+    /// 位址
+    pub address: S7Address,
+    /// 編碼後的資料，位元位址為 0 或 1
+    pub data: Vec<u8>,
+    /// 包含點位的合併讀取，寫入後會清除該次讀取的結果
+    pub batch: Option<ReadBatch>,
+}
+
+impl DeviceStateWrite for S7Write {}
+
+/// S7 PLC 連線
+///
+/// 合併讀取的結果會保留更新間隔的一半，期間內的其他點位直接使用該結果，並跳過等待間隔
+pub struct S7Connection {
+    config: S7Config,
+    session: Option<Session>,
+    /// 最近一次協商的 PDU 長度
+    pdu_size: u16,
+    reads: ResponseCache<Arc<[Result<Bytes, S7Error>]>>,
+    batches: Vec<ReadBatch>,
+    remote_address: RemoteAddress,
+    rejected: Vec<InvalidTarget>,
+}
+
+impl S7Connection {
+    /// 初始化點位時被略過的點位
+    #[must_use]
+    pub fn rejected_targets(&self) -> &[InvalidTarget] {
+        &self.rejected
+    }
+
+    /// 協商的 PDU 長度
+    #[must_use]
+    pub const fn pdu_size(&self) -> u16 {
+        self.pdu_size
+    }
+
+    fn set_ttl(&self) {
+        let policy = CachePolicy {
+            ttl: self.config.update_interval / 2,
+            stale_while_revalidate: Duration::ZERO,
+        };
+        for batch in &self.batches {
+            self.reads.enable(batch.key(), policy);
+        }
+    }
+
+    /// 取得工作階段，連線已中斷時先重新連線
+    async fn session(&mut self) -> Result<&mut Session, ConnectionError> {
+        if self.session.is_none() {
+            let session = Session::open(&self.config).await?;
+            self.remote_address.set(session.peer);
+            self.pdu_size = session.pdu_size;
+            self.session = Some(session);
+        }
+        self.session
+            .as_mut()
+            .ok_or_else(|| std::io::Error::from(ErrorKind::NotConnected).into())
+    }
+
+    /// 讀寫失敗或回覆無法解析時關閉連線，讓下一個請求重新連線
+    fn settle<T>(&mut self, result: Result<T, ConnectionError>) -> Result<T, ConnectionError> {
+        if let Err(
+            ConnectionError::Io(_) | ConnectionError::Protocol(_) | ConnectionError::Timeout,
+        ) = &result
+        {
+            self.session = None;
+            self.remote_address.set(None);
+        }
+        result
+    }
+
+    /// 讀取多個範圍，依目前的 PDU 長度分為多個請求
+    async fn read(
+        &mut self,
+        reads: &[S7Read],
+    ) -> Result<Vec<Result<Bytes, S7Error>>, ConnectionError> {
+        let pdu_size = self.session().await?.pdu_size;
+        let chunk = pdu_size - READ_OVERHEAD;
+        let mut results = Vec::with_capacity(reads.len());
+        for group in plan(reads, pdu_size) {
+            let reads = &reads[group];
+            if let [read] = reads
+                && read.length > chunk
+            {
+                results.push(self.read_large(read, chunk).await?);
+                continue;
+            }
+            let items: Vec<_> = reads
+                .iter()
+                .map(|read| (read, read.start, read.length))
+                .collect();
+            let session = self.session().await?;
+            let result = session.read_var(&items).await;
+            results.extend(self.settle(result)?);
+        }
+        Ok(results)
+    }
+
+    /// 分為多個請求讀取超過單一 PDU 的範圍
+    async fn read_large(
+        &mut self,
+        read: &S7Read,
+        chunk: u16,
+    ) -> Result<Result<Bytes, S7Error>, ConnectionError> {
+        let mut data = BytesMut::with_capacity(usize::from(read.length));
+        let mut offset = 0;
+        while offset < read.length {
+            let length = chunk.min(read.length - offset);
+            let session = self.session().await?;
+            let result = session
+                .read_var(&[(read, read.start + u32::from(offset), length)])
+                .await;
+            match self.settle(result)?.pop().ok_or_else(malformed)? {
+                Ok(part) => data.extend_from_slice(&part),
+                Err(error) => return Ok(Err(error)),
+            }
+            offset += length;
+        }
+        Ok(Ok(data.freeze()))
+    }
+}
+
+impl Connection for S7Connection {
+    const NAMES: &[&str] = &["S7", "Siemens"];
+    type Config = S7Config;
+    type Target = S7Target;
+    type Request = S7Request;
+    type Response = S7Response;
+    type Result = ();
+
+    async fn init(config: &S7Config) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+        let session = Session::open(config).await?;
+        let statistics = ConnectionStats::new(
+            format!("{}:{}", config.endpoint.host, config.endpoint.port),
+            None,
+        );
+        statistics.remote_address.set(session.peer);
+
+        let artifact = ConnectionArtifact::new(
+            Self {
+                config: config.clone(),
+                pdu_size: session.pdu_size,
+                session: Some(session),
+                reads: ResponseCache::new(),
+                batches: Vec::new(),
+                remote_address: statistics.remote_address.clone(),
+                rejected: Vec::new(),
+            },
+            statistics,
+        )
+        .update_every(config.update_interval)
+        .timeout_after(config.timeout);
+
+        Ok(match config.max_retry_count {
+            Some(count) => artifact.retry_up_to(count),
+            None => artifact,
+        })
+    }
+
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<S7Target>,
+    ) -> ConnectionTargets<S7Request, ()> {
+        let mut parsed = Vec::with_capacity(targets.len());
+        for S7Target(definition) in targets {
+            match S7Request::parse(&definition) {
+                Ok(request) => parsed.push((definition, request)),
+                Err(error) => self.rejected.push(error),
+            }
+        }
+
+        // 依 PDU 長度合併自動更新的點位，同一位元組的位元點位共用讀取
+        let mut polled: Vec<S7Read> = parsed
+            .iter()
+            .filter(|(definition, _)| definition.auto_refresh)
+            .map(|(_, request)| request.read())
+            .collect();
+        polled.sort_unstable();
+        polled.dedup();
+        self.batches = plan(&polled, self.pdu_size)
+            .into_iter()
+            .filter(|group| group.len() > 1)
+            .map(|group| ReadBatch {
+                reads: polled[group].into(),
+            })
+            .collect();
+        self.set_ttl();
+
+        ConnectionTargets(
+            parsed
+                .into_iter()
+                .map(|(definition, mut request)| {
+                    let read = request.read();
+                    request.batch = self
+                        .batches
+                        .iter()
+                        .find(|batch| definition.auto_refresh && batch.reads.contains(&read))
+                        .cloned();
+                    InitedTarget {
+                        name: definition.name,
+                        request,
+                        result: (),
+                        default_status: definition.default_status,
+                        auto_refresh: definition.auto_refresh,
+                        refresh_interval: None,
+                        keep_raw_frames: None,
+                        group: None,
+                        safe_state: None,
+                        array: None,
+                        change: None,
+                        statistics: Some(connection_statistics.insert_target(definition.device)),
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    async fn request_process(
+        &mut self,
+        request: S7Request,
+    ) -> Result<(S7Response, bool), ConnectionError> {
+        let read = request.read();
+        let (result, wait) = match &request.batch {
+            None => (
+                self.read(std::slice::from_ref(&read))
+                    .await?
+                    .pop()
+                    .ok_or_else(malformed)?,
+                true,
+            ),
+            Some(batch) => {
+                let position = batch
+                    .reads
+                    .iter()
+                    .position(|batched| *batched == read)
+                    .ok_or_else(|| ConnectionError::Protocol("點位不在合併讀取中".to_owned()))?;
+                match self.reads.lookup(&batch.key(), None) {
+                    CacheLookup::Fresh(results) => (results[position].clone(), false),
+                    CacheLookup::Stale { .. } | CacheLookup::Miss => {
+                        let results: Arc<[_]> = self.read(&batch.reads).await?.into();
+                        self.reads.store(&batch.key(), results.clone());
+                        (results[position].clone(), true)
+                    }
+                }
+            }
+        };
+        let raw = result.map_err(ConnectionError::custom)?;
+        Ok((request.respond(raw)?, wait))
+    }
+
+    fn write_preprocess(
+        &self,
+        request: S7Request,
+        value: Value,
+        _: &RequestContext,
+    ) -> Result<Box<dyn DeviceStateWrite>, ConnectionError> {
+        let invalid = || ConnectionError::InvalidConfig(format!("無效的設定值：{value}"));
+        let data = if request.elements > 1 {
+            value
+                .as_array()
+                .filter(|items| items.len() == usize::from(request.elements))
+                .ok_or_else(invalid)?
+                .iter()
+                .map(|item| request.data_type.encode(item))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(invalid)?
+                .concat()
+        } else {
+            request.data_type.encode(&value).ok_or_else(invalid)?
+        };
+
+        Ok(Box::new(S7Write {
+            address: request.address,
+            data,
+            batch: request.batch,
+        }))
+    }
+
+    async fn write_process(
+        &mut self,
+        write: Box<dyn DeviceStateWrite>,
+    ) -> Result<Option<S7Response>, ConnectionError> {
+        let write = write
+            .downcast::<S7Write>()
+            .map_err(|_| ConnectionError::Protocol("非預期的寫入請求".to_owned()))?;
+        if let Some(batch) = &write.batch {
+            self.reads.invalidate(&batch.key());
+        }
+
+        let pdu_size = self.session().await?.pdu_size;
+        // Job 標頭、參數與資料標頭之外的長度
+        let chunk = usize::from(pdu_size)
+            - (JOB_HEADER_LENGTH + PARAMETER_HEADER_LENGTH + ITEM_LENGTH + DATA_HEADER_LENGTH);
+        let mut start = write.address.start;
+        for part in write.data.chunks(chunk) {
+            let session = self.session().await?;
+            let result = session
+                .write_var(&write.address, start, write.address.bit, part)
+                .await;
+            self.settle(result)?;
+            start += u32::try_from(part.len()).map_err(|_| malformed())?;
+        }
+        Ok(None)
+    }
+
+    async fn disconnect(&mut self) -> Result<(), ConnectionError> {
+        self.session = None;
+        self.remote_address.set(None);
+        Ok(())
+    }
+
+    async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+        self.session = None;
+        self.remote_address.set(None);
+        self.session().await.map(|_| ())
+    }
+
+    async fn update_config(&mut self, new_config: &S7Config) -> Result<(), ConnectionError> {
+        self.config = new_config.clone();
+        self.set_ttl();
+        self.reconnect().await
+    }
+}
+
+/// 以記錄下來的 Read Var 回覆解碼點位
+///
+/// `frame` 為包含單一項目的 Read Var 回覆（TPKT 、COTP 與 S7 Ack-Data），`target` 為點位的 `address` ，
+/// 需指定資料型別時以 `/` 接在位址之後，如 `DB1.DBW0/int`
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::{fixture::Fixture, s7::S7Connection};
+///
+/// let fixture = Fixture::from_json(r#"{
+///     "connection": "S7",
+///     "cases": [
+///         { "name": "INT", "target": "DB1.DBW0/int", "frame": "0300 001b 02f080 3203 0000 0001 0002 0006 0000 0401 ff04 0010 002a", "expected": 42 },
+///         { "name": "REAL", "target": "DB10.DBD4/real", "frame": "0300 001d 02f080 3203 0000 0001 0002 0008 0000 0401 ff04 0020 41ac0000", "expected": 21.5 },
+///         { "name": "位元", "target": "M20.3", "frame": "0300 001a 02f080 3203 0000 0001 0002 0005 0000 0401 ff04 0008 08", "expected": true },
+///         { "name": "物件不存在", "target": "DB99.DBW0/int", "frame": "0300 0019 02f080 3203 0000 0001 0002 0004 0000 0401 0a00 0000" },
+///         { "name": "PLC 拒絕請求", "target": "DB1.DBW0/int", "frame": "0300 0013 02f080 3203 0000 0001 0000 0000 8104" }
+///     ]
+/// }"#).unwrap();
+///
+/// let report = fixture.run::<S7Connection>();
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+impl FrameDecoder for S7Connection {
+    fn decode_frame(target: &str, frame: &[u8]) -> Result<S7Response, Box<dyn Error>> {
+        let (address, data_type) = match target.split_once('/') {
+            Some((address, data_type)) => (address, Some(data_type.to_owned())),
+            None => (target, None),
+        };
+        let request = S7Request::parse(&TargetDefinition {
+            name: target.to_owned(),
+            device: None,
+            device_type: None,
+            address: address.to_owned(),
+            data_type,
+            auto_refresh: true,
+            default_status: None,
+            extra: serde_json::Map::new(),
+        })?;
+
+        // 單一 TPKT 封包，COTP 資料封包需帶有 EOT
+        let [0x03, _, high, low, packet @ ..] = frame else {
+            return Err(malformed().into());
+        };
+        let header = usize::from(*packet.first().ok_or_else(malformed)?) + 1;
+        if usize::from(u16::from_be_bytes([*high, *low])) != frame.len()
+            || packet.get(1) != Some(&COTP_DATA[1])
+            || packet.get(2).is_none_or(|eot| eot & 0x80 == 0)
+            || packet.len() < header
+        {
+            return Err(malformed().into());
+        }
+        let pdu = Bytes::copy_from_slice(&packet[header..]);
+        if pdu.get(..2) != Some(&[PROTOCOL_ID, ACK_DATA]) {
+            return Err(malformed().into());
+        }
+
+        let (parameter, data) = ack_payload(&pdu)?;
+        if *parameter != [READ_VAR, 1] {
+            return Err(malformed().into());
+        }
+        let raw = read_items(&data, 1)?.pop().ok_or_else(malformed)??;
+        Ok(request.respond(raw)?)
+    }
+}
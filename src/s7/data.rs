@@ -0,0 +1,331 @@
+//! S7 資料的解碼與編碼
+//!
+//! S7 PLC 的資料一律為大端序（big-endian），本模組提供依位移讀取 DB 、M 區等原始資料的工具，
+//! 可用於解析 [`crate::DeviceStateResponse::raw()`] 保留的原始資料，或一次讀取整個 DB 後自行取出各欄位；
+//! 位移超出資料範圍時回傳 [`None`]
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::s7::data::{get_bool, get_int, get_real, get_string};
+//!
+//! // DB 中依序為 BOOL（第 0 位元組第 1 位元）、INT 、REAL 與 STRING[4]
+//! let db = [0x02, 0x00, 0xFF, 0x9C, 0x41, 0xAC, 0x00, 0x00, 0x04, 0x02, b'O', b'K', 0x00, 0x00];
+//! assert_eq!(get_bool(&db, 0, 1), Some(true));
+//! assert_eq!(get_int(&db, 2), Some(-100));
+//! assert_eq!(get_real(&db, 4), Some(21.5));
+//! assert_eq!(get_string(&db, 8).as_deref(), Some("OK"));
+//! assert_eq!(get_real(&db, 12), None);
+//! ```
+
+use std::fmt::Display;
+
+use serde_json::Value;
+
+/// 由 `offset` 開始取出 `N` 個位元組
+fn array<const N: usize>(data: &[u8], offset: usize) -> Option<[u8; N]> {
+    data.get(offset..)?.first_chunk().copied()
+}
+
+/// 讀取位元，`bit` 為 0 至 7
+#[must_use]
+pub fn get_bool(data: &[u8], offset: usize, bit: u8) -> Option<bool> {
+    if bit > 7 {
+        return None;
+    }
+    Some(data.get(offset)? >> bit & 1 == 1)
+}
+
+/// 讀取 `BYTE` 、`USINT`
+#[must_use]
+pub fn get_byte(data: &[u8], offset: usize) -> Option<u8> {
+    data.get(offset).copied()
+}
+
+/// 讀取 `SINT`
+#[must_use]
+pub fn get_sint(data: &[u8], offset: usize) -> Option<i8> {
+    array(data, offset).map(i8::from_be_bytes)
+}
+
+/// 讀取 `WORD` 、`UINT`
+#[must_use]
+pub fn get_word(data: &[u8], offset: usize) -> Option<u16> {
+    array(data, offset).map(u16::from_be_bytes)
+}
+
+/// 讀取 `INT`
+#[must_use]
+pub fn get_int(data: &[u8], offset: usize) -> Option<i16> {
+    array(data, offset).map(i16::from_be_bytes)
+}
+
+/// 讀取 `DWORD` 、`UDINT`
+#[must_use]
+pub fn get_dword(data: &[u8], offset: usize) -> Option<u32> {
+    array(data, offset).map(u32::from_be_bytes)
+}
+
+/// 讀取 `DINT` 、`TIME`（毫秒）
+#[must_use]
+pub fn get_dint(data: &[u8], offset: usize) -> Option<i32> {
+    array(data, offset).map(i32::from_be_bytes)
+}
+
+/// 讀取 `LWORD` 、`ULINT`
+#[must_use]
+pub fn get_lword(data: &[u8], offset: usize) -> Option<u64> {
+    array(data, offset).map(u64::from_be_bytes)
+}
+
+/// 讀取 `LINT`
+#[must_use]
+pub fn get_lint(data: &[u8], offset: usize) -> Option<i64> {
+    array(data, offset).map(i64::from_be_bytes)
+}
+
+/// 讀取 `REAL`
+#[must_use]
+pub fn get_real(data: &[u8], offset: usize) -> Option<f32> {
+    array(data, offset).map(f32::from_be_bytes)
+}
+
+/// 讀取 `LREAL`
+#[must_use]
+pub fn get_lreal(data: &[u8], offset: usize) -> Option<f64> {
+    array(data, offset).map(f64::from_be_bytes)
+}
+
+/// 讀取 `STRING` ：最大長度、目前長度各 1 個位元組，接著是 ISO 8859-1 字元
+#[must_use]
+pub fn get_string(data: &[u8], offset: usize) -> Option<String> {
+    let [capacity, length] = array(data, offset)?;
+    let length = usize::from(length.min(capacity));
+    let text = data.get(offset + 2..offset + 2 + length)?;
+    Some(text.iter().copied().map(char::from).collect())
+}
+
+/// 資料型別
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::s7::S7Type;
+/// use serde_json::json;
+///
+/// let real = S7Type::parse("real").unwrap();
+/// assert_eq!(real.size(), 4);
+/// assert_eq!(real.decode(&[0x41, 0xAC, 0x00, 0x00]), Some(json!(21.5)));
+/// assert_eq!(real.encode(&json!(21.5)), Some(vec![0x41, 0xAC, 0x00, 0x00]));
+///
+/// let text = S7Type::parse("STRING[4]").unwrap();
+/// assert_eq!(text, S7Type::String(4));
+/// assert_eq!(text.encode(&json!("OK")), Some(vec![0x04, 0x02, b'O', b'K', 0x00, 0x00]));
+/// assert_eq!(text.encode(&json!("TOO LONG")), None);
+/// assert_eq!(S7Type::parse("STRING").unwrap().size(), 256);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum S7Type {
+    /// `BOOL` ，只能用於位元位址
+    Bool,
+    /// `BYTE`
+    Byte,
+    /// `CHAR` ，解碼為 1 個字元的字串
+    Char,
+    /// `WORD`
+    Word,
+    /// `DWORD`
+    Dword,
+    /// `LWORD`
+    Lword,
+    /// `SINT` ，有號 8 位元
+    Sint,
+    /// `USINT` ，無號 8 位元
+    Usint,
+    /// `INT` ，有號 16 位元
+    Int,
+    /// `UINT` ，無號 16 位元
+    Uint,
+    /// `DINT` ，有號 32 位元
+    Dint,
+    /// `UDINT` ，無號 32 位元
+    Udint,
+    /// `LINT` ，有號 64 位元
+    Lint,
+    /// `ULINT` ，無號 64 位元
+    Ulint,
+    /// `REAL` ，32 位元浮點數
+    Real,
+    /// `LREAL` ，64 位元浮點數
+    Lreal,
+    /// `TIME` ，以毫秒表示的有號 32 位元
+    Time,
+    /// `STRING[n]` ，數值為最大長度（1 至 254），未指定長度時為 254
+    String(u8),
+}
+
+impl S7Type {
+    const NAMED: [Self; 17] = [
+        Self::Bool,
+        Self::Byte,
+        Self::Char,
+        Self::Word,
+        Self::Dword,
+        Self::Lword,
+        Self::Sint,
+        Self::Usint,
+        Self::Int,
+        Self::Uint,
+        Self::Dint,
+        Self::Udint,
+        Self::Lint,
+        Self::Ulint,
+        Self::Real,
+        Self::Lreal,
+        Self::Time,
+    ];
+
+    /// 以型別名稱解析，不分大小寫，如 `INT` 、`real` 、`STRING[20]`
+    #[must_use]
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim().to_ascii_uppercase();
+        if let Some(rest) = name.strip_prefix("STRING") {
+            let length = match rest.trim() {
+                "" => 254,
+                length => length
+                    .strip_prefix('[')?
+                    .strip_suffix(']')?
+                    .trim()
+                    .parse()
+                    .ok()
+                    .filter(|length| (1..=254).contains(length))?,
+            };
+            return Some(Self::String(length));
+        }
+        Self::NAMED
+            .into_iter()
+            .find(|kind| kind.to_string() == name)
+    }
+
+    /// 資料長度（位元組），`BOOL` 為所在的 1 個位元組
+    #[must_use]
+    pub const fn size(self) -> usize {
+        match self {
+            Self::Bool | Self::Byte | Self::Char | Self::Sint | Self::Usint => 1,
+            Self::Word | Self::Int | Self::Uint => 2,
+            Self::Dword | Self::Dint | Self::Udint | Self::Real | Self::Time => 4,
+            Self::Lword | Self::Lint | Self::Ulint | Self::Lreal => 8,
+            Self::String(length) => length as usize + 2,
+        }
+    }
+
+    /// 解碼，長度不足時回傳 [`None`] ，NaN 或無限大的浮點數為 [`Value::Null`] ；`BOOL` 為第 0 位元
+    ///
+    /// # 範例
+    /// 以內建的測試向量驗證：
+    /// ```rust
+    /// use device_state_exchange_lib::{s7::S7Type, vectors::{self, encode_hex}};
+    ///
+    /// let report = vectors::built_in_set("s7/data").unwrap().verify(|frame, value| {
+    ///     let kind = value["type"].as_str().and_then(S7Type::parse).ok_or("不支援的資料型別")?;
+    ///     match kind.decode(frame) {
+    ///         None if value["value"].is_null() => Ok(()),
+    ///         Some(decoded) if decoded == value["value"] => match kind.encode(&decoded) {
+    ///             Some(encoded) if encoded == frame => Ok(()),
+    ///             encoded => Err(format!("重新編碼為 {:?}", encoded.as_deref().map(encode_hex))),
+    ///         },
+    ///         decoded => Err(format!("解碼結果為 {decoded:?}")),
+    ///     }
+    /// });
+    /// assert!(report.is_complete(), "{report:?}");
+    /// ```
+    #[must_use]
+    pub fn decode(self, data: &[u8]) -> Option<Value> {
+        Some(match self {
+            Self::Bool => Value::Bool(get_bool(data, 0, 0)?),
+            Self::Byte | Self::Usint => Value::from(get_byte(data, 0)?),
+            Self::Char => Value::String(char::from(get_byte(data, 0)?).to_string()),
+            Self::Sint => Value::from(get_sint(data, 0)?),
+            Self::Word | Self::Uint => Value::from(get_word(data, 0)?),
+            Self::Int => Value::from(get_int(data, 0)?),
+            Self::Dword | Self::Udint => Value::from(get_dword(data, 0)?),
+            Self::Dint | Self::Time => Value::from(get_dint(data, 0)?),
+            Self::Lword | Self::Ulint => Value::from(get_lword(data, 0)?),
+            Self::Lint => Value::from(get_lint(data, 0)?),
+            Self::Real => Value::from(get_real(data, 0)?),
+            Self::Lreal => Value::from(get_lreal(data, 0)?),
+            Self::String(_) => Value::String(get_string(data, 0)?),
+        })
+    }
+
+    /// 編碼，超出範圍或格式不符時回傳 [`None`] ；`STRING` 包含最大長度與目前長度，並以 0 補滿
+    #[must_use]
+    pub fn encode(self, value: &Value) -> Option<Vec<u8>> {
+        let signed = || value.as_i64();
+        let unsigned = || value.as_u64();
+        Some(match self {
+            Self::Bool => vec![u8::from(value.as_bool()?)],
+            Self::Byte | Self::Usint => vec![u8::try_from(unsigned()?).ok()?],
+            Self::Char => match value.as_str()?.chars().collect::<Vec<_>>().as_slice() {
+                [character] => vec![u8::try_from(u32::from(*character)).ok()?],
+                _ => return None,
+            },
+            Self::Sint => i8::try_from(signed()?).ok()?.to_be_bytes().to_vec(),
+            Self::Word | Self::Uint => u16::try_from(unsigned()?).ok()?.to_be_bytes().to_vec(),
+            Self::Int => i16::try_from(signed()?).ok()?.to_be_bytes().to_vec(),
+            Self::Dword | Self::Udint => u32::try_from(unsigned()?).ok()?.to_be_bytes().to_vec(),
+            Self::Dint | Self::Time => i32::try_from(signed()?).ok()?.to_be_bytes().to_vec(),
+            Self::Lword | Self::Ulint => unsigned()?.to_be_bytes().to_vec(),
+            Self::Lint => signed()?.to_be_bytes().to_vec(),
+            #[expect(clippy::cast_possible_truncation)]
+            Self::Real => {
+                let value = value
+                    .as_f64()
+                    .filter(|value| value.abs() <= f64::from(f32::MAX))?;
+                (value as f32).to_be_bytes().to_vec()
+            }
+            Self::Lreal => value
+                .as_f64()
+                .filter(|value| value.is_finite())?
+                .to_be_bytes()
+                .to_vec(),
+            Self::String(capacity) => {
+                let text = value
+                    .as_str()?
+                    .chars()
+                    .map(|character| u8::try_from(u32::from(character)).ok())
+                    .collect::<Option<Vec<u8>>>()?;
+                let length = u8::try_from(text.len())
+                    .ok()
+                    .filter(|length| *length <= capacity)?;
+                let mut bytes = vec![capacity, length];
+                bytes.extend_from_slice(&text);
+                bytes.resize(self.size(), 0);
+                bytes
+            }
+        })
+    }
+}
+
+impl Display for S7Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Bool => "BOOL",
+            Self::Byte => "BYTE",
+            Self::Char => "CHAR",
+            Self::Word => "WORD",
+            Self::Dword => "DWORD",
+            Self::Lword => "LWORD",
+            Self::Sint => "SINT",
+            Self::Usint => "USINT",
+            Self::Int => "INT",
+            Self::Uint => "UINT",
+            Self::Dint => "DINT",
+            Self::Udint => "UDINT",
+            Self::Lint => "LINT",
+            Self::Ulint => "ULINT",
+            Self::Real => "REAL",
+            Self::Lreal => "LREAL",
+            Self::Time => "TIME",
+            Self::String(length) => return write!(f, "STRING[{length}]"),
+        })
+    }
+}
@@ -0,0 +1,276 @@
+//! 安全狀態
+//!
+//! 部分可寫入的點位在交換層停止運作時必須回到安全的狀態（如將設定值歸零、關閉閥門），
+//! 點位可以設定 [`crate::InitedTarget::safe_state`] ，主程式在以下時機寫入安全狀態：
+//!
+//! - 正常結束時，在調用 [`crate::Connection::disconnect()`] 前以 [`crate::ConnectionTargets::safe_state_requests()`] 取得請求，
+//!   再以 [`apply_safe_state()`] 直接寫入所有點位的安全狀態
+//! - 上游控制中斷時（非必需），外部界面每次收到控制端的請求時調用 [`ControlWatchdog::feed()`] ，
+//!   主程式排程時檢查 [`ControlWatchdog::check()`] ，逾時後將 [`crate::ConnectionTargets::safe_state_commands()`] 加入 [`crate::CommandQueue`]
+//!
+//! 安全狀態與一般的寫入相同，會透過 [`crate::Connection::preprocess()`] 的 `new_status` 參數傳遞給設備連線
+//!
+//! # 範例
+//! ```rust
+//! # use std::{borrow::Cow, error::Error};
+//! # use device_state_exchange_lib::{*, safe_state::apply_safe_state};
+//! use serde_json::{Value, json};
+//!
+//! # #[derive(Debug, Clone)] struct Config;
+//! # impl ConnectionConfig for Config {}
+//! # #[derive(Debug, Clone)] struct Point(&'static str, Option<Value>);
+//! # impl Target for Point {}
+//! #[derive(Debug, Clone)]
+//! struct Request(Option<Value>);
+//! # impl DeviceStateRequest for Request {}
+//! # #[derive(Debug, Clone)] struct Response;
+//! # impl DeviceStateResponse for Response { fn to_value(&self) -> Result<Cow<'_, Value>, value::ConversionError> { Ok(Cow::Owned(Value::Null)) } }
+//! #[derive(Default)]
+//! struct Chiller {
+//!     written: Vec<Value>,
+//! }
+//!
+//! impl Connection for Chiller {
+//! #   const NAMES: &[&str] = &["Chiller"];
+//! #   type Config = Config;
+//! #   type Target = Point;
+//! #   type Request = Request;
+//! #   type Response = Response;
+//! #   type Result = ();
+//! #   async fn init(_: &Config) -> Result<ConnectionArtifact<Self>, ConnectionError> { Ok(ConnectionArtifact::new(Self::default(), ConnectionStats::new("COM1", None))) }
+//! #   fn init_targets(&mut self, _: &mut ConnectionStats, targets: Vec<Point>) -> ConnectionTargets<Request, ()> {
+//! #       ConnectionTargets(targets.into_iter().map(|Point(name, safe_state)| InitedTarget { name: name.to_owned(), request: Request(None), result: (), default_status: None, auto_refresh: true, refresh_interval: None, keep_raw_frames: None, group: None, safe_state, array: None, change: None, statistics: None }).collect())
+//! #   }
+//! #   async fn reconnect(&mut self) -> Result<(), ConnectionError> { Ok(()) }
+//! #   async fn update_config(&mut self, _: &Config) -> Result<(), ConnectionError> { Ok(()) }
+//!     fn preprocess(&self, _: Request, new_status: Option<Value>) -> Result<Request, ConnectionError> {
+//!         Ok(Request(new_status))
+//!     }
+//!
+//!     async fn request_process(&mut self, Request(value): Request) -> Result<(Response, bool), ConnectionError> {
+//!         self.written.extend(value);
+//!         Ok((Response, true))
+//!     }
+//! }
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let mut artifact = Chiller::init(&Config).await.unwrap();
+//! let targets = artifact.artifact.init_targets(
+//!     &mut artifact.statistics,
+//!     vec![Point("setpoint", Some(json!(0))), Point("temperature", None)],
+//! );
+//!
+//! // 正常結束
+//! let report = apply_safe_state(&mut artifact.artifact, targets.safe_state_requests()).await;
+//! assert_eq!(report.applied, ["setpoint"]);
+//! assert_eq!(artifact.artifact.written, [json!(0)]);
+//! artifact.artifact.disconnect().await.unwrap();
+//! # }
+//! ```
+
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{Connection, ConnectionTargets, DeviceStateRequest, WriteCommand};
+
+/// 安全狀態寫入失敗的點位
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SafeStateFailure {
+    /// 點位名稱
+    pub target: String,
+    /// 錯誤訊息
+    pub error: String,
+}
+
+/// 安全狀態寫入結果
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SafeStateReport {
+    /// 已寫入安全狀態的點位，依點位順序排列
+    pub applied: Vec<String>,
+    /// 寫入失敗的點位，依點位順序排列
+    pub failed: Vec<SafeStateFailure>,
+}
+
+impl SafeStateReport {
+    /// 所有點位是否都已寫入安全狀態
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+impl<REQ: DeviceStateRequest, RES> ConnectionTargets<REQ, RES> {
+    /// 取得所有設定了安全狀態的點位的寫入指令
+    ///
+    /// 指令沒有請求來源（[`WriteCommand::client`] 為 [`None`]），依點位順序排列
+    #[must_use]
+    pub fn safe_state_commands(&self) -> Vec<WriteCommand> {
+        self.0
+            .iter()
+            .filter_map(|target| {
+                target.safe_state.as_ref().map(|value| WriteCommand {
+                    target: target.name.clone(),
+                    value: value.clone(),
+                    client: None,
+                    correlation: None,
+                })
+            })
+            .collect()
+    }
+
+    /// 取得所有設定了安全狀態的點位的請求，供 [`apply_safe_state()`] 使用
+    ///
+    /// # 回傳值
+    /// 點位名稱、請求的複本與安全狀態，依點位順序排列
+    #[must_use]
+    pub fn safe_state_requests(&self) -> Vec<(String, REQ, Value)> {
+        self.0
+            .iter()
+            .filter_map(|target| {
+                target.safe_state.as_ref().map(|value| {
+                    (
+                        target.name.clone(),
+                        dyn_clone::clone(&target.request),
+                        value.clone(),
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+/// 將點位寫入安全狀態
+///
+/// 依序以 [`Connection::preprocess()`] 與 [`Connection::request_process()`] 逐一寫入，單一點位寫入失敗時會繼續寫入其他點位
+///
+/// # 參數
+/// - `connection`：設備連線，請在調用 [`Connection::disconnect()`] 前調用
+/// - `requests`：由 [`ConnectionTargets::safe_state_requests()`] 取得的點位名稱、請求與安全狀態
+pub async fn apply_safe_state<T: Connection>(
+    connection: &mut T,
+    requests: Vec<(String, T::Request, Value)>,
+) -> SafeStateReport {
+    let mut report = SafeStateReport::default();
+
+    for (target, request, safe_state) in requests {
+        let request = connection
+            .preprocess(request, Some(safe_state))
+            .map_err(|error| error.to_string());
+        let result = match request {
+            Ok(request) => connection
+                .request_process(request)
+                .await
+                .map(|_| ())
+                .map_err(|error| error.to_string()),
+            Err(error) => Err(error),
+        };
+
+        match result {
+            Ok(()) => report.applied.push(target),
+            Err(error) => report.failed.push(SafeStateFailure { target, error }),
+        }
+    }
+
+    report
+}
+
+/// 上游控制中斷偵測
+///
+/// 控制端（如 SCADA）需要定期送出請求或心跳，超過逾時時間未收到時視為控制中斷
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份資料
+///
+/// # 範例
+/// ```rust
+/// use std::time::Duration;
+/// use device_state_exchange_lib::safe_state::ControlWatchdog;
+///
+/// let watchdog = ControlWatchdog::new(Duration::ZERO);
+/// assert!(watchdog.check());
+/// // 同一次中斷只會回報一次
+/// assert!(!watchdog.check());
+/// assert!(watchdog.is_lost());
+///
+/// watchdog.feed();
+/// assert!(watchdog.check());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ControlWatchdog {
+    timeout: Duration,
+    state: Arc<Mutex<WatchdogState>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct WatchdogState {
+    last_fed: Instant,
+    tripped: bool,
+}
+
+impl ControlWatchdog {
+    /// 建立上游控制中斷偵測，從建立時開始計時
+    ///
+    /// # 參數
+    /// - `timeout`：逾時時間
+    #[must_use]
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            state: Arc::new(Mutex::new(WatchdogState {
+                last_fed: Instant::now(),
+                tripped: false,
+            })),
+        }
+    }
+
+    /// 逾時時間
+    #[must_use]
+    pub const fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// 收到控制端的請求或心跳，重新計時
+    pub fn feed(&self) {
+        *self.state.lock().unwrap_or_else(PoisonError::into_inner) = WatchdogState {
+            last_fed: Instant::now(),
+            tripped: false,
+        };
+    }
+
+    /// 是否已超過逾時時間未收到控制端的請求
+    #[must_use]
+    pub fn is_lost(&self) -> bool {
+        self.state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .last_fed
+            .elapsed()
+            >= self.timeout
+    }
+
+    /// 檢查控制是否剛中斷
+    ///
+    /// # 回傳值
+    /// 逾時後第一次檢查時為 `true` ，主程式應在此時寫入安全狀態；之後直到下一次 [`ControlWatchdog::feed()`] 前都為 `false`
+    #[must_use]
+    pub fn check(&self) -> bool {
+        trip(
+            &mut self.state.lock().unwrap_or_else(PoisonError::into_inner),
+            self.timeout,
+        )
+    }
+}
+
+fn trip(state: &mut WatchdogState, timeout: Duration) -> bool {
+    if state.tripped || state.last_fed.elapsed() < timeout {
+        return false;
+    }
+
+    state.tripped = true;
+    true
+}
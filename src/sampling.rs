@@ -0,0 +1,300 @@
+//! 取樣群組
+//!
+//! 以多個點位計算的數值（如功率 = 電壓 × 電流）必須使用同一輪輪詢的取樣，若各點位分開排程，
+//! 兩次讀取之間的時間差會讓計算結果失真，[`SampleGroup`] 讓多個點位成為一組一起讀取與發布：
+//!
+//! - 主程式以 [`SampleGroup::read()`] 在同一次借用設備連線期間依序讀取所有成員，期間不會穿插其他請求
+//! - 所有成員的 [`ResponseEnvelope`] 使用相同的時間，並附加相同的群組序號（[`ResponseEnvelope::sample_group`]）
+//! - 任一成員讀取失敗時整組不會發布，下游不會拿到不完整的一組取樣
+//!
+//! 群組成員以 [`crate::ConnectionTargets::sample_group_requests()`] 取得請求，成員應位於同一個設備上；
+//! 設備支援一次讀取多個點位（如 Modbus 連續暫存器）時，實作者可以在 [`crate::Connection::request_process()`] 中合併請求
+//!
+//! # 範例
+//! ```rust
+//! # use std::{borrow::Cow, error::Error};
+//! # use device_state_exchange_lib::*;
+//! use device_state_exchange_lib::{envelope::EnvelopeSource, sampling::SampleGroup, value::DeviceData};
+//! use serde_json::{Value, json};
+//!
+//! # #[derive(Debug, Clone)] struct Config;
+//! # impl ConnectionConfig for Config {}
+//! # #[derive(Debug, Clone)] struct Point(&'static str);
+//! # impl Target for Point {}
+//! #[derive(Debug, Clone)]
+//! struct Request(&'static str);
+//! # impl DeviceStateRequest for Request {}
+//! #[derive(Debug, Clone)]
+//! struct Response(f64);
+//! # impl DeviceStateResponse for Response { fn to_value(&self) -> Result<Cow<'_, Value>, value::ConversionError> { Ok(Cow::Owned(json!(self.0))) } }
+//! struct Meter;
+//!
+//! impl Connection for Meter {
+//! #   const NAMES: &[&str] = &["Meter"];
+//! #   type Config = Config;
+//! #   type Target = Point;
+//! #   type Request = Request;
+//! #   type Response = Response;
+//! #   type Result = ();
+//! #   async fn init(_: &Config) -> Result<ConnectionArtifact<Self>, ConnectionError> { Ok(ConnectionArtifact::new(Self, ConnectionStats::new("COM1", None))) }
+//! #   fn init_targets(&mut self, _: &mut ConnectionStats, targets: Vec<Point>) -> ConnectionTargets<Request, ()> {
+//! #       ConnectionTargets(targets.into_iter().map(|Point(name)| InitedTarget { name: name.to_owned(), request: Request(name), result: (), default_status: None, auto_refresh: true, refresh_interval: None, keep_raw_frames: None, group: None, safe_state: None, array: None, change: None, statistics: None }).collect())
+//! #   }
+//! #   async fn reconnect(&mut self) -> Result<(), ConnectionError> { Ok(()) }
+//! #   async fn update_config(&mut self, _: &Config) -> Result<(), ConnectionError> { Ok(()) }
+//!     async fn request_process(&mut self, Request(name): Request) -> Result<(Response, bool), ConnectionError> {
+//!         Ok((Response(if name == "voltage" { 220.0 } else { 5.0 }), true))
+//!     }
+//! }
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let mut artifact = Meter::init(&Config).await.unwrap();
+//! let targets = artifact.artifact.init_targets(
+//!     &mut artifact.statistics,
+//!     vec![Point("voltage"), Point("current"), Point("frequency")],
+//! );
+//!
+//! let source = EnvelopeSource::new("panel-a");
+//! let power = SampleGroup::new("power", ["voltage", "current"]);
+//! let requests = targets.sample_group_requests(&power).unwrap();
+//!
+//! let reading = power.read(&mut artifact.artifact, requests, &Some("1".to_owned()), &source).await.unwrap();
+//! let [voltage, current] = [reading.value("voltage"), reading.value("current")]
+//!     .map(|data| data.and_then(DeviceData::as_scalar).and_then(Value::as_f64).unwrap());
+//! assert_eq!(voltage * current, 1100.0);
+//!
+//! assert_eq!(reading.envelopes[0].timestamp, reading.envelopes[1].timestamp);
+//! assert_eq!(reading.envelopes[1].sample_group.as_ref().unwrap().sequence, 0);
+//!
+//! let next = power.read(&mut artifact.artifact, targets.sample_group_requests(&power).unwrap(), &None, &source).await.unwrap();
+//! assert_eq!(next.sequence, 1);
+//! # }
+//! ```
+
+use std::{
+    error::Error,
+    fmt::Display,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Connection, ConnectionTargets, DeviceStateRequest, TargetAddressNumber,
+    envelope::{EnvelopeSource, ResponseEnvelope},
+    value::DeviceData,
+};
+
+/// 取樣群組標記，參見 [`ResponseEnvelope::sample_group`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SampleGroupStamp {
+    /// 群組名稱
+    pub group: String,
+    /// 群組序號，每次讀取群組時遞增，讀取失敗的輪次也會使用一個序號
+    pub sequence: u64,
+}
+
+/// 取樣群組
+///
+/// 本 struct 內部利用 [`Arc`] 共享序號，複製後的物件會延續同一組序號
+#[derive(Debug, Clone)]
+pub struct SampleGroup {
+    name: String,
+    targets: Vec<String>,
+    sequence: Arc<AtomicU64>,
+}
+
+impl SampleGroup {
+    /// 建立取樣群組，序號由 0 開始
+    ///
+    /// # 參數
+    /// - `name`：群組名稱
+    /// - `targets`：成員點位名稱，讀取與發布時依此順序排列
+    #[must_use]
+    pub fn new(
+        name: impl Into<String>,
+        targets: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            targets: targets.into_iter().map(Into::into).collect(),
+            sequence: Arc::default(),
+        }
+    }
+
+    /// 群組名稱
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 成員點位名稱
+    #[must_use]
+    pub fn targets(&self) -> &[String] {
+        &self.targets
+    }
+
+    /// 讀取整個群組
+    ///
+    /// 依序以 [`Connection::preprocess()`] 、[`Connection::request_process()`] 與 [`Connection::postprocess()`] 處理每個成員，
+    /// 再以 [`EnvelopeSource::wrap()`] 包裝，所有信封的時間為開始讀取的時間
+    ///
+    /// # 參數
+    /// - `connection`：設備連線，讀取期間會持續借用，不會穿插其他請求
+    /// - `requests`：由 [`ConnectionTargets::sample_group_requests()`] 取得的點位名稱與請求
+    /// - `address`：成員所在的設備編號
+    /// - `source`：回覆信封來源
+    ///
+    /// # Errors
+    /// 任一成員處理失敗時回傳 [`SampleGroupError::Request`] ，已讀取的成員不會發布
+    pub async fn read<T: Connection>(
+        &self,
+        connection: &mut T,
+        requests: Vec<(String, T::Request)>,
+        address: &TargetAddressNumber,
+        source: &EnvelopeSource,
+    ) -> Result<SampleGroupReading, SampleGroupError> {
+        let sequence = self.sequence.fetch_add(1, Ordering::Relaxed);
+        let timestamp = SystemTime::now();
+        let mut envelopes = Vec::with_capacity(requests.len());
+
+        for (target, request) in requests {
+            let request = connection
+                .preprocess(request, None)
+                .map_err(|error| error.to_string());
+            let response = match request {
+                Ok(request) => connection
+                    .request_process(dyn_clone::clone(&request))
+                    .await
+                    .map_err(|error| error.to_string())
+                    .and_then(|(response, _)| {
+                        connection
+                            .postprocess(request, response)
+                            .map_err(|error| error.to_string())
+                    }),
+                Err(error) => Err(error),
+            };
+
+            match response {
+                Ok(response) => envelopes.push(ResponseEnvelope {
+                    timestamp,
+                    sample_group: Some(SampleGroupStamp {
+                        group: self.name.clone(),
+                        sequence,
+                    }),
+                    ..source.wrap(target, address, &response)
+                }),
+                Err(error) => {
+                    return Err(SampleGroupError::Request {
+                        group: self.name.clone(),
+                        target,
+                        error,
+                    });
+                }
+            }
+        }
+
+        Ok(SampleGroupReading {
+            group: self.name.clone(),
+            sequence,
+            timestamp,
+            envelopes,
+        })
+    }
+}
+
+/// 取樣群組讀取結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleGroupReading {
+    /// 群組名稱
+    pub group: String,
+    /// 群組序號
+    pub sequence: u64,
+    /// 所有成員共用的時間
+    pub timestamp: SystemTime,
+    /// 成員的回覆信封，依成員順序排列
+    pub envelopes: Vec<ResponseEnvelope>,
+}
+
+impl SampleGroupReading {
+    /// 取得成員的回覆資料，成員不存在或回覆無法轉換時為 [`None`]
+    #[must_use]
+    pub fn value(&self, target: &str) -> Option<&DeviceData> {
+        self.envelopes
+            .iter()
+            .find(|envelope| envelope.target == target)
+            .and_then(|envelope| envelope.value.as_ref())
+    }
+}
+
+/// 取樣群組錯誤
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SampleGroupError {
+    /// 成員點位不存在
+    MissingTarget {
+        /// 群組名稱
+        group: String,
+        /// 點位名稱
+        target: String,
+    },
+    /// 成員處理失敗
+    Request {
+        /// 群組名稱
+        group: String,
+        /// 點位名稱
+        target: String,
+        /// 錯誤訊息
+        error: String,
+    },
+}
+
+impl Display for SampleGroupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingTarget { group, target } => {
+                write!(f, "取樣群組「{group}」的成員「{target}」不存在")
+            }
+            Self::Request {
+                group,
+                target,
+                error,
+            } => write!(f, "取樣群組「{group}」的成員「{target}」讀取失敗：{error}"),
+        }
+    }
+}
+
+impl Error for SampleGroupError {}
+
+impl<REQ: DeviceStateRequest, RES> ConnectionTargets<REQ, RES> {
+    /// 取得取樣群組中所有成員的請求，供 [`SampleGroup::read()`] 使用
+    ///
+    /// # 回傳值
+    /// 點位名稱與請求的複本，依成員順序排列
+    ///
+    /// # Errors
+    /// 任一成員不存在時回傳 [`SampleGroupError::MissingTarget`]
+    pub fn sample_group_requests(
+        &self,
+        group: &SampleGroup,
+    ) -> Result<Vec<(String, REQ)>, SampleGroupError> {
+        group
+            .targets
+            .iter()
+            .map(|name| {
+                self.0
+                    .iter()
+                    .find(|target| &target.name == name)
+                    .map(|target| (target.name.clone(), dyn_clone::clone(&target.request)))
+                    .ok_or_else(|| SampleGroupError::MissingTarget {
+                        group: group.name.clone(),
+                        target: name.clone(),
+                    })
+            })
+            .collect()
+    }
+}
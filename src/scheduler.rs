@@ -0,0 +1,251 @@
+//! 輪詢排程
+//!
+//! [`crate::ConnectionArtifact::update_interval`] 讓同一個連線的所有點位以相同的間隔更新，但實際的點位常有不同的需求（如電錶的電能每分鐘更新一次即可，
+//! 警報狀態則需要每秒更新），點位可以在 [`crate::InitedTarget::refresh_interval`] 設定各自的間隔，再由 [`Scheduler`] 安排輪詢順序：
+//!
+//! - 以 [`Scheduler::from_targets()`] 加入所有自動更新點位，未設定間隔的點位使用連線的預設間隔
+//! - 主程式重複調用 [`Scheduler::next()`] ，依序取得到期的點位或外部服務的請求，外部服務的請求優先於到期的點位
+//! - 點位的下一次輪詢時間會在下一次調用 [`Scheduler::next()`] 時，以當下的時間重新計算，處理時間超過間隔時不會累積待處理的輪詢，
+//!   與 [`crate::Connection::request_process()`] 描述的行為相同
+//!
+//! 間隔以單調時鐘計算（參見 [`crate::clock`]），作業系統時鐘校時不會影響排程
+//!
+//! # 範例
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use device_state_exchange_lib::{WriteCommand, scheduler::{Scheduled, Scheduler}};
+//! use serde_json::json;
+//! use tokio::sync::mpsc;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let (requests, receiver) = mpsc::channel(8);
+//! let mut scheduler = Scheduler::new(Duration::from_millis(40)).with_requests(receiver);
+//! scheduler.schedule("alarm", Some(Duration::from_millis(10)));
+//! scheduler.schedule("energy", None);
+//!
+//! requests.send(WriteCommand { target: "setpoint".to_owned(), value: json!(25), client: None, correlation: None }).await.unwrap();
+//! drop(requests);
+//!
+//! let mut polled = Vec::new();
+//! while polled.len() < 5 {
+//!     match scheduler.next().await.unwrap() {
+//!         Scheduled::Request(command) => assert!(polled.is_empty() && command.target == "setpoint"),
+//!         Scheduled::Poll { target, .. } => polled.push(target),
+//!     }
+//! }
+//!
+//! // 兩個點位一開始都會立即輪詢，之後「alarm」的輪詢次數較多
+//! assert_eq!(polled[..2], ["alarm", "energy"]);
+//! assert_eq!(polled[2..], ["alarm", "alarm", "alarm"]);
+//! assert_eq!(scheduler.interval("energy"), Some(Duration::from_millis(40)));
+//! # }
+//! ```
+
+use std::{cmp::Reverse, collections::BinaryHeap, time::Duration};
+
+use tokio::{sync::mpsc, time::Instant};
+
+use crate::{ConnectionTargets, DeviceStateRequest, HashMap, WriteCommand};
+
+/// [`Scheduler::next()`] 取得的工作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scheduled<T> {
+    /// 外部服務的請求
+    Request(T),
+    /// 到期的點位
+    Poll {
+        /// 點位名稱
+        target: String,
+        /// 原本預定的輪詢時間，與目前時間的差距代表延遲
+        due: Instant,
+    },
+}
+
+#[derive(Debug)]
+struct Slot {
+    interval: Option<Duration>,
+    generation: u64,
+}
+
+/// 輪詢排程
+///
+/// 泛型 `T` 為外部服務請求的型別，預設為 [`WriteCommand`]
+#[derive(Debug)]
+pub struct Scheduler<T = WriteCommand> {
+    default_interval: Duration,
+    targets: HashMap<String, Slot>,
+    deadlines: BinaryHeap<Reverse<(Instant, u64, String)>>,
+    generation: u64,
+    in_flight: Option<(String, u64)>,
+    requests: Option<mpsc::Receiver<T>>,
+}
+
+impl<T> Scheduler<T> {
+    /// 建立沒有任何點位的輪詢排程
+    ///
+    /// # 參數
+    /// - `default_interval`：未設定間隔的點位使用的間隔，通常為 [`crate::ConnectionArtifact::update_interval`]
+    #[must_use]
+    pub fn new(default_interval: Duration) -> Self {
+        Self {
+            default_interval,
+            targets: HashMap::default(),
+            deadlines: BinaryHeap::new(),
+            generation: 0,
+            in_flight: None,
+            requests: None,
+        }
+    }
+
+    /// 建立輪詢排程，並加入所有 [`crate::InitedTarget::auto_refresh`] 的點位
+    ///
+    /// # 參數
+    /// - `targets`：連線的點位
+    /// - `default_interval`：未設定 [`crate::InitedTarget::refresh_interval`] 的點位使用的間隔
+    #[must_use]
+    pub fn from_targets<REQ: DeviceStateRequest, RES>(
+        targets: &ConnectionTargets<REQ, RES>,
+        default_interval: Duration,
+    ) -> Self {
+        let mut scheduler = Self::new(default_interval);
+        for target in targets.0.iter().filter(|target| target.auto_refresh) {
+            scheduler.schedule(&target.name, target.refresh_interval);
+        }
+        scheduler
+    }
+
+    /// 設定外部服務請求的來源
+    ///
+    /// 傳送端全部關閉且沒有待處理的請求後，[`Scheduler::next()`] 只會回傳到期的點位
+    #[must_use]
+    pub fn with_requests(mut self, requests: mpsc::Receiver<T>) -> Self {
+        self.requests = Some(requests);
+        self
+    }
+
+    /// 加入點位，點位已存在時會以新的間隔取代，兩者都會立即輪詢一次
+    ///
+    /// # 參數
+    /// - `target`：點位名稱
+    /// - `interval`：輪詢間隔，為 [`None`] 時使用預設間隔
+    pub fn schedule(&mut self, target: impl Into<String>, interval: Option<Duration>) {
+        let target = target.into();
+        self.generation += 1;
+        self.targets.insert(
+            target.clone(),
+            Slot {
+                interval,
+                generation: self.generation,
+            },
+        );
+        self.deadlines
+            .push(Reverse((Instant::now(), self.generation, target)));
+    }
+
+    /// 移除點位
+    ///
+    /// # 回傳值
+    /// 點位是否存在
+    pub fn unschedule(&mut self, target: &str) -> bool {
+        self.targets.remove(target).is_some()
+    }
+
+    /// 變更預設間隔，未設定間隔的點位會在下一次輪詢後套用
+    pub const fn set_default_interval(&mut self, default_interval: Duration) {
+        self.default_interval = default_interval;
+    }
+
+    /// 點位實際使用的輪詢間隔，點位不存在時為 [`None`]
+    #[must_use]
+    pub fn interval(&self, target: &str) -> Option<Duration> {
+        self.targets
+            .get(target)
+            .map(|slot| slot.interval.unwrap_or(self.default_interval))
+    }
+
+    /// 排程中的點位數量
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// 是否沒有任何排程中的點位
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    /// 取得下一個工作
+    ///
+    /// 有待處理的外部服務請求時立即回傳，否則等待最早到期的點位；上一次取得的點位會視為已處理完成，並以目前的時間排定下一次輪詢
+    ///
+    /// # 回傳值
+    /// 下一個工作，沒有排程中的點位且外部服務請求的來源已關閉時為 [`None`]
+    pub async fn next(&mut self) -> Option<Scheduled<T>> {
+        self.reschedule_in_flight();
+
+        loop {
+            let deadline = self.next_deadline();
+            let requests = self.requests.as_mut();
+            let has_requests = requests.is_some();
+
+            let request = tokio::select! {
+                biased;
+                request = async {
+                    match requests {
+                        Some(requests) => requests.recv().await,
+                        None => std::future::pending().await,
+                    }
+                }, if has_requests => request,
+                () = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                    return self.pop_due();
+                }
+                else => return None,
+            };
+
+            match request {
+                Some(request) => return Some(Scheduled::Request(request)),
+                None => self.requests = None,
+            }
+        }
+    }
+
+    /// 最早到期的時間，並移除已被取代或移除的點位
+    fn next_deadline(&mut self) -> Option<Instant> {
+        while let Some(Reverse((due, generation, target))) = self.deadlines.peek() {
+            if self
+                .targets
+                .get(target)
+                .is_some_and(|slot| slot.generation == *generation)
+            {
+                return Some(*due);
+            }
+            self.deadlines.pop();
+        }
+        None
+    }
+
+    fn pop_due(&mut self) -> Option<Scheduled<T>> {
+        let Reverse((due, generation, target)) = self.deadlines.pop()?;
+        self.in_flight = Some((target.clone(), generation));
+        Some(Scheduled::Poll { target, due })
+    }
+
+    fn reschedule_in_flight(&mut self) {
+        let Some((target, generation)) = self.in_flight.take() else {
+            return;
+        };
+        let Some(slot) = self
+            .targets
+            .get(&target)
+            .filter(|slot| slot.generation == generation)
+        else {
+            return;
+        };
+
+        let due = Instant::now() + slot.interval.unwrap_or(self.default_interval);
+        self.deadlines.push(Reverse((due, generation, target)));
+    }
+}
@@ -0,0 +1,183 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::discovery::ProbeOutcome;
+
+pub mod lock;
+#[cfg(feature = "serial")]
+pub mod port;
+
+/// 常見的調變速率（鮑率 baud rate），依實務上的使用頻率排序
+pub const COMMON_BAUD_RATES: &[u32] = &[9600, 19200, 38400, 115_200, 57600, 4800, 2400, 1200];
+
+/// 序列埠同位（parity）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Parity {
+    /// 無同位
+    None,
+    /// 奇同位
+    Odd,
+    /// 偶同位
+    Even,
+}
+
+/// 序列埠線路參數
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SerialSettings {
+    /// 調變速率（鮑率 baud rate）
+    pub baud_rate: u32,
+    /// 數據位
+    pub data_bits: u8,
+    /// 同位
+    pub parity: Parity,
+    /// 停止位
+    pub stop_bits: u8,
+}
+
+impl SerialSettings {
+    /// 產生候選線路參數
+    ///
+    /// 依照 `baud_rates` 與 `parities` 的順序組合，數據位固定為 8 ，無同位時停止位為 2 以外皆為 1 （符合 Modbus RTU 等協定的慣例）
+    ///
+    /// # 參數
+    /// - `baud_rates`：候選調變速率，可使用 [`COMMON_BAUD_RATES`]
+    /// - `parities`：候選同位
+    ///
+    /// # 範例
+    /// ```rust
+    /// use device_state_exchange_lib::serial::{Parity, SerialSettings};
+    ///
+    /// let candidates = SerialSettings::candidates(&[9600], &[Parity::None, Parity::Even]);
+    ///
+    /// assert_eq!(candidates[0].to_string(), "9600 8N2");
+    /// assert_eq!(candidates[1].to_string(), "9600 8E1");
+    /// ```
+    #[must_use]
+    pub fn candidates(baud_rates: &[u32], parities: &[Parity]) -> Vec<Self> {
+        baud_rates
+            .iter()
+            .flat_map(|&baud_rate| {
+                parities.iter().map(move |&parity| Self {
+                    baud_rate,
+                    data_bits: 8,
+                    parity,
+                    stop_bits: if parity == Parity::None { 2 } else { 1 },
+                })
+            })
+            .collect()
+    }
+}
+
+impl Display for SerialSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parity = match self.parity {
+            Parity::None => 'N',
+            Parity::Odd => 'O',
+            Parity::Even => 'E',
+        };
+        write!(
+            f,
+            "{} {}{}{}",
+            self.baud_rate, self.data_bits, parity, self.stop_bits
+        )
+    }
+}
+
+/// 單次探測記錄
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProbeAttempt {
+    /// 本次探測使用的線路參數
+    pub settings: SerialSettings,
+    /// 探測結果
+    pub outcome: ProbeOutcome,
+    /// 本次探測所花費的時間
+    pub elapsed: Duration,
+}
+
+/// 線路參數探測報告
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerialProbeReport {
+    /// 偵測到的線路參數，所有候選參數皆無回應時為 [`None`]
+    pub detected: Option<SerialSettings>,
+    /// 各次探測記錄，依探測順序排列
+    pub attempts: Vec<ProbeAttempt>,
+}
+
+/// 探測序列埠線路參數
+///
+/// 依序以各候選線路參數呼叫 `probe` ，第一個在 `timeout` 內回傳 [`Ok`] 的線路參數即視為偵測結果，並停止後續探測
+///
+/// 實作者可以在 [`crate::Connection::init()`] 中，於設定檔未指定線路參數時呼叫本 function ，並將報告放入 [`crate::discovery::DiscoveryReport`] 回報給主程式
+///
+/// 本 function 利用 [`tokio::time::timeout()`] 計算逾時，需於 tokio runtime 中執行
+///
+/// # 參數
+/// - `candidates`：候選線路參數，參見 [`SerialSettings::candidates()`]
+/// - `timeout`：單次探測的逾時
+/// - `probe`：以指定線路參數開啟序列埠並送出探測請求（如讀取一個已知存在的 Register ）的 closure
+///
+/// # 回傳值
+/// 探測報告，參見 [`SerialProbeReport`]
+///
+/// # 範例
+/// ```rust
+/// use std::time::Duration;
+/// use device_state_exchange_lib::serial::{COMMON_BAUD_RATES, Parity, SerialSettings, probe_line_settings};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let candidates = SerialSettings::candidates(COMMON_BAUD_RATES, &[Parity::None, Parity::Even]);
+///
+/// let report = probe_line_settings(candidates, Duration::from_millis(200), |settings| async move {
+///     // 實際使用時，請在此處以 settings 開啟序列埠並送出探測請求
+///     if settings.baud_rate == 19200 && settings.parity == Parity::Even {
+///         Ok(())
+///     } else {
+///         Err("no response".into())
+///     }
+/// })
+/// .await;
+///
+/// assert_eq!(report.detected.map(|settings| settings.baud_rate), Some(19200));
+/// assert_eq!(report.attempts.len(), 4);
+/// # }
+/// ```
+pub async fn probe_line_settings<F, Fut>(
+    candidates: impl IntoIterator<Item = SerialSettings>,
+    timeout: Duration,
+    mut probe: F,
+) -> SerialProbeReport
+where
+    F: FnMut(SerialSettings) -> Fut,
+    Fut: Future<Output = Result<(), Box<dyn Error + Send + Sync>>>,
+{
+    let mut report = SerialProbeReport::default();
+
+    for settings in candidates {
+        let started = Instant::now();
+        let outcome = match tokio::time::timeout(timeout, probe(settings)).await {
+            Ok(Ok(())) => ProbeOutcome::Responded,
+            Ok(Err(error)) => ProbeOutcome::Failed(error.to_string()),
+            Err(_) => ProbeOutcome::TimedOut,
+        };
+        let responded = outcome == ProbeOutcome::Responded;
+
+        report.attempts.push(ProbeAttempt {
+            settings,
+            outcome,
+            elapsed: started.elapsed(),
+        });
+
+        if responded {
+            report.detected = Some(settings);
+            break;
+        }
+    }
+
+    report
+}
@@ -0,0 +1,208 @@
+//! 跨程序序列埠鎖定
+//!
+//! 同一個序列埠被兩個程序（如兩個主程式實例，或主程式與其他調試工具）同時開啟時，雙方送出的封包會互相干擾，
+//! 本模組利用鎖定檔（lock file）與作業系統的檔案鎖（`flock`/`LockFileEx`）標記序列埠的使用權，讓衝突可以被偵測，而不是產生錯誤的資料
+//!
+//! 鎖定為建議性（advisory）鎖定，只對同樣使用本模組（或遵循相同鎖定檔慣例）的程序有效
+//!
+//! 實作者應在 [`crate::Connection::init()`] 與 [`crate::Connection::reconnect()`] 開啟序列埠前取得 [`PortLock`] ，並與連線一同保存，
+//! [`PortLock`] 被 drop 時會自動釋放鎖定
+
+use std::{
+    error::Error,
+    fmt::Display,
+    fs::{File, OpenOptions, TryLockError},
+    io::{Read, Seek, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// 等待鎖定時，重新嘗試的間隔
+const RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 序列埠被佔用時的處理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum LockPolicy {
+    /// 立即回傳 [`PortLockError::PortBusy`]
+    #[default]
+    Fail,
+    /// 等待其他程序釋放，超過 `timeout` 後回傳 [`PortLockError::PortBusy`]
+    Wait {
+        /// 最長等待時間
+        #[serde(with = "crate::millis")]
+        timeout: Duration,
+    },
+}
+
+/// 序列埠鎖定錯誤
+#[derive(Debug)]
+pub enum PortLockError {
+    /// 序列埠正被其他程序使用
+    PortBusy {
+        /// 裝置名稱
+        port: String,
+        /// 持有鎖定的程序 ID ，無法讀取時為 [`None`]
+        holder: Option<u32>,
+    },
+    /// 建立或鎖定鎖定檔失敗
+    Io(std::io::Error),
+}
+
+impl Display for PortLockError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PortBusy {
+                port,
+                holder: Some(holder),
+            } => write!(f, "序列埠 {port} 正被程序 {holder} 使用"),
+            Self::PortBusy { port, holder: None } => write!(f, "序列埠 {port} 正被其他程序使用"),
+            Self::Io(error) => write!(f, "無法鎖定序列埠：{error}"),
+        }
+    }
+}
+
+impl Error for PortLockError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::PortBusy { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for PortLockError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// 序列埠鎖定
+///
+/// 持有期間，其他程序無法取得同一個序列埠的鎖定，drop 時自動釋放
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::serial::lock::{LockPolicy, PortLock, PortLockError};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let lock_dir = std::env::temp_dir();
+/// let lock = PortLock::acquire("/dev/ttyDOCTEST0", &lock_dir, LockPolicy::Fail).await.unwrap();
+///
+/// let busy = PortLock::acquire("/dev/ttyDOCTEST0", &lock_dir, LockPolicy::Fail).await;
+/// assert!(matches!(busy, Err(PortLockError::PortBusy { holder: Some(_), .. })));
+///
+/// drop(lock);
+/// assert!(PortLock::acquire("/dev/ttyDOCTEST0", &lock_dir, LockPolicy::Fail).await.is_ok());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct PortLock {
+    port: String,
+    path: PathBuf,
+    _file: File,
+}
+
+impl PortLock {
+    /// 取得序列埠鎖定
+    ///
+    /// 鎖定檔位於 `lock_dir` 中，檔名為 `LCK..{裝置名稱}`（路徑分隔符號會被替換為 `_`），內容為持有鎖定的程序 ID
+    ///
+    /// 本 function 利用 [`tokio::time::sleep()`] 等待，需於 tokio runtime 中執行
+    ///
+    /// # 參數
+    /// - `port`：實際的裝置名稱，如 `/dev/ttyUSB0` 、 `COM3`
+    /// - `lock_dir`：存放鎖定檔的目錄，所有程序需使用相同的目錄，可使用 [`default_lock_dir()`]
+    /// - `policy`：序列埠被佔用時的處理方式
+    ///
+    /// # Errors
+    /// 序列埠被佔用（依 `policy` 處理後仍無法取得）時回傳 [`PortLockError::PortBusy`] ，無法建立鎖定檔時回傳 [`PortLockError::Io`]
+    pub async fn acquire(
+        port: &str,
+        lock_dir: &Path,
+        policy: LockPolicy,
+    ) -> Result<Self, PortLockError> {
+        let path = lock_dir.join(lock_file_name(port));
+        let started = Instant::now();
+
+        loop {
+            match Self::try_acquire_at(port, &path)? {
+                Some(lock) => return Ok(lock),
+                None => match policy {
+                    LockPolicy::Wait { timeout } if started.elapsed() < timeout => {
+                        tokio::time::sleep(RETRY_INTERVAL).await;
+                    }
+                    _ => {
+                        return Err(PortLockError::PortBusy {
+                            port: port.to_owned(),
+                            holder: read_holder(&path),
+                        });
+                    }
+                },
+            }
+        }
+    }
+
+    fn try_acquire_at(port: &str, path: &Path) -> Result<Option<Self>, PortLockError> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Ok(None),
+            Err(TryLockError::Error(error)) => return Err(error.into()),
+        }
+
+        file.set_len(0)?;
+        file.rewind()?;
+        write!(file, "{}", std::process::id())?;
+        file.flush()?;
+
+        Ok(Some(Self {
+            port: port.to_owned(),
+            path: path.to_owned(),
+            _file: file,
+        }))
+    }
+
+    /// 被鎖定的裝置名稱
+    #[must_use]
+    pub fn port(&self) -> &str {
+        &self.port
+    }
+
+    /// 鎖定檔路徑
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// 預設的鎖定檔目錄
+///
+/// 為作業系統的暫存目錄，參見 [`std::env::temp_dir()`]
+#[must_use]
+pub fn default_lock_dir() -> PathBuf {
+    std::env::temp_dir()
+}
+
+fn lock_file_name(port: &str) -> String {
+    let name = port
+        .trim_start_matches("/dev/")
+        .trim_start_matches(r"\\.\")
+        .replace(['/', '\\', ':'], "_");
+    format!("LCK..{name}")
+}
+
+fn read_holder(path: &Path) -> Option<u32> {
+    let mut content = String::new();
+    File::open(path).ok()?.read_to_string(&mut content).ok()?;
+    content.trim().parse().ok()
+}
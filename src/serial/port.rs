@@ -0,0 +1,386 @@
+//! 序列埠解析（需啟用 `serial` feature）
+//!
+//! `/dev/ttyUSB0` 、 `COM3` 等裝置名稱會因插拔順序或重新開機而改變，導致設定檔失效，
+//! 本模組讓設定檔可以利用 USB 裝置資訊或固定路徑指定序列埠，並在連線時解析為實際的裝置名稱
+//!
+//! 實作者應在 [`crate::Connection::init()`] 與 [`crate::Connection::reconnect()`] 中都呼叫 [`PortSelector::resolve()`] ，
+//! 如此在轉接器重新插拔而取得不同的裝置名稱後，重新連線時仍可找到正確的序列埠
+//!
+//! 如需在轉接器重新插入時立即重新連線，而非等待失敗次數累積到 [`crate::ConnectionArtifact::max_retry_count`] ，請使用 [`HotplugMonitor`]
+
+use std::{error::Error, fmt::Display, path::Path, time::Duration};
+
+use device_state_exchange_hotplug::DeviceEvents;
+use serde::{Deserialize, Serialize};
+use serialport::{SerialPortInfo, SerialPortType, UsbPortInfo};
+
+use crate::{
+    Connection, ConnectionError,
+    event::{EventBus, EventKind, HotplugChange},
+};
+
+/// 序列埠指定方式
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "by", rename_all = "snake_case")]
+pub enum PortSelector {
+    /// 直接指定裝置名稱，如 `/dev/ttyUSB0` 、 `COM3`
+    Path {
+        /// 裝置名稱
+        path: String,
+    },
+    /// 利用 Linux 的 `/dev/serial/by-id/` 或 `/dev/serial/by-path/` 固定路徑指定
+    ///
+    /// 解析時會跟隨符號連結，回傳實際的裝置名稱
+    ById {
+        /// 固定路徑
+        path: String,
+    },
+    /// 利用 USB 裝置資訊指定
+    Usb {
+        /// USB Vendor ID
+        vid: u16,
+        /// USB Product ID
+        pid: u16,
+        /// USB 序號，同時接有多個相同型號的轉接器時，需要指定序號以區分
+        #[serde(default)]
+        serial_number: Option<String>,
+    },
+    /// 利用裝置顯示名稱指定（適用於 Windows 裝置管理員中顯示的名稱）
+    ///
+    /// 只要裝置的產品名稱包含此處設定的文字即視為符合
+    FriendlyName {
+        /// 裝置顯示名稱
+        name: String,
+    },
+}
+
+/// 序列埠解析錯誤
+#[derive(Debug)]
+pub enum PortResolveError {
+    /// 找不到符合條件的序列埠
+    NotFound(PortSelector),
+    /// 有多個符合條件的序列埠
+    Ambiguous {
+        /// 指定方式
+        selector: PortSelector,
+        /// 所有符合條件的裝置名稱
+        candidates: Vec<String>,
+    },
+    /// 列舉序列埠或解析路徑失敗
+    Io(Box<dyn Error + Send + Sync>),
+}
+
+impl Display for PortResolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound(selector) => write!(f, "找不到符合條件的序列埠：{selector:?}"),
+            Self::Ambiguous {
+                selector,
+                candidates,
+            } => write!(
+                f,
+                "有多個符合條件的序列埠：{selector:?} => {}",
+                candidates.join(", ")
+            ),
+            Self::Io(error) => write!(f, "無法列舉序列埠：{error}"),
+        }
+    }
+}
+
+impl Error for PortResolveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl PortSelector {
+    /// 解析為實際的裝置名稱
+    ///
+    /// 每次呼叫都會重新列舉系統中的序列埠，不會沿用先前的結果
+    ///
+    /// # Errors
+    /// 找不到或有多個符合條件的序列埠、列舉序列埠失敗時回傳 [`PortResolveError`]
+    pub fn resolve(&self) -> Result<String, PortResolveError> {
+        match self {
+            Self::Path { path } => Ok(path.clone()),
+            Self::ById { path } => Path::new(path)
+                .canonicalize()
+                .map(|path| path.to_string_lossy().into_owned())
+                .map_err(|error| match error.kind() {
+                    std::io::ErrorKind::NotFound => PortResolveError::NotFound(self.clone()),
+                    _ => PortResolveError::Io(error.into()),
+                }),
+            Self::Usb { .. } | Self::FriendlyName { .. } => {
+                let ports = serialport::available_ports()
+                    .map_err(|error| PortResolveError::Io(error.into()))?;
+                self.select(&ports)
+            }
+        }
+    }
+
+    fn select(&self, ports: &[SerialPortInfo]) -> Result<String, PortResolveError> {
+        let candidates = ports
+            .iter()
+            .filter(|port| match &port.port_type {
+                SerialPortType::UsbPort(info) => self.matches(info),
+                _ => false,
+            })
+            .map(|port| port.port_name.clone())
+            .collect::<Vec<_>>();
+
+        match <[String; 1]>::try_from(candidates) {
+            Ok([port_name]) => Ok(port_name),
+            Err(candidates) if candidates.is_empty() => {
+                Err(PortResolveError::NotFound(self.clone()))
+            }
+            Err(candidates) => Err(PortResolveError::Ambiguous {
+                selector: self.clone(),
+                candidates,
+            }),
+        }
+    }
+
+    fn matches(&self, info: &UsbPortInfo) -> bool {
+        match self {
+            Self::Usb {
+                vid,
+                pid,
+                serial_number,
+            } => {
+                info.vid == *vid
+                    && info.pid == *pid
+                    && serial_number.as_ref().is_none_or(|serial_number| {
+                        info.serial_number.as_ref() == Some(serial_number)
+                    })
+            }
+            Self::FriendlyName { name } => info
+                .product
+                .as_ref()
+                .is_some_and(|product| product.contains(name.as_str())),
+            Self::Path { .. } | Self::ById { .. } => false,
+        }
+    }
+}
+
+/// 預設的插拔偵測間隔，無法接收系統的插拔通知時使用
+pub const DEFAULT_HOTPLUG_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 可以接收系統的插拔通知時，補充偵測的間隔，用於涵蓋遺漏的通知（如在容器中只收得到部分通知）
+pub const EVENT_DRIVEN_HOTPLUG_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 收到插拔通知後，等待 udev 建立 `/dev/serial/by-id/` 等符號連結的時間
+const HOTPLUG_SETTLE: Duration = Duration::from_millis(250);
+
+/// 序列埠插拔偵測
+///
+/// 利用 [`PortSelector::resolve()`] 重新解析序列埠，並在裝置出現、消失或裝置名稱改變時回報，
+/// 重新解析的時機依平台而定：
+///
+/// - Linux：收到 kernel 的 `tty` uevent（udev 的事件來源）時
+/// - Windows：收到 `WM_DEVICECHANGE` 的插入或移除時
+/// - 其他平台，或無法接收系統的插拔通知時：每隔 [`HotplugMonitor::with_interval()`] 設定的間隔
+///
+/// 可以接收系統的插拔通知時，仍會每隔 [`EVENT_DRIVEN_HOTPLUG_INTERVAL`] 重新解析一次；兩種方式都不需要額外的系統函式庫
+///
+/// 主程式可以在驅動連線的迴圈中等待 [`HotplugMonitor::changed()`] ，於收到 [`HotplugChange::Arrived`] 時立即呼叫 [`crate::Connection::reconnect()`] ，
+/// 或直接使用 [`HotplugMonitor::reconnect_on_arrival()`]
+///
+/// # 範例
+/// ```rust,no_run
+/// use device_state_exchange_lib::{
+///     event::{EventBus, HotplugChange},
+///     serial::port::{HotplugMonitor, PortSelector},
+/// };
+///
+/// # async fn example() {
+/// let selector = PortSelector::Usb { vid: 0x0403, pid: 0x6001, serial_number: None };
+/// let mut monitor = HotplugMonitor::new(selector).with_events(EventBus::default(), "COM-A");
+///
+/// loop {
+///     tokio::select! {
+///         change = monitor.changed() => {
+///             if change == HotplugChange::Arrived {
+///                 // connection.reconnect().await
+///             }
+///         }
+///         // 其他請求處理
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct HotplugMonitor {
+    selector: PortSelector,
+    interval: Duration,
+    current: Option<String>,
+    events: Option<(EventBus, String)>,
+    notifications: Notifications,
+}
+
+/// 系統的插拔通知，在第一次等待時才開啟（Linux 需要在 tokio runtime 中開啟）
+#[derive(Debug)]
+enum Notifications {
+    Unopened,
+    Open(DeviceEvents),
+    Unavailable,
+}
+
+impl HotplugMonitor {
+    /// 建立插拔偵測，並立即解析一次序列埠作為初始狀態
+    #[must_use]
+    pub fn new(selector: PortSelector) -> Self {
+        let current = selector.resolve().ok();
+
+        Self {
+            selector,
+            interval: DEFAULT_HOTPLUG_INTERVAL,
+            current,
+            events: None,
+            notifications: Notifications::Unopened,
+        }
+    }
+
+    /// 只以固定間隔偵測，不接收系統的插拔通知
+    #[must_use]
+    pub fn polling_only(mut self) -> Self {
+        self.notifications = Notifications::Unavailable;
+        self
+    }
+
+    /// 設定無法接收系統的插拔通知時的偵測間隔，預設為 [`DEFAULT_HOTPLUG_INTERVAL`]
+    #[must_use]
+    pub const fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// 偵測到變化時，同時發佈 [`EventKind::Hotplug`] 事件
+    ///
+    /// # 參數
+    /// - `events`：事件匯流排
+    /// - `connection`：連線識別名稱
+    #[must_use]
+    pub fn with_events(mut self, events: EventBus, connection: impl Into<String>) -> Self {
+        self.events = Some((events, connection.into()));
+        self
+    }
+
+    /// 是否正在接收系統的插拔通知
+    ///
+    /// 第一次調用 [`HotplugMonitor::changed()`] 前，以及無法接收通知而改為固定間隔偵測時為 `false`
+    #[must_use]
+    pub const fn is_event_driven(&self) -> bool {
+        matches!(self.notifications, Notifications::Open(_))
+    }
+
+    /// 目前解析到的裝置名稱，裝置不存在時為 [`None`]
+    #[must_use]
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    /// 立即重新解析一次序列埠
+    ///
+    /// # 回傳值
+    /// 與上次解析結果相比的變化，沒有變化時為 [`None`]
+    ///
+    /// 裝置名稱改變（如由 `/dev/ttyUSB0` 變為 `/dev/ttyUSB1`）視為 [`HotplugChange::Arrived`]
+    pub fn poll(&mut self) -> Option<HotplugChange> {
+        let resolved = self.selector.resolve().ok();
+
+        if resolved == self.current {
+            return None;
+        }
+
+        let (change, port) = match (&resolved, &self.current) {
+            (Some(port), _) => (HotplugChange::Arrived, port.clone()),
+            (None, Some(port)) => (HotplugChange::Removed, port.clone()),
+            (None, None) => return None,
+        };
+
+        if let Some((events, connection)) = &self.events {
+            events.publish(EventKind::Hotplug {
+                connection: connection.clone(),
+                port,
+                change,
+            });
+        }
+
+        self.current = resolved;
+        Some(change)
+    }
+
+    /// 等待下一次變化
+    ///
+    /// 每次收到系統的插拔通知或經過偵測間隔時呼叫一次 [`HotplugMonitor::poll()`] ，直到偵測到變化為止；
+    /// 第一次調用時開啟系統的插拔通知，開啟失敗或接收中斷時改為固定間隔偵測
+    ///
+    /// 本 function 可以安全地在 `tokio::select!` 中取消
+    pub async fn changed(&mut self) -> HotplugChange {
+        if matches!(self.notifications, Notifications::Unopened) {
+            self.notifications =
+                DeviceEvents::open().map_or(Notifications::Unavailable, Notifications::Open);
+        }
+
+        loop {
+            let notified = match &mut self.notifications {
+                Notifications::Open(events) => {
+                    tokio::select! {
+                        result = events.next() => Some(result.is_ok()),
+                        () = tokio::time::sleep(EVENT_DRIVEN_HOTPLUG_INTERVAL) => None,
+                    }
+                }
+                Notifications::Unopened | Notifications::Unavailable => {
+                    tokio::time::sleep(self.interval).await;
+                    None
+                }
+            };
+
+            match notified {
+                Some(true) => tokio::time::sleep(HOTPLUG_SETTLE).await,
+                Some(false) => self.notifications = Notifications::Unavailable,
+                None => {}
+            }
+
+            if let Some(change) = self.poll() {
+                return change;
+            }
+        }
+    }
+
+    /// 等待裝置插入（包含裝置名稱改變），並呼叫 `connection` 的 [`Connection::reconnect()`]
+    ///
+    /// 裝置移除時繼續等待；主程式可以在迴圈中重複調用，等待期間可以安全地在 `tokio::select!` 中取消
+    ///
+    /// # 範例
+    /// ```rust,no_run
+    /// use device_state_exchange_lib::{
+    ///     Connection,
+    ///     serial::port::{HotplugMonitor, PortSelector},
+    /// };
+    ///
+    /// async fn supervise<T: Connection>(connection: &mut T) {
+    ///     let selector = PortSelector::ById { path: "/dev/serial/by-id/usb-FTDI_FT232R-if00-port0".to_owned() };
+    ///     let mut monitor = HotplugMonitor::new(selector);
+    ///
+    ///     loop {
+    ///         if let Err(error) = monitor.reconnect_on_arrival(connection).await {
+    ///             eprintln!("重新連線失敗：{error}");
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    /// 回傳 [`Connection::reconnect()`] 的錯誤
+    pub async fn reconnect_on_arrival<T: Connection>(
+        &mut self,
+        connection: &mut T,
+    ) -> Result<(), ConnectionError> {
+        while self.changed().await != HotplugChange::Arrived {}
+        connection.reconnect().await
+    }
+}
@@ -0,0 +1,135 @@
+//! 工作階段續用
+//!
+//! 部分協定的連線帶有工作階段狀態（如 OPC UA 的安全通道、IEC 60870-5-104 的收發序號），完整重新連線的成本很高，
+//! 實作者可以實作 [`crate::Connection::session_state()`] 提供可續用的狀態，並實作 [`crate::Connection::reconnect_with()`] 依 [`ReconnectHint`] 嘗試續用工作階段，
+//! 續用失敗時再退回完整的重新連線
+//!
+//! 主程式在調用 [`crate::Connection::disconnect()`] 前，或失敗次數超過 [`crate::ConnectionArtifact::max_retry_count`] 時，以 [`ReconnectHint::capture()`] 擷取狀態，
+//! 並在需要重新連線時以 [`crate::Connection::reconnect_with()`] 取代 [`crate::Connection::reconnect()`]
+//!
+//! # 範例
+//! ```rust
+//! # use std::{borrow::Cow, error::Error};
+//! # use device_state_exchange_lib::*;
+//! use device_state_exchange_lib::session::{DisconnectReason, ReconnectHint, ReconnectOutcome};
+//! use serde_json::{Value, json};
+//!
+//! # #[derive(Debug, Clone)] struct Config;
+//! # impl ConnectionConfig for Config {}
+//! # #[derive(Debug, Clone)] struct Point;
+//! # impl Target for Point {}
+//! # #[derive(Debug, Clone)] struct Request;
+//! # impl DeviceStateRequest for Request {}
+//! # #[derive(Debug, Clone)] struct Response;
+//! # impl DeviceStateResponse for Response { fn to_value(&self) -> Result<Cow<'_, Value>, value::ConversionError> { Ok(Cow::Owned(Value::Null)) } }
+//! #[derive(Default)]
+//! struct Iec104 {
+//!     send_sequence: u64,
+//! }
+//!
+//! impl Connection for Iec104 {
+//! #   const NAMES: &[&str] = &["Iec104"];
+//! #   type Config = Config;
+//! #   type Target = Point;
+//! #   type Request = Request;
+//! #   type Response = Response;
+//! #   type Result = ();
+//! #   async fn init(_: &Config) -> Result<ConnectionArtifact<Self>, ConnectionError> { Ok(ConnectionArtifact::new(Self::default(), ConnectionStats::new("10.0.0.1:2404", None))) }
+//! #   fn init_targets(&mut self, _: &mut ConnectionStats, _: Vec<Point>) -> ConnectionTargets<Request, ()> { ConnectionTargets(Vec::new()) }
+//! #   async fn request_process(&mut self, _: Request) -> Result<(Response, bool), ConnectionError> { Ok((Response, true)) }
+//! #   async fn update_config(&mut self, _: &Config) -> Result<(), ConnectionError> { Ok(()) }
+//!     fn session_state(&self) -> Option<Value> {
+//!         Some(json!({ "send_sequence": self.send_sequence }))
+//!     }
+//!
+//!     async fn reconnect_with(&mut self, hint: ReconnectHint) -> Result<ReconnectOutcome, ConnectionError> {
+//!         match hint.session.as_ref().and_then(|session| session["send_sequence"].as_u64()) {
+//!             Some(send_sequence) => {
+//!                 self.send_sequence = send_sequence;
+//!                 Ok(ReconnectOutcome::Resumed)
+//!             }
+//!             None => self.reconnect().await.map(|()| ReconnectOutcome::Cold),
+//!         }
+//!     }
+//!
+//!     async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+//!         self.send_sequence = 0;
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let mut connection = Iec104 { send_sequence: 42 };
+//!
+//! let hint = ReconnectHint::capture(&connection, DisconnectReason::Idle);
+//! connection.disconnect().await.unwrap();
+//!
+//! assert_eq!(connection.reconnect_with(hint).await.unwrap(), ReconnectOutcome::Resumed);
+//! assert_eq!(connection.send_sequence, 42);
+//! # }
+//! ```
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::Connection;
+
+/// 中斷連線的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DisconnectReason {
+    /// 閒置超過 [`crate::ConnectionArtifact::idle_timeout`] ，由主程式主動中斷
+    Idle,
+    /// 失敗次數超過 [`crate::ConnectionArtifact::max_retry_count`]
+    Failures,
+}
+
+/// 重新連線提示
+///
+/// 由 [`ReconnectHint::capture()`] 在中斷連線時建立，傳入 [`crate::Connection::reconnect_with()`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReconnectHint {
+    /// 中斷連線前擷取的工作階段狀態，參見 [`crate::Connection::session_state()`]
+    pub session: Option<Value>,
+    /// 中斷連線的原因
+    pub reason: DisconnectReason,
+    /// 擷取狀態的時間，實作者可以依此判斷工作階段是否已經逾期
+    pub captured_at: SystemTime,
+}
+
+impl ReconnectHint {
+    /// 擷取連線的工作階段狀態
+    ///
+    /// 請在調用 [`crate::Connection::disconnect()`] 前，或決定重新連線時調用
+    #[must_use]
+    pub fn capture<T: Connection>(connection: &T, reason: DisconnectReason) -> Self {
+        Self {
+            session: connection.session_state(),
+            reason,
+            captured_at: SystemTime::now(),
+        }
+    }
+
+    /// 建立沒有工作階段狀態的提示，實作者只能完整重新連線
+    #[must_use]
+    pub fn cold(reason: DisconnectReason) -> Self {
+        Self {
+            session: None,
+            reason,
+            captured_at: SystemTime::now(),
+        }
+    }
+}
+
+/// 重新連線結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconnectOutcome {
+    /// 已續用原本的工作階段
+    Resumed,
+    /// 已完整重新連線，原本的工作階段狀態已不再有效
+    Cold,
+}
@@ -0,0 +1,127 @@
+//! 暖機與穩定期
+//!
+//! 部分設備在上電或重新連線後的前幾秒會回覆不正確的數值（如感測器尚未穩定、類比輸入仍在充電），
+//! 實作者可以在 [`crate::Connection::init()`] 中透過 [`crate::ConnectionArtifact::warm_up()`] 宣告暖機時間與 [`SettlePolicy`]
+//!
+//! 主程式依 [`WarmupWindow::from_artifact()`] 建立暖機視窗，在 [`crate::Connection::init()`] 與 [`crate::Connection::reconnect()`] 成功後調用 [`WarmupWindow::restart()`] ，
+//! 並以 [`WarmupWindow::update()`] 取代 [`StateStore::update()`] 寫入點位狀態
+//!
+//! # 範例
+//! ```rust
+//! use std::time::Duration;
+//! use device_state_exchange_lib::{StateStore, settle::{SettlePolicy, WarmupWindow}, value::Quality};
+//! use serde_json::json;
+//!
+//! let store = StateStore::new();
+//! store.register("pressure", None);
+//!
+//! let mut warmup = WarmupWindow::new(Some(Duration::from_secs(5)), SettlePolicy::MarkUncertain);
+//! warmup.restart();
+//!
+//! assert!(warmup.update(&store, "pressure", json!(0.0)));
+//! assert_eq!(store.get("pressure").unwrap().quality, Quality::Uncertain);
+//! ```
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{Connection, ConnectionArtifact, StateStore, value::Quality};
+
+/// 暖機期間的數值處理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettlePolicy {
+    /// 寫入數值，但標記為 [`Quality::Uncertain`]
+    #[default]
+    MarkUncertain,
+    /// 捨棄數值，點位維持原本的狀態
+    Discard,
+}
+
+/// 暖機視窗
+///
+/// 記錄連線最後一次建立的時間，判斷目前是否仍在暖機期間
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WarmupWindow {
+    warmup: Option<Duration>,
+    policy: SettlePolicy,
+    started_at: Option<Instant>,
+}
+
+impl WarmupWindow {
+    /// 建立暖機視窗
+    ///
+    /// 建立後尚未開始計時，請在連線建立後調用 [`WarmupWindow::restart()`]
+    ///
+    /// # 參數
+    /// - `warmup`：暖機時間，為 [`None`] 時不會有暖機期間
+    /// - `policy`：暖機期間的數值處理方式
+    #[must_use]
+    pub const fn new(warmup: Option<Duration>, policy: SettlePolicy) -> Self {
+        Self {
+            warmup,
+            policy,
+            started_at: None,
+        }
+    }
+
+    /// 依 [`ConnectionArtifact::warmup`] 與 [`ConnectionArtifact::settle_policy`] 建立暖機視窗
+    #[must_use]
+    pub const fn from_artifact<T: Connection>(artifact: &ConnectionArtifact<T>) -> Self {
+        Self::new(artifact.warmup, artifact.settle_policy)
+    }
+
+    /// 重新開始計時
+    ///
+    /// 請在 [`crate::Connection::init()`] 與 [`crate::Connection::reconnect()`] 成功後調用
+    pub fn restart(&mut self) {
+        self.started_at = Some(Instant::now());
+    }
+
+    /// 暖機期間的剩餘時間，不在暖機期間時為 [`None`]
+    #[must_use]
+    pub fn remaining(&self) -> Option<Duration> {
+        let warmup = self.warmup?;
+        let elapsed = self.started_at?.elapsed();
+
+        warmup
+            .checked_sub(elapsed)
+            .filter(|remaining| !remaining.is_zero())
+    }
+
+    /// 是否仍在暖機期間
+    #[must_use]
+    pub fn is_settling(&self) -> bool {
+        self.remaining().is_some()
+    }
+
+    /// 目前取得的數值應標記的品質
+    ///
+    /// # 回傳值
+    /// 暖機期間且處理方式為 [`SettlePolicy::Discard`] 時為 [`None`]，代表應捨棄數值
+    #[must_use]
+    pub fn quality(&self) -> Option<Quality> {
+        if !self.is_settling() {
+            return Some(Quality::Good);
+        }
+
+        match self.policy {
+            SettlePolicy::MarkUncertain => Some(Quality::Uncertain),
+            SettlePolicy::Discard => None,
+        }
+    }
+
+    /// 依暖機狀態更新點位狀態
+    ///
+    /// 不在暖機期間時與 [`StateStore::update()`] 相同
+    ///
+    /// # 回傳值
+    /// 數值是否有寫入，點位未登記或數值被捨棄時為 `false`
+    #[must_use]
+    pub fn update(&self, store: &StateStore, name: &str, value: Value) -> bool {
+        self.quality()
+            .is_some_and(|quality| store.update_with_quality(name, value, quality))
+    }
+}
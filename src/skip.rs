@@ -0,0 +1,50 @@
+//! 略過請求的原因
+//!
+//! 主程式可能因為各種原因決定不向設備送出請求（如間隔已過、時鐘調整、點位群組停用），外部界面若只是收不到回覆，
+//! 無法分辨是「設備故障」還是「主程式決定不詢問」，主程式略過請求時，應以下列方式之一回報 [`SkipReason`] ：
+//!
+//! - 以 [`crate::envelope::EnvelopeSource::skipped()`] 產生不含數值的回覆信封，交給事件接收端與外部界面
+//! - 以 [`crate::EventKind::RequestSkipped`] 發佈至 [`crate::EventBus`]
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{envelope::EnvelopeSource, skip::SkipReason, value::Quality};
+//!
+//! let source = EnvelopeSource::new("boiler-room");
+//! let envelope = source.skipped("temperature", &Some("1".to_owned()), SkipReason::Paused);
+//!
+//! assert_eq!(envelope.skipped, Some(SkipReason::Paused));
+//! assert_eq!(envelope.quality, Quality::Uncertain);
+//! assert_eq!(envelope.value, None);
+//! assert_eq!(SkipReason::Paused.to_string(), "點位或連線已暫停");
+//! ```
+
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// 略過請求的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum SkipReason {
+    /// 處理前一個請求時已超過輪詢間隔，本次輪詢被略過
+    IntervalMissed,
+    /// 偵測到作業系統時鐘調整，本次輪詢被略過，參見 [`crate::clock::ClockMonitor`]
+    ClockAdjusted,
+    /// 點位群組停用或連線被租約暫停，參見 [`crate::group::TargetGroups`] 與 [`crate::lease::LeaseManager`]
+    Paused,
+    /// 連續失敗次數過多，主程式暫時停止向設備送出請求
+    CircuitOpen,
+}
+
+impl Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::IntervalMissed => "已超過輪詢間隔",
+            Self::ClockAdjusted => "作業系統時鐘已調整",
+            Self::Paused => "點位或連線已暫停",
+            Self::CircuitOpen => "連續失敗次數過多，暫停送出請求",
+        })
+    }
+}
@@ -0,0 +1,1191 @@
+//! SNMP 參考實作（需啟用 `snmp` feature）
+//!
+//! 以 SNMP v1 或 v2c 輪詢網路設備（如 UPS 、PDU 、交換器），點位為 OID ：
+//!
+//! - 純量點位：同一輪輪詢中的自動更新點位以一個 GET 請求合併讀取（參見 [`SnmpConfig::max_oids_per_request`]），
+//!   讀取結果保留更新間隔的一半，期間內的其他點位直接使用該結果
+//! - 表格點位：設定 `walk` 後以 GETBULK（v1 使用 GETNEXT）走訪整個子樹，數值轉換為以 OID 後綴為鍵的 JSON 物件
+//!
+//! 回覆中保留 SNMP 的原始型別（參見 [`SnmpValue`]），[`crate::DeviceStateResponse::to_value()`] 回傳轉換後的 JSON ；
+//! 本實作為唯讀，寫入請求會回傳 [`crate::command::UnsupportedWrite`]
+//!
+//! 點位設定：
+//!
+//! | 欄位 | 說明 |
+//! | --- | --- |
+//! | `address` | OID ，如 `1.3.6.1.2.1.33.1.2.4.0`，可以有開頭的 `.` |
+//! | `walk` | 是否走訪子樹，未設定時為 `false` |
+//! | `scale` | 讀取後乘上的倍率，只套用於數值型別 |
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{
+//!     TargetDefinition,
+//!     snmp::{Oid, SnmpConfig, SnmpPoint, SnmpValue, SnmpVersion},
+//! };
+//! use serde_json::json;
+//!
+//! let config: SnmpConfig = serde_json::from_value(json!({
+//!     "host": "192.168.1.30",
+//!     "community": "monitor",
+//! }))
+//! .unwrap();
+//! assert_eq!((config.port, config.version), (161, SnmpVersion::V2c));
+//!
+//! let definition: TargetDefinition = serde_json::from_value(json!({
+//!     "name": "UPS 剩餘電量",
+//!     "address": ".1.3.6.1.2.1.33.1.2.4.0",
+//! }))
+//! .unwrap();
+//! let point = SnmpPoint::parse(&definition).unwrap();
+//! assert_eq!(point.oid, Oid::parse("1.3.6.1.2.1.33.1.2.4.0").unwrap());
+//! assert_eq!(point.oid.to_string(), "1.3.6.1.2.1.33.1.2.4.0");
+//!
+//! assert_eq!(SnmpValue::TimeTicks(360_000).to_json(), json!(360_000));
+//! assert_eq!(SnmpValue::IpAddress([192, 168, 1, 30]).to_json(), json!("192.168.1.30"));
+//! assert_eq!(SnmpValue::OctetString(b"UPS-01".to_vec()).to_json(), json!("UPS-01"));
+//! ```
+
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::{Display, Write},
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tokio::{
+    net::UdpSocket,
+    time::{Instant, timeout_at},
+};
+
+use crate::{
+    Connection, ConnectionArtifact, ConnectionConfig, ConnectionError, ConnectionStats,
+    ConnectionTargets, DeviceStateRequest, DeviceStateResponse, InitedTarget, RemoteAddress,
+    Target, TargetDefinition,
+    cache::{CacheLookup, CachePolicy, ResponseCache},
+    definition::InvalidTarget,
+    fixture::FrameDecoder,
+    millis,
+    value::ConversionError,
+};
+
+/// SNMP 預設的 UDP 埠號
+pub const DEFAULT_PORT: u16 = 161;
+
+/// 預設的更新間隔
+pub const DEFAULT_UPDATE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 預設的回覆逾時
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// 預設的單次 GET 請求 OID 數量上限
+pub const DEFAULT_MAX_OIDS_PER_REQUEST: u16 = 16;
+
+/// 預設的 GETBULK 單次取回數量
+pub const DEFAULT_MAX_REPETITIONS: u16 = 16;
+
+/// 可接受的封包長度上限
+const MAX_MESSAGE: usize = 65_507;
+
+const fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+fn default_community() -> String {
+    "public".to_owned()
+}
+
+const fn default_update_interval() -> Duration {
+    DEFAULT_UPDATE_INTERVAL
+}
+
+const fn default_timeout() -> Duration {
+    DEFAULT_TIMEOUT
+}
+
+const fn default_max_oids_per_request() -> u16 {
+    DEFAULT_MAX_OIDS_PER_REQUEST
+}
+
+const fn default_max_repetitions() -> u16 {
+    DEFAULT_MAX_REPETITIONS
+}
+
+/// SNMP 版本
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnmpVersion {
+    /// SNMP v1 ，不支援 GETBULK 與 64 位元計數器
+    V1,
+    /// SNMP v2c
+    #[default]
+    V2c,
+}
+
+impl SnmpVersion {
+    const fn number(self) -> i64 {
+        match self {
+            Self::V1 => 0,
+            Self::V2c => 1,
+        }
+    }
+}
+
+/// SNMP 連線設定
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnmpConfig {
+    /// 設備主機名稱或 IP 位址
+    pub host: String,
+    /// 埠號，預設為 161
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// 社群名稱，預設為 `public`
+    #[serde(default = "default_community")]
+    pub community: String,
+    /// SNMP 版本，預設為 v2c
+    #[serde(default)]
+    pub version: SnmpVersion,
+    /// 更新間隔
+    #[serde(
+        rename = "update_interval_ms",
+        with = "millis",
+        default = "default_update_interval"
+    )]
+    pub update_interval: Duration,
+    /// 回覆逾時
+    #[serde(rename = "timeout_ms", with = "millis", default = "default_timeout")]
+    pub timeout: Duration,
+    /// 單次 GET 請求的 OID 數量上限，設備回覆 tooBig 時請調低
+    ///
+    /// v1 的 GET 請求中只要有一個 OID 不存在，整個請求都會失敗，設為 1 可以避免影響其他點位
+    #[serde(default = "default_max_oids_per_request")]
+    pub max_oids_per_request: u16,
+    /// GETBULK 的 max-repetitions
+    #[serde(default = "default_max_repetitions")]
+    pub max_repetitions: u16,
+    /// 最大重試次數，未設定時使用 [`crate::ConnectionArtifact`] 的預設值
+    #[serde(default)]
+    pub max_retry_count: Option<u32>,
+}
+
+impl std::fmt::Debug for SnmpConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SnmpConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("community", &"<redacted>")
+            .field("version", &self.version)
+            .field("update_interval", &self.update_interval)
+            .field("timeout", &self.timeout)
+            .field("max_oids_per_request", &self.max_oids_per_request)
+            .field("max_repetitions", &self.max_repetitions)
+            .field("max_retry_count", &self.max_retry_count)
+            .finish()
+    }
+}
+
+impl ConnectionConfig for SnmpConfig {}
+
+/// 物件識別碼（OID）
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Oid(pub Vec<u32>);
+
+impl Oid {
+    /// 解析以 `.` 分隔的 OID ，可以有開頭的 `.`
+    ///
+    /// 至少需要兩個節點，第一個節點為 0 至 2 ，第一個節點為 0 或 1 時第二個節點需小於 40
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let arcs = text
+            .strip_prefix('.')
+            .unwrap_or(text)
+            .split('.')
+            .map(|arc| arc.parse().ok())
+            .collect::<Option<Vec<u32>>>()?;
+        match arcs.as_slice() {
+            [0 | 1, second, ..] if *second < 40 => Some(Self(arcs)),
+            [2, ..] if arcs.len() >= 2 => Some(Self(arcs)),
+            _ => None,
+        }
+    }
+
+    /// 是否位於 `root` 的子樹中（不含 `root` 本身）
+    #[must_use]
+    pub fn is_under(&self, root: &Self) -> bool {
+        self.0.len() > root.0.len() && self.0.starts_with(&root.0)
+    }
+
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        let mut contents = Vec::new();
+        let (first, rest) = match self.0.as_slice() {
+            [first, second, rest @ ..] => (first.saturating_mul(40).saturating_add(*second), rest),
+            [first] => (first.saturating_mul(40), &[][..]),
+            [] => (0, &[][..]),
+        };
+        for arc in std::iter::once(first).chain(rest.iter().copied()) {
+            let groups = (0..5)
+                .rev()
+                .map(|group| arc >> (group * 7))
+                .skip_while(|remaining| *remaining == 0)
+                .count()
+                .max(1);
+            for group in (0..groups).rev() {
+                let byte = u8::try_from((arc >> (group * 7)) & 0x7f).unwrap_or_default();
+                contents.push(if group == 0 { byte } else { byte | 0x80 });
+            }
+        }
+        encode_tlv(buffer, 0x06, &contents);
+    }
+
+    fn decode(contents: &[u8]) -> Option<Self> {
+        let mut arcs = Vec::new();
+        let mut arc: u32 = 0;
+        for byte in contents {
+            arc = arc.checked_mul(128)? | u32::from(byte & 0x7f);
+            if byte & 0x80 == 0 {
+                if arcs.is_empty() {
+                    let first = (arc / 40).min(2);
+                    arcs.push(first);
+                    arcs.push(arc - first * 40);
+                } else {
+                    arcs.push(arc);
+                }
+                arc = 0;
+            }
+        }
+        (!arcs.is_empty() && contents.last()? & 0x80 == 0).then_some(Self(arcs))
+    }
+}
+
+impl Display for Oid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, arc) in self.0.iter().enumerate() {
+            if index > 0 {
+                f.write_char('.')?;
+            }
+            write!(f, "{arc}")?;
+        }
+        Ok(())
+    }
+}
+
+/// SNMP 數值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnmpValue {
+    /// 整數（INTEGER）
+    Integer(i64),
+    /// 位元組字串（OCTET STRING）
+    OctetString(Vec<u8>),
+    /// 空值（NULL）
+    Null,
+    /// 物件識別碼（OBJECT IDENTIFIER）
+    ObjectIdentifier(Oid),
+    /// IPv4 位址（IpAddress）
+    IpAddress([u8; 4]),
+    /// 32 位元計數器（Counter32）
+    Counter32(u32),
+    /// 32 位元量測值（Gauge32）
+    Gauge32(u32),
+    /// 時間，單位為百分之一秒（TimeTicks）
+    TimeTicks(u32),
+    /// 不透明資料（Opaque）
+    Opaque(Vec<u8>),
+    /// 64 位元計數器（Counter64），只有 v2c 支援
+    Counter64(u64),
+    /// 物件不存在（noSuchObject）
+    NoSuchObject,
+    /// 物件存在但沒有此實例（noSuchInstance）
+    NoSuchInstance,
+    /// 走訪已到達 MIB 的結尾（endOfMibView）
+    EndOfMibView,
+}
+
+impl SnmpValue {
+    /// 是否為 v2c 的例外數值（[`Self::NoSuchObject`] 、[`Self::NoSuchInstance`] 、[`Self::EndOfMibView`]）
+    #[must_use]
+    pub const fn is_exception(&self) -> bool {
+        matches!(
+            self,
+            Self::NoSuchObject | Self::NoSuchInstance | Self::EndOfMibView
+        )
+    }
+
+    /// 轉換為 JSON
+    ///
+    /// 可列印的 UTF-8 字串轉換為字串，其他位元組字串轉換為以 `:` 分隔的十六進位字串（如 MAC 位址），
+    /// IP 位址與 OID 轉換為字串，例外數值轉換為 `null`
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        match self {
+            Self::Integer(value) => Value::from(*value),
+            Self::OctetString(bytes) => match std::str::from_utf8(bytes) {
+                Ok(text)
+                    if !text
+                        .chars()
+                        .any(|character| character.is_control() && !character.is_whitespace()) =>
+                {
+                    Value::String(text.to_owned())
+                }
+                _ => Value::String(hex(bytes)),
+            },
+            Self::Opaque(bytes) => Value::String(hex(bytes)),
+            Self::ObjectIdentifier(oid) => Value::String(oid.to_string()),
+            Self::IpAddress([a, b, c, d]) => Value::String(format!("{a}.{b}.{c}.{d}")),
+            Self::Counter32(value) | Self::Gauge32(value) | Self::TimeTicks(value) => {
+                Value::from(*value)
+            }
+            Self::Counter64(value) => Value::from(*value),
+            Self::Null | Self::NoSuchObject | Self::NoSuchInstance | Self::EndOfMibView => {
+                Value::Null
+            }
+        }
+    }
+
+    /// 解析 BER 編碼（型別、長度與內容）的數值，如 varbind 中 OID 之後的部分
+    ///
+    /// # Errors
+    /// 資料不完整、包含多餘的資料或不支援的型別時回傳 [`ConnectionError::Protocol`]
+    ///
+    /// # 範例
+    /// 以內建的測試向量驗證：
+    /// ```rust
+    /// use device_state_exchange_lib::{snmp::SnmpValue, vectors};
+    ///
+    /// let report = vectors::built_in_set("snmp/value").unwrap().verify(|frame, value| {
+    ///     match SnmpValue::from_ber(frame) {
+    ///         Ok(decoded) if decoded.to_json() == *value => Ok(()),
+    ///         result => Err(format!("解析結果為 {result:?}")),
+    ///     }
+    /// });
+    /// assert!(report.is_complete(), "{report:?}");
+    /// ```
+    pub fn from_ber(data: &[u8]) -> Result<Self, ConnectionError> {
+        let mut reader = Reader::new(data);
+        let (tag, contents) = reader.tlv()?;
+        if !reader.is_empty() {
+            return Err(malformed());
+        }
+        Self::decode(tag, contents)
+    }
+
+    fn decode(tag: u8, contents: &[u8]) -> Result<Self, ConnectionError> {
+        let unsigned = || {
+            decode_unsigned(contents)
+                .and_then(|value| u32::try_from(value).ok())
+                .ok_or_else(malformed)
+        };
+        Ok(match tag {
+            0x02 => Self::Integer(decode_integer(contents).ok_or_else(malformed)?),
+            0x04 => Self::OctetString(contents.to_vec()),
+            0x05 => Self::Null,
+            0x06 => Self::ObjectIdentifier(Oid::decode(contents).ok_or_else(malformed)?),
+            0x40 => Self::IpAddress(contents.try_into().map_err(|_| malformed())?),
+            0x41 => Self::Counter32(unsigned()?),
+            0x42 => Self::Gauge32(unsigned()?),
+            0x43 => Self::TimeTicks(unsigned()?),
+            0x44 => Self::Opaque(contents.to_vec()),
+            0x46 => Self::Counter64(decode_unsigned(contents).ok_or_else(malformed)?),
+            0x80 => Self::NoSuchObject,
+            0x81 => Self::NoSuchInstance,
+            0x82 => Self::EndOfMibView,
+            tag => {
+                return Err(ConnectionError::Protocol(format!(
+                    "不支援的 SNMP 資料型別 0x{tag:02x}"
+                )));
+            }
+        })
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .enumerate()
+        .fold(String::new(), |mut text, (index, byte)| {
+            if index > 0 {
+                text.push(':');
+            }
+            let _ = write!(text, "{byte:02x}");
+            text
+        })
+}
+
+/// 設備回覆的錯誤
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnmpError {
+    /// 回覆的 error-status 不為 0
+    Status {
+        /// 錯誤狀態，如 `1`（tooBig）、`2`（noSuchName）
+        status: i64,
+        /// 發生錯誤的 OID 位置，由 1 開始，0 代表整個請求
+        index: i64,
+    },
+    /// OID 不存在（v1 的 noSuchName 或 v2c 的 noSuchObject 、noSuchInstance）
+    NoSuchName(Oid),
+}
+
+impl Display for SnmpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Status { status, index } => {
+                let reason = match status {
+                    1 => "回覆過大",
+                    2 => "OID 不存在",
+                    3 => "無效的數值",
+                    4 => "唯讀",
+                    5 => "設備錯誤",
+                    6 => "沒有存取權限",
+                    16 => "授權錯誤",
+                    _ => "未知的錯誤",
+                };
+                write!(
+                    f,
+                    "設備回覆錯誤（error-status {status}，error-index {index}）：{reason}"
+                )
+            }
+            Self::NoSuchName(oid) => write!(f, "OID {oid} 不存在"),
+        }
+    }
+}
+
+impl Error for SnmpError {}
+
+fn malformed() -> ConnectionError {
+    ConnectionError::Protocol("無法解析的 SNMP 封包".to_owned())
+}
+
+fn encode_length(buffer: &mut Vec<u8>, length: usize) {
+    if let Ok(length) = u8::try_from(length)
+        && length < 0x80
+    {
+        buffer.push(length);
+        return;
+    }
+    let bytes = length.to_be_bytes();
+    let skip = bytes.iter().take_while(|byte| **byte == 0).count();
+    buffer.push(0x80 | u8::try_from(bytes.len() - skip).unwrap_or_default());
+    buffer.extend(&bytes[skip..]);
+}
+
+fn encode_tlv(buffer: &mut Vec<u8>, tag: u8, contents: &[u8]) {
+    buffer.push(tag);
+    encode_length(buffer, contents.len());
+    buffer.extend(contents);
+}
+
+fn encode_integer(buffer: &mut Vec<u8>, value: i64) {
+    let bytes = value.to_be_bytes();
+    let skip = bytes
+        .windows(2)
+        .take_while(|pair| {
+            (pair[0] == 0x00 && pair[1] & 0x80 == 0) || (pair[0] == 0xff && pair[1] & 0x80 != 0)
+        })
+        .count();
+    encode_tlv(buffer, 0x02, &bytes[skip..]);
+}
+
+fn decode_integer(contents: &[u8]) -> Option<i64> {
+    let first = *contents.first()?;
+    if contents.len() > 8 {
+        return None;
+    }
+    let mut bytes = [if first & 0x80 == 0 { 0x00 } else { 0xff }; 8];
+    bytes[8 - contents.len()..].copy_from_slice(contents);
+    Some(i64::from_be_bytes(bytes))
+}
+
+/// 解析無號數值，允許為了避免被視為負數而補上的前導 0
+fn decode_unsigned(contents: &[u8]) -> Option<u64> {
+    let contents = match contents {
+        [0, rest @ ..] if !rest.is_empty() => rest,
+        contents => contents,
+    };
+    if contents.is_empty() || contents.len() > 8 {
+        return None;
+    }
+    Some(
+        contents
+            .iter()
+            .fold(0, |value, byte| (value << 8) | u64::from(*byte)),
+    )
+}
+
+/// BER 解析器
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    const fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// 讀取下一個 TLV
+    fn tlv(&mut self) -> Result<(u8, &'a [u8]), ConnectionError> {
+        let [tag, first, rest @ ..] = self.data else {
+            return Err(malformed());
+        };
+        let (length, rest) = if first & 0x80 == 0 {
+            (usize::from(*first), rest)
+        } else {
+            let count = usize::from(first & 0x7f);
+            if count == 0 || count > std::mem::size_of::<usize>() {
+                return Err(malformed());
+            }
+            let bytes = rest.get(..count).ok_or_else(malformed)?;
+            let length = bytes
+                .iter()
+                .fold(0, |length, byte| (length << 8) | usize::from(*byte));
+            (length, &rest[count..])
+        };
+        let contents = rest.get(..length).ok_or_else(malformed)?;
+        self.data = &rest[length..];
+        Ok((*tag, contents))
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<&'a [u8], ConnectionError> {
+        match self.tlv()? {
+            (tag, contents) if tag == expected => Ok(contents),
+            _ => Err(malformed()),
+        }
+    }
+
+    fn integer(&mut self) -> Result<i64, ConnectionError> {
+        decode_integer(self.expect(0x02)?).ok_or_else(malformed)
+    }
+}
+
+/// 請求類型
+#[derive(Debug, Clone, Copy)]
+enum PduKind {
+    Get,
+    GetNext,
+    GetBulk { max_repetitions: u16 },
+}
+
+/// SNMP 用戶端
+///
+/// 一次只處理一個請求，請求編號（request-id）不符的回覆會被捨棄，並繼續等待本次請求的回覆
+///
+/// # 範例
+/// ```rust
+/// use std::time::Duration;
+/// use device_state_exchange_lib::snmp::{Oid, SnmpClient, SnmpError, SnmpValue, SnmpVersion};
+/// use tokio::net::UdpSocket;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// // 模擬設備
+/// let agent = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+/// let address = agent.local_addr().unwrap();
+/// tokio::spawn(async move {
+///     let mut buffer = [0; 1500];
+///     let (length, peer) = agent.recv_from(&mut buffer).await.unwrap();
+///     // 版本 v2c 、社群 public 、GetRequest
+///     assert_eq!(buffer[2..13], [0x02, 0x01, 0x01, 0x04, 0x06, b'p', b'u', b'b', b'l', b'i', b'c']);
+///     assert_eq!(buffer[13], 0xa0);
+///     let request_id = &buffer[15..18];
+///     assert_eq!(request_id[..2], [0x02, 0x01]);
+///
+///     // GetResponse ：sysUpTime.0 = TimeTicks 360000
+///     let mut response = vec![0x30, 0x29, 0x02, 0x01, 0x01, 0x04, 0x06];
+///     response.extend(b"public");
+///     response.extend([0xa2, 0x1c]);
+///     response.extend(request_id);
+///     response.extend([0x02, 0x01, 0x00, 0x02, 0x01, 0x00, 0x30, 0x11, 0x30, 0x0f]);
+///     response.extend([0x06, 0x08, 0x2b, 0x06, 0x01, 0x02, 0x01, 0x01, 0x03, 0x00]);
+///     response.extend([0x43, 0x03, 0x05, 0x7e, 0x40]);
+///     agent.send_to(&response, peer).await.unwrap();
+///     assert!(length > 0);
+/// });
+///
+/// let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+/// socket.connect(address).await.unwrap();
+/// let mut client = SnmpClient::new(socket, SnmpVersion::V2c, "public", Duration::from_millis(500));
+/// let uptime = Oid::parse("1.3.6.1.2.1.1.3.0").unwrap();
+/// let values = client.get(&[uptime.clone()]).await.unwrap();
+/// assert_eq!(values, [(uptime, SnmpValue::TimeTicks(360_000))]);
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SnmpClient {
+    socket: UdpSocket,
+    version: SnmpVersion,
+    community: Vec<u8>,
+    timeout: Duration,
+    request_id: i32,
+}
+
+impl SnmpClient {
+    /// 建立用戶端
+    ///
+    /// # 參數
+    /// - `socket`：已以 [`UdpSocket::connect()`] 連線至設備的 UDP socket
+    /// - `version`：SNMP 版本
+    /// - `community`：社群名稱
+    /// - `timeout`：回覆逾時
+    #[must_use]
+    pub fn new(
+        socket: UdpSocket,
+        version: SnmpVersion,
+        community: impl Into<String>,
+        timeout: Duration,
+    ) -> Self {
+        Self {
+            socket,
+            version,
+            community: community.into().into_bytes(),
+            timeout,
+            request_id: 0,
+        }
+    }
+
+    /// 讀取多個 OID（GetRequest）
+    ///
+    /// # 回傳值
+    /// 與 `oids` 順序相同的 OID 與數值，v2c 中不存在的 OID 以 [`SnmpValue::NoSuchObject`] 或 [`SnmpValue::NoSuchInstance`] 表示
+    ///
+    /// # Errors
+    /// 逾時回傳 [`ConnectionError::Timeout`] ，設備回覆錯誤時回傳包含 [`SnmpError`] 的 [`ConnectionError::Custom`] ，
+    /// 無法解析的回覆回傳 [`ConnectionError::Protocol`]
+    pub async fn get(&mut self, oids: &[Oid]) -> Result<Vec<(Oid, SnmpValue)>, ConnectionError> {
+        let varbinds = self.exchange(PduKind::Get, oids).await?;
+        if varbinds.len() != oids.len() {
+            return Err(ConnectionError::Protocol(
+                "回覆的 OID 數量與請求不符".to_owned(),
+            ));
+        }
+        Ok(varbinds)
+    }
+
+    /// 讀取各 OID 的下一個 OID（GetNextRequest）
+    ///
+    /// # Errors
+    /// 同 [`Self::get()`]
+    pub async fn get_next(
+        &mut self,
+        oids: &[Oid],
+    ) -> Result<Vec<(Oid, SnmpValue)>, ConnectionError> {
+        self.exchange(PduKind::GetNext, oids).await
+    }
+
+    /// 由各 OID 起讀取後續的 `max_repetitions` 個 OID（GetBulkRequest），只有 v2c 支援
+    ///
+    /// # Errors
+    /// 版本為 v1 時回傳 [`ConnectionError::InvalidConfig`] ，其他錯誤同 [`Self::get()`]
+    pub async fn get_bulk(
+        &mut self,
+        oids: &[Oid],
+        max_repetitions: u16,
+    ) -> Result<Vec<(Oid, SnmpValue)>, ConnectionError> {
+        if self.version == SnmpVersion::V1 {
+            return Err(ConnectionError::InvalidConfig(
+                "SNMPv1 不支援 GETBULK".to_owned(),
+            ));
+        }
+        self.exchange(PduKind::GetBulk { max_repetitions }, oids)
+            .await
+    }
+
+    /// 走訪子樹，v2c 使用 GETBULK ，v1 使用 GETNEXT
+    ///
+    /// # 回傳值
+    /// 子樹中的所有 OID 與數值，依 OID 順序排列
+    ///
+    /// # Errors
+    /// 同 [`Self::get()`]，設備回覆的 OID 沒有遞增時回傳 [`ConnectionError::Protocol`]
+    pub async fn walk(
+        &mut self,
+        root: &Oid,
+        max_repetitions: u16,
+    ) -> Result<Vec<(Oid, SnmpValue)>, ConnectionError> {
+        let mut values: Vec<(Oid, SnmpValue)> = Vec::new();
+        let mut cursor = root.clone();
+        loop {
+            let result = match self.version {
+                SnmpVersion::V1 => self.get_next(std::slice::from_ref(&cursor)).await,
+                SnmpVersion::V2c => {
+                    self.get_bulk(std::slice::from_ref(&cursor), max_repetitions.max(1))
+                        .await
+                }
+            };
+            let varbinds = match result {
+                // v1 走訪到 MIB 結尾時回覆 noSuchName
+                Err(error)
+                    if matches!(
+                        error.downcast_ref(),
+                        Some(SnmpError::Status { status: 2, .. })
+                    ) =>
+                {
+                    return Ok(values);
+                }
+                result => result?,
+            };
+            if varbinds.is_empty() {
+                return Ok(values);
+            }
+            for (oid, value) in varbinds {
+                if !oid.is_under(root) || value == SnmpValue::EndOfMibView {
+                    return Ok(values);
+                }
+                if oid <= cursor {
+                    return Err(ConnectionError::Protocol(format!(
+                        "走訪時 OID 沒有遞增：{oid}"
+                    )));
+                }
+                cursor = oid.clone();
+                values.push((oid, value));
+            }
+        }
+    }
+
+    async fn exchange(
+        &mut self,
+        kind: PduKind,
+        oids: &[Oid],
+    ) -> Result<Vec<(Oid, SnmpValue)>, ConnectionError> {
+        self.request_id = self.request_id.wrapping_add(1) & i32::MAX;
+        let message = self.message(kind, oids);
+        self.socket.send(&message).await?;
+
+        let deadline = Instant::now() + self.timeout;
+        let mut buffer = vec![0; MAX_MESSAGE];
+        loop {
+            let length = timeout_at(deadline, self.socket.recv(&mut buffer)).await??;
+            let Some((request_id, result)) = Self::parse(&buffer[..length]) else {
+                continue;
+            };
+            if request_id == i64::from(self.request_id) {
+                return result;
+            }
+        }
+    }
+
+    fn message(&self, kind: PduKind, oids: &[Oid]) -> Vec<u8> {
+        let mut varbinds = Vec::new();
+        for oid in oids {
+            let mut varbind = Vec::new();
+            oid.encode(&mut varbind);
+            encode_tlv(&mut varbind, 0x05, &[]);
+            encode_tlv(&mut varbinds, 0x30, &varbind);
+        }
+
+        let (tag, second, third) = match kind {
+            PduKind::Get => (0xa0, 0, 0),
+            PduKind::GetNext => (0xa1, 0, 0),
+            // non-repeaters 與 max-repetitions
+            PduKind::GetBulk { max_repetitions } => (0xa5, 0, i64::from(max_repetitions)),
+        };
+        let mut pdu = Vec::new();
+        encode_integer(&mut pdu, i64::from(self.request_id));
+        encode_integer(&mut pdu, second);
+        encode_integer(&mut pdu, third);
+        encode_tlv(&mut pdu, 0x30, &varbinds);
+
+        let mut message = Vec::new();
+        encode_integer(&mut message, self.version.number());
+        encode_tlv(&mut message, 0x04, &self.community);
+        encode_tlv(&mut message, tag, &pdu);
+
+        let mut frame = Vec::new();
+        encode_tlv(&mut frame, 0x30, &message);
+        frame
+    }
+
+    /// 解析 `GetResponse` ，無法解析的封包回傳 [`None`]
+    #[expect(clippy::type_complexity)]
+    fn parse(datagram: &[u8]) -> Option<(i64, Result<Vec<(Oid, SnmpValue)>, ConnectionError>)> {
+        let mut message = Reader::new(Reader::new(datagram).expect(0x30).ok()?);
+        message.integer().ok()?;
+        message.expect(0x04).ok()?;
+        let mut pdu = Reader::new(message.expect(0xa2).ok()?);
+        let request_id = pdu.integer().ok()?;
+
+        let result = (|| {
+            let status = pdu.integer()?;
+            let index = pdu.integer()?;
+            let mut varbinds = Reader::new(pdu.expect(0x30)?);
+            let mut values = Vec::new();
+            while !varbinds.is_empty() {
+                let mut varbind = Reader::new(varbinds.expect(0x30)?);
+                let oid = Oid::decode(varbind.expect(0x06)?).ok_or_else(malformed)?;
+                let (tag, contents) = varbind.tlv()?;
+                values.push((oid, SnmpValue::decode(tag, contents)?));
+            }
+            if status != 0 {
+                return Err(ConnectionError::custom(SnmpError::Status { status, index }));
+            }
+            Ok(values)
+        })();
+        Some((request_id, result))
+    }
+}
+
+/// 點位
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnmpPoint {
+    /// OID
+    pub oid: Oid,
+    /// 是否走訪子樹
+    pub walk: bool,
+    /// 倍率
+    pub scale: Option<f64>,
+}
+
+impl SnmpPoint {
+    /// 由通用點位定義轉換，欄位說明參見 [`crate::snmp`]
+    ///
+    /// # Errors
+    /// OID 或其他欄位無效時回傳 [`InvalidTarget`]
+    pub fn parse(definition: &TargetDefinition) -> Result<Self, InvalidTarget> {
+        let invalid = |reason: String| InvalidTarget {
+            target: definition.name.clone(),
+            reason,
+        };
+
+        let oid = Oid::parse(&definition.address)
+            .ok_or_else(|| invalid(format!("無效的 OID「{}」", definition.address)))?;
+        let walk = definition
+            .extra
+            .get("walk")
+            .map(|walk| {
+                walk.as_bool()
+                    .ok_or_else(|| invalid(format!("無效的 walk ：{walk}")))
+            })
+            .transpose()?
+            .unwrap_or_default();
+        let scale = definition
+            .extra
+            .get("scale")
+            .map(|scale| {
+                scale
+                    .as_f64()
+                    .filter(|scale| scale.is_normal())
+                    .ok_or_else(|| invalid(format!("無效的 scale ：{scale}")))
+            })
+            .transpose()?;
+
+        Ok(Self { oid, walk, scale })
+    }
+
+    /// 轉換數值，套用倍率
+    fn convert(&self, value: &SnmpValue) -> Value {
+        let json = value.to_json();
+        match (self.scale, json.as_f64()) {
+            (Some(scale), Some(number)) if !value.is_exception() => Value::from(number * scale),
+            _ => json,
+        }
+    }
+
+    /// 產生回覆，走訪的結果以 OID 後綴為鍵
+    fn respond(&self, varbinds: Vec<(Oid, SnmpValue)>) -> Result<SnmpResponse, ConnectionError> {
+        let value = if self.walk {
+            Value::Object(
+                varbinds
+                    .iter()
+                    .map(|(oid, value)| {
+                        let suffix = Oid(oid.0[self.oid.0.len()..].to_vec());
+                        (suffix.to_string(), self.convert(value))
+                    })
+                    .collect::<Map<_, _>>(),
+            )
+        } else {
+            match varbinds.first() {
+                Some((oid, SnmpValue::NoSuchObject | SnmpValue::NoSuchInstance)) => {
+                    return Err(ConnectionError::custom(SnmpError::NoSuchName(oid.clone())));
+                }
+                Some((_, value)) => self.convert(value),
+                None => return Err(malformed()),
+            }
+        };
+        Ok(SnmpResponse { value, varbinds })
+    }
+}
+
+/// 合併讀取的 OID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadBatch {
+    /// OID ，依順序排列
+    pub oids: Arc<[Oid]>,
+}
+
+impl ReadBatch {
+    fn key(&self) -> String {
+        self.oids[0].to_string()
+    }
+}
+
+/// 點位
+#[derive(Debug, Clone)]
+pub struct SnmpTarget(pub TargetDefinition);
+
+impl Target for SnmpTarget {}
+
+/// 讀取請求
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnmpRequest {
+    /// 點位
+    pub point: SnmpPoint,
+    /// 包含點位的合併讀取，單獨讀取與走訪的點位為 [`None`]
+    pub batch: Option<ReadBatch>,
+}
+
+impl DeviceStateRequest for SnmpRequest {}
+
+/// 讀取回覆
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnmpResponse {
+    /// 轉換後的數值
+    pub value: Value,
+    /// 設備回覆的 OID 與原始型別的數值，純量點位只有一筆
+    pub varbinds: Vec<(Oid, SnmpValue)>,
+}
+
+impl DeviceStateResponse for SnmpResponse {
+    fn to_value(&self) -> Result<Cow<'_, Value>, ConversionError> {
+        Ok(Cow::Borrowed(&self.value))
+    }
+}
+
+/// SNMP 設備連線
+///
+/// 合併讀取的結果會保留更新間隔的一半，期間內的其他點位直接使用該結果，並跳過等待間隔
+pub struct SnmpConnection {
+    config: SnmpConfig,
+    client: SnmpClient,
+    reads: ResponseCache<Arc<[(Oid, SnmpValue)]>>,
+    batches: Vec<ReadBatch>,
+    remote_address: RemoteAddress,
+    rejected: Vec<InvalidTarget>,
+}
+
+impl SnmpConnection {
+    /// 初始化點位時被略過的點位
+    #[must_use]
+    pub fn rejected_targets(&self) -> &[InvalidTarget] {
+        &self.rejected
+    }
+
+    async fn open(config: &SnmpConfig) -> Result<(SnmpClient, SocketAddr), ConnectionError> {
+        let peer = tokio::net::lookup_host((config.host.as_str(), config.port))
+            .await?
+            .next()
+            .ok_or_else(|| {
+                ConnectionError::InvalidConfig(format!("無法解析主機名稱「{}」", config.host))
+            })?;
+        let bind: SocketAddr = if peer.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(bind).await?;
+        socket.connect(peer).await?;
+        Ok((
+            SnmpClient::new(
+                socket,
+                config.version,
+                config.community.clone(),
+                config.timeout,
+            ),
+            peer,
+        ))
+    }
+
+    fn set_ttl(&self) {
+        let policy = CachePolicy {
+            ttl: self.config.update_interval / 2,
+            stale_while_revalidate: Duration::ZERO,
+        };
+        for batch in &self.batches {
+            self.reads.enable(batch.key(), policy);
+        }
+    }
+}
+
+impl Connection for SnmpConnection {
+    const NAMES: &[&str] = &["Snmp"];
+    type Config = SnmpConfig;
+    type Target = SnmpTarget;
+    type Request = SnmpRequest;
+    type Response = SnmpResponse;
+    type Result = ();
+
+    async fn init(config: &SnmpConfig) -> Result<ConnectionArtifact<Self>, ConnectionError> {
+        let (client, peer) = Self::open(config).await?;
+        let statistics = ConnectionStats::new(format!("{}:{}", config.host, config.port), None);
+        statistics.remote_address.set(Some(peer));
+
+        let artifact = ConnectionArtifact::new(
+            Self {
+                config: config.clone(),
+                client,
+                reads: ResponseCache::new(),
+                batches: Vec::new(),
+                remote_address: statistics.remote_address.clone(),
+                rejected: Vec::new(),
+            },
+            statistics,
+        )
+        .update_every(config.update_interval)
+        .timeout_after(config.timeout);
+
+        Ok(match config.max_retry_count {
+            Some(count) => artifact.retry_up_to(count),
+            None => artifact,
+        })
+    }
+
+    fn init_targets(
+        &mut self,
+        connection_statistics: &mut ConnectionStats,
+        targets: Vec<SnmpTarget>,
+    ) -> ConnectionTargets<SnmpRequest, ()> {
+        let mut parsed = Vec::with_capacity(targets.len());
+        for SnmpTarget(definition) in targets {
+            match SnmpPoint::parse(&definition) {
+                Ok(point) => parsed.push((definition, point)),
+                Err(error) => self.rejected.push(error),
+            }
+        }
+
+        let mut polled: Vec<Oid> = parsed
+            .iter()
+            .filter(|(definition, point)| definition.auto_refresh && !point.walk)
+            .map(|(_, point)| point.oid.clone())
+            .collect();
+        polled.sort_unstable();
+        polled.dedup();
+        self.batches = polled
+            .chunks(usize::from(self.config.max_oids_per_request.max(1)))
+            .filter(|oids| oids.len() > 1)
+            .map(|oids| ReadBatch { oids: oids.into() })
+            .collect();
+        self.set_ttl();
+
+        ConnectionTargets(
+            parsed
+                .into_iter()
+                .map(|(definition, point)| InitedTarget {
+                    name: definition.name,
+                    request: SnmpRequest {
+                        batch: self
+                            .batches
+                            .iter()
+                            .find(|batch| {
+                                definition.auto_refresh
+                                    && !point.walk
+                                    && batch.oids.contains(&point.oid)
+                            })
+                            .cloned(),
+                        point,
+                    },
+                    result: (),
+                    default_status: definition.default_status,
+                    auto_refresh: definition.auto_refresh,
+                    refresh_interval: None,
+                    keep_raw_frames: None,
+                    group: None,
+                    safe_state: None,
+                    array: None,
+                    change: None,
+                    statistics: Some(connection_statistics.insert_target(definition.device)),
+                })
+                .collect(),
+        )
+    }
+
+    async fn request_process(
+        &mut self,
+        request: SnmpRequest,
+    ) -> Result<(SnmpResponse, bool), ConnectionError> {
+        if request.point.walk {
+            let varbinds = self
+                .client
+                .walk(&request.point.oid, self.config.max_repetitions)
+                .await?;
+            return Ok((request.point.respond(varbinds)?, true));
+        }
+
+        let Some(batch) = &request.batch else {
+            let varbinds = self
+                .client
+                .get(std::slice::from_ref(&request.point.oid))
+                .await?;
+            return Ok((request.point.respond(varbinds)?, true));
+        };
+        let position = batch
+            .oids
+            .iter()
+            .position(|oid| *oid == request.point.oid)
+            .ok_or_else(|| ConnectionError::Protocol("點位不在合併讀取中".to_owned()))?;
+
+        let (varbinds, wait) = match self.reads.lookup(&batch.key(), None) {
+            CacheLookup::Fresh(varbinds) => (varbinds, false),
+            CacheLookup::Stale { .. } | CacheLookup::Miss => {
+                let varbinds: Arc<[_]> = self.client.get(&batch.oids).await?.into();
+                self.reads.store(&batch.key(), varbinds.clone());
+                (varbinds, true)
+            }
+        };
+        let response = request.point.respond(vec![varbinds[position].clone()])?;
+        Ok((response, wait))
+    }
+
+    async fn reconnect(&mut self) -> Result<(), ConnectionError> {
+        let (client, peer) = Self::open(&self.config).await?;
+        self.client = client;
+        self.remote_address.set(Some(peer));
+        Ok(())
+    }
+
+    async fn update_config(&mut self, new_config: &SnmpConfig) -> Result<(), ConnectionError> {
+        self.config = new_config.clone();
+        self.set_ttl();
+        self.reconnect().await
+    }
+}
+
+/// 以記錄下來的 `GetResponse` 封包解碼點位
+///
+/// `target` 為點位的 OID ，由回覆中取出相同 OID 的數值，不套用 `scale`
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::{fixture::Fixture, snmp::SnmpConnection};
+///
+/// let fixture = Fixture::from_json(r#"{
+///     "connection": "Snmp",
+///     "cases": [
+///         { "name": "sysUpTime", "target": "1.3.6.1.2.1.1.3.0", "frame": "3029 020101 0406 7075626c6963 a21c 020101 020100 020100 3011 300f 0608 2b06010201010300 4303 057e40", "expected": 360000 },
+///         { "name": "noSuchInstance", "target": "1.3.6.1.2.1.1.3.0", "frame": "3026 020101 0406 7075626c6963 a219 020101 020100 020100 300e 300c 0608 2b06010201010300 8100" },
+///         { "name": "error-status", "target": "1.3.6.1.2.1.1.3.0", "frame": "3029 020101 0406 7075626c6963 a21c 020101 020102 020101 3011 300f 0608 2b06010201010300 4303 057e40" }
+///     ]
+/// }"#).unwrap();
+///
+/// let report = fixture.run::<SnmpConnection>();
+/// assert!(report.is_complete(), "{report:?}");
+/// ```
+impl FrameDecoder for SnmpConnection {
+    fn decode_frame(target: &str, frame: &[u8]) -> Result<SnmpResponse, Box<dyn Error>> {
+        let point = SnmpPoint {
+            oid: Oid::parse(target).ok_or_else(|| format!("無效的 OID「{target}」"))?,
+            walk: false,
+            scale: None,
+        };
+        let (_, result) = SnmpClient::parse(frame).ok_or_else(malformed)?;
+        let varbinds = result?
+            .into_iter()
+            .filter(|(oid, _)| *oid == point.oid)
+            .collect();
+        Ok(point.respond(varbinds)?)
+    }
+}
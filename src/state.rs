@@ -0,0 +1,615 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    error::Error,
+    fmt::Display,
+    sync::{Arc, PoisonError, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{DeviceStateResponse, HashMap, value::Quality};
+
+/// 點位狀態儲存區
+///
+/// 主程式在 [`crate::Connection::init_targets()`] 後，需將每個 [`crate::InitedTarget`] 透過 [`StateStore::register()`] 登記至本 struct，
+/// 並在每次 [`crate::Connection::postprocess()`] 完成後，利用 [`StateStore::update()`] 寫入最新狀態，供外部界面查詢
+///
+/// 連線中斷時，主程式應利用 [`StateStore::set_offline()`] 標記所屬的點位，外部界面透過 [`StateStore::read()`] 與 [`StateStore::snapshot()`]
+/// 讀取時，會依各點位的 [`OfflinePolicy`] 決定回傳的內容
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份狀態
+#[derive(Debug, Clone, Default)]
+pub struct StateStore {
+    targets: Arc<RwLock<HashMap<String, StoredTarget>>>,
+}
+
+#[derive(Debug)]
+struct StoredTarget {
+    state: TargetState,
+    default_status: Option<Value>,
+    policy: OfflinePolicy,
+    offline: bool,
+    frozen_after: Option<Duration>,
+}
+
+impl StoredTarget {
+    fn apply(&mut self, value: Option<Value>, quality: Quality, now: SystemTime) {
+        let state = &mut self.state;
+
+        if let Some(value) = value {
+            if state.changed_at.is_none() || state.value.as_ref() != Some(&value) {
+                state.change_count += 1;
+                state.changed_at = Some(now);
+            }
+            state.value = Some(value);
+            state.suspected_frozen = quality == Quality::Good
+                && self.frozen_after.zip(state.changed_at).is_some_and(
+                    |(frozen_after, changed_at)| {
+                        now.duration_since(changed_at).unwrap_or_default() > frozen_after
+                    },
+                );
+        }
+
+        state.quality = quality;
+        state.updated_at = Some(now);
+        state.sequence += 1;
+    }
+
+    /// 依離線處理方式產生外部界面看到的狀態，離線且處理方式為 [`OfflinePolicy::Fail`] 時為 [`None`]
+    fn effective(&self) -> Option<TargetState> {
+        if !self.offline {
+            return Some(self.state.clone());
+        }
+
+        match self.policy {
+            OfflinePolicy::Fail => None,
+            OfflinePolicy::ServeLastKnown => Some(TargetState {
+                quality: Quality::Stale,
+                ..self.state.clone()
+            }),
+            OfflinePolicy::ServeDefault => Some(TargetState {
+                value: self.default_status.clone(),
+                quality: Quality::Uncertain,
+                ..self.state.clone()
+            }),
+        }
+    }
+}
+
+/// 離線處理方式
+///
+/// 點位所屬的連線中斷時，外部界面讀取點位狀態的結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OfflinePolicy {
+    /// 讀取失敗，[`StateStore::read()`] 回傳 [`StateReadError::Offline`]
+    #[default]
+    Fail,
+    /// 回傳最後一次取得的數值，並標記為 [`Quality::Stale`]
+    ServeLastKnown,
+    /// 回傳 [`crate::InitedTarget::default_status`] ，並標記為 [`Quality::Uncertain`]
+    ServeDefault,
+}
+
+/// 點位狀態讀取錯誤
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StateReadError {
+    /// 點位未登記
+    NotRegistered(String),
+    /// 點位所屬的連線中斷，且離線處理方式為 [`OfflinePolicy::Fail`]
+    Offline(String),
+}
+
+impl Display for StateReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotRegistered(name) => write!(f, "點位未登記：{name}"),
+            Self::Offline(name) => write!(f, "點位所屬的連線已中斷：{name}"),
+        }
+    }
+}
+
+impl Error for StateReadError {}
+
+/// 點位狀態
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetState {
+    /// 點位目前的狀態
+    ///
+    /// 尚未取得數值時，為登記時傳入的 [`crate::InitedTarget::default_status`]
+    pub value: Option<Value>,
+    /// 數值品質
+    ///
+    /// 登記時為 [`Quality::Uncertain`] ，成功更新後為 [`Quality::Good`] ，設備回覆無法轉換時為 [`Quality::Bad`] ，
+    /// 連線中斷時依 [`OfflinePolicy`] 決定
+    pub quality: Quality,
+    /// 最後一次更新的時間，尚未更新過時為 [`None`]
+    pub updated_at: Option<SystemTime>,
+    /// 更新序號
+    ///
+    /// 登記時為 0 ，每次更新遞增，[`StateStore::import_state()`] 時會一併轉移，可用於判斷數值是否較新
+    #[serde(default)]
+    pub sequence: u64,
+    /// 數值實際變動的次數
+    ///
+    /// 只有數值與前一次不同時才會遞增，第一次取得數值也視為變動
+    #[serde(default)]
+    pub change_count: u64,
+    /// 最後一次數值變動的時間，尚未取得數值時為 [`None`]
+    #[serde(default)]
+    pub changed_at: Option<SystemTime>,
+    /// 是否疑似凍結
+    ///
+    /// 設定了 [`StateStore::set_frozen_after()`] 的點位，輪詢成功但數值超過設定時間沒有變動時為 `true` ，可用於找出故障後維持固定讀值的感測器
+    #[serde(default)]
+    pub suspected_frozen: bool,
+}
+
+/// 目前的匯出格式版本，參見 [`StateStore::export_state()`]
+pub const STATE_EXPORT_VERSION: u32 = 1;
+
+/// 點位狀態儲存區匯出資料
+///
+/// 由 [`StateStore::export_state()`] 產生，可序列化後傳送至備援主機，再由 [`StateStore::import_state()`] 匯入
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateExport {
+    /// 匯出格式版本
+    pub version: u32,
+    /// 各點位的狀態與設定
+    pub targets: BTreeMap<String, ExportedTarget>,
+}
+
+/// 匯出的點位
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedTarget {
+    /// 點位狀態
+    #[serde(flatten)]
+    pub state: TargetState,
+    /// 點位初始狀態
+    pub default_status: Option<Value>,
+    /// 離線處理方式
+    #[serde(default)]
+    pub policy: OfflinePolicy,
+    /// 點位所屬的連線是否中斷
+    #[serde(default)]
+    pub offline: bool,
+    /// 疑似凍結的判斷時間，參見 [`StateStore::set_frozen_after()`]
+    #[serde(
+        default,
+        rename = "frozen_after_ms",
+        with = "crate::millis::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub frozen_after: Option<Duration>,
+}
+
+/// 匯出資料的版本不受支援
+///
+/// 匯出資料由較新版本的程式產生時回傳，參見 [`StateStore::import_state()`] 與 [`crate::ConnectionStats::import_state()`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedExportVersion {
+    /// 匯出資料的版本
+    pub found: u32,
+    /// 目前支援的版本
+    pub supported: u32,
+}
+
+impl Display for UnsupportedExportVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "不支援的匯出資料版本：{}（目前支援的版本為 {}）",
+            self.found, self.supported
+        )
+    }
+}
+
+impl Error for UnsupportedExportVersion {}
+
+impl StateStore {
+    /// 建立空的點位狀態儲存區
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登記點位
+    ///
+    /// 如點位已經存在，會以新的初始狀態取代既有狀態，離線處理方式為 [`OfflinePolicy::Fail`]
+    ///
+    /// # 參數
+    /// - `name`：點位名稱
+    /// - `default_status`：點位初始狀態
+    pub fn register(&self, name: impl Into<String>, default_status: Option<Value>) {
+        self.register_with_policy(name, default_status, OfflinePolicy::default());
+    }
+
+    /// 登記點位，並指定離線處理方式
+    ///
+    /// # 參數
+    /// - `name`：點位名稱
+    /// - `default_status`：點位初始狀態
+    /// - `policy`：離線處理方式
+    ///
+    /// # 範例
+    /// ```rust
+    /// use device_state_exchange_lib::{state::{OfflinePolicy, StateReadError, StateStore}, value::Quality};
+    /// use serde_json::json;
+    ///
+    /// let store = StateStore::new();
+    /// store.register_with_policy("temperature", None, OfflinePolicy::ServeLastKnown);
+    /// store.register_with_policy("mode", Some(json!("auto")), OfflinePolicy::ServeDefault);
+    /// store.register("valve", None);
+    ///
+    /// let _ = store.update("temperature", json!(21.5));
+    /// let _ = store.update("mode", json!("manual"));
+    ///
+    /// // 連線中斷
+    /// for name in ["temperature", "mode", "valve"] {
+    ///     store.set_offline(name, true);
+    /// }
+    ///
+    /// let temperature = store.read("temperature").unwrap();
+    /// assert_eq!((temperature.value, temperature.quality), (Some(json!(21.5)), Quality::Stale));
+    /// assert_eq!(store.read("mode").unwrap().value, Some(json!("auto")));
+    /// assert_eq!(store.read("valve").unwrap_err(), StateReadError::Offline("valve".to_owned()));
+    /// ```
+    pub fn register_with_policy(
+        &self,
+        name: impl Into<String>,
+        default_status: Option<Value>,
+        policy: OfflinePolicy,
+    ) {
+        self.targets
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                name.into(),
+                StoredTarget {
+                    state: TargetState {
+                        value: default_status.clone(),
+                        quality: Quality::Uncertain,
+                        updated_at: None,
+                        sequence: 0,
+                        change_count: 0,
+                        changed_at: None,
+                        suspected_frozen: false,
+                    },
+                    default_status,
+                    policy,
+                    offline: false,
+                    frozen_after: None,
+                },
+            );
+    }
+
+    /// 變更點位的離線處理方式
+    ///
+    /// # 回傳值
+    /// 點位是否已登記
+    pub fn set_policy(&self, name: &str, policy: OfflinePolicy) -> bool {
+        self.targets
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(name)
+            .map(|target| target.policy = policy)
+            .is_some()
+    }
+
+    /// 設定點位疑似凍結的判斷時間
+    ///
+    /// 輪詢成功但數值超過此時間沒有變動時，點位會被標記為 [`TargetState::suspected_frozen`] ，
+    /// 請依設備特性設定，如室溫感測器可設定為數小時，預設不判斷
+    ///
+    /// # 參數
+    /// - `name`：點位名稱
+    /// - `frozen_after`：判斷時間，為 [`None`] 時不判斷
+    ///
+    /// # 回傳值
+    /// 點位是否已登記
+    ///
+    /// # 範例
+    /// ```rust
+    /// use std::time::Duration;
+    /// use device_state_exchange_lib::state::StateStore;
+    /// use serde_json::json;
+    ///
+    /// let store = StateStore::new();
+    /// store.register("temperature", None);
+    /// store.set_frozen_after("temperature", Some(Duration::from_millis(10)));
+    ///
+    /// let _ = store.update("temperature", json!(21.5));
+    /// std::thread::sleep(Duration::from_millis(20));
+    /// let _ = store.update("temperature", json!(21.5));
+    ///
+    /// let state = store.get("temperature").unwrap();
+    /// assert_eq!((state.sequence, state.change_count), (2, 1));
+    /// assert!(state.suspected_frozen);
+    ///
+    /// let _ = store.update("temperature", json!(21.6));
+    /// assert!(!store.get("temperature").unwrap().suspected_frozen);
+    /// ```
+    pub fn set_frozen_after(&self, name: &str, frozen_after: Option<Duration>) -> bool {
+        self.targets
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(name)
+            .map(|target| target.frozen_after = frozen_after)
+            .is_some()
+    }
+
+    /// 標記點位所屬的連線中斷或恢復
+    ///
+    /// 連線恢復後，點位會維持離線前的狀態，直到下一次更新
+    ///
+    /// # 參數
+    /// - `name`：點位名稱
+    /// - `offline`：連線是否中斷
+    ///
+    /// # 回傳值
+    /// 點位是否已登記
+    pub fn set_offline(&self, name: &str, offline: bool) -> bool {
+        self.targets
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(name)
+            .map(|target| target.offline = offline)
+            .is_some()
+    }
+
+    /// 更新點位狀態
+    ///
+    /// # 參數
+    /// - `name`：點位名稱
+    /// - `value`：最新狀態
+    ///
+    /// # 回傳值
+    /// 點位是否已登記，未登記的點位不會被寫入
+    #[must_use]
+    pub fn update(&self, name: &str, value: Value) -> bool {
+        self.update_with_quality(name, value, Quality::Good)
+    }
+
+    /// 更新點位狀態，並指定數值品質
+    ///
+    /// # 參數
+    /// - `name`：點位名稱
+    /// - `value`：最新狀態
+    /// - `quality`：數值品質，如暖機期間的數值可標記為 [`Quality::Uncertain`]
+    ///
+    /// # 回傳值
+    /// 點位是否已登記，未登記的點位不會被寫入
+    #[must_use]
+    pub fn update_with_quality(&self, name: &str, value: Value, quality: Quality) -> bool {
+        self.modify(name, Some(value), quality)
+    }
+
+    /// 以設備回覆更新點位狀態
+    ///
+    /// [`DeviceStateResponse::to_value()`] 轉換成功時與 [`StateStore::update()`] 相同；轉換失敗時保留原本的數值，並將點位標記為 [`Quality::Bad`]
+    ///
+    /// # 參數
+    /// - `name`：點位名稱
+    /// - `response`：經過 [`crate::Connection::postprocess()`] 的設備回覆
+    ///
+    /// # 回傳值
+    /// 點位是否已登記，未登記的點位不會被寫入
+    ///
+    /// # 範例
+    /// ```rust
+    /// # use std::borrow::Cow;
+    /// # use device_state_exchange_lib::{DeviceStateResponse, value::{ConversionError, Quality}};
+    /// use device_state_exchange_lib::state::StateStore;
+    ///
+    /// #[derive(Debug, Clone)]
+    /// struct Temperature(f64);
+    ///
+    /// impl DeviceStateResponse for Temperature {
+    ///     fn to_value(&self) -> Result<Cow<'_, serde_json::Value>, ConversionError> {
+    ///         device_state_exchange_lib::value::finite(self.0).map(Cow::Owned)
+    ///     }
+    /// }
+    ///
+    /// let store = StateStore::new();
+    /// store.register("temperature", None);
+    ///
+    /// store.update_response("temperature", &Temperature(21.5));
+    /// store.update_response("temperature", &Temperature(f64::NAN));
+    ///
+    /// let state = store.get("temperature").unwrap();
+    /// assert_eq!(state.value, Some(serde_json::json!(21.5)));
+    /// assert_eq!(state.quality, Quality::Bad);
+    /// ```
+    pub fn update_response(&self, name: &str, response: &dyn DeviceStateResponse) -> bool {
+        let value = response.to_value().map(Cow::into_owned).ok();
+        let quality = if value.is_some() {
+            Quality::Good
+        } else {
+            Quality::Bad
+        };
+
+        self.modify(name, value, quality)
+    }
+
+    /// 變更點位的數值品質，並保留原本的數值
+    ///
+    /// # 參數
+    /// - `name`：點位名稱
+    /// - `quality`：數值品質，如設備回覆中缺少此點位時標記為 [`Quality::Bad`]
+    ///
+    /// # 回傳值
+    /// 點位是否已登記
+    #[must_use]
+    pub fn set_quality(&self, name: &str, quality: Quality) -> bool {
+        self.modify(name, None, quality)
+    }
+
+    /// 更新點位狀態，`value` 為 [`None`] 時保留原本的數值
+    fn modify(&self, name: &str, value: Option<Value>, quality: Quality) -> bool {
+        self.targets
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(name)
+            .map(|target| target.apply(value, quality, SystemTime::now()))
+            .is_some()
+    }
+
+    /// 移除點位
+    pub fn remove(&self, name: &str) -> Option<TargetState> {
+        self.targets
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(name)
+            .map(|target| target.state)
+    }
+
+    /// 取得點位最後一次寫入的狀態
+    ///
+    /// 不套用 [`OfflinePolicy`] ，外部界面請使用 [`StateStore::read()`]
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<TargetState> {
+        self.targets
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .map(|target| target.state.clone())
+    }
+
+    /// 讀取點位狀態，點位所屬的連線中斷時依 [`OfflinePolicy`] 處理
+    ///
+    /// # Errors
+    /// 點位未登記，或連線中斷且離線處理方式為 [`OfflinePolicy::Fail`] 時回傳 [`StateReadError`]
+    pub fn read(&self, name: &str) -> Result<TargetState, StateReadError> {
+        self.targets
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(name)
+            .ok_or_else(|| StateReadError::NotRegistered(name.to_owned()))?
+            .effective()
+            .ok_or_else(|| StateReadError::Offline(name.to_owned()))
+    }
+
+    /// 點位是否已登記
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.targets
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains_key(name)
+    }
+
+    /// 取得所有點位狀態的複本
+    ///
+    /// 點位所屬的連線中斷時依 [`OfflinePolicy`] 處理，處理方式為 [`OfflinePolicy::Fail`] 的點位數值為 [`None`] ，並標記為 [`Quality::Bad`]
+    #[must_use]
+    pub fn snapshot(&self) -> std::collections::HashMap<String, TargetState> {
+        self.targets
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(name, target)| {
+                let state = target.effective().unwrap_or_else(|| TargetState {
+                    value: None,
+                    quality: Quality::Bad,
+                    ..target.state.clone()
+                });
+                (name.clone(), state)
+            })
+            .collect()
+    }
+
+    /// 匯出所有點位的狀態與設定
+    ///
+    /// 用於主機之間的熱備援切換，備援主機匯入後即可提供與原主機相同的狀態，不需要等待完整的輪詢週期
+    ///
+    /// # 範例
+    /// ```rust
+    /// use device_state_exchange_lib::state::{OfflinePolicy, StateStore};
+    /// use serde_json::json;
+    ///
+    /// let primary = StateStore::new();
+    /// primary.register_with_policy("temperature", None, OfflinePolicy::ServeLastKnown);
+    /// let _ = primary.update("temperature", json!(21.5));
+    ///
+    /// let transferred = serde_json::to_string(&primary.export_state()).unwrap();
+    ///
+    /// let standby = StateStore::new();
+    /// standby.import_state(serde_json::from_str(&transferred).unwrap()).unwrap();
+    /// assert_eq!(standby.get("temperature"), primary.get("temperature"));
+    /// assert_eq!(standby.get("temperature").unwrap().sequence, 1);
+    /// ```
+    #[must_use]
+    pub fn export_state(&self) -> StateExport {
+        StateExport {
+            version: STATE_EXPORT_VERSION,
+            targets: self
+                .targets
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .iter()
+                .map(|(name, target)| {
+                    (
+                        name.clone(),
+                        ExportedTarget {
+                            state: target.state.clone(),
+                            default_status: target.default_status.clone(),
+                            policy: target.policy,
+                            offline: target.offline,
+                            frozen_after: target.frozen_after,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// 匯入 [`StateStore::export_state()`] 匯出的資料
+    ///
+    /// 匯出資料中的點位會取代同名的既有點位，其餘既有點位維持不變
+    ///
+    /// # 回傳值
+    /// 匯入的點位數量
+    ///
+    /// # Errors
+    /// 匯出資料的版本較新時回傳 [`UnsupportedExportVersion`]
+    pub fn import_state(&self, export: StateExport) -> Result<usize, UnsupportedExportVersion> {
+        check_export_version(export.version)?;
+
+        let count = export.targets.len();
+        self.targets
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend(export.targets.into_iter().map(|(name, target)| {
+                (
+                    name,
+                    StoredTarget {
+                        state: target.state,
+                        default_status: target.default_status,
+                        policy: target.policy,
+                        offline: target.offline,
+                        frozen_after: target.frozen_after,
+                    },
+                )
+            }));
+
+        Ok(count)
+    }
+}
+
+/// 檢查匯出資料的版本
+///
+/// # Errors
+/// 版本較 [`STATE_EXPORT_VERSION`] 新時回傳 [`UnsupportedExportVersion`]
+pub(crate) const fn check_export_version(version: u32) -> Result<(), UnsupportedExportVersion> {
+    if version > STATE_EXPORT_VERSION {
+        Err(UnsupportedExportVersion {
+            found: version,
+            supported: STATE_EXPORT_VERSION,
+        })
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use std::{error::Error, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::HashMap;
+
+/// 點位樣板
+///
+/// 設備上有大量相似點位時（如 16 個通道，每個通道的位址間隔固定），可以利用本 struct 定義一個樣板與參數，
+/// 在呼叫 [`crate::Connection::init_targets()`] 前展開成實際的點位
+///
+/// 樣板中的字串可以利用 `{參數名稱}` 插入參數值，如字串內容只有單一參數（如 `"{address}"`），展開後會直接替換為數字
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::template::{TargetTemplate, TemplateParameter};
+/// use serde_json::json;
+///
+/// let template = TargetTemplate {
+///     template: json!({ "name": "CH{channel} 電壓", "address": "{address}" }),
+///     count: 16,
+///     parameters: vec![
+///         TemplateParameter { name: "channel".into(), start: 1, step: 1 },
+///         TemplateParameter { name: "address".into(), start: 40001, step: 2 },
+///     ],
+/// };
+///
+/// let targets = template.expand().unwrap();
+///
+/// assert_eq!(targets.len(), 16);
+/// assert_eq!(targets[2], json!({ "name": "CH3 電壓", "address": 40005 }));
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetTemplate {
+    /// 點位樣板
+    pub template: Value,
+    /// 展開的點位數量
+    pub count: usize,
+    /// 樣板參數
+    pub parameters: Vec<TemplateParameter>,
+}
+
+/// 樣板參數
+///
+/// 展開第 `n` 個點位（由 0 起算）時，參數值為 `start + step × n`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateParameter {
+    /// 參數名稱
+    pub name: String,
+    /// 起始值
+    pub start: i64,
+    /// 間隔
+    #[serde(default = "default_step")]
+    pub step: i64,
+}
+
+const fn default_step() -> i64 {
+    1
+}
+
+/// 樣板展開錯誤
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    /// 樣板中使用了未定義的參數
+    UnknownParameter(String),
+    /// 樣板中有未閉合的 `{`
+    UnclosedPlaceholder(String),
+    /// 參數值超出 [`i64`] 範圍
+    Overflow(String),
+}
+
+impl Display for TemplateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownParameter(name) => write!(f, "未定義的樣板參數：{name}"),
+            Self::UnclosedPlaceholder(text) => write!(f, "樣板字串中有未閉合的 {{：{text}"),
+            Self::Overflow(name) => write!(f, "樣板參數 {name} 的數值超出範圍"),
+        }
+    }
+}
+
+impl Error for TemplateError {}
+
+impl TargetTemplate {
+    /// 展開樣板
+    ///
+    /// # 回傳值
+    /// 展開後的點位，可回傳錯誤
+    ///
+    /// # Errors
+    /// 樣板中使用了未定義的參數、有未閉合的 `{` 或參數值溢位時回傳 [`TemplateError`]
+    pub fn expand(&self) -> Result<Vec<Value>, TemplateError> {
+        (0..self.count)
+            .map(|index| {
+                let values = self.values_at(index)?;
+                interpolate(&self.template, &values)
+            })
+            .collect()
+    }
+
+    /// 展開樣板並轉換為點位型別
+    ///
+    /// # 參數
+    /// - `convert`：將展開後的 [`Value`] 轉換為 [`crate::Connection::Target`] 的 closure
+    ///
+    /// # Errors
+    /// 同 [`TargetTemplate::expand()`]
+    pub fn expand_into<T>(&self, convert: impl FnMut(Value) -> T) -> Result<Vec<T>, TemplateError> {
+        Ok(self.expand()?.into_iter().map(convert).collect())
+    }
+
+    fn values_at(&self, index: usize) -> Result<HashMap<&str, i64>, TemplateError> {
+        self.parameters
+            .iter()
+            .map(|parameter| {
+                i64::try_from(index)
+                    .ok()
+                    .and_then(|index| parameter.step.checked_mul(index))
+                    .and_then(|offset| parameter.start.checked_add(offset))
+                    .map(|value| (parameter.name.as_str(), value))
+                    .ok_or_else(|| TemplateError::Overflow(parameter.name.clone()))
+            })
+            .collect()
+    }
+}
+
+fn interpolate(template: &Value, values: &HashMap<&str, i64>) -> Result<Value, TemplateError> {
+    Ok(match template {
+        Value::String(text) => {
+            if let Some(name) = text
+                .strip_prefix('{')
+                .and_then(|text| text.strip_suffix('}'))
+                .filter(|name| !name.contains(['{', '}']))
+            {
+                values
+                    .get(name)
+                    .map(|value| Value::from(*value))
+                    .ok_or_else(|| TemplateError::UnknownParameter(name.to_owned()))?
+            } else {
+                Value::String(interpolate_str(text, values)?)
+            }
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| interpolate(item, values))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(key, value)| {
+                    Ok((interpolate_str(key, values)?, interpolate(value, values)?))
+                })
+                .collect::<Result<_, TemplateError>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+fn interpolate_str(text: &str, values: &HashMap<&str, i64>) -> Result<String, TemplateError> {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| TemplateError::UnclosedPlaceholder(text.to_owned()))?;
+        let name = &rest[start + 1..start + end];
+        let value = values
+            .get(name)
+            .ok_or_else(|| TemplateError::UnknownParameter(name.to_owned()))?;
+        output.push_str(&value.to_string());
+        rest = &rest[start + end + 1..];
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
@@ -0,0 +1,202 @@
+use std::{
+    fmt::Display,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::{
+    CommandQueue, ConnectionStatsRegistry, EventBus, HashMap, StateStore,
+    diagnostics::{RawFrameStore, RequestJournal},
+    registry::ConnectionRegistry,
+};
+
+/// 租戶識別名稱
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TenantId(String);
+
+impl TenantId {
+    /// 建立租戶識別名稱
+    #[must_use]
+    pub fn new(id: impl AsRef<str>) -> Self {
+        Self(id.as_ref().to_owned())
+    }
+
+    /// 取得字串形式的租戶識別名稱
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for TenantId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for TenantId {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+/// 租戶
+///
+/// 每個租戶持有各自獨立的 [`StateStore`] 、 [`CommandQueue`] 、 [`EventBus`] 、 [`ConnectionRegistry`] 、 [`ConnectionStatsRegistry`] 、 [`RawFrameStore`] 與 [`RequestJournal`] ，
+/// 本 struct 沒有提供任何存取其他租戶資料的方法，主程式只需將對應的 [`Tenant`] 交給該租戶的設備連線與外部界面，即可避免跨租戶存取
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份資料
+#[derive(Debug, Clone)]
+pub struct Tenant {
+    id: TenantId,
+    store: StateStore,
+    commands: CommandQueue,
+    events: EventBus,
+    connections: Arc<Mutex<ConnectionRegistry>>,
+    statistics: ConnectionStatsRegistry,
+    raw_frames: RawFrameStore,
+    journal: RequestJournal,
+}
+
+impl Tenant {
+    fn new(id: TenantId) -> Self {
+        Self {
+            id,
+            store: StateStore::new(),
+            commands: CommandQueue::new(),
+            events: EventBus::default(),
+            connections: Arc::default(),
+            statistics: ConnectionStatsRegistry::new(),
+            raw_frames: RawFrameStore::new(),
+            journal: RequestJournal::new(),
+        }
+    }
+
+    /// 租戶識別名稱
+    #[must_use]
+    pub const fn id(&self) -> &TenantId {
+        &self.id
+    }
+
+    /// 本租戶的點位狀態儲存區
+    #[must_use]
+    pub const fn store(&self) -> &StateStore {
+        &self.store
+    }
+
+    /// 本租戶的寫入指令佇列
+    #[must_use]
+    pub const fn commands(&self) -> &CommandQueue {
+        &self.commands
+    }
+
+    /// 本租戶的事件匯流排
+    ///
+    /// 訂閱後只會收到本租戶的設備連線所發佈的事件
+    #[must_use]
+    pub const fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    /// 本租戶的連線登記表
+    ///
+    /// 連線以識別名稱登記在所屬租戶的登記表中，不同租戶可以使用相同的連線識別名稱
+    #[must_use]
+    pub fn connections(&self) -> &Mutex<ConnectionRegistry> {
+        &self.connections
+    }
+
+    /// 本租戶的連線統計數據登記表
+    #[must_use]
+    pub const fn statistics(&self) -> &ConnectionStatsRegistry {
+        &self.statistics
+    }
+
+    /// 本租戶的原始封包保留區
+    #[must_use]
+    pub const fn raw_frames(&self) -> &RawFrameStore {
+        &self.raw_frames
+    }
+
+    /// 本租戶的請求紀錄保留區
+    #[must_use]
+    pub const fn journal(&self) -> &RequestJournal {
+        &self.journal
+    }
+}
+
+/// 租戶登記表
+///
+/// 主程式在同一個程式中服務多個客戶時，可利用本 struct 依租戶區分點位狀態、寫入指令、事件、連線與連線統計數據
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::{TenantId, Tenants};
+///
+/// let tenants = Tenants::new();
+/// let a = tenants.get_or_create(TenantId::new("customer-a"));
+/// let b = tenants.get_or_create(TenantId::new("customer-b"));
+///
+/// a.store().register("temperature", None);
+///
+/// assert!(a.store().contains("temperature"));
+/// assert!(!b.store().contains("temperature"));
+///
+/// // 連線登記在所屬租戶的登記表中
+/// assert!(b.connections().try_lock().unwrap().is_empty());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Tenants {
+    tenants: Arc<RwLock<HashMap<TenantId, Tenant>>>,
+}
+
+impl Tenants {
+    /// 建立空的租戶登記表
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 取得租戶，如不存在則建立新的租戶
+    pub fn get_or_create(&self, id: TenantId) -> Tenant {
+        self.tenants
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(id)
+            .or_insert_with_key(|id| Tenant::new(id.clone()))
+            .clone()
+    }
+
+    /// 取得租戶
+    #[must_use]
+    pub fn get(&self, id: &TenantId) -> Option<Tenant> {
+        self.tenants
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(id)
+            .cloned()
+    }
+
+    /// 移除租戶
+    ///
+    /// 已經取得的 [`Tenant`] 仍可繼續使用，但無法再透過本登記表查詢
+    pub fn remove(&self, id: &TenantId) -> Option<Tenant> {
+        self.tenants
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(id)
+    }
+
+    /// 所有租戶識別名稱
+    #[must_use]
+    pub fn ids(&self) -> Vec<TenantId> {
+        self.tenants
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .cloned()
+            .collect()
+    }
+}
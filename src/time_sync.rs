@@ -0,0 +1,241 @@
+//! 設備時鐘同步
+//!
+//! 許多設備的 RTC 會隨時間漂移，需要由主程式定期校正，支援校時的連線可以實作 [`TimeSync`] trait ，
+//! 主程式依 [`TimeSyncPolicy::interval`] 定期調用 [`sync_time()`] ，偏差超過 [`TimeSyncPolicy::drift_threshold`] 時才會寫入設備時鐘
+
+use std::{
+    sync::atomic::{AtomicI64, AtomicU64, Ordering},
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Connection, ConnectionError,
+    event::{EventBus, EventKind},
+};
+
+/// 設備時鐘同步（非必需）
+///
+/// 需為實作 [`Connection`] trait 的 struct/enum ，讀寫時鐘的請求與一般請求共用同一個連線，主程式不會同時調用本 trait 的 function 與 [`Connection::request_process()`]
+#[expect(async_fn_in_trait)]
+pub trait TimeSync: Connection {
+    /// 讀取設備時鐘
+    ///
+    /// # 回傳值
+    /// 設備目前的時間，可回傳錯誤
+    async fn read_time(&mut self) -> Result<SystemTime, ConnectionError>;
+
+    /// 寫入設備時鐘
+    ///
+    /// # 參數
+    /// - `now`：主程式目前的時間，時區或曆法的轉換請由實作者處理
+    ///
+    /// # 回傳值
+    /// 無，可回傳錯誤
+    async fn write_time(&mut self, now: SystemTime) -> Result<(), ConnectionError>;
+}
+
+/// 時鐘同步策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeSyncPolicy {
+    /// 檢查間隔
+    ///
+    /// 主程式會依據此處設定的時間定期調用 [`sync_time()`] ，設定檔中以毫秒數表示
+    #[serde(with = "crate::millis")]
+    pub interval: Duration,
+    /// 校正門檻
+    ///
+    /// 設備時鐘與主程式時鐘的偏差大於等於此數值時，才會寫入設備時鐘，設定檔中以毫秒數表示
+    #[serde(with = "crate::millis")]
+    pub drift_threshold: Duration,
+    /// 警示門檻
+    ///
+    /// 非必填，偏差大於等於此數值時，會發佈 [`EventKind::ClockSkew`] 事件，通常代表設備的 RTC 電池耗盡或時鐘故障，設定檔中以毫秒數表示
+    #[serde(default, with = "crate::millis::option")]
+    pub alarm_threshold: Option<Duration>,
+}
+
+/// 時鐘同步結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeSyncOutcome {
+    /// 偏差在校正門檻內，沒有寫入設備時鐘
+    WithinThreshold {
+        /// 偏差毫秒數，正數代表設備時鐘較快
+        drift_ms: i64,
+    },
+    /// 偏差超過校正門檻，已寫入設備時鐘
+    Corrected {
+        /// 校正前的偏差毫秒數，正數代表設備時鐘較快
+        drift_ms: i64,
+    },
+}
+
+/// 時鐘同步統計數據
+///
+/// 如需共享，請利用 [`std::sync::Arc`] 包裝
+#[derive(Debug, Default)]
+pub struct TimeSyncStats {
+    last_drift_ms: AtomicI64,
+    max_drift_ms: AtomicI64,
+    check_count: AtomicU64,
+    correction_count: AtomicU64,
+}
+
+impl TimeSyncStats {
+    /// 記錄一次檢查結果
+    pub fn record(&self, outcome: TimeSyncOutcome) {
+        let (TimeSyncOutcome::WithinThreshold { drift_ms }
+        | TimeSyncOutcome::Corrected { drift_ms }) = outcome;
+
+        self.last_drift_ms.store(drift_ms, Ordering::Relaxed);
+        self.max_drift_ms
+            .fetch_max(drift_ms.saturating_abs(), Ordering::Relaxed);
+        self.check_count.fetch_add(1, Ordering::Relaxed);
+
+        if matches!(outcome, TimeSyncOutcome::Corrected { .. }) {
+            self.correction_count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// 取得統計數據快照
+    #[must_use]
+    pub fn snapshot(&self) -> TimeSyncStatsSnapshot {
+        TimeSyncStatsSnapshot {
+            last_drift_ms: self.last_drift_ms.load(Ordering::Relaxed),
+            max_drift_ms: self.max_drift_ms.load(Ordering::Relaxed),
+            check_count: self.check_count.load(Ordering::Relaxed),
+            correction_count: self.correction_count.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// 時鐘同步統計數據快照
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimeSyncStatsSnapshot {
+    /// 最近一次檢查的偏差毫秒數，正數代表設備時鐘較快
+    pub last_drift_ms: i64,
+    /// 曾經檢查到的最大偏差毫秒數（絕對值）
+    pub max_drift_ms: i64,
+    /// 檢查次數
+    pub check_count: u64,
+    /// 寫入設備時鐘的次數
+    pub correction_count: u64,
+}
+
+/// 檢查並校正設備時鐘
+///
+/// 讀取設備時鐘，並與讀取期間的主程式時鐘中間點比較，以扣除通訊延遲的影響，偏差超過 [`TimeSyncPolicy::drift_threshold`] 時寫入主程式目前的時間
+///
+/// # 參數
+/// - `connection`：已建立的設備連線
+/// - `policy`：時鐘同步策略
+/// - `statistics`：時鐘同步統計數據，結果會記錄於此
+/// - `events`：事件匯流排與連線識別名稱，偏差超過 [`TimeSyncPolicy::alarm_threshold`] 時發佈 [`EventKind::ClockSkew`] 事件，不需要時請傳入 [`None`]
+///
+/// # 回傳值
+/// 同步結果，參見 [`TimeSyncOutcome`]
+///
+/// # Errors
+/// 讀取或寫入設備時鐘失敗時，回傳 [`TimeSync`] 實作者回傳的錯誤
+///
+/// # 範例
+/// ```rust
+/// # use std::{error::Error, time::{Duration, SystemTime}};
+/// # use device_state_exchange_lib::*;
+/// use device_state_exchange_lib::time_sync::{sync_time, TimeSync, TimeSyncOutcome, TimeSyncPolicy, TimeSyncStats};
+/// # #[derive(Debug, Clone)] struct Config;
+/// # impl ConnectionConfig for Config {}
+/// # #[derive(Debug, Clone)] struct Point;
+/// # impl Target for Point {}
+/// # #[derive(Debug, Clone)] struct Request;
+/// # impl DeviceStateRequest for Request {}
+/// # #[derive(Debug, Clone)] struct Response;
+/// # impl DeviceStateResponse for Response { fn to_value(&self) -> Result<std::borrow::Cow<'_, serde_json::Value>, value::ConversionError> { Ok(Default::default()) } }
+///
+/// struct Device {
+///     clock: SystemTime,
+/// }
+///
+/// impl TimeSync for Device {
+///     async fn read_time(&mut self) -> Result<SystemTime, ConnectionError> {
+///         Ok(self.clock)
+///     }
+///
+///     async fn write_time(&mut self, now: SystemTime) -> Result<(), ConnectionError> {
+///         self.clock = now;
+///         Ok(())
+///     }
+/// }
+/// # impl Connection for Device {
+/// #     const NAMES: &[&str] = &["Device"];
+/// #     type Config = Config;
+/// #     type Target = Point;
+/// #     type Request = Request;
+/// #     type Response = Response;
+/// #     type Result = ();
+/// #     async fn init(_: &Config) -> Result<ConnectionArtifact<Self>, ConnectionError> { unimplemented!() }
+/// #     fn init_targets(&mut self, _: &mut ConnectionStats, _: Vec<Point>) -> ConnectionTargets<Request, ()> { ConnectionTargets(Vec::new()) }
+/// #     async fn request_process(&mut self, _: Request) -> Result<(Response, bool), ConnectionError> { Ok((Response, true)) }
+/// #     async fn reconnect(&mut self) -> Result<(), ConnectionError> { Ok(()) }
+/// #     async fn update_config(&mut self, _: &Config) -> Result<(), ConnectionError> { Ok(()) }
+/// # }
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let mut device = Device { clock: SystemTime::now() - Duration::from_secs(90) };
+/// let policy = TimeSyncPolicy {
+///     interval: Duration::from_secs(60 * 60),
+///     drift_threshold: Duration::from_secs(1),
+///     alarm_threshold: None,
+/// };
+/// let statistics = TimeSyncStats::default();
+///
+/// let outcome = sync_time(&mut device, &policy, &statistics, None).await.unwrap();
+/// assert!(matches!(outcome, TimeSyncOutcome::Corrected { drift_ms } if drift_ms <= -90_000));
+///
+/// let outcome = sync_time(&mut device, &policy, &statistics, None).await.unwrap();
+/// assert!(matches!(outcome, TimeSyncOutcome::WithinThreshold { .. }));
+/// assert_eq!(statistics.snapshot().correction_count, 1);
+/// # }
+/// ```
+pub async fn sync_time<C: TimeSync>(
+    connection: &mut C,
+    policy: &TimeSyncPolicy,
+    statistics: &TimeSyncStats,
+    events: Option<(&EventBus, &str)>,
+) -> Result<TimeSyncOutcome, ConnectionError> {
+    let before = SystemTime::now();
+    let device_time = connection.read_time().await?;
+    let after = SystemTime::now();
+
+    let midpoint = before + after.duration_since(before).unwrap_or_default() / 2;
+    let drift_ms = match device_time.duration_since(midpoint) {
+        Ok(ahead) => saturating_millis(ahead),
+        Err(behind) => -saturating_millis(behind.duration()),
+    };
+
+    if let (Some(alarm_threshold), Some((events, connection_name))) =
+        (policy.alarm_threshold, events)
+        && u128::from(drift_ms.unsigned_abs()) >= alarm_threshold.as_millis()
+    {
+        events.publish(EventKind::ClockSkew {
+            connection: connection_name.to_owned(),
+            drift_ms,
+        });
+    }
+
+    let outcome = if u128::from(drift_ms.unsigned_abs()) >= policy.drift_threshold.as_millis() {
+        connection.write_time(SystemTime::now()).await?;
+        TimeSyncOutcome::Corrected { drift_ms }
+    } else {
+        TimeSyncOutcome::WithinThreshold { drift_ms }
+    };
+
+    statistics.record(outcome);
+    Ok(outcome)
+}
+
+fn saturating_millis(duration: Duration) -> i64 {
+    i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+}
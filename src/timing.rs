@@ -0,0 +1,282 @@
+//! 處理階段耗時
+//!
+//! 請求從排隊到發佈會經過數個階段（參見 [`Stage`]），主程式可以為每個請求建立 [`StageTimer`] ，在每個階段結束時調用 [`StageTimer::mark()`] ，
+//! 完成後以 [`crate::envelope::ResponseEnvelope::with_timings()`] 附加在回覆信封中，並以 [`StageStats::record()`] 累計至 [`crate::ConnectionStats::stages`] ，
+//! 統計數據快照會顯示各階段的平均與最大耗時，以及佔總耗時的比例，用於判斷延遲的來源（如序列埠飽和時大部分的時間花在排隊）
+//!
+//! # 範例
+//! ```rust
+//! use std::time::Duration;
+//! use device_state_exchange_lib::{ConnectionStats, timing::{Stage, StageTimings}};
+//!
+//! let statistics = ConnectionStats::new("COM1", None);
+//! statistics.stages.record(&StageTimings::from_iter([
+//!     (Stage::Queueing, Duration::from_millis(80)),
+//!     (Stage::DeviceIo, Duration::from_millis(20)),
+//! ]));
+//!
+//! let stages = statistics.snapshot().stages;
+//! assert_eq!(stages[&Stage::Queueing].share_permille, 800);
+//! assert_eq!(stages[&Stage::DeviceIo].average, Duration::from_millis(20));
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// 處理階段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    /// 在佇列中等待，如等待 [`crate::concurrency::InFlightLimiter`] 或共用匯流排
+    Queueing,
+    /// [`crate::Connection::preprocess()`]
+    Preprocess,
+    /// [`crate::Connection::request_process()`] ，與設備交換資料
+    DeviceIo,
+    /// [`crate::Connection::postprocess()`]
+    Postprocess,
+    /// 發佈給事件接收端與外部界面
+    Publication,
+}
+
+impl Stage {
+    /// 所有處理階段，依處理順序排列
+    pub const ALL: [Self; 5] = [
+        Self::Queueing,
+        Self::Preprocess,
+        Self::DeviceIo,
+        Self::Postprocess,
+        Self::Publication,
+    ];
+
+    /// 階段名稱，與序列化的名稱相同
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Queueing => "queueing",
+            Self::Preprocess => "preprocess",
+            Self::DeviceIo => "device_io",
+            Self::Postprocess => "postprocess",
+            Self::Publication => "publication",
+        }
+    }
+}
+
+/// 單一請求各階段的耗時
+///
+/// 序列化時以微秒數表示，未經過的階段不會出現
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageTimings(#[serde(with = "stage_micros")] BTreeMap<Stage, Duration>);
+
+impl StageTimings {
+    /// 取得階段的耗時，未經過的階段為 [`None`]
+    #[must_use]
+    pub fn get(&self, stage: Stage) -> Option<Duration> {
+        self.0.get(&stage).copied()
+    }
+
+    /// 累加階段的耗時
+    pub fn add(&mut self, stage: Stage, elapsed: Duration) {
+        *self.0.entry(stage).or_default() += elapsed;
+    }
+
+    /// 所有階段的總耗時
+    #[must_use]
+    pub fn total(&self) -> Duration {
+        self.0.values().sum()
+    }
+
+    /// 依處理順序列出經過的階段與耗時
+    pub fn iter(&self) -> impl Iterator<Item = (Stage, Duration)> + '_ {
+        self.0.iter().map(|(stage, elapsed)| (*stage, *elapsed))
+    }
+}
+
+impl FromIterator<(Stage, Duration)> for StageTimings {
+    fn from_iter<I: IntoIterator<Item = (Stage, Duration)>>(iter: I) -> Self {
+        let mut timings = Self::default();
+        for (stage, elapsed) in iter {
+            timings.add(stage, elapsed);
+        }
+        timings
+    }
+}
+
+/// 處理階段計時器
+///
+/// 以單調時鐘計時，每次 [`StageTimer::mark()`] 會將上一次標記（或建立計時器）至今的時間計入指定的階段
+#[derive(Debug, Clone)]
+pub struct StageTimer {
+    last: Instant,
+    timings: StageTimings,
+}
+
+impl Default for StageTimer {
+    fn default() -> Self {
+        Self::start()
+    }
+}
+
+impl StageTimer {
+    /// 開始計時，請在請求加入佇列時建立
+    #[must_use]
+    pub fn start() -> Self {
+        Self {
+            last: Instant::now(),
+            timings: StageTimings::default(),
+        }
+    }
+
+    /// 標記階段結束
+    ///
+    /// # 回傳值
+    /// 本階段的耗時
+    pub fn mark(&mut self, stage: Stage) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last);
+        self.last = now;
+        self.timings.add(stage, elapsed);
+        elapsed
+    }
+
+    /// 目前為止各階段的耗時
+    #[must_use]
+    pub const fn timings(&self) -> &StageTimings {
+        &self.timings
+    }
+
+    /// 結束計時
+    #[must_use]
+    pub fn finish(self) -> StageTimings {
+        self.timings
+    }
+}
+
+/// 處理階段耗時統計
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件（包含登記至 [`crate::ConnectionStatsRegistry`] 的 [`crate::ConnectionStats`]）會存取同一份資料
+#[derive(Debug, Clone, Default)]
+pub struct StageStats(Arc<Mutex<BTreeMap<Stage, StageTotals>>>);
+
+#[derive(Debug, Clone, Copy, Default)]
+struct StageTotals {
+    count: u32,
+    total: Duration,
+    max: Duration,
+}
+
+/// 處理階段耗時統計快照
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageSnapshot {
+    /// 經過本階段的請求數量
+    pub count: u32,
+    /// 平均耗時，序列化時以微秒數表示
+    #[serde(rename = "average_us", with = "crate::micros")]
+    pub average: Duration,
+    /// 最大耗時，序列化時以微秒數表示
+    #[serde(rename = "max_us", with = "crate::micros")]
+    pub max: Duration,
+    /// 本階段累計耗時佔所有階段累計耗時的千分比
+    pub share_permille: u32,
+}
+
+impl StageStats {
+    /// 累計單一請求各階段的耗時
+    pub fn record(&self, timings: &StageTimings) {
+        accumulate(
+            &mut self.0.lock().unwrap_or_else(PoisonError::into_inner),
+            timings,
+        );
+    }
+
+    /// 清除統計
+    pub fn reset(&self) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    /// 取得各階段的耗時統計快照
+    #[must_use]
+    pub fn snapshot(&self) -> BTreeMap<Stage, StageSnapshot> {
+        let stages = self
+            .0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        let total: Duration = stages.values().map(|totals| totals.total).sum();
+
+        stages
+            .into_iter()
+            .map(|(stage, totals)| {
+                let share_permille = if total.is_zero() {
+                    0
+                } else {
+                    u32::try_from(totals.total.as_nanos() * 1000 / total.as_nanos()).unwrap_or(1000)
+                };
+
+                (
+                    stage,
+                    StageSnapshot {
+                        count: totals.count,
+                        average: totals.total / totals.count.max(1),
+                        max: totals.max,
+                        share_permille,
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+/// 將單一請求各階段的耗時累加至統計
+fn accumulate(stages: &mut BTreeMap<Stage, StageTotals>, timings: &StageTimings) {
+    for (stage, elapsed) in timings.iter() {
+        let totals = stages.entry(stage).or_default();
+        totals.count = totals.count.saturating_add(1);
+        totals.total = totals.total.saturating_add(elapsed);
+        totals.max = totals.max.max(elapsed);
+    }
+}
+
+/// 以微秒數序列化以 [`Stage`] 索引的 [`Duration`]
+mod stage_micros {
+    use std::{collections::BTreeMap, time::Duration};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Stage;
+
+    pub fn serialize<S: Serializer>(
+        timings: &BTreeMap<Stage, Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        timings
+            .iter()
+            .map(|(stage, elapsed)| {
+                (
+                    *stage,
+                    u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX),
+                )
+            })
+            .collect::<BTreeMap<_, _>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<Stage, Duration>, D::Error> {
+        BTreeMap::<Stage, u64>::deserialize(deserializer).map(|timings| {
+            timings
+                .into_iter()
+                .map(|(stage, micros)| (stage, Duration::from_micros(micros)))
+                .collect()
+        })
+    }
+}
@@ -0,0 +1,121 @@
+//! 封包讀取
+//!
+//! 每次輪詢都為回覆配置新的 `Vec<u8>` 、`Vec<u16>` ，在大量點位時會成為主要的 CPU 負擔，
+//! [`FrameReader`] 將收到的資料累積於同一個 [`BytesMut`] ，再以 [`Bytes`] 切出封包，切出的封包與緩衝區共用記憶體，不需要複製
+//!
+//! 切出的 [`Bytes`] 可以直接存放在 [`crate::DeviceStateResponse`] 中，並由 [`crate::DeviceStateResponse::raw()`] 回傳，
+//! 複製回覆時只會增加參考計數
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::transport::frame::FrameReader;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! // 兩個 Modbus TCP 回覆：MBAP 標頭（7 bytes）後接 PDU
+//! let received: &[u8] = &[
+//!     0x00, 0x01, 0x00, 0x00, 0x00, 0x05, 0x01, 0x03, 0x02, 0x00, 0x2a,
+//!     0x00, 0x02, 0x00, 0x00, 0x00, 0x05, 0x01, 0x03, 0x02, 0x00, 0x2b,
+//! ];
+//! let mut reader = FrameReader::new(received);
+//!
+//! for expected in [0x2a, 0x2b] {
+//!     let header = reader.read_frame(7).await.unwrap();
+//!     let length = usize::from(u16::from_be_bytes([header[4], header[5]])) - 1;
+//!     let pdu = reader.read_frame(length).await.unwrap();
+//!
+//!     assert_eq!(pdu.slice(2..), [0x00, expected][..]);
+//! }
+//! # }
+//! ```
+
+use std::io::{Error, ErrorKind};
+
+use bytes::{Bytes, BytesMut};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// 預設緩衝區大小
+pub const DEFAULT_FRAME_BUFFER_CAPACITY: usize = 4096;
+
+/// 封包讀取器
+///
+/// 包裝 [`AsyncRead`] （如 [`tokio::net::TcpStream`] 、序列埠），從同一個緩衝區切出封包
+#[derive(Debug)]
+pub struct FrameReader<R> {
+    reader: R,
+    buffer: BytesMut,
+}
+
+impl<R: AsyncRead + Unpin> FrameReader<R> {
+    /// 建立封包讀取器，緩衝區大小為 [`DEFAULT_FRAME_BUFFER_CAPACITY`]
+    #[must_use]
+    pub fn new(reader: R) -> Self {
+        Self::with_capacity(reader, DEFAULT_FRAME_BUFFER_CAPACITY)
+    }
+
+    /// 建立指定緩衝區大小的封包讀取器
+    ///
+    /// # 參數
+    /// - `reader`：資料來源
+    /// - `capacity`：緩衝區大小，封包超過此大小時緩衝區會自動擴充
+    #[must_use]
+    pub fn with_capacity(reader: R, capacity: usize) -> Self {
+        Self {
+            reader,
+            buffer: BytesMut::with_capacity(capacity),
+        }
+    }
+
+    /// 讀取固定長度的封包
+    ///
+    /// 緩衝區中的資料不足時，會持續讀取直到長度足夠，多讀取的資料會保留給下一次呼叫
+    ///
+    /// # 參數
+    /// - `length`：封包長度
+    ///
+    /// # 回傳值
+    /// 封包內容，與緩衝區共用記憶體
+    ///
+    /// # Errors
+    /// 讀取失敗時回傳 [`Error`] ，資料來源在長度足夠前結束時回傳 [`ErrorKind::UnexpectedEof`]
+    pub async fn read_frame(&mut self, length: usize) -> Result<Bytes, Error> {
+        while self.buffer.len() < length {
+            self.buffer.reserve(length - self.buffer.len());
+            if self.reader.read_buf(&mut self.buffer).await? == 0 {
+                return Err(Error::new(
+                    ErrorKind::UnexpectedEof,
+                    "資料來源在封包讀取完成前結束",
+                ));
+            }
+        }
+
+        Ok(self.buffer.split_to(length).freeze())
+    }
+
+    /// 捨棄緩衝區中尚未讀取的資料
+    ///
+    /// 逾時或收到無法解析的封包後，請先調用本 method ，避免殘留的資料影響下一個封包
+    pub fn discard(&mut self) {
+        self.buffer.clear();
+    }
+
+    /// 緩衝區中尚未讀取的資料長度
+    #[must_use]
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// 取得資料來源的可變引用
+    ///
+    /// 資料來源同時實作 [`tokio::io::AsyncWrite`] 時（如 [`tokio::net::TcpStream`] 、序列埠），可以利用本 method 送出請求，
+    /// 請勿直接由資料來源讀取資料，以免與緩衝區中的資料順序不一致
+    pub const fn get_mut(&mut self) -> &mut R {
+        &mut self.reader
+    }
+
+    /// 取回資料來源，緩衝區中尚未讀取的資料會被捨棄
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
@@ -0,0 +1,263 @@
+//! 共用實體線路
+//!
+//! 同一條 RS-485 匯流排上可能同時接有不同型態的設備，分別由不同的 [`crate::Connection`] 實作處理，
+//! 但實體線路為半雙工，同一時間只能有一個請求在線路上，各連線若各自開啟序列埠，送出的封包會互相干擾
+//!
+//! [`SharedBus`] 包裝同一個實體線路，各連線在 [`crate::Connection::init()`] 時向 [`SharedBus`] 登記並保存取得的 [`BusHandle`] ，
+//! 每次存取線路前調用 [`BusHandle::lock()`] ：
+//!
+//! - 同一時間只有一個連線可以存取線路（半雙工保護）
+//! - 等待中的連線依到達順序取得線路，單一連線無法連續佔用線路讓其他連線等不到
+//! - 可設定線路切換間隔，讓前一個設備的回覆完全結束後，才送出下一個請求
+//!
+//! 各連線的 [`crate::Connection::init()`] 只能取得自己的設定檔，可以利用 [`BusRegistry`] 以線路名稱（如 `COM1`）找到同一個 [`SharedBus`]
+//!
+//! # 範例
+//! ```rust
+//! use std::time::Duration;
+//! use device_state_exchange_lib::transport::shared::BusRegistry;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let registry = BusRegistry::new();
+//! // 實際使用時請開啟序列埠，此處以 `Vec<u8>` 代替
+//! let open = || Ok::<_, std::io::Error>(Vec::<u8>::new());
+//!
+//! // 電表與溫控器的連線分別在 `init()` 中取得同一條線路，只有第一次會調用 `open`
+//! let meter = registry.get_or_open("COM1", Duration::from_millis(5), open).unwrap().register("meter");
+//! let thermostat = registry.get_or_open("COM1", Duration::from_millis(5), open).unwrap().register("thermostat");
+//!
+//! meter.lock().await.extend_from_slice(b"meter");
+//! thermostat.lock().await.extend_from_slice(b"thermostat");
+//!
+//! assert_eq!(&meter.lock().await[..], b"meterthermostat");
+//! assert_eq!(meter.bus().members(), ["meter", "thermostat"]);
+//! # }
+//! ```
+
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{
+        Arc, Mutex, PoisonError, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::{
+    sync::{Mutex as AsyncMutex, OwnedMutexGuard},
+    time::Instant,
+};
+
+use crate::HashMap;
+
+/// 共用實體線路
+///
+/// 泛型 `T` 為實際的線路，如序列埠
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一條線路
+#[derive(Debug)]
+pub struct SharedBus<T>(Arc<SharedBusInner<T>>);
+
+#[derive(Debug)]
+struct SharedBusInner<T> {
+    line: Arc<AsyncMutex<Line<T>>>,
+    turnaround: Duration,
+    members: Mutex<Vec<(u64, String)>>,
+    next_member: AtomicU64,
+}
+
+#[derive(Debug)]
+struct Line<T> {
+    transport: T,
+    released_at: Option<Instant>,
+}
+
+impl<T> Clone for SharedBus<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> SharedBus<T> {
+    /// 建立共用實體線路
+    ///
+    /// # 參數
+    /// - `transport`：已開啟的線路
+    /// - `turnaround`：線路切換間隔，前一次存取結束後，至少經過此時間才會讓下一次存取開始，不需要時請填入 [`Duration::ZERO`]
+    #[must_use]
+    pub fn new(transport: T, turnaround: Duration) -> Self {
+        Self(Arc::new(SharedBusInner {
+            line: Arc::new(AsyncMutex::new(Line {
+                transport,
+                released_at: None,
+            })),
+            turnaround,
+            members: Mutex::new(Vec::new()),
+            next_member: AtomicU64::new(0),
+        }))
+    }
+
+    /// 登記使用本線路的連線
+    ///
+    /// 取得的 [`BusHandle`] 被 drop 時會自動取消登記
+    ///
+    /// # 參數
+    /// - `member`：連線名稱，用於 [`SharedBus::members()`]
+    #[must_use]
+    pub fn register(&self, member: impl Into<String>) -> BusHandle<T> {
+        let id = self.0.next_member.fetch_add(1, Ordering::Relaxed);
+
+        self.0
+            .members
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((id, member.into()));
+
+        BusHandle {
+            bus: self.clone(),
+            id,
+        }
+    }
+
+    /// 取得已登記的連線名稱，依登記順序排列
+    #[must_use]
+    pub fn members(&self) -> Vec<String> {
+        self.0
+            .members
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(_, member)| member.clone())
+            .collect()
+    }
+
+    /// 線路切換間隔
+    #[must_use]
+    pub fn turnaround(&self) -> Duration {
+        self.0.turnaround
+    }
+}
+
+/// 共用實體線路的使用權
+///
+/// 由 [`SharedBus::register()`] 取得，drop 時自動取消登記
+#[derive(Debug)]
+pub struct BusHandle<T> {
+    bus: SharedBus<T>,
+    id: u64,
+}
+
+impl<T> BusHandle<T> {
+    /// 取得線路的存取權，其他連線使用中時依到達順序等待
+    ///
+    /// 回傳的 [`BusGuard`] 被 drop 前，其他連線都無法存取線路，請在完成一次完整的請求/回覆後立即釋放
+    pub async fn lock(&self) -> BusGuard<T> {
+        let line = Arc::clone(&self.bus.0.line).lock_owned().await;
+
+        if let Some(released_at) = line.released_at {
+            tokio::time::sleep_until(released_at + self.bus.0.turnaround).await;
+        }
+
+        BusGuard(line)
+    }
+
+    /// 所屬的共用實體線路
+    #[must_use]
+    pub const fn bus(&self) -> &SharedBus<T> {
+        &self.bus
+    }
+}
+
+impl<T> Drop for BusHandle<T> {
+    fn drop(&mut self) {
+        self.bus
+            .0
+            .members
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|(id, _)| *id != self.id);
+    }
+}
+
+/// 線路的存取權
+///
+/// 可透過 [`Deref`] 與 [`DerefMut`] 存取線路，drop 時釋放存取權並開始計算線路切換間隔
+#[derive(Debug)]
+pub struct BusGuard<T>(OwnedMutexGuard<Line<T>>);
+
+impl<T> Deref for BusGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0.transport
+    }
+}
+
+impl<T> DerefMut for BusGuard<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0.transport
+    }
+}
+
+impl<T> Drop for BusGuard<T> {
+    fn drop(&mut self) {
+        self.0.released_at = Some(Instant::now());
+    }
+}
+
+/// 共用實體線路登記表
+///
+/// 以線路名稱找到同一個 [`SharedBus`] ，所有 [`SharedBus`] 與 [`BusHandle`] 都被 drop 後，線路會被關閉並從登記表中移除
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份登記表
+#[derive(Debug)]
+pub struct BusRegistry<T>(Arc<Mutex<HashMap<String, Weak<SharedBusInner<T>>>>>);
+
+impl<T> Clone for BusRegistry<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> Default for BusRegistry<T> {
+    fn default() -> Self {
+        Self(Arc::default())
+    }
+}
+
+impl<T> BusRegistry<T> {
+    /// 建立空的共用實體線路登記表
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 取得線路，線路尚未開啟時調用 `open` 開啟
+    ///
+    /// # 參數
+    /// - `name`：線路名稱，如 `COM1`
+    /// - `turnaround`：開啟新線路時使用的線路切換間隔，參見 [`SharedBus::new()`]
+    /// - `open`：開啟線路
+    ///
+    /// # Errors
+    /// 回傳 `open` 的錯誤
+    pub fn get_or_open<E>(
+        &self,
+        name: &str,
+        turnaround: Duration,
+        open: impl FnOnce() -> Result<T, E>,
+    ) -> Result<SharedBus<T>, E> {
+        let mut buses = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        buses.retain(|_, bus| bus.strong_count() > 0);
+
+        if let Some(bus) = buses.get(name).and_then(Weak::upgrade) {
+            return Ok(SharedBus(bus));
+        }
+
+        let bus = SharedBus::new(open()?, turnaround);
+        buses.insert(name.to_owned(), Arc::downgrade(&bus.0));
+        drop(buses);
+        Ok(bus)
+    }
+}
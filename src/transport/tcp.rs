@@ -0,0 +1,419 @@
+//! TCP 連線
+//!
+//! 以 TCP 為基礎的協定（如 Modbus TCP）可以利用 [`connect()`] 建立連線，設定檔中只要指定 [`TcpEndpoint::proxy`] ，
+//! 連線就會透過 SOCKS5 或 HTTP CONNECT 代理伺服器建立，實作者不需要額外處理
+//!
+//! 需要 TLS 的協定，請在 [`connect()`] 回傳的 [`TcpStream`] 上建立 TLS 連線，代理伺服器的設定同樣適用
+//!
+//! [`connect()`] 每次呼叫都會重新解析主機名稱，實作者應在 [`crate::Connection::reconnect()`] 中重新呼叫 [`connect()`] ，
+//! 而不是沿用初次連線時解析的位址，如此設備位於動態 DNS 之後、位址改變時，重新連線仍可連上正確的位址
+//!
+//! 連線成功後，可以將 [`TcpStream::peer_addr()`] 記錄至 [`crate::ConnectionStats::remote_address`] ，
+//! 經由代理伺服器時，此位址為代理伺服器的位址
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{RemoteAddress, transport::tcp::{self, TcpEndpoint}};
+//! use tokio::net::TcpListener;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+//! let endpoint = TcpEndpoint {
+//!     // `localhost` 可能同時解析出 `::1` 與 `127.0.0.1` ，無法連線的位址會被略過
+//!     host: "localhost".to_owned(),
+//!     port: listener.local_addr().unwrap().port(),
+//!     proxy: None,
+//! };
+//!
+//! let remote_address = RemoteAddress::default();
+//! let stream = tcp::connect(&endpoint).await.unwrap();
+//! remote_address.set(stream.peer_addr().ok());
+//!
+//! assert_eq!(remote_address.get(), Some(listener.local_addr().unwrap()));
+//! # }
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt::Write,
+    io::{Error, ErrorKind},
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpStream, lookup_host},
+    task::JoinSet,
+};
+
+/// 前一個連線嘗試尚未完成時，開始嘗試下一個位址前的等待時間，參見 RFC 8305
+pub const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// TCP 連線目標
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TcpEndpoint {
+    /// 主機名稱或 IP 位址
+    pub host: String,
+    /// 連接埠
+    pub port: u16,
+    /// 代理伺服器，未設定時直接連線
+    #[serde(default)]
+    pub proxy: Option<ProxyConfig>,
+}
+
+/// 代理伺服器設定
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProxyConfig {
+    /// SOCKS5 代理伺服器（RFC 1928），目標主機名稱交由代理伺服器解析
+    Socks5 {
+        /// 代理伺服器主機名稱或 IP 位址
+        host: String,
+        /// 代理伺服器連接埠
+        port: u16,
+        /// 帳號密碼（RFC 1929），未設定時使用免驗證模式
+        #[serde(default)]
+        credentials: Option<ProxyCredentials>,
+    },
+    /// 支援 `CONNECT` 方法的 HTTP 代理伺服器
+    HttpConnect {
+        /// 代理伺服器主機名稱或 IP 位址
+        host: String,
+        /// 代理伺服器連接埠
+        port: u16,
+        /// 帳號密碼（Basic 驗證），未設定時不送出 `Proxy-Authorization`
+        #[serde(default)]
+        credentials: Option<ProxyCredentials>,
+    },
+}
+
+/// 代理伺服器帳號密碼
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ProxyCredentials {
+    /// 帳號
+    pub username: String,
+    /// 密碼
+    pub password: String,
+}
+
+impl std::fmt::Debug for ProxyCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProxyCredentials")
+            .field("username", &self.username)
+            .field("password", &"***")
+            .finish()
+    }
+}
+
+/// 建立 TCP 連線
+///
+/// 主機名稱解析出多個位址時（如同時有 IPv6 與 IPv4 位址，或有多筆 A 紀錄），會依 RFC 8305（Happy Eyeballs）的方式，
+/// 交錯排列 IPv6 與 IPv4 位址並依序嘗試，前一個位址在 [`CONNECTION_ATTEMPT_DELAY`] 內沒有結果時，會同時開始嘗試下一個位址，
+/// 採用最先成功的連線
+///
+/// 經由代理伺服器時，上述方式用於連線至代理伺服器，目標的主機名稱則交由代理伺服器解析
+///
+/// 本 function 不處理逾時，請依 [`crate::ConnectionArtifact::timeout`] 自行利用 [`tokio::time::timeout()`] 包裝
+///
+/// # 參數
+/// - `endpoint`：連線目標
+///
+/// # 回傳值
+/// 已連線至目標的 [`TcpStream`] ，經由代理伺服器時，回傳的是已完成交握、可直接與目標通訊的連線
+///
+/// # Errors
+/// 無法連線至目標或代理伺服器、代理伺服器拒絕連線時回傳 [`std::io::Error`]
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::transport::tcp::{self, ProxyConfig, ProxyCredentials, TcpEndpoint};
+/// use tokio::{io::{AsyncReadExt, AsyncWriteExt}, net::TcpListener};
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// // 模擬 HTTP 代理伺服器
+/// let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+/// let proxy_port = listener.local_addr().unwrap().port();
+/// tokio::spawn(async move {
+///     let (mut stream, _) = listener.accept().await.unwrap();
+///     let mut request = vec![0; 1024];
+///     let length = stream.read(&mut request).await.unwrap();
+///     let request = String::from_utf8_lossy(&request[..length]);
+///     assert!(request.starts_with("CONNECT 192.168.1.10:502 HTTP/1.1\r\n"));
+///     assert!(request.contains("Proxy-Authorization: Basic dXNlcjpwYXNz\r\n"));
+///     stream.write_all(b"HTTP/1.1 200 Connection established\r\n\r\nhello").await.unwrap();
+/// });
+///
+/// let endpoint = TcpEndpoint {
+///     host: "192.168.1.10".to_owned(),
+///     port: 502,
+///     proxy: Some(ProxyConfig::HttpConnect {
+///         host: "127.0.0.1".to_owned(),
+///         port: proxy_port,
+///         credentials: Some(ProxyCredentials { username: "user".to_owned(), password: "pass".to_owned() }),
+///     }),
+/// };
+///
+/// let mut stream = tcp::connect(&endpoint).await.unwrap();
+/// let mut greeting = [0; 5];
+/// stream.read_exact(&mut greeting).await.unwrap();
+/// assert_eq!(&greeting, b"hello");
+/// # }
+/// ```
+pub async fn connect(endpoint: &TcpEndpoint) -> Result<TcpStream, Error> {
+    match &endpoint.proxy {
+        None => connect_direct(&endpoint.host, endpoint.port).await,
+        Some(ProxyConfig::Socks5 {
+            host,
+            port,
+            credentials,
+        }) => {
+            let mut stream = connect_direct(host, *port).await?;
+            socks5_handshake(&mut stream, endpoint, credentials.as_ref()).await?;
+            Ok(stream)
+        }
+        Some(ProxyConfig::HttpConnect {
+            host,
+            port,
+            credentials,
+        }) => {
+            let mut stream = connect_direct(host, *port).await?;
+            http_connect_handshake(&mut stream, endpoint, credentials.as_ref()).await?;
+            Ok(stream)
+        }
+    }
+}
+
+async fn connect_direct(host: &str, port: u16) -> Result<TcpStream, Error> {
+    let mut pending = interleave_families(lookup_host((host, port)).await?);
+    let mut attempts = JoinSet::new();
+    let mut last_error = None;
+
+    loop {
+        match pending.pop_front() {
+            Some(address) => {
+                attempts.spawn(TcpStream::connect(address));
+            }
+            None if attempts.is_empty() => {
+                return Err(last_error.unwrap_or_else(|| {
+                    Error::new(ErrorKind::NotFound, format!("無法解析主機名稱 {host}"))
+                }));
+            }
+            None => {}
+        }
+
+        tokio::select! {
+            Some(result) = attempts.join_next() => match result {
+                // 回傳時 drop `attempts` ，其餘尚未完成的連線嘗試會被中止
+                Ok(Ok(stream)) => return Ok(stream),
+                Ok(Err(error)) => last_error = Some(error),
+                Err(error) => last_error = Some(Error::other(error)),
+            },
+            () = tokio::time::sleep(CONNECTION_ATTEMPT_DELAY), if !pending.is_empty() => {}
+        }
+    }
+}
+
+/// 依 RFC 8305 交錯排列 IPv6 與 IPv4 位址，以 IPv6 優先
+fn interleave_families(addresses: impl Iterator<Item = SocketAddr>) -> VecDeque<SocketAddr> {
+    let (mut ipv6, mut ipv4): (VecDeque<_>, VecDeque<_>) = addresses.partition(SocketAddr::is_ipv6);
+    let mut interleaved = VecDeque::with_capacity(ipv6.len() + ipv4.len());
+
+    while !ipv6.is_empty() || !ipv4.is_empty() {
+        interleaved.extend(ipv6.pop_front());
+        interleaved.extend(ipv4.pop_front());
+    }
+
+    interleaved
+}
+
+async fn socks5_handshake(
+    stream: &mut TcpStream,
+    endpoint: &TcpEndpoint,
+    credentials: Option<&ProxyCredentials>,
+) -> Result<(), Error> {
+    const VERSION: u8 = 0x05;
+    const NO_AUTHENTICATION: u8 = 0x00;
+    const USERNAME_PASSWORD: u8 = 0x02;
+    const NO_ACCEPTABLE_METHODS: u8 = 0xFF;
+
+    let method = if credentials.is_some() {
+        USERNAME_PASSWORD
+    } else {
+        NO_AUTHENTICATION
+    };
+    stream.write_all(&[VERSION, 1, method]).await?;
+
+    let mut reply = [0; 2];
+    stream.read_exact(&mut reply).await?;
+    match reply {
+        [VERSION, NO_ACCEPTABLE_METHODS] => {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "SOCKS5 代理伺服器不接受此驗證方式",
+            ));
+        }
+        [VERSION, selected] if selected == method => {}
+        _ => return Err(invalid_data("SOCKS5 代理伺服器回應格式錯誤")),
+    }
+
+    if let Some(credentials) = credentials {
+        let username = length_prefixed(&credentials.username)?;
+        let password = length_prefixed(&credentials.password)?;
+
+        let mut request = vec![0x01];
+        request.extend_from_slice(&username);
+        request.extend_from_slice(&password);
+        stream.write_all(&request).await?;
+
+        let mut reply = [0; 2];
+        stream.read_exact(&mut reply).await?;
+        if reply[1] != 0x00 {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "SOCKS5 代理伺服器帳號密碼驗證失敗",
+            ));
+        }
+    }
+
+    let mut request = vec![VERSION, 0x01, 0x00];
+    match endpoint.host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(address)) => {
+            request.push(0x01);
+            request.extend_from_slice(&address.octets());
+        }
+        Ok(IpAddr::V6(address)) => {
+            request.push(0x04);
+            request.extend_from_slice(&address.octets());
+        }
+        Err(_) => {
+            request.push(0x03);
+            request.extend_from_slice(&length_prefixed(&endpoint.host)?);
+        }
+    }
+    request.extend_from_slice(&endpoint.port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut reply = [0; 4];
+    stream.read_exact(&mut reply).await?;
+    if reply[0] != VERSION {
+        return Err(invalid_data("SOCKS5 代理伺服器回應格式錯誤"));
+    }
+    if reply[1] != 0x00 {
+        return Err(Error::new(
+            ErrorKind::ConnectionRefused,
+            format!(
+                "SOCKS5 代理伺服器無法連線至目標（錯誤碼 {:#04x}）",
+                reply[1]
+            ),
+        ));
+    }
+
+    let bound_address_length = match reply[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => usize::from(stream.read_u8().await?),
+        _ => return Err(invalid_data("SOCKS5 代理伺服器回應格式錯誤")),
+    };
+    let mut bound_address = vec![0; bound_address_length + 2];
+    stream.read_exact(&mut bound_address).await?;
+
+    Ok(())
+}
+
+async fn http_connect_handshake(
+    stream: &mut TcpStream,
+    endpoint: &TcpEndpoint,
+    credentials: Option<&ProxyCredentials>,
+) -> Result<(), Error> {
+    const MAX_RESPONSE_HEADER_LENGTH: usize = 8192;
+
+    let authority = match endpoint.host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(address)) => format!("[{address}]:{}", endpoint.port),
+        _ => format!("{}:{}", endpoint.host, endpoint.port),
+    };
+
+    let mut request = format!("CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n");
+    if let Some(credentials) = credentials {
+        let token = base64(format!("{}:{}", credentials.username, credentials.password).as_bytes());
+        let _ = write!(request, "Proxy-Authorization: Basic {token}\r\n");
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // 逐位元組讀取，避免讀到標頭之後屬於目標連線的資料
+    let mut response = Vec::new();
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() >= MAX_RESPONSE_HEADER_LENGTH {
+            return Err(invalid_data("HTTP 代理伺服器回應標頭過長"));
+        }
+        response.push(stream.read_u8().await?);
+    }
+
+    let status_line = String::from_utf8_lossy(&response);
+    let status = status_line
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|status| status.parse::<u16>().ok())
+        .ok_or_else(|| invalid_data("HTTP 代理伺服器回應格式錯誤"))?;
+
+    match status {
+        200..=299 => Ok(()),
+        407 => Err(Error::new(
+            ErrorKind::PermissionDenied,
+            "HTTP 代理伺服器要求驗證（407 Proxy Authentication Required）",
+        )),
+        status => Err(Error::new(
+            ErrorKind::ConnectionRefused,
+            format!("HTTP 代理伺服器無法連線至目標（狀態碼 {status}）"),
+        )),
+    }
+}
+
+fn length_prefixed(value: &str) -> Result<Vec<u8>, Error> {
+    let length = u8::try_from(value.len()).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            "SOCKS5 欄位長度不可超過 255 位元組",
+        )
+    })?;
+    let mut bytes = vec![length];
+    bytes.extend_from_slice(value.as_bytes());
+    Ok(bytes)
+}
+
+fn invalid_data(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+fn base64(input: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let indices = [
+            bytes[0] >> 2,
+            ((bytes[0] & 0b11) << 4) | (bytes[1] >> 4),
+            ((bytes[1] & 0b1111) << 2) | (bytes[2] >> 6),
+            bytes[2] & 0b11_1111,
+        ];
+
+        for (position, index) in indices.into_iter().enumerate() {
+            if position <= chunk.len() {
+                output.push(char::from(ALPHABET[usize::from(index)]));
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
@@ -0,0 +1,269 @@
+//! 設備分身（device twin）
+//!
+//! 可寫入的點位（如設定值、模式切換）除了設備回報的數值外，還有外部界面期望的數值，
+//! [`DeviceTwin`] 分別記錄「期望值」（desired）與「回報值」（reported），並判斷兩者是否同步：
+//!
+//! - 外部界面透過 [`DeviceTwin::set_desired()`] 設定期望值，取得的 [`WriteCommand`] 交由主程式加入 [`crate::CommandQueue`]
+//! - 主程式在每次取得點位數值後調用 [`DeviceTwin::report()`] ，回報值與期望值不同時，會發佈 [`EventKind::TwinDrift`] 事件，並回傳重新寫入的指令
+//! - 設備重新連線後（如斷電重啟恢復為舊設定），主程式調用 [`DeviceTwin::reconnected()`] 重新寫入所有期望值
+//!
+//! 期望值與回報值以 [`Value`] 比較，`1` 與 `1.0` 視為不同的數值，請在 [`crate::Connection::postprocess()`] 中統一數值型別
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{CommandQueue, EventBus, EventKind, twin::{DeviceTwin, SyncStatus}};
+//! use serde_json::json;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let events = EventBus::default();
+//! let mut received = events.subscribe();
+//! let commands = CommandQueue::new();
+//! let twin = DeviceTwin::new().with_events(events, "chiller");
+//!
+//! commands.push(twin.set_desired("setpoint", json!(7)));
+//! assert_eq!(twin.get("setpoint").unwrap().status, SyncStatus::Pending);
+//!
+//! // 寫入完成後，設備回報期望值
+//! assert!(twin.report("setpoint", json!(7)).is_none());
+//! assert_eq!(twin.get("setpoint").unwrap().status, SyncStatus::InSync);
+//!
+//! // 設備被現場人員改為 12
+//! let rewrite = twin.report("setpoint", json!(12)).unwrap();
+//! assert_eq!(rewrite.value, json!(7));
+//! assert!(matches!(received.recv().await.unwrap().kind, EventKind::TwinDrift { reported, .. } if reported == json!(12)));
+//! # }
+//! ```
+
+use std::{
+    sync::{Arc, PoisonError, RwLock},
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{EventBus, EventKind, HashMap, WriteCommand};
+
+/// 同步狀態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncStatus {
+    /// 期望值已送出寫入，尚未收到相同的回報值
+    Pending,
+    /// 回報值與期望值相同
+    InSync,
+    /// 曾經同步，之後回報值與期望值不同
+    Drifted,
+    /// 未設定期望值，只記錄回報值
+    ReportOnly,
+}
+
+/// 點位分身狀態
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TwinState {
+    /// 期望值，未設定時為 [`None`]
+    pub desired: Option<Value>,
+    /// 最後一次的回報值，尚未回報時為 [`None`]
+    pub reported: Option<Value>,
+    /// 同步狀態
+    pub status: SyncStatus,
+    /// 最後一次設定期望值的時間
+    pub desired_at: Option<SystemTime>,
+    /// 最後一次回報的時間
+    pub reported_at: Option<SystemTime>,
+}
+
+/// 設備分身
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份資料
+#[derive(Debug, Clone, Default)]
+pub struct DeviceTwin {
+    targets: Arc<RwLock<HashMap<String, TwinState>>>,
+    events: Option<(EventBus, String)>,
+}
+
+impl DeviceTwin {
+    /// 建立空的設備分身
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 回報值偏離期望值時，同時發佈 [`EventKind::TwinDrift`] 事件
+    ///
+    /// # 參數
+    /// - `events`：事件匯流排
+    /// - `connection`：連線識別名稱
+    #[must_use]
+    pub fn with_events(mut self, events: EventBus, connection: impl Into<String>) -> Self {
+        self.events = Some((events, connection.into()));
+        self
+    }
+
+    /// 設定期望值
+    ///
+    /// # 參數
+    /// - `target`：點位名稱
+    /// - `value`：期望值
+    ///
+    /// # 回傳值
+    /// 寫入期望值的指令，請交由主程式加入 [`crate::CommandQueue`]
+    #[must_use]
+    pub fn set_desired(&self, target: impl Into<String>, value: Value) -> WriteCommand {
+        let target = target.into();
+
+        let mut targets = self.targets.write().unwrap_or_else(PoisonError::into_inner);
+        let state = targets.entry(target.clone()).or_insert_with(empty_state);
+        state.status = if state.reported.as_ref() == Some(&value) {
+            SyncStatus::InSync
+        } else {
+            SyncStatus::Pending
+        };
+        state.desired = Some(value.clone());
+        state.desired_at = Some(SystemTime::now());
+        drop(targets);
+
+        WriteCommand {
+            target,
+            value,
+            client: None,
+            correlation: None,
+        }
+    }
+
+    /// 清除期望值，之後只記錄回報值
+    pub fn clear_desired(&self, target: &str) {
+        if let Some(state) = self
+            .targets
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(target)
+        {
+            state.desired = None;
+            state.desired_at = None;
+            state.status = SyncStatus::ReportOnly;
+        }
+    }
+
+    /// 記錄回報值
+    ///
+    /// 已同步的點位回報了不同的數值時，狀態改為 [`SyncStatus::Drifted`] 並發佈 [`EventKind::TwinDrift`] 事件；
+    /// 寫入中（[`SyncStatus::Pending`]）的點位回報不同的數值時，視為寫入尚未生效，不會發佈事件
+    ///
+    /// # 參數
+    /// - `target`：點位名稱
+    /// - `value`：回報值
+    ///
+    /// # 回傳值
+    /// 回報值偏離期望值時，重新寫入期望值的指令
+    #[must_use]
+    pub fn report(&self, target: &str, value: Value) -> Option<WriteCommand> {
+        let mut targets = self.targets.write().unwrap_or_else(PoisonError::into_inner);
+        let state = targets.entry(target.to_owned()).or_insert_with(empty_state);
+        state.reported = Some(value);
+        state.reported_at = Some(SystemTime::now());
+
+        let desired = state.desired.clone()?;
+
+        if state.reported.as_ref() == Some(&desired) {
+            state.status = SyncStatus::InSync;
+            return None;
+        }
+
+        match state.status {
+            SyncStatus::Pending => None,
+            SyncStatus::Drifted => Some(WriteCommand {
+                target: target.to_owned(),
+                value: desired,
+                client: None,
+                correlation: None,
+            }),
+            SyncStatus::InSync | SyncStatus::ReportOnly => {
+                state.status = SyncStatus::Drifted;
+                let reported = state.reported.clone().unwrap_or_default();
+                drop(targets);
+
+                if let Some((events, connection)) = &self.events {
+                    events.publish(EventKind::TwinDrift {
+                        connection: connection.clone(),
+                        target: target.to_owned(),
+                        desired: desired.clone(),
+                        reported,
+                    });
+                }
+
+                Some(WriteCommand {
+                    target: target.to_owned(),
+                    value: desired,
+                    client: None,
+                    correlation: None,
+                })
+            }
+        }
+    }
+
+    /// 設備重新連線
+    ///
+    /// 設備重新連線後無法確認設定是否仍然有效，所有設定了期望值的點位都會改為 [`SyncStatus::Pending`]
+    ///
+    /// # 回傳值
+    /// 重新寫入所有期望值的指令
+    #[must_use]
+    pub fn reconnected(&self) -> Vec<WriteCommand> {
+        self.targets
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter_mut()
+            .filter_map(|(target, state)| {
+                let value = state.desired.clone()?;
+                state.status = SyncStatus::Pending;
+                Some(WriteCommand {
+                    target: target.clone(),
+                    value,
+                    client: None,
+                    correlation: None,
+                })
+            })
+            .collect()
+    }
+
+    /// 取得點位分身狀態
+    #[must_use]
+    pub fn get(&self, target: &str) -> Option<TwinState> {
+        self.targets
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(target)
+            .cloned()
+    }
+
+    /// 移除點位
+    pub fn remove(&self, target: &str) {
+        self.targets
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(target);
+    }
+
+    /// 取得所有點位分身狀態的快照
+    #[must_use]
+    pub fn snapshot(&self) -> std::collections::HashMap<String, TwinState> {
+        self.targets
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(target, state)| (target.clone(), state.clone()))
+            .collect()
+    }
+}
+
+const fn empty_state() -> TwinState {
+    TwinState {
+        desired: None,
+        reported: None,
+        status: SyncStatus::ReportOnly,
+        desired_at: None,
+        reported_at: None,
+    }
+}
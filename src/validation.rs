@@ -0,0 +1,84 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use crate::TargetAddressNumber;
+
+/// 設備編號衝突
+///
+/// 同一個連線中，相同 [`TargetAddressNumber`] 的點位被設定為不同的設備型態時產生，
+/// 這類點位會共用同一份 [`crate::TargetStats`] ，導致統計數據互相污染
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressConflict {
+    /// 發生衝突的設備編號
+    pub address_number: TargetAddressNumber,
+    /// 各設備型態與使用該型態的點位名稱
+    pub device_types: BTreeMap<String, Vec<String>>,
+}
+
+impl Display for AddressConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "設備編號 {} 被設定為多種設備型態：",
+            self.address_number.as_deref().unwrap_or("(無)")
+        )?;
+
+        for (index, (device_type, targets)) in self.device_types.iter().enumerate() {
+            if index > 0 {
+                f.write_str("、")?;
+            }
+            write!(f, "{device_type}（{}）", targets.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 檢查設備編號衝突
+///
+/// 建議在 [`crate::Connection::init_targets()`] 中，建立 [`crate::TargetStats`] 前先呼叫本 function，並顯示回傳的警告
+///
+/// # 參數
+/// - `targets`：點位名稱、設備編號與設備型態
+///
+/// # 回傳值
+/// 所有衝突的設備編號，依設備編號排序，沒有衝突時為空陣列
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::validation::detect_address_conflicts;
+///
+/// let a = Some("1".to_owned());
+/// let b = Some("2".to_owned());
+///
+/// let conflicts = detect_address_conflicts([
+///     ("電表電壓", &a, "meter"),
+///     ("溫控溫度", &a, "thermostat"),
+///     ("電表電流", &b, "meter"),
+/// ]);
+///
+/// assert_eq!(conflicts.len(), 1);
+/// assert_eq!(conflicts[0].address_number, a);
+/// ```
+pub fn detect_address_conflicts<'a>(
+    targets: impl IntoIterator<Item = (&'a str, &'a TargetAddressNumber, &'a str)>,
+) -> Vec<AddressConflict> {
+    let mut addresses = BTreeMap::<&TargetAddressNumber, BTreeMap<String, Vec<String>>>::new();
+
+    for (name, address_number, device_type) in targets {
+        addresses
+            .entry(address_number)
+            .or_default()
+            .entry(device_type.to_owned())
+            .or_default()
+            .push(name.to_owned());
+    }
+
+    addresses
+        .into_iter()
+        .filter(|(_, device_types)| device_types.len() > 1)
+        .map(|(address_number, device_types)| AddressConflict {
+            address_number: address_number.clone(),
+            device_types,
+        })
+        .collect()
+}
@@ -0,0 +1,343 @@
+use std::{any::Any, borrow::Cow, error::Error, fmt::Display};
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+
+use crate::codec::{ByteOrder, ElementType};
+
+/// 數值品質
+///
+/// 外部界面可以依此判斷點位狀態是否可信，參見 [`crate::state::TargetState::quality`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Quality {
+    /// 數值為最近一次成功取得的設備狀態
+    #[default]
+    Good,
+    /// 數值不一定反映設備目前的狀態，如尚未取得數值時的初始狀態
+    Uncertain,
+    /// 數值無法使用，如設備回覆無法轉換
+    Bad,
+    /// 連線中斷，數值為中斷前最後一次取得的設備狀態，參見 [`crate::state::OfflinePolicy::ServeLastKnown`]
+    Stale,
+}
+
+/// 設備資料
+///
+/// [`Value`] 無法有效率地表示二進位資料（如攝影機影像、波形擷取），以 JSON 傳遞時需要 base64 編碼而增加約三分之一的資料量，
+/// 本 enum 讓二進位資料保持原樣，搭配 [`crate::encoding::CborEncoder`] 等二進位編碼器時可以直接以位元組傳遞
+///
+/// 由 [`crate::DeviceStateResponse::to_data()`] 產生，並作為 [`crate::envelope::ResponseEnvelope::value`] 的型別
+///
+/// # 範例
+/// ```rust
+/// use bytes::Bytes;
+/// use device_state_exchange_lib::value::DeviceData;
+///
+/// let frame = DeviceData::from(Bytes::from_static(b"\xff\xd8\xff\xe0"));
+/// assert_eq!(frame.as_bytes().map(Bytes::len), Some(4));
+/// assert_eq!(frame.as_scalar(), None);
+///
+/// let reading = DeviceData::from(serde_json::json!(21.5));
+/// assert_eq!(serde_json::to_value(&reading).unwrap(), serde_json::json!({ "scalar": 21.5 }));
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceData {
+    /// 一般數值
+    Scalar(Value),
+    /// 二進位資料，複製時只會增加參考計數
+    Bytes(Bytes),
+    /// 取樣陣列，如波形擷取的取樣點
+    Array(Vec<f64>),
+    /// 保留原始位元組的取樣陣列，由 [`crate::codec::ArrayLayout::decode()`] 產生，適合較大的陣列（如諧波、波形）
+    Samples(Samples),
+}
+
+impl DeviceData {
+    /// 取得一般數值，資料不是 [`DeviceData::Scalar`] 時為 [`None`]
+    #[must_use]
+    pub const fn as_scalar(&self) -> Option<&Value> {
+        match self {
+            Self::Scalar(value) => Some(value),
+            Self::Bytes(_) | Self::Array(_) | Self::Samples(_) => None,
+        }
+    }
+
+    /// 取得二進位資料，資料不是 [`DeviceData::Bytes`] 時為 [`None`]
+    #[must_use]
+    pub const fn as_bytes(&self) -> Option<&Bytes> {
+        match self {
+            Self::Bytes(bytes) => Some(bytes),
+            Self::Scalar(_) | Self::Array(_) | Self::Samples(_) => None,
+        }
+    }
+
+    /// 取得取樣陣列，資料不是 [`DeviceData::Array`] 時為 [`None`]
+    #[must_use]
+    pub fn as_array(&self) -> Option<&[f64]> {
+        match self {
+            Self::Array(samples) => Some(samples),
+            Self::Scalar(_) | Self::Bytes(_) | Self::Samples(_) => None,
+        }
+    }
+
+    /// 取得保留原始位元組的取樣陣列，資料不是 [`DeviceData::Samples`] 時為 [`None`]
+    #[must_use]
+    pub const fn as_samples(&self) -> Option<&Samples> {
+        match self {
+            Self::Samples(samples) => Some(samples),
+            Self::Scalar(_) | Self::Bytes(_) | Self::Array(_) => None,
+        }
+    }
+}
+
+impl From<Value> for DeviceData {
+    fn from(value: Value) -> Self {
+        Self::Scalar(value)
+    }
+}
+
+impl From<Bytes> for DeviceData {
+    fn from(bytes: Bytes) -> Self {
+        Self::Bytes(bytes)
+    }
+}
+
+impl From<Vec<f64>> for DeviceData {
+    fn from(samples: Vec<f64>) -> Self {
+        Self::Array(samples)
+    }
+}
+
+impl From<Samples> for DeviceData {
+    fn from(samples: Samples) -> Self {
+        Self::Samples(samples)
+    }
+}
+
+/// 保留原始位元組的取樣陣列
+///
+/// 以設備回傳的位元組存放陣列，複製時只會增加參考計數，需要時才轉換為 [`f64`] ；
+/// 序列化時包含元素型別與排列方式，原始資料在 JSON 等人類可讀的格式中以 base64 字串表示，在 CBOR 等二進位格式中維持原本的位元組，
+/// 不會展開為數字陣列
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawSamples")]
+pub struct Samples {
+    element_type: ElementType,
+    byte_order: ByteOrder,
+    #[serde(with = "crate::base64")]
+    data: Bytes,
+}
+
+#[derive(Deserialize)]
+struct RawSamples {
+    element_type: ElementType,
+    #[serde(default)]
+    byte_order: ByteOrder,
+    #[serde(with = "crate::base64")]
+    data: Bytes,
+}
+
+impl TryFrom<RawSamples> for Samples {
+    type Error = ConversionError;
+
+    fn try_from(raw: RawSamples) -> Result<Self, Self::Error> {
+        Self::new(raw.element_type, raw.byte_order, raw.data)
+    }
+}
+
+impl Samples {
+    /// 建立取樣陣列
+    ///
+    /// # Errors
+    /// 原始資料長度不是元素大小的倍數時回傳 [`ConversionError::InvalidRaw`]
+    pub fn new(
+        element_type: ElementType,
+        byte_order: ByteOrder,
+        data: Bytes,
+    ) -> Result<Self, ConversionError> {
+        if !data.len().is_multiple_of(element_type.size()) {
+            return Err(ConversionError::InvalidRaw(format!(
+                "{} 位元組的資料不是 {} 位元組元素的倍數",
+                data.len(),
+                element_type.size()
+            )));
+        }
+
+        Ok(Self {
+            element_type,
+            byte_order,
+            data,
+        })
+    }
+
+    /// 元素資料型別
+    #[must_use]
+    pub const fn element_type(&self) -> ElementType {
+        self.element_type
+    }
+
+    /// 排列方式
+    #[must_use]
+    pub const fn byte_order(&self) -> ByteOrder {
+        self.byte_order
+    }
+
+    /// 原始資料
+    #[must_use]
+    pub const fn data(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// 元素數量
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.data.len() / self.element_type.size()
+    }
+
+    /// 是否沒有任何元素
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// 取得單一元素，超出範圍時為 [`None`]
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<f64> {
+        let size = self.element_type.size();
+        self.data
+            .get(index * size..(index + 1) * size)
+            .map(|chunk| self.element(chunk))
+    }
+
+    /// 依序取得所有元素
+    pub fn iter(&self) -> impl Iterator<Item = f64> + '_ {
+        self.data
+            .chunks_exact(self.element_type.size())
+            .map(|chunk| self.element(chunk))
+    }
+
+    /// 轉換為 [`f64`] 陣列
+    #[must_use]
+    pub fn to_vec(&self) -> Vec<f64> {
+        self.iter().collect()
+    }
+
+    fn element(&self, chunk: &[u8]) -> f64 {
+        match self.element_type {
+            ElementType::U16 => f64::from(u16::from_be_bytes(big_endian(chunk, self.byte_order))),
+            ElementType::I16 => f64::from(i16::from_be_bytes(big_endian(chunk, self.byte_order))),
+            ElementType::U32 => f64::from(u32::from_be_bytes(big_endian(chunk, self.byte_order))),
+            ElementType::I32 => f64::from(i32::from_be_bytes(big_endian(chunk, self.byte_order))),
+            ElementType::F32 => f64::from(f32::from_be_bytes(big_endian(chunk, self.byte_order))),
+            ElementType::F64 => f64::from_be_bytes(big_endian(chunk, self.byte_order)),
+        }
+    }
+}
+
+/// 將單一元素的位元組轉換為大端序
+fn big_endian<const N: usize>(chunk: &[u8], byte_order: ByteOrder) -> [u8; N] {
+    let mut bytes = [0; N];
+    bytes.copy_from_slice(chunk);
+
+    match byte_order {
+        ByteOrder::BigEndian => {}
+        ByteOrder::LittleEndian => bytes.reverse(),
+        ByteOrder::WordSwapped => {
+            let words = N / 2;
+            for word in 0..words / 2 {
+                for offset in 0..2 {
+                    bytes.swap(word * 2 + offset, (words - 1 - word) * 2 + offset);
+                }
+            }
+        }
+    }
+
+    bytes
+}
+
+/// 設備回覆轉換錯誤
+///
+/// 由 [`crate::DeviceStateResponse::to_value()`] 回傳，主程式會將點位標記為 [`Quality::Bad`] ，而不是寫入錯誤或空白的數值
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ConversionError {
+    /// 數值為 NaN 或無限大，無法以 JSON 表示
+    NonFinite(f64),
+    /// 原始資料無法轉換，如長度不符、編碼錯誤
+    InvalidRaw(String),
+    /// 其他錯誤
+    Other(Box<dyn Error + Send + Sync>),
+}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NonFinite(value) => write!(f, "數值 {value} 無法以 JSON 表示"),
+            Self::InvalidRaw(reason) => write!(f, "原始資料無法轉換：{reason}"),
+            Self::Other(error) => write!(f, "設備回覆轉換失敗：{error}"),
+        }
+    }
+}
+
+impl Error for ConversionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Other(error) => Some(error.as_ref()),
+            Self::NonFinite(_) | Self::InvalidRaw(_) => None,
+        }
+    }
+}
+
+/// 將可序列化的數值轉換為 [`Value`]
+///
+/// `value` 為 [`Value`] 時直接借用，浮點數以 [`finite()`] 轉換，其餘型別以 [`serde_json::to_value()`] 轉換，
+/// 供 `#[derive(DeviceStateResponse)]` 產生的 [`crate::DeviceStateResponse::to_value()`] 使用
+///
+/// # Errors
+/// 浮點數為 NaN 或無限大時回傳 [`ConversionError::NonFinite`] ，序列化失敗時回傳 [`ConversionError::Other`]
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::value;
+/// use serde_json::json;
+///
+/// assert_eq!(value::serialize(&[1u16, 2, 3]).unwrap().into_owned(), json!([1, 2, 3]));
+/// assert!(value::serialize(&f32::INFINITY).is_err());
+/// ```
+pub fn serialize<T: Serialize + Any>(value: &T) -> Result<Cow<'_, Value>, ConversionError> {
+    let any: &dyn Any = value;
+    if let Some(value) = any.downcast_ref::<Value>() {
+        return Ok(Cow::Borrowed(value));
+    }
+    if let Some(value) = any.downcast_ref::<f64>() {
+        return finite(*value).map(Cow::Owned);
+    }
+    if let Some(value) = any.downcast_ref::<f32>() {
+        return finite(f64::from(*value)).map(Cow::Owned);
+    }
+
+    serde_json::to_value(value)
+        .map(Cow::Owned)
+        .map_err(|error| ConversionError::Other(Box::new(error)))
+}
+
+/// 將浮點數轉換為 [`Value`]
+///
+/// # Errors
+/// `value` 為 NaN 或無限大時回傳 [`ConversionError::NonFinite`]
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::value::{finite, ConversionError};
+///
+/// assert_eq!(finite(1.5).unwrap(), serde_json::json!(1.5));
+/// assert!(matches!(finite(f64::NAN), Err(ConversionError::NonFinite(_))));
+/// ```
+pub fn finite(value: f64) -> Result<Value, ConversionError> {
+    Number::from_f64(value)
+        .map(Value::Number)
+        .ok_or(ConversionError::NonFinite(value))
+}
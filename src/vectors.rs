@@ -0,0 +1,301 @@
+//! 編解碼測試向量
+//!
+//! 本函式庫在 `vectors/` 目錄中提供機器可讀的測試向量（十六進位封包與解碼後數值的對應），涵蓋封包切割（[`crate::transport::frame`]）、
+//! 回覆編碼（[`crate::encoding`]）與內建設備連線的封包格式（如 `modbus_rtu/adu`），第三方設備連線的實作者可以用同一份測試向量驗證自己的實作，
+//! 發現不一致時，可以將 [`VectorFailure`] 的內容（包含實際的封包）直接回報為 issue
+//!
+//! 測試向量檔案為 JSON 格式，參見 [`VectorSet`] ，封包以十六進位字串表示，空白字元會被忽略；
+//! 以 [`built_in()`] 取得本函式庫內建的測試向量，或以 [`VectorSet::from_json()`] 載入自行撰寫的檔案
+//!
+//! # 範例
+//! ```rust
+//! use device_state_exchange_lib::{
+//!     encoding::{JsonEncoder, ResponseEncoder},
+//!     vectors::{self, VectorReport, encode_hex},
+//! };
+//!
+//! fn verify(codec: &str, encoder: &dyn ResponseEncoder) -> VectorReport {
+//!     vectors::built_in_set(codec).unwrap().verify(|frame, value| {
+//!         let encoded = encoder.encode(value).map_err(|error| error.to_string())?;
+//!         if encoded == frame {
+//!             Ok(())
+//!         } else {
+//!             Err(format!("編碼結果為 {}", encode_hex(&encoded)))
+//!         }
+//!     })
+//! }
+//!
+//! let report = verify("encoding/json", &JsonEncoder);
+//! assert!(report.is_complete(), "{report:?}");
+//! # #[cfg(feature = "cbor")]
+//! # assert!(verify("encoding/cbor", &device_state_exchange_lib::encoding::CborEncoder).is_complete());
+//! # #[cfg(feature = "msgpack")]
+//! # assert!(verify("encoding/msgpack", &device_state_exchange_lib::encoding::MessagePackEncoder).is_complete());
+//! ```
+//!
+//! 封包切割：
+//! ```rust
+//! use device_state_exchange_lib::{transport::frame::FrameReader, vectors::{self, decode_hex}};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! for vector in vectors::built_in_set("framing/fixed_length").unwrap().vectors {
+//!     let received = vector.bytes().unwrap();
+//!     let mut reader = FrameReader::new(received.as_slice());
+//!
+//!     for (length, expected) in vector.value["lengths"].as_array().unwrap().iter().zip(vector.value["frames"].as_array().unwrap()) {
+//!         let frame = reader.read_frame(length.as_u64().unwrap() as usize).await.unwrap();
+//!         assert_eq!(frame, decode_hex(expected.as_str().unwrap()).unwrap(), "{}", vector.name);
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::{error::Error, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// 本函式庫內建的測試向量檔案
+const BUILT_IN: &[&str] = &[
+    include_str!("../vectors/framing.json"),
+    include_str!("../vectors/encoding-json.json"),
+    include_str!("../vectors/encoding-cbor.json"),
+    include_str!("../vectors/encoding-msgpack.json"),
+    include_str!("../vectors/modbus-rtu.json"),
+    include_str!("../vectors/modbus-tcp.json"),
+    include_str!("../vectors/mqtt.json"),
+    include_str!("../vectors/bacnet.json"),
+    include_str!("../vectors/snmp.json"),
+    include_str!("../vectors/opcua.json"),
+    include_str!("../vectors/dnp3.json"),
+    include_str!("../vectors/mbus.json"),
+    include_str!("../vectors/http.json"),
+    include_str!("../vectors/knx.json"),
+    include_str!("../vectors/ethernet-ip.json"),
+    include_str!("../vectors/s7.json"),
+    include_str!("../vectors/canbus.json"),
+];
+
+/// 測試向量
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVector {
+    /// 測試向量名稱，同一個 [`VectorSet`] 中不會重複
+    pub name: String,
+    /// 封包內容，以十六進位字串表示，參見 [`TestVector::bytes()`]
+    pub frame: String,
+    /// 解碼後的數值，格式依 [`VectorSet::codec`] 而定
+    pub value: Value,
+}
+
+impl TestVector {
+    /// 取得封包內容
+    ///
+    /// # Errors
+    /// [`TestVector::frame`] 不是有效的十六進位字串時回傳 [`VectorError::InvalidHex`]
+    pub fn bytes(&self) -> Result<Vec<u8>, VectorError> {
+        decode_hex(&self.frame)
+    }
+}
+
+/// 測試向量檔案
+///
+/// # 範例
+/// ```rust
+/// use device_state_exchange_lib::vectors::VectorSet;
+/// use serde_json::json;
+///
+/// let set = VectorSet::from_json(r#"{
+///     "codec": "data_type/u16_be",
+///     "vectors": [{ "name": "42", "frame": "00 2a", "value": 42 }]
+/// }"#).unwrap();
+///
+/// assert_eq!(set.vectors[0].bytes().unwrap(), [0x00, 0x2a]);
+/// assert_eq!(set.vectors[0].value, json!(42));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VectorSet {
+    /// 編解碼模組名稱，如 `framing/fixed_length` 、`encoding/cbor`
+    pub codec: String,
+    /// 說明
+    #[serde(default)]
+    pub description: String,
+    /// 測試向量
+    pub vectors: Vec<TestVector>,
+}
+
+impl VectorSet {
+    /// 載入 JSON 格式的測試向量檔案
+    ///
+    /// # Errors
+    /// 檔案格式錯誤時回傳 [`VectorError::Parse`] ，任何封包不是有效的十六進位字串時回傳 [`VectorError::InvalidHex`]
+    pub fn from_json(json: &str) -> Result<Self, VectorError> {
+        let set: Self = serde_json::from_str(json).map_err(VectorError::Parse)?;
+
+        for vector in &set.vectors {
+            vector.bytes()?;
+        }
+
+        Ok(set)
+    }
+
+    /// 以所有測試向量驗證實作
+    ///
+    /// # 參數
+    /// - `check`：驗證單一測試向量，傳入封包內容與解碼後的數值，不一致時回傳說明
+    ///
+    /// # 回傳值
+    /// 驗證結果，參見 [`VectorReport`]
+    pub fn verify<F>(&self, mut check: F) -> VectorReport
+    where
+        F: FnMut(&[u8], &Value) -> Result<(), String>,
+    {
+        let mut report = VectorReport {
+            codec: self.codec.clone(),
+            passed: 0,
+            failures: Vec::new(),
+        };
+
+        for vector in &self.vectors {
+            let result = vector
+                .bytes()
+                .map_err(|error| error.to_string())
+                .and_then(|frame| check(&frame, &vector.value));
+
+            match result {
+                Ok(()) => report.passed += 1,
+                Err(message) => report.failures.push(VectorFailure {
+                    codec: self.codec.clone(),
+                    name: vector.name.clone(),
+                    frame: vector.frame.clone(),
+                    message,
+                }),
+            }
+        }
+
+        report
+    }
+}
+
+/// 測試向量驗證結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VectorReport {
+    /// 編解碼模組名稱
+    pub codec: String,
+    /// 通過的測試向量數量
+    pub passed: usize,
+    /// 未通過的測試向量
+    pub failures: Vec<VectorFailure>,
+}
+
+impl VectorReport {
+    /// 是否所有測試向量都已通過
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// 未通過的測試向量
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VectorFailure {
+    /// 編解碼模組名稱
+    pub codec: String,
+    /// 測試向量名稱
+    pub name: String,
+    /// 封包內容，以十六進位字串表示
+    pub frame: String,
+    /// 不一致的說明
+    pub message: String,
+}
+
+impl Display for VectorFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} 測試向量「{}」未通過：{}（封包：{}）",
+            self.codec, self.name, self.message, self.frame
+        )
+    }
+}
+
+/// 測試向量錯誤
+#[derive(Debug)]
+pub enum VectorError {
+    /// 測試向量檔案格式錯誤
+    Parse(serde_json::Error),
+    /// 封包不是有效的十六進位字串
+    InvalidHex(String),
+}
+
+impl Display for VectorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse(error) => write!(f, "測試向量檔案格式錯誤：{error}"),
+            Self::InvalidHex(frame) => write!(f, "封包「{frame}」不是有效的十六進位字串"),
+        }
+    }
+}
+
+impl Error for VectorError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Parse(error) => Some(error),
+            Self::InvalidHex(_) => None,
+        }
+    }
+}
+
+/// 取得本函式庫內建的所有測試向量，包含未啟用 feature 的編碼
+///
+/// # Panics
+/// 內建的測試向量檔案格式錯誤時
+#[must_use]
+pub fn built_in() -> Vec<VectorSet> {
+    BUILT_IN
+        .iter()
+        .map(|json| VectorSet::from_json(json).unwrap_or_else(|error| panic!("{error}")))
+        .collect()
+}
+
+/// 依編解碼模組名稱取得本函式庫內建的測試向量
+///
+/// # Panics
+/// 內建的測試向量檔案格式錯誤時
+#[must_use]
+pub fn built_in_set(codec: &str) -> Option<VectorSet> {
+    built_in().into_iter().find(|set| set.codec == codec)
+}
+
+/// 將十六進位字串轉換為位元組，空白字元會被忽略
+///
+/// # Errors
+/// 含有非十六進位字元或長度為奇數時回傳 [`VectorError::InvalidHex`]
+pub fn decode_hex(hex: &str) -> Result<Vec<u8>, VectorError> {
+    let digits: Vec<u8> = hex
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_digit(16).and_then(|digit| u8::try_from(digit).ok()))
+        .collect::<Option<_>>()
+        .ok_or_else(|| VectorError::InvalidHex(hex.to_owned()))?;
+
+    if !digits.len().is_multiple_of(2) {
+        return Err(VectorError::InvalidHex(hex.to_owned()));
+    }
+
+    Ok(digits
+        .chunks_exact(2)
+        .map(|pair| (pair[0] << 4) | pair[1])
+        .collect())
+}
+
+/// 將位元組轉換為十六進位字串（小寫，不含空白），用於回報未通過的測試向量
+#[must_use]
+pub fn encode_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
@@ -0,0 +1,225 @@
+//! 滾動時間窗統計
+//!
+//! [`crate::TargetStats`] 的累計數值無法回答「過去 5 分鐘的失敗率」這類問題，[`crate::bucket::BucketedCounters`] 則是依 UNIX 時間對齊的區間，
+//! 適合顯示歷史趨勢，[`WindowedStats`] 以固定數量的環形區間記錄最近的輪詢結果，可以查詢任意長度（不超過保留範圍）的最近時間窗
+//!
+//! 區間以單調時鐘計算（參見 [`crate::clock`]），查詢的時間窗會以區間長度為單位向上取整，並包含目前尚未結束的區間
+//!
+//! [`crate::TargetStats::record()`] 會同時記錄至點位的 [`WindowedStats`] ，區間設定參見 [`crate::StatsConfig::window`]
+//!
+//! # 範例
+//! ```rust
+//! use std::{thread, time::Duration};
+//! use device_state_exchange_lib::{Outcome, windowed::WindowedStats};
+//!
+//! // 每 10 毫秒一個區間，保留 5 個（50 毫秒）
+//! let statistics = WindowedStats::new(Duration::from_millis(10), 5);
+//! assert_eq!(statistics.success_rate(Duration::from_millis(50)), None);
+//!
+//! statistics.record(Outcome::Success { response_ms: 20 });
+//! statistics.record(Outcome::Success { response_ms: 40 });
+//! statistics.record(Outcome::Failure);
+//! statistics.record(Outcome::Failure);
+//!
+//! assert_eq!(statistics.success_rate(Duration::from_millis(50)), Some(0.5));
+//! assert_eq!(statistics.avg_response_ms(Duration::from_millis(50)), Some(30));
+//!
+//! // 超過保留範圍後，舊的紀錄不會再計入
+//! thread::sleep(Duration::from_millis(60));
+//! assert_eq!(statistics.snapshot(Duration::from_millis(50)).polls, 0);
+//! ```
+
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Outcome;
+
+/// 預設區間長度，10 秒
+pub const DEFAULT_WINDOW_BUCKET_WIDTH: Duration = Duration::from_secs(10);
+
+/// 預設保留的區間數量，搭配預設區間長度為 15 分鐘
+pub const DEFAULT_WINDOW_BUCKETS: usize = 90;
+
+/// 滾動時間窗設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowConfig {
+    /// 區間長度，序列化時以毫秒數表示
+    #[serde(rename = "bucket_width_ms", with = "crate::millis")]
+    pub bucket_width: Duration,
+    /// 保留的區間數量，可查詢的最長時間窗為 `bucket_width * buckets`
+    pub buckets: usize,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            bucket_width: DEFAULT_WINDOW_BUCKET_WIDTH,
+            buckets: DEFAULT_WINDOW_BUCKETS,
+        }
+    }
+}
+
+/// 滾動時間窗統計
+///
+/// 本 struct 內部利用 [`Arc`] 共享資料，複製後的物件會存取同一份數據
+#[derive(Debug, Clone)]
+pub struct WindowedStats(Arc<Mutex<WindowRing>>);
+
+#[derive(Debug)]
+struct WindowRing {
+    started: Instant,
+    bucket_width: Duration,
+    slots: Vec<Slot>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Slot {
+    index: u64,
+    polls: u64,
+    failures: u64,
+    response_sum: i64,
+}
+
+/// 時間窗統計快照
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowSnapshot {
+    /// 輪詢次數
+    pub polls: u64,
+    /// 失敗的輪詢次數
+    pub failures: u64,
+    /// 成功的回覆毫秒數總和
+    pub response_sum_ms: i64,
+}
+
+impl WindowSnapshot {
+    /// 成功率，介於 0 至 1 ，時間窗內沒有任何輪詢時為 [`None`]
+    #[must_use]
+    #[expect(clippy::cast_precision_loss)]
+    pub fn success_rate(&self) -> Option<f64> {
+        (self.polls > 0).then(|| (self.polls - self.failures) as f64 / self.polls as f64)
+    }
+
+    /// 平均回覆毫秒數，時間窗內沒有任何成功的輪詢時為 [`None`]
+    #[must_use]
+    pub fn avg_response_ms(&self) -> Option<i64> {
+        self.response_sum_ms
+            .checked_div(i64::try_from(self.polls - self.failures).unwrap_or(i64::MAX))
+    }
+}
+
+impl Default for WindowedStats {
+    fn default() -> Self {
+        Self::with_config(WindowConfig::default())
+    }
+}
+
+impl WindowedStats {
+    /// 建立滾動時間窗統計
+    ///
+    /// # 參數
+    /// - `bucket_width`：區間長度，小於 1 毫秒時視為 1 毫秒
+    /// - `buckets`：保留的區間數量（包含目前的區間），小於 1 時視為 1
+    #[must_use]
+    pub fn new(bucket_width: Duration, buckets: usize) -> Self {
+        Self(Arc::new(Mutex::new(WindowRing {
+            started: Instant::now(),
+            bucket_width: bucket_width.max(Duration::from_millis(1)),
+            slots: vec![Slot::default(); buckets.max(1)],
+        })))
+    }
+
+    /// 依設定建立滾動時間窗統計，參見 [`WindowedStats::new()`]
+    #[must_use]
+    pub fn with_config(config: WindowConfig) -> Self {
+        Self::new(config.bucket_width, config.buckets)
+    }
+
+    /// 記錄一次輪詢的結果
+    pub fn record(&self, outcome: Outcome) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .record(Instant::now(), outcome);
+    }
+
+    /// 取得最近時間窗內的統計
+    ///
+    /// # 參數
+    /// - `window`：時間窗長度，以區間長度為單位向上取整，超過保留範圍時以保留範圍計算
+    #[must_use]
+    pub fn snapshot(&self, window: Duration) -> WindowSnapshot {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .snapshot(Instant::now(), window)
+    }
+
+    /// 最近時間窗內的成功率，參見 [`WindowSnapshot::success_rate()`]
+    #[must_use]
+    pub fn success_rate(&self, window: Duration) -> Option<f64> {
+        self.snapshot(window).success_rate()
+    }
+
+    /// 最近時間窗內的平均回覆毫秒數，參見 [`WindowSnapshot::avg_response_ms()`]
+    #[must_use]
+    pub fn avg_response_ms(&self, window: Duration) -> Option<i64> {
+        self.snapshot(window).avg_response_ms()
+    }
+
+    /// 清除所有紀錄
+    pub fn clear(&self) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .slots
+            .fill(Slot::default());
+    }
+}
+
+impl WindowRing {
+    fn index(&self, now: Instant) -> u64 {
+        let elapsed = now.saturating_duration_since(self.started);
+        u64::try_from(elapsed.as_nanos() / self.bucket_width.as_nanos()).unwrap_or(u64::MAX)
+    }
+
+    fn record(&mut self, now: Instant, outcome: Outcome) {
+        let index = self.index(now);
+        let len = self.slots.len() as u64;
+        #[expect(clippy::cast_possible_truncation)]
+        let slot = &mut self.slots[(index % len) as usize];
+        if slot.index != index {
+            *slot = Slot {
+                index,
+                ..Slot::default()
+            };
+        }
+
+        slot.polls += 1;
+        match outcome {
+            Outcome::Success { response_ms } => {
+                slot.response_sum = slot.response_sum.saturating_add(response_ms);
+            }
+            Outcome::Failure => slot.failures += 1,
+        }
+    }
+
+    fn snapshot(&self, now: Instant, window: Duration) -> WindowSnapshot {
+        let index = self.index(now);
+        let buckets = u64::try_from(window.as_nanos().div_ceil(self.bucket_width.as_nanos()))
+            .unwrap_or(u64::MAX)
+            .clamp(1, self.slots.len() as u64);
+
+        self.slots
+            .iter()
+            .filter(|slot| slot.polls > 0 && index - slot.index < buckets)
+            .fold(WindowSnapshot::default(), |snapshot, slot| WindowSnapshot {
+                polls: snapshot.polls + slot.polls,
+                failures: snapshot.failures + slot.failures,
+                response_sum_ms: snapshot.response_sum_ms.saturating_add(slot.response_sum),
+            })
+    }
+}